@@ -24,7 +24,9 @@ mod scope;
 mod value;
 
 pub use activation::{Activation, ActivationIdentifier};
-pub use debug::VariableDumper;
+pub use debug::{
+    evaluate_expression, StepValues, Trace, TraceRecorder, TraceStep, Tracepoint, VariableDumper,
+};
 pub use error::Error;
 pub use flv::FlvValueAvm1Ext;
 pub use function::ExecutionReason;
@@ -1,4 +1,5 @@
 use crate::avm1::callable_value::CallableValue;
+use crate::avm1::debug::{StepValues, TraceStep};
 use crate::avm1::error::Error;
 use crate::avm1::function::{Avm1Function, ExecutionReason, FunctionObject};
 use crate::avm1::property::Attribute;
@@ -437,16 +438,82 @@ impl<'a, 'gc> Activation<'a, 'gc> {
     }
 
     pub fn run_actions(&mut self, code: SwfSlice) -> Result<ReturnType<'gc>, Error<'gc>> {
-        let mut read = Reader::new(&code.movie.data()[code.start..], self.swf_version());
+        let mut read = Reader::new(&code.movie_data()[code.start..], self.swf_version());
 
-        loop {
+        let result = loop {
             let result = self.do_action(&code, &mut read);
             match result {
                 Ok(FrameControl::Return(return_type)) => break Ok(return_type),
                 Ok(FrameControl::Continue) => {}
                 Err(e) => break Err(e),
             }
+        };
+
+        if self.context.avm1.trace_recorder().is_active() {
+            self.context
+                .avm1
+                .trace_recorder_mut()
+                .leave_actions(self.id.depth(), code.start);
         }
+
+        result
+    }
+
+    /// Let the trace recorder start a trace on or record the action that is
+    /// about to run.
+    #[cold]
+    fn trace_action(&mut self, data: &SwfSlice, reader: &Reader<'_>) {
+        let offset = reader.get_ref().as_ptr() as usize - data.movie_data().as_ptr() as usize;
+        if !self.context.avm1.trace_recorder_mut().should_record(
+            data.movie.url(),
+            offset,
+            self.id.depth(),
+            data.start,
+        ) {
+            return;
+        }
+
+        let mut action = match Reader::new(reader.get_ref(), self.swf_version).read_action() {
+            Ok(action) => format!("{action:?}"),
+            Err(e) => format!("Invalid action: {e}"),
+        };
+        // Function bodies are part of their action, which makes them too long to show.
+        if let Some((end, _)) = action.char_indices().nth(200) {
+            action.truncate(end);
+            action.push('…');
+        }
+
+        let register_count = match self.local_registers {
+            Some(registers) => registers.read().len(),
+            None => 4,
+        };
+        let scope = self.scope;
+        let locals = StepValues::copy_locals(self, scope);
+        let values = StepValues {
+            stack: self.context.avm1.stack().to_vec(),
+            registers: (0..register_count)
+                .map(|id| self.current_register(id))
+                .collect(),
+            locals,
+            this: self.this,
+            scope,
+            constant_pool: self.constant_pool,
+            base_clip: self.base_clip,
+            swf_version: self.swf_version,
+        };
+        let values = self
+            .context
+            .dynamic_root
+            .stash(self.gc(), Gc::new(self.gc(), values));
+
+        self.context.avm1.trace_recorder_mut().record(TraceStep {
+            depth: self.id.depth(),
+            activation: self.id.to_string(),
+            swf_url: data.movie.url().to_string(),
+            offset,
+            action,
+            values,
+        });
     }
 
     /// Run a single action from a given action reader.
@@ -467,6 +534,10 @@ impl<'a, 'gc> Activation<'a, 'gc> {
             //Executing beyond the end of a function constitutes an implicit return.
             Ok(FrameControl::Return(ReturnType::Implicit))
         } else {
+            if self.context.avm1.trace_recorder().is_active() {
+                self.trace_action(data, reader);
+            }
+
             let action = reader.read_action()?;
             avm_debug!(
                 self.context.avm1,
@@ -1453,7 +1524,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
         let val = self.context.avm1.pop();
         if val.as_bool(self.swf_version()) {
-            reader.seek(data.movie.data(), action.offset);
+            reader.seek(data.movie_data(), action.offset);
         }
         Ok(FrameControl::Continue)
     }
@@ -1562,7 +1633,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         reader: &mut Reader<'b>,
         data: &'b SwfSlice,
     ) -> Result<FrameControl<'gc>, Error<'gc>> {
-        reader.seek(data.movie.data(), action.offset);
+        reader.seek(data.movie_data(), action.offset);
         Ok(FrameControl::Continue)
     }
 
@@ -1,7 +1,579 @@
-use crate::avm1::activation::Activation;
+use crate::avm1::activation::{Activation, ActivationIdentifier};
+use crate::avm1::scope::{Scope, ScopeClass};
 use crate::avm1::{Object, ObjectPtr, Value};
+use crate::context::UpdateContext;
+use crate::display_object::DisplayObject;
 use crate::string::AvmString;
-use std::fmt::Write;
+use gc_arena::{Collect, DynamicRoot, DynamicRootSet, Gc, Rootable};
+use std::fmt::{self, Write};
+
+/// The most actions that are recorded in a single trace.
+const MAX_RECORDED_STEPS: usize = 10_000;
+
+/// A place where AVM1 execution starts being recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tracepoint {
+    /// The action at `offset` in the uncompressed tags of the SWF at `swf_url`.
+    Action { swf_url: String, offset: usize },
+
+    /// The start of every call to a function with this name.
+    Function(String),
+}
+
+impl fmt::Display for Tracepoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tracepoint::Action { swf_url, offset } => write!(f, "{swf_url} @ {offset:#x}"),
+            Tracepoint::Function(name) => write!(f, "function {name}"),
+        }
+    }
+}
+
+/// Records a trace of the AVM1 actions that run after a tracepoint is hit.
+///
+/// This isn't a debugger: execution can't stop in the middle of an action, as
+/// the rest of the player is still on the stack. Instead, every action is
+/// recorded along with a copy of the values it saw, until the activation that
+/// hit the tracepoint returns. Only then is the player paused, so that the
+/// trace can be stepped through after the fact.
+#[derive(Default)]
+pub struct TraceRecorder {
+    tracepoints: Vec<Tracepoint>,
+
+    /// Why the next action should start a trace, if it should.
+    record_requested: Option<String>,
+
+    recording: Option<Recording>,
+    trace: Option<Trace>,
+}
+
+struct Recording {
+    reason: String,
+
+    /// The depth and the start of the code of the activation that hit the
+    /// tracepoint.
+    depth: u16,
+    code_start: usize,
+
+    steps: Vec<TraceStep>,
+}
+
+/// The actions that ran after a tracepoint was hit.
+pub struct Trace {
+    pub reason: String,
+    pub steps: Vec<TraceStep>,
+}
+
+/// An action that ran while a trace was being recorded.
+#[derive(Clone)]
+pub struct TraceStep {
+    /// How many activations deeper than the one that hit the tracepoint this
+    /// action ran in.
+    pub depth: u16,
+
+    /// The activation this action ran in.
+    pub activation: String,
+
+    pub swf_url: String,
+
+    /// The offset of this action in the uncompressed tags of the SWF.
+    pub offset: usize,
+
+    pub action: String,
+
+    pub(crate) values: DynamicRoot<Rootable![StepValues<'_>]>,
+}
+
+/// The state of an activation just before one of its actions ran.
+///
+/// The stack, the registers and the local variables of the function are copied
+/// at every step. Objects aren't, so their properties are the current ones
+/// rather than the ones they had at that step.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+pub struct StepValues<'gc> {
+    pub stack: Vec<Value<'gc>>,
+    pub registers: Vec<Value<'gc>>,
+
+    /// The local variables of the function, sorted by name, if the action ran
+    /// in one.
+    pub locals: Option<Vec<(AvmString<'gc>, Value<'gc>)>>,
+
+    pub this: Value<'gc>,
+    pub scope: Gc<'gc, Scope<'gc>>,
+    pub constant_pool: Gc<'gc, Vec<Value<'gc>>>,
+    pub base_clip: DisplayObject<'gc>,
+    pub swf_version: u8,
+}
+
+impl<'gc> StepValues<'gc> {
+    /// Copy the local variables of the function that `scope` belongs to.
+    ///
+    /// Getters aren't called, as they could run any code.
+    pub(crate) fn copy_locals(
+        activation: &mut Activation<'_, 'gc>,
+        scope: Gc<'gc, Scope<'gc>>,
+    ) -> Option<Vec<(AvmString<'gc>, Value<'gc>)>> {
+        let mut scope = Some(scope);
+        while let Some(current) = scope {
+            if current.class() == ScopeClass::Local {
+                let locals = current.locals_cell();
+                let mut keys = locals.get_keys(activation, true);
+                keys.sort();
+                return Some(
+                    keys.into_iter()
+                        .map(|key| {
+                            let value = locals
+                                .get_stored(key, activation)
+                                .unwrap_or(Value::Undefined);
+                            (key, value)
+                        })
+                        .collect(),
+                );
+            }
+            scope = current.parent();
+        }
+        None
+    }
+
+    /// The scope chain, from the innermost scope outwards.
+    pub fn scope_chain(&self) -> Vec<(ScopeClass, Object<'gc>)> {
+        let mut chain = vec![];
+        let mut scope = Some(self.scope);
+        while let Some(current) = scope {
+            chain.push((current.class(), current.locals_cell()));
+            scope = current.parent();
+        }
+        chain
+    }
+}
+
+impl TraceStep {
+    pub fn values<'gc>(&self, dynamic_root: DynamicRootSet<'gc>) -> StepValues<'gc> {
+        dynamic_root.fetch(&self.values).clone()
+    }
+}
+
+impl TraceRecorder {
+    pub fn tracepoints(&self) -> &[Tracepoint] {
+        &self.tracepoints
+    }
+
+    pub fn add_tracepoint(&mut self, tracepoint: Tracepoint) {
+        if !self.tracepoints.contains(&tracepoint) {
+            self.tracepoints.push(tracepoint);
+        }
+    }
+
+    pub fn remove_tracepoint(&mut self, tracepoint: &Tracepoint) {
+        self.tracepoints.retain(|other| other != tracepoint);
+    }
+
+    /// Start a trace on the next action that runs.
+    pub fn record_next_action(&mut self) {
+        self.record_requested = Some("Recorded from the next action".to_string());
+    }
+
+    /// The last trace, once it has been recorded.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Forget the last trace, and let tracepoints be hit again.
+    pub fn resume(&mut self) {
+        self.trace = None;
+    }
+
+    /// Whether the recorder needs to see what runs.
+    #[inline]
+    pub(crate) fn is_active(&self) -> bool {
+        !self.tracepoints.is_empty() || self.record_requested.is_some() || self.recording.is_some()
+    }
+
+    /// Called when a function starts, to start a trace if it has a tracepoint.
+    pub(crate) fn enter_function(&mut self, name: &str) {
+        if self.recording.is_none()
+            && self.trace.is_none()
+            && self
+                .tracepoints
+                .iter()
+                .any(|tracepoint| matches!(tracepoint, Tracepoint::Function(n) if n == name))
+        {
+            self.record_requested = Some(format!("Tracepoint on function {name}"));
+        }
+    }
+
+    /// Whether the action that is about to run should be recorded, either
+    /// because a trace is underway or because one starts here.
+    pub(crate) fn should_record(
+        &mut self,
+        swf_url: &str,
+        offset: usize,
+        depth: u16,
+        code_start: usize,
+    ) -> bool {
+        if self.recording.is_some() {
+            return true;
+        }
+        if self.trace.is_some() {
+            // Code that runs while a trace is shown shouldn't start another one.
+            return false;
+        }
+
+        let reason = self.record_requested.take().or_else(|| {
+            self.tracepoints
+                .iter()
+                .find(|tracepoint| {
+                    matches!(tracepoint, Tracepoint::Action { swf_url: url, offset: o } if url == swf_url && *o == offset)
+                })
+                .map(|tracepoint| format!("Tracepoint at {tracepoint}"))
+        });
+        let Some(reason) = reason else {
+            return false;
+        };
+
+        self.recording = Some(Recording {
+            reason,
+            depth,
+            code_start,
+            steps: vec![],
+        });
+        true
+    }
+
+    /// Record an action, where `step.depth` is the depth of its activation.
+    pub(crate) fn record(&mut self, mut step: TraceStep) {
+        let Some(recording) = &mut self.recording else {
+            return;
+        };
+        step.depth = step.depth.saturating_sub(recording.depth);
+        recording.steps.push(step);
+        if recording.steps.len() >= MAX_RECORDED_STEPS {
+            tracing::warn!("AVM1 trace recorder stopped after {MAX_RECORDED_STEPS} actions");
+            self.finish_recording();
+        }
+    }
+
+    /// Called when an activation has run a block of code, to finish the trace
+    /// once the activation that hit the tracepoint is done.
+    pub(crate) fn leave_actions(&mut self, depth: u16, code_start: usize) {
+        if let Some(recording) = &self.recording {
+            if depth < recording.depth
+                || (depth == recording.depth && code_start == recording.code_start)
+            {
+                self.finish_recording();
+            }
+        }
+    }
+
+    fn finish_recording(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            self.trace = Some(Trace {
+                reason: recording.reason,
+                steps: recording.steps,
+            });
+        }
+    }
+}
+
+/// Evaluate an expression in the scope of a recorded step.
+///
+/// Expressions are variable paths as used by `eval`, such as `_root.clip._x`
+/// or `/clip:_x`, which may start with `this` and be followed by `.name` and
+/// `[...]` lookups. Number, string and constant literals are also supported.
+///
+/// No ActionScript runs while evaluating: properties are read without calling
+/// getters or `__resolve`, so properties that only have a getter read as
+/// `undefined`, and only primitive values can be used as keys.
+pub fn evaluate_expression<'gc>(
+    context: &mut UpdateContext<'gc>,
+    step: &TraceStep,
+    expression: &str,
+) -> Result<Value<'gc>, String> {
+    let expression = ExpressionParser::parse(expression)?;
+
+    let values = step.values(context.dynamic_root);
+    let mut activation = Activation::from_action(
+        context,
+        ActivationIdentifier::root("[TraceRecorder]"),
+        values.swf_version,
+        values.scope,
+        values.constant_pool,
+        values.base_clip,
+        values.this,
+        None,
+    );
+    expression.evaluate(&mut activation, &values)
+}
+
+/// A parsed trace recorder expression.
+#[derive(Debug, PartialEq)]
+enum Expression {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+
+    /// A variable path, split into the names along it. An absolute path
+    /// starts at `_root`, and `..` is the same as `_parent`.
+    Path {
+        absolute: bool,
+        names: Vec<String>,
+    },
+
+    /// `object.name` or `object[key]`.
+    Member(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate<'gc>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        values: &StepValues<'gc>,
+    ) -> Result<Value<'gc>, String> {
+        Ok(match self {
+            Expression::Undefined => Value::Undefined,
+            Expression::Null => Value::Null,
+            Expression::Bool(value) => (*value).into(),
+            Expression::Number(value) => Value::Number(*value),
+            Expression::String(value) => AvmString::new_utf8(activation.gc(), value).into(),
+            Expression::Path { absolute, names } => {
+                let mut names = names.iter();
+                let mut value = if *absolute {
+                    activation.root_object()
+                } else {
+                    match names.next().map(String::as_str) {
+                        Some("this") => activation.this_cell(),
+                        Some("_root") => activation.root_object(),
+                        Some(name) => get_variable(activation, values, name),
+                        None => Value::Undefined,
+                    }
+                };
+                for name in names {
+                    let name = AvmString::new_utf8(activation.gc(), name);
+                    value = get_member(activation, value, name)?;
+                }
+                value
+            }
+            Expression::Member(object, key) => {
+                let object = object.evaluate(activation, values)?;
+                let key = match key.evaluate(activation, values)? {
+                    Value::Object(_) | Value::MovieClip(_) => {
+                        return Err("Only primitive values can be used as keys".to_string())
+                    }
+                    // Converting a primitive to a string doesn't run any code.
+                    key => key
+                        .coerce_to_string(activation)
+                        .map_err(|e| e.to_string())?,
+                };
+                get_member(activation, object, key)?
+            }
+        })
+    }
+}
+
+struct ExpressionParser<'a> {
+    /// What is left to parse.
+    input: &'a str,
+}
+
+impl<'a> ExpressionParser<'a> {
+    fn parse(input: &'a str) -> Result<Expression, String> {
+        let mut parser = Self { input };
+        let expression = parser.expression()?;
+        match parser.input.trim_start() {
+            "" => Ok(expression),
+            rest => Err(format!("Unexpected {rest:?}")),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, String> {
+        let mut expression = self.primary()?;
+
+        loop {
+            self.input = self.input.trim_start();
+            if let Some(rest) = self.input.strip_prefix('[') {
+                self.input = rest;
+                let key = self.expression()?;
+                self.input = self.input.trim_start();
+                self.input = self.input.strip_prefix(']').ok_or("Expected `]`")?;
+                expression = Expression::Member(Box::new(expression), Box::new(key));
+            } else if let Some(rest) = self.input.strip_prefix('.') {
+                self.input = rest;
+                let name = self.take_while(is_identifier_char);
+                if name.is_empty() {
+                    return Err("Expected a name after `.`".to_string());
+                }
+                let name = Expression::String(name.to_string());
+                expression = Expression::Member(Box::new(expression), Box::new(name));
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        self.input = self.input.trim_start();
+
+        if let Some(quote) = self
+            .input
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+        {
+            let mut string = String::new();
+            let mut chars = self.input[1..].char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => match chars.next() {
+                        Some((_, 'n')) => string.push('\n'),
+                        Some((_, 'r')) => string.push('\r'),
+                        Some((_, 't')) => string.push('\t'),
+                        Some((_, c)) => string.push(c),
+                        None => break,
+                    },
+                    c if c == quote => {
+                        self.input = &self.input[i + 2..];
+                        return Ok(Expression::String(string));
+                    }
+                    c => string.push(c),
+                }
+            }
+            return Err("Unterminated string".to_string());
+        }
+
+        if self
+            .input
+            .starts_with(|c: char| c.is_ascii_digit() || c == '-')
+        {
+            let number =
+                self.take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+            return number
+                .parse::<f64>()
+                .map(Expression::Number)
+                .map_err(|_| format!("Invalid number {number:?}"));
+        }
+
+        // Variable paths may contain `.` and `/` and `:`, and are resolved all
+        // at once, like `eval` does.
+        let path = self.take_while(|c| is_identifier_char(c) || matches!(c, '.' | '/' | ':'));
+        match path {
+            "" => Err(match self.input.chars().next() {
+                Some(c) => format!("Unexpected {c:?}"),
+                None => "Expected an expression".to_string(),
+            }),
+            "undefined" => Ok(Expression::Undefined),
+            "null" => Ok(Expression::Null),
+            "true" => Ok(Expression::Bool(true)),
+            "false" => Ok(Expression::Bool(false)),
+            _ => parse_path(path),
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let end = self
+            .input
+            .find(|c: char| !predicate(c))
+            .unwrap_or(self.input.len());
+        let (taken, rest) = self.input.split_at(end);
+        self.input = rest;
+        taken
+    }
+}
+
+/// Split a variable path into the names along it.
+fn parse_path(path: &str) -> Result<Expression, String> {
+    let (absolute, mut rest) = match path.strip_prefix('/') {
+        Some(rest) => (true, rest),
+        None => (false, path),
+    };
+
+    let mut names = vec![];
+    while !rest.is_empty() {
+        // `foo`, `:foo`, and `:::foo` are all the same.
+        rest = rest.trim_start_matches(':');
+        let name = match rest.strip_prefix("..") {
+            Some(after) => {
+                rest = after;
+                "_parent"
+            }
+            None => {
+                let end = rest.find(['.', '/', ':']).unwrap_or(rest.len());
+                let (name, after) = rest.split_at(end);
+                rest = after;
+                name
+            }
+        };
+        if name.is_empty() {
+            return Err(format!("Expected a name in {path:?}"));
+        }
+        names.push(name.to_string());
+
+        if let Some(delimiter) = rest.chars().next() {
+            rest = &rest[1..];
+            if rest.is_empty() {
+                return Err(format!("Expected a name after `{delimiter}`"));
+            }
+        }
+    }
+
+    Ok(Expression::Path { absolute, names })
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Look up a variable on the scope chain of a step.
+///
+/// The local variables of the function are read as they were at that step.
+fn get_variable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    values: &StepValues<'gc>,
+    name: &str,
+) -> Value<'gc> {
+    let name = AvmString::new_utf8(activation.gc(), name);
+    let is_case_sensitive = activation.is_case_sensitive();
+    let mut copied_locals = values.locals.as_ref();
+    for (class, locals) in values.scope_chain() {
+        if class == ScopeClass::Local {
+            if let Some(copied) = copied_locals.take() {
+                let found = copied.iter().find(|(key, _)| {
+                    if is_case_sensitive {
+                        *key == name
+                    } else {
+                        key.eq_ignore_case(&name)
+                    }
+                });
+                match found {
+                    Some((_, value)) => return *value,
+                    None => continue,
+                }
+            }
+        }
+        if locals.has_property(activation, name) {
+            return locals
+                .get_stored(name, activation)
+                .unwrap_or(Value::Undefined);
+        }
+    }
+    Value::Undefined
+}
+
+fn get_member<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    value: Value<'gc>,
+    name: AvmString<'gc>,
+) -> Result<Value<'gc>, String> {
+    match value {
+        Value::Undefined | Value::Null => Err(format!("Can't read {name} of {value:?}")),
+        _ => value
+            .coerce_to_object(activation)
+            .get_stored(name, activation)
+            .map_err(|e| e.to_string()),
+    }
+}
 
 #[allow(dead_code)]
 pub struct VariableDumper<'a> {
@@ -188,3 +760,152 @@ impl<'a> VariableDumper<'a> {
         self.output.push('\n');
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(absolute: bool, names: &[&str]) -> Expression {
+        Expression::Path {
+            absolute,
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn member(object: Expression, key: Expression) -> Expression {
+        Expression::Member(Box::new(object), Box::new(key))
+    }
+
+    fn string(value: &str) -> Expression {
+        Expression::String(value.to_string())
+    }
+
+    #[test]
+    fn parses_literals() {
+        let parse = ExpressionParser::parse;
+        assert_eq!(parse("undefined"), Ok(Expression::Undefined));
+        assert_eq!(parse(" null "), Ok(Expression::Null));
+        assert_eq!(parse("true"), Ok(Expression::Bool(true)));
+        assert_eq!(parse("-1.5e3"), Ok(Expression::Number(-1500.0)));
+        assert_eq!(parse(r#""a\"b\n""#), Ok(string("a\"b\n")));
+        assert_eq!(parse(r"'it\'s'"), Ok(string("it's")));
+    }
+
+    #[test]
+    fn parses_paths() {
+        let parse = ExpressionParser::parse;
+        assert_eq!(parse("this"), Ok(path(false, &["this"])));
+        assert_eq!(
+            parse("_root.clip._x"),
+            Ok(path(false, &["_root", "clip", "_x"]))
+        );
+        assert_eq!(parse("/clip:_x"), Ok(path(true, &["clip", "_x"])));
+        assert_eq!(parse("/"), Ok(path(true, &[])));
+        assert_eq!(parse("../a/::b"), Ok(path(false, &["_parent", "a", "b"])));
+    }
+
+    #[test]
+    fn parses_members() {
+        assert_eq!(
+            ExpressionParser::parse(r#"a["b"] .c[ 0 ][d.e]"#),
+            Ok(member(
+                member(
+                    member(member(path(false, &["a"]), string("b")), string("c")),
+                    Expression::Number(0.0)
+                ),
+                path(false, &["d", "e"])
+            ))
+        );
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = |input| ExpressionParser::parse(input).unwrap_err();
+        assert_eq!(error(""), "Expected an expression");
+        assert_eq!(error("a."), "Expected a name after `.`");
+        assert_eq!(error("a[1"), "Expected `]`");
+        assert_eq!(error("a[1]."), "Expected a name after `.`");
+        assert_eq!(error("'abc"), "Unterminated string");
+        assert_eq!(error("1x"), "Invalid number \"1x\"");
+        assert_eq!(error("a b"), "Unexpected \"b\"");
+        assert_eq!(error("(a)"), "Unexpected '('");
+    }
+
+    const URL: &str = "file:///movie.swf";
+
+    fn action_tracepoint(offset: usize) -> Tracepoint {
+        Tracepoint::Action {
+            swf_url: URL.to_string(),
+            offset,
+        }
+    }
+
+    #[test]
+    fn records_nothing_without_tracepoints() {
+        let mut recorder = TraceRecorder::default();
+        assert!(!recorder.is_active());
+        assert!(!recorder.should_record(URL, 0x10, 0, 0));
+        recorder.leave_actions(0, 0);
+        assert!(recorder.trace().is_none());
+    }
+
+    #[test]
+    fn records_until_the_tracing_activation_leaves() {
+        let mut recorder = TraceRecorder::default();
+        recorder.add_tracepoint(action_tracepoint(0x10));
+        assert!(recorder.is_active());
+        assert!(!recorder.should_record(URL, 0x08, 2, 0x100));
+        assert!(!recorder.should_record("file:///other.swf", 0x10, 2, 0x100));
+        assert!(recorder.should_record(URL, 0x10, 2, 0x100));
+
+        // Everything runs in between is recorded, including deeper calls and
+        // other blocks of code at the same depth.
+        assert!(recorder.should_record(URL, 0x200, 3, 0x200));
+        recorder.leave_actions(3, 0x200);
+        recorder.leave_actions(2, 0x300);
+        assert!(recorder.trace().is_none());
+        assert!(recorder.should_record(URL, 0x14, 2, 0x100));
+
+        recorder.leave_actions(2, 0x100);
+        let trace = recorder.trace().unwrap();
+        assert_eq!(trace.reason, "Tracepoint at file:///movie.swf @ 0x10");
+        assert!(trace.steps.is_empty());
+
+        // Tracepoints aren't hit while a trace is shown, but are again once resumed.
+        assert!(!recorder.should_record(URL, 0x10, 2, 0x100));
+        recorder.resume();
+        assert!(recorder.should_record(URL, 0x10, 2, 0x100));
+    }
+
+    #[test]
+    fn stops_recording_when_returning_past_the_tracepoint() {
+        let mut recorder = TraceRecorder::default();
+        recorder.record_next_action();
+        assert!(recorder.should_record(URL, 0x10, 2, 0x100));
+        recorder.leave_actions(1, 0x80);
+        assert_eq!(
+            recorder.trace().unwrap().reason,
+            "Recorded from the next action"
+        );
+    }
+
+    #[test]
+    fn traces_functions() {
+        let mut recorder = TraceRecorder::default();
+        recorder.add_tracepoint(Tracepoint::Function("foo".to_string()));
+        recorder.enter_function("bar");
+        assert!(!recorder.should_record(URL, 0x10, 1, 0x10));
+        recorder.enter_function("foo");
+        assert!(recorder.should_record(URL, 0x20, 1, 0x20));
+        recorder.leave_actions(1, 0x20);
+        assert_eq!(
+            recorder.trace().unwrap().reason,
+            "Tracepoint on function foo"
+        );
+
+        // Functions called while a trace is shown don't start another one.
+        recorder.enter_function("foo");
+        recorder.resume();
+        assert!(!recorder.should_record(URL, 0x20, 1, 0x20));
+    }
+}
@@ -405,6 +405,19 @@ impl<'gc> Executable<'gc> {
         // The caller is the previous callee.
         let arguments_caller = activation.callee;
 
+        if activation.context.avm1.trace_recorder().is_active() {
+            let function_name = match (af.name(), &name) {
+                (Some(name), _) => name.to_string(),
+                (None, ExecutionName::Dynamic(name)) => name.to_string(),
+                (None, ExecutionName::Static(name)) => name.to_string(),
+            };
+            activation
+                .context
+                .avm1
+                .trace_recorder_mut()
+                .enter_function(&function_name);
+        }
+
         let name = if cfg!(feature = "avm_debug") {
             Cow::Owned(af.debug_string_for_call(activation, name, args))
         } else {
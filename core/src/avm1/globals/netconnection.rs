@@ -8,8 +8,8 @@ use crate::context::UpdateContext;
 use crate::net_connection::{NetConnectionHandle, NetConnections, ResponderCallback};
 use crate::string::{AvmString, StringContext};
 use flash_lso::packet::Header;
-use flash_lso::types::ObjectId;
 use flash_lso::types::Value as AMFValue;
+use flash_lso::types::{AMFVersion, ObjectId};
 use gc_arena::{Collect, Gc};
 use ruffle_macros::istr;
 use ruffle_wstr::WStr;
@@ -133,6 +133,44 @@ impl<'gc> NetConnection<'gc> {
         )?;
         Ok(())
     }
+
+    pub fn call_client(
+        context: &mut UpdateContext<'gc>,
+        this: Object<'gc>,
+        method: &str,
+        arguments: &[Rc<flash_lso::types::Value>],
+    ) -> Result<(), Error<'gc>> {
+        let Some(root_clip) = context.stage.root_clip() else {
+            tracing::warn!("Ignored NetConnection server call as there's no root movie");
+            return Ok(());
+        };
+        let mut activation = Activation::from_nothing(
+            context,
+            ActivationIdentifier::root("[NetConnection server call]"),
+            root_clip,
+        );
+        let reader = flash_lso::read::Reader::default();
+        let mut reference_cache = BTreeMap::default();
+        let arguments: Vec<_> = arguments
+            .iter()
+            .map(|argument| {
+                deserialize_value(
+                    &mut activation,
+                    argument,
+                    &reader.amf0_decoder,
+                    &mut reference_cache,
+                )
+            })
+            .collect();
+        let method = AvmString::new_utf8(activation.gc(), method);
+        this.call_method(
+            method,
+            &arguments,
+            &mut activation,
+            ExecutionReason::Special,
+        )?;
+        Ok(())
+    }
 }
 
 pub fn constructor<'gc>(
@@ -321,12 +359,24 @@ fn connect<'gc>(
     {
         // HTTP(S) is for Flash Remoting, which is just POST requests to the URL.
         NetConnections::connect_to_flash_remoting(activation.context, this, url.to_string());
+    } else if url.starts_with(WStr::from_units(b"rtmp://")) {
+        let arguments = args[1..]
+            .iter()
+            .map(|arg| Rc::new(serialize(activation, *arg)))
+            .collect();
+        NetConnections::connect_to_rtmp(
+            activation.context,
+            this,
+            &url.to_string(),
+            AMFVersion::AMF0,
+            arguments,
+        );
     } else {
         avm1_stub!(
             activation,
             "NetConnection",
             "connect",
-            "with non-null, non-http command"
+            "with non-null, non-http, non-rtmp command"
         );
     }
 
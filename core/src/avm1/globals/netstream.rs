@@ -1,4 +1,5 @@
 use crate::avm1::function::FunctionObject;
+use crate::avm1::globals::netconnection::NetConnection;
 use crate::avm1::object::NativeObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Activation, Error, Object, Value};
@@ -9,11 +10,19 @@ use crate::string::StringContext;
 pub fn constructor<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let netstream = NetStream::new(activation.gc(), Some(this.into()));
     this.set_native(activation.gc(), NativeObject::NetStream(netstream));
 
+    let handle = args
+        .first()
+        .and_then(|connection| NetConnection::cast(*connection))
+        .and_then(|connection| connection.handle());
+    if let Some(handle) = handle {
+        netstream.attach_to_connection(activation.context, handle);
+    }
+
     Ok(Value::Undefined)
 }
 
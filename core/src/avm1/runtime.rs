@@ -4,7 +4,7 @@ use crate::avm1::globals::{as_broadcaster, create_globals};
 use crate::avm1::object::stage_object;
 use crate::avm1::property_map::PropertyMap;
 use crate::avm1::scope::Scope;
-use crate::avm1::{scope, Activation, ActivationIdentifier, Error, Object, Value};
+use crate::avm1::{scope, Activation, ActivationIdentifier, Error, Object, TraceRecorder, Value};
 use crate::context::UpdateContext;
 use crate::frame_lifecycle::FramePhase;
 use crate::prelude::*;
@@ -84,6 +84,10 @@ pub struct Avm1<'gc> {
     /// More examples of this are in the movieclip_invalid_get_bounds_X tests.
     use_new_invalid_bounds_value: bool,
 
+    /// Tracepoints, and what ran after the last one was hit.
+    #[collect(require_static)]
+    trace_recorder: TraceRecorder,
+
     #[cfg(feature = "avm_debug")]
     pub debug_output: bool,
 }
@@ -117,6 +121,7 @@ impl<'gc> Avm1<'gc> {
             #[cfg(feature = "avm_debug")]
             debug_output: false,
             use_new_invalid_bounds_value: false,
+            trace_recorder: TraceRecorder::default(),
         }
     }
 
@@ -336,6 +341,11 @@ impl<'gc> Avm1<'gc> {
         self.stack.clear()
     }
 
+    /// The operand stack, from the bottom up.
+    pub fn stack(&self) -> &[Value<'gc>] {
+        &self.stack
+    }
+
     pub fn push(&mut self, value: Value<'gc>) {
         avm_debug!(self, "Stack push {}: {value:?}", self.stack.len());
         self.stack.push(value);
@@ -410,6 +420,14 @@ impl<'gc> Avm1<'gc> {
         self.registers.get_mut(id)
     }
 
+    pub fn trace_recorder(&self) -> &TraceRecorder {
+        &self.trace_recorder
+    }
+
+    pub fn trace_recorder_mut(&mut self) -> &mut TraceRecorder {
+        &mut self.trace_recorder
+    }
+
     /// Find all display objects with negative depth recursively
     ///
     /// If an object is pending removal due to being removed by a removeObject tag on the previous frame,
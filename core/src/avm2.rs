@@ -3,6 +3,7 @@
 use std::rc::Rc;
 
 use crate::avm2::class::{AllocatorFn, CustomConstructorFn};
+use crate::avm2::debugger::Debugger;
 use crate::avm2::e4x::XmlSettings;
 use crate::avm2::error::{make_error_1014, make_error_1107, type_error, Error1014Type};
 use crate::avm2::globals::{
@@ -42,6 +43,7 @@ mod array;
 pub mod bytearray;
 mod call_stack;
 mod class;
+pub mod debugger;
 mod domain;
 mod dynamic_map;
 mod e4x;
@@ -191,6 +193,10 @@ pub struct Avm2<'gc> {
     pub debug_output: bool,
 
     pub optimizer_enabled: bool,
+
+    /// The debugger attached to this AVM, if any.
+    #[collect(require_static)]
+    debugger: Option<Box<Debugger>>,
 }
 
 impl<'gc> Avm2<'gc> {
@@ -241,6 +247,8 @@ impl<'gc> Avm2<'gc> {
             debug_output: false,
 
             optimizer_enabled: true,
+
+            debugger: None,
         }
     }
 
@@ -848,6 +856,20 @@ impl<'gc> Avm2<'gc> {
     pub fn set_optimizer_enabled(&mut self, value: bool) {
         self.optimizer_enabled = value;
     }
+
+    pub fn set_debugger(&mut self, debugger: Option<Debugger>) {
+        self.debugger = debugger.map(Box::new);
+    }
+
+    /// Handle the requests that the debugger has received while running,
+    /// detaching it if its frontend has gone away.
+    pub fn poll_debugger(&mut self) {
+        if let Some(debugger) = &mut self.debugger {
+            if !debugger.poll() {
+                self.debugger = None;
+            }
+        }
+    }
 }
 
 /// If the provided `DisplayObjectWeak` should have frames run, returns
@@ -22,7 +22,7 @@ use crate::avm2::Multiname;
 use crate::avm2::Namespace;
 use crate::avm2::{Avm2, Error};
 use crate::context::UpdateContext;
-use crate::string::{AvmAtom, AvmString, HasStringContext, StringContext};
+use crate::string::{AvmAtom, AvmString, HasStringContext, StringContext, WString};
 use crate::tag_utils::SwfMovie;
 use gc_arena::Gc;
 use ruffle_macros::istr;
@@ -680,6 +680,20 @@ impl<'a, 'gc> Activation<'a, 'gc> {
     }
 
     pub fn run_actions(&mut self, method: Method<'gc>) -> Result<Value<'gc>, Error<'gc>> {
+        if self.context.avm2.debugger.is_none() {
+            return self.interpret(method);
+        }
+
+        self.enter_debugger_frame();
+        let result = self.interpret(method);
+        if let Some(debugger) = &mut self.context.avm2.debugger {
+            debugger.leave_frame();
+        }
+
+        result
+    }
+
+    fn interpret(&mut self, method: Method<'gc>) -> Result<Value<'gc>, Error<'gc>> {
         // The method must be verified at this point
 
         let verified_info = method.get_verified_info();
@@ -691,6 +705,9 @@ impl<'a, 'gc> Activation<'a, 'gc> {
 
         loop {
             let op = &opcodes[ip];
+            if self.context.avm2.debugger.is_some() {
+                self.debug_op(verified_info.byte_offsets[ip], op);
+            }
             ip += 1;
             avm_debug!(self.avm2(), "Opcode: {op:?}");
 
@@ -910,6 +927,38 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         }
     }
 
+    #[cold]
+    fn enter_debugger_frame(&mut self) {
+        let call_stack = self.context.avm2.call_stack().borrow();
+        let call_depth = call_stack.len().saturating_sub(1);
+        let mut name = WString::new();
+        if let Some(call) = call_stack.get(call_depth) {
+            call.display(&mut name);
+        }
+
+        let (stack_depth, num_locals) = (self.stack_depth, self.num_locals);
+        if let Some(debugger) = &mut self.context.avm2.debugger {
+            debugger.enter_frame(call_depth, stack_depth, num_locals, name.to_string());
+        }
+    }
+
+    /// Let the debugger see the op that is about to run, and stop before it
+    /// if need be.
+    #[cold]
+    fn debug_op(&mut self, offset: usize, op: &Op<'gc>) {
+        let Some(mut debugger) = self.context.avm2.debugger.take() else {
+            return;
+        };
+
+        let connected = match debugger.before_op(offset, op) {
+            Some(reason) => debugger.pause(self, reason),
+            None => true,
+        };
+        if connected {
+            self.context.avm2.debugger = Some(debugger);
+        }
+    }
+
     /// If a local exception handler exists for the error, use it to handle
     /// the error. Otherwise pass the error down the stack.
     fn handle_err(
@@ -16,6 +16,27 @@ pub enum CallNode<'gc> {
     },
 }
 
+impl CallNode<'_> {
+    pub fn display(&self, output: &mut WString) {
+        match self {
+            CallNode::GlobalInit(script) => {
+                let tunit = script.translation_unit();
+                let name = if let Some(name) = tunit.name() {
+                    name.to_utf8_lossy().to_string()
+                } else {
+                    "<No name>".to_string()
+                };
+
+                // NOTE: We intentionally diverge from Flash Player's output
+                // here - everything with the [] brackets is extra information
+                // added by Ruffle
+                output.push_utf8(&format!("global$init() [TU={}]", name));
+            }
+            CallNode::Method { method, class } => display_function(output, *method, *class),
+        }
+    }
+}
+
 #[derive(Collect, Clone)]
 #[collect(no_drop)]
 pub struct CallStack<'gc> {
@@ -42,28 +63,22 @@ impl<'gc> CallStack<'gc> {
     pub fn display(&self, output: &mut WString) {
         for call in self.stack.iter().rev() {
             output.push_utf8("\n\tat ");
-            match call {
-                CallNode::GlobalInit(script) => {
-                    let tunit = script.translation_unit();
-                    let name = if let Some(name) = tunit.name() {
-                        name.to_utf8_lossy().to_string()
-                    } else {
-                        "<No name>".to_string()
-                    };
-
-                    // NOTE: We intentionally diverge from Flash Player's output
-                    // here - everything with the [] brackets is extra information
-                    // added by Ruffle
-                    output.push_utf8(&format!("global$init() [TU={}]", name));
-                }
-                CallNode::Method { method, class } => display_function(output, *method, *class),
-            }
+            call.display(output);
         }
     }
 
     pub fn is_empty(&self) -> bool {
         self.stack.is_empty()
     }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Get a call by its depth, where 0 is the outermost call.
+    pub fn get(&self, depth: usize) -> Option<&CallNode<'gc>> {
+        self.stack.get(depth)
+    }
 }
 
 impl Default for CallStack<'_> {
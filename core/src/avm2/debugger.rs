@@ -0,0 +1,778 @@
+//! Debugging AVM2 code from outside of the player.
+//!
+//! A frontend talks to the [`Debugger`] over a pair of channels. While the
+//! movie runs, its requests are handled once per tick. When execution stops,
+//! the interpreter blocks before the next instruction and answers requests
+//! until it's told to carry on.
+//!
+//! Stopping blocks the thread that the player runs on, which on desktop is
+//! the event loop, so the frontend must run on a thread of its own, and the
+//! player window doesn't respond while execution is stopped.
+
+use crate::avm2::dynamic_map::DynamicKey;
+use crate::avm2::object::TObject;
+use crate::avm2::op::Op;
+use crate::avm2::property::Property;
+use crate::avm2::{Activation, Object, Value};
+use crate::string::WString;
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
+use web_time::Instant;
+
+/// A place to stop execution at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// An instruction, by the name of its method as shown in stack traces
+    /// (such as `com.example::Main/init()`) and its byte offset within the
+    /// method body.
+    Instruction { method: String, offset: usize },
+
+    /// A source line, as marked by the `debugfile` and `debugline`
+    /// instructions that compilers emit in debug builds.
+    Line { file: String, line: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepKind {
+    /// Stop at the next line, even if it's in a method called by this one.
+    Into,
+
+    /// Stop at the next line of this method or its callers.
+    Over,
+
+    /// Stop at the next line of a caller.
+    Out,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint,
+    Step,
+    Pause,
+}
+
+#[derive(Debug)]
+pub enum DebuggerRequest {
+    /// Replace every breakpoint.
+    SetBreakpoints(Vec<Breakpoint>),
+
+    /// Stop before the next instruction that runs.
+    Pause,
+
+    /// Carry on after stopping.
+    Continue,
+
+    /// Carry on after stopping, until the next line of the given kind.
+    Step(StepKind),
+
+    /// List the call stack, innermost first. This is empty unless stopped.
+    StackTrace(Sender<Vec<StackFrame>>),
+
+    /// List the variables behind a reference from a `StackFrame` or a
+    /// `Variable`. References are only valid until execution carries on.
+    Variables(u32, Sender<Vec<Variable>>),
+}
+
+#[derive(Debug)]
+pub enum DebuggerEvent {
+    Stopped(StopReason),
+}
+
+#[derive(Clone, Debug)]
+pub struct StackFrame {
+    /// The method, as displayed in stack traces.
+    pub name: String,
+
+    /// Where this frame is at, or `None` for native methods.
+    pub location: Option<Location>,
+
+    /// The reference to the local registers of this frame, or 0 if there are none.
+    pub locals: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Location {
+    /// The byte offset of the next instruction within the method body.
+    pub offset: usize,
+
+    /// The source file, if a `debugfile` instruction has run in this frame.
+    pub file: Option<String>,
+
+    /// The source line, if a `debugline` instruction has run in this frame.
+    pub line: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    pub type_name: String,
+
+    /// The reference to the properties of this value, or 0 if it has none.
+    pub children: u32,
+}
+
+/// A bytecode method that is currently running.
+struct Frame {
+    /// The index of this method in the AVM2 call stack.
+    call_depth: usize,
+
+    /// The index of the first local register on the operand stack.
+    stack_depth: usize,
+
+    num_locals: usize,
+    name: String,
+    offset: usize,
+    file: Option<String>,
+    line: Option<u32>,
+
+    /// Names given to local registers by `debug` instructions.
+    register_names: HashMap<usize, String>,
+}
+
+enum Stepping {
+    Pause,
+    Step { kind: StepKind, depth: usize },
+}
+
+/// Something that a variable reference points to.
+///
+/// These are only kept while execution is stopped, during which the
+/// garbage collector can't run.
+#[derive(Clone, Copy)]
+enum Reference<'gc> {
+    Locals(usize),
+    Object(Object<'gc>),
+}
+
+pub struct Debugger {
+    requests: Receiver<DebuggerRequest>,
+    events: Sender<DebuggerEvent>,
+    breakpoints: Vec<Breakpoint>,
+
+    /// The bytecode methods that are running, innermost last.
+    frames: Vec<Frame>,
+
+    stepping: Option<Stepping>,
+}
+
+impl Debugger {
+    pub fn new(requests: Receiver<DebuggerRequest>, events: Sender<DebuggerEvent>) -> Self {
+        Self {
+            requests,
+            events,
+            breakpoints: Vec::new(),
+            frames: Vec::new(),
+            stepping: None,
+        }
+    }
+
+    /// Handle the requests that have arrived while running.
+    ///
+    /// Returns `false` once the frontend has gone away.
+    pub(crate) fn poll(&mut self) -> bool {
+        loop {
+            match self.requests.try_recv() {
+                Ok(DebuggerRequest::SetBreakpoints(breakpoints)) => self.breakpoints = breakpoints,
+                Ok(DebuggerRequest::Pause) => self.stepping = Some(Stepping::Pause),
+                Ok(DebuggerRequest::Continue | DebuggerRequest::Step(_)) => {}
+                Ok(DebuggerRequest::StackTrace(reply)) => {
+                    let _ = reply.send(Vec::new());
+                }
+                Ok(DebuggerRequest::Variables(_, reply)) => {
+                    let _ = reply.send(Vec::new());
+                }
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    pub(crate) fn enter_frame(
+        &mut self,
+        call_depth: usize,
+        stack_depth: usize,
+        num_locals: usize,
+        name: String,
+    ) {
+        self.frames.push(Frame {
+            call_depth,
+            stack_depth,
+            num_locals,
+            name,
+            offset: 0,
+            file: None,
+            line: None,
+            register_names: HashMap::new(),
+        });
+    }
+
+    pub(crate) fn leave_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    /// Note the instruction that is about to run in the innermost frame, and
+    /// decide whether to stop before it.
+    pub(crate) fn before_op(&mut self, offset: usize, op: &Op<'_>) -> Option<StopReason> {
+        let depth = self.frames.len();
+        let frame = self.frames.last_mut()?;
+        frame.offset = offset;
+
+        // Without line information, every instruction is a line of its own.
+        let mut line_start = frame.line.is_none();
+        match op {
+            Op::DebugFile { file_name } => {
+                frame.file = Some(file_name.to_string());
+                line_start = false;
+            }
+            Op::DebugLine { line_num } => {
+                frame.line = Some(*line_num);
+                line_start = true;
+            }
+            Op::Debug {
+                is_local_register: true,
+                register_name,
+                register,
+            } => {
+                // The register of a `debug` instruction doesn't count `this`.
+                frame
+                    .register_names
+                    .insert(*register as usize + 1, register_name.to_string());
+                line_start = false;
+            }
+            Op::Bkpt | Op::BkptLine { .. } => return Some(StopReason::Breakpoint),
+            _ => {}
+        }
+
+        let hit = self.breakpoints.iter().any(|breakpoint| match breakpoint {
+            Breakpoint::Instruction { method, offset: at } => {
+                *at == offset && *method == frame.name
+            }
+            Breakpoint::Line { file, line } => {
+                matches!(op, Op::DebugLine { line_num } if line_num == line)
+                    && frame
+                        .file
+                        .as_deref()
+                        .is_some_and(|current| same_file(current, file))
+            }
+        });
+        if hit {
+            return Some(StopReason::Breakpoint);
+        }
+
+        match self.stepping {
+            Some(Stepping::Pause) => Some(StopReason::Pause),
+            Some(Stepping::Step { kind, depth: from }) if line_start => {
+                let stop = match kind {
+                    StepKind::Into => true,
+                    StepKind::Over => depth <= from,
+                    StepKind::Out => depth < from,
+                };
+                stop.then_some(StopReason::Step)
+            }
+            _ => None,
+        }
+    }
+
+    /// Block until the frontend says to carry on.
+    ///
+    /// This blocks the thread the player runs on, such as the winit event
+    /// loop on desktop, so nothing else happens in the player until then.
+    ///
+    /// Returns `false` once the frontend has gone away.
+    pub(crate) fn pause<'gc>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        reason: StopReason,
+    ) -> bool {
+        self.stepping = None;
+        if self.events.send(DebuggerEvent::Stopped(reason)).is_err() {
+            return false;
+        }
+
+        let paused_at = Instant::now();
+        let mut references = Vec::new();
+        let connected = loop {
+            let Ok(request) = self.requests.recv() else {
+                break false;
+            };
+
+            match request {
+                DebuggerRequest::SetBreakpoints(breakpoints) => self.breakpoints = breakpoints,
+                DebuggerRequest::Pause => {}
+                DebuggerRequest::Continue => break true,
+                DebuggerRequest::Step(kind) => {
+                    self.stepping = Some(Stepping::Step {
+                        kind,
+                        depth: self.frames.len(),
+                    });
+                    break true;
+                }
+                DebuggerRequest::StackTrace(reply) => {
+                    let _ = reply.send(self.stack_trace(activation, &mut references));
+                }
+                DebuggerRequest::Variables(reference, reply) => {
+                    let _ = reply.send(self.variables(activation, reference, &mut references));
+                }
+            }
+        };
+
+        // Time spent stopped shouldn't count towards the script timeout.
+        activation.context.update_start += paused_at.elapsed();
+
+        connected
+    }
+
+    fn stack_trace<'gc>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        references: &mut Vec<Reference<'gc>>,
+    ) -> Vec<StackFrame> {
+        let call_stack = activation.context.avm2.call_stack().borrow();
+
+        (0..call_stack.len())
+            .rev()
+            .filter_map(|call_depth| {
+                let mut name = WString::new();
+                call_stack.get(call_depth)?.display(&mut name);
+
+                let Some(index) = self
+                    .frames
+                    .iter()
+                    .rposition(|frame| frame.call_depth == call_depth)
+                else {
+                    return Some(StackFrame {
+                        name: name.to_string(),
+                        location: None,
+                        locals: 0,
+                    });
+                };
+
+                let frame = &self.frames[index];
+                references.push(Reference::Locals(index));
+                Some(StackFrame {
+                    name: name.to_string(),
+                    location: Some(Location {
+                        offset: frame.offset,
+                        file: frame.file.clone(),
+                        line: frame.line,
+                    }),
+                    locals: references.len() as u32,
+                })
+            })
+            .collect()
+    }
+
+    fn variables<'gc>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        reference: u32,
+        references: &mut Vec<Reference<'gc>>,
+    ) -> Vec<Variable> {
+        let Some(index) = (reference as usize).checked_sub(1) else {
+            return Vec::new();
+        };
+
+        match references.get(index).copied() {
+            Some(Reference::Locals(frame)) => {
+                let frame = &self.frames[frame];
+                (0..frame.num_locals)
+                    .map(|register| {
+                        let name = match frame.register_names.get(&register) {
+                            Some(name) => name.clone(),
+                            None if register == 0 => "this".to_string(),
+                            None => format!("local{register}"),
+                        };
+                        let value = activation
+                            .context
+                            .avm2
+                            .stack_at(frame.stack_depth + register);
+                        variable(activation, name, value, references)
+                    })
+                    .collect()
+            }
+            Some(Reference::Object(object)) => properties(activation, object, references),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// The slots, array elements and dynamic properties of an object.
+///
+/// Getters aren't called, as that would run code while stopped.
+fn properties<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    references: &mut Vec<Reference<'gc>>,
+) -> Vec<Variable> {
+    let mut slots = object
+        .vtable()
+        .resolved_traits()
+        .iter()
+        .filter_map(|(name, _ns, property)| match property {
+            Property::Slot { slot_id } | Property::ConstSlot { slot_id } => {
+                Some((name.to_string(), *slot_id))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    slots.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut entries = slots
+        .into_iter()
+        .map(|(name, slot_id)| (name, object.get_slot(slot_id)))
+        .collect::<Vec<_>>();
+
+    if let Some(array) = object.as_array_storage() {
+        for (index, value) in array.iter().enumerate() {
+            if let Some(value) = value {
+                entries.push((index.to_string(), value));
+            }
+        }
+    }
+
+    for (key, property) in object.base().values().as_hashmap().iter() {
+        let name = match key {
+            DynamicKey::String(name) => name.to_string(),
+            DynamicKey::Uint(index) => index.to_string(),
+            DynamicKey::Object(key) => {
+                format!("[object {}]", key.instance_class().name().local_name())
+            }
+        };
+        entries.push((name, property.value));
+    }
+
+    entries
+        .into_iter()
+        .map(|(name, value)| variable(activation, name, value, references))
+        .collect()
+}
+
+fn variable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    name: String,
+    value: Value<'gc>,
+    references: &mut Vec<Reference<'gc>>,
+) -> Variable {
+    let (value, type_name, children) = match value {
+        Value::Undefined => ("undefined".to_string(), "void".to_string(), 0),
+        Value::Null => ("null".to_string(), "null".to_string(), 0),
+        Value::Bool(value) => (value.to_string(), "Boolean".to_string(), 0),
+        Value::Number(value) => (value.to_string(), "Number".to_string(), 0),
+        Value::Integer(value) => (value.to_string(), "int".to_string(), 0),
+        Value::String(value) => (format!("{:?}", value.to_string()), "String".to_string(), 0),
+        Value::Object(object) => {
+            let class = object.instance_class().name();
+            references.push(Reference::Object(object));
+            (
+                format!("[object {}]", class.local_name()),
+                class
+                    .to_qualified_name_err_message(activation.gc())
+                    .to_string(),
+                references.len() as u32,
+            )
+        }
+    };
+
+    Variable {
+        name,
+        value,
+        type_name,
+        children,
+    }
+}
+
+/// Whether two source paths name the same file.
+///
+/// Compilers join the source root, package and file name of `debugfile`
+/// with semicolons, and frontends may give either absolute or relative
+/// paths, so this compares the paths from the end. Drive letters may differ
+/// in case, so the comparison ignores it.
+fn same_file(a: &str, b: &str) -> bool {
+    let components = |path: &str| {
+        path.split(['/', '\\', ';'])
+            .filter(|component| !component.is_empty() && *component != ".")
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+    };
+    let (a, b) = (components(a), components(b));
+
+    !a.is_empty() && !b.is_empty() && (a.ends_with(&b) || b.ends_with(&a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerBuilder;
+    use crate::tag_utils::SwfMovie;
+    use std::sync::mpsc::channel;
+
+    /// `inner(x)` is called twice from `outer()`, which is called from the
+    /// script initializer, with the lines of each shown on the left:
+    ///
+    /// ```text
+    ///  1 var obj = {a: 5};        10 inner(1);          20 var doubled = x + x;
+    ///  2 outer();                 11 inner(2);          21 }
+    ///  3 }                        12 }
+    /// ```
+    const TEST_ABC: &str = r#"
+        method 0 "outer"
+          returns *
+          body
+            code
+              debugfile "C:\\src;com\\example;Test.as"
+              debugline 10
+              findpropstrict QName(PackageNamespace(""), "inner")
+              pushbyte 1
+              callpropvoid QName(PackageNamespace(""), "inner"), 1
+              debugline 11
+              findpropstrict QName(PackageNamespace(""), "inner")
+              pushbyte 2
+              callpropvoid QName(PackageNamespace(""), "inner"), 1
+              debugline 12
+              returnvoid
+            end
+          end
+        end
+
+        method 1 "inner"
+          param * name "x"
+          returns *
+          body
+            code
+              debugfile "C:\\src;com\\example;Test.as"
+              debug 1, "x", 0
+              debug 1, "doubled", 1
+              debugline 20
+              getlocal 1
+              getlocal 1
+              add
+              setlocal 2
+              debugline 21
+              returnvoid
+            end
+          end
+        end
+
+        method 2 null
+          returns *
+          body
+            code
+              getlocal 0
+              pushscope
+              debugfile "C:\\src;com\\example;Test.as"
+              debug 1, "obj", 0
+              debugline 1
+              pushstring "a"
+              pushbyte 5
+              newobject 1
+              setlocal 1
+              debugline 2
+              findpropstrict QName(PackageNamespace(""), "outer")
+              callpropvoid QName(PackageNamespace(""), "outer"), 0
+              debugline 3
+              returnvoid
+            end
+          end
+        end
+
+        script 0
+          init 2
+          trait method QName(PackageNamespace(""), "outer") disp_id 1 method 0
+          trait method QName(PackageNamespace(""), "inner") disp_id 2 method 1
+        end
+    "#;
+
+    /// What the frontend saw when execution stopped.
+    struct Stop {
+        reason: StopReason,
+        frames: Vec<StackFrame>,
+
+        /// The local registers of the innermost frame.
+        locals: Vec<Variable>,
+
+        /// The properties of each of `locals`.
+        properties: Vec<Vec<Variable>>,
+    }
+
+    impl Stop {
+        fn line(&self) -> Option<u32> {
+            self.frames[0].location.as_ref()?.line
+        }
+
+        fn local(&self, name: &str) -> &Variable {
+            self.locals
+                .iter()
+                .find(|local| local.name == name)
+                .unwrap_or_else(|| panic!("No local named {name}"))
+        }
+    }
+
+    fn variables(requests: &Sender<DebuggerRequest>, reference: u32) -> Vec<Variable> {
+        let (reply, variables) = channel();
+        requests
+            .send(DebuggerRequest::Variables(reference, reply))
+            .unwrap();
+        variables.recv().unwrap()
+    }
+
+    /// Run the test movie with a debugger attached, answering each stop with
+    /// the next of `answers`, or with `Continue` once they run out.
+    fn run(breakpoints: Vec<Breakpoint>, answers: Vec<DebuggerRequest>) -> Vec<Stop> {
+        let (requests, request_receiver) = channel();
+        let (event_sender, events) = channel();
+        requests
+            .send(DebuggerRequest::SetBreakpoints(breakpoints))
+            .unwrap();
+
+        let swf = swfdump::assembler::assemble_swf(TEST_ABC).unwrap();
+        let movie = SwfMovie::from_data(&swf, "file:///test.swf".into(), None).unwrap();
+        let player = PlayerBuilder::new()
+            .with_movie(movie)
+            .with_avm2_debugger(Debugger::new(request_receiver, event_sender))
+            .build();
+
+        // The player blocks while execution is stopped, so the frontend has
+        // to run on another thread.
+        let frontend = std::thread::spawn(move || {
+            let mut answers = answers.into_iter();
+            let mut stops = vec![];
+            while let Ok(DebuggerEvent::Stopped(reason)) = events.recv() {
+                let (reply, frames) = channel();
+                requests.send(DebuggerRequest::StackTrace(reply)).unwrap();
+                let frames = frames.recv().unwrap();
+                let locals = variables(&requests, frames[0].locals);
+                let properties = locals
+                    .iter()
+                    .map(|local| variables(&requests, local.children))
+                    .collect();
+                stops.push(Stop {
+                    reason,
+                    frames,
+                    locals,
+                    properties,
+                });
+
+                let answer = answers.next().unwrap_or(DebuggerRequest::Continue);
+                requests.send(answer).unwrap();
+            }
+            stops
+        });
+
+        let mut player = player.lock().unwrap();
+        player.mutate_with_update_context(|context| context.avm2.poll_debugger());
+        player.run_frame();
+        // Detaching the debugger lets the frontend know that we're done.
+        player.mutate_with_update_context(|context| context.avm2.set_debugger(None));
+        frontend.join().unwrap()
+    }
+
+    fn line_breakpoint(line: u32) -> Breakpoint {
+        Breakpoint::Line {
+            file: "com/example/Test.as".to_string(),
+            line,
+        }
+    }
+
+    #[test]
+    fn stops_at_line_breakpoints() {
+        let stops = run(vec![line_breakpoint(20)], vec![]);
+
+        assert_eq!(stops.len(), 2);
+        for (stop, x) in stops.iter().zip(["1", "2"]) {
+            assert_eq!(stop.reason, StopReason::Breakpoint);
+            assert_eq!(stop.line(), Some(20));
+            let names: Vec<_> = stop.locals.iter().map(|local| &local.name[..]).collect();
+            assert_eq!(names, ["this", "x", "doubled"]);
+            assert_eq!(stop.local("x").value, x);
+            assert_eq!(stop.local("doubled").value, "undefined");
+        }
+
+        let lines: Vec<_> = stops[0]
+            .frames
+            .iter()
+            .map(|frame| frame.location.as_ref().and_then(|location| location.line))
+            .collect();
+        assert_eq!(lines, [Some(20), Some(10), Some(2)]);
+    }
+
+    #[test]
+    fn stops_at_instruction_breakpoints() {
+        // Find where line 21 starts from a line breakpoint first.
+        let stops = run(vec![line_breakpoint(21)], vec![]);
+        let frame = &stops[0].frames[0];
+        let breakpoint = Breakpoint::Instruction {
+            method: frame.name.clone(),
+            offset: frame.location.as_ref().unwrap().offset,
+        };
+
+        let stops = run(vec![breakpoint], vec![]);
+        assert_eq!(stops.len(), 2);
+        for (stop, doubled) in stops.iter().zip(["2", "4"]) {
+            assert_eq!(stop.reason, StopReason::Breakpoint);
+            assert_eq!(stop.frames[0].name, frame.name);
+            assert_eq!(stop.line(), Some(21));
+            assert_eq!(stop.local("doubled").value, doubled);
+        }
+    }
+
+    #[test]
+    fn steps_into_over_and_out() {
+        let stops = run(
+            vec![line_breakpoint(10)],
+            vec![
+                DebuggerRequest::Step(StepKind::Into),
+                DebuggerRequest::Step(StepKind::Over),
+                DebuggerRequest::Step(StepKind::Out),
+                DebuggerRequest::Step(StepKind::Over),
+                DebuggerRequest::Step(StepKind::Out),
+            ],
+        );
+
+        let seen: Vec<_> = stops
+            .iter()
+            .map(|stop| (stop.reason, stop.line(), stop.frames.len()))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                (StopReason::Breakpoint, Some(10), 2),
+                (StopReason::Step, Some(20), 3),
+                (StopReason::Step, Some(21), 3),
+                (StopReason::Step, Some(11), 2),
+                (StopReason::Step, Some(12), 2),
+                (StopReason::Step, Some(3), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_variables_by_reference() {
+        let stops = run(vec![line_breakpoint(2)], vec![]);
+
+        let [stop] = &stops[..] else {
+            panic!("Expected to stop once");
+        };
+        let obj = stop.local("obj");
+        assert_eq!(obj.value, "[object Object]");
+        assert_eq!(obj.type_name, "Object");
+        assert_ne!(obj.children, 0);
+
+        let index = stop
+            .locals
+            .iter()
+            .position(|local| local.name == "obj")
+            .unwrap();
+        let properties: Vec<_> = stop.properties[index]
+            .iter()
+            .map(|property| {
+                (
+                    &property.name[..],
+                    &property.value[..],
+                    &property.type_name[..],
+                )
+            })
+            .collect();
+        assert_eq!(properties, [("a", "5", "int")]);
+    }
+}
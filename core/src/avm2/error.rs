@@ -590,6 +590,20 @@ pub fn make_error_2027<'gc>(activation: &mut Activation<'_, 'gc>, value: i32) ->
     }
 }
 
+#[inline(never)]
+#[cold]
+pub fn make_error_2029<'gc>(activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
+    let err = io_error(
+        activation,
+        "Error #2029: This URLStream object does not have a stream opened.",
+        2029,
+    );
+    match err {
+        Ok(err) => Error::AvmError(err),
+        Err(err) => err,
+    }
+}
+
 #[inline(never)]
 #[cold]
 pub fn make_error_2037<'gc>(activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
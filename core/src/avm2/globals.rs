@@ -74,6 +74,8 @@ pub struct SystemClasses<'gc> {
     pub framelabel: ClassObject<'gc>,
     pub scene: ClassObject<'gc>,
     pub application_domain: ClassObject<'gc>,
+    pub worker: ClassObject<'gc>,
+    pub messagechannel: ClassObject<'gc>,
     pub event: ClassObject<'gc>,
     pub fullscreenevent: ClassObject<'gc>,
     pub video: ClassObject<'gc>,
@@ -165,6 +167,9 @@ pub struct SystemClasses<'gc> {
     pub filefilter: ClassObject<'gc>,
     pub font: ClassObject<'gc>,
     pub textline: ClassObject<'gc>,
+    pub groupelement: ClassObject<'gc>,
+    pub graphicelement: ClassObject<'gc>,
+    pub spacejustifier: ClassObject<'gc>,
     pub sampledataevent: ClassObject<'gc>,
     pub avm1movie: ClassObject<'gc>,
     pub focusevent: ClassObject<'gc>,
@@ -239,6 +244,8 @@ impl<'gc> SystemClasses<'gc> {
             framelabel: object,
             scene: object,
             application_domain: object,
+            worker: object,
+            messagechannel: object,
             event: object,
             fullscreenevent: object,
             video: object,
@@ -328,6 +335,9 @@ impl<'gc> SystemClasses<'gc> {
             filefilter: object,
             font: object,
             textline: object,
+            groupelement: object,
+            graphicelement: object,
+            spacejustifier: object,
             sampledataevent: object,
             avm1movie: object,
             focusevent: object,
@@ -705,6 +715,8 @@ pub fn init_native_system_classes(activation: &mut Activation<'_, '_>) {
             ("flash.utils", "ByteArray", bytearray),
             ("flash.utils", "Dictionary", dictionary),
             ("flash.system", "ApplicationDomain", application_domain),
+            ("flash.system", "Worker", worker),
+            ("flash.system", "MessageChannel", messagechannel),
             ("flash.text", "Font", font),
             ("flash.text", "StaticText", statictext),
             ("flash.text", "TextFormat", textformat),
@@ -712,6 +724,9 @@ pub fn init_native_system_classes(activation: &mut Activation<'_, '_>) {
             ("flash.text", "TextLineMetrics", textlinemetrics),
             ("flash.text", "TextRun", textrun),
             ("flash.text.engine", "TextLine", textline),
+            ("flash.text.engine", "GroupElement", groupelement),
+            ("flash.text.engine", "GraphicElement", graphicelement),
+            ("flash.text.engine", "SpaceJustifier", spacejustifier),
             ("flash.filters", "BevelFilter", bevelfilter),
             ("flash.filters", "BitmapFilter", bitmapfilter),
             ("flash.filters", "BlurFilter", blurfilter),
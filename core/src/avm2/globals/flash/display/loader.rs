@@ -22,6 +22,7 @@ use crate::display_object::MovieClip;
 use crate::loader::LoadManager;
 use crate::loader::MovieLoaderVMData;
 use crate::tag_utils::SwfMovie;
+use crate::worker::Workers;
 use std::sync::Arc;
 
 pub fn loader_allocator<'gc>(
@@ -60,6 +61,8 @@ pub fn load<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "Loader.load")?;
+
     let this = this.as_object().unwrap();
 
     let url_request = args.get_object(activation, 0, "request")?;
@@ -229,6 +232,8 @@ pub fn load_bytes<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "Loader.loadBytes")?;
+
     let this = this.as_object().unwrap();
 
     let arg0 = args.get_object(activation, 0, "data")?;
@@ -432,7 +432,8 @@ pub fn get_bytes<'gc>(
         .as_object()
         .unwrap();
 
-    if root.data().is_empty() {
+    let data = root.data();
+    if data.is_empty() {
         return Ok(ba.into());
     }
 
@@ -462,7 +463,7 @@ pub fn get_bytes<'gc>(
     let correct_header_length = ba_write.len() - 2;
     ba_write.set_position(correct_header_length);
     ba_write
-        .write_bytes(root.data())
+        .write_bytes(&data)
         .map_err(|e| e.to_avm(activation))?;
 
     // `swf` wrote the wrong length (since we wrote the data
@@ -470,7 +471,7 @@ pub fn get_bytes<'gc>(
     ba_write.set_position(4);
     ba_write.set_endian(Endian::Little);
     ba_write
-        .write_unsigned_int((root.data().len() + correct_header_length) as u32)
+        .write_unsigned_int((data.len() + correct_header_length) as u32)
         .map_err(|e| e.to_avm(activation))?;
 
     // Finally, reset the array to the correct state.
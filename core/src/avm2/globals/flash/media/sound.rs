@@ -1,7 +1,7 @@
 //! `flash.media.Sound` builtin/prototype
 
 use crate::avm2::activation::Activation;
-use crate::avm2::error::{argument_error, make_error_2037};
+use crate::avm2::error::{argument_error, make_error_2008, make_error_2029, make_error_2037};
 use crate::avm2::globals::methods::flash_media_sound as sound_methods;
 use crate::avm2::globals::slots::flash_net_url_request as url_request_slots;
 use crate::avm2::object::{
@@ -11,11 +11,13 @@ use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
 use crate::avm2::Avm2;
 use crate::avm2::Error;
+use crate::avm2_stub_getter;
 use crate::backend::navigator::Request;
 use crate::character::Character;
 use crate::display_object::SoundTransform;
-use crate::{avm2_stub_getter, avm2_stub_method};
-use swf::{SoundEvent, SoundInfo};
+use crate::worker::Workers;
+use ruffle_macros::istr;
+use swf::{AudioCompression, SoundEvent, SoundFormat, SoundInfo};
 
 pub use crate::avm2::object::sound_allocator;
 
@@ -64,6 +66,9 @@ pub fn get_bytes_total<'gc>(
     let this = this.as_object().unwrap();
 
     if let Some(sound) = this.as_sound_object() {
+        if let Some(bytes_total) = sound.bytes_total() {
+            return Ok(bytes_total.into());
+        }
         if let Some(sound_handle) = sound.sound_handle() {
             if let Some(length) = activation.context.audio.get_sound_size(sound_handle) {
                 return Ok((length).into());
@@ -75,24 +80,31 @@ pub fn get_bytes_total<'gc>(
     Ok(Value::Undefined)
 }
 
+/// Implements `Sound.bytesLoaded`
 pub fn get_bytes_loaded<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // This should have a different value from bytesTotal when the sound is loading.
-    avm2_stub_getter!(activation, "flash.media.Sound", "bytesLoaded");
+    if let Some(sound) = this.as_object().and_then(|o| o.as_sound_object()) {
+        if let Some(bytes_loaded) = sound.bytes_loaded() {
+            return Ok(bytes_loaded.into());
+        }
+    }
+
     get_bytes_total(activation, this, args)
 }
 
 /// Implements `Sound.isBuffering`
 pub fn get_is_buffering<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Value<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.media.Sound", "isBuffering");
-    //STUB: We do not yet support network-loaded sounds.
+    if let Some(sound) = this.as_object().and_then(|o| o.as_sound_object()) {
+        return Ok(sound.is_buffering().into());
+    }
+
     Ok(false.into())
 }
 
@@ -109,12 +121,18 @@ pub fn get_is_url_inaccessible<'gc>(
 
 /// Implements `Sound.url`
 pub fn get_url<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    _this: Value<'gc>,
+    _activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.media.Sound", "url");
-    //STUB: We do not yet support network-loaded sounds.
+    if let Some(url) = this
+        .as_object()
+        .and_then(|o| o.as_sound_object())
+        .and_then(|sound| sound.url())
+    {
+        return Ok(url.into());
+    }
+
     Ok(Value::Null)
 }
 
@@ -127,6 +145,10 @@ pub fn get_length<'gc>(
     let this = this.as_object().unwrap();
 
     if let Some(sound) = this.as_sound_object() {
+        // While streaming, the length only covers the downloaded data.
+        if let Some(duration) = sound.buffered_duration() {
+            return Ok(duration.into());
+        }
         if let Some(sound_handle) = sound.sound_handle() {
             if let Some(duration) = activation.context.audio.get_sound_duration(sound_handle) {
                 return Ok((duration).into());
@@ -144,6 +166,8 @@ pub fn play<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "Sound.play")?;
+
     let this = this.as_object().unwrap();
 
     if let Some(sound_object) = this.as_sound_object() {
@@ -201,32 +225,68 @@ pub fn play<'gc>(
 /// `Sound.extract`
 pub fn extract<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Value<'gc>,
+    this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_method!(activation, "flash.media.Sound", "extract");
+    let this = this.as_object().unwrap().as_sound_object().unwrap();
 
-    let bytearray = args.try_get_object(activation, 0);
+    let bytearray = args.get_object(activation, 0, "target")?;
     let length = args.get_f64(activation, 1)?;
+    let start_position = args.get_f64(activation, 2)?;
 
-    if let Some(bytearray) = bytearray {
-        if let Some(mut bytearray) = bytearray.as_bytearray_mut() {
+    let Some(sound_handle) = this.sound_handle() else {
+        return Ok(0.into());
+    };
+
+    let start = if start_position < 0.0 {
+        this.extract_position()
+    } else {
+        start_position as u32
+    };
+    let num_frames = length.max(0.0) as u32;
+
+    let sample_frames = activation
+        .context
+        .audio
+        .extract_sound_data(sound_handle, start, num_frames)
+        .unwrap_or_default();
+
+    if let Some(mut bytearray) = bytearray.as_bytearray_mut() {
+        for [left, right] in &sample_frames {
             bytearray
-                .write_bytes(vec![0u8; length.ceil() as usize].as_slice())
+                .write_float(*left)
+                .map_err(|e| e.to_avm(activation))?;
+            bytearray
+                .write_float(*right)
                 .map_err(|e| e.to_avm(activation))?;
         }
     }
 
-    Ok(Value::Undefined)
+    let num_extracted = sample_frames.len() as u32;
+    this.set_extract_position(start + num_extracted);
+
+    Ok(num_extracted.into())
 }
 
 /// `Sound.close`
 pub fn close<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Value<'gc>,
+    this: Value<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_method!(activation, "flash.media.Sound", "close");
+    let this = this.as_object().unwrap().as_sound_object().unwrap();
+    if this.loading_state() != SoundLoadingState::Loading {
+        return Err(make_error_2029(activation));
+    }
+
+    // The loader stops downloading once it sees that the sound is no longer
+    // loading. Whatever was downloaded so far remains playable.
+    if let Err(e) = this.finish_streaming(activation.context) {
+        tracing::error!("Sound.close: couldn't register partial sound: {}", e);
+        this.close_stream(activation.gc());
+        this.set_loading_state(SoundLoadingState::Loaded);
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -236,6 +296,8 @@ pub fn load<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "Sound.load")?;
+
     let this_object = this.as_object().unwrap();
 
     let this = this_object.as_sound_object().unwrap();
@@ -253,11 +315,13 @@ pub fn load<'gc>(
         .get_slot(url_request_slots::_URL)
         .coerce_to_string(activation)?;
 
-    // TODO: context parameter currently unused.
-    let sound_context = args.try_get_object(activation, 1);
-    if sound_context.is_some() {
-        avm2_stub_method!(activation, "flash.media.Sound", "load", "with context");
-    }
+    // TODO: `checkPolicyFile` is currently unused.
+    let buffer_time = match args.try_get_object(activation, 1) {
+        Some(sound_context) => Value::from(sound_context)
+            .get_public_property(istr!("bufferTime"), activation)?
+            .coerce_to_number(activation)?,
+        None => 1000.0,
+    };
 
     let future = activation.context.load_manager.load_sound_avm2(
         activation.context.player.clone(),
@@ -266,7 +330,7 @@ pub fn load<'gc>(
         Request::get(url.to_string()),
     );
     activation.context.navigator.spawn_future(future);
-    this.set_loading_state(SoundLoadingState::Loading);
+    this.start_streaming(activation.gc(), url, buffer_time);
 
     Ok(Value::Undefined)
 }
@@ -319,7 +383,7 @@ pub fn load_compressed_data_from_byte_array<'gc>(
 pub fn load_pcm_from_byte_array<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Value<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     let this_object = this.as_object().unwrap();
 
@@ -328,11 +392,71 @@ pub fn load_pcm_from_byte_array<'gc>(
         return Ok(Value::Undefined);
     }
 
-    // TODO Add proper implementation.
-    //   The following line ensures proper behavior
-    //   when calling load multiple times.
-    this.set_loading_state(SoundLoadingState::Loaded);
-    avm2_stub_method!(activation, "flash.media.Sound", "loadPCMFromByteArray");
+    let bytearray = args.get_object(activation, 0, "bytes")?;
+    let samples = args.get_u32(activation, 1)?;
+    let format = args.get_string(activation, 2)?;
+    let is_stereo = args.get_bool(3);
+    let sample_rate = args.get_f64(activation, 4)?;
+
+    let is_float = if &format == b"float" {
+        true
+    } else if &format == b"short" {
+        false
+    } else {
+        return Err(make_error_2008(activation, "format"));
+    };
+
+    let sample_rate = match sample_rate as u16 {
+        sample_rate @ (5512 | 11025 | 22050 | 44100) if f64::from(sample_rate) == sample_rate => {
+            sample_rate
+        }
+        _ => return Err(make_error_2008(activation, "sampleRate")),
+    };
+
+    // The audio backend plays 16-bit PCM, so float samples are converted.
+    let bytearray = bytearray.as_bytearray().unwrap();
+    let num_channels = if is_stereo { 2 } else { 1 };
+    let sample_size = if is_float { 4 } else { 2 };
+    let num_frames =
+        (bytearray.bytes_available() / (sample_size * num_channels)).min(samples as usize);
+
+    let mut data = Vec::with_capacity(num_frames * num_channels * 2);
+    for _ in 0..num_frames * num_channels {
+        let sample = if is_float {
+            let sample = bytearray.read_float().map_err(|e| e.to_avm(activation))?;
+            // Scale by the same factor that `Sound.extract` divides by, so
+            // that samples round-trip exactly.
+            (sample * 32768.0)
+                .round()
+                .clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16
+        } else {
+            bytearray.read_short().map_err(|e| e.to_avm(activation))?
+        };
+        data.extend_from_slice(&sample.to_le_bytes());
+    }
+    drop(bytearray);
+
+    let sound = swf::Sound {
+        id: 0,
+        format: SoundFormat {
+            compression: AudioCompression::Uncompressed,
+            sample_rate,
+            is_stereo,
+            is_16_bit: true,
+        },
+        num_samples: num_frames as u32,
+        data: &data,
+    };
+
+    // FIXME - determine the actual error thrown by Flash Player
+    let handle = activation
+        .context
+        .audio
+        .register_sound(&sound)
+        .map_err(|e| {
+            Error::RustError(format!("Failed to register sound from bytearray: {e:?}").into())
+        })?;
+    this.set_sound(activation.context, handle)?;
 
     Ok(Value::Undefined)
 }
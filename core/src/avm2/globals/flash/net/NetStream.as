@@ -18,7 +18,9 @@ package flash.net {
         public static const DIRECT_CONNECTIONS: String = "directConnections";
 
         public function NetStream(connection:NetConnection, peer:String = CONNECT_TO_FMS) {
-
+            if (connection) {
+                this.attach(connection);
+            }
         }
 
         public function appendBytes(bytes:ByteArray) {
@@ -29,9 +31,7 @@ package flash.net {
             stub_method("flash.net.NetStream", "appendBytesAction");
         }
 
-        public function attach(connection:NetConnection) {
-            stub_method("flash.net.NetStream", "attach");
-        }
+        public native function attach(connection:NetConnection);
 
         public function attachAudio(mic:Microphone) {
             stub_method("flash.net.NetStream", "attachAudio");
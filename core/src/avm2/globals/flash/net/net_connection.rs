@@ -14,6 +14,7 @@ use flash_lso::types::AMFVersion;
 use flash_lso::types::ObjectId;
 use flash_lso::types::Value as AMFValue;
 use fnv::FnvHashMap;
+use ruffle_macros::istr;
 use ruffle_wstr::WStr;
 use std::rc::Rc;
 
@@ -39,12 +40,38 @@ pub fn connect<'gc>(
     {
         // HTTP(S) is for Flash Remoting, which is just POST requests to the URL.
         NetConnections::connect_to_flash_remoting(activation.context, connection, url.to_string());
+    } else if url.starts_with(WStr::from_units(b"rtmp://")) {
+        let object_encoding = match Value::from(this)
+            .get_public_property(istr!("objectEncoding"), activation)?
+            .coerce_to_u32(activation)?
+        {
+            0 => AMFVersion::AMF0,
+            _ => AMFVersion::AMF3,
+        };
+
+        let mut object_table = FnvHashMap::default();
+        let mut arguments = Vec::new();
+        for arg in &args[1..] {
+            if let Some(value) =
+                serialize_value(activation, *arg, AMFVersion::AMF0, &mut object_table)
+            {
+                arguments.push(Rc::new(value));
+            }
+        }
+
+        NetConnections::connect_to_rtmp(
+            activation.context,
+            connection,
+            &url.to_string(),
+            object_encoding,
+            arguments,
+        );
     } else {
         avm2_stub_method!(
             activation,
             "flash.net.NetConnection",
             "connect",
-            "with non-null, non-http command"
+            "with non-null, non-http, non-rtmp command"
         );
     }
 
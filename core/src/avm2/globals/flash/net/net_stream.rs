@@ -1,6 +1,7 @@
 use crate::avm2::error::{make_error_2004, Error2004Type};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::{Activation, Error, TObject, Value};
+use crate::worker::Workers;
 
 pub use crate::avm2::object::netstream_allocator as net_stream_allocator;
 
@@ -32,11 +33,34 @@ pub fn get_bytes_total<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn attach<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    if let Some(ns) = this.as_netstream() {
+        let handle = args
+            .try_get_object(activation, 0)
+            .and_then(|connection| connection.as_net_connection())
+            .and_then(|connection| connection.handle());
+
+        if let Some(handle) = handle {
+            ns.attach_to_connection(activation.context, handle);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn play<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "NetStream.play")?;
+
     let this = this.as_object().unwrap();
 
     if let Some(ns) = this.as_netstream() {
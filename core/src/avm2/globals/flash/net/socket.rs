@@ -7,6 +7,7 @@ use crate::avm2::parameters::ParametersExt;
 use crate::avm2::string::AvmString;
 use crate::avm2::{Activation, Error, TObject, Value};
 use crate::context::UpdateContext;
+use crate::worker::Workers;
 use encoding_rs::Encoding;
 use encoding_rs::UTF_8;
 use flash_lso::amf0::read::AMF0Decoder;
@@ -31,6 +32,8 @@ pub fn connect<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "Socket.connect")?;
+
     let this = this.as_object().unwrap();
 
     let socket = match this.as_socket() {
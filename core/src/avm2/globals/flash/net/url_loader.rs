@@ -5,6 +5,7 @@ use crate::avm2::globals::flash::display::loader::request_from_url_request;
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
 use crate::avm2::{Error, Object};
+use crate::worker::Workers;
 
 /// Native function definition for `URLLoader.load`
 pub fn load<'gc>(
@@ -12,6 +13,8 @@ pub fn load<'gc>(
     this: Value<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    Workers::require_primordial(activation, "URLLoader.load")?;
+
     let this = this.as_object().unwrap();
 
     let request = args.get_object(activation, 0, "request")?;
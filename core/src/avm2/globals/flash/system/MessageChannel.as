@@ -4,8 +4,22 @@ package flash.system {
     [API("682")]
     [Ruffle(Abstract)]
     public final class MessageChannel extends EventDispatcher {
+        // The handle of the channel this object represents.
+        [Ruffle(NativeAccessible)]
+        private var _handle:uint;
+
         public function MessageChannel() {
             super();
         }
+
+        public native function get messageAvailable():Boolean;
+
+        public native function get state():String;
+
+        public native function send(arg:*, queueLimit:int = -1):void;
+
+        public native function receive(blockUntilReceived:Boolean = false):*;
+
+        public native function close():void;
     }
 }
@@ -4,8 +4,28 @@ package flash.system {
     [API("682")]
     [Ruffle(Abstract)]
     public final class Worker extends EventDispatcher {
+        // The handle of the worker this object represents.
+        [Ruffle(NativeAccessible)]
+        private var _handle:uint;
+
         public static function get isSupported():Boolean {
-            return false;
+            return true;
         }
+
+        public static native function get current():Worker;
+
+        public native function get isPrimordial():Boolean;
+
+        public native function get state():String;
+
+        public native function start():void;
+
+        public native function terminate():Boolean;
+
+        public native function setSharedProperty(key:String, value:*):void;
+
+        public native function getSharedProperty(key:String):*;
+
+        public native function createMessageChannel(receiver:Worker):MessageChannel;
     }
 }
@@ -1,10 +1,31 @@
 package flash.system {
+    import flash.utils.ByteArray;
+
     [API("680")] // the docs say 682, that's wrong
     public final class WorkerDomain {
-        public static const isSupported: Boolean = false;
+        public static const isSupported: Boolean = true;
+
+        private static var _current:WorkerDomain;
+        private static var _constructing:Boolean = false;
+
+        public static function get current():WorkerDomain {
+            if (!_current) {
+                _constructing = true;
+                _current = new WorkerDomain();
+                _constructing = false;
+            }
+
+            return _current;
+        }
 
         public function WorkerDomain() {
-            throw new ArgumentError("Error #2012: WorkerDomain$ class cannot be instantiated.", 2012)
+            if (!_constructing) {
+                throw new ArgumentError("Error #2012: WorkerDomain$ class cannot be instantiated.", 2012)
+            }
         }
+
+        public native function createWorker(swf:ByteArray, giveAppPrivileges:Boolean = false):Worker;
+
+        public native function listWorkers():Vector.<Worker>;
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,105 @@
+//! `flash.system.MessageChannel` native methods
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::slots::flash_system_messagechannel as slots;
+use crate::avm2::object::TObject;
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::value::Value;
+use crate::avm2::{Error, Object};
+use crate::avm2_stub_method;
+use crate::string::AvmString;
+use crate::worker::{ChannelHandle, SharedValue};
+
+fn handle(this: Object<'_>) -> ChannelHandle {
+    ChannelHandle::from_u32(this.get_slot(slots::_HANDLE).as_u32())
+}
+
+/// Implements `MessageChannel.messageAvailable`
+pub fn get_message_available<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    Ok(activation.context.workers.has_message(handle(this)).into())
+}
+
+/// Implements `MessageChannel.state`
+pub fn get_state<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let state = activation.context.workers.channel_state(handle(this));
+
+    Ok(AvmString::new_utf8(activation.gc(), state.as_str()).into())
+}
+
+/// Implements `MessageChannel.send`
+pub fn send<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let queue_limit = args.get_i32(activation, 1)?;
+    if queue_limit >= 0 {
+        // Workers never run at the same time, so the sender can't wait for
+        // the receiver to catch up.
+        avm2_stub_method!(
+            activation,
+            "flash.system.MessageChannel",
+            "send",
+            "with queueLimit"
+        );
+    }
+
+    let message = SharedValue::from_value(activation, args.get_value(0))?;
+    if !activation.context.workers.send(handle(this), message) {
+        tracing::warn!("MessageChannel.send: channel is closed");
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `MessageChannel.receive`
+pub fn receive<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let Some(message) = activation.context.workers.receive(handle(this)) else {
+        if args.get_bool(0) {
+            // Blocking would stall the only thread every worker runs on.
+            avm2_stub_method!(
+                activation,
+                "flash.system.MessageChannel",
+                "receive",
+                "with blockUntilReceived and no message available"
+            );
+        }
+
+        return Ok(Value::Null);
+    };
+
+    message.to_value(activation)
+}
+
+/// Implements `MessageChannel.close`
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    activation.context.workers.close(handle(this));
+
+    Ok(Value::Undefined)
+}
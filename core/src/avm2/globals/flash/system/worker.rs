@@ -0,0 +1,129 @@
+//! `flash.system.Worker` native methods
+
+use crate::avm2::activation::Activation;
+use crate::avm2::globals::slots::flash_system_worker as slots;
+use crate::avm2::object::TObject;
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::value::Value;
+use crate::avm2::{Error, Object};
+use crate::string::AvmString;
+use crate::worker::{SharedValue, WorkerHandle, Workers};
+
+fn handle(this: Object<'_>) -> WorkerHandle {
+    WorkerHandle::from_u32(this.get_slot(slots::_HANDLE).as_u32())
+}
+
+/// Implements `Worker.current`
+pub fn get_current<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let current = activation.context.workers.current();
+
+    Ok(Workers::worker_object(activation, current)?.into())
+}
+
+/// Implements `Worker.isPrimordial`
+pub fn get_is_primordial<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    Ok((handle(this) == WorkerHandle::PRIMORDIAL).into())
+}
+
+/// Implements `Worker.state`
+pub fn get_state<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let state = activation.context.workers.state(handle(this));
+
+    Ok(AvmString::new_utf8(activation.gc(), state.as_str()).into())
+}
+
+/// Implements `Worker.start`
+pub fn start<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    Workers::start(activation.context, handle(this));
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Worker.terminate`
+pub fn terminate<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    Ok(activation.context.workers.terminate(handle(this)).into())
+}
+
+/// Implements `Worker.setSharedProperty`
+pub fn set_shared_property<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let key = args.get_string(activation, 0)?.to_string();
+    let value = SharedValue::from_value(activation, args.get_value(1))?;
+    activation
+        .context
+        .workers
+        .set_shared_property(handle(this), key, value);
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `Worker.getSharedProperty`
+pub fn get_shared_property<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let key = args.get_string(activation, 0)?.to_string();
+    let Some(value) = activation
+        .context
+        .workers
+        .shared_property(handle(this), &key)
+        .cloned()
+    else {
+        return Ok(Value::Undefined);
+    };
+
+    value.to_value(activation)
+}
+
+/// Implements `Worker.createMessageChannel`
+pub fn create_message_channel<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let receiver = handle(args.get_object(activation, 0, "receiver")?);
+    let channel = activation
+        .context
+        .workers
+        .create_channel(handle(this), receiver);
+
+    Ok(Workers::channel_object(activation, channel)?.into())
+}
@@ -0,0 +1,64 @@
+//! `flash.system.WorkerDomain` native methods
+
+use crate::avm2::activation::Activation;
+use crate::avm2::error::{make_error_2004, Error2004Type};
+use crate::avm2::object::{TObject, VectorObject};
+use crate::avm2::parameters::ParametersExt;
+use crate::avm2::value::Value;
+use crate::avm2::vector::VectorStorage;
+use crate::avm2::Error;
+use crate::tag_utils::SwfMovie;
+use crate::worker::Workers;
+use std::sync::Arc;
+
+/// Implements `WorkerDomain.createWorker`
+pub fn create_worker<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let swf = args.get_object(activation, 0, "swf")?;
+    let bytes = swf
+        .as_bytearray()
+        .map(|bytearray| bytearray.bytes().to_vec())
+        .unwrap_or_default();
+
+    let caller_movie = activation.caller_movie_or_root();
+    let movie = match SwfMovie::from_data(
+        &bytes,
+        caller_movie.url().to_string(),
+        caller_movie.loader_url().map(str::to_string),
+    ) {
+        Ok(movie) if movie.is_action_script_3() => movie,
+        Ok(_) => {
+            tracing::error!("WorkerDomain.createWorker: AVM1 movies can't be run in a worker");
+            return Err(make_error_2004(activation, Error2004Type::ArgumentError));
+        }
+        Err(e) => {
+            tracing::error!("WorkerDomain.createWorker: invalid SWF: {e}");
+            return Err(make_error_2004(activation, Error2004Type::ArgumentError));
+        }
+    };
+
+    let handle = activation.context.workers.create_worker(Arc::new(movie));
+
+    Ok(Workers::worker_object(activation, handle)?.into())
+}
+
+/// Implements `WorkerDomain.listWorkers`
+pub fn list_workers<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let handles = activation.context.workers.live_workers();
+    let mut workers = Vec::with_capacity(handles.len());
+    for handle in handles {
+        workers.push(Workers::worker_object(activation, handle)?.into());
+    }
+
+    let worker_class = activation.avm2().classes().worker.inner_class_definition();
+    let storage = VectorStorage::from_values(workers, false, Some(worker_class));
+
+    Ok(VectorObject::from_vector(storage, activation)?.into())
+}
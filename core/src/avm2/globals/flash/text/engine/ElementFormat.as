@@ -28,6 +28,7 @@ package flash.text.engine {
         [Ruffle(NativeAccessible)]
         private var _fontSize:Number;
 
+        [Ruffle(NativeAccessible)]
         private var _kerning:String;
 
         private var _ligatureLevel:String;
@@ -36,8 +37,10 @@ package flash.text.engine {
 
         private var _textRotation:String;
 
+        [Ruffle(NativeAccessible)]
         private var _trackingLeft:Number;
 
+        [Ruffle(NativeAccessible)]
         private var _trackingRight:Number;
 
         private var _typographicCase:String;
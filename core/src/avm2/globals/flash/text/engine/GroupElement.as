@@ -5,6 +5,7 @@ package flash.text.engine {
 
     [API("662")]
     public final class GroupElement extends ContentElement {
+        [Ruffle(NativeAccessible)]
         internal var _elements = null;
 
         public function GroupElement(elements:Vector.<ContentElement> = null, elementFormat:ElementFormat = null, eventMirror:EventDispatcher = null, textRotation:String = "rotate0") {
@@ -2,8 +2,13 @@ package flash.text.engine {
     [API("662")]
     public final class TabStop {
         // FIXME: These should be getters/setters to match Flash
+        [Ruffle(NativeAccessible)]
         public var alignment:String;
+
+        [Ruffle(NativeAccessible)]
         public var position:Number;
+
+        [Ruffle(NativeAccessible)]
         public var decimalAlignmentToken:String;
 
         public function TabStop(alignment:String = "start", position:Number = 0, decimalAlignmentToken:String = "") {
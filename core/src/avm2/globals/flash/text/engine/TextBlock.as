@@ -11,7 +11,11 @@ package flash.text.engine {
         private var _baselineZero:String = "roman";
         private var _bidiLevel:int;
         private var _lineRotation:String;
+
+        [Ruffle(NativeAccessible)]
         private var _tabStops:Vector.<TabStop>;
+
+        [Ruffle(NativeAccessible)]
         private var _textJustifier:TextJustifier;
 
         [Ruffle(NativeAccessible)]
@@ -23,6 +27,8 @@ package flash.text.engine {
         [Ruffle(NativeAccessible)]
         private var _firstLine:TextLine = null;
 
+        [Ruffle(NativeAccessible)]
+        private var _lastLine:TextLine = null;
 
         public function TextBlock(content:ContentElement = null,
                                   tabStops:Vector.<TabStop> = null,
@@ -166,16 +172,41 @@ package flash.text.engine {
         }
 
         public function get lastLine():TextLine {
-            return this._firstLine;
+            return this._lastLine;
         }
 
         public function releaseLines(start:TextLine, end:TextLine):void {
-            if (start != end || end != this._firstLine) {
-                stub_method("flash.text.engine.TextBlock", "releaseLines", "with start != end or multiple lines");
-                return;
+            if (start == null || end == null || start._textBlock != this || end._textBlock != this) {
+                throw new ArgumentError("Error #2004: One of the parameters is invalid.", 2004);
+            }
+
+            var before:TextLine = start._previousLine;
+            var after:TextLine = end._nextLine;
+
+            var line:TextLine = start;
+            while (line != null) {
+                var next:TextLine = line._nextLine;
+                line._validity = "invalid";
+                line._textBlock = null;
+                line._previousLine = null;
+                line._nextLine = null;
+                if (line == end) {
+                    break;
+                }
+                line = next;
+            }
+
+            if (before != null) {
+                before._nextLine = after;
+            } else {
+                this._firstLine = after;
+            }
+
+            if (after != null) {
+                after._previousLine = before;
+            } else {
+                this._lastLine = before;
             }
-            this._firstLine._validity = "invalid";
-            this._firstLine._textBlock = null;
         }
     }
 }
@@ -3,6 +3,7 @@ package flash.text.engine {
 
     [API("662")]
     public class TextJustifier {
+        [Ruffle(NativeAccessible)]
         private var _lineJustification:String = null;
         public function TextJustifier(locale:String, lineJustification:String) {
             // TODO: Is there a better way to do this?
@@ -7,15 +7,56 @@ use crate::avm2::globals::methods::flash_text_engine_content_element as element_
 use crate::avm2::globals::slots::flash_text_engine_content_element as element_slots;
 use crate::avm2::globals::slots::flash_text_engine_element_format as format_slots;
 use crate::avm2::globals::slots::flash_text_engine_font_description as font_desc_slots;
+use crate::avm2::globals::slots::flash_text_engine_graphic_element as graphic_slots;
+use crate::avm2::globals::slots::flash_text_engine_group_element as group_slots;
+use crate::avm2::globals::slots::flash_text_engine_space_justifier as space_justifier_slots;
+use crate::avm2::globals::slots::flash_text_engine_tab_stop as tab_stop_slots;
 use crate::avm2::globals::slots::flash_text_engine_text_block as block_slots;
+use crate::avm2::globals::slots::flash_text_engine_text_justifier as justifier_slots;
 use crate::avm2::globals::slots::flash_text_engine_text_line as line_slots;
 use crate::avm2::object::{Object, TObject};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
 use crate::avm2_stub_method;
-use crate::display_object::{EditText, TDisplayObject};
+use crate::context::UpdateContext;
+use crate::display_object::EditText;
 use crate::html::TextFormat;
-use crate::string::WStr;
+use crate::prelude::*;
+use crate::string::{WStr, WString};
+use std::ops::Range;
+
+/// How many characters of the remaining text are laid out at first when
+/// looking for the end of a line.
+const INITIAL_LAYOUT_WINDOW: usize = 256;
+
+/// A tab stop of a `TextBlock`.
+struct TabStop {
+    position: f64,
+    alignment: TabAlignment,
+    decimal_alignment_token: WString,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TabAlignment {
+    Start,
+    Center,
+    End,
+    Decimal,
+}
+
+/// A run of the flattened content of a `TextBlock` that shares one format.
+struct ContentRun {
+    text: WString,
+    format: TextFormat,
+    is_device_font: bool,
+
+    /// The height of the `GraphicElement` this run stands in for, if any.
+    graphic_height: Option<f64>,
+}
+
+/// The part of a `ContentRun` that falls on a line: its start and end in the
+/// text of the line, and its format there.
+type LineRun<'a> = (usize, usize, &'a ContentRun, TextFormat);
 
 pub fn create_text_line<'gc>(
     activation: &mut Activation<'_, 'gc>,
@@ -24,48 +65,61 @@ pub fn create_text_line<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     let this = this.as_object().unwrap();
 
-    avm2_stub_method!(activation, "flash.text.TextBlock", "createTextLine");
-
     let previous_text_line = args.try_get_object(activation, 0);
     let width = args.get_f64(activation, 1)?;
 
-    let content = this.get_slot(block_slots::_CONTENT);
+    let content = match this.get_slot(block_slots::_CONTENT) {
+        Value::Object(content) => content,
+        _ => return Ok(Value::Null),
+    };
+
+    // Get the content element's text property (it's a getter).
+    let txt = content
+        .call_method(element_methods::GET_TEXT, &[], activation)
+        .unwrap_or_else(|_| istr!("").into());
 
-    let content = if matches!(content, Value::Null) {
+    if matches!(txt, Value::Null) {
+        // FP returns a null TextLine when `o` is null- note that
+        // `o` is already coerced to a String because of the AS bindings.
         return Ok(Value::Null);
-    } else {
-        content
-    };
+    }
 
-    let text = match previous_text_line {
-        Some(_) => {
-            // Some SWFs rely on eventually getting `null` from createLineText.
-            // TODO: Support multiple lines
-            this.set_slot(
-                block_slots::_TEXT_LINE_CREATION_RESULT,
-                istr!("complete").into(),
-                activation,
-            )?;
-            return Ok(Value::Null);
-        }
-        // Get the content element's text property (it's a getter).
-        // TODO: GraphicElement?
-        None => {
-            let txt = content
-                .call_method(element_methods::GET_TEXT, &[], activation)
-                .unwrap_or_else(|_| istr!("").into());
-
-            if matches!(txt, Value::Null) {
-                // FP returns a null TextLine when `o` is null- note that
-                // `o` is already coerced to a String because of the AS bindings.
-                return Ok(Value::Null);
-            } else {
-                txt.coerce_to_string(activation)
-                    .expect("Guaranteed by AS bindings")
-            }
+    let mut runs = Vec::new();
+    collect_runs(activation, content, None, &mut runs)?;
+
+    let tab_stops = tab_stops(activation, this)?;
+    let tab_positions: Option<Vec<f64>> = tab_stops
+        .as_ref()
+        .map(|tab_stops| tab_stops.iter().map(|stop| stop.position).collect());
+
+    let mut text = WString::new();
+    for run in &runs {
+        text.push_str(&run.text);
+    }
+
+    let begin_index = match previous_text_line {
+        Some(previous_text_line) => {
+            let begin_index = previous_text_line
+                .get_slot(line_slots::_TEXT_BLOCK_BEGIN_INDEX)
+                .coerce_to_u32(activation)?;
+            let raw_text_length = previous_text_line
+                .get_slot(line_slots::_RAW_TEXT_LENGTH)
+                .coerce_to_u32(activation)?;
+            (begin_index + raw_text_length) as usize
         }
+        None => 0,
     };
 
+    if previous_text_line.is_some() && begin_index >= text.len() {
+        // Some SWFs rely on eventually getting `null` from createLineText.
+        this.set_slot(
+            block_slots::_TEXT_LINE_CREATION_RESULT,
+            istr!("complete").into(),
+            activation,
+        )?;
+        return Ok(Value::Null);
+    }
+
     let class = activation.avm2().classes().textline;
     let movie = activation.caller_movie_or_root();
 
@@ -73,16 +127,125 @@ pub fn create_text_line<'gc>(
     let display_object: EditText =
         EditText::new_fte(activation.context, movie, 0.0, 0.0, width, 15.0);
 
-    display_object.set_text(text.as_wstr(), activation.context);
+    let is_device_font = runs.first().is_none_or(|run| run.is_device_font);
+    display_object.set_is_device_font(activation.context, is_device_font);
+    display_object.set_word_wrap(true, activation.context);
+
+    // Lay out a window of the remaining text, wrapped to the requested width.
+    // Once the window wraps onto a second line, its first line is the same as
+    // that of all of the remaining text, and is the contents of this TextLine.
+    // Otherwise the window is doubled, so that long paragraphs aren't laid out
+    // in full for every line.
+    let remaining_length = text.len() - begin_index;
+    let mut window = INITIAL_LAYOUT_WINDOW;
+    let (raw_text_length, window_length, line_runs) = loop {
+        let window_length = window.min(remaining_length);
+        let window_end = begin_index + window_length;
+
+        let mut run_start = 0;
+        let mut line_runs = Vec::new();
+        for run in &runs {
+            let run_end = run_start + run.text.len();
+            if run_end > begin_index && run_start < window_end {
+                let mut format = run.format.clone();
+                if tab_positions.is_some() {
+                    format.tab_stops = tab_positions.clone();
+                }
+                line_runs.push((
+                    run_start.max(begin_index) - begin_index,
+                    run_end.min(window_end) - begin_index,
+                    run,
+                    format,
+                ));
+            }
+            run_start = run_end;
+        }
+
+        if let Some((_, _, _, format)) = line_runs.first() {
+            display_object.set_new_text_format(format.clone(), activation.context);
+        }
+        display_object.set_text(&text[begin_index..window_end], activation.context);
+        for (start, end, _, format) in &line_runs {
+            display_object.set_text_format(*start, *end, format.clone(), activation.context);
+        }
+
+        if window_length == remaining_length || display_object.line_length(1).is_some() {
+            let raw_text_length = display_object
+                .line_length(0)
+                .unwrap_or(window_length)
+                .clamp(window_length.min(1), window_length);
+            break (raw_text_length, window_length, line_runs);
+        }
+
+        window *= 2;
+    };
+
+    let line_text = &text[begin_index..begin_index + raw_text_length];
+    let ends_with_break =
+        !line_text.is_empty() && matches!(line_text.at(line_text.len() - 1), 0x0A | 0x0D);
+
+    // The line break itself counts towards the raw text of this line,
+    // but isn't displayed.
+    let display_length = raw_text_length - ends_with_break as usize;
+    display_object.replace_text(
+        display_length,
+        window_length,
+        WStr::empty(),
+        activation.context,
+    );
+    display_object.set_word_wrap(false, activation.context);
+
+    if let Some(tab_stops) = &tab_stops {
+        align_tabs(
+            activation.context,
+            display_object,
+            &line_text[..display_length],
+            &line_runs,
+            tab_stops,
+        );
+    }
+
+    let unjustified_width = display_object.measure_text(activation.context).0;
+    let is_last_line = begin_index + raw_text_length >= text.len();
+    justify_line(
+        activation,
+        this,
+        display_object,
+        &line_text[..display_length],
+        width - unjustified_width.to_pixels(),
+        is_last_line,
+        ends_with_break,
+    )?;
+
+    let (mut ascent, descent) = display_object
+        .line_metrics(0)
+        .map_or((Twips::ZERO, Twips::ZERO), |metrics| {
+            (metrics.ascent, metrics.descent)
+        });
 
-    // FIXME: This needs to use `intrinsic_bounds` to measure the width
-    // of the provided text, and set the width of the EditText to that.
-    // Some games depend on this (e.g. Realm Grinder).
+    let mut has_graphic_element = false;
+    for (start, _, run, _) in &line_runs {
+        if let Some(graphic_height) = run.graphic_height {
+            if *start < display_length {
+                // Graphics sit on the baseline.
+                has_graphic_element = true;
+                ascent = ascent.max(Twips::from_pixels(graphic_height));
+            }
+        }
+    }
 
-    let content = content.as_object().unwrap();
-    let element_format = content.get_slot(element_slots::_ELEMENT_FORMAT).as_object();
+    if has_graphic_element {
+        // FIXME: The space for the graphic is reserved, but the graphic
+        // itself isn't displayed, as TextLine can't have children yet.
+        avm2_stub_method!(
+            activation,
+            "flash.text.engine.TextBlock",
+            "createTextLine",
+            "with GraphicElement"
+        );
+    }
 
-    apply_format(activation, display_object, text.as_wstr(), element_format)?;
+    display_object.fit_fte_line_bounds(activation.context.gc(), ascent);
 
     let instance = initialize_for_allocator(activation, display_object.into(), class)?;
     class.call_init(instance.into(), &[], activation)?;
@@ -91,7 +254,52 @@ pub fn create_text_line<'gc>(
 
     instance.set_slot(line_slots::_SPECIFIED_WIDTH, args.get_value(1), activation)?;
 
-    instance.set_slot(line_slots::_RAW_TEXT_LENGTH, text.len().into(), activation)?;
+    instance.set_slot(
+        line_slots::_TEXT_BLOCK_BEGIN_INDEX,
+        begin_index.into(),
+        activation,
+    )?;
+
+    instance.set_slot(
+        line_slots::_RAW_TEXT_LENGTH,
+        raw_text_length.into(),
+        activation,
+    )?;
+
+    instance.set_slot(line_slots::_ASCENT, ascent.to_pixels().into(), activation)?;
+
+    instance.set_slot(line_slots::_DESCENT, descent.to_pixels().into(), activation)?;
+
+    instance.set_slot(
+        line_slots::_UNJUSTIFIED_TEXT_WIDTH,
+        unjustified_width.to_pixels().into(),
+        activation,
+    )?;
+
+    instance.set_slot(
+        line_slots::_HAS_GRAPHIC_ELEMENT,
+        has_graphic_element.into(),
+        activation,
+    )?;
+
+    instance.set_slot(
+        line_slots::_HAS_TABS,
+        line_text.contains(b'\t').into(),
+        activation,
+    )?;
+
+    if let Some(previous_text_line) = previous_text_line {
+        previous_text_line.set_slot(line_slots::_NEXT_LINE, instance.into(), activation)?;
+        instance.set_slot(
+            line_slots::_PREVIOUS_LINE,
+            previous_text_line.into(),
+            activation,
+        )?;
+    } else {
+        this.set_slot(block_slots::_FIRST_LINE, instance.into(), activation)?;
+    }
+
+    this.set_slot(block_slots::_LAST_LINE, instance.into(), activation)?;
 
     this.set_slot(
         block_slots::_TEXT_LINE_CREATION_RESULT,
@@ -99,79 +307,414 @@ pub fn create_text_line<'gc>(
         activation,
     )?;
 
-    this.set_slot(block_slots::_FIRST_LINE, instance.into(), activation)?;
-
     Ok(instance.into())
 }
 
-fn apply_format<'gc>(
+/// Flatten a tree of content elements into runs of formatted text.
+///
+/// Elements without an `ElementFormat` inherit the one of their group.
+fn collect_runs<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    display_object: EditText<'gc>,
-    text: &WStr,
-    element_format: Option<Object<'gc>>,
+    element: Object<'gc>,
+    inherited_format: Option<Object<'gc>>,
+    runs: &mut Vec<ContentRun>,
 ) -> Result<(), Error<'gc>> {
-    if let Some(element_format) = element_format {
-        // TODO: Support more ElementFormat properties
-        let color = element_format
-            .get_slot(format_slots::_COLOR)
-            .coerce_to_u32(activation)?;
-        let size = element_format
-            .get_slot(format_slots::_FONT_SIZE)
+    let element_format = element
+        .get_slot(element_slots::_ELEMENT_FORMAT)
+        .as_object()
+        .or(inherited_format);
+
+    let group_element = activation
+        .avm2()
+        .classes()
+        .groupelement
+        .inner_class_definition();
+    let graphic_element = activation
+        .avm2()
+        .classes()
+        .graphicelement
+        .inner_class_definition();
+
+    if element.is_of_type(group_element) {
+        let elements: Vec<Value<'gc>> = element
+            .get_slot(group_slots::_ELEMENTS)
+            .as_object()
+            .and_then(|elements| {
+                elements
+                    .as_vector_storage()
+                    .map(|storage| storage.iter().collect())
+            })
+            .unwrap_or_default();
+
+        for child in elements {
+            if let Value::Object(child) = child {
+                collect_runs(activation, child, element_format, runs)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    let (mut format, is_device_font) = convert_element_format(activation, element_format)?;
+
+    if element.is_of_type(graphic_element) {
+        let width = element
+            .get_slot(graphic_slots::_ELEMENT_WIDTH)
+            .coerce_to_number(activation)?;
+        let height = element
+            .get_slot(graphic_slots::_ELEMENT_HEIGHT)
             .coerce_to_number(activation)?;
 
-        let (font, bold, italic, is_device_font) = if let Value::Object(font_description) =
-            element_format.get_slot(format_slots::_FONT_DESCRIPTION)
-        {
-            (
-                Some(
-                    font_description
-                        .get_slot(font_desc_slots::_FONT_NAME)
-                        .coerce_to_string(activation)?
-                        .as_wstr()
-                        .into(),
-                ),
-                Some(
-                    &font_description
-                        .get_slot(font_desc_slots::_FONT_WEIGHT)
-                        .coerce_to_string(activation)?
-                        == b"bold",
-                ),
-                Some(
-                    &font_description
-                        .get_slot(font_desc_slots::_FONT_POSTURE)
-                        .coerce_to_string(activation)?
-                        == b"italic",
-                ),
-                &font_description
-                    .get_slot(font_desc_slots::_FONT_LOOKUP)
-                    .coerce_to_string(activation)?
-                    == b"device",
-            )
+        // The graphic is stood in for by a tiny space, stretched out to the
+        // width of the element.
+        format.size = Some(1.0);
+        format.letter_spacing = Some(width);
+
+        runs.push(ContentRun {
+            text: WString::from_utf8(" "),
+            format,
+            is_device_font,
+            graphic_height: Some(height),
+        });
+    } else {
+        let text = element.call_method(element_methods::GET_TEXT, &[], activation)?;
+        if matches!(text, Value::Null) {
+            return Ok(());
+        }
+
+        runs.push(ContentRun {
+            text: text.coerce_to_string(activation)?.as_wstr().into(),
+            format,
+            is_device_font,
+            graphic_height: None,
+        });
+    }
+
+    Ok(())
+}
+
+/// Read the tab stops of a `TextBlock`.
+fn tab_stops<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    text_block: Object<'gc>,
+) -> Result<Option<Vec<TabStop>>, Error<'gc>> {
+    let Some(tab_stops) = text_block.get_slot(block_slots::_TAB_STOPS).as_object() else {
+        return Ok(None);
+    };
+
+    let tab_stops: Vec<Value<'gc>> = tab_stops
+        .as_vector_storage()
+        .map(|storage| storage.iter().collect())
+        .unwrap_or_default();
+
+    let mut stops = Vec::with_capacity(tab_stops.len());
+    for tab_stop in tab_stops {
+        let Value::Object(tab_stop) = tab_stop else {
+            continue;
+        };
+
+        let alignment = tab_stop
+            .get_slot(tab_stop_slots::ALIGNMENT)
+            .coerce_to_string(activation)?;
+        let alignment = if &alignment == b"center" {
+            TabAlignment::Center
+        } else if &alignment == b"end" {
+            TabAlignment::End
+        } else if &alignment == b"decimal" {
+            TabAlignment::Decimal
         } else {
-            (None, None, None, true)
+            TabAlignment::Start
         };
 
-        let format = TextFormat {
-            color: Some(swf::Color::from_rgb(color, 0xFF)),
-            size: Some(size),
-            font,
-            bold,
-            italic,
-            ..TextFormat::default()
+        stops.push(TabStop {
+            position: tab_stop
+                .get_slot(tab_stop_slots::POSITION)
+                .coerce_to_number(activation)?,
+            alignment,
+            decimal_alignment_token: tab_stop
+                .get_slot(tab_stop_slots::DECIMAL_ALIGNMENT_TOKEN)
+                .coerce_to_string(activation)?
+                .as_wstr()
+                .into(),
+        });
+    }
+
+    Ok(Some(stops))
+}
+
+/// Move the text after each tab of a laid out line to where the alignment
+/// of its tab stop puts it.
+///
+/// Text fields only know start-aligned tab stops, so the text following each
+/// tab is measured, and the line is laid out again with each stop replaced by
+/// the start-aligned stop that the text ends up at.
+fn align_tabs<'gc>(
+    context: &mut UpdateContext<'gc>,
+    display_object: EditText<'gc>,
+    text: &WStr,
+    line_runs: &[LineRun<'_>],
+    tab_stops: &[TabStop],
+) {
+    if tab_stops
+        .iter()
+        .all(|stop| stop.alignment == TabAlignment::Start)
+    {
+        return;
+    }
+
+    let tabs: Vec<usize> = (0..text.len())
+        .filter(|i| text.at(*i) == b'\t' as u16)
+        .collect();
+    let Some(first_tab) = tabs.first() else {
+        return;
+    };
+
+    let mut positions = Vec::with_capacity(tabs.len());
+    let mut cursor = measure_range(context, display_object, text, line_runs, 0..*first_tab);
+    for (i, tab) in tabs.iter().enumerate() {
+        // Like in text fields, a tab moves on to the next stop past the text
+        // before it, and does nothing if there is none.
+        let Some(stop) = tab_stops.iter().find(|stop| stop.position > cursor) else {
+            break;
+        };
+
+        let segment = tab + 1..tabs.get(i + 1).copied().unwrap_or(text.len());
+        let width = measure_range(context, display_object, text, line_runs, segment.clone());
+        let anchor = match stop.alignment {
+            TabAlignment::Start => 0.0,
+            TabAlignment::Center => width / 2.0,
+            TabAlignment::End => width,
+            TabAlignment::Decimal => {
+                let token = &stop.decimal_alignment_token;
+                match text[segment.clone()].find(&token[..]) {
+                    Some(index) if !token.is_empty() => measure_range(
+                        context,
+                        display_object,
+                        text,
+                        line_runs,
+                        segment.start..segment.start + index,
+                    ),
+                    _ => width,
+                }
+            }
         };
 
-        display_object.set_is_device_font(activation.context, is_device_font);
-        display_object.set_text_format(0, text.len(), format.clone(), activation.context);
-        display_object.set_new_text_format(format, activation.context);
+        // The text after a tab never overlaps the text before it. The stop
+        // must also stay past the cursor for the layout to pick it.
+        let start = (stop.position - anchor).max(cursor + Twips::new(1).to_pixels());
+        positions.push(start);
+        cursor = start + width;
+    }
+
+    lay_out_range(
+        context,
+        display_object,
+        text,
+        line_runs,
+        0..text.len(),
+        Some(&positions),
+    );
+}
+
+/// Lay out `range` of the text of a line on its own, returning its width.
+fn measure_range<'gc>(
+    context: &mut UpdateContext<'gc>,
+    display_object: EditText<'gc>,
+    text: &WStr,
+    line_runs: &[LineRun<'_>],
+    range: Range<usize>,
+) -> f64 {
+    if range.is_empty() {
+        return 0.0;
+    }
+
+    lay_out_range(context, display_object, text, line_runs, range, None);
+    display_object.measure_text(context).0.to_pixels()
+}
+
+/// Replace the contents of `display_object` with `range` of the text of a
+/// line, formatted like the runs of the line.
+///
+/// The tab stops of every run are replaced with `tab_stops`, if given.
+fn lay_out_range<'gc>(
+    context: &mut UpdateContext<'gc>,
+    display_object: EditText<'gc>,
+    text: &WStr,
+    line_runs: &[LineRun<'_>],
+    range: Range<usize>,
+    tab_stops: Option<&[f64]>,
+) {
+    let mut formats = Vec::with_capacity(line_runs.len());
+    for (start, end, _, format) in line_runs {
+        let start = (*start).clamp(range.start, range.end);
+        let end = (*end).clamp(range.start, range.end);
+        if start < end {
+            let mut format = format.clone();
+            if let Some(tab_stops) = tab_stops {
+                format.tab_stops = Some(tab_stops.to_vec());
+            }
+            formats.push((start - range.start, end - range.start, format));
+        }
+    }
+
+    if let Some((_, _, format)) = formats.first() {
+        display_object.set_new_text_format(format.clone(), context);
+    }
+    display_object.set_text(&text[range], context);
+    for (start, end, format) in formats {
+        display_object.set_text_format(start, end, format, context);
+    }
+}
+
+/// Stretch a laid out line to fill its width, according to the
+/// `TextJustifier` of its `TextBlock`.
+///
+/// `SpaceJustifier` distributes the extra space between words, while
+/// `EastAsianJustifier` (and `SpaceJustifier` with `letterSpacing` set)
+/// distributes it between all letters.
+fn justify_line<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    text_block: Object<'gc>,
+    display_object: EditText<'gc>,
+    text: &WStr,
+    extra_width: f64,
+    is_last_line: bool,
+    ends_with_break: bool,
+) -> Result<(), Error<'gc>> {
+    let Some(justifier) = text_block
+        .get_slot(block_slots::_TEXT_JUSTIFIER)
+        .as_object()
+    else {
+        return Ok(());
+    };
+
+    let line_justification = justifier
+        .get_slot(justifier_slots::_LINE_JUSTIFICATION)
+        .coerce_to_string(activation)?;
+
+    let should_justify = if &line_justification == b"allIncludingLast" {
+        true
+    } else if &line_justification == b"allButLast" {
+        !is_last_line
+    } else if &line_justification == b"allButMandatoryBreak" {
+        !is_last_line && !ends_with_break
     } else {
-        display_object.set_is_device_font(activation.context, true);
+        false
+    };
+
+    if !should_justify || extra_width <= 0.0 {
+        return Ok(());
     }
 
-    display_object.set_word_wrap(true, activation.context);
+    let space_justifier = activation
+        .avm2()
+        .classes()
+        .spacejustifier
+        .inner_class_definition();
+    let between_letters = !justifier.is_of_type(space_justifier)
+        || justifier
+            .get_slot(space_justifier_slots::_LETTER_SPACING)
+            .coerce_to_boolean();
+
+    // Trailing spaces hang past the end of the line, and aren't stretched.
+    let length = text.len() - text.iter().rev().take_while(|c| *c == b' ' as u16).count();
 
-    let measured_text = display_object.measure_text(activation.context);
+    let gaps: Vec<usize> = if between_letters {
+        (0..length.saturating_sub(1)).collect()
+    } else {
+        (0..length).filter(|i| text.at(*i) == b' ' as u16).collect()
+    };
 
-    display_object.set_height(activation.context, measured_text.1.to_pixels());
+    if gaps.is_empty() {
+        return Ok(());
+    }
+
+    let spacing = extra_width / gaps.len() as f64;
+    for i in gaps {
+        let letter_spacing = display_object
+            .text_format(i, i + 1)
+            .letter_spacing
+            .unwrap_or(0.0);
+        let format = TextFormat {
+            letter_spacing: Some(letter_spacing + spacing),
+            ..TextFormat::default()
+        };
+        display_object.set_text_format(i, i + 1, format, activation.context);
+    }
 
     Ok(())
 }
+
+/// Convert an `ElementFormat` into the equivalent `TextFormat`, also
+/// returning whether it uses device fonts.
+fn convert_element_format<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    element_format: Option<Object<'gc>>,
+) -> Result<(TextFormat, bool), Error<'gc>> {
+    let Some(element_format) = element_format else {
+        return Ok((TextFormat::default(), true));
+    };
+
+    // TODO: Support more ElementFormat properties
+    let color = element_format
+        .get_slot(format_slots::_COLOR)
+        .coerce_to_u32(activation)?;
+    let size = element_format
+        .get_slot(format_slots::_FONT_SIZE)
+        .coerce_to_number(activation)?;
+    let tracking = element_format
+        .get_slot(format_slots::_TRACKING_LEFT)
+        .coerce_to_number(activation)?
+        + element_format
+            .get_slot(format_slots::_TRACKING_RIGHT)
+            .coerce_to_number(activation)?;
+    let kerning = &element_format
+        .get_slot(format_slots::_KERNING)
+        .coerce_to_string(activation)?
+        != b"off";
+
+    let (font, bold, italic, is_device_font) = if let Value::Object(font_description) =
+        element_format.get_slot(format_slots::_FONT_DESCRIPTION)
+    {
+        (
+            Some(
+                font_description
+                    .get_slot(font_desc_slots::_FONT_NAME)
+                    .coerce_to_string(activation)?
+                    .as_wstr()
+                    .into(),
+            ),
+            Some(
+                &font_description
+                    .get_slot(font_desc_slots::_FONT_WEIGHT)
+                    .coerce_to_string(activation)?
+                    == b"bold",
+            ),
+            Some(
+                &font_description
+                    .get_slot(font_desc_slots::_FONT_POSTURE)
+                    .coerce_to_string(activation)?
+                    == b"italic",
+            ),
+            &font_description
+                .get_slot(font_desc_slots::_FONT_LOOKUP)
+                .coerce_to_string(activation)?
+                == b"device",
+        )
+    } else {
+        (None, None, None, true)
+    };
+
+    let format = TextFormat {
+        color: Some(swf::Color::from_rgb(color, 0xFF)),
+        size: Some(size),
+        font,
+        bold,
+        italic,
+        letter_spacing: Some(tracking),
+        kerning: Some(kerning),
+        ..TextFormat::default()
+    };
+
+    Ok((format, is_device_font))
+}
@@ -2,7 +2,7 @@ use std::rc::Rc;
 
 use crate::avm2::activation::Activation;
 use crate::avm2::bytearray::{Endian, ObjectEncoding};
-use crate::avm2::error::make_error_2008;
+use crate::avm2::error::{make_error_2006, make_error_2008};
 use crate::avm2::object::{Object, TObject};
 use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
@@ -865,3 +865,110 @@ pub fn set_object_encoding<'gc>(
 
     Ok(Value::Undefined)
 }
+
+pub fn get_shareable<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    if let Some(bytearray) = this.as_bytearray_object() {
+        return Ok(bytearray.shareable().into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_shareable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    if let Some(bytearray) = this.as_bytearray_object() {
+        let shareable = args.get_bool(0);
+        bytearray.set_shareable(activation.gc(), shareable);
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Replaces the int at `byteIndex` with `newValue` if it is `expectedValue`,
+/// returning the int that was there before.
+///
+/// Workers are run one at a time, so nothing can happen between the
+/// comparison and the swap.
+pub fn atomic_compare_and_swap_int_at<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let byte_index = args.get_i32(activation, 0)?;
+    let expected_value = args.get_i32(activation, 1)?;
+    let new_value = args.get_i32(activation, 2)?;
+
+    if let Some(mut bytearray) = this.as_bytearray_mut() {
+        if byte_index < 0 || byte_index % 4 != 0 {
+            return Err(make_error_2006(activation));
+        }
+
+        let index = byte_index as usize;
+        let endian = bytearray.endian();
+        let current = match bytearray.read_at(4, index) {
+            Ok(bytes) => {
+                let bytes = <[u8; 4]>::try_from(bytes).unwrap();
+                match endian {
+                    Endian::Big => i32::from_be_bytes(bytes),
+                    Endian::Little => i32::from_le_bytes(bytes),
+                }
+            }
+            Err(_) => return Err(make_error_2006(activation)),
+        };
+
+        if current == expected_value {
+            let bytes = match endian {
+                Endian::Big => new_value.to_be_bytes(),
+                Endian::Little => new_value.to_le_bytes(),
+            };
+            bytearray
+                .write_at_nongrowing(&bytes, index)
+                .map_err(|e| e.to_avm(activation))?;
+        }
+
+        return Ok(current.into());
+    }
+
+    Ok(Value::Undefined)
+}
+
+/// Changes the length of the ByteArray to `newLength` if it is
+/// `expectedLength`, returning the length it had before.
+pub fn atomic_compare_and_swap_length<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Value<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let this = this.as_object().unwrap();
+
+    let expected_length = args.get_i32(activation, 0)?;
+    let new_length = args.get_i32(activation, 1)?;
+
+    if let Some(mut bytearray) = this.as_bytearray_mut() {
+        if new_length < 0 {
+            return Err(make_error_2006(activation));
+        }
+
+        let current = bytearray.len() as i32;
+        if current == expected_length {
+            bytearray.set_length(new_length as usize);
+        }
+
+        return Ok(current.into());
+    }
+
+    Ok(Value::Undefined)
+}
@@ -50,6 +50,7 @@ pub fn start<'gc>(
             TimerCallback::Avm2Callback {
                 closure: on_update,
                 params: vec![],
+                worker: activation.context.workers.current(),
             },
             delay,
             false,
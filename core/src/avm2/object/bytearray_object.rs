@@ -7,8 +7,10 @@ use crate::avm2::Error;
 use crate::avm2::Multiname;
 use crate::character::Character;
 use core::fmt;
-use gc_arena::{Collect, Gc, GcWeak};
-use std::cell::{Ref, RefCell, RefMut};
+use gc_arena::barrier::unlock;
+use gc_arena::lock::Lock;
+use gc_arena::{Collect, Gc, GcWeak, Mutation};
+use std::cell::{Cell, Ref, RefCell, RefMut};
 
 /// A class instance allocator that allocates ByteArray objects.
 pub fn byte_array_allocator<'gc>(
@@ -44,7 +46,8 @@ pub fn byte_array_allocator<'gc>(
         activation.gc(),
         ByteArrayObjectData {
             base,
-            storage: RefCell::new(storage),
+            storage: Lock::new(Gc::new(activation.gc(), RefCell::new(storage))),
+            shareable: Cell::new(false),
         },
     ))
     .into())
@@ -73,7 +76,15 @@ pub struct ByteArrayObjectData<'gc> {
     /// Base script object
     base: ScriptObjectData<'gc>,
 
-    storage: RefCell<ByteArrayStorage>,
+    /// The bytes of this ByteArray.
+    ///
+    /// This is shared with ByteArrays in other workers when this ByteArray is
+    /// shareable and has been sent to them.
+    storage: Lock<Gc<'gc, RefCell<ByteArrayStorage>>>,
+
+    /// Whether this ByteArray shares its bytes when sent to another worker,
+    /// rather than being copied.
+    shareable: Cell<bool>,
 }
 
 const _: () = assert!(std::mem::offset_of!(ByteArrayObjectData, base) == 0);
@@ -93,7 +104,31 @@ impl<'gc> ByteArrayObject<'gc> {
             activation.gc(),
             ByteArrayObjectData {
                 base,
-                storage: RefCell::new(bytes),
+                storage: Lock::new(Gc::new(activation.gc(), RefCell::new(bytes))),
+                shareable: Cell::new(false),
+            },
+        ));
+
+        class.call_init(instance.into(), &[], activation)?;
+
+        Ok(instance)
+    }
+
+    /// Create a shareable ByteArray using the bytes of a ByteArray that was
+    /// shared from another worker.
+    pub fn from_shared_storage(
+        activation: &mut Activation<'_, 'gc>,
+        storage: Gc<'gc, RefCell<ByteArrayStorage>>,
+    ) -> Result<ByteArrayObject<'gc>, Error<'gc>> {
+        let class = activation.avm2().classes().bytearray;
+        let base = ScriptObjectData::new(class);
+
+        let instance = ByteArrayObject(Gc::new(
+            activation.gc(),
+            ByteArrayObjectData {
+                base,
+                storage: Lock::new(storage),
+                shareable: Cell::new(true),
             },
         ));
 
@@ -103,7 +138,34 @@ impl<'gc> ByteArrayObject<'gc> {
     }
 
     pub fn storage(&self) -> Ref<ByteArrayStorage> {
-        self.0.storage.borrow()
+        Gc::as_ref(self.0.storage.get()).borrow()
+    }
+
+    pub fn storage_mut(&self) -> RefMut<ByteArrayStorage> {
+        Gc::as_ref(self.0.storage.get()).borrow_mut()
+    }
+
+    /// The bytes of this ByteArray, to be shared with another worker.
+    pub fn shared_storage(&self) -> Gc<'gc, RefCell<ByteArrayStorage>> {
+        self.0.storage.get()
+    }
+
+    pub fn shareable(&self) -> bool {
+        self.0.shareable.get()
+    }
+
+    /// Set whether this ByteArray is shareable.
+    ///
+    /// A ByteArray that stops being shareable gets its own copy of its bytes,
+    /// so that it no longer sees changes made by other workers.
+    pub fn set_shareable(&self, mc: &Mutation<'gc>, shareable: bool) {
+        if self.0.shareable.get() && !shareable {
+            let bytes = self.storage().clone();
+            unlock!(Gc::write(mc, self.0), ByteArrayObjectData, storage)
+                .set(Gc::new(mc, RefCell::new(bytes)));
+        }
+
+        self.0.shareable.set(shareable);
     }
 }
 
@@ -139,9 +201,7 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
     fn get_index_property(self, index: usize) -> Option<Value<'gc>> {
         // ByteArrays never forward to base even for out-of-bounds access.
         Some(
-            self.0
-                .storage
-                .borrow()
+            self.storage()
                 .get(index)
                 .map_or(Value::Undefined, |val| Value::Integer(val as i32)),
         )
@@ -156,9 +216,7 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
         if name.contains_public_namespace() {
             if let Some(name) = name.local_name() {
                 if let Ok(index) = name.parse::<usize>() {
-                    self.0
-                        .storage
-                        .borrow_mut()
+                    self.storage_mut()
                         .set(index, value.coerce_to_u32(activation)? as u8);
 
                     return Ok(());
@@ -178,9 +236,7 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
         if name.contains_public_namespace() {
             if let Some(name) = name.local_name() {
                 if let Ok(index) = name.parse::<usize>() {
-                    self.0
-                        .storage
-                        .borrow_mut()
+                    self.storage_mut()
                         .set(index, value.coerce_to_u32(activation)? as u8);
 
                     return Ok(());
@@ -199,7 +255,7 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
         if name.contains_public_namespace() {
             if let Some(name) = name.local_name() {
                 if let Ok(index) = name.parse::<usize>() {
-                    self.0.storage.borrow_mut().delete(index);
+                    self.storage_mut().delete(index);
                     return Ok(true);
                 }
             }
@@ -212,7 +268,7 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
         if name.contains_public_namespace() {
             if let Some(name) = name.local_name() {
                 if let Ok(index) = name.parse::<usize>() {
-                    return self.0.storage.borrow().get(index).is_some();
+                    return self.storage().get(index).is_some();
                 }
             }
         }
@@ -221,11 +277,11 @@ impl<'gc> TObject<'gc> for ByteArrayObject<'gc> {
     }
 
     fn as_bytearray(&self) -> Option<Ref<ByteArrayStorage>> {
-        Some(self.0.storage.borrow())
+        Some(self.storage())
     }
 
     fn as_bytearray_mut(&self) -> Option<RefMut<ByteArrayStorage>> {
-        Some(self.0.storage.borrow_mut())
+        Some(self.storage_mut())
     }
 
     fn as_bytearray_object(&self) -> Option<ByteArrayObject<'gc>> {
@@ -2,13 +2,18 @@
 
 use crate::avm2::activation::Activation;
 use crate::avm2::object::script_object::ScriptObjectData;
-use crate::avm2::object::{ClassObject, Object, ObjectPtr, TObject};
-use crate::avm2::Error;
+use crate::avm2::object::{ClassObject, EventObject, Object, ObjectPtr, TObject};
+use crate::avm2::{Avm2, Error, Value};
+use crate::context::UpdateContext;
 use crate::net_connection::NetConnectionHandle;
+use crate::string::AvmString;
+use flash_lso::types::Value as AmfValue;
 use gc_arena::{Collect, Gc, GcWeak};
+use ruffle_macros::istr;
 use std::cell::Cell;
 use std::fmt;
 use std::fmt::Debug;
+use std::rc::Rc;
 
 pub fn net_connection_allocator<'gc>(
     class: ClassObject<'gc>,
@@ -67,7 +72,7 @@ impl<'gc> TObject<'gc> for NetConnectionObject<'gc> {
     }
 }
 
-impl NetConnectionObject<'_> {
+impl<'gc> NetConnectionObject<'gc> {
     pub fn handle(&self) -> Option<NetConnectionHandle> {
         self.0.handle.get()
     }
@@ -75,6 +80,45 @@ impl NetConnectionObject<'_> {
     pub fn set_handle(&self, handle: Option<NetConnectionHandle>) -> Option<NetConnectionHandle> {
         self.0.handle.replace(handle)
     }
+
+    /// Dispatch a `netStatus` event whose info object was sent by a server.
+    pub fn send_status(
+        &self,
+        context: &mut UpdateContext<'gc>,
+        info: &AmfValue,
+    ) -> Result<(), Error<'gc>> {
+        let mut activation = Activation::from_nothing(context);
+        let info = crate::avm2::amf::deserialize_value(&mut activation, info)?;
+        let net_status_cls = activation.avm2().classes().netstatusevent;
+        let event = EventObject::from_class_and_args(
+            &mut activation,
+            net_status_cls,
+            &[istr!("netStatus").into(), false.into(), false.into(), info],
+        );
+        Avm2::dispatch_event(activation.context, event, (*self).into());
+
+        Ok(())
+    }
+
+    /// Call a method of `NetConnection.client` on behalf of a server.
+    pub fn call_client(
+        &self,
+        context: &mut UpdateContext<'gc>,
+        method: &str,
+        arguments: &[Rc<AmfValue>],
+    ) -> Result<(), Error<'gc>> {
+        let mut activation = Activation::from_nothing(context);
+        let arguments = arguments
+            .iter()
+            .map(|argument| crate::avm2::amf::deserialize_value(&mut activation, argument))
+            .collect::<Result<Vec<_>, _>>()?;
+        let client = Value::from(Object::from(*self))
+            .get_public_property(istr!("client"), &mut activation)?;
+        let method = AvmString::new_utf8(activation.gc(), method);
+        client.call_public_property(method, &arguments, &mut activation)?;
+
+        Ok(())
+    }
 }
 
 impl Debug for NetConnectionObject<'_> {
@@ -7,7 +7,11 @@ use crate::avm2::object::{ClassObject, Object, ObjectPtr, TObject};
 use crate::avm2::Avm2;
 use crate::avm2::Error;
 use crate::avm2::EventObject;
-use crate::backend::audio::{AudioManager, SoundHandle};
+use crate::backend::audio::mp3_header::{id3v2_length, Mp3FrameHeader};
+use crate::backend::audio::{
+    AudioManager, DecodeError, SoundHandle, SoundStreamInfo, SoundStreamWrapping,
+};
+use crate::buffer::{Buffer, Substream};
 use crate::context::UpdateContext;
 use crate::display_object::SoundTransform;
 use crate::string::AvmString;
@@ -20,7 +24,7 @@ use gc_arena::{
 use id3::{Tag, TagLike};
 use std::cell::Cell;
 use std::io::Cursor;
-use swf::SoundInfo;
+use swf::{AudioCompression, SoundFormat, SoundInfo};
 
 use super::SoundChannelObject;
 
@@ -36,10 +40,12 @@ pub fn sound_allocator<'gc>(
         SoundObjectData {
             base,
             loading_state: Cell::new(SoundLoadingState::New),
+            extract_position: Cell::new(0),
             sound_data: RefLock::new(SoundData::NotLoaded {
                 queued_plays: Vec::new(),
             }),
             id3: Lock::new(None),
+            url: Lock::new(None),
         },
     ))
     .into())
@@ -71,11 +77,18 @@ pub struct SoundObjectData<'gc> {
     /// Loading state of the sound.
     loading_state: Cell<SoundLoadingState>,
 
+    /// The sample frame that `Sound.extract` continues from when no start
+    /// position is given.
+    extract_position: Cell<u32>,
+
     /// The sound this object holds.
     sound_data: RefLock<SoundData<'gc>>,
 
     /// ID3Info Object
     id3: Lock<Option<Object<'gc>>>,
+
+    /// The URL this sound was loaded from, if any.
+    url: Lock<Option<AvmString<'gc>>>,
 }
 
 const _: () = assert!(std::mem::offset_of!(SoundObjectData, base) == 0);
@@ -88,6 +101,14 @@ pub enum SoundData<'gc> {
     NotLoaded {
         queued_plays: Vec<QueuedPlay<'gc>>,
     },
+    /// The sound is still being downloaded, but may already be played.
+    Streaming {
+        #[collect(require_static)]
+        stream: SoundStream,
+
+        /// Plays waiting for enough data to be buffered.
+        queued_plays: Vec<QueuedPlay<'gc>>,
+    },
     Loaded {
         #[collect(require_static)]
         sound: SoundHandle,
@@ -105,22 +126,266 @@ pub struct QueuedPlay<'gc> {
     pub position: f64,
 }
 
+/// An MP3 that is still being downloaded.
+///
+/// Playback of the downloaded data goes through `Substream`s that are
+/// extended as more data arrives, so that long sounds can start playing long
+/// before their download completes.
+pub struct SoundStream {
+    /// All data downloaded so far.
+    buffer: Buffer,
+
+    /// The expected length of the download, if known.
+    bytes_total: Option<usize>,
+
+    /// The number of milliseconds of audio that must be downloaded before
+    /// playback begins.
+    buffer_time: f64,
+
+    /// The offset of the first MP3 frame in `buffer` and its header, once
+    /// enough data has been downloaded to find it.
+    first_frame: Option<(usize, Mp3FrameHeader)>,
+
+    /// Where the search for the first MP3 frame continues when more data
+    /// arrives. Everything before it was already ruled out.
+    frame_search_start: usize,
+
+    /// The end of the last complete MP3 frame in `buffer`.
+    ///
+    /// Playing substreams only receive complete frames, so that a decoder
+    /// that catches up with the download waits at a frame boundary.
+    frames_end: usize,
+
+    /// The substreams of every instance of this sound that has started
+    /// playing, all of which receive newly downloaded data.
+    ///
+    /// These are marked as loading until the stream is closed, so that
+    /// instances that run out of data wait for more instead of ending.
+    substreams: Vec<Substream>,
+
+    /// Whether the ID3 tag at the start of the file has been read.
+    id3_read: bool,
+}
+
+impl SoundStream {
+    fn new(buffer_time: f64) -> Self {
+        Self {
+            buffer: Buffer::new(),
+            bytes_total: None,
+            buffer_time,
+            first_frame: None,
+            frame_search_start: 0,
+            frames_end: 0,
+            substreams: Vec::new(),
+            id3_read: false,
+        }
+    }
+
+    /// Append newly downloaded data to the stream.
+    fn append(&mut self, data: &mut Vec<u8>) {
+        self.buffer.append(data);
+
+        if self.first_frame.is_none() {
+            let slice = self.buffer.to_full_slice();
+            let data = slice.data();
+            if let Some(audio_start) = id3v2_length(&data).filter(|start| *start < data.len()) {
+                let search_start = audio_start.max(self.frame_search_start);
+                self.first_frame = Mp3FrameHeader::find(&data[search_start..])
+                    .map(|(offset, header)| (search_start + offset, header));
+                // A candidate within a frame of the end can still be
+                // confirmed once the header of the frame after it arrives.
+                self.frame_search_start = data
+                    .len()
+                    .saturating_sub(Mp3FrameHeader::MAX_FRAME_LENGTH + 3);
+            }
+            drop(data);
+
+            let Some((first_frame, _)) = self.first_frame else {
+                return;
+            };
+            self.frames_end = first_frame;
+        }
+
+        self.append_complete_frames();
+    }
+
+    /// Advance `frames_end` past every MP3 frame that has been downloaded
+    /// completely, and pass those frames on to the playing substreams.
+    fn append_complete_frames(&mut self) {
+        let start = self.frames_end;
+        let slice = self.buffer.to_full_slice();
+        let data = slice.data();
+        loop {
+            let rest = &data[self.frames_end..];
+            // Anything that isn't a frame is passed on with the frame after
+            // it, the decoder will skip over it.
+            let Some((offset, header)) = Mp3FrameHeader::parse(rest)
+                .map(|header| (0, header))
+                .or_else(|| Mp3FrameHeader::find(rest))
+            else {
+                break;
+            };
+
+            let end = self.frames_end + offset + header.frame_length;
+            if end > data.len() {
+                break;
+            }
+            self.frames_end = end;
+        }
+        drop(data);
+
+        if let Some(chunk) = self.buffer.get(start..self.frames_end) {
+            if !chunk.is_empty() {
+                for substream in &mut self.substreams {
+                    substream
+                        .append(chunk.clone())
+                        .expect("substream of the same buffer");
+                }
+            }
+        }
+    }
+
+    /// Pass whatever is left of the download on to the playing substreams,
+    /// and let them end once they have played it.
+    fn close(&mut self) {
+        let rest = self.buffer.get(self.frames_end..);
+        self.frames_end = self.buffer.len();
+        for mut substream in self.substreams.drain(..) {
+            if let Some(rest) = rest.clone().filter(|rest| !rest.is_empty()) {
+                substream
+                    .append(rest)
+                    .expect("substream of the same buffer");
+            }
+            substream.set_loading(false);
+        }
+    }
+
+    /// The duration of the audio downloaded so far, in milliseconds.
+    fn buffered_duration(&self) -> f64 {
+        match self.first_frame {
+            Some((offset, header)) => header.duration_of(self.buffer.len() - offset),
+            None => 0.0,
+        }
+    }
+
+    fn is_buffering(&self) -> bool {
+        self.buffered_duration() < self.buffer_time
+    }
+
+    /// Take the ID3 tag at the start of the file, once all of it is
+    /// downloaded.
+    fn take_id3_tag(&mut self) -> Option<Vec<u8>> {
+        let (audio_start, _) = self.first_frame?;
+        if self.id3_read || audio_start == 0 {
+            return None;
+        }
+
+        self.id3_read = true;
+        Some(self.buffer.get(..audio_start)?.data().to_vec())
+    }
+
+    /// Create a substream that plays this sound starting at `position`
+    /// milliseconds.
+    ///
+    /// Returns `None` if that position hasn't been downloaded yet.
+    fn substream_at(&mut self, position: f64) -> Option<(Substream, SoundStreamInfo)> {
+        let (first_frame, header) = self.first_frame?;
+        let target = first_frame + header.length_of(position.max(0.0));
+        let (offset, _) = Mp3FrameHeader::find(&self.buffer.get(target..)?.data())?;
+        let frames = self
+            .buffer
+            .get(target + offset..self.frames_end)
+            .filter(|frames| !frames.is_empty())?;
+
+        let mut substream = Substream::new(self.buffer.clone());
+        substream
+            .append(frames)
+            .expect("substream of the same buffer");
+        substream.set_loading(true);
+        self.substreams.push(substream.clone());
+
+        let stream_info = SoundStreamInfo {
+            wrapping: SoundStreamWrapping::Unwrapped,
+            stream_format: SoundFormat {
+                compression: AudioCompression::Mp3,
+                sample_rate: header.sample_rate,
+                is_stereo: header.is_stereo,
+                is_16_bit: true,
+            },
+            num_samples_per_block: header.samples_per_frame,
+            latency_seek: 0,
+        };
+
+        Some((substream, stream_info))
+    }
+}
+
+impl Drop for SoundStream {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum SoundLoadingState {
     New,
     Loading,
     Loaded,
+    /// The download failed. Whatever was downloaded so far stays playable,
+    /// but the sound won't load any further.
+    Failed,
 }
 
 impl<'gc> SoundObject<'gc> {
     pub fn sound_handle(self) -> Option<SoundHandle> {
         let sound_data = self.0.sound_data.borrow();
         match &*sound_data {
-            SoundData::NotLoaded { .. } => None,
+            SoundData::NotLoaded { .. } | SoundData::Streaming { .. } => None,
             SoundData::Loaded { sound } => Some(*sound),
         }
     }
 
+    /// The number of bytes downloaded so far, if this sound is streaming.
+    pub fn bytes_loaded(self) -> Option<usize> {
+        match &*self.0.sound_data.borrow() {
+            SoundData::Streaming { stream, .. } => Some(stream.buffer.len()),
+            _ => None,
+        }
+    }
+
+    /// The expected total length of the download, if this sound is
+    /// streaming.
+    pub fn bytes_total(self) -> Option<usize> {
+        match &*self.0.sound_data.borrow() {
+            SoundData::Streaming { stream, .. } => {
+                let len = stream.buffer.len();
+                Some(stream.bytes_total.unwrap_or(len).max(len))
+            }
+            _ => None,
+        }
+    }
+
+    /// The duration of the audio downloaded so far in milliseconds, if this
+    /// sound is streaming.
+    pub fn buffered_duration(self) -> Option<f64> {
+        match &*self.0.sound_data.borrow() {
+            SoundData::Streaming { stream, .. } => Some(stream.buffered_duration()),
+            _ => None,
+        }
+    }
+
+    /// Whether this sound is waiting for more data before it can play.
+    pub fn is_buffering(self) -> bool {
+        match &*self.0.sound_data.borrow() {
+            SoundData::Streaming { stream, .. } => stream.is_buffering(),
+            _ => false,
+        }
+    }
+
+    pub fn url(self) -> Option<AvmString<'gc>> {
+        self.0.url.get()
+    }
+
     pub fn loading_state(self) -> SoundLoadingState {
         self.0.loading_state.get()
     }
@@ -129,6 +394,14 @@ impl<'gc> SoundObject<'gc> {
         self.0.loading_state.set(value);
     }
 
+    pub fn extract_position(self) -> u32 {
+        self.0.extract_position.get()
+    }
+
+    pub fn set_extract_position(self, value: u32) {
+        self.0.extract_position.set(value);
+    }
+
     /// Returns `true` if a `SoundChannel` should be returned back to the AVM2 caller.
     pub fn play(
         self,
@@ -153,10 +426,139 @@ impl<'gc> SoundObject<'gc> {
                 // We don't know the length yet, so return the `SoundChannel`
                 Ok(true)
             }
+            SoundData::Streaming {
+                stream,
+                queued_plays,
+            } => {
+                if stream.is_buffering() {
+                    if queued_plays.len() >= AudioManager::MAX_SOUNDS {
+                        tracing::warn!("Sound.play: too many buffering sounds queued");
+                        return Ok(false);
+                    }
+
+                    queued_plays.push(queued);
+                    Ok(true)
+                } else {
+                    Ok(play_streaming(queued, stream, activation.context))
+                }
+            }
             SoundData::Loaded { sound } => play_queued(queued, *sound, activation),
         }
     }
 
+    /// Begin downloading this sound from `url`.
+    ///
+    /// Any plays requested until the download completes will stream the sound
+    /// once `buffer_time` milliseconds of it have been downloaded.
+    pub fn start_streaming(self, mc: &Mutation<'gc>, url: AvmString<'gc>, buffer_time: f64) {
+        let write = Gc::write(mc, self.0);
+        unlock!(write, SoundObjectData, url).set(Some(url));
+
+        let mut sound_data = unlock!(write, SoundObjectData, sound_data).borrow_mut();
+        if let SoundData::NotLoaded { queued_plays } = &mut *sound_data {
+            *sound_data = SoundData::Streaming {
+                stream: SoundStream::new(buffer_time),
+                queued_plays: std::mem::take(queued_plays),
+            };
+        }
+
+        self.set_loading_state(SoundLoadingState::Loading);
+    }
+
+    /// Append downloaded data to a streaming sound, starting any plays that
+    /// were waiting for it and firing `id3` and `progress` events.
+    pub fn append_stream_data(
+        self,
+        context: &mut UpdateContext<'gc>,
+        data: &mut Vec<u8>,
+        bytes_total: Option<usize>,
+    ) {
+        let mut sound_data =
+            unlock!(Gc::write(context.gc(), self.0), SoundObjectData, sound_data).borrow_mut();
+        let SoundData::Streaming {
+            stream,
+            queued_plays,
+        } = &mut *sound_data
+        else {
+            return;
+        };
+
+        stream.bytes_total = bytes_total;
+        stream.append(data);
+
+        if !stream.is_buffering() {
+            for queued in std::mem::take(queued_plays) {
+                play_streaming(queued, stream, context);
+            }
+        }
+
+        let id3_tag = stream.take_id3_tag();
+        drop(sound_data);
+
+        let mut activation = Activation::from_nothing(context);
+        if let Some(id3_tag) = id3_tag {
+            self.read_and_call_id3_event(&mut activation, &id3_tag);
+        }
+
+        let progress_evt = EventObject::progress_event(
+            &mut activation,
+            "progress",
+            self.bytes_loaded().unwrap_or_default(),
+            self.bytes_total().unwrap_or_default(),
+        );
+        Avm2::dispatch_event(activation.context, progress_evt, self.into());
+    }
+
+    /// Finish streaming this sound, either because it has been downloaded
+    /// completely or because the download was closed.
+    ///
+    /// Sounds that are already playing continue to play from their streams.
+    pub fn finish_streaming(self, context: &mut UpdateContext<'gc>) -> Result<(), DecodeError> {
+        let mut sound_data =
+            unlock!(Gc::write(context.gc(), self.0), SoundObjectData, sound_data).borrow_mut();
+        let SoundData::Streaming {
+            stream,
+            queued_plays,
+        } = &mut *sound_data
+        else {
+            return Ok(());
+        };
+
+        let data = stream.buffer.to_full_slice().data().to_vec();
+        let id3_read = stream.id3_read;
+        let queued_plays = std::mem::take(queued_plays);
+        let sound = context.audio.register_mp3(&data)?;
+        *sound_data = SoundData::Loaded { sound };
+        drop(sound_data);
+        self.set_loading_state(SoundLoadingState::Loaded);
+
+        let mut activation = Activation::from_nothing(context);
+        for queued in queued_plays {
+            if let Err(e) = play_queued(queued, sound, &mut activation) {
+                tracing::error!("Encountered AVM2 error when playing sound: {}", e);
+            }
+        }
+
+        if !id3_read {
+            self.read_and_call_id3_event(&mut activation, &data);
+        }
+
+        Ok(())
+    }
+
+    /// Stop passing downloaded data on to this sound, because its download
+    /// failed or was closed without producing a valid sound.
+    ///
+    /// Sounds that are already playing end once they have played whatever
+    /// was downloaded so far.
+    pub fn close_stream(self, mc: &Mutation<'gc>) {
+        let mut sound_data =
+            unlock!(Gc::write(mc, self.0), SoundObjectData, sound_data).borrow_mut();
+        if let SoundData::Streaming { stream, .. } = &mut *sound_data {
+            stream.close();
+        }
+    }
+
     pub fn set_sound(
         self,
         context: &mut UpdateContext<'gc>,
@@ -166,7 +568,7 @@ impl<'gc> SoundObject<'gc> {
             unlock!(Gc::write(context.gc(), self.0), SoundObjectData, sound_data).borrow_mut();
         let mut activation = Activation::from_nothing(context);
         match &mut *sound_data {
-            SoundData::NotLoaded { queued_plays } => {
+            SoundData::NotLoaded { queued_plays } | SoundData::Streaming { queued_plays, .. } => {
                 for queued in std::mem::take(queued_plays) {
                     play_queued(queued, sound, &mut activation)?;
                 }
@@ -305,6 +707,43 @@ fn play_queued<'gc>(
     Ok(true)
 }
 
+/// Start playing a sound that is still being downloaded.
+///
+/// Returns `true` if the requested position has already been downloaded.
+fn play_streaming<'gc>(
+    queued: QueuedPlay<'gc>,
+    stream: &mut SoundStream,
+    context: &mut UpdateContext<'gc>,
+) -> bool {
+    // TODO: Streamed sounds ignore `loops` until the download is complete.
+    let Some((substream, stream_info)) = stream.substream_at(queued.position) else {
+        return false;
+    };
+
+    match context
+        .audio_manager
+        .start_substream(context.audio, substream, None, &stream_info)
+    {
+        Ok(instance) => {
+            if let Some(sound_transform) = queued.sound_transform {
+                context.set_local_sound_transform(instance, sound_transform);
+            }
+
+            let mut activation = Activation::from_nothing(context);
+            queued
+                .sound_channel
+                .as_sound_channel()
+                .unwrap()
+                .set_sound_instance(&mut activation, instance);
+
+            context.attach_avm2_sound_channel(instance, queued.sound_channel);
+        }
+        Err(e) => tracing::error!("Sound.play: couldn't start streaming sound: {}", e),
+    }
+
+    true
+}
+
 impl<'gc> TObject<'gc> for SoundObject<'gc> {
     fn gc_base(&self) -> Gc<'gc, ScriptObjectData<'gc>> {
         // SAFETY: Object data is repr(C), and a compile-time assert ensures
@@ -23,6 +23,9 @@ use swf::error::Error as AbcReadError;
 pub struct VerifiedMethodInfo<'gc> {
     pub parsed_code: Vec<Op<'gc>>,
 
+    /// The offset in the method body of each op in `parsed_code`.
+    pub byte_offsets: Vec<usize>,
+
     pub exceptions: Vec<Exception<'gc>>,
 }
 
@@ -535,6 +538,7 @@ pub fn verify_method<'gc>(
 
     Ok(VerifiedMethodInfo {
         parsed_code: verified_code,
+        byte_offsets: idx_to_byte_offset,
         exceptions: new_exceptions,
     })
 }
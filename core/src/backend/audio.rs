@@ -12,6 +12,7 @@ use slotmap::{new_key_type, Key, SlotMap};
 
 #[cfg(feature = "audio")]
 pub mod decoders;
+pub mod mp3_header;
 pub mod swf {
     pub use swf::{
         read, AudioCompression, CharacterId, Sound, SoundEnvelope, SoundEnvelopePoint, SoundEvent,
@@ -154,6 +155,19 @@ pub trait AudioBackend: Downcast {
     /// Get the sound format that a given sound was added with.
     fn get_sound_format(&self, sound: SoundHandle) -> Option<&swf::SoundFormat>;
 
+    /// Decode part of a sound into 44.1KHz stereo sample frames, in the range
+    /// `[-1.0, 1.0]`.
+    ///
+    /// Returns up to `num_frames` sample frames starting at sample frame
+    /// `start`; fewer are returned if the sound ends first. Returns `None` if
+    /// the sound could not be decoded.
+    fn extract_sound_data(
+        &mut self,
+        sound: SoundHandle,
+        start: u32,
+        num_frames: u32,
+    ) -> Option<Vec<[f32; 2]>>;
+
     /// Set the volume transform for a sound instance.
     fn set_sound_transform(&mut self, instance: SoundInstanceHandle, transform: SoundTransform);
 
@@ -308,6 +322,19 @@ impl AudioBackend for NullAudioBackend {
         self.sounds.get(sound).map(|s| &s.format)
     }
 
+    fn extract_sound_data(
+        &mut self,
+        sound: SoundHandle,
+        start: u32,
+        num_frames: u32,
+    ) -> Option<Vec<[f32; 2]>> {
+        // Extract silence for as long as the sound lasts.
+        let duration = self.sounds.get(sound)?.duration;
+        let total_frames = (duration * 44.1) as u32;
+        let num_frames = total_frames.saturating_sub(start).min(num_frames);
+        Some(vec![[0.0; 2]; num_frames as usize])
+    }
+
     fn set_sound_transform(&mut self, _instance: SoundInstanceHandle, _transform: SoundTransform) {}
 
     fn get_sound_peak(&mut self, _instance: SoundInstanceHandle) -> Option<[f32; 2]> {
@@ -597,11 +624,13 @@ impl<'gc> AudioManager<'gc> {
         }
     }
 
+    /// Starts a `Substream` backed audio stream, optionally associated with
+    /// the `MovieClip` it is attached to.
     pub fn start_substream(
         &mut self,
         audio: &mut dyn AudioBackend,
         stream_data: Substream,
-        movie_clip: MovieClip<'gc>,
+        movie_clip: Option<MovieClip<'gc>>,
         stream_info: &SoundStreamInfo,
     ) -> Result<SoundInstanceHandle, DecodeError> {
         if self.sounds.len() < Self::MAX_SOUNDS {
@@ -609,7 +638,7 @@ impl<'gc> AudioManager<'gc> {
             let instance = SoundInstance {
                 sound: None,
                 instance: handle,
-                display_object: Some(movie_clip.into()),
+                display_object: movie_clip.map(|clip| clip.into()),
                 transform: display_object::SoundTransform::default(),
                 avm1_object: None,
                 avm2_object: None,
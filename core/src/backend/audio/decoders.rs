@@ -19,7 +19,7 @@ pub use pcm::PcmDecoder;
 use crate::backend::audio::{SoundStreamInfo, SoundStreamWrapping};
 use crate::buffer::{Slice, Substream, SubstreamChunksIter};
 use crate::tag_utils::{ControlFlow, SwfSlice};
-use std::io::{Cursor, Read};
+use std::io::{Cursor, ErrorKind, Read};
 use swf::{AudioCompression, SoundFormat, TagCode};
 use thiserror::Error;
 
@@ -392,6 +392,9 @@ struct SubstreamTagReader {
     /// The tag data of the `MovieClip` that contains the streaming audio track.
     data_stream: SubstreamChunksIter,
 
+    /// The substream being read, used to tell if more data may still arrive.
+    substream: Substream,
+
     /// The compressed audio data in the most recent `SoundStreamBlock` we've seen, returned by `Iterator::next`.
     current_audio_data: Option<Slice>,
 
@@ -419,6 +422,7 @@ impl SubstreamTagReader {
     fn new(stream_info: &SoundStreamInfo, data_stream: Substream) -> Self {
         Self {
             data_stream: data_stream.iter_chunks(),
+            substream: data_stream,
             compression: stream_info.stream_format.compression,
             wrapping: stream_info.wrapping,
             current_audio_data: None,
@@ -467,10 +471,16 @@ impl Iterator for SubstreamTagReader {
 
 /// Returns an `Reader` that reads through SWF tags and returns slices of any
 /// audio stream data for `SoundStreamBlock` tags.
+///
+/// Reading past the end of a substream that is still loading fails with
+/// `ErrorKind::WouldBlock`, so that decoders can wait for more data.
 impl Read for SubstreamTagReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if self.current_audio_data.is_none() && self.next().is_none() {
             //next() fills current_audio_data
+            if self.substream.is_loading() {
+                return Err(ErrorKind::WouldBlock.into());
+            }
             return Ok(0);
         }
 
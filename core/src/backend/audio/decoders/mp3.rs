@@ -1,5 +1,5 @@
 use crate::backend::audio::decoders::{Decoder, Mp3Metadata, SeekableDecoder};
-use std::io::{Cursor, Read};
+use std::io::{Cursor, ErrorKind, Read};
 use symphonia::{
     core::{
         self, audio, codecs, errors,
@@ -33,6 +33,10 @@ pub struct Mp3Decoder {
     sample_rate: u16,
     num_channels: u8,
     stream_ended: bool,
+
+    /// The number of sample frames of silence left to play while waiting
+    /// for the reader to provide more data.
+    stalled_samples: u64,
 }
 
 impl Mp3Decoder {
@@ -59,6 +63,7 @@ impl Mp3Decoder {
                 .map_err(|_| Error::InvalidChannels)?,
             sample_rate: sample_rate.try_into().map_err(|_| Error::InvalidChannels)?,
             stream_ended: false,
+            stalled_samples: 0,
         })
     }
 
@@ -84,6 +89,7 @@ impl Mp3Decoder {
             num_channels: channels.count() as u8,
             sample_rate: sample_rate as u16,
             stream_ended: false,
+            stalled_samples: 0,
         })
     }
 
@@ -93,7 +99,18 @@ impl Mp3Decoder {
         }
 
         self.cur_sample = 0;
-        while let Ok(packet) = self.reader.next_packet() {
+        loop {
+            let packet = match self.reader.next_packet() {
+                Ok(packet) => packet,
+                // The reader is waiting for more data to be downloaded, so
+                // play a frame of silence and try again afterwards.
+                Err(errors::Error::IoError(e)) if e.kind() == ErrorKind::WouldBlock => {
+                    self.sample_buf.clear();
+                    self.stalled_samples = Self::SAMPLE_BUFFER_DURATION;
+                    return;
+                }
+                Err(_) => break,
+            };
             match self.decoder.decode(&packet) {
                 Ok(decoded) => {
                     if self.sample_buf.capacity() < decoded.capacity() {
@@ -121,11 +138,19 @@ impl Iterator for Mp3Decoder {
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        if self.stalled_samples > 0 {
+            self.stalled_samples -= 1;
+            return Some([0, 0]);
+        }
+
         if self.cur_sample >= self.sample_buf.len() {
             self.next_frame();
             if self.stream_ended {
                 return None;
             }
+            if self.stalled_samples > 0 {
+                return self.next();
+            }
         }
 
         let sample_buf = self.sample_buf.samples();
@@ -161,6 +186,7 @@ impl SeekableDecoder for Mp3Decoder {
         self.decoder.reset();
         self.cur_sample = 0;
         self.stream_ended = false;
+        self.stalled_samples = 0;
         // Seeking isn't exact, so we may end up slightly before our desired position.
         // Pump samples until we get to the exact position.
         let samples_remaining =
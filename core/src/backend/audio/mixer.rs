@@ -159,6 +159,11 @@ struct Sound {
     /// `skip_sample_frames` indicates how many sample frames to skip to bypass the delay.
     /// This is `0` unless `format.compression` is `AudioCompression::Mp3`.
     skip_sample_frames: u16,
+
+    /// The decoded audio of this sound at its own sample rate.
+    ///
+    /// This is only populated once the sound is extracted.
+    decoded_sample_frames: Option<Vec<[i16; 2]>>,
 }
 
 /// An actively playing instance of a sound.
@@ -522,6 +527,7 @@ impl AudioMixer {
             data: Arc::from(data),
             num_sample_frames: swf_sound.num_samples,
             skip_sample_frames,
+            decoded_sample_frames: None,
         };
         Ok(self.sounds.insert(sound))
     }
@@ -542,6 +548,7 @@ impl AudioMixer {
             data,
             num_sample_frames: metadata.num_sample_frames,
             skip_sample_frames: 0,
+            decoded_sample_frames: None,
         };
         Ok(self.sounds.insert(sound))
     }
@@ -682,6 +689,64 @@ impl AudioMixer {
         self.sounds.get(sound).map(|s| s.data.len() as u32)
     }
 
+    /// Decodes part of a registered sound into 44.1KHz stereo sample frames.
+    ///
+    /// The whole sound is decoded and kept around on first use, as sounds
+    /// are usually extracted a little at a time (e.g. by visualizers).
+    pub fn extract_sound_data(
+        &mut self,
+        sound: SoundHandle,
+        start: u32,
+        num_frames: u32,
+    ) -> Option<Vec<[f32; 2]>> {
+        const EXTRACT_SAMPLE_RATE: f64 = 44100.0;
+
+        let sound = self.sounds.get_mut(sound)?;
+        if sound.decoded_sample_frames.is_none() {
+            let data = Cursor::new(ArcAsRef(Arc::clone(&sound.data)));
+            let decoder = match Self::make_seekable_decoder(&sound.format, data) {
+                Ok(decoder) => decoder,
+                Err(e) => {
+                    tracing::error!("Couldn't decode sound for extraction: {}", e);
+                    return None;
+                }
+            };
+
+            // Externally loaded MP3s may not know their own length.
+            let num_sample_frames = match sound.num_sample_frames {
+                0 => usize::MAX,
+                n => n as usize,
+            };
+            sound.decoded_sample_frames = Some(
+                decoder
+                    .skip(sound.skip_sample_frames.into())
+                    .take(num_sample_frames)
+                    .collect(),
+            );
+        }
+
+        let sample_frames = sound.decoded_sample_frames.as_deref()?;
+        let step = f64::from(sound.format.sample_rate) / EXTRACT_SAMPLE_RATE;
+        let to_f32 = |sample: i16| f32::from(sample) / 32768.0;
+
+        // Linearly interpolate between the source sample frames.
+        let extracted = (0..num_frames)
+            .map_while(|i| {
+                let position = f64::from(start) + f64::from(i);
+                let position = position * step;
+                let index = position as usize;
+                let a = sample_frames.get(index)?;
+                let b = sample_frames.get(index + 1).unwrap_or(a);
+                let t = (position - index as f64) as f32;
+                Some([
+                    to_f32(a[0]) + (to_f32(b[0]) - to_f32(a[0])) * t,
+                    to_f32(a[1]) + (to_f32(b[1]) - to_f32(a[1])) * t,
+                ])
+            })
+            .collect();
+        Some(extracted)
+    }
+
     pub fn get_sound_format(&self, sound: SoundHandle) -> Option<&swf::SoundFormat> {
         self.sounds.get(sound).map(|s| &s.format)
     }
@@ -1135,6 +1200,16 @@ macro_rules! impl_audio_mixer_backend {
             self.$mixer.get_sound_format(sound)
         }
 
+        #[inline]
+        fn extract_sound_data(
+            &mut self,
+            sound: SoundHandle,
+            start: u32,
+            num_frames: u32,
+        ) -> Option<Vec<[f32; 2]>> {
+            self.$mixer.extract_sound_data(sound, start, num_frames)
+        }
+
         #[inline]
         fn set_sound_transform(
             &mut self,
@@ -0,0 +1,211 @@
+//! MP3 frame header parsing.
+//!
+//! This is used to learn the format of an MP3 that is still being
+//! downloaded, before there is enough data for a full decoder.
+
+/// The format of an MP3 stream, as described by the header of one of its
+/// frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mp3FrameHeader {
+    /// The sample rate of the audio in Hz.
+    pub sample_rate: u16,
+
+    /// Whether the audio has two channels.
+    pub is_stereo: bool,
+
+    /// The bitrate of the frame in bits per second.
+    pub bitrate: u32,
+
+    /// The number of sample frames in the frame.
+    pub samples_per_frame: u16,
+
+    /// The length of the frame in bytes, including this header.
+    pub frame_length: usize,
+}
+
+impl Mp3FrameHeader {
+    /// The length of the longest possible frame, a padded 320kbps MPEG 1
+    /// frame at 32000Hz.
+    pub const MAX_FRAME_LENGTH: usize = 1441;
+
+    /// Parse a 4-byte MPEG audio frame header.
+    ///
+    /// Only Layer III headers are accepted. Free-format frames are rejected,
+    /// as their length can't be determined from the header alone.
+    pub fn parse(header: &[u8]) -> Option<Self> {
+        let header = u32::from_be_bytes(header.get(..4)?.try_into().ok()?);
+
+        if header >> 21 != 0x7FF {
+            return None;
+        }
+
+        // 0 = MPEG 2.5, 2 = MPEG 2, 3 = MPEG 1.
+        let version = (header >> 19) & 0b11;
+        let layer = (header >> 17) & 0b11;
+        let bitrate_index = ((header >> 12) & 0b1111) as usize;
+        let sample_rate_index = ((header >> 10) & 0b11) as usize;
+        let padding = (header >> 9) & 1;
+        let channel_mode = (header >> 6) & 0b11;
+
+        if version == 1 || layer != 1 || bitrate_index == 0 || bitrate_index == 15 {
+            return None;
+        }
+
+        const MPEG1_BITRATES: [u32; 15] = [
+            0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+        ];
+        const MPEG2_BITRATES: [u32; 15] =
+            [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+        const SAMPLE_RATES: [u16; 3] = [44100, 48000, 32000];
+
+        let is_mpeg1 = version == 3;
+        let bitrate = if is_mpeg1 {
+            MPEG1_BITRATES[bitrate_index]
+        } else {
+            MPEG2_BITRATES[bitrate_index]
+        } * 1000;
+        let sample_rate = SAMPLE_RATES.get(sample_rate_index)?
+            >> match version {
+                3 => 0,
+                2 => 1,
+                _ => 2,
+            };
+        let samples_per_frame = if is_mpeg1 { 1152 } else { 576 };
+        let frame_length =
+            (samples_per_frame as u32 / 8 * bitrate / sample_rate as u32 + padding) as usize;
+
+        Some(Self {
+            sample_rate,
+            is_stereo: channel_mode != 0b11,
+            bitrate,
+            samples_per_frame,
+            frame_length,
+        })
+    }
+
+    /// Find the first MP3 frame in `data`, returning its offset and header.
+    ///
+    /// A frame is only accepted once it is followed by the header of another
+    /// valid frame, to avoid being fooled by stray sync bits. The last frame
+    /// of `data` is never found, as there is no way to confirm it yet.
+    pub fn find(data: &[u8]) -> Option<(usize, Self)> {
+        (0..data.len().saturating_sub(3)).find_map(|offset| {
+            let header = Self::parse(&data[offset..])?;
+            Self::parse(data.get(offset + header.frame_length..)?)?;
+            Some((offset, header))
+        })
+    }
+
+    /// The duration, in milliseconds, of `len` bytes of audio in this format.
+    pub fn duration_of(&self, len: usize) -> f64 {
+        len as f64 * 8000.0 / self.bitrate as f64
+    }
+
+    /// The number of bytes of audio in this format that play for `duration`
+    /// milliseconds.
+    pub fn length_of(&self, duration: f64) -> usize {
+        (duration * self.bitrate as f64 / 8000.0) as usize
+    }
+}
+
+/// Determine the length of the ID3v2 tag at the start of `data`.
+///
+/// Returns `Some(0)` if `data` does not start with a tag, and `None` if there
+/// isn't enough data to tell yet.
+pub fn id3v2_length(data: &[u8]) -> Option<usize> {
+    if data.len() < 3 {
+        return None;
+    }
+
+    if &data[..3] != b"ID3" {
+        return Some(0);
+    }
+
+    let header = data.get(..10)?;
+    let has_footer = header[5] & 0x10 != 0;
+    let size = header[6..10]
+        .iter()
+        .fold(0, |size, byte| (size << 7) | (*byte as usize & 0x7F));
+
+    Some(10 + size + if has_footer { 10 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // MPEG 1 Layer III, 128kbps, 44100Hz, joint stereo.
+    const MPEG1_HEADER: [u8; 4] = [0xFF, 0xFB, 0x90, 0x64];
+
+    // MPEG 2 Layer III, 32kbps, 22050Hz, mono.
+    const MPEG2_HEADER: [u8; 4] = [0xFF, 0xF3, 0x40, 0xC4];
+
+    #[test]
+    fn parse_headers() {
+        assert_eq!(
+            Mp3FrameHeader::parse(&MPEG1_HEADER),
+            Some(Mp3FrameHeader {
+                sample_rate: 44100,
+                is_stereo: true,
+                bitrate: 128000,
+                samples_per_frame: 1152,
+                frame_length: 417,
+            })
+        );
+        assert_eq!(
+            Mp3FrameHeader::parse(&MPEG2_HEADER),
+            Some(Mp3FrameHeader {
+                sample_rate: 22050,
+                is_stereo: false,
+                bitrate: 32000,
+                samples_per_frame: 576,
+                frame_length: 104,
+            })
+        );
+        assert_eq!(Mp3FrameHeader::parse(&[0xFF, 0xFB, 0xF0, 0x64]), None);
+        assert_eq!(Mp3FrameHeader::parse(&[0xFF, 0xFB]), None);
+    }
+
+    #[test]
+    fn find_frame() {
+        let mut data = vec![0xFF, 0xFB, 0x00, 0x12];
+        let header = Mp3FrameHeader::parse(&MPEG1_HEADER).unwrap();
+        for _ in 0..2 {
+            data.extend_from_slice(&MPEG1_HEADER);
+            data.resize(data.len() + header.frame_length - 4, 0);
+        }
+
+        assert_eq!(Mp3FrameHeader::find(&data), Some((4, header)));
+        assert_eq!(Mp3FrameHeader::find(&data[..3]), None);
+
+        // The first frame can't be confirmed until the next header is there.
+        let second_frame = 4 + header.frame_length;
+        assert_eq!(Mp3FrameHeader::find(&data[..second_frame]), None);
+        assert_eq!(Mp3FrameHeader::find(&data[..second_frame + 3]), None);
+        assert_eq!(
+            Mp3FrameHeader::find(&data[..second_frame + 4]),
+            Some((4, header))
+        );
+    }
+
+    #[test]
+    fn max_frame_length() {
+        let header = Mp3FrameHeader::parse(&[0xFF, 0xFB, 0xEA, 0x00]).unwrap();
+        assert_eq!(header.frame_length, Mp3FrameHeader::MAX_FRAME_LENGTH);
+    }
+
+    #[test]
+    fn id3v2_tag_length() {
+        assert_eq!(id3v2_length(b"ID"), None);
+        assert_eq!(id3v2_length(b"ID3\x04\x00\x00\x00\x00"), None);
+        assert_eq!(id3v2_length(&MPEG1_HEADER), Some(0));
+        assert_eq!(
+            id3v2_length(b"ID3\x04\x00\x00\x00\x00\x02\x01"),
+            Some(10 + 257)
+        );
+        assert_eq!(
+            id3v2_length(b"ID3\x04\x00\x10\x00\x00\x00\x05"),
+            Some(10 + 5 + 10)
+        );
+    }
+}
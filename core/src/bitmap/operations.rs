@@ -13,8 +13,9 @@ use gc_arena::Mutation;
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::bitmap::{PixelRegion, PixelSnapping};
 use ruffle_render::commands::{CommandHandler, CommandList, RenderBlendMode};
-use ruffle_render::filters::Filter;
+use ruffle_render::filters::{cpu, Filter};
 use ruffle_render::matrix::Matrix;
+use ruffle_render::pixmap::Pixmap;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::transform::Transform;
 use std::cell::{Ref, RefMut};
@@ -1242,15 +1243,16 @@ pub fn apply_filter<'gc>(
             return;
         }
 
-        // Until we support these filters, treat this like a copy
-        copy_on_cpu(
+        apply_filter_on_cpu(
             context.gc(),
             context.renderer,
             source,
             target,
+            source_point,
+            source_size,
             source_region,
             dest_region,
-            false,
+            &filter,
         );
         return;
     }
@@ -1277,6 +1279,71 @@ pub fn apply_filter<'gc>(
     }
 }
 
+/// Applies a filter with the shared CPU implementation, for renderers which can't do it themselves.
+#[allow(clippy::too_many_arguments)]
+fn apply_filter_on_cpu<'gc>(
+    context: &Mutation<'gc>,
+    renderer: &mut dyn RenderBackend,
+    source: BitmapDataWrapper<'gc>,
+    dest: BitmapDataWrapper<'gc>,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+    source_region: PixelRegion,
+    dest_region: PixelRegion,
+    filter: &Filter,
+) {
+    // The whole source rectangle is filtered, even the parts which don't end up in `dest`,
+    // as they can still blur or glow into the parts which do.
+    let mut pixmap = Pixmap::new(source_size.0, source_size.1);
+    {
+        let source_read = source.read_area(
+            PixelRegion::for_whole_size(source.width(), source.height()),
+            renderer,
+        );
+        let width = source_size
+            .0
+            .min(source_read.width().saturating_sub(source_point.0));
+        let height = source_size
+            .1
+            .min(source_read.height().saturating_sub(source_point.1));
+        for y in 0..height {
+            for x in 0..width {
+                let color = source_read.get_pixel32_raw(source_point.0 + x, source_point.1 + y);
+                let index = ((y * source_size.0 + x) * 4) as usize;
+                pixmap.data_mut()[index..index + 4].copy_from_slice(&[
+                    color.red(),
+                    color.green(),
+                    color.blue(),
+                    color.alpha(),
+                ]);
+            }
+        }
+    }
+
+    // Only the renderer could read the map of a displacement map filter, and it can't filter.
+    let filtered = cpu::apply_filter(&pixmap, filter, |_| None);
+
+    let dest = dest.sync(renderer);
+    let mut write = dest.write(context);
+    let opaque = !write.transparency();
+    let offset = (
+        source_region.x_min - source_point.0,
+        source_region.y_min - source_point.1,
+    );
+    for y in 0..dest_region.height() {
+        for x in 0..dest_region.width() {
+            let index = (((offset.1 + y) * source_size.0 + offset.0 + x) * 4) as usize;
+            let pixel = &filtered.data()[index..index + 4];
+            let mut color = Color::argb(pixel[3], pixel[0], pixel[1], pixel[2]);
+            if opaque {
+                color = color.with_alpha(255);
+            }
+            write.set_pixel32_raw(dest_region.x_min + x, dest_region.y_min + y, color);
+        }
+    }
+    write.set_cpu_dirty(context, dest_region);
+}
+
 #[allow(clippy::too_many_arguments)]
 fn copy_on_cpu<'gc>(
     context: &Mutation<'gc>,
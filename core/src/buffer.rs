@@ -5,6 +5,7 @@ use std::cmp::min;
 use std::fmt::{Debug, Formatter, LowerHex, UpperHex};
 use std::io::{Error as IoError, Read, Result as IoResult};
 use std::ops::{Bound, Deref, RangeBounds};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock, RwLockReadGuard};
 use thiserror::Error;
 
@@ -331,6 +332,12 @@ pub struct Substream {
 
     /// Shared list of chunks. Chunks are stored as (start, end) pairs.
     chunks: Arc<RwLock<Vec<(usize, usize)>>>,
+
+    /// Whether more chunks are still expected to be appended.
+    ///
+    /// Readers that catch up with the end of a loading `Substream` should
+    /// wait for more chunks instead of treating it as the end of the stream.
+    loading: Arc<AtomicBool>,
 }
 
 impl Substream {
@@ -338,9 +345,22 @@ impl Substream {
         Self {
             buf,
             chunks: Arc::new(RwLock::new(vec![])),
+            loading: Arc::new(AtomicBool::new(false)),
         }
     }
 
+    /// Mark whether more chunks are still expected to be appended.
+    ///
+    /// This applies to all clones of the `Substream`.
+    pub fn set_loading(&self, loading: bool) {
+        self.loading.store(loading, Ordering::Release);
+    }
+
+    /// Determine if more chunks are still expected to be appended.
+    pub fn is_loading(&self) -> bool {
+        self.loading.load(Ordering::Acquire)
+    }
+
     /// Append another `Slice` onto the end of the `Substream`.
     ///
     /// Appended chunks will be present in all clones of the `Substream`.
@@ -430,10 +450,7 @@ impl Substream {
 
 impl From<Buffer> for Substream {
     fn from(buf: Buffer) -> Self {
-        Self {
-            buf,
-            chunks: Arc::new(RwLock::new(vec![])),
-        }
+        Self::new(buf)
     }
 }
 
@@ -442,6 +459,7 @@ impl From<Slice> for Substream {
         Self {
             buf: slice.buf,
             chunks: Arc::new(RwLock::new(vec![(slice.start, slice.end)])),
+            loading: Arc::new(AtomicBool::new(false)),
         }
     }
 }
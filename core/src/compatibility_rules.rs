@@ -1,6 +1,9 @@
+use crate::PlayerRuntime;
+use md5::Md5;
+use sha2::{Digest, Sha256};
 use url::Url;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UrlRewriteRule {
     pub host: String,
     pub replacement: String,
@@ -15,13 +18,163 @@ impl UrlRewriteRule {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Decides which movies a rule set applies to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MovieMatcher {
+    /// The MD5 hash of the SWF file, as a hex string.
+    Md5(String),
+
+    /// The SHA-256 hash of the SWF file, as a hex string.
+    Sha256(String),
+
+    /// A pattern for the URL of the SWF file, where `*` matches any
+    /// sequence of characters.
+    Url(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct RuleSet {
     name: String,
+
+    /// The movies this rule set applies to, or every movie if empty.
+    matchers: Vec<MovieMatcher>,
+
+    /// Rewrites the host of the root movie URL, as seen by the movie itself.
     swf_domain_rewrite_rules: Vec<UrlRewriteRule>,
+
+    /// Rewrites the host of any URL that is fetched, such as assets on
+    /// servers that no longer exist.
+    fetch_url_rewrite_rules: Vec<UrlRewriteRule>,
+
+    player_version: Option<u8>,
+    player_runtime: Option<PlayerRuntime>,
+
+    /// Extra parameters given to the root movie.
+    flash_vars: Vec<(String, String)>,
+
+    frame_rate: Option<f64>,
+}
+
+impl RuleSet {
+    /// Creates an empty rule set that applies to every movie.
+    pub fn new(name: impl ToString) -> Self {
+        Self {
+            name: name.to_string(),
+            matchers: vec![],
+            swf_domain_rewrite_rules: vec![],
+            fetch_url_rewrite_rules: vec![],
+            player_version: None,
+            player_runtime: None,
+            flash_vars: vec![],
+            frame_rate: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn matchers(&self) -> &[MovieMatcher] {
+        &self.matchers
+    }
+
+    pub fn swf_domain_rewrite_rules(&self) -> &[UrlRewriteRule] {
+        &self.swf_domain_rewrite_rules
+    }
+
+    pub fn fetch_url_rewrite_rules(&self) -> &[UrlRewriteRule] {
+        &self.fetch_url_rewrite_rules
+    }
+
+    pub fn player_version(&self) -> Option<u8> {
+        self.player_version
+    }
+
+    pub fn player_runtime(&self) -> Option<PlayerRuntime> {
+        self.player_runtime
+    }
+
+    pub fn flash_vars(&self) -> &[(String, String)] {
+        &self.flash_vars
+    }
+
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.frame_rate
+    }
+
+    /// Restricts this rule set to movies that are matched by `matcher`.
+    ///
+    /// A rule set with several matchers applies to a movie if any of them match.
+    pub fn with_matcher(mut self, matcher: MovieMatcher) -> Self {
+        self.matchers.push(matcher);
+        self
+    }
+
+    /// Removes all matchers, making this rule set apply to every movie.
+    pub fn without_matchers(mut self) -> Self {
+        self.matchers.clear();
+        self
+    }
+
+    pub fn with_swf_domain_rewrite_rule(mut self, rule: UrlRewriteRule) -> Self {
+        self.swf_domain_rewrite_rules.push(rule);
+        self
+    }
+
+    pub fn with_fetch_url_rewrite_rule(mut self, rule: UrlRewriteRule) -> Self {
+        self.fetch_url_rewrite_rules.push(rule);
+        self
+    }
+
+    pub fn with_player_version(mut self, version: u8) -> Self {
+        self.player_version = Some(version);
+        self
+    }
+
+    pub fn with_player_runtime(mut self, runtime: PlayerRuntime) -> Self {
+        self.player_runtime = Some(runtime);
+        self
+    }
+
+    pub fn with_flash_var(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.flash_vars.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_frame_rate(mut self, frame_rate: f64) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self
+    }
+
+    /// Whether this rule set matches any movie, regardless of its URL or contents.
+    fn matches_all(&self) -> bool {
+        self.matchers.is_empty()
+    }
+}
+
+/// The hashes of a movie, computed only once they're needed.
+struct MovieHashes<'a> {
+    data: Option<&'a [u8]>,
+    md5: Option<String>,
+    sha256: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+impl MovieHashes<'_> {
+    fn md5(&mut self) -> Option<&str> {
+        let data = self.data?;
+        Some(self.md5.get_or_insert_with(|| to_hex(&Md5::digest(data))))
+    }
+
+    fn sha256(&mut self) -> Option<&str> {
+        let data = self.data?;
+        Some(
+            self.sha256
+                .get_or_insert_with(|| to_hex(&Sha256::digest(data))),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct CompatibilityRules {
     rule_sets: Vec<RuleSet>,
 }
@@ -43,19 +196,112 @@ impl CompatibilityRules {
         Self { rule_sets: vec![] }
     }
 
+    pub fn new(rule_sets: Vec<RuleSet>) -> Self {
+        Self { rule_sets }
+    }
+
+    pub fn rule_sets(&self) -> &[RuleSet] {
+        &self.rule_sets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rule_sets.is_empty()
+    }
+
+    /// Adds a rule set after all existing ones.
+    ///
+    /// Where several rule sets configure the same option, the first one wins.
+    pub fn add_rule_set(&mut self, rule_set: RuleSet) {
+        self.rule_sets.push(rule_set);
+    }
+
+    /// Adds all rule sets of `other` after the existing ones.
+    pub fn extend(&mut self, other: CompatibilityRules) {
+        self.rule_sets.extend(other.rule_sets);
+    }
+
+    /// Whether any rule set matches movies by hash.
+    ///
+    /// Such rules can only be checked once the whole movie has downloaded.
+    pub fn matches_by_hash(&self) -> bool {
+        self.rule_sets.iter().any(|rule_set| {
+            rule_set
+                .matchers
+                .iter()
+                .any(|matcher| matches!(matcher, MovieMatcher::Md5(_) | MovieMatcher::Sha256(_)))
+        })
+    }
+
+    /// Returns the rule sets that apply to the movie at `url`.
+    ///
+    /// Rule sets that match movies by hash can only apply when the movie
+    /// `data` is known.
+    pub fn for_movie(&self, url: &str, data: Option<&[u8]>) -> CompatibilityRules {
+        let mut hashes = MovieHashes {
+            data,
+            md5: None,
+            sha256: None,
+        };
+
+        let rule_sets = self
+            .rule_sets
+            .iter()
+            .filter(|rule_set| {
+                rule_set.matches_all()
+                    || rule_set.matchers.iter().any(|matcher| match matcher {
+                        MovieMatcher::Md5(hash) => hashes
+                            .md5()
+                            .is_some_and(|actual| actual.eq_ignore_ascii_case(hash)),
+                        MovieMatcher::Sha256(hash) => hashes
+                            .sha256()
+                            .is_some_and(|actual| actual.eq_ignore_ascii_case(hash)),
+                        MovieMatcher::Url(pattern) => wildcard_matches(pattern, url),
+                    })
+            })
+            .cloned()
+            .collect();
+
+        Self { rule_sets }
+    }
+
+    /// The player version to emulate, if any rule set forces one.
+    pub fn player_version(&self) -> Option<u8> {
+        self.rule_sets
+            .iter()
+            .find_map(|rule_set| rule_set.player_version)
+    }
+
+    /// The player runtime to emulate, if any rule set forces one.
+    pub fn player_runtime(&self) -> Option<PlayerRuntime> {
+        self.rule_sets
+            .iter()
+            .find_map(|rule_set| rule_set.player_runtime)
+    }
+
+    /// The frame rate to run the root movie at, if any rule set forces one.
+    pub fn frame_rate(&self) -> Option<f64> {
+        self.rule_sets
+            .iter()
+            .find_map(|rule_set| rule_set.frame_rate)
+    }
+
+    /// The extra parameters to give to the root movie.
+    pub fn flash_vars(&self) -> Vec<(String, String)> {
+        self.rule_sets
+            .iter()
+            .flat_map(|rule_set| rule_set.flash_vars.iter().cloned())
+            .collect()
+    }
+
     /// Default rules for general SWF compatibility.
     /// Rules that are added here must, to the best of our ability:
     /// - Only affect content that cannot run anymore, such as requiring lost assets
     /// - Not allow people to easily pirate or cheat games more than they can already
     pub fn builtin_rules() -> Self {
         // Replaces konggames.com domains with kongregate.com to fool old sitelocks that no longer work.
-        let kongregate_sitelock = RuleSet {
-            name: "kongregate_sitelock".to_string(),
-            swf_domain_rewrite_rules: vec![UrlRewriteRule::new(
-                "*.konggames.com",
-                "chat.kongregate.com",
-            )],
-        };
+        let kongregate_sitelock = RuleSet::new("kongregate_sitelock").with_swf_domain_rewrite_rule(
+            UrlRewriteRule::new("*.konggames.com", "chat.kongregate.com"),
+        );
 
         Self {
             rule_sets: vec![kongregate_sitelock],
@@ -92,6 +338,68 @@ impl CompatibilityRules {
 
         url.to_string()
     }
+
+    /// Rewrites the URL of a request, if any rule set replaces its host.
+    ///
+    /// Returns `None` if the URL should be fetched as it is.
+    pub fn rewrite_fetch_url(&self, original_url: &str) -> Option<String> {
+        let mut url = Url::parse(original_url).ok()?;
+        let mut rewritten = false;
+
+        for rule_set in &self.rule_sets {
+            for rule in &rule_set.fetch_url_rewrite_rules {
+                let Some(host) = url.host_str() else {
+                    continue;
+                };
+
+                if domain_matches(&rule.host, host) {
+                    tracing::info!(
+                        "Rewriting fetch of {original_url} due to compatibility ruleset '{}'",
+                        rule_set.name
+                    );
+                    match url.set_host(Some(&rule.replacement)) {
+                        Ok(()) => rewritten = true,
+                        Err(e) => tracing::warn!(
+                            "Couldn't rewrite fetch host to {}: {e}",
+                            rule.replacement
+                        ),
+                    }
+                }
+            }
+        }
+
+        rewritten.then(|| url.to_string())
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Tests that a string matches a pattern, where `*` matches any sequence of
+/// characters (including none).
+pub fn wildcard_matches(pattern: &str, actual: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = actual.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // The last part must be at the very end.
+            return rest.ends_with(part);
+        }
+
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    // There was no `*`, so the whole string must have matched.
+    rest.is_empty()
 }
 
 /// Tests that two domains match.
@@ -123,7 +431,9 @@ pub fn domain_matches(expected: &str, actual: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use crate::compatibility_rules::domain_matches;
+    use crate::compatibility_rules::{
+        domain_matches, wildcard_matches, CompatibilityRules, MovieMatcher, RuleSet, UrlRewriteRule,
+    };
 
     #[test]
     fn test_domain_matches() {
@@ -138,4 +448,74 @@ mod tests {
         assert!(!domain_matches("bar.example.com", "foo.example.com"));
         assert!(!domain_matches("bar.foo.example.com", "foo.example.com"));
     }
+
+    #[test]
+    fn test_wildcard_matches() {
+        assert!(wildcard_matches(
+            "https://example.com/game.swf",
+            "https://example.com/game.swf"
+        ));
+        assert!(wildcard_matches(
+            "https://*.example.com/*",
+            "https://www.example.com/game.swf"
+        ));
+        assert!(wildcard_matches(
+            "*game.swf",
+            "https://example.com/game.swf"
+        ));
+        assert!(wildcard_matches("*", ""));
+        assert!(wildcard_matches("a*b*c", "abbc"));
+        assert!(!wildcard_matches(
+            "https://example.com/game.swf",
+            "https://example.com/game.swf?v=1"
+        ));
+        assert!(!wildcard_matches(
+            "https://*.example.com/*",
+            "https://example.org/game.swf"
+        ));
+        assert!(!wildcard_matches("a*b*c", "acb"));
+        assert!(!wildcard_matches("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_for_movie() {
+        let rules = CompatibilityRules::new(vec![
+            RuleSet::new("all").with_flash_var("a", "1"),
+            RuleSet::new("by_url")
+                .with_matcher(MovieMatcher::Url("https://example.com/*".to_string()))
+                .with_frame_rate(30.0),
+            RuleSet::new("by_hash")
+                .with_matcher(MovieMatcher::Md5(
+                    "5D41402ABC4B2A76B9719D911017C592".to_string(),
+                ))
+                .with_player_version(10),
+        ]);
+
+        let by_url = rules.for_movie("https://example.com/game.swf", None);
+        assert_eq!(by_url.rule_sets().len(), 2);
+        assert_eq!(by_url.frame_rate(), Some(30.0));
+        assert_eq!(by_url.player_version(), None);
+
+        let by_hash = rules.for_movie("file:///game.swf", Some(b"hello"));
+        assert_eq!(by_hash.rule_sets().len(), 2);
+        assert_eq!(by_hash.player_version(), Some(10));
+        assert_eq!(by_hash.frame_rate(), None);
+        assert_eq!(
+            by_hash.flash_vars(),
+            vec![("a".to_string(), "1".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_fetch_url() {
+        let rules = CompatibilityRules::new(vec![RuleSet::new("assets")
+            .with_fetch_url_rewrite_rule(UrlRewriteRule::new("*.dead.com", "archive.org"))]);
+
+        assert_eq!(
+            rules.rewrite_fetch_url("http://cdn.dead.com/assets/a.png"),
+            Some("http://archive.org/assets/a.png".to_string())
+        );
+        assert_eq!(rules.rewrite_fetch_url("http://alive.com/a.png"), None);
+        assert_eq!(rules.rewrite_fetch_url("assets/a.png"), None);
+    }
 }
@@ -40,6 +40,7 @@ use crate::system_properties::SystemProperties;
 use crate::tag_utils::{SwfMovie, SwfSlice};
 use crate::timer::Timers;
 use crate::vminterface::Instantiator;
+use crate::worker::Workers;
 use async_channel::Sender;
 use core::fmt;
 use gc_arena::{Collect, Mutation};
@@ -219,6 +220,9 @@ pub struct UpdateContext<'gc> {
 
     pub local_connections: &'gc mut LocalConnections<'gc>,
 
+    /// Background workers, and the message channels between them.
+    pub workers: &'gc mut Workers<'gc>,
+
     /// Dynamic root for allowing handles to GC objects to exist outside of the GC.
     pub dynamic_root: gc_arena::DynamicRootSet<'gc>,
 
@@ -1,4 +1,5 @@
 mod avm1;
+mod avm1_trace_recorder;
 mod avm2;
 mod common;
 mod display_object;
@@ -8,6 +9,7 @@ mod movie;
 
 use crate::context::{RenderContext, UpdateContext};
 use crate::debug_ui::avm1::Avm1ObjectWindow;
+use crate::debug_ui::avm1_trace_recorder::Avm1TraceRecorderWindow;
 use crate::debug_ui::avm2::Avm2ObjectWindow;
 use crate::debug_ui::display_object::{DisplayObjectSearchWindow, DisplayObjectWindow};
 use crate::debug_ui::domain::DomainListWindow;
@@ -36,6 +38,9 @@ pub struct DebugUi {
     movie_list: Option<MovieListWindow>,
     domain_list: Option<DomainListWindow>,
     display_object_search: Option<DisplayObjectSearchWindow>,
+    avm1_trace_recorder: Option<Avm1TraceRecorderWindow>,
+    /// Whether the player is paused to show an AVM1 trace.
+    avm1_paused: bool,
 }
 
 #[derive(Debug)]
@@ -51,6 +56,7 @@ pub enum Message {
     ShowDomains,
     SaveFile(ItemToSave),
     SearchForDisplayObject,
+    ShowAvm1TraceRecorder,
 }
 
 impl DebugUi {
@@ -98,6 +104,17 @@ impl DebugUi {
             }
         }
 
+        // A trace may have been recorded since the recorder was last shown.
+        if context.avm1.trace_recorder().trace().is_some() && self.avm1_trace_recorder.is_none() {
+            self.avm1_trace_recorder = Some(Default::default());
+        }
+        if let Some(mut recorder) = self.avm1_trace_recorder.take() {
+            if recorder.show(egui_ctx, context, &mut messages) {
+                self.avm1_trace_recorder = Some(recorder);
+            }
+        }
+        self.avm1_paused = context.avm1.trace_recorder().trace().is_some();
+
         for message in messages {
             match message {
                 Message::TrackDisplayObject(object) => {
@@ -133,12 +150,15 @@ impl DebugUi {
                 Message::SearchForDisplayObject => {
                     self.display_object_search = Some(Default::default());
                 }
+                Message::ShowAvm1TraceRecorder => {
+                    self.avm1_trace_recorder = Some(Default::default());
+                }
             }
         }
     }
 
     pub fn should_suspend_player(&self) -> bool {
-        self.display_object_search.is_some()
+        self.display_object_search.is_some() || self.avm1_paused
     }
 
     pub fn items_to_save(&mut self) -> Vec<ItemToSave> {
@@ -199,6 +219,17 @@ impl DebugUi {
             }
         }
 
+        if let Some(object) = self
+            .avm1_trace_recorder
+            .as_ref()
+            .and_then(|window| window.hovered_debug_rect())
+        {
+            let object = object.fetch(dynamic_root_set);
+            let bounds = world_matrix * object.debug_rect_bounds();
+
+            draw_debug_rect(context, Color::RED, bounds, 5.0);
+        }
+
         for (_object, window) in self.avm2_objects.iter() {
             if let Some(object) = window.hovered_debug_rect() {
                 let object = object.fetch(dynamic_root_set);
@@ -359,7 +359,7 @@ impl UiExt for egui::Ui {
     }
 }
 
-fn object_name(object: Object) -> String {
+pub fn object_name(object: Object) -> String {
     // TODO: Find a way to give more meaningful names here.
     // Matching __proto__ to a constant and taking the constants name works, but is super expensive
     if object.as_executable().is_some() {
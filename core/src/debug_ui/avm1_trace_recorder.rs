@@ -0,0 +1,486 @@
+use crate::avm1::{
+    evaluate_expression, Activation, ActivationIdentifier, TraceStep, Tracepoint, Value,
+};
+use crate::context::UpdateContext;
+use crate::debug_ui::avm1::object_name;
+use crate::debug_ui::display_object::open_display_object_button;
+use crate::debug_ui::handle::{AVM1ObjectHandle, DisplayObjectHandle};
+use crate::debug_ui::Message;
+use egui::{CollapsingHeader, Grid, TextEdit, Ui, Window};
+
+/// How many recorded actions are listed around the current one.
+const STEPS_AROUND_CURRENT: usize = 8;
+
+const RECORDING_EXPLANATION: &str = "This records a trace rather than stopping execution. \
+    Once a tracepoint is hit, every action is recorded until the activation that hit it returns, \
+    and only then is the player paused to step through the trace.";
+
+#[derive(Debug, Default)]
+pub struct Avm1TraceRecorderWindow {
+    hovered_debug_rect: Option<DisplayObjectHandle>,
+
+    /// The index of the recorded step being inspected.
+    current_step: usize,
+
+    function_tracepoint: String,
+    action_tracepoint_url: String,
+    action_tracepoint_offset: String,
+
+    expression: String,
+    evaluated: Option<Result<String, String>>,
+}
+
+impl Avm1TraceRecorderWindow {
+    pub fn hovered_debug_rect(&self) -> Option<DisplayObjectHandle> {
+        self.hovered_debug_rect.clone()
+    }
+
+    pub fn show(
+        &mut self,
+        egui_ctx: &egui::Context,
+        context: &mut UpdateContext,
+        messages: &mut Vec<Message>,
+    ) -> bool {
+        let mut keep_open = true;
+        Window::new("AVM1 Trace Recorder")
+            .open(&mut keep_open)
+            .scroll([true, true])
+            .show(egui_ctx, |ui| {
+                self.show_controls(ui, context);
+                ui.separator();
+
+                CollapsingHeader::new("Tracepoints")
+                    .default_open(true)
+                    .show(ui, |ui| self.show_tracepoints(ui, context));
+
+                if context.avm1.trace_recorder().trace().is_some() {
+                    ui.separator();
+                    self.show_trace(ui, context, messages);
+                }
+            });
+
+        if !keep_open {
+            // Nobody could resume the player once the window is gone.
+            context.avm1.trace_recorder_mut().resume();
+        }
+        keep_open
+    }
+
+    fn show_controls(&mut self, ui: &mut Ui, context: &mut UpdateContext) {
+        let Some(trace) = context.avm1.trace_recorder().trace() else {
+            ui.horizontal(|ui| {
+                ui.label("Running");
+                if ui.button("Record from next action").clicked() {
+                    context.avm1.trace_recorder_mut().record_next_action();
+                }
+            });
+            ui.label(RECORDING_EXPLANATION);
+            return;
+        };
+
+        let steps = &trace.steps;
+        ui.label(format!("{} (replaying the trace)", trace.reason));
+        ui.label(
+            "These actions have already run. Stepping moves through the trace, \
+             and the player stays paused until you continue.",
+        );
+        if steps.is_empty() {
+            self.current_step = 0;
+        } else {
+            self.current_step = self.current_step.min(steps.len() - 1);
+        }
+        let depth = steps.get(self.current_step).map(|step| step.depth);
+
+        let mut resume = false;
+        let mut next_step = None;
+        ui.horizontal(|ui| {
+            if ui.button("Continue Player").clicked() {
+                resume = true;
+            }
+            ui.add_enabled_ui(self.current_step > 0, |ui| {
+                if ui.button("Step Back").clicked() {
+                    next_step = Some(self.current_step - 1);
+                }
+            });
+            let later_steps = || steps.iter().enumerate().skip(self.current_step + 1);
+            let step_into = later_steps().next().map(|(i, _)| i);
+            let step_over = later_steps()
+                .find(|(_, step)| Some(step.depth) <= depth)
+                .map(|(i, _)| i);
+            let step_out = later_steps()
+                .find(|(_, step)| Some(step.depth) < depth)
+                .map(|(i, _)| i);
+            for (label, step) in [
+                ("Step Into", step_into),
+                ("Step Over", step_over),
+                ("Step Out", step_out),
+            ] {
+                if ui
+                    .add_enabled(step.is_some(), egui::Button::new(label))
+                    .clicked()
+                {
+                    next_step = step;
+                }
+            }
+            ui.label(format!(
+                "Action {} of {} recorded",
+                self.current_step + 1,
+                steps.len()
+            ));
+        });
+
+        if let Some(next_step) = next_step {
+            self.current_step = next_step;
+            self.evaluated = None;
+        }
+        if resume {
+            context.avm1.trace_recorder_mut().resume();
+            self.current_step = 0;
+            self.evaluated = None;
+        }
+    }
+
+    fn show_tracepoints(&mut self, ui: &mut Ui, context: &mut UpdateContext) {
+        let mut removed = None;
+        Grid::new(ui.id().with("tracepoints"))
+            .num_columns(2)
+            .show(ui, |ui| {
+                for tracepoint in context.avm1.trace_recorder().tracepoints() {
+                    ui.label(tracepoint.to_string());
+                    if ui.button("Remove").clicked() {
+                        removed = Some(tracepoint.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(removed) = removed {
+            context
+                .avm1
+                .trace_recorder_mut()
+                .remove_tracepoint(&removed);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.function_tracepoint)
+                    .hint_text("Function name")
+                    .desired_width(160.0),
+            );
+            if ui.button("Add").clicked() && !self.function_tracepoint.is_empty() {
+                context
+                    .avm1
+                    .trace_recorder_mut()
+                    .add_tracepoint(Tracepoint::Function(std::mem::take(
+                        &mut self.function_tracepoint,
+                    )));
+            }
+        });
+
+        if self.action_tracepoint_url.is_empty() {
+            self.action_tracepoint_url = context.swf.url().to_string();
+        }
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.action_tracepoint_url)
+                    .hint_text("SWF URL")
+                    .desired_width(240.0),
+            );
+            ui.add(
+                TextEdit::singleline(&mut self.action_tracepoint_offset)
+                    .hint_text("Offset")
+                    .desired_width(80.0),
+            );
+            let offset = self.action_tracepoint_offset.trim();
+            let offset = match offset.strip_prefix("0x") {
+                Some(hex) => usize::from_str_radix(hex, 16),
+                None => offset.parse(),
+            };
+            if ui
+                .add_enabled(offset.is_ok(), egui::Button::new("Add"))
+                .on_hover_text("Offset of the action in the uncompressed SWF, after its header")
+                .clicked()
+            {
+                if let Ok(offset) = offset {
+                    context
+                        .avm1
+                        .trace_recorder_mut()
+                        .add_tracepoint(Tracepoint::Action {
+                            swf_url: self.action_tracepoint_url.clone(),
+                            offset,
+                        });
+                    self.action_tracepoint_offset.clear();
+                }
+            }
+        });
+    }
+
+    fn show_trace(
+        &mut self,
+        ui: &mut Ui,
+        context: &mut UpdateContext,
+        messages: &mut Vec<Message>,
+    ) {
+        let Some(trace) = context.avm1.trace_recorder().trace() else {
+            return;
+        };
+        let Some(step) = trace.steps.get(self.current_step) else {
+            ui.label("No actions were recorded");
+            return;
+        };
+
+        let mut selected = None;
+        let mut new_tracepoint = None;
+        CollapsingHeader::new("Actions")
+            .default_open(true)
+            .show(ui, |ui| {
+                let first = self.current_step.saturating_sub(STEPS_AROUND_CURRENT);
+                for (i, other) in trace
+                    .steps
+                    .iter()
+                    .enumerate()
+                    .skip(first)
+                    .take(STEPS_AROUND_CURRENT * 2 + 1)
+                {
+                    let indent = "  ".repeat(other.depth as usize);
+                    let text = format!("{indent}{:#07x} {}", other.offset, other.action);
+                    if ui
+                        .selectable_label(
+                            i == self.current_step,
+                            egui::RichText::new(text).monospace(),
+                        )
+                        .clicked()
+                    {
+                        selected = Some(i);
+                    }
+                }
+            });
+        ui.horizontal(|ui| {
+            ui.label(format!("In {}", step.activation));
+            if ui.button("Trace from here").clicked() {
+                new_tracepoint = Some(Tracepoint::Action {
+                    swf_url: step.swf_url.clone(),
+                    offset: step.offset,
+                });
+            }
+        });
+
+        // The trace can't stay borrowed while code runs to show values.
+        let step = step.clone();
+        self.show_values(ui, context, &step, messages);
+        self.show_evaluate(ui, context, messages);
+
+        if let Some(selected) = selected {
+            self.current_step = selected;
+            self.evaluated = None;
+        }
+        if let Some(tracepoint) = new_tracepoint {
+            context.avm1.trace_recorder_mut().add_tracepoint(tracepoint);
+        }
+    }
+
+    fn show_values(
+        &mut self,
+        ui: &mut Ui,
+        context: &mut UpdateContext,
+        step: &TraceStep,
+        messages: &mut Vec<Message>,
+    ) {
+        let values = step.values(context.dynamic_root);
+        let base_clip = context.stage.into();
+        let mut activation =
+            Activation::from_nothing(context, ActivationIdentifier::root("Debug"), base_clip);
+
+        ui.label(
+            "The stack, registers and locals are as they were at this step. \
+             Objects aren't copied, so they show their current properties.",
+        );
+        CollapsingHeader::new(format!("Stack ({})", values.stack.len()))
+            .default_open(true)
+            .show(ui, |ui| {
+                Grid::new(ui.id().with("stack"))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (i, value) in values.stack.iter().enumerate().rev() {
+                            ui.label(i.to_string());
+                            self.show_value(ui, &mut activation, *value, messages);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        CollapsingHeader::new("Registers")
+            .default_open(true)
+            .show(ui, |ui| {
+                Grid::new(ui.id().with("registers"))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (i, value) in values.registers.iter().enumerate() {
+                            ui.label(format!("r{i}"));
+                            self.show_value(ui, &mut activation, *value, messages);
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        CollapsingHeader::new("Locals")
+            .default_open(true)
+            .show(ui, |ui| match &values.locals {
+                Some(locals) => {
+                    Grid::new(ui.id().with("locals"))
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            for (name, value) in locals {
+                                ui.label(name.to_string());
+                                self.show_value(ui, &mut activation, *value, messages);
+                                ui.end_row();
+                            }
+                        });
+                }
+                None => {
+                    ui.label("Not in a function");
+                }
+            });
+
+        CollapsingHeader::new("Scope Chain")
+            .default_open(false)
+            .show(ui, |ui| {
+                Grid::new(ui.id().with("scope_chain"))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("this");
+                        self.show_value(ui, &mut activation, values.this, messages);
+                        ui.end_row();
+                        for (class, object) in values.scope_chain() {
+                            ui.label(format!("{class:?}"));
+                            self.show_value(ui, &mut activation, object.into(), messages);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    fn show_evaluate(
+        &mut self,
+        ui: &mut Ui,
+        context: &mut UpdateContext,
+        messages: &mut Vec<Message>,
+    ) {
+        ui.horizontal(|ui| {
+            let response = ui
+                .add(
+                    TextEdit::singleline(&mut self.expression)
+                        .hint_text("Expression, such as this._x")
+                        .desired_width(240.0),
+                )
+                .on_hover_text("Getters aren't called, and no ActionScript runs");
+            let entered =
+                response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            if ui.button("Evaluate").clicked() || entered {
+                self.evaluated = Some(self.evaluate(context, messages));
+            }
+        });
+
+        match &self.evaluated {
+            Some(Ok(result)) => {
+                ui.label(egui::RichText::new(result).monospace());
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, error);
+            }
+            None => {}
+        }
+    }
+
+    /// Evaluate the expression in the current step, opening any object it
+    /// results in.
+    fn evaluate(
+        &self,
+        context: &mut UpdateContext,
+        messages: &mut Vec<Message>,
+    ) -> Result<String, String> {
+        let Some(step) = context
+            .avm1
+            .trace_recorder()
+            .trace()
+            .and_then(|trace| trace.steps.get(self.current_step))
+        else {
+            return Err("No trace was recorded".to_string());
+        };
+        let step = step.clone();
+        let value = evaluate_expression(context, &step, &self.expression)?;
+
+        let base_clip = context.stage.into();
+        let mut activation =
+            Activation::from_nothing(context, ActivationIdentifier::root("Debug"), base_clip);
+        match value {
+            Value::Object(object) if object.as_executable().is_none() => {
+                messages.push(Message::TrackAVM1Object(AVM1ObjectHandle::new(
+                    activation.context,
+                    object,
+                )));
+                Ok(object_name(object))
+            }
+            Value::MovieClip(clip) => {
+                if let Some((_, object, _)) = clip.resolve_reference(&mut activation) {
+                    messages.push(Message::TrackAVM1Object(AVM1ObjectHandle::new(
+                        activation.context,
+                        object,
+                    )));
+                }
+                Ok(clip.path().to_string())
+            }
+            value => Ok(value_text(&mut activation, value)),
+        }
+    }
+
+    fn show_value<'gc>(
+        &mut self,
+        ui: &mut Ui,
+        activation: &mut Activation<'_, 'gc>,
+        value: Value<'gc>,
+        messages: &mut Vec<Message>,
+    ) {
+        match value {
+            Value::Object(object) => {
+                if object.as_executable().is_some() {
+                    ui.label("Function");
+                } else if ui.button(object_name(object)).clicked() {
+                    messages.push(Message::TrackAVM1Object(AVM1ObjectHandle::new(
+                        activation.context,
+                        object,
+                    )));
+                }
+            }
+            Value::MovieClip(clip) => {
+                if let Some((_, _, object)) = clip.resolve_reference(activation) {
+                    open_display_object_button(
+                        ui,
+                        activation.context,
+                        messages,
+                        object,
+                        &mut self.hovered_debug_rect,
+                    );
+                } else {
+                    ui.colored_label(
+                        ui.style().visuals.error_fg_color,
+                        format!("Unknown movieclip {}", clip.path()),
+                    );
+                }
+            }
+            value => {
+                ui.label(value_text(activation, value));
+            }
+        }
+    }
+}
+
+/// A short description of a primitive value.
+fn value_text<'gc>(activation: &mut Activation<'_, 'gc>, value: Value<'gc>) -> String {
+    match value {
+        Value::String(string) => format!("{:?}", string.to_utf8_lossy()),
+        value => match value.coerce_to_string(activation) {
+            Ok(string) => string.to_string(),
+            Err(e) => e.to_string(),
+        },
+    }
+}
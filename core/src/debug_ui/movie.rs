@@ -284,7 +284,7 @@ fn save_swf(movie: &Arc<SwfMovie>, messages: &mut Vec<Message>) {
     };
     let mut data = Vec::new();
     if let Err(e) =
-        swf::write::write_swf_raw_tags(movie.header().swf_header(), movie.data(), &mut data)
+        swf::write::write_swf_raw_tags(movie.header().swf_header(), &movie.data(), &mut data)
     {
         tracing::error!("Couldn't write swf: {e}");
     } else {
@@ -423,6 +423,9 @@ impl<'gc> EditText<'gc> {
     }
 
     /// Create a new, dynamic `EditText` representing an AVM2 TextLine.
+    ///
+    /// `width` is the width available to the text itself, not including
+    /// the gutter.
     pub fn new_fte(
         context: &mut UpdateContext<'gc>,
         swf_movie: Arc<SwfMovie>,
@@ -431,6 +434,7 @@ impl<'gc> EditText<'gc> {
         width: f64,
         height: f64,
     ) -> Self {
+        let width = width + (Self::GUTTER * 2).to_pixels();
         let text = Self::new(context, swf_movie, x, y, width, height);
         text.set_is_fte(context.gc(), true);
         text.set_selectable(false, context);
@@ -801,6 +805,25 @@ impl<'gc> EditText<'gc> {
         self.0.write(gc_context).is_fte = is_fte;
     }
 
+    /// Fit the bounds of this FTE text line around its laid out text.
+    ///
+    /// FTE text lines are positioned by their baseline, so the origin is
+    /// placed `ascent` below the top of the text.
+    pub fn fit_fte_line_bounds(self, gc_context: &Mutation<'gc>, ascent: Twips) {
+        let edit_text = self.0.read();
+        let text_size = edit_text.layout.text_size();
+        let x_min = -Self::GUTTER;
+        let y_min = -Self::GUTTER - ascent;
+        edit_text.bounds.set(Rectangle {
+            x_min,
+            x_max: text_size.width() + Self::GUTTER,
+            y_min,
+            y_max: y_min + text_size.height() + Self::GUTTER * 2,
+        });
+        drop(edit_text);
+        self.invalidate_cached_bitmap(gc_context);
+    }
+
     pub fn layout_debug_boxes_flag(self, flag: LayoutDebugBoxesFlag) -> bool {
         self.0.read().layout_debug_boxes_flags.contains(flag)
     }
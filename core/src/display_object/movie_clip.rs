@@ -541,10 +541,14 @@ impl<'gc> MovieClip<'gc> {
         context: &mut UpdateContext<'gc>,
         chunk_limit: &mut ExecutionLimit,
     ) -> bool {
+        // The main timeline of a movie that is still downloading may have more
+        // data to preload, or may have finished downloading without an `End` tag.
+        let is_streamed = self.is_root() && self.movie().is_streamed();
         {
             let read = self.0.read();
-            if read.shared.preload_progress.read().next_preload_chunk
-                >= read.shared.swf.len() as u64
+            let next_preload_chunk = read.shared.preload_progress.read().next_preload_chunk;
+            if next_preload_chunk == u64::MAX
+                || (next_preload_chunk >= read.shared.swf.len() as u64 && !is_streamed)
             {
                 return true;
             }
@@ -553,7 +557,12 @@ impl<'gc> MovieClip<'gc> {
         // TODO: Re-creating static data because preload step occurs after construction.
         // Should be able to hoist this up somewhere, or use MaybeUninit.
         let mut shared = (*self.0.read().shared).clone();
-        let data = self.0.read().shared.swf.clone();
+        let is_loading = is_streamed && self.movie().is_loading();
+        if is_streamed {
+            // Pick up any data that has arrived since the last chunk.
+            shared.swf = self.movie().into();
+        }
+        let data = shared.swf.clone();
         let (mut cur_frame, mut start_pos, next_preload_chunk, preload_symbol) = {
             let read = shared.preload_progress.read();
             (
@@ -769,7 +778,10 @@ impl<'gc> MovieClip<'gc> {
         } else {
             Ok(true)
         };
-        let is_finished = end_tag_found || result.is_err() || !result.unwrap_or_default();
+        // Running out of data only ends the preload once the movie has finished downloading.
+        let is_waiting_for_data = is_loading && !end_tag_found && reader.get_ref().is_empty();
+        let is_finished = !is_waiting_for_data
+            && (end_tag_found || result.is_err() || !result.unwrap_or_default());
 
         self.0
             .write(context.gc())
@@ -1273,7 +1285,11 @@ impl<'gc> MovieClip<'gc> {
             return max(self.total_bytes(), 0) as u32;
         }
 
-        let swf_header_size = max(self.total_bytes(), 0) as u32 - self.tag_stream_len() as u32;
+        let swf_header_size = if self.is_root() {
+            self.movie().header_len() as u32
+        } else {
+            0
+        };
 
         swf_header_size + progress_read.next_preload_chunk as u32
     }
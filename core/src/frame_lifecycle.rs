@@ -11,7 +11,7 @@
 //! runs in one phase, with timeline operations executing with all phases
 //! inline in the order that clips were originally created.
 
-use crate::avm2::Avm2;
+use crate::avm2::{Avm2, EventObject as Avm2EventObject};
 use crate::avm2_stub_method_context;
 use crate::context::UpdateContext;
 use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
@@ -108,6 +108,55 @@ pub fn run_all_phases_avm2(context: &mut UpdateContext<'_>) {
     *context.frame_phase = FramePhase::Idle;
 }
 
+/// Run all frame phases for a background worker.
+///
+/// A worker's root movie is never placed on the stage, so it is run
+/// directly, along with any orphans created by the worker. This must be
+/// called with the worker's `Avm2` swapped into the context.
+#[instrument(level = "debug", skip_all)]
+pub fn run_worker_phases_avm2<'gc>(context: &mut UpdateContext<'gc>, root: DisplayObject<'gc>) {
+    let old_phase = *context.frame_phase;
+
+    *context.frame_phase = FramePhase::Enter;
+    Avm2::each_orphan_obj(context, |orphan, context| {
+        if !DisplayObject::ptr_eq(orphan, root) {
+            orphan.enter_frame(context);
+        }
+    });
+    root.enter_frame(context);
+    let enter_frame_evt = Avm2EventObject::bare_default_event(context, "enterFrame");
+    let dobject_constr = context.avm2.classes().display_object;
+    Avm2::broadcast_event(context, enter_frame_evt, dobject_constr);
+
+    *context.frame_phase = FramePhase::Construct;
+    Avm2::each_orphan_obj(context, |orphan, context| {
+        if !DisplayObject::ptr_eq(orphan, root) {
+            orphan.construct_frame(context);
+        }
+    });
+    root.construct_frame(context);
+    root.frame_constructed(context);
+
+    *context.frame_phase = FramePhase::FrameScripts;
+    Avm2::each_orphan_obj(context, |orphan, context| {
+        if !DisplayObject::ptr_eq(orphan, root) {
+            orphan.run_frame_scripts(context);
+        }
+    });
+    root.run_frame_scripts(context);
+
+    // Unlike `DisplayObject::exit_frame`, this doesn't run the load manager,
+    // whose loaders belong to the primordial worker.
+    *context.frame_phase = FramePhase::Exit;
+    let exit_frame_evt = Avm2EventObject::bare_default_event(context, "exitFrame");
+    let dobject_constr = context.avm2.classes().display_object;
+    Avm2::broadcast_event(context, exit_frame_evt, dobject_constr);
+
+    Avm2::cleanup_dead_orphans(context);
+
+    *context.frame_phase = old_phase;
+}
+
 /// Like `run_all_phases_avm2`, but specialized for the "nested frame" triggered
 /// by a goto. This is different enough to not be worth combining into a single
 /// method with extra parameters.
@@ -53,6 +53,7 @@ pub mod tag_utils;
 pub mod timer;
 mod types;
 mod vminterface;
+mod worker;
 mod xml;
 
 pub mod backend;
@@ -64,6 +65,7 @@ pub mod external;
 pub mod i18n;
 pub mod stub;
 
+pub use avm2::debugger as avm2_debugger;
 pub use context_menu::ContextMenuItem;
 pub use events::PlayerEvent;
 pub use font::DefaultFont;
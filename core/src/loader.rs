@@ -41,10 +41,13 @@ use ruffle_render::utils::{determine_jpeg_tag_format, JpegTagFormat};
 use slotmap::{new_key_type, SlotMap};
 use std::borrow::Borrow;
 use std::fmt;
+use std::future::Future;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
 use std::time::Duration;
 use swf::read::{extract_swz, read_compression_type};
+use swf::StreamDecompressor;
 use thiserror::Error;
 use url::{form_urlencoded, ParseError, Url};
 
@@ -376,7 +379,7 @@ impl<'gc> LoadManager<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
 
             match Loader::wait_for_full_response(fetch).await {
                 Ok((body, url, _status, _redirected)) => {
@@ -1058,7 +1061,10 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            // Compatibility rules are matched against the URL that was asked
+            // for, as fetching it may have been redirected by those same rules.
+            let request_url = request.url().to_string();
+            let fetch = player.lock().unwrap().fetch(request);
             let response = fetch.await.map_err(|error| {
                 player
                     .lock()
@@ -1068,41 +1074,54 @@ impl<'gc> Loader<'gc> {
                 error.error
             })?;
             let url = response.url().into_owned();
-            let body = response.body().await.inspect_err(|_error| {
+            let expected_len = response
+                .expected_length()
+                .ok()
+                .flatten()
+                .map(|len| len as usize);
+
+            // Rules that match the movie by its hash need all of it, so it
+            // can't start playing until it's downloaded.
+            let can_stream = !player
+                .lock()
+                .unwrap()
+                .compatibility_rules()
+                .matches_by_hash();
+
+            let mut parameters = Some(parameters);
+            let mut on_metadata = Some(on_metadata);
+            let body = download_movie(response, can_stream, |stream| {
+                start_root_movie(
+                    &player,
+                    &request_url,
+                    url.clone(),
+                    RootMovieData::Streaming(stream, expected_len),
+                    parameters.take().unwrap_or_default(),
+                    on_metadata.take(),
+                )
+            })
+            .await;
+
+            let result = match body {
+                Ok(Some(body)) => start_root_movie(
+                    &player,
+                    &request_url,
+                    url,
+                    RootMovieData::Complete(&body),
+                    parameters.take().unwrap_or_default(),
+                    on_metadata.take(),
+                )
+                .map(|_movie| ()),
+                Ok(None) => Ok(()),
+                Err(error) => Err(error),
+            };
+            result.inspect_err(|_error| {
                 player
                     .lock()
                     .unwrap()
                     .ui()
                     .display_root_movie_download_failed_message(true);
-            })?;
-
-            // The spoofed root movie URL takes precedence over the actual URL.
-            let swf_url = player
-                .lock()
-                .unwrap()
-                .compatibility_rules()
-                .rewrite_swf_url(url);
-            let spoofed_or_swf_url = player
-                .lock()
-                .unwrap()
-                .spoofed_url()
-                .map(|u| u.to_string())
-                .unwrap_or(swf_url);
-
-            let mut movie =
-                SwfMovie::from_data(&body, spoofed_or_swf_url, None).inspect_err(|_error| {
-                    player
-                        .lock()
-                        .unwrap()
-                        .ui()
-                        .display_root_movie_download_failed_message(true);
-                })?;
-            on_metadata(movie.header());
-            movie.append_parameters(parameters);
-            player.lock().unwrap().mutate_with_update_context(|uc| {
-                uc.set_root_movie(movie);
-            });
-            Ok(())
+            })
         })
     }
 
@@ -1132,7 +1151,7 @@ impl<'gc> Loader<'gc> {
             let request_url = request.url().to_string();
             let resolved_url = player.lock().unwrap().navigator().resolve_url(&request_url);
 
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
 
             let mut replacing_root_movie = false;
             player.lock().unwrap().update(|uc| -> Result<(), Error> {
@@ -1175,94 +1194,95 @@ impl<'gc> Loader<'gc> {
                 Loader::movie_loader_start(handle, uc)
             })?;
 
-            match Self::wait_for_full_response(fetch).await {
-                Ok((body, url, _status, _redirected)) if replacing_root_movie => {
-                    ContentType::sniff(&body).expect(ContentType::Swf)?;
+            let response = match fetch.await {
+                Ok(response) => response,
+                Err(response) => return movie_loader_download_failed(&player, handle, response),
+            };
+            let url = response.url().to_string();
+            let status = response.status();
+            let redirected = response.redirected();
+            let expected_len = response
+                .expected_length()
+                .ok()
+                .flatten()
+                .map(|len| len as usize);
+
+            // A movie replacing the root movie is swapped in all at once.
+            let body = download_movie(response, !replacing_root_movie, |stream| {
+                let movie = Arc::new(SwfMovie::from_stream(
+                    stream,
+                    url.clone(),
+                    loader_url.clone(),
+                    expected_len,
+                )?);
+                player.lock().unwrap().mutate_with_update_context(|uc| {
+                    Loader::movie_loader_swf(handle, uc, movie.clone(), status, redirected)
+                })?;
+                Ok(movie)
+            })
+            .await;
+
+            let body = match body {
+                Ok(Some(body)) => body,
+                Ok(None) => return Ok(()),
+                Err(error) => {
+                    return movie_loader_download_failed(
+                        &player,
+                        handle,
+                        ErrorResponse { url, error },
+                    )
+                }
+            };
+
+            if replacing_root_movie {
+                ContentType::sniff(&body).expect(ContentType::Swf)?;
+
+                let movie = SwfMovie::from_data(&body, url, loader_url)?;
+                player.lock().unwrap().mutate_with_update_context(|uc| {
+                    // Make a copy of the properties on the root, so we can put them back after replacing it
+                    let mut root_properties: IndexMap<AvmString, Value> = IndexMap::new();
+                    if let Some(root) = uc.stage.root_clip() {
+                        let root_val = root.object();
+                        if let Value::Object(root_object) = root_val {
+                            let mut activation = Activation::from_nothing(
+                                uc,
+                                ActivationIdentifier::root("unknown"),
+                                root,
+                            );
+                            for key in root_object.get_keys(&mut activation, true) {
+                                let val = root_object
+                                    .get_stored(key, &mut activation)
+                                    .unwrap_or(Value::Undefined);
+                                root_properties.insert(key, val);
+                            }
+                        }
+                    }
 
-                    let movie = SwfMovie::from_data(&body, url.to_string(), loader_url)?;
-                    player.lock().unwrap().mutate_with_update_context(|uc| {
-                        // Make a copy of the properties on the root, so we can put them back after replacing it
-                        let mut root_properties: IndexMap<AvmString, Value> = IndexMap::new();
+                    uc.replace_root_movie(movie);
+
+                    // Add the copied properties back onto the new root
+                    if !root_properties.is_empty() {
                         if let Some(root) = uc.stage.root_clip() {
-                            let root_val = root.object();
-                            if let Value::Object(root_object) = root_val {
+                            let val = root.object();
+                            if let Value::Object(clip_object) = val {
                                 let mut activation = Activation::from_nothing(
                                     uc,
                                     ActivationIdentifier::root("unknown"),
                                     root,
                                 );
-                                for key in root_object.get_keys(&mut activation, true) {
-                                    let val = root_object
-                                        .get_stored(key, &mut activation)
-                                        .unwrap_or(Value::Undefined);
-                                    root_properties.insert(key, val);
-                                }
-                            }
-                        }
-
-                        uc.replace_root_movie(movie);
-
-                        // Add the copied properties back onto the new root
-                        if !root_properties.is_empty() {
-                            if let Some(root) = uc.stage.root_clip() {
-                                let val = root.object();
-                                if let Value::Object(clip_object) = val {
-                                    let mut activation = Activation::from_nothing(
-                                        uc,
-                                        ActivationIdentifier::root("unknown"),
-                                        root,
-                                    );
-                                    for (key, val) in root_properties {
-                                        let _ = clip_object.set(key, val, &mut activation);
-                                    }
+                                for (key, val) in root_properties {
+                                    let _ = clip_object.set(key, val, &mut activation);
                                 }
                             }
                         }
-                    });
-                    return Ok(());
-                }
-                Ok((body, url, status, redirected)) => {
-                    player.lock().unwrap().mutate_with_update_context(|uc| {
-                        Loader::movie_loader_data(
-                            handle,
-                            uc,
-                            &body,
-                            url.to_string(),
-                            status,
-                            redirected,
-                            loader_url,
-                        )
-                    })?;
-                }
-                Err(response) => {
-                    tracing::error!(
-                        "Error during movie loading of {:?}: {:?}",
-                        response.url,
-                        response.error
-                    );
-                    player.lock().unwrap().update(|uc| -> Result<(), Error> {
-                        // FIXME - match Flash's error message
-
-                        let (status_code, redirected) =
-                            if let Error::HttpNotOk(_, status_code, redirected, _) = response.error
-                            {
-                                (status_code, redirected)
-                            } else {
-                                (0, false)
-                            };
-                        Loader::movie_loader_error(
-                            handle,
-                            uc,
-                            "Movie loader error",
-                            status_code,
-                            redirected,
-                            response.url,
-                        )
-                    })?;
-                }
+                    }
+                });
+                return Ok(());
             }
 
-            Ok(())
+            player.lock().unwrap().mutate_with_update_context(|uc| {
+                Loader::movie_loader_data(handle, uc, &body, url, status, redirected, loader_url)
+            })
         })
     }
 
@@ -1322,7 +1342,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
 
             let response = fetch.await.map_err(|e| e.error)?;
             let response_encoding = response.text_encoding();
@@ -1406,7 +1426,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             let response = Self::wait_for_full_response(fetch).await;
 
             // Fire the load handler.
@@ -1502,7 +1522,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             let response = Self::wait_for_full_response(fetch).await;
 
             // Fire the load handler.
@@ -1571,7 +1591,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             let response = Self::wait_for_full_response(fetch).await;
 
             player.lock().unwrap().update(|uc| {
@@ -1729,7 +1749,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             let response = Self::wait_for_full_response(fetch).await;
 
             // Fire the load handler.
@@ -1796,8 +1816,38 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
-            let response = Self::wait_for_full_response(fetch).await;
+            let fetch = player.lock().unwrap().fetch(request);
+
+            let mut response = match fetch.await {
+                Ok(response) => response,
+                Err(_response) => {
+                    return player.lock().unwrap().update(|uc| {
+                        let loader = uc.load_manager.get_loader(handle);
+                        let sound_object = match loader {
+                            Some(&Loader::SoundAvm2 { target_object, .. }) => target_object,
+                            None => return Err(Error::Cancelled),
+                            _ => return Err(Error::NotSoundLoader),
+                        };
+                        sound_object
+                            .as_sound_object()
+                            .expect("Not a sound object")
+                            .set_loading_state(SoundLoadingState::Failed);
+
+                        let mut activation = Avm2Activation::from_nothing(uc);
+
+                        // FIXME: Match the exact error message generated by Flash.
+                        let io_error_evt = Avm2EventObject::io_error_event(
+                            &mut activation,
+                            "Error #2032: Stream Error",
+                            2032,
+                        );
+
+                        Avm2::dispatch_event(uc, io_error_evt, sound_object);
+                        Ok(())
+                    });
+                }
+            };
+            let expected_length = response.expected_length().ok().flatten();
 
             player.lock().unwrap().update(|uc| {
                 let loader = uc.load_manager.get_loader(handle);
@@ -1806,61 +1856,69 @@ impl<'gc> Loader<'gc> {
                     None => return Err(Error::Cancelled),
                     _ => return Err(Error::NotSoundLoader),
                 };
-                let sound = sound_object.as_sound_object().expect("Not a sound object");
-
-                if sound.loading_state() == SoundLoadingState::Loaded {
-                    // Sound has already been loaded.
-                    return Ok(());
-                }
-
-                match response {
-                    Ok((body, _, _, _)) => {
-                        let handle = uc.audio.register_mp3(&body)?;
-                        if let Err(e) = sound.set_sound(uc, handle) {
-                            tracing::error!("Encountered AVM2 error when setting sound: {}", e);
-                        }
 
-                        let total_len = body.len();
+                let open_evt = Avm2EventObject::bare_default_event(uc, "open");
+                Avm2::dispatch_event(uc, open_evt, sound_object);
+                Ok(())
+            })?;
 
-                        // FIXME - the "open" event should be fired earlier, and not fired in case of ioerror.
-                        let mut activation = Avm2Activation::from_nothing(uc);
-                        let open_evt =
-                            Avm2EventObject::bare_default_event(activation.context, "open");
-                        Avm2::dispatch_event(activation.context, open_evt, sound_object);
+            loop {
+                let chunk = response.next_chunk().await;
+                let is_done = player.lock().unwrap().update(|uc| {
+                    let loader = uc.load_manager.get_loader(handle);
+                    let sound_object = match loader {
+                        Some(&Loader::SoundAvm2 { target_object, .. }) => target_object,
+                        None => return Err(Error::Cancelled),
+                        _ => return Err(Error::NotSoundLoader),
+                    };
+                    let sound = sound_object.as_sound_object().expect("Not a sound object");
 
-                        // FIXME - As in load_url_loader, we should fire "progress" events as we receive data,
-                        // not just at the end
-                        let progress_evt = Avm2EventObject::progress_event(
-                            &mut activation,
-                            "progress",
-                            total_len,
-                            total_len,
-                        );
+                    if sound.loading_state() != SoundLoadingState::Loading {
+                        // The download was closed, or the sound was replaced.
+                        return Ok(true);
+                    }
 
-                        Avm2::dispatch_event(activation.context, progress_evt, sound_object);
+                    match chunk {
+                        Ok(Some(mut data)) => {
+                            sound.append_stream_data(
+                                uc,
+                                &mut data,
+                                expected_length.map(|len| len as usize),
+                            );
+                            return Ok(false);
+                        }
+                        Ok(None) => match sound.finish_streaming(uc) {
+                            Ok(()) => {
+                                let complete_evt =
+                                    Avm2EventObject::bare_default_event(uc, "complete");
+                                Avm2::dispatch_event(uc, complete_evt, sound_object);
+                                return Ok(true);
+                            }
+                            Err(e) => tracing::error!("Couldn't register streamed sound: {e}"),
+                        },
+                        Err(_err) => {}
+                    }
 
-                        sound.read_and_call_id3_event(&mut activation, body.as_slice());
+                    // The download failed, or it didn't contain a valid sound.
+                    sound.close_stream(uc.gc());
+                    sound.set_loading_state(SoundLoadingState::Failed);
+                    let mut activation = Avm2Activation::from_nothing(uc);
 
-                        let complete_evt =
-                            Avm2EventObject::bare_default_event(activation.context, "complete");
-                        Avm2::dispatch_event(activation.context, complete_evt, sound_object);
-                    }
-                    Err(_err) => {
-                        let mut activation = Avm2Activation::from_nothing(uc);
+                    // FIXME: Match the exact error message generated by Flash.
+                    let io_error_evt = Avm2EventObject::io_error_event(
+                        &mut activation,
+                        "Error #2032: Stream Error",
+                        2032,
+                    );
 
-                        // FIXME: Match the exact error message generated by Flash.
-                        let io_error_evt = Avm2EventObject::io_error_event(
-                            &mut activation,
-                            "Error #2032: Stream Error",
-                            2032,
-                        );
+                    Avm2::dispatch_event(uc, io_error_evt, sound_object);
+                    Ok(true)
+                })?;
 
-                        Avm2::dispatch_event(uc, io_error_evt, sound_object);
-                    }
+                if is_done {
+                    return Ok(());
                 }
-
-                Ok(())
-            })
+            }
         })
     }
 
@@ -1881,7 +1939,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             match fetch.await {
                 Ok(mut response) => {
                     let expected_length = response.expected_length();
@@ -1984,40 +2042,12 @@ impl<'gc> Loader<'gc> {
         Ok(())
     }
 
-    /// Load data into a movie loader.
-    fn movie_loader_data(
-        handle: LoaderHandle,
-        uc: &mut UpdateContext<'gc>,
-        data: &[u8],
-        url: String,
-        status: u16,
-        redirected: bool,
-        loader_url: Option<String>,
-    ) -> Result<(), Error> {
-        let sniffed_type = ContentType::sniff(data);
-        let length = data.len();
-
-        if sniffed_type == ContentType::Unknown {
-            if let Ok(data) = extract_swz(data) {
-                return Self::movie_loader_data(
-                    handle, uc, &data, url, status, redirected, loader_url,
-                );
-            }
-        }
-        let (clip, vm_data, from_bytes) = match uc.load_manager.get_loader(handle) {
-            Some(Loader::Movie {
-                target_clip,
-                vm_data,
-                from_bytes,
-                ..
-            }) => (*target_clip, *vm_data, *from_bytes),
-            None => return Err(Error::Cancelled),
-            _ => unreachable!(),
-        };
-
-        let mut activation = Avm2Activation::from_nothing(uc);
-
-        let domain = if let MovieLoaderVMData::Avm2 {
+    /// The AVM2 domain that a movie loaded by this loader runs in.
+    fn movie_loader_domain(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        vm_data: MovieLoaderVMData<'gc>,
+    ) -> Avm2Domain<'gc> {
+        if let MovieLoaderVMData::Avm2 {
             context,
             default_domain,
             ..
@@ -2031,26 +2061,30 @@ impl<'gc> Loader<'gc> {
                 .and_then(|o| o.as_application_domain())
                 .unwrap_or_else(|| {
                     let parent_domain = default_domain;
-                    Avm2Domain::movie_domain(&mut activation, parent_domain)
+                    Avm2Domain::movie_domain(activation, parent_domain)
                 });
             domain
         } else {
             // This is necessary when the MovieLoaderData is AVM1,
             // but loaded an AVM2 SWF (mixed AVM).
             activation.context.avm2.stage_domain()
-        };
-
-        let movie = match sniffed_type {
-            ContentType::Swf => {
-                Arc::new(SwfMovie::from_data(data, url.clone(), loader_url.clone())?)
-            }
-            ContentType::Gif | ContentType::Jpeg | ContentType::Png => {
-                Arc::new(SwfMovie::from_loaded_image(url.clone(), length))
-            }
-            ContentType::Unknown => Arc::new(SwfMovie::error_movie(url.clone())),
-        };
+        }
+    }
 
-        match activation.context.load_manager.get_loader_mut(handle) {
+    /// Store the movie that a loader is about to parse, and fire the initial
+    /// `progress` event for AVM2.
+    ///
+    /// `length` is the compressed length of the loaded file.
+    fn movie_loader_parsing(
+        handle: LoaderHandle,
+        uc: &mut UpdateContext<'gc>,
+        clip: DisplayObject<'gc>,
+        vm_data: MovieLoaderVMData<'gc>,
+        movie: &Arc<SwfMovie>,
+        content_type: ContentType,
+        length: usize,
+    ) -> Result<(), Error> {
+        match uc.load_manager.get_loader_mut(handle) {
             Some(Loader::Movie {
                 movie: old,
                 loader_status,
@@ -2063,103 +2097,192 @@ impl<'gc> Loader<'gc> {
         };
 
         if let MovieLoaderVMData::Avm2 { loader_info, .. } = vm_data {
-            loader_info.set_content_type(sniffed_type);
-            let fake_movie = Arc::new(SwfMovie::fake_with_compressed_len(
-                activation.context.swf.version(),
-                data.len(),
-            ));
+            loader_info.set_content_type(content_type);
+            let fake_movie = Arc::new(SwfMovie::fake_with_compressed_len(uc.swf.version(), length));
 
             // Expose 'bytesTotal' (via the fake movie) during the first 'progress' event,
             // but nothing else (in particular, the `parameters` and `url` properties are not set
             // to their real values)
             loader_info.set_loader_stream(
                 LoaderStream::NotYetLoaded(fake_movie, Some(clip), false),
-                activation.gc(),
+                uc.gc(),
             );
 
             // Flash always fires an initial 'progress' event with
             // bytesLoaded=0 and bytesTotal set to the proper value.
             // This only seems to happen for an AVM2 event handler
-            Loader::movie_loader_progress(handle, activation.context, 0, length)?;
+            Loader::movie_loader_progress(handle, uc, 0, length)?;
 
             // Update the LoaderStream - we now have a real SWF movie and a real target clip
             // This is intentionally set *after* the first 'progress' event, to match Flash's behavior
             // (`LoaderInfo.parameters` is always empty during the first 'progress' event)
             loader_info.set_loader_stream(
                 LoaderStream::NotYetLoaded(movie.clone(), Some(clip), false),
-                activation.gc(),
+                uc.gc(),
             );
         }
 
-        match sniffed_type {
-            ContentType::Swf => {
-                let library = activation
-                    .context
-                    .library
-                    .library_for_movie_mut(movie.clone());
+        Ok(())
+    }
 
-                library.set_avm2_domain(domain);
+    /// Load an SWF into a movie loader.
+    ///
+    /// The movie may still be downloading, in which case the rest of it is
+    /// preloaded as it arrives.
+    fn movie_loader_swf(
+        handle: LoaderHandle,
+        uc: &mut UpdateContext<'gc>,
+        movie: Arc<SwfMovie>,
+        status: u16,
+        redirected: bool,
+    ) -> Result<(), Error> {
+        let (clip, vm_data, from_bytes) = match uc.load_manager.get_loader(handle) {
+            Some(Loader::Movie {
+                target_clip,
+                vm_data,
+                from_bytes,
+                ..
+            }) => (*target_clip, *vm_data, *from_bytes),
+            None => return Err(Error::Cancelled),
+            _ => unreachable!(),
+        };
 
-                if let Some(mc) = clip.as_movie_clip() {
-                    let loader_info = if let MovieLoaderVMData::Avm2 { loader_info, .. } = vm_data {
-                        Some(loader_info)
-                    } else {
-                        None
-                    };
+        let mut activation = Avm2Activation::from_nothing(uc);
 
-                    // Store our downloaded `SwfMovie` into our target `MovieClip`,
-                    // and initialize it.
+        let domain = Self::movie_loader_domain(&mut activation, vm_data);
 
-                    mc.replace_with_movie(
-                        activation.context,
-                        Some(movie.clone()),
-                        true,
-                        loader_info,
-                    );
+        Self::movie_loader_parsing(
+            handle,
+            activation.context,
+            clip,
+            vm_data,
+            &movie,
+            ContentType::Swf,
+            movie.compressed_len(),
+        )?;
 
-                    if matches!(vm_data, MovieLoaderVMData::Avm2 { .. })
-                        && !movie.is_action_script_3()
-                    {
-                        // When an AVM2 movie loads an AVM1 movie, we need to call `post_instantiation` here.
-                        mc.post_instantiation(uc, None, Instantiator::Movie, false);
+        let library = activation
+            .context
+            .library
+            .library_for_movie_mut(movie.clone());
 
-                        mc.set_depth(LOADER_INSERTED_AVM1_DEPTH);
-                    }
+        library.set_avm2_domain(domain);
 
-                    if from_bytes {
-                        mc.preload(uc, &mut ExecutionLimit::none());
-                        Loader::movie_loader_progress(
-                            handle,
-                            uc,
-                            mc.compressed_loaded_bytes() as usize,
-                            mc.compressed_total_bytes() as usize,
-                        )?;
-                        uc.post_frame_callbacks.push(PostFrameCallback {
-                            callback: Box::new(move |uc, dobj: DisplayObject<'_>| {
-                                if let Err(e) =
-                                    Loader::movie_loader_complete(handle, uc, Some(dobj), 0, false)
-                                {
-                                    tracing::error!("Error finishing loading of Loader.loadBytes movie {dobj:?}: {e:?}");
-                                }
-                            }),
-                            data: clip,
-                        });
-                    }
-                }
+        if let Some(mc) = clip.as_movie_clip() {
+            let loader_info = if let MovieLoaderVMData::Avm2 { loader_info, .. } = vm_data {
+                Some(loader_info)
+            } else {
+                None
+            };
 
-                // NOTE: Certain tests specifically expect small files to preload immediately
-                if !from_bytes {
-                    Loader::preload_tick(
-                        handle,
-                        uc,
-                        &mut ExecutionLimit::with_max_ops_and_time(10000, Duration::from_millis(1)),
-                        status,
-                        redirected,
-                    )?;
-                };
+            // Store our downloaded `SwfMovie` into our target `MovieClip`,
+            // and initialize it.
 
-                return Ok(());
+            mc.replace_with_movie(activation.context, Some(movie.clone()), true, loader_info);
+
+            if matches!(vm_data, MovieLoaderVMData::Avm2 { .. }) && !movie.is_action_script_3() {
+                // When an AVM2 movie loads an AVM1 movie, we need to call `post_instantiation` here.
+                mc.post_instantiation(uc, None, Instantiator::Movie, false);
+
+                mc.set_depth(LOADER_INSERTED_AVM1_DEPTH);
+            }
+
+            if from_bytes {
+                mc.preload(uc, &mut ExecutionLimit::none());
+                Loader::movie_loader_progress(
+                    handle,
+                    uc,
+                    mc.compressed_loaded_bytes() as usize,
+                    mc.compressed_total_bytes() as usize,
+                )?;
+                uc.post_frame_callbacks.push(PostFrameCallback {
+                    callback: Box::new(move |uc, dobj: DisplayObject<'_>| {
+                        if let Err(e) =
+                            Loader::movie_loader_complete(handle, uc, Some(dobj), 0, false)
+                        {
+                            tracing::error!(
+                                "Error finishing loading of Loader.loadBytes movie {dobj:?}: {e:?}"
+                            );
+                        }
+                    }),
+                    data: clip,
+                });
+            }
+        }
+
+        // NOTE: Certain tests specifically expect small files to preload immediately
+        if !from_bytes {
+            Loader::preload_tick(
+                handle,
+                uc,
+                &mut ExecutionLimit::with_max_ops_and_time(10000, Duration::from_millis(1)),
+                status,
+                redirected,
+            )?;
+        };
+
+        Ok(())
+    }
+
+    /// Load data into a movie loader.
+    fn movie_loader_data(
+        handle: LoaderHandle,
+        uc: &mut UpdateContext<'gc>,
+        data: &[u8],
+        url: String,
+        status: u16,
+        redirected: bool,
+        loader_url: Option<String>,
+    ) -> Result<(), Error> {
+        let sniffed_type = ContentType::sniff(data);
+        let length = data.len();
+
+        if sniffed_type == ContentType::Unknown {
+            if let Ok(data) = extract_swz(data) {
+                return Self::movie_loader_data(
+                    handle, uc, &data, url, status, redirected, loader_url,
+                );
             }
+        }
+
+        if sniffed_type == ContentType::Swf {
+            let movie = Arc::new(SwfMovie::from_data(data, url, loader_url)?);
+            return Self::movie_loader_swf(handle, uc, movie, status, redirected);
+        }
+
+        let (clip, vm_data, from_bytes) = match uc.load_manager.get_loader(handle) {
+            Some(Loader::Movie {
+                target_clip,
+                vm_data,
+                from_bytes,
+                ..
+            }) => (*target_clip, *vm_data, *from_bytes),
+            None => return Err(Error::Cancelled),
+            _ => unreachable!(),
+        };
+
+        let mut activation = Avm2Activation::from_nothing(uc);
+
+        let domain = Self::movie_loader_domain(&mut activation, vm_data);
+
+        let movie = match sniffed_type {
+            ContentType::Gif | ContentType::Jpeg | ContentType::Png => {
+                Arc::new(SwfMovie::from_loaded_image(url.clone(), length))
+            }
+            ContentType::Swf | ContentType::Unknown => Arc::new(SwfMovie::error_movie(url.clone())),
+        };
+
+        Self::movie_loader_parsing(
+            handle,
+            activation.context,
+            clip,
+            vm_data,
+            &movie,
+            sniffed_type,
+            length,
+        )?;
+
+        match sniffed_type {
             ContentType::Gif | ContentType::Jpeg | ContentType::Png => {
                 let library = activation
                     .context
@@ -2298,6 +2421,7 @@ impl<'gc> Loader<'gc> {
                     }
                 }
             }
+            ContentType::Swf => unreachable!(),
         }
 
         //TODO: content sniffing errors need to be reported somehow
@@ -2939,7 +3063,7 @@ impl<'gc> Loader<'gc> {
             // Download the data
             let req = Request::get(url.clone());
             // Doing this in two steps to prevent holding the player lock during fetch
-            let future = player.lock().unwrap().navigator().fetch(req);
+            let future = player.lock().unwrap().fetch(req);
             let download_res = Self::wait_for_full_response(future).await;
 
             // Fire the load handler.
@@ -3174,7 +3298,7 @@ impl<'gc> Loader<'gc> {
                 )),
             );
             // Doing this in two steps to prevent holding the player lock during fetch
-            let future = player.lock().unwrap().navigator().fetch(req);
+            let future = player.lock().unwrap().fetch(req);
             let result = future.await;
 
             // Fire the load handler.
@@ -3280,3 +3404,210 @@ impl<'gc> Loader<'gc> {
         })
     }
 }
+
+/// The data that the root movie starts playing from.
+enum RootMovieData<'a> {
+    /// The whole SWF file.
+    Complete(&'a [u8]),
+
+    /// The start of an SWF that is still downloading, along with the length
+    /// it's expected to have.
+    Streaming(&'a StreamDecompressor, Option<usize>),
+}
+
+/// Apply the compatibility rules for the root movie, and start playing it.
+fn start_root_movie(
+    player: &Mutex<Player>,
+    request_url: &str,
+    url: String,
+    data: RootMovieData<'_>,
+    parameters: Vec<(String, String)>,
+    on_metadata: Option<Box<dyn FnOnce(&swf::HeaderExt)>>,
+) -> Result<Arc<SwfMovie>, Error> {
+    let complete_data = match data {
+        RootMovieData::Complete(data) => Some(data),
+        RootMovieData::Streaming(..) => None,
+    };
+    player
+        .lock()
+        .unwrap()
+        .apply_movie_compatibility_rules(request_url, complete_data);
+
+    // The spoofed root movie URL takes precedence over the actual URL.
+    let swf_url = player
+        .lock()
+        .unwrap()
+        .movie_compatibility_rules()
+        .rewrite_swf_url(url);
+    let spoofed_or_swf_url = player
+        .lock()
+        .unwrap()
+        .spoofed_url()
+        .map(|u| u.to_string())
+        .unwrap_or(swf_url);
+
+    let mut movie = match data {
+        RootMovieData::Complete(data) => SwfMovie::from_data(data, spoofed_or_swf_url, None)?,
+        RootMovieData::Streaming(stream, expected_len) => {
+            SwfMovie::from_stream(stream, spoofed_or_swf_url, None, expected_len)?
+        }
+    };
+    if let Some(on_metadata) = on_metadata {
+        on_metadata(movie.header());
+    }
+    movie.append_parameters(
+        player
+            .lock()
+            .unwrap()
+            .movie_compatibility_rules()
+            .flash_vars(),
+    );
+    movie.append_parameters(parameters);
+    Ok(player.lock().unwrap().mutate_with_update_context(|uc| {
+        uc.set_root_movie(movie);
+        uc.swf.clone()
+    }))
+}
+
+/// Report a movie that couldn't be downloaded to its loader.
+fn movie_loader_download_failed(
+    player: &Mutex<Player>,
+    handle: LoaderHandle,
+    response: ErrorResponse,
+) -> Result<(), Error> {
+    tracing::error!(
+        "Error during movie loading of {:?}: {:?}",
+        response.url,
+        response.error
+    );
+    player.lock().unwrap().update(|uc| -> Result<(), Error> {
+        // FIXME - match Flash's error message
+
+        let (status_code, redirected) =
+            if let Error::HttpNotOk(_, status_code, redirected, _) = response.error {
+                (status_code, redirected)
+            } else {
+                (0, false)
+            };
+        Loader::movie_loader_error(
+            handle,
+            uc,
+            "Movie loader error",
+            status_code,
+            redirected,
+            response.url,
+        )
+    })
+}
+
+/// Poll a future once, without waiting for it.
+///
+/// Returns `None` if the future isn't ready yet, in which case it must still
+/// be awaited later.
+fn poll_now<T>(future: &mut OwnedFuture<T, Error>) -> Option<Result<T, Error>> {
+    match future
+        .as_mut()
+        .poll(&mut Context::from_waker(Waker::noop()))
+    {
+        Poll::Ready(result) => Some(result),
+        Poll::Pending => None,
+    }
+}
+
+/// Download a movie, and start it before the download is over if it's an
+/// SWF whose data is slow to arrive.
+///
+/// Once the header and the first few tags of an SWF have arrived,
+/// `start_stream` is called with them, and returns the movie that the rest of
+/// the SWF is appended to. This then returns `None` once the download is over.
+/// Otherwise, the whole body is returned, such as for images or for SWFs that
+/// arrive all at once.
+async fn download_movie(
+    mut response: Box<dyn SuccessResponse>,
+    mut can_stream: bool,
+    start_stream: impl FnOnce(&StreamDecompressor) -> Result<Arc<SwfMovie>, Error>,
+) -> Result<Option<Vec<u8>>, Error> {
+    let mut body = vec![];
+    let mut stream: Option<StreamDecompressor> = None;
+
+    loop {
+        let mut next_chunk = response.next_chunk();
+
+        // Only start the movie early if we'd otherwise have to wait, so that
+        // small and local movies still load all at once.
+        let chunk = match poll_now(&mut next_chunk) {
+            Some(chunk) => chunk,
+            None => {
+                if let Some(stream) = stream.take_if(|stream| stream.header().is_some()) {
+                    let movie = start_stream(&stream)?;
+                    stream_movie(response, next_chunk, stream, &movie).await;
+                    return Ok(None);
+                }
+                next_chunk.await
+            }
+        }?;
+
+        let Some(chunk) = chunk else {
+            return Ok(Some(body));
+        };
+
+        if let Some(decompressor) = &mut stream {
+            if let Err(error) = decompressor.push(&chunk) {
+                tracing::warn!("Can't stream movie, waiting for all of it: {error}");
+                stream = None;
+            }
+        }
+        body.extend_from_slice(&chunk);
+
+        if can_stream && body.len() >= 3 {
+            can_stream = false;
+            if ContentType::sniff(&body) == ContentType::Swf {
+                let mut decompressor = StreamDecompressor::new();
+                if decompressor.push(&body).is_ok() {
+                    stream = Some(decompressor);
+                }
+            }
+        }
+    }
+}
+
+/// Append the rest of a streamed SWF to its movie as it downloads.
+async fn stream_movie(
+    mut response: Box<dyn SuccessResponse>,
+    mut next_chunk: OwnedFuture<Option<Vec<u8>>, Error>,
+    mut stream: StreamDecompressor,
+    movie: &SwfMovie,
+) {
+    // The movie already has all the data of the stream so far.
+    let mut appended = stream.data().len();
+
+    loop {
+        match next_chunk.await {
+            Ok(Some(chunk)) => {
+                if let Err(error) = stream.push(&chunk) {
+                    tracing::error!("Error streaming movie {}: {error}", movie.url());
+                    break;
+                }
+                movie.append_data(&stream.data()[appended..]);
+                appended = stream.data().len();
+            }
+            Ok(None) => break,
+            Err(error) => {
+                tracing::error!("Error downloading movie {}: {error}", movie.url());
+                break;
+            }
+        }
+        next_chunk = response.next_chunk();
+    }
+
+    let compressed_len = stream.compressed_len();
+    match stream.finish() {
+        Ok(swf_buf) => {
+            if let Some(rest) = swf_buf.data.get(appended..) {
+                movie.append_data(rest);
+            }
+        }
+        Err(error) => tracing::error!("Error streaming movie {}: {error}", movie.url()),
+    }
+    movie.finish_loading(compressed_len);
+}
@@ -1,3 +1,5 @@
+mod rtmp;
+
 use crate::avm1::globals::netconnection::NetConnection as Avm1NetConnectionObject;
 use crate::avm1::Object as Avm1Object;
 use crate::avm2::object::{
@@ -7,15 +9,18 @@ use crate::avm2::{Activation as Avm2Activation, Avm2, EventObject as Avm2EventOb
 use crate::backend::navigator::{ErrorResponse, NavigatorBackend, OwnedFuture, Request};
 use crate::context::UpdateContext;
 use crate::loader::Error;
+use crate::net_connection::rtmp::{amf_string, Rtmp, RtmpEvent};
+use crate::streams::NetStream;
 use crate::Player;
 use flash_lso::packet::{Header, Message, Packet};
-use flash_lso::types::{AMFVersion, Value as AmfValue};
+use flash_lso::types::{AMFVersion, Element, Value as AmfValue};
 use gc_arena::collect::Trace;
 use gc_arena::{Collect, DynamicRoot, Gc, Rootable};
 use slotmap::{new_key_type, SlotMap};
 use std::fmt::{Debug, Formatter};
 use std::rc::Rc;
 use std::sync::{Mutex, Weak};
+use url::Url;
 
 new_key_type! {
     pub struct NetConnectionHandle;
@@ -75,7 +80,7 @@ pub enum NetConnectionObject<'gc> {
     Avm1(Avm1Object<'gc>),
 }
 
-impl NetConnectionObject<'_> {
+impl<'gc> NetConnectionObject<'gc> {
     pub fn set_handle(&self, handle: Option<NetConnectionHandle>) -> Option<NetConnectionHandle> {
         match self {
             NetConnectionObject::Avm2(object) => object.set_handle(handle),
@@ -88,6 +93,50 @@ impl NetConnectionObject<'_> {
             }
         }
     }
+
+    /// Dispatch a `netStatus` event with the given info object.
+    pub fn send_status(&self, context: &mut UpdateContext<'gc>, info: &AmfValue) {
+        match self {
+            NetConnectionObject::Avm2(object) => {
+                if let Err(e) = object.send_status(context, info) {
+                    tracing::error!("Unhandled error sending connection status: {e}");
+                }
+            }
+            NetConnectionObject::Avm1(object) => {
+                if let Err(e) = Avm1NetConnectionObject::send_callback(
+                    context,
+                    *object,
+                    ResponderCallback::Status,
+                    info,
+                ) {
+                    tracing::error!("Unhandled error sending connection status: {e}");
+                }
+            }
+        }
+    }
+
+    /// Call a method on the client of this connection, as requested by the server.
+    pub fn call_client(
+        &self,
+        context: &mut UpdateContext<'gc>,
+        method: &str,
+        arguments: &[Rc<AmfValue>],
+    ) {
+        match self {
+            NetConnectionObject::Avm2(object) => {
+                if let Err(e) = object.call_client(context, method, arguments) {
+                    tracing::error!("Unhandled error calling client method {method}: {e}");
+                }
+            }
+            NetConnectionObject::Avm1(object) => {
+                if let Err(e) =
+                    Avm1NetConnectionObject::call_client(context, *object, method, arguments)
+                {
+                    tracing::error!("Unhandled error calling client method {method}: {e}");
+                }
+            }
+        }
+    }
 }
 
 impl<'gc> From<Avm2NetConnectionObject<'gc>> for NetConnectionObject<'gc> {
@@ -131,6 +180,7 @@ impl<'gc> NetConnections<'gc> {
         let target = target.into();
         let connection = NetConnection {
             object: target,
+            streams: vec![],
             protocol: NetConnectionProtocol::Local,
         };
         let handle = context.net_connections.connections.insert(connection);
@@ -171,6 +221,7 @@ impl<'gc> NetConnections<'gc> {
         let target = target.into();
         let connection = NetConnection {
             object: target,
+            streams: vec![],
             protocol: NetConnectionProtocol::FlashRemoting(FlashRemoting {
                 url,
                 headers: vec![],
@@ -186,6 +237,68 @@ impl<'gc> NetConnections<'gc> {
         // No open event here
     }
 
+    pub fn connect_to_rtmp<O: Into<NetConnectionObject<'gc>>>(
+        context: &mut UpdateContext<'gc>,
+        target: O,
+        url: &str,
+        object_encoding: AMFVersion,
+        arguments: Vec<Rc<AmfValue>>,
+    ) {
+        let target = target.into();
+        let url = match Url::parse(url) {
+            Ok(url) if url.scheme() == "rtmp" && url.has_host() => url,
+            _ => {
+                tracing::warn!("Unsupported RTMP URL {url}");
+                target.send_status(
+                    context,
+                    &rtmp::status_info("NetConnection.Connect.Failed", "error"),
+                );
+                return;
+            }
+        };
+
+        let string = |value: &str| Rc::new(AmfValue::String(value.to_string()));
+        let number = |value: f64| Rc::new(AmfValue::Number(value));
+        let command_object = vec![
+            Element::new(
+                "flashVer",
+                string(&context.system.get_version_string(context.player_version)),
+            ),
+            Element::new("swfUrl", string(context.swf.url())),
+            Element::new("fpad", Rc::new(AmfValue::Bool(false))),
+            Element::new("capabilities", number(239.0)),
+            Element::new("audioCodecs", number(3575.0)),
+            Element::new("videoCodecs", number(252.0)),
+            Element::new("videoFunction", number(1.0)),
+            Element::new("pageUrl", Rc::new(AmfValue::Undefined)),
+            Element::new(
+                "objectEncoding",
+                number(match object_encoding {
+                    AMFVersion::AMF0 => 0.0,
+                    AMFVersion::AMF3 => 3.0,
+                }),
+            ),
+        ];
+
+        let connection = NetConnection {
+            object: target,
+            streams: vec![],
+            protocol: NetConnectionProtocol::Rtmp(Rtmp::connect(
+                context.navigator,
+                &url,
+                command_object,
+                arguments,
+            )),
+        };
+        let handle = context.net_connections.connections.insert(connection);
+
+        if let Some(existing_handle) = target.set_handle(Some(handle)) {
+            NetConnections::close(context, existing_handle, false);
+        }
+
+        // The connection status arrives once the server answered.
+    }
+
     pub fn close(context: &mut UpdateContext<'gc>, handle: NetConnectionHandle, is_explicit: bool) {
         let Some(connection) = context.net_connections.connections.remove(handle) else {
             return;
@@ -240,8 +353,106 @@ impl<'gc> NetConnections<'gc> {
     }
 
     pub fn update_connections(context: &mut UpdateContext<'gc>) {
+        let mut rtmp_events = vec![];
+
         for (handle, connection) in context.net_connections.connections.iter_mut() {
             connection.update(handle, context.navigator, context.player.clone());
+
+            if let NetConnectionProtocol::Rtmp(rtmp) = &mut connection.protocol {
+                rtmp_events.extend(rtmp.poll().into_iter().map(|event| (handle, event)));
+            }
+        }
+
+        for (handle, event) in rtmp_events {
+            NetConnections::handle_rtmp_event(context, handle, event);
+        }
+    }
+
+    fn handle_rtmp_event(
+        context: &mut UpdateContext<'gc>,
+        handle: NetConnectionHandle,
+        event: RtmpEvent,
+    ) {
+        let Some(connection) = context.net_connections.connections.get(handle) else {
+            // Closed while processing earlier events.
+            return;
+        };
+        let object = connection.object;
+
+        match event {
+            RtmpEvent::Failed(info) => {
+                context.net_connections.connections.remove(handle);
+                object.set_handle(None);
+                object.send_status(context, &info);
+            }
+            RtmpEvent::Status(info) => object.send_status(context, &info),
+            RtmpEvent::Response(responder_handle, callback, message) => {
+                responder_handle.call(context, callback, message);
+            }
+            RtmpEvent::ClientCall(method, arguments) => {
+                object.call_client(context, &method, &arguments);
+            }
+            RtmpEvent::StreamStatus(index, info) => {
+                if let Some(stream) = connection.streams.get(index).copied() {
+                    let values = match &*info {
+                        AmfValue::Object(_, elements, _) => elements
+                            .iter()
+                            .filter_map(|e| amf_string(e.value()).map(|value| (e.name(), value)))
+                            .collect(),
+                        _ => vec![],
+                    };
+                    stream.trigger_status_event(context, values);
+                }
+            }
+            RtmpEvent::StreamData {
+                stream,
+                tag_type,
+                timestamp,
+                data,
+            } => {
+                if let Some(stream) = connection.streams.get(stream).copied() {
+                    stream.append_rtmp_data(context, tag_type, timestamp, &data);
+                }
+            }
+            RtmpEvent::Closed => NetConnections::close(context, handle, false),
+        }
+    }
+
+    /// Create a server-side stream for a `NetStream` on the given connection.
+    ///
+    /// Returns the index the stream is known by on this connection, or `None`
+    /// if this connection does not carry streams.
+    pub fn create_stream(
+        &mut self,
+        handle: NetConnectionHandle,
+        stream: NetStream<'gc>,
+    ) -> Option<usize> {
+        let connection = self.connections.get_mut(handle)?;
+        match &mut connection.protocol {
+            NetConnectionProtocol::Rtmp(rtmp) => {
+                let index = rtmp.create_stream();
+                debug_assert_eq!(index, connection.streams.len());
+                connection.streams.push(stream);
+                Some(index)
+            }
+            NetConnectionProtocol::Local | NetConnectionProtocol::FlashRemoting(_) => None,
+        }
+    }
+
+    /// Send a command (such as `play`) for a stream created with `create_stream`.
+    pub fn send_stream_command(
+        &mut self,
+        handle: NetConnectionHandle,
+        index: usize,
+        command: &str,
+        arguments: Vec<AmfValue>,
+    ) {
+        if let Some(NetConnection {
+            protocol: NetConnectionProtocol::Rtmp(rtmp),
+            ..
+        }) = self.connections.get_mut(handle)
+        {
+            rtmp.stream_command(index, command, arguments.into_iter().map(Rc::new).collect());
         }
     }
 
@@ -341,6 +552,10 @@ impl<'gc> NetConnections<'gc> {
 pub struct NetConnection<'gc> {
     object: NetConnectionObject<'gc>,
 
+    /// The `NetStream`s created on this connection, in the order of their
+    /// creation.
+    streams: Vec<NetStream<'gc>>,
+
     #[collect(require_static)]
     protocol: NetConnectionProtocol,
 }
@@ -350,20 +565,23 @@ impl NetConnection<'_> {
         match self.protocol {
             NetConnectionProtocol::Local => true,
             NetConnectionProtocol::FlashRemoting(_) => false,
+            NetConnectionProtocol::Rtmp(ref rtmp) => rtmp.is_connected(),
         }
     }
 
     pub fn connected_proxy_type(&self) -> Option<&'static str> {
         match self.protocol {
             NetConnectionProtocol::Local => Some("none"),
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => Some("none"),
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
     pub fn far_id(&self) -> Option<&'static str> {
         match self.protocol {
             NetConnectionProtocol::Local => Some(""),
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => Some(""),
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
@@ -372,14 +590,18 @@ impl NetConnection<'_> {
             NetConnectionProtocol::Local => {
                 Some("0000000000000000000000000000000000000000000000000000000000000000")
             }
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => {
+                Some("0000000000000000000000000000000000000000000000000000000000000000")
+            }
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
     pub fn near_id(&self) -> Option<&'static str> {
         match self.protocol {
             NetConnectionProtocol::Local => Some(""),
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => Some(""),
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
@@ -388,14 +610,18 @@ impl NetConnection<'_> {
             NetConnectionProtocol::Local => {
                 Some("0000000000000000000000000000000000000000000000000000000000000000")
             }
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => {
+                Some("0000000000000000000000000000000000000000000000000000000000000000")
+            }
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
     pub fn protocol(&self) -> Option<&'static str> {
         match self.protocol {
             NetConnectionProtocol::Local => Some("rtmp"),
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(ref rtmp) if rtmp.is_connected() => Some("rtmp"),
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
@@ -403,13 +629,15 @@ impl NetConnection<'_> {
         match &self.protocol {
             NetConnectionProtocol::Local => Some("null".to_string()), // Yes, it's a string "null", not a real null.
             NetConnectionProtocol::FlashRemoting(remoting) => Some(remoting.url.to_string()),
+            NetConnectionProtocol::Rtmp(rtmp) => Some(rtmp.url().to_string()),
         }
     }
 
     pub fn using_tls(&self) -> Option<bool> {
         match &self.protocol {
             NetConnectionProtocol::Local => Some(false),
-            NetConnectionProtocol::FlashRemoting(_) => None,
+            NetConnectionProtocol::Rtmp(rtmp) if rtmp.is_connected() => Some(false),
+            NetConnectionProtocol::FlashRemoting(_) | NetConnectionProtocol::Rtmp(_) => None,
         }
     }
 
@@ -424,6 +652,14 @@ impl NetConnection<'_> {
             NetConnectionProtocol::FlashRemoting(remoting) => {
                 remoting.send(command, responder_handle, message)
             }
+            NetConnectionProtocol::Rtmp(rtmp) => {
+                // RTMP sends the arguments as separate values.
+                let arguments = match message {
+                    AmfValue::StrictArray(_, arguments) => arguments,
+                    other => vec![Rc::new(other)],
+                };
+                rtmp.call(command, responder_handle, arguments)
+            }
         }
     }
 
@@ -440,6 +676,9 @@ impl NetConnection<'_> {
                     navigator.spawn_future(remoting.flush_queue(self_handle, player));
                 }
             }
+            // Incoming data is handled by `NetConnections::update_connections`,
+            // and outgoing data is sent immediately.
+            NetConnectionProtocol::Rtmp(_) => {}
         }
     }
 
@@ -449,6 +688,7 @@ impl NetConnection<'_> {
             NetConnectionProtocol::FlashRemoting(remoting) => {
                 remoting.set_header(header);
             }
+            NetConnectionProtocol::Rtmp(_) => {}
         }
     }
 }
@@ -460,6 +700,9 @@ pub enum NetConnectionProtocol {
 
     /// Flash Remoting protocol, caused by connecting to a `http://` address.
     FlashRemoting(FlashRemoting),
+
+    /// Real Time Messaging Protocol, caused by connecting to a `rtmp://` address.
+    Rtmp(Rtmp),
 }
 
 #[derive(Debug)]
@@ -519,7 +762,7 @@ impl FlashRemoting {
             let bytes = flash_lso::packet::write::write_to_bytes(&packet, true)
                 .expect("Must be able to serialize a packet");
             let request = Request::post(url, Some((bytes, "application/x-amf".to_string())));
-            let fetch = player.lock().unwrap().navigator().fetch(request);
+            let fetch = player.lock().unwrap().fetch(request);
             let response: Result<_, ErrorResponse> = async {
                 let response = fetch.await?;
                 let url = response.url().to_string();
@@ -0,0 +1,1653 @@
+//! RTMP client transport, used by `NetConnection`s connected to `rtmp://` URLs.
+//!
+//! After a short handshake, both peers exchange "chunks" which get reassembled
+//! into messages. Protocol control messages are handled here directly, AMF
+//! commands are turned into [`RtmpEvent`]s for the player to dispatch, and
+//! audio/video/data messages are handed to the `NetStream` they belong to.
+//! The bodies of those media messages share their layout with FLV tag bodies.
+
+use crate::backend::navigator::NavigatorBackend;
+use crate::net_connection::{ResponderCallback, ResponderHandle};
+use crate::socket::{ConnectionState, SocketAction, SocketHandle};
+use async_channel::{unbounded, Receiver, Sender};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use flash_lso::amf0::read::AMF0Decoder;
+use flash_lso::types::{Element, ObjectId, Value as AmfValue};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read};
+use std::rc::Rc;
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+
+/// The only version of the protocol Flash Player ever spoke.
+const RTMP_VERSION: u8 = 3;
+
+/// The size of the C1/C2 and S1/S2 handshake packets.
+const HANDSHAKE_SIZE: usize = 1536;
+
+const DEFAULT_PORT: u16 = 1935;
+
+/// The chunk size both ends assume until told otherwise.
+const DEFAULT_CHUNK_SIZE: usize = 128;
+
+/// How long we wait for the server to accept the TCP connection.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Chunk stream used for protocol control messages.
+const CONTROL_CHUNK_STREAM: u32 = 2;
+
+/// Chunk stream used for `NetConnection` commands.
+const COMMAND_CHUNK_STREAM: u32 = 3;
+
+/// Chunk stream used for `NetStream` commands.
+const STREAM_CHUNK_STREAM: u32 = 8;
+
+/// The transaction ID of the `connect` command.
+const CONNECT_TRANSACTION: u32 = 1;
+
+mod message_type {
+    pub const SET_CHUNK_SIZE: u8 = 1;
+    pub const ABORT: u8 = 2;
+    pub const ACKNOWLEDGEMENT: u8 = 3;
+    pub const USER_CONTROL: u8 = 4;
+    pub const WINDOW_ACK_SIZE: u8 = 5;
+    pub const SET_PEER_BANDWIDTH: u8 = 6;
+    pub const AUDIO: u8 = 8;
+    pub const VIDEO: u8 = 9;
+    pub const DATA_AMF3: u8 = 15;
+    pub const SHARED_OBJECT_AMF3: u8 = 16;
+    pub const COMMAND_AMF3: u8 = 17;
+    pub const DATA_AMF0: u8 = 18;
+    pub const SHARED_OBJECT_AMF0: u8 = 19;
+    pub const COMMAND_AMF0: u8 = 20;
+    pub const AGGREGATE: u8 = 22;
+}
+
+mod user_control {
+    pub const STREAM_BEGIN: u16 = 0;
+    pub const STREAM_EOF: u16 = 1;
+    pub const STREAM_DRY: u16 = 2;
+    pub const STREAM_IS_RECORDED: u16 = 4;
+    pub const PING_REQUEST: u16 = 6;
+    pub const PING_RESPONSE: u16 = 7;
+}
+
+/// The FLV tag type used for script data, which RTMP data messages map onto.
+pub const FLV_SCRIPT_TAG: u8 = 18;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RtmpError {
+    #[error("Unsupported RTMP version {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Chunk on stream {0} continues a message that was never started")]
+    MissingChunkHeader(u32),
+
+    #[error("Invalid chunk size {0}")]
+    InvalidChunkSize(u32),
+}
+
+/// A fully reassembled RTMP message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RtmpMessage {
+    pub type_id: u8,
+    pub stream_id: u32,
+    pub timestamp: u32,
+    pub payload: Vec<u8>,
+}
+
+/// The message header most recently seen on a given chunk stream.
+///
+/// Chunks with a compressed header inherit missing fields from here.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChunkHeader {
+    timestamp: u32,
+    timestamp_delta: u32,
+    length: usize,
+    type_id: u8,
+    stream_id: u32,
+    has_extended_timestamp: bool,
+}
+
+#[derive(Debug, Default)]
+struct ChunkStream {
+    header: ChunkHeader,
+    payload: Vec<u8>,
+}
+
+/// Reassembles incoming chunks into messages.
+#[derive(Debug)]
+pub struct ChunkReader {
+    chunk_size: usize,
+    streams: HashMap<u32, ChunkStream>,
+}
+
+impl Default for ChunkReader {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            streams: HashMap::new(),
+        }
+    }
+}
+
+impl ChunkReader {
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Drop a partially received message, as requested by an Abort message.
+    pub fn abort(&mut self, chunk_stream_id: u32) {
+        if let Some(stream) = self.streams.get_mut(&chunk_stream_id) {
+            stream.payload.clear();
+        }
+    }
+
+    /// Read a single chunk from the start of `data`.
+    ///
+    /// Returns `Ok(None)` if `data` does not yet contain a complete chunk, in
+    /// which case no state is changed. Otherwise, returns how many bytes were
+    /// consumed, along with the message this chunk completed (if any).
+    pub fn read_chunk(
+        &mut self,
+        data: &[u8],
+    ) -> Result<Option<(usize, Option<RtmpMessage>)>, RtmpError> {
+        let mut reader = data;
+        match self.read_chunk_inner(&mut reader) {
+            Ok(Some(message)) => Ok(Some((data.len() - reader.len(), message))),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn read_chunk_inner(
+        &mut self,
+        reader: &mut &[u8],
+    ) -> Result<Option<Option<RtmpMessage>>, RtmpError> {
+        macro_rules! try_read {
+            ($e:expr) => {
+                match $e {
+                    Ok(value) => value,
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => unreachable!("Reading from a slice can only fail at its end: {e}"),
+                }
+            };
+        }
+
+        let first = try_read!(reader.read_u8());
+        let format = first >> 6;
+        let chunk_stream_id = match first & 0x3F {
+            0 => try_read!(reader.read_u8()) as u32 + 64,
+            1 => try_read!(reader.read_u16::<LittleEndian>()) as u32 + 64,
+            id => id as u32,
+        };
+
+        let existing = self.streams.get(&chunk_stream_id);
+        if format != 0 && existing.is_none() {
+            return Err(RtmpError::MissingChunkHeader(chunk_stream_id));
+        }
+        let mut header = existing.map(|s| s.header).unwrap_or_default();
+        let received = existing.map(|s| s.payload.len()).unwrap_or_default();
+        let starts_message = received == 0;
+
+        match format {
+            0 => {
+                let timestamp = try_read!(reader.read_u24::<BigEndian>());
+                header.length = try_read!(reader.read_u24::<BigEndian>()) as usize;
+                header.type_id = try_read!(reader.read_u8());
+                header.stream_id = try_read!(reader.read_u32::<LittleEndian>());
+                header.has_extended_timestamp = timestamp == 0xFFFFFF;
+                header.timestamp = if header.has_extended_timestamp {
+                    try_read!(reader.read_u32::<BigEndian>())
+                } else {
+                    timestamp
+                };
+                header.timestamp_delta = 0;
+            }
+            1 | 2 => {
+                let mut delta = try_read!(reader.read_u24::<BigEndian>());
+                if format == 1 {
+                    header.length = try_read!(reader.read_u24::<BigEndian>()) as usize;
+                    header.type_id = try_read!(reader.read_u8());
+                }
+                header.has_extended_timestamp = delta == 0xFFFFFF;
+                if header.has_extended_timestamp {
+                    delta = try_read!(reader.read_u32::<BigEndian>());
+                }
+                header.timestamp_delta = delta;
+                header.timestamp = header.timestamp.wrapping_add(delta);
+            }
+            _ => {
+                if header.has_extended_timestamp {
+                    let _timestamp = try_read!(reader.read_u32::<BigEndian>());
+                }
+                if starts_message {
+                    header.timestamp = header.timestamp.wrapping_add(header.timestamp_delta);
+                }
+            }
+        }
+
+        let chunk_length = header
+            .length
+            .saturating_sub(if format == 3 { received } else { 0 })
+            .min(self.chunk_size);
+        if reader.len() < chunk_length {
+            return Ok(None);
+        }
+        let (chunk, rest) = reader.split_at(chunk_length);
+        *reader = rest;
+
+        let stream = self.streams.entry(chunk_stream_id).or_default();
+        if format != 3 {
+            // A new header always starts a new message.
+            stream.payload.clear();
+        }
+        stream.header = header;
+        stream.payload.extend_from_slice(chunk);
+
+        if stream.payload.len() >= header.length {
+            Ok(Some(Some(RtmpMessage {
+                type_id: header.type_id,
+                stream_id: header.stream_id,
+                timestamp: header.timestamp,
+                payload: std::mem::take(&mut stream.payload),
+            })))
+        } else {
+            Ok(Some(None))
+        }
+    }
+}
+
+/// Split `message` into chunks of at most `chunk_size` bytes of payload.
+///
+/// The first chunk always carries a full header; the rest are continuations.
+pub fn write_chunks(
+    out: &mut Vec<u8>,
+    chunk_size: usize,
+    chunk_stream_id: u32,
+    message: &RtmpMessage,
+) {
+    debug_assert!((2..64).contains(&chunk_stream_id));
+
+    let extended_timestamp = message.timestamp >= 0xFFFFFF;
+
+    out.push(chunk_stream_id as u8);
+    out.write_u24::<BigEndian>(message.timestamp.min(0xFFFFFF))
+        .expect("Writing to a Vec cannot fail");
+    out.write_u24::<BigEndian>(message.payload.len() as u32)
+        .expect("Writing to a Vec cannot fail");
+    out.push(message.type_id);
+    out.write_u32::<LittleEndian>(message.stream_id)
+        .expect("Writing to a Vec cannot fail");
+    if extended_timestamp {
+        out.write_u32::<BigEndian>(message.timestamp)
+            .expect("Writing to a Vec cannot fail");
+    }
+
+    let mut chunks = message.payload.chunks(chunk_size).peekable();
+    while let Some(chunk) = chunks.next() {
+        out.extend_from_slice(chunk);
+        if chunks.peek().is_some() {
+            out.push(0xC0 | chunk_stream_id as u8);
+            if extended_timestamp {
+                out.write_u32::<BigEndian>(message.timestamp)
+                    .expect("Writing to a Vec cannot fail");
+            }
+        }
+    }
+}
+
+/// Serialize a value in AMF0.
+///
+/// Values that have no AMF0 representation (such as `ByteArray`s) are
+/// written as `undefined`.
+pub fn write_amf0(out: &mut Vec<u8>, value: &AmfValue) {
+    fn write_string(out: &mut Vec<u8>, value: &str) {
+        out.write_u16::<BigEndian>(value.len().min(u16::MAX as usize) as u16)
+            .expect("Writing to a Vec cannot fail");
+        out.extend_from_slice(&value.as_bytes()[..value.len().min(u16::MAX as usize)]);
+    }
+
+    fn write_properties(out: &mut Vec<u8>, elements: &[Element]) {
+        for element in elements {
+            write_string(out, element.name());
+            write_amf0(out, element.value());
+        }
+        out.extend_from_slice(&[0, 0, 9]);
+    }
+
+    match value {
+        AmfValue::Number(number) => {
+            out.push(0x00);
+            out.write_f64::<BigEndian>(*number)
+                .expect("Writing to a Vec cannot fail");
+        }
+        AmfValue::Integer(number) => write_amf0(out, &AmfValue::Number(*number as f64)),
+        AmfValue::Bool(value) => {
+            out.push(0x01);
+            out.push(*value as u8);
+        }
+        AmfValue::String(value) if value.len() <= u16::MAX as usize => {
+            out.push(0x02);
+            write_string(out, value);
+        }
+        AmfValue::String(value) => {
+            out.push(0x0C);
+            out.write_u32::<BigEndian>(value.len() as u32)
+                .expect("Writing to a Vec cannot fail");
+            out.extend_from_slice(value.as_bytes());
+        }
+        AmfValue::Object(_, elements, Some(class)) if !class.name.is_empty() => {
+            out.push(0x10);
+            write_string(out, &class.name);
+            write_properties(out, elements);
+        }
+        AmfValue::Object(_, elements, _) => {
+            out.push(0x03);
+            write_properties(out, elements);
+        }
+        AmfValue::Null => out.push(0x05),
+        AmfValue::ECMAArray(_, dense, sparse, length) => {
+            out.push(0x08);
+            out.write_u32::<BigEndian>(*length)
+                .expect("Writing to a Vec cannot fail");
+            for (index, value) in dense.iter().enumerate() {
+                write_string(out, &index.to_string());
+                write_amf0(out, value);
+            }
+            write_properties(out, sparse);
+        }
+        AmfValue::StrictArray(_, values) => {
+            out.push(0x0A);
+            out.write_u32::<BigEndian>(values.len() as u32)
+                .expect("Writing to a Vec cannot fail");
+            for value in values {
+                write_amf0(out, value);
+            }
+        }
+        AmfValue::Date(time, _) => {
+            // The timezone is reserved and should always be zero.
+            out.push(0x0B);
+            out.write_f64::<BigEndian>(*time)
+                .expect("Writing to a Vec cannot fail");
+            out.write_u16::<BigEndian>(0)
+                .expect("Writing to a Vec cannot fail");
+        }
+        AmfValue::XML(content, _) => {
+            out.push(0x0F);
+            out.write_u32::<BigEndian>(content.len() as u32)
+                .expect("Writing to a Vec cannot fail");
+            out.extend_from_slice(content.as_bytes());
+        }
+        AmfValue::AMF3(value) => write_amf0(out, value),
+        _ => out.push(0x06),
+    }
+}
+
+/// Read every AMF0 value in `data`, stopping at the first malformed one.
+pub fn read_amf0_values(mut data: &[u8]) -> Vec<Rc<AmfValue>> {
+    let mut decoder = AMF0Decoder::default();
+    let mut values = Vec::new();
+    while !data.is_empty() {
+        match decoder.parse_single_element(data) {
+            Ok((rest, value)) => {
+                let value: &AmfValue = &value;
+                values.push(Rc::new(value.clone()));
+                data = rest;
+            }
+            Err(_) => break,
+        }
+    }
+    values
+}
+
+/// Look up a named property of an AMF object or associative array.
+pub fn amf_property<'a>(value: &'a AmfValue, name: &str) -> Option<&'a AmfValue> {
+    match value {
+        AmfValue::Object(_, elements, _) | AmfValue::ECMAArray(_, _, elements, _) => elements
+            .iter()
+            .find(|e| e.name() == name)
+            .map(Element::value),
+        AmfValue::AMF3(value) => amf_property(value, name),
+        _ => None,
+    }
+}
+
+pub fn amf_string(value: &AmfValue) -> Option<&str> {
+    match value {
+        AmfValue::String(value) => Some(value),
+        AmfValue::AMF3(value) => amf_string(value),
+        _ => None,
+    }
+}
+
+pub fn amf_number(value: &AmfValue) -> Option<f64> {
+    match value {
+        AmfValue::Number(value) => Some(*value),
+        AmfValue::Integer(value) => Some(*value as f64),
+        AmfValue::AMF3(value) => amf_number(value),
+        _ => None,
+    }
+}
+
+/// Build an info object for a `netStatus` event.
+pub fn status_info(code: &str, level: &str) -> AmfValue {
+    AmfValue::Object(
+        ObjectId::INVALID,
+        vec![
+            Element::new("code", Rc::new(AmfValue::String(code.to_string()))),
+            Element::new("level", Rc::new(AmfValue::String(level.to_string()))),
+        ],
+        None,
+    )
+}
+
+/// Build the AMF0 body of a command message.
+pub fn encode_command(name: &str, transaction_id: u32, arguments: &[Rc<AmfValue>]) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_amf0(&mut out, &AmfValue::String(name.to_string()));
+    write_amf0(&mut out, &AmfValue::Number(transaction_id as f64));
+    for argument in arguments {
+        write_amf0(&mut out, argument);
+    }
+    out
+}
+
+/// Build the C0 and C1 handshake packets.
+///
+/// C1 consists of our epoch, four zero bytes and 1528 bytes of "random"
+/// data. Servers only ever echo this data back, so we keep it zeroed to stay
+/// deterministic.
+pub fn handshake_c0_c1() -> Vec<u8> {
+    let mut out = vec![0; HANDSHAKE_SIZE + 1];
+    out[0] = RTMP_VERSION;
+    out
+}
+
+/// Things that happened on an RTMP connection which the player must react to.
+#[derive(Debug)]
+pub enum RtmpEvent {
+    /// The connection could not be established at all.
+    ///
+    /// This carries the info object of the `netStatus` event to dispatch;
+    /// no `NetConnection.Connect.Closed` event should follow.
+    Failed(Rc<AmfValue>),
+
+    /// A `netStatus` event for the `NetConnection`.
+    Status(Rc<AmfValue>),
+
+    /// The response to a `NetConnection.call`.
+    Response(ResponderHandle, ResponderCallback, Rc<AmfValue>),
+
+    /// The server called a method on the `NetConnection`'s client.
+    ClientCall(String, Vec<Rc<AmfValue>>),
+
+    /// A `netStatus` event for the stream at the given index.
+    StreamStatus(usize, Rc<AmfValue>),
+
+    /// An audio, video or script data payload for the stream at the given
+    /// index, labeled with its FLV tag type and timestamp.
+    StreamData {
+        stream: usize,
+        tag_type: u8,
+        timestamp: u32,
+        data: Vec<u8>,
+    },
+
+    /// The connection was closed, by either end.
+    Closed,
+}
+
+#[derive(Debug)]
+enum Transaction {
+    Connect,
+    CreateStream(usize),
+    Call(Option<ResponderHandle>),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RtmpState {
+    /// Waiting for the socket to open.
+    Connecting,
+
+    /// Waiting for S0, S1 and S2.
+    Handshaking,
+
+    /// Handshake done, exchanging chunks.
+    Open,
+
+    /// The socket is gone.
+    Closed,
+}
+
+/// A `NetStream` created on this connection.
+#[derive(Debug, Default)]
+struct RtmpStream {
+    /// The message stream ID assigned by the server, once `createStream`
+    /// returned.
+    id: Option<u32>,
+
+    /// Commands issued before the server assigned an ID.
+    queued_commands: Vec<(String, Vec<Rc<AmfValue>>)>,
+}
+
+/// A client connection to an RTMP server.
+#[derive(Debug)]
+pub struct Rtmp {
+    url: String,
+
+    state: RtmpState,
+
+    /// Whether the server accepted our `connect` command.
+    is_connected: bool,
+
+    /// The full `connect` command to send once the handshake is over.
+    connect_command: Vec<u8>,
+
+    /// Outgoing data for the socket.
+    socket_sender: Sender<Vec<u8>>,
+
+    /// Incoming events from the socket.
+    socket_receiver: Receiver<SocketAction>,
+
+    /// Received bytes that are not yet a full chunk or handshake packet.
+    read_buffer: Vec<u8>,
+
+    chunk_reader: ChunkReader,
+
+    /// The total number of bytes received, for acknowledgements.
+    bytes_received: u32,
+
+    /// The value of `bytes_received` when we last sent an acknowledgement.
+    last_acknowledgement: u32,
+
+    /// How many bytes the server wants us to receive between acknowledgements.
+    window_ack_size: u32,
+
+    next_transaction_id: u32,
+
+    transactions: HashMap<u32, Transaction>,
+
+    streams: Vec<RtmpStream>,
+}
+
+impl Rtmp {
+    /// Start connecting to the RTMP server described by `url`.
+    ///
+    /// `command_object` contains the properties of the `connect` command
+    /// object other than those derived from the URL, and `arguments` are
+    /// the extra arguments passed to `NetConnection.connect`.
+    pub fn connect(
+        navigator: &mut dyn NavigatorBackend,
+        url: &Url,
+        command_object: Vec<Element>,
+        arguments: Vec<Rc<AmfValue>>,
+    ) -> Self {
+        let (socket_sender, data_receiver) = unbounded();
+        let (action_sender, socket_receiver) = unbounded();
+
+        // The socket events are delivered to our own channel, so there is no
+        // need for a handle from the `Sockets` manager.
+        navigator.connect_socket(
+            url.host_str().unwrap_or_default().to_string(),
+            url.port().unwrap_or(DEFAULT_PORT),
+            CONNECT_TIMEOUT,
+            SocketHandle::default(),
+            data_receiver,
+            action_sender,
+        );
+
+        Self::new(
+            url,
+            command_object,
+            arguments,
+            socket_sender,
+            socket_receiver,
+        )
+    }
+
+    fn new(
+        url: &Url,
+        mut command_object: Vec<Element>,
+        arguments: Vec<Rc<AmfValue>>,
+        socket_sender: Sender<Vec<u8>>,
+        socket_receiver: Receiver<SocketAction>,
+    ) -> Self {
+        let app = url.path().trim_start_matches('/').to_string();
+        let mut properties = vec![
+            Element::new("app", Rc::new(AmfValue::String(app))),
+            Element::new("tcUrl", Rc::new(AmfValue::String(url.to_string()))),
+        ];
+        properties.append(&mut command_object);
+        let mut connect_arguments = vec![Rc::new(AmfValue::Object(
+            ObjectId::INVALID,
+            properties,
+            None,
+        ))];
+        connect_arguments.extend(arguments);
+
+        let mut connect_command = Vec::new();
+        write_chunks(
+            &mut connect_command,
+            DEFAULT_CHUNK_SIZE,
+            COMMAND_CHUNK_STREAM,
+            &RtmpMessage {
+                type_id: message_type::COMMAND_AMF0,
+                stream_id: 0,
+                timestamp: 0,
+                payload: encode_command("connect", CONNECT_TRANSACTION, &connect_arguments),
+            },
+        );
+
+        let mut transactions = HashMap::new();
+        transactions.insert(CONNECT_TRANSACTION, Transaction::Connect);
+
+        Self {
+            url: url.to_string(),
+            state: RtmpState::Connecting,
+            is_connected: false,
+            connect_command,
+            socket_sender,
+            socket_receiver,
+            read_buffer: Vec::new(),
+            chunk_reader: ChunkReader::default(),
+            bytes_received: 0,
+            last_acknowledgement: 0,
+            window_ack_size: 0,
+            next_transaction_id: CONNECT_TRANSACTION + 1,
+            transactions,
+            streams: Vec::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.is_connected
+    }
+
+    /// Invoke a remote method, as done by `NetConnection.call`.
+    pub fn call(
+        &mut self,
+        command: String,
+        responder_handle: Option<ResponderHandle>,
+        arguments: Vec<Rc<AmfValue>>,
+    ) {
+        let transaction_id = self.next_transaction_id();
+        self.transactions
+            .insert(transaction_id, Transaction::Call(responder_handle));
+
+        let mut command_arguments = vec![Rc::new(AmfValue::Null)];
+        command_arguments.extend(arguments);
+        self.send_command(
+            COMMAND_CHUNK_STREAM,
+            0,
+            encode_command(&command, transaction_id, &command_arguments),
+        );
+    }
+
+    /// Ask the server for a new message stream.
+    ///
+    /// Returns the index identifying the stream in later calls and events.
+    pub fn create_stream(&mut self) -> usize {
+        let index = self.streams.len();
+        self.streams.push(RtmpStream::default());
+
+        let transaction_id = self.next_transaction_id();
+        self.transactions
+            .insert(transaction_id, Transaction::CreateStream(index));
+        self.send_command(
+            COMMAND_CHUNK_STREAM,
+            0,
+            encode_command("createStream", transaction_id, &[Rc::new(AmfValue::Null)]),
+        );
+
+        index
+    }
+
+    /// Send a command such as `play`, `pause` or `seek` on a stream.
+    ///
+    /// If the stream has not been assigned an ID yet, the command is sent
+    /// once it is. The null command object is added in front of `arguments`.
+    pub fn stream_command(&mut self, index: usize, command: &str, arguments: Vec<Rc<AmfValue>>) {
+        let Some(stream) = self.streams.get_mut(index) else {
+            return;
+        };
+
+        if let Some(id) = stream.id {
+            let mut command_arguments = vec![Rc::new(AmfValue::Null)];
+            command_arguments.extend(arguments);
+            self.send_command(
+                STREAM_CHUNK_STREAM,
+                id,
+                encode_command(command, 0, &command_arguments),
+            );
+        } else {
+            stream
+                .queued_commands
+                .push((command.to_string(), arguments));
+        }
+    }
+
+    /// Process everything the socket delivered since the last call.
+    pub fn poll(&mut self) -> Vec<RtmpEvent> {
+        let mut events = Vec::new();
+
+        while let Ok(action) = self.socket_receiver.try_recv() {
+            match action {
+                SocketAction::Connect(_, ConnectionState::Connected) => {
+                    self.state = RtmpState::Handshaking;
+                    self.send_bytes(handshake_c0_c1());
+                }
+                SocketAction::Connect(_, ConnectionState::Failed | ConnectionState::TimedOut) => {
+                    self.state = RtmpState::Closed;
+                    events.push(RtmpEvent::Failed(Rc::new(status_info(
+                        "NetConnection.Connect.Failed",
+                        "error",
+                    ))));
+                }
+                SocketAction::Data(_, mut data) => {
+                    self.bytes_received = self.bytes_received.wrapping_add(data.len() as u32);
+                    self.read_buffer.append(&mut data);
+                    if let Err(e) = self.process_buffer(&mut events) {
+                        tracing::error!("Closing RTMP connection to {}: {e}", self.url);
+                        self.close(&mut events);
+                    }
+                }
+                SocketAction::Close(_) => self.close(&mut events),
+            }
+
+            if self.state == RtmpState::Closed {
+                break;
+            }
+        }
+
+        events
+    }
+
+    fn close(&mut self, events: &mut Vec<RtmpEvent>) {
+        if self.state == RtmpState::Closed {
+            return;
+        }
+
+        if self.is_connected {
+            events.push(RtmpEvent::Closed);
+        } else {
+            events.push(RtmpEvent::Failed(Rc::new(status_info(
+                "NetConnection.Connect.Failed",
+                "error",
+            ))));
+        }
+        self.state = RtmpState::Closed;
+        self.is_connected = false;
+    }
+
+    fn process_buffer(&mut self, events: &mut Vec<RtmpEvent>) -> Result<(), RtmpError> {
+        if self.state == RtmpState::Handshaking {
+            // S0 + S1 + S2
+            if self.read_buffer.len() < 1 + HANDSHAKE_SIZE * 2 {
+                return Ok(());
+            }
+            if self.read_buffer[0] != RTMP_VERSION {
+                return Err(RtmpError::UnsupportedVersion(self.read_buffer[0]));
+            }
+
+            // C2 is an echo of S1.
+            let c2 = self.read_buffer[1..1 + HANDSHAKE_SIZE].to_vec();
+            self.send_bytes(c2);
+            self.read_buffer.drain(..1 + HANDSHAKE_SIZE * 2);
+            self.state = RtmpState::Open;
+
+            let connect_command = std::mem::take(&mut self.connect_command);
+            self.send_bytes(connect_command);
+        }
+
+        let mut offset = 0;
+        while self.state == RtmpState::Open {
+            let Some((length, message)) =
+                self.chunk_reader.read_chunk(&self.read_buffer[offset..])?
+            else {
+                break;
+            };
+            offset += length;
+
+            if let Some(message) = message {
+                self.handle_message(message, events)?;
+            }
+        }
+        self.read_buffer.drain(..offset);
+
+        if self.window_ack_size > 0
+            && self.bytes_received.wrapping_sub(self.last_acknowledgement) >= self.window_ack_size
+        {
+            self.last_acknowledgement = self.bytes_received;
+            self.send_control(
+                message_type::ACKNOWLEDGEMENT,
+                self.bytes_received.to_be_bytes().to_vec(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn handle_message(
+        &mut self,
+        message: RtmpMessage,
+        events: &mut Vec<RtmpEvent>,
+    ) -> Result<(), RtmpError> {
+        let mut payload = &message.payload[..];
+
+        match message.type_id {
+            message_type::SET_CHUNK_SIZE => {
+                let size = payload.read_u32::<BigEndian>().unwrap_or_default() & 0x7FFFFFFF;
+                if size == 0 {
+                    return Err(RtmpError::InvalidChunkSize(size));
+                }
+                self.chunk_reader.set_chunk_size(size as usize);
+            }
+            message_type::ABORT => {
+                if let Ok(chunk_stream_id) = payload.read_u32::<BigEndian>() {
+                    self.chunk_reader.abort(chunk_stream_id);
+                }
+            }
+            message_type::ACKNOWLEDGEMENT => {}
+            message_type::USER_CONTROL => self.handle_user_control(payload),
+            message_type::WINDOW_ACK_SIZE => {
+                self.window_ack_size = payload.read_u32::<BigEndian>().unwrap_or_default();
+            }
+            message_type::SET_PEER_BANDWIDTH => {
+                // Reply with our own window size, like Flash Player does.
+                let size = payload.read_u32::<BigEndian>().unwrap_or_default();
+                self.send_control(message_type::WINDOW_ACK_SIZE, size.to_be_bytes().to_vec());
+            }
+            message_type::AUDIO | message_type::VIDEO | message_type::DATA_AMF0 => {
+                if let Some(stream) = self.stream_index(message.stream_id) {
+                    events.push(RtmpEvent::StreamData {
+                        stream,
+                        tag_type: message.type_id,
+                        timestamp: message.timestamp,
+                        data: message.payload,
+                    });
+                }
+            }
+            message_type::DATA_AMF3 => {
+                if let Some(stream) = self.stream_index(message.stream_id) {
+                    events.push(RtmpEvent::StreamData {
+                        stream,
+                        tag_type: FLV_SCRIPT_TAG,
+                        timestamp: message.timestamp,
+                        data: payload.get(1..).unwrap_or_default().to_vec(),
+                    });
+                }
+            }
+            message_type::COMMAND_AMF0 => {
+                self.handle_command(message.stream_id, payload, events);
+            }
+            message_type::COMMAND_AMF3 => {
+                // AMF3 commands start with a format selector, then are AMF0
+                // values which may switch over to AMF3 individually.
+                self.handle_command(
+                    message.stream_id,
+                    payload.get(1..).unwrap_or_default(),
+                    events,
+                );
+            }
+            message_type::AGGREGATE => {
+                for message in split_aggregate(&message) {
+                    self.handle_message(message, events)?;
+                }
+            }
+            message_type::SHARED_OBJECT_AMF0 | message_type::SHARED_OBJECT_AMF3 => {
+                tracing::warn!("Stub: RTMP remote shared objects");
+            }
+            other => tracing::warn!("Unknown RTMP message type {other}"),
+        }
+
+        Ok(())
+    }
+
+    fn handle_user_control(&mut self, mut payload: &[u8]) {
+        let Ok(event_type) = payload.read_u16::<BigEndian>() else {
+            return;
+        };
+
+        match event_type {
+            user_control::PING_REQUEST => {
+                let mut response = user_control::PING_RESPONSE.to_be_bytes().to_vec();
+                response.extend_from_slice(payload);
+                self.send_control(message_type::USER_CONTROL, response);
+            }
+            user_control::STREAM_BEGIN
+            | user_control::STREAM_EOF
+            | user_control::STREAM_DRY
+            | user_control::STREAM_IS_RECORDED => {}
+            other => tracing::debug!("Ignoring RTMP user control event {other}"),
+        }
+    }
+
+    fn handle_command(&mut self, stream_id: u32, payload: &[u8], events: &mut Vec<RtmpEvent>) {
+        let mut values = read_amf0_values(payload).into_iter();
+        let Some(name) = values
+            .next()
+            .and_then(|v| amf_string(&v).map(str::to_string))
+        else {
+            tracing::warn!("Ignoring RTMP command without a name");
+            return;
+        };
+        let transaction_id = values.next().and_then(|v| amf_number(&v)).unwrap_or(0.0);
+        // The command object is always null for responses and client calls.
+        let _command_object = values.next();
+        let arguments: Vec<_> = values.collect();
+        let first_argument = arguments
+            .first()
+            .cloned()
+            .unwrap_or_else(|| Rc::new(AmfValue::Undefined));
+
+        match name.as_str() {
+            "_result" | "_error" => {
+                let is_error = name == "_error";
+                let Some(transaction) = self.transactions.remove(&(transaction_id as u32)) else {
+                    tracing::warn!("RTMP response to unknown transaction {transaction_id}");
+                    return;
+                };
+
+                match transaction {
+                    Transaction::Connect => {
+                        let is_success = !is_error
+                            && amf_property(&first_argument, "code").and_then(amf_string)
+                                == Some("NetConnection.Connect.Success");
+                        self.is_connected = is_success;
+                        events.push(RtmpEvent::Status(first_argument));
+                        if !is_success {
+                            // A rejected connection is closed right after.
+                            self.state = RtmpState::Closed;
+                            events.push(RtmpEvent::Closed);
+                        }
+                    }
+                    Transaction::CreateStream(index) => {
+                        match (is_error, amf_number(&first_argument)) {
+                            (false, Some(id)) => self.stream_created(index, id as u32),
+                            _ => tracing::error!("RTMP server refused to create a stream"),
+                        }
+                    }
+                    Transaction::Call(Some(responder)) => {
+                        let callback = if is_error {
+                            ResponderCallback::Status
+                        } else {
+                            ResponderCallback::Result
+                        };
+                        events.push(RtmpEvent::Response(responder, callback, first_argument));
+                    }
+                    Transaction::Call(None) => {}
+                }
+            }
+            "onStatus" if stream_id != 0 => {
+                if let Some(index) = self.stream_index(stream_id) {
+                    events.push(RtmpEvent::StreamStatus(index, first_argument));
+                }
+            }
+            "onStatus" => events.push(RtmpEvent::Status(first_argument)),
+            "close" => self.close(events),
+            _ if stream_id != 0 => {
+                // Stream-level calls (such as `onPlayStatus`) are delivered
+                // to the stream's client just like FLV script data.
+                if let Some(index) = self.stream_index(stream_id) {
+                    let mut data = Vec::new();
+                    write_amf0(&mut data, &AmfValue::String(name));
+                    write_amf0(&mut data, &first_argument);
+                    events.push(RtmpEvent::StreamData {
+                        stream: index,
+                        tag_type: FLV_SCRIPT_TAG,
+                        timestamp: 0,
+                        data,
+                    });
+                }
+            }
+            _ => events.push(RtmpEvent::ClientCall(name, arguments)),
+        }
+    }
+
+    fn stream_created(&mut self, index: usize, id: u32) {
+        let Some(stream) = self.streams.get_mut(index) else {
+            return;
+        };
+        stream.id = Some(id);
+
+        for (command, arguments) in std::mem::take(&mut stream.queued_commands) {
+            self.stream_command(index, &command, arguments);
+        }
+    }
+
+    fn stream_index(&self, stream_id: u32) -> Option<usize> {
+        self.streams
+            .iter()
+            .position(|stream| stream.id == Some(stream_id))
+    }
+
+    fn next_transaction_id(&mut self) -> u32 {
+        let id = self.next_transaction_id;
+        self.next_transaction_id += 1;
+        id
+    }
+
+    fn send_command(&mut self, chunk_stream_id: u32, stream_id: u32, payload: Vec<u8>) {
+        self.send_message(
+            chunk_stream_id,
+            RtmpMessage {
+                type_id: message_type::COMMAND_AMF0,
+                stream_id,
+                timestamp: 0,
+                payload,
+            },
+        );
+    }
+
+    fn send_control(&mut self, type_id: u8, payload: Vec<u8>) {
+        self.send_message(
+            CONTROL_CHUNK_STREAM,
+            RtmpMessage {
+                type_id,
+                stream_id: 0,
+                timestamp: 0,
+                payload,
+            },
+        );
+    }
+
+    fn send_message(&mut self, chunk_stream_id: u32, message: RtmpMessage) {
+        let mut bytes = Vec::new();
+        write_chunks(&mut bytes, DEFAULT_CHUNK_SIZE, chunk_stream_id, &message);
+
+        if self.state == RtmpState::Open {
+            self.send_bytes(bytes);
+        } else {
+            // Anything sent before the handshake completes goes out right
+            // after the `connect` command.
+            self.connect_command.append(&mut bytes);
+        }
+    }
+
+    fn send_bytes(&mut self, bytes: Vec<u8>) {
+        // We use an unbounded channel, so this only fails when the socket is gone.
+        if let Err(e) = self.socket_sender.try_send(bytes) {
+            tracing::error!("Failed to send data to RTMP socket: {:?}", e);
+        }
+    }
+}
+
+/// Split an aggregate message into its sub-messages.
+///
+/// Sub-messages are stored like FLV tags. Their timestamps are relative to
+/// the first one, which is mapped onto the aggregate message's timestamp.
+fn split_aggregate(message: &RtmpMessage) -> Vec<RtmpMessage> {
+    let mut messages = Vec::new();
+    let mut data = &message.payload[..];
+    let mut base_timestamp = None;
+
+    let mut read_message = |data: &mut &[u8]| -> std::io::Result<RtmpMessage> {
+        let type_id = data.read_u8()?;
+        let length = data.read_u24::<BigEndian>()? as usize;
+        let timestamp = data.read_u24::<BigEndian>()? | (data.read_u8()? as u32) << 24;
+        let _stream_id = data.read_u24::<BigEndian>()?;
+        let mut payload = vec![0; length];
+        data.read_exact(&mut payload)?;
+        let _back_pointer = data.read_u32::<BigEndian>()?;
+
+        let base = *base_timestamp.get_or_insert(timestamp);
+        Ok(RtmpMessage {
+            type_id,
+            stream_id: message.stream_id,
+            timestamp: message.timestamp.wrapping_add(timestamp.wrapping_sub(base)),
+            payload,
+        })
+    };
+
+    while !data.is_empty() {
+        match read_message(&mut data) {
+            Ok(message) => messages.push(message),
+            Err(_) => {
+                tracing::warn!("Truncated RTMP aggregate message");
+                break;
+            }
+        }
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::navigator::{
+        ErrorResponse, NavigationMethod, NullNavigatorBackend, OwnedFuture, Request,
+        SuccessResponse,
+    };
+    use crate::loader::Error;
+    use crate::net_connection::NetConnections;
+    use crate::streams::NetStream;
+    use crate::string::AvmString;
+    use crate::tag_utils::SwfMovie;
+    use flash_lso::types::AMFVersion;
+    use indexmap::IndexMap;
+    use std::sync::{Arc, Mutex};
+    use url::ParseError;
+
+    /// The socket ends of the connections a `SocketNavigator` was asked to open.
+    type Sockets = Arc<Mutex<Vec<(Receiver<Vec<u8>>, Sender<SocketAction>)>>>;
+
+    /// A navigator that hands the sockets it opens over to the test.
+    struct SocketNavigator {
+        inner: NullNavigatorBackend,
+        sockets: Sockets,
+    }
+
+    impl NavigatorBackend for SocketNavigator {
+        fn navigate_to_url(
+            &self,
+            url: &str,
+            target: &str,
+            vars_method: Option<(NavigationMethod, IndexMap<String, String>)>,
+        ) {
+            self.inner.navigate_to_url(url, target, vars_method)
+        }
+
+        fn fetch(&self, request: Request) -> OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse> {
+            self.inner.fetch(request)
+        }
+
+        fn resolve_url(&self, url: &str) -> Result<Url, ParseError> {
+            self.inner.resolve_url(url)
+        }
+
+        fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
+            self.inner.spawn_future(future)
+        }
+
+        fn pre_process_url(&self, url: Url) -> Url {
+            self.inner.pre_process_url(url)
+        }
+
+        fn connect_socket(
+            &mut self,
+            _host: String,
+            _port: u16,
+            _timeout: Duration,
+            _handle: SocketHandle,
+            receiver: Receiver<Vec<u8>>,
+            sender: Sender<SocketAction>,
+        ) {
+            self.sockets.lock().unwrap().push((receiver, sender));
+        }
+    }
+
+    fn read_all(reader: &mut ChunkReader, mut data: &[u8]) -> Vec<RtmpMessage> {
+        let mut messages = Vec::new();
+        while let Some((length, message)) = reader.read_chunk(data).unwrap() {
+            data = &data[length..];
+            messages.extend(message);
+        }
+        assert!(data.is_empty(), "Data left over after reading chunks");
+        messages
+    }
+
+    #[test]
+    fn chunk_round_trip() {
+        let message = RtmpMessage {
+            type_id: message_type::VIDEO,
+            stream_id: 1,
+            timestamp: 1234,
+            payload: (0..=255).cycle().take(300).collect(),
+        };
+
+        let mut bytes = Vec::new();
+        write_chunks(&mut bytes, DEFAULT_CHUNK_SIZE, 6, &message);
+        // One full header, two continuation headers.
+        assert_eq!(bytes.len(), 12 + 2 + 300);
+
+        let mut reader = ChunkReader::default();
+        assert_eq!(read_all(&mut reader, &bytes), vec![message]);
+    }
+
+    #[test]
+    fn chunk_extended_timestamp() {
+        let message = RtmpMessage {
+            type_id: message_type::AUDIO,
+            stream_id: 1,
+            timestamp: 0x01000000,
+            payload: vec![0xAF; 200],
+        };
+
+        let mut bytes = Vec::new();
+        write_chunks(&mut bytes, DEFAULT_CHUNK_SIZE, 4, &message);
+
+        let mut reader = ChunkReader::default();
+        assert_eq!(read_all(&mut reader, &bytes), vec![message]);
+    }
+
+    #[test]
+    fn chunk_incomplete_data() {
+        let message = RtmpMessage {
+            type_id: message_type::COMMAND_AMF0,
+            stream_id: 0,
+            timestamp: 0,
+            payload: vec![1; 64],
+        };
+
+        let mut bytes = Vec::new();
+        write_chunks(&mut bytes, DEFAULT_CHUNK_SIZE, 3, &message);
+
+        let mut reader = ChunkReader::default();
+        for length in 0..bytes.len() {
+            assert_eq!(reader.read_chunk(&bytes[..length]), Ok(None));
+        }
+        assert_eq!(
+            reader.read_chunk(&bytes),
+            Ok(Some((bytes.len(), Some(message))))
+        );
+    }
+
+    #[test]
+    fn chunk_compressed_headers() {
+        let bytes = [
+            // Type 0: timestamp 100, length 2, audio, stream 1
+            0x04, 0x00, 0x00, 0x64, 0x00, 0x00, 0x02, 0x08, 0x01, 0x00, 0x00, 0x00, 0xAA, 0xBB,
+            // Type 2: delta 20
+            0x84, 0x00, 0x00, 0x14, 0xCC, 0xDD, // Type 3: same delta again
+            0xC4, 0xEE, 0xFF, // Type 1: delta 5, length 1, video
+            0x44, 0x00, 0x00, 0x05, 0x00, 0x00, 0x01, 0x09, 0x11,
+        ];
+
+        let mut reader = ChunkReader::default();
+        let messages = read_all(&mut reader, &bytes);
+        let summary: Vec<_> = messages
+            .iter()
+            .map(|m| (m.type_id, m.stream_id, m.timestamp, m.payload.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (8, 1, 100, vec![0xAA, 0xBB]),
+                (8, 1, 120, vec![0xCC, 0xDD]),
+                (8, 1, 140, vec![0xEE, 0xFF]),
+                (9, 1, 145, vec![0x11]),
+            ]
+        );
+    }
+
+    #[test]
+    fn chunk_missing_header() {
+        let mut reader = ChunkReader::default();
+        assert_eq!(
+            reader.read_chunk(&[0xC5, 0x00]),
+            Err(RtmpError::MissingChunkHeader(5))
+        );
+    }
+
+    #[test]
+    fn chunk_large_stream_ids() {
+        let message = RtmpMessage {
+            type_id: message_type::DATA_AMF0,
+            stream_id: 1,
+            timestamp: 0,
+            payload: vec![2; 10],
+        };
+
+        // Chunk stream 320 needs the three byte form of the basic header.
+        let mut bytes = vec![0x01, 0x00, 0x01];
+        bytes.extend_from_slice(&[0, 0, 0, 0, 0, 10, 18, 1, 0, 0, 0]);
+        bytes.extend_from_slice(&message.payload);
+
+        let mut reader = ChunkReader::default();
+        assert_eq!(read_all(&mut reader, &bytes), vec![message]);
+    }
+
+    #[test]
+    fn amf0_encoding() {
+        let mut out = Vec::new();
+        write_amf0(
+            &mut out,
+            &AmfValue::Object(
+                ObjectId::INVALID,
+                vec![
+                    Element::new("a", Rc::new(AmfValue::Number(1.0))),
+                    Element::new("b", Rc::new(AmfValue::Bool(true))),
+                ],
+                None,
+            ),
+        );
+        assert_eq!(
+            out,
+            vec![
+                0x03, 0x00, 0x01, b'a', 0x00, 0x3F, 0xF0, 0, 0, 0, 0, 0, 0, 0x00, 0x01, b'b', 0x01,
+                0x01, 0x00, 0x00, 0x09
+            ]
+        );
+    }
+
+    #[test]
+    fn command_round_trip() {
+        let payload = encode_command(
+            "_result",
+            4,
+            &[Rc::new(AmfValue::Null), Rc::new(AmfValue::Number(7.0))],
+        );
+        let values = read_amf0_values(&payload);
+        assert_eq!(values.len(), 4);
+        assert_eq!(amf_string(&values[0]), Some("_result"));
+        assert_eq!(amf_number(&values[1]), Some(4.0));
+        assert_eq!(amf_number(&values[3]), Some(7.0));
+    }
+
+    #[test]
+    fn aggregate_messages() {
+        let mut payload = Vec::new();
+        for (type_id, timestamp, data) in [(8u8, 1000u32, [1u8, 2]), (9, 1040, [3, 4])] {
+            payload.push(type_id);
+            payload.write_u24::<BigEndian>(data.len() as u32).unwrap();
+            payload.write_u24::<BigEndian>(timestamp).unwrap();
+            payload.push(0);
+            payload.write_u24::<BigEndian>(0).unwrap();
+            payload.extend_from_slice(&data);
+            payload
+                .write_u32::<BigEndian>(11 + data.len() as u32)
+                .unwrap();
+        }
+
+        let messages = split_aggregate(&RtmpMessage {
+            type_id: message_type::AGGREGATE,
+            stream_id: 1,
+            timestamp: 50,
+            payload,
+        });
+        let summary: Vec<_> = messages
+            .iter()
+            .map(|m| (m.type_id, m.stream_id, m.timestamp, m.payload.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![(8, 1, 50, vec![1, 2]), (9, 1, 90, vec![3, 4])]
+        );
+    }
+
+    #[test]
+    fn connect_session() {
+        let (socket_sender, data_receiver) = unbounded();
+        let (action_sender, socket_receiver) = unbounded();
+        let url = Url::parse("rtmp://localhost/app/instance").unwrap();
+        let mut rtmp = Rtmp::new(&url, vec![], vec![], socket_sender, socket_receiver);
+
+        action_sender
+            .try_send(SocketAction::Connect(
+                SocketHandle::default(),
+                ConnectionState::Connected,
+            ))
+            .unwrap();
+        assert!(rtmp.poll().is_empty());
+        assert_eq!(data_receiver.try_recv().unwrap(), handshake_c0_c1());
+
+        // S0, S1 and S2; the connect command must follow C2.
+        let mut handshake = vec![RTMP_VERSION];
+        handshake.extend_from_slice(&[0x42; HANDSHAKE_SIZE]);
+        handshake.extend_from_slice(&[0; HANDSHAKE_SIZE]);
+        action_sender
+            .try_send(SocketAction::Data(SocketHandle::default(), handshake))
+            .unwrap();
+        assert!(rtmp.poll().is_empty());
+        assert_eq!(
+            data_receiver.try_recv().unwrap(),
+            vec![0x42; HANDSHAKE_SIZE]
+        );
+
+        let mut reader = ChunkReader::default();
+        let messages = read_all(&mut reader, &data_receiver.try_recv().unwrap());
+        assert_eq!(messages.len(), 1);
+        let values = read_amf0_values(&messages[0].payload);
+        assert_eq!(amf_string(&values[0]), Some("connect"));
+        assert_eq!(
+            amf_property(&values[2], "app").and_then(amf_string),
+            Some("app/instance")
+        );
+
+        // Reply with a 4096 byte chunk size, then accept the connection.
+        let mut response = Vec::new();
+        write_chunks(
+            &mut response,
+            DEFAULT_CHUNK_SIZE,
+            CONTROL_CHUNK_STREAM,
+            &RtmpMessage {
+                type_id: message_type::SET_CHUNK_SIZE,
+                stream_id: 0,
+                timestamp: 0,
+                payload: 4096u32.to_be_bytes().to_vec(),
+            },
+        );
+        write_chunks(
+            &mut response,
+            4096,
+            COMMAND_CHUNK_STREAM,
+            &RtmpMessage {
+                type_id: message_type::COMMAND_AMF0,
+                stream_id: 0,
+                timestamp: 0,
+                payload: encode_command(
+                    "_result",
+                    CONNECT_TRANSACTION,
+                    &[
+                        Rc::new(AmfValue::Null),
+                        Rc::new(status_info("NetConnection.Connect.Success", "status")),
+                    ],
+                ),
+            },
+        );
+        action_sender
+            .try_send(SocketAction::Data(SocketHandle::default(), response))
+            .unwrap();
+
+        let events = rtmp.poll();
+        assert!(rtmp.is_connected());
+        assert!(matches!(&events[..], [RtmpEvent::Status(_)]));
+
+        // Commands on a stream are held back until the server assigns an ID.
+        let index = rtmp.create_stream();
+        rtmp.stream_command(
+            index,
+            "play",
+            vec![Rc::new(AmfValue::String("video".to_string()))],
+        );
+        let messages = read_all(&mut reader, &data_receiver.try_recv().unwrap());
+        assert_eq!(
+            amf_string(&read_amf0_values(&messages[0].payload)[0]),
+            Some("createStream")
+        );
+        assert!(data_receiver.try_recv().is_err());
+
+        let mut response = Vec::new();
+        write_chunks(
+            &mut response,
+            4096,
+            COMMAND_CHUNK_STREAM,
+            &RtmpMessage {
+                type_id: message_type::COMMAND_AMF0,
+                stream_id: 0,
+                timestamp: 0,
+                payload: encode_command(
+                    "_result",
+                    CONNECT_TRANSACTION + 1,
+                    &[Rc::new(AmfValue::Null), Rc::new(AmfValue::Number(5.0))],
+                ),
+            },
+        );
+        write_chunks(
+            &mut response,
+            4096,
+            6,
+            &RtmpMessage {
+                type_id: message_type::VIDEO,
+                stream_id: 5,
+                timestamp: 40,
+                payload: vec![0x12, 0x34],
+            },
+        );
+        action_sender
+            .try_send(SocketAction::Data(SocketHandle::default(), response))
+            .unwrap();
+
+        let events = rtmp.poll();
+        assert!(matches!(
+            &events[..],
+            [RtmpEvent::StreamData {
+                stream: 0,
+                tag_type: 9,
+                timestamp: 40,
+                ..
+            }]
+        ));
+        let messages = read_all(&mut reader, &data_receiver.try_recv().unwrap());
+        assert_eq!(messages[0].stream_id, 5);
+        assert_eq!(
+            amf_string(&read_amf0_values(&messages[0].payload)[0]),
+            Some("play")
+        );
+
+        action_sender
+            .try_send(SocketAction::Close(SocketHandle::default()))
+            .unwrap();
+        assert!(matches!(&rtmp.poll()[..], [RtmpEvent::Closed]));
+        assert!(!rtmp.is_connected());
+    }
+
+    #[test]
+    fn net_stream_commands() {
+        let sockets = Sockets::default();
+        let player = crate::player::PlayerBuilder::new()
+            .with_navigator(SocketNavigator {
+                inner: NullNavigatorBackend::new(),
+                sockets: sockets.clone(),
+            })
+            .with_movie(SwfMovie::empty(10))
+            .build();
+        let mut player = player.lock().unwrap();
+
+        player.mutate_with_update_context(|context| {
+            let object = crate::avm1::Object::new(&context.strings, None);
+            NetConnections::connect_to_rtmp(
+                context,
+                object,
+                "rtmp://localhost/app",
+                AMFVersion::AMF0,
+                vec![],
+            );
+            let (data_receiver, action_sender) = sockets.lock().unwrap().pop().unwrap();
+            let handle = context.net_connections.connections.keys().next().unwrap();
+
+            let server_send = |context: &mut UpdateContext<'_>, action| {
+                action_sender.try_send(action).unwrap();
+                NetConnections::update_connections(context);
+            };
+
+            // Get through the handshake and accept the connection.
+            server_send(
+                context,
+                SocketAction::Connect(SocketHandle::default(), ConnectionState::Connected),
+            );
+            let mut handshake = vec![RTMP_VERSION];
+            handshake.extend_from_slice(&[0x42; HANDSHAKE_SIZE]);
+            handshake.extend_from_slice(&[0; HANDSHAKE_SIZE]);
+            server_send(
+                context,
+                SocketAction::Data(SocketHandle::default(), handshake),
+            );
+            let mut response = Vec::new();
+            write_chunks(
+                &mut response,
+                DEFAULT_CHUNK_SIZE,
+                COMMAND_CHUNK_STREAM,
+                &RtmpMessage {
+                    type_id: message_type::COMMAND_AMF0,
+                    stream_id: 0,
+                    timestamp: 0,
+                    payload: encode_command(
+                        "_result",
+                        CONNECT_TRANSACTION,
+                        &[
+                            Rc::new(AmfValue::Null),
+                            Rc::new(status_info("NetConnection.Connect.Success", "status")),
+                        ],
+                    ),
+                },
+            );
+            server_send(
+                context,
+                SocketAction::Data(SocketHandle::default(), response),
+            );
+
+            let mut reader = ChunkReader::default();
+            let mut received = Vec::new();
+            while let Ok(data) = data_receiver.try_recv() {
+                received.extend(data);
+            }
+            // C0 and C1, then C2.
+            let mut messages = read_all(&mut reader, &received[2 * HANDSHAKE_SIZE + 1..]);
+            assert_eq!(
+                amf_string(&read_amf0_values(&messages.remove(0).payload)[0]),
+                Some("connect")
+            );
+
+            // `play` is queued until the stream exists, `seek` is sent right away.
+            let stream = NetStream::new(context.gc(), None);
+            stream.attach_to_connection(context, handle);
+            let name = AvmString::new_utf8(context.gc(), "video");
+            stream.play(context, Some(name));
+
+            let mut response = Vec::new();
+            write_chunks(
+                &mut response,
+                DEFAULT_CHUNK_SIZE,
+                COMMAND_CHUNK_STREAM,
+                &RtmpMessage {
+                    type_id: message_type::COMMAND_AMF0,
+                    stream_id: 0,
+                    timestamp: 0,
+                    payload: encode_command(
+                        "_result",
+                        CONNECT_TRANSACTION + 1,
+                        &[Rc::new(AmfValue::Null), Rc::new(AmfValue::Number(5.0))],
+                    ),
+                },
+            );
+            server_send(
+                context,
+                SocketAction::Data(SocketHandle::default(), response),
+            );
+            stream.seek(context, 1500.0, true);
+
+            let mut received = Vec::new();
+            while let Ok(data) = data_receiver.try_recv() {
+                received.extend(data);
+            }
+            let commands: Vec<_> = read_all(&mut reader, &received)
+                .into_iter()
+                .map(|message| (message.stream_id, read_amf0_values(&message.payload)))
+                .collect();
+            assert_eq!(commands.len(), 3);
+            assert_eq!(amf_string(&commands[0].1[0]), Some("createStream"));
+
+            let (stream_id, play) = &commands[1];
+            assert_eq!(*stream_id, 5);
+            assert_eq!(play.len(), 4);
+            assert_eq!(amf_string(&play[0]), Some("play"));
+            assert!(matches!(*play[2], AmfValue::Null));
+            assert_eq!(amf_string(&play[3]), Some("video"));
+
+            let (stream_id, seek) = &commands[2];
+            assert_eq!(*stream_id, 5);
+            assert_eq!(seek.len(), 4);
+            assert_eq!(amf_string(&seek[0]), Some("seek"));
+            assert!(matches!(*seek[2], AmfValue::Null));
+            assert_eq!(amf_number(&seek[3]), Some(1500.0));
+        });
+    }
+}
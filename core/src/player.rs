@@ -4,13 +4,14 @@ use crate::avm1::Object;
 use crate::avm1::Value;
 use crate::avm1::VariableDumper;
 use crate::avm1::{Activation, ActivationIdentifier};
+use crate::avm2::debugger::Debugger;
 use crate::avm2::object::{EventObject as Avm2EventObject, Object as Avm2Object};
 use crate::avm2::{Activation as Avm2Activation, Avm2, CallStack};
 use crate::backend::ui::FontDefinition;
 use crate::backend::{
     audio::{AudioBackend, AudioManager},
     log::LogBackend,
-    navigator::{NavigatorBackend, Request},
+    navigator::{ErrorResponse, NavigatorBackend, OwnedFuture, Request, SuccessResponse},
     storage::StorageBackend,
     ui::{MouseCursor, UiBackend},
 };
@@ -49,6 +50,7 @@ use crate::system_properties::SystemProperties;
 use crate::tag_utils::SwfMovie;
 use crate::timer::Timers;
 use crate::vminterface::Instantiator;
+use crate::worker::Workers;
 use crate::DefaultFont;
 use async_channel::Sender;
 use gc_arena::lock::GcRefLock;
@@ -193,6 +195,9 @@ struct GcRootData<'gc> {
 
     local_connections: LocalConnections<'gc>,
 
+    /// Background workers, and the message channels between them.
+    workers: Workers<'gc>,
+
     /// Dynamic root for allowing handles to GC objects to exist outside of the GC.
     dynamic_root: DynamicRootSet<'gc>,
 
@@ -234,6 +239,7 @@ impl<'gc> GcRootData<'gc> {
         &mut Sockets<'gc>,
         &mut NetConnections<'gc>,
         &mut LocalConnections<'gc>,
+        &mut Workers<'gc>,
         &mut Vec<PostFrameCallback<'gc>>,
         &mut MouseData<'gc>,
         DynamicRootSet<'gc>,
@@ -258,6 +264,7 @@ impl<'gc> GcRootData<'gc> {
             &mut self.sockets,
             &mut self.net_connections,
             &mut self.local_connections,
+            &mut self.workers,
             &mut self.post_frame_callbacks,
             &mut self.mouse_data,
             self.dynamic_root,
@@ -384,6 +391,9 @@ pub struct Player {
     /// Any compatibility rules to apply for this movie.
     compatibility_rules: CompatibilityRules,
 
+    /// The compatibility rules that match the root movie.
+    movie_compatibility_rules: CompatibilityRules,
+
     /// Sends notifications back from the core player to the frontend.
     notification_sender: Option<Sender<PlayerNotification>>,
 
@@ -457,6 +467,9 @@ impl Player {
         parameters: Vec<(String, String)>,
         on_metadata: Box<dyn FnOnce(&swf::HeaderExt)>,
     ) {
+        // Rules matched by the movie's hash only apply once it's downloaded.
+        self.movie_compatibility_rules = self.compatibility_rules.for_movie(&movie_url, None);
+
         self.mutate_with_update_context(|context| {
             let future = context.load_manager.load_root_movie(
                 context.player.clone(),
@@ -511,6 +524,8 @@ impl Player {
     }
 
     pub fn tick(&mut self, dt: f64) {
+        self.mutate_with_update_context(|context| context.avm2.poll_debugger());
+
         if !self.is_playing() {
             return;
         }
@@ -948,6 +963,23 @@ impl Player {
         })
     }
 
+    /// Converts a position in viewport pixels (as given to mouse events)
+    /// into a position in stage pixels.
+    pub fn viewport_to_stage_position(&mut self, x: f64, y: f64) -> (f64, f64) {
+        let inverse_view_matrix =
+            self.mutate_with_update_context(|context| context.stage.inverse_view_matrix());
+        let point = inverse_view_matrix * Point::from_pixels(x, y);
+        (point.x.to_pixels(), point.y.to_pixels())
+    }
+
+    /// Converts a position in stage pixels into a position in viewport pixels,
+    /// suitable for mouse events.
+    pub fn stage_to_viewport_position(&mut self, x: f64, y: f64) -> (f64, f64) {
+        let view_matrix = self.mutate_with_update_context(|context| context.stage.view_matrix());
+        let point = view_matrix * Point::from_pixels(x, y);
+        (point.x.to_pixels(), point.y.to_pixels())
+    }
+
     pub fn set_show_menu(&mut self, show_menu: bool) {
         self.mutate_with_update_context(|context| {
             let stage = context.stage;
@@ -1963,6 +1995,7 @@ impl Player {
             Avm1::run_frame(context);
             AudioManager::update_sounds(context);
             LocalConnections::update_connections(context);
+            Workers::run_frame(context);
 
             // Only run the current list of callbacks - any callbacks added during callback execution
             // will be run at the end of the *next* frame.
@@ -2035,6 +2068,20 @@ impl Player {
         self.current_frame
     }
 
+    /// The label of the current frame of the main timeline, if it has one.
+    pub fn current_frame_label(&mut self) -> Option<String> {
+        self.mutate_with_update_context(|context| {
+            let root = context.stage.root_clip()?.as_movie_clip()?;
+            let (label, frame) = root.current_label()?;
+            (frame == root.current_frame()).then(|| label.to_utf8_lossy().into_owned())
+        })
+    }
+
+    /// The stubs that have been encountered while running this player.
+    pub fn stubs(&self) -> &StubCollection {
+        &self.stub_tracker
+    }
+
     pub fn audio(&self) -> &Audio {
         &self.audio
     }
@@ -2051,6 +2098,22 @@ impl Player {
         &mut self.navigator
     }
 
+    /// Fetch a request with the navigator, after applying the fetch URL
+    /// rewrites of the root movie's compatibility rules.
+    pub fn fetch(
+        &self,
+        mut request: Request,
+    ) -> OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse> {
+        if let Some(url) = self
+            .movie_compatibility_rules
+            .rewrite_fetch_url(request.url())
+        {
+            request.set_url(url);
+        }
+
+        self.navigator.fetch(request)
+    }
+
     // The frame rate of the current movie in FPS.
     pub fn frame_rate(&self) -> f64 {
         self.frame_rate
@@ -2195,6 +2258,7 @@ impl Player {
                 sockets,
                 net_connections,
                 local_connections,
+                workers,
                 post_frame_callbacks,
                 mouse_data,
                 dynamic_root,
@@ -2250,6 +2314,7 @@ impl Player {
                 sockets,
                 net_connections,
                 local_connections,
+                workers,
                 dynamic_root,
                 post_frame_callbacks,
                 notification_sender: this.notification_sender.as_ref(),
@@ -2402,6 +2467,11 @@ impl Player {
         })
     }
 
+    /// The url of the root movie.
+    pub fn swf_url(&self) -> &str {
+        self.swf.url()
+    }
+
     pub fn spoofed_url(&self) -> Option<&str> {
         self.spoofed_url.as_deref()
     }
@@ -2410,6 +2480,54 @@ impl Player {
         &self.compatibility_rules
     }
 
+    /// The compatibility rules that apply to the root movie.
+    ///
+    /// Until the root movie is downloaded, this only includes the rules
+    /// matched by its URL.
+    pub fn movie_compatibility_rules(&self) -> &CompatibilityRules {
+        &self.movie_compatibility_rules
+    }
+
+    /// Match the compatibility rules against the root movie, and apply the
+    /// options they force.
+    ///
+    /// `data` is `None` when the movie starts playing before it has fully
+    /// downloaded, in which case rules that match by hash are skipped.
+    ///
+    /// The player version and runtime can't be changed once the player is
+    /// built, so those must have already been applied by the frontend.
+    pub(crate) fn apply_movie_compatibility_rules(&mut self, url: &str, data: Option<&[u8]>) {
+        let rules = self.compatibility_rules.for_movie(url, data);
+
+        if let Some(version) = rules.player_version() {
+            if version != self.player_version {
+                tracing::warn!(
+                    "Compatibility rules for this movie require player version {version}, but it is running as version {}",
+                    self.player_version
+                );
+            }
+        }
+
+        if let Some(runtime) = rules.player_runtime() {
+            if runtime != self.player_runtime {
+                tracing::warn!(
+                    "Compatibility rules for this movie require player runtime {runtime:?}, but it is running as {:?}",
+                    self.player_runtime
+                );
+            }
+        }
+
+        if let Some(frame_rate) = rules.frame_rate() {
+            if !self.forced_frame_rate {
+                tracing::info!("Forcing frame rate of {frame_rate} due to compatibility rules");
+                self.frame_rate = frame_rate;
+                self.forced_frame_rate = true;
+            }
+        }
+
+        self.movie_compatibility_rules = rules;
+    }
+
     pub fn log_backend(&self) -> &Log {
         &self.log
     }
@@ -2489,6 +2607,7 @@ pub struct PlayerBuilder {
     #[cfg(feature = "known_stubs")]
     stub_report_output: Option<std::path::PathBuf>,
     avm2_optimizer_enabled: bool,
+    avm2_debugger: Option<Debugger>,
 }
 
 impl PlayerBuilder {
@@ -2542,6 +2661,7 @@ impl PlayerBuilder {
             #[cfg(feature = "known_stubs")]
             stub_report_output: None,
             avm2_optimizer_enabled: true,
+            avm2_debugger: None,
         }
     }
 
@@ -2757,6 +2877,12 @@ impl PlayerBuilder {
         self
     }
 
+    /// Attaches a debugger to the AVM2 of this player.
+    pub fn with_avm2_debugger(mut self, debugger: Debugger) -> Self {
+        self.avm2_debugger = Some(debugger);
+        self
+    }
+
     fn create_gc_root<'gc>(
         gc_context: &'gc Mutation<'gc>,
         player_version: u8,
@@ -2807,6 +2933,7 @@ impl PlayerBuilder {
             sockets: Sockets::empty(),
             net_connections: NetConnections::default(),
             local_connections: LocalConnections::empty(),
+            workers: Workers::new(),
             dynamic_root: DynamicRootSet::new(gc_context),
             post_frame_callbacks: Vec::new(),
         };
@@ -2906,6 +3033,7 @@ impl PlayerBuilder {
                 load_behavior: self.load_behavior,
                 spoofed_url: self.spoofed_url.clone(),
                 compatibility_rules: self.compatibility_rules.clone(),
+                movie_compatibility_rules: CompatibilityRules::empty(),
                 stub_tracker: StubCollection::new(),
                 notification_sender: self.notification_sender,
                 #[cfg(feature = "egui")]
@@ -2972,6 +3100,7 @@ impl PlayerBuilder {
                 .avm2
                 .set_optimizer_enabled(self.avm2_optimizer_enabled);
             Avm2::load_player_globals(context).expect("Unable to load AVM2 globals");
+            context.avm2.set_debugger(self.avm2_debugger);
 
             let stage = context.stage;
             stage.set_align(context, self.align);
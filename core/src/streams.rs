@@ -17,8 +17,10 @@ use crate::buffer::{Buffer, Slice, Substream, SubstreamError};
 use crate::context::UpdateContext;
 use crate::display_object::{MovieClip, TDisplayObject};
 use crate::loader::Error;
+use crate::net_connection::NetConnectionHandle;
 use crate::string::AvmString;
 use crate::vminterface::AvmObject;
+use flash_lso::types::Value as AmfValue;
 use flv_rs::{
     AudioData as FlvAudioData, AudioDataType as FlvAudioDataType, Error as FlvError, FlvReader,
     FrameType as FlvFrameType, Header as FlvHeader, ScriptData as FlvScriptData,
@@ -252,6 +254,18 @@ pub struct NetStreamData<'gc> {
 
     /// True if the stream should play when ticked.
     playing: bool,
+
+    /// The RTMP connection this stream was created on, and the index of the
+    /// stream within that connection.
+    ///
+    /// Streams created on an RTMP connection receive their data from the
+    /// server as it is played, rather than downloading a file.
+    #[collect(require_static)]
+    rtmp_stream: Option<(NetConnectionHandle, usize)>,
+
+    /// The size of the last FLV tag written into the buffer from an RTMP
+    /// stream.
+    last_tag_size: u32,
 }
 
 impl<'gc> NetStream<'gc> {
@@ -277,6 +291,8 @@ impl<'gc> NetStream<'gc> {
                 attached_to: None,
                 playing: false,
                 expected_length: Some(0),
+                rtmp_stream: None,
+                last_tag_size: 0,
             },
         ))
     }
@@ -322,6 +338,7 @@ impl<'gc> NetStream<'gc> {
         write.audio_stream = None;
         write.sound_instance = None;
         write.expected_length = Some(0);
+        write.last_tag_size = 0;
     }
 
     /// Set the total number of bytes expected to be downloaded.
@@ -358,6 +375,78 @@ impl<'gc> NetStream<'gc> {
         );
     }
 
+    /// Create this stream on a `NetConnection`.
+    ///
+    /// Only RTMP connections carry streams; attaching to any other kind of
+    /// connection has no effect and the stream will continue to play files
+    /// over HTTP.
+    pub fn attach_to_connection(
+        self,
+        context: &mut UpdateContext<'gc>,
+        handle: NetConnectionHandle,
+    ) {
+        let index = context.net_connections.create_stream(handle, self);
+        self.0.write(context.gc()).rtmp_stream = index.map(|index| (handle, index));
+    }
+
+    /// Append a single media message received over RTMP to the buffer.
+    ///
+    /// RTMP audio, video and data messages carry the same payloads as FLV
+    /// tags, so each message is wrapped into an FLV tag and then played back
+    /// exactly like a progressively downloaded FLV file.
+    pub fn append_rtmp_data(
+        self,
+        context: &mut UpdateContext<'gc>,
+        tag_type: u8,
+        timestamp: u32,
+        data: &[u8],
+    ) {
+        let mut write = self.0.write(context.gc());
+
+        if write.buffer.len() == 0 {
+            // Audio + video FLV header, with the first tag directly after it.
+            write
+                .buffer
+                .extend_from_slice(b"FLV\x01\x05\x00\x00\x00\x09");
+            write.stream_time = timestamp as f64;
+        }
+
+        let length = data.len() as u32;
+        let mut tag = Vec::with_capacity(15 + data.len());
+        tag.extend_from_slice(&write.last_tag_size.to_be_bytes());
+        tag.push(tag_type);
+        tag.extend_from_slice(&length.to_be_bytes()[1..]);
+        tag.extend_from_slice(&timestamp.to_be_bytes()[1..]);
+        tag.push((timestamp >> 24) as u8);
+        tag.extend_from_slice(&[0, 0, 0]);
+        tag.extend_from_slice(data);
+
+        write.buffer.append(&mut tag);
+        write.last_tag_size = 11 + length;
+        drop(write);
+
+        StreamManager::activate(context, self);
+    }
+
+    /// Send a command for this stream to the RTMP server it was created on.
+    ///
+    /// Returns `false` if this stream is not an RTMP stream.
+    fn send_rtmp_command(
+        self,
+        context: &mut UpdateContext<'gc>,
+        command: &str,
+        arguments: Vec<AmfValue>,
+    ) -> bool {
+        let Some((handle, index)) = self.0.read().rtmp_stream else {
+            return false;
+        };
+
+        context
+            .net_connections
+            .send_stream_command(handle, index, command, arguments);
+        true
+    }
+
     /// Indicate that the buffer has finished loading and that no further data
     /// is expected to be downloaded to it.
     pub fn finish_buffer(self, context: &mut UpdateContext<'gc>) {
@@ -395,7 +484,14 @@ impl<'gc> NetStream<'gc> {
     ///
     /// `offset` is in milliseconds.
     pub fn seek(self, context: &mut UpdateContext<'gc>, offset: f64, notify: bool) {
-        self.0.write(context.gc()).queued_seek_time = Some(offset);
+        if self.0.read().rtmp_stream.is_some() {
+            // The server restarts the stream from the new position, so any
+            // data we already have is useless.
+            self.reset_buffer(context);
+            self.send_rtmp_command(context, "seek", vec![AmfValue::Number(offset)]);
+        } else {
+            self.0.write(context.gc()).queued_seek_time = Some(offset);
+        }
         StreamManager::activate(context, self);
 
         if notify {
@@ -545,6 +641,19 @@ impl<'gc> NetStream<'gc> {
     /// the given resource. Otherwise, the stream will play whatever data is
     /// available in the buffer.
     pub fn play(self, context: &mut UpdateContext<'gc>, name: Option<AvmString<'gc>>) {
+        if self.0.read().rtmp_stream.is_some() {
+            // The server reports `NetStream.Play.Start` itself once the stream
+            // is ready.
+            if let Some(name) = name {
+                self.reset_buffer(context);
+                self.send_rtmp_command(context, "play", vec![AmfValue::String(name.to_string())]);
+            }
+
+            self.0.write(context.gc()).playing = true;
+            StreamManager::activate(context, self);
+            return;
+        }
+
         if let Some(name) = name {
             let request = if let Ok(stream_url) =
                 Url::parse(context.swf.url()).and_then(|url| url.join(name.to_string().as_str()))
@@ -578,6 +687,13 @@ impl<'gc> NetStream<'gc> {
         // work to be done at tick time.
         self.0.write(context.gc()).playing = false;
 
+        let time = self.time();
+        self.send_rtmp_command(
+            context,
+            "pause",
+            vec![AmfValue::Bool(true), AmfValue::Number(time)],
+        );
+
         if notify {
             self.trigger_status_event(
                 context,
@@ -594,6 +710,13 @@ impl<'gc> NetStream<'gc> {
     pub fn resume(self, context: &mut UpdateContext<'gc>) {
         self.0.write(context.gc()).playing = true;
         StreamManager::activate(context, self);
+
+        let time = self.time();
+        self.send_rtmp_command(
+            context,
+            "pause",
+            vec![AmfValue::Bool(false), AmfValue::Number(time)],
+        );
     }
 
     /// Resume stream playback if paused, pause otherwise.
@@ -775,7 +898,7 @@ impl<'gc> NetStream<'gc> {
                     write.sound_instance = Some(context.audio_manager.start_substream(
                         context.audio,
                         substream.clone(),
-                        mc,
+                        Some(mc),
                         sound_stream_head,
                     )?);
                 } else {
@@ -1241,7 +1364,9 @@ impl<'gc> NetStream<'gc> {
         }
         drop(write);
 
-        if buffer_underrun {
+        // RTMP streams simply wait for the server to send more data; the
+        // server notifies the stream itself when playback stops.
+        if buffer_underrun && self.0.read().rtmp_stream.is_none() {
             let is_end_of_video = self.0.read().expected_length.is_none();
 
             self.trigger_status_event(
@@ -83,6 +83,7 @@ define_common_strings! {
     str_boldItalic: b"boldItalic",
     str_boolean: b"boolean",
     str_broadcastMessage: b"broadcastMessage",
+    str_bufferTime: b"bufferTime",
     str_builtInItems: b"builtInItems",
     str_bytesLoaded: b"bytesLoaded",
     str_bytesTotal: b"bytesTotal",
@@ -92,6 +93,7 @@ define_common_strings! {
     str_center: b"center",
     str_clamp: b"clamp",
     str_click: b"click",
+    str_client: b"client",
     str_code: b"code",
     str_color: b"color",
     str_complete: b"complete",
@@ -182,6 +184,7 @@ define_common_strings! {
     str_null: b"null",
     str_number: b"number",
     str_object: b"object",
+    str_objectEncoding: b"objectEncoding",
     str_onCancel: b"onCancel",
     str_onChanged: b"onChanged",
     str_onClose: b"onClose",
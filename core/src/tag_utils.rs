@@ -1,7 +1,10 @@
 use gc_arena::Collect;
+use std::cell::UnsafeCell;
 use std::fmt::{Debug, Formatter};
-use std::sync::Arc;
-use swf::{CharacterId, Fixed8, HeaderExt, Rectangle, TagCode, Twips};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use swf::{CharacterId, Fixed8, HeaderExt, Rectangle, StreamDecompressor, TagCode, Twips};
 use thiserror::Error;
 use url::Url;
 
@@ -51,8 +54,11 @@ pub struct SwfMovie {
     /// The SWF header parsed from the data stream.
     header: HeaderExt,
 
-    /// Uncompressed SWF data.
-    data: Vec<u8>,
+    /// Uncompressed SWF data, along with its compressed length.
+    data: Arc<MovieData>,
+
+    /// The length of the SWF header that precedes `data` in the uncompressed SWF.
+    header_len: usize,
 
     /// The URL the SWF was downloaded from.
     url: String,
@@ -67,9 +73,6 @@ pub struct SwfMovie {
     /// The suggest encoding for this SWF.
     encoding: &'static swf::Encoding,
 
-    /// The compressed length of the entire datastream
-    compressed_len: usize,
-
     /// Whether this SwfMovie actually represents a loaded movie or fills in for
     /// something else, like an loaded image, filler movie, or error state.
     is_movie: bool,
@@ -91,12 +94,12 @@ impl SwfMovie {
         let sandbox_type = SandboxType::infer(url.as_str(), &header);
         Self {
             header,
-            data: vec![],
+            data: Arc::new(MovieData::new(vec![], 0)),
+            header_len: 0,
             url,
             loader_url: None,
             parameters: Vec::new(),
             encoding: swf::UTF_8,
-            compressed_len: 0,
             is_movie: false,
             sandbox_type,
         }
@@ -114,8 +117,8 @@ impl SwfMovie {
         let sandbox_type = SandboxType::infer(url.as_str(), &header);
         Self {
             header,
-            compressed_len,
-            data: Vec::new(),
+            data: Arc::new(MovieData::new(vec![], compressed_len)),
+            header_len: 0,
             url,
             loader_url: None,
             parameters: Vec::new(),
@@ -128,6 +131,7 @@ impl SwfMovie {
     /// Like `fake_with_compressed_len`, but uses actual data.
     /// This is used when loading a Bitmap to expose the underlying content
     pub fn fake_with_compressed_data(swf_version: u8, compressed_data: Vec<u8>) -> Self {
+        let compressed_len = compressed_data.len();
         let url = "file:///".to_string();
         let header = HeaderExt::default_with_swf_version(swf_version);
 
@@ -135,8 +139,8 @@ impl SwfMovie {
         let sandbox_type = SandboxType::infer(url.as_str(), &header);
         Self {
             header,
-            compressed_len: compressed_data.len(),
-            data: compressed_data,
+            data: Arc::new(MovieData::new(compressed_data, compressed_len)),
+            header_len: 0,
             url,
             loader_url: None,
             parameters: Vec::new(),
@@ -158,12 +162,12 @@ impl SwfMovie {
         let sandbox_type = SandboxType::infer(movie_url.as_str(), &header);
         Self {
             header,
-            data: vec![],
+            data: Arc::new(MovieData::new(vec![], 0)),
+            header_len: 0,
             url: movie_url,
             loader_url: None,
             parameters: Vec::new(),
             encoding: swf::UTF_8,
-            compressed_len: 0,
             is_movie: false,
             sandbox_type,
         }
@@ -193,14 +197,61 @@ impl SwfMovie {
         let swf_buf = swf::read::decompress_swf(swf_data)?;
         let encoding = swf::SwfStr::encoding_for_version(swf_buf.header.version());
         let sandbox_type = SandboxType::infer(url.as_str(), &swf_buf.header);
+        let header_len =
+            (swf_buf.header.uncompressed_len().max(0) as usize).saturating_sub(swf_buf.data.len());
         let mut movie = Self {
             header: swf_buf.header,
-            data: swf_buf.data,
+            data: Arc::new(MovieData::new(swf_buf.data, compressed_len)),
+            header_len,
+            url,
+            loader_url,
+            parameters: Vec::new(),
+            encoding,
+            is_movie: true,
+            sandbox_type,
+        };
+        movie.append_parameters_from_url();
+        Ok(movie)
+    }
+
+    /// Construct a movie that is still downloading, from the start of its
+    /// datastream.
+    ///
+    /// The rest of the data is added with `append_data` as it arrives, and
+    /// `finish_loading` is called once the download is over. Fails if the
+    /// header of the SWF hasn't arrived yet.
+    ///
+    /// LZMA compressed movies are streamed like any other, but their data
+    /// usually arrives all at once at the end, as `StreamDecompressor` only
+    /// gets it whenever the LZMA decoder flushes. Decoding them any sooner is
+    /// out of scope.
+    pub fn from_stream(
+        stream: &StreamDecompressor,
+        url: String,
+        loader_url: Option<String>,
+        expected_len: Option<usize>,
+    ) -> Result<Self, Error> {
+        let (Some(header), Some(header_len)) = (stream.header(), stream.header_len()) else {
+            return Err(swf::error::Error::invalid_data("SWF header hasn't arrived yet").into());
+        };
+        let header = header.clone();
+        let uncompressed_len = header.uncompressed_len().max(0) as usize;
+        let data = MovieData::streaming(
+            stream.data(),
+            // Until the download is over, the total size is only an estimate.
+            expected_len.unwrap_or(uncompressed_len),
+        );
+
+        let encoding = swf::SwfStr::encoding_for_version(header.version());
+        let sandbox_type = SandboxType::infer(url.as_str(), &header);
+        let mut movie = Self {
+            header,
+            data: Arc::new(data),
+            header_len,
             url,
             loader_url,
             parameters: Vec::new(),
             encoding,
-            compressed_len,
             is_movie: true,
             sandbox_type,
         };
@@ -214,12 +265,12 @@ impl SwfMovie {
         let sandbox_type = SandboxType::infer(url.as_str(), &header);
         let mut movie = Self {
             header,
-            data: vec![],
+            data: Arc::new(MovieData::new(vec![], length)),
+            header_len: 0,
             url,
             loader_url: None,
             parameters: Vec::new(),
             encoding: swf::UTF_8,
-            compressed_len: length,
             is_movie: false,
             sandbox_type,
         };
@@ -252,8 +303,41 @@ impl SwfMovie {
         self.header.version()
     }
 
-    pub fn data(&self) -> &[u8] {
-        &self.data
+    /// The uncompressed tag data of the SWF.
+    ///
+    /// For a movie that is still downloading, this is only the data that has
+    /// arrived so far.
+    pub fn data(&self) -> MovieBytes {
+        self.data.get()
+    }
+
+    /// Add data to a movie that is still downloading.
+    pub fn append_data(&self, data: &[u8]) {
+        self.data.append(data);
+    }
+
+    /// Mark a movie as having finished downloading, now that its compressed
+    /// length is known.
+    pub fn finish_loading(&self, compressed_len: usize) {
+        self.data
+            .compressed_len
+            .store(compressed_len, Ordering::Relaxed);
+        self.data.is_loading.store(false, Ordering::Release);
+    }
+
+    /// Whether this movie is still downloading.
+    pub fn is_loading(&self) -> bool {
+        self.data.is_loading.load(Ordering::Acquire)
+    }
+
+    /// Whether this movie started playing before it finished downloading.
+    pub fn is_streamed(&self) -> bool {
+        self.data.is_streamed
+    }
+
+    /// The length of the SWF header that precedes `data` in the uncompressed SWF.
+    pub fn header_len(&self) -> usize {
+        self.header_len
     }
 
     /// Returns the suggested string encoding for the given SWF version.
@@ -297,7 +381,7 @@ impl SwfMovie {
     }
 
     pub fn compressed_len(&self) -> usize {
-        self.compressed_len
+        self.data.compressed_len.load(Ordering::Relaxed)
     }
 
     pub fn uncompressed_len(&self) -> i32 {
@@ -333,33 +417,202 @@ impl Debug for SwfMovie {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SwfMovie")
             .field("header", &self.header)
-            .field("data", &self.data.len())
+            .field("data", &self.data().len())
             .field("url", &self.url)
             .field("loader_url", &self.loader_url)
             .field("parameters", &self.parameters)
             .field("encoding", &self.encoding)
-            .field("compressed_len", &self.compressed_len)
+            .field("compressed_len", &self.compressed_len())
+            .field("is_loading", &self.is_loading())
             .field("is_movie", &self.is_movie)
             .field("sandbox_type", &self.sandbox_type)
             .finish()
     }
 }
 
+/// The uncompressed tag data of a movie.
+///
+/// A movie that is still downloading has its data appended as it arrives, and
+/// every chunk can be read as soon as it has been appended. The data is kept in
+/// a `ChunkStore`, which the chunks are appended to in place. The part of a
+/// store that can be read is never written to again, so it can be handed out
+/// while more data is appended. Once a store is full, its data is moved into a
+/// new one with twice the room, so that the copying adds up to about the size
+/// of the movie. Only the latest store is kept here: readers hold on to the
+/// store that they read from with `MovieBytes`, so a store that has been
+/// replaced is dropped as soon as nothing reads from it anymore.
+///
+/// Nothing is allocated based on the length in the SWF header, which may be
+/// wrong or even bogus, and data past that length is kept like it is for a
+/// movie that is loaded all at once.
+struct MovieData {
+    store: Mutex<Arc<ChunkStore>>,
+
+    /// The compressed length of the entire datastream.
+    compressed_len: AtomicUsize,
+
+    is_loading: AtomicBool,
+    is_streamed: bool,
+}
+
+impl MovieData {
+    /// The least amount of room in a store that a streamed movie is moved
+    /// into, so that the start of the movie isn't copied for every chunk that
+    /// arrives.
+    const MIN_CAPACITY: usize = 64 * 1024;
+
+    fn new(data: Vec<u8>, compressed_len: usize) -> Self {
+        Self {
+            store: Mutex::new(Arc::new(ChunkStore::new(data))),
+            compressed_len: AtomicUsize::new(compressed_len),
+            is_loading: AtomicBool::new(false),
+            is_streamed: false,
+        }
+    }
+
+    fn streaming(data: &[u8], compressed_len: usize) -> Self {
+        Self {
+            is_loading: AtomicBool::new(true),
+            is_streamed: true,
+            ..Self::new(data.to_vec(), compressed_len)
+        }
+    }
+
+    fn get(&self) -> MovieBytes {
+        let store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        MovieBytes::from(store.clone())
+    }
+
+    fn append(&self, data: &[u8]) {
+        let mut store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+        // SAFETY: Stores are only appended to while `store` is locked.
+        if unsafe { store.append(data) } {
+            return;
+        }
+
+        let len = store.len.load(Ordering::Relaxed);
+        let capacity = (len * 2).max(len + data.len()).max(Self::MIN_CAPACITY);
+        let next = ChunkStore::with_capacity(capacity);
+        // SAFETY: Nothing else has `next` yet.
+        unsafe {
+            next.append(&MovieBytes::from(store.clone()));
+            next.append(data);
+        }
+        *store = Arc::new(next);
+    }
+}
+
+/// An allocation that the data of a movie is appended to in place.
+struct ChunkStore {
+    bytes: Box<[UnsafeCell<u8>]>,
+
+    /// The number of bytes that have been appended. These are never written
+    /// to again.
+    len: AtomicUsize,
+}
+
+// SAFETY: Only bytes before `len` are read, and they're never written to again.
+// Bytes past `len` are only written to by `append`, which is never called by
+// more than one thread at a time.
+unsafe impl Sync for ChunkStore {}
+
+impl ChunkStore {
+    fn new(data: Vec<u8>) -> Self {
+        Self {
+            len: AtomicUsize::new(data.len()),
+            bytes: data.into_iter().map(UnsafeCell::new).collect(),
+        }
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bytes: (0..capacity).map(|_| UnsafeCell::new(0)).collect(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Append `data`, if there's room for it.
+    ///
+    /// # Safety
+    ///
+    /// This must not be called by more than one thread at a time.
+    unsafe fn append(&self, data: &[u8]) -> bool {
+        let len = self.len.load(Ordering::Relaxed);
+        let Some(room) = self.bytes.get(len..len + data.len()) else {
+            return false;
+        };
+        for (byte, value) in room.iter().zip(data) {
+            // SAFETY: Bytes past `len` aren't read, and the caller makes sure
+            // that nothing else writes to them.
+            unsafe { *byte.get() = *value };
+        }
+        self.len.store(len + data.len(), Ordering::Release);
+        true
+    }
+}
+
+/// The data of a movie, as much of it as had arrived when this was made.
+///
+/// The data stays available for as long as this is held, even if more data
+/// has been appended to the movie since.
+#[derive(Clone, Collect)]
+#[collect(require_static)]
+pub struct MovieBytes {
+    store: Arc<ChunkStore>,
+    len: usize,
+}
+
+impl From<Arc<ChunkStore>> for MovieBytes {
+    fn from(store: Arc<ChunkStore>) -> Self {
+        Self {
+            len: store.len.load(Ordering::Acquire),
+            store,
+        }
+    }
+}
+
+impl Deref for MovieBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `UnsafeCell<u8>` has the same layout as `u8`, and the first
+        // `len` bytes of the store are never written to again.
+        unsafe { std::slice::from_raw_parts(self.store.bytes.as_ptr().cast(), self.len) }
+    }
+}
+
+impl Debug for MovieBytes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MovieBytes")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
 /// A shared-ownership reference to some portion of an SWF datastream.
 #[derive(Debug, Clone, Collect)]
 #[collect(no_drop)]
 pub struct SwfSlice {
     pub movie: Arc<SwfMovie>,
+
+    /// The data of the movie that this slice refers to.
+    ///
+    /// Slices made from this one share it, even if more data has been
+    /// appended to the movie since, as their offsets are only valid for it.
+    bytes: MovieBytes,
+
     pub start: usize,
     pub end: usize,
 }
 
 impl From<Arc<SwfMovie>> for SwfSlice {
     fn from(movie: Arc<SwfMovie>) -> Self {
-        let end = movie.data().len();
+        let bytes = movie.data();
+        let end = bytes.len();
 
         Self {
             movie,
+            bytes,
             start: 0,
             end,
         }
@@ -378,6 +631,7 @@ impl SwfSlice {
     #[inline]
     pub fn empty(movie: Arc<SwfMovie>) -> Self {
         Self {
+            bytes: movie.data(),
             movie,
             start: 0,
             end: 0,
@@ -387,7 +641,12 @@ impl SwfSlice {
     /// Creates an empty SwfSlice of the same movie.
     #[inline]
     pub fn copy_empty(&self) -> Self {
-        Self::empty(self.movie.clone())
+        Self {
+            movie: self.movie.clone(),
+            bytes: self.bytes.clone(),
+            start: 0,
+            end: 0,
+        }
     }
 
     /// Construct a new SwfSlice from a regular slice.
@@ -395,12 +654,13 @@ impl SwfSlice {
     /// This function returns None if the given slice is not a subslice of the
     /// current slice.
     pub fn to_subslice(&self, slice: &[u8]) -> Self {
-        let self_pval = self.movie.data().as_ptr() as usize;
+        let self_pval = self.bytes.as_ptr() as usize;
         let slice_pval = slice.as_ptr() as usize;
 
         if (self_pval + self.start) <= slice_pval && slice_pval < (self_pval + self.end) {
             Self {
                 movie: self.movie.clone(),
+                bytes: self.bytes.clone(),
                 start: slice_pval - self_pval,
                 end: (slice_pval - self_pval) + slice.len(),
             }
@@ -414,13 +674,14 @@ impl SwfSlice {
     /// This function allows subslices outside the current slice to be formed,
     /// as long as they are valid subslices of the movie itself.
     pub fn to_unbounded_subslice(&self, slice: &[u8]) -> Self {
-        let self_pval = self.movie.data().as_ptr() as usize;
-        let self_len = self.movie.data().len();
+        let self_pval = self.bytes.as_ptr() as usize;
+        let self_len = self.bytes.len();
         let slice_pval = slice.as_ptr() as usize;
 
         if self_pval <= slice_pval && slice_pval < (self_pval + self_len) {
             Self {
                 movie: self.movie.clone(),
+                bytes: self.bytes.clone(),
                 start: slice_pval - self_pval,
                 end: (slice_pval - self_pval) + slice.len(),
             }
@@ -440,20 +701,20 @@ impl SwfSlice {
     /// movie, or the given reader refers to a different underlying movie, this
     /// function returns an empty slice.
     pub fn resize_to_reader(&self, reader: &mut SwfStream<'_>, size: usize) -> Self {
-        if self.movie.data().as_ptr() as usize <= reader.get_ref().as_ptr() as usize
+        if self.bytes.as_ptr() as usize <= reader.get_ref().as_ptr() as usize
             && (reader.get_ref().as_ptr() as usize)
-                < self.movie.data().as_ptr() as usize + self.movie.data().len()
+                < self.bytes.as_ptr() as usize + self.bytes.len()
         {
-            let outer_offset =
-                reader.get_ref().as_ptr() as usize - self.movie.data().as_ptr() as usize;
+            let outer_offset = reader.get_ref().as_ptr() as usize - self.bytes.as_ptr() as usize;
             let new_start = outer_offset;
             let new_end = outer_offset + size;
 
-            let len = self.movie.data().len();
+            let len = self.bytes.len();
 
             if new_start < len && new_end < len {
                 Self {
                     movie: self.movie.clone(),
+                    bytes: self.bytes.clone(),
                     start: new_start,
                     end: new_end,
                 }
@@ -476,7 +737,7 @@ impl SwfSlice {
         let new_end = self.start + end;
 
         if new_start <= new_end {
-            if let Some(result) = self.movie.data().get(new_start..new_end) {
+            if let Some(result) = self.bytes.get(new_start..new_end) {
                 self.to_subslice(result)
             } else {
                 self.copy_empty()
@@ -488,7 +749,13 @@ impl SwfSlice {
 
     /// Convert the SwfSlice into a standard data slice.
     pub fn data(&self) -> &[u8] {
-        &self.movie.data()[self.start..self.end]
+        &self.bytes[self.start..self.end]
+    }
+
+    /// All of the data of the movie that this slice refers to, for reading
+    /// past the bounds of the slice.
+    pub fn movie_data(&self) -> &[u8] {
+        &self.bytes
     }
 
     /// Get the version of the SWF this data comes from.
@@ -570,3 +837,134 @@ where
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{Compression, DefineBinaryData, Header, Tag};
+
+    const NUM_BINARY_DATA: u16 = 100;
+
+    /// An uncompressed SWF with a header that claims it's `claimed_len` bytes long.
+    fn mislabelled_swf(claimed_len: u32) -> Vec<u8> {
+        let data = vec![0xAB; 2000];
+        let mut tags: Vec<Tag> = (1..=NUM_BINARY_DATA)
+            .map(|id| Tag::DefineBinaryData(DefineBinaryData { id, data: &data }))
+            .collect();
+        tags.push(Tag::ShowFrame);
+
+        let header = Header {
+            compression: Compression::None,
+            num_frames: 1,
+            ..Header::default_with_swf_version(10)
+        };
+        let mut swf = vec![];
+        swf::write_swf(&header, &tags, &mut swf).unwrap();
+        swf[4..8].copy_from_slice(&claimed_len.to_le_bytes());
+        swf
+    }
+
+    /// Stream an SWF into a movie, `chunk_len` bytes at a time.
+    fn stream_swf(swf: &[u8], chunk_len: usize, on_chunk: impl Fn(&SwfMovie)) -> SwfMovie {
+        let mut stream = StreamDecompressor::new();
+        let mut movie: Option<SwfMovie> = None;
+        let mut appended = 0;
+        for chunk in swf.chunks(chunk_len) {
+            stream.push(chunk).unwrap();
+            match &movie {
+                Some(movie) => movie.append_data(&stream.data()[appended..]),
+                None if stream.header().is_some() => {
+                    movie = Some(
+                        SwfMovie::from_stream(&stream, "file:///movie.swf".into(), None, None)
+                            .unwrap(),
+                    );
+                }
+                None => continue,
+            }
+            appended = stream.data().len();
+            on_chunk(movie.as_ref().unwrap());
+        }
+
+        let movie = movie.unwrap();
+        let compressed_len = stream.compressed_len();
+        movie.append_data(&stream.finish().unwrap().data[appended..]);
+        movie.finish_loading(compressed_len);
+        movie
+    }
+
+    fn count_binary_data(movie: &SwfMovie) -> u16 {
+        let mut count = 0;
+        let data = movie.data();
+        let mut reader = SwfStream::new(&data, movie.version());
+        decode_tags(&mut reader, |_reader, tag_code, _tag_len| {
+            match tag_code {
+                TagCode::DefineBinaryData => count += 1,
+                TagCode::End => return Ok(ControlFlow::Exit),
+                _ => {}
+            }
+            Ok(ControlFlow::Continue)
+        })
+        .unwrap();
+        count
+    }
+
+    #[test]
+    fn streams_movie_longer_than_its_header() {
+        let swf = mislabelled_swf(1000);
+        let expected = SwfMovie::from_data(&swf, "file:///movie.swf".into(), None).unwrap();
+
+        let movie = stream_swf(&swf, 1500, |movie| {
+            assert!(movie.is_loading());
+            assert!(expected.data().starts_with(&movie.data()));
+        });
+
+        assert!(movie.is_streamed());
+        assert!(!movie.is_loading());
+        assert_eq!(movie.compressed_len(), swf.len());
+        assert_eq!(*movie.data(), *expected.data());
+        assert_eq!(count_binary_data(&movie), NUM_BINARY_DATA);
+    }
+
+    #[test]
+    fn streams_movie_with_bogus_length() {
+        // Loading it all at once would try to allocate all 4 GiB up front.
+        let swf = mislabelled_swf(u32::MAX);
+        let expected =
+            SwfMovie::from_data(&mislabelled_swf(1000), "file:///movie.swf".into(), None).unwrap();
+
+        let movie = stream_swf(&swf, 4096, |_movie| {});
+
+        assert_eq!(*movie.data(), *expected.data());
+        assert_eq!(count_binary_data(&movie), NUM_BINARY_DATA);
+    }
+
+    #[test]
+    fn publishes_every_chunk() {
+        let data = MovieData::streaming(&[], 0);
+        let first = data.get();
+        let first_store = Arc::downgrade(&first.store);
+
+        let mut expected = vec![];
+        let mut held = vec![];
+        for chunk in 0..100u8 {
+            let chunk = vec![chunk; 1000];
+            data.append(&chunk);
+            expected.extend_from_slice(&chunk);
+            assert_eq!(*data.get(), *expected);
+            held.push(data.get());
+        }
+
+        // Data that has been handed out stays the same as more is appended.
+        assert!(first.is_empty());
+        for (index, bytes) in held.iter().enumerate() {
+            assert_eq!(**bytes, expected[..(index + 1) * 1000]);
+        }
+
+        // Stores that have been replaced are dropped once nothing reads from them.
+        assert!(!Arc::ptr_eq(&first.store, &data.get().store));
+        drop(first);
+        assert!(first_store.upgrade().is_none());
+        drop(held);
+        assert_eq!(Arc::strong_count(&data.get().store), 2);
+    }
+}
@@ -10,6 +10,7 @@ use crate::avm2::{Activation as Avm2Activation, Object as Avm2Object, Value as A
 use crate::context::UpdateContext;
 use crate::display_object::{DisplayObject, TDisplayObject};
 use crate::string::AvmString;
+use crate::worker::{WorkerHandle, Workers};
 use gc_arena::Collect;
 use std::collections::{binary_heap::PeekMut, BinaryHeap};
 
@@ -135,20 +136,28 @@ impl<'gc> Timers<'gc> {
                         true
                     }
                 }
-                TimerCallback::Avm2Callback { closure, params } => {
-                    let domain = context.avm2.stage_domain();
-                    let mut avm2_activation = Avm2Activation::from_domain(context, domain);
-                    match Avm2Value::from(closure).call(
-                        &mut avm2_activation,
-                        Avm2Value::Null,
-                        &params,
-                    ) {
-                        Ok(v) => v.coerce_to_boolean(),
-                        Err(e) => {
-                            tracing::error!("Unhandled AVM2 error in timer callback: {e:?}",);
-                            false
+                TimerCallback::Avm2Callback {
+                    closure,
+                    params,
+                    worker,
+                } => {
+                    // Timers of a terminated worker are cancelled.
+                    Workers::run_in(context, worker, |context| {
+                        let domain = context.avm2.stage_domain();
+                        let mut avm2_activation = Avm2Activation::from_domain(context, domain);
+                        match Avm2Value::from(closure).call(
+                            &mut avm2_activation,
+                            Avm2Value::Null,
+                            &params,
+                        ) {
+                            Ok(v) => v.coerce_to_boolean(),
+                            Err(e) => {
+                                tracing::error!("Unhandled AVM2 error in timer callback: {e:?}",);
+                                false
+                            }
                         }
-                    }
+                    })
+                    .unwrap_or(true)
                 }
             };
 
@@ -366,5 +375,7 @@ pub enum TimerCallback<'gc> {
     Avm2Callback {
         closure: Avm2Object<'gc>,
         params: Vec<Avm2Value<'gc>>,
+        /// The worker the callback was created in, and must be run in.
+        worker: WorkerHandle,
     },
 }
@@ -0,0 +1,748 @@
+//! Background workers, and the message channels between them.
+//!
+//! Every worker other than the primordial one runs a movie in its own `Avm2`
+//! instance, with its own domains. Workers don't run in parallel; instead,
+//! each running worker advances one frame in turn during the player's frame.
+//! While a worker runs, its `Avm2` is swapped into the `UpdateContext`, so
+//! code running in it only ever sees its own classes.
+//!
+//! Values only cross between workers through shared properties and
+//! `MessageChannel`s, which copy them with AMF serialization. The exceptions
+//! are workers and channels themselves, which are passed by handle, and
+//! shareable ByteArrays, whose bytes are shared. Display objects and classes
+//! can't be passed at all, so a worker can never reach the display list or
+//! the domains of another.
+//!
+//! Loaders, sounds, net streams and sockets can only be used from the
+//! primordial worker, as their events are always dispatched there.
+
+use crate::avm2::amf::{deserialize_value, serialize_value};
+use crate::avm2::api_version::ApiVersion;
+use crate::avm2::bytearray::ByteArrayStorage;
+use crate::avm2::error::{illegal_operation_error, make_error_2004, Error2004Type};
+use crate::avm2::globals::slots::flash_system_messagechannel as channel_slots;
+use crate::avm2::globals::slots::flash_system_worker as worker_slots;
+use crate::avm2::object::{ByteArrayObject, ScriptObject, ScriptObjectData};
+use crate::avm2::property::Property;
+use crate::avm2::{
+    Activation as Avm2Activation, Avm2, Error as Avm2Error, EventObject as Avm2EventObject,
+    Object as Avm2Object, TObject as _, Value as Avm2Value,
+};
+use crate::context::UpdateContext;
+use crate::display_object::{DisplayObject, MovieClip, TDisplayObject};
+use crate::frame_lifecycle::run_worker_phases_avm2;
+use crate::limits::ExecutionLimit;
+use crate::tag_utils::SwfMovie;
+use crate::vminterface::Instantiator;
+use flash_lso::types::{AMFVersion, Value as AmfValue};
+use gc_arena::{Collect, Gc};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+/// Identifies a worker.
+#[derive(Clone, Copy, Collect, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[collect(require_static)]
+pub struct WorkerHandle(u32);
+
+impl WorkerHandle {
+    /// The worker running the player's root movie.
+    pub const PRIMORDIAL: Self = Self(0);
+
+    pub fn from_u32(handle: u32) -> Self {
+        Self(handle)
+    }
+
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Identifies a message channel.
+#[derive(Clone, Copy, Collect, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[collect(require_static)]
+pub struct ChannelHandle(u32);
+
+impl ChannelHandle {
+    pub fn from_u32(handle: u32) -> Self {
+        Self(handle)
+    }
+
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// The values of `flash.system.WorkerState`.
+#[derive(Clone, Copy, Collect, Debug, PartialEq, Eq)]
+#[collect(require_static)]
+pub enum WorkerState {
+    New,
+    Running,
+    Terminated,
+}
+
+impl WorkerState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WorkerState::New => "new",
+            WorkerState::Running => "running",
+            WorkerState::Terminated => "terminated",
+        }
+    }
+}
+
+/// The values of `flash.system.MessageChannelState`.
+#[derive(Clone, Copy, Collect, Debug, PartialEq, Eq)]
+#[collect(require_static)]
+pub enum ChannelState {
+    Open,
+    /// The channel was closed, but still has messages waiting to be received.
+    Closing,
+    Closed,
+}
+
+impl ChannelState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChannelState::Open => "open",
+            ChannelState::Closing => "closing",
+            ChannelState::Closed => "closed",
+        }
+    }
+}
+
+/// A value passed from one worker to another.
+#[derive(Clone, Collect)]
+#[collect(no_drop)]
+pub enum SharedValue<'gc> {
+    /// A copy of a value, serialized as AMF3.
+    Amf(#[collect(require_static)] AmfValue),
+
+    /// The bytes of a shareable ByteArray.
+    ByteArray(Gc<'gc, RefCell<ByteArrayStorage>>),
+
+    Worker(WorkerHandle),
+
+    Channel(ChannelHandle),
+}
+
+impl<'gc> SharedValue<'gc> {
+    /// Prepare a value from the current worker to be passed to another.
+    ///
+    /// Display objects and classes can't be passed, even inside of other
+    /// values, as they belong to the display list and domains of the worker
+    /// that created them.
+    pub fn from_value(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        value: Avm2Value<'gc>,
+    ) -> Result<Self, Avm2Error<'gc>> {
+        check_shareable(activation, value, &mut Vec::new())?;
+
+        if let Some(object) = value.as_object() {
+            if let Some(bytearray) = object.as_bytearray_object().filter(|b| b.shareable()) {
+                return Ok(SharedValue::ByteArray(bytearray.shared_storage()));
+            }
+
+            let classes = activation.avm2().classes();
+            if object.is_of_type(classes.worker.inner_class_definition()) {
+                let handle = object.get_slot(worker_slots::_HANDLE).as_u32();
+                return Ok(SharedValue::Worker(WorkerHandle(handle)));
+            }
+            if object.is_of_type(classes.messagechannel.inner_class_definition()) {
+                let handle = object.get_slot(channel_slots::_HANDLE).as_u32();
+                return Ok(SharedValue::Channel(ChannelHandle(handle)));
+            }
+        }
+
+        Ok(SharedValue::Amf(
+            serialize_value(activation, value, AMFVersion::AMF3, &mut Default::default())
+                .unwrap_or(AmfValue::Undefined),
+        ))
+    }
+
+    /// Recreate this value in the current worker.
+    pub fn to_value(
+        &self,
+        activation: &mut Avm2Activation<'_, 'gc>,
+    ) -> Result<Avm2Value<'gc>, Avm2Error<'gc>> {
+        Ok(match self {
+            SharedValue::Amf(value) => deserialize_value(activation, value)?,
+            SharedValue::ByteArray(storage) => {
+                ByteArrayObject::from_shared_storage(activation, *storage)?.into()
+            }
+            SharedValue::Worker(handle) => Workers::worker_object(activation, *handle)?.into(),
+            SharedValue::Channel(handle) => Workers::channel_object(activation, *handle)?.into(),
+        })
+    }
+}
+
+/// Fail if a display object or class is reachable from `value`, walking it
+/// the same way that AMF serialization does.
+fn check_shareable<'gc>(
+    activation: &mut Avm2Activation<'_, 'gc>,
+    value: Avm2Value<'gc>,
+    visited: &mut Vec<Avm2Object<'gc>>,
+) -> Result<(), Avm2Error<'gc>> {
+    let Some(object) = value.as_object() else {
+        return Ok(());
+    };
+
+    if object.as_display_object().is_some() || object.as_class_object().is_some() {
+        return Err(make_error_2004(activation, Error2004Type::ArgumentError));
+    }
+
+    if object.as_function_object().is_some()
+        || object.as_date_object().is_some()
+        || object.as_xml_object().is_some()
+        || object.as_bytearray().is_some()
+        || visited
+            .iter()
+            .any(|visited| Avm2Object::ptr_eq(*visited, object))
+    {
+        return Ok(());
+    }
+    visited.push(object);
+
+    let mut values = Vec::new();
+    if let Some(vector) = object.as_vector_storage() {
+        values.extend(vector.iter());
+    } else {
+        if object.as_array_storage().is_none() {
+            for (name, property) in object.vtable().public_properties() {
+                match property {
+                    Property::Method { .. } => continue,
+                    Property::Virtual { get, set } if get.is_none() || set.is_none() => continue,
+                    _ => {}
+                }
+                values.push(Avm2Value::from(object).get_public_property(name, activation)?);
+            }
+        }
+
+        let mut index = object.get_next_enumerant(0, activation)?;
+        while index != 0 {
+            values.push(object.get_enumerant_value(index, activation)?);
+            index = object.get_next_enumerant(index, activation)?;
+        }
+    }
+
+    for value in values {
+        check_shareable(activation, value, visited)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Worker<'gc> {
+    state: WorkerState,
+
+    /// The movie this worker runs. The primordial worker has none, as it
+    /// runs the player's root movie.
+    #[collect(require_static)]
+    movie: Option<Arc<SwfMovie>>,
+
+    /// The interpreter this worker runs in, once it has been started.
+    ///
+    /// This is always `None` for the primordial worker, whose interpreter is
+    /// the player's, and for any worker that is currently running.
+    avm2: Option<Avm2<'gc>>,
+
+    /// The root of this worker's movie, once its interpreter is set up.
+    root: Option<DisplayObject<'gc>>,
+
+    shared_properties: BTreeMap<String, SharedValue<'gc>>,
+
+    /// The `Worker` objects representing workers in this worker's interpreter.
+    worker_objects: BTreeMap<WorkerHandle, Avm2Object<'gc>>,
+
+    /// The `MessageChannel` objects representing channels in this worker's
+    /// interpreter.
+    channel_objects: BTreeMap<ChannelHandle, Avm2Object<'gc>>,
+}
+
+impl<'gc> Worker<'gc> {
+    fn new(state: WorkerState, movie: Option<Arc<SwfMovie>>) -> Self {
+        Self {
+            state,
+            movie,
+            avm2: None,
+            root: None,
+            shared_properties: BTreeMap::new(),
+            worker_objects: BTreeMap::new(),
+            channel_objects: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Collect)]
+#[collect(no_drop)]
+struct Channel<'gc> {
+    sender: WorkerHandle,
+    receiver: WorkerHandle,
+    state: ChannelState,
+    messages: VecDeque<SharedValue<'gc>>,
+}
+
+/// An event to be dispatched to every object representing a worker or
+/// channel, at the end of the frame.
+#[derive(Clone, Copy, Collect, Debug)]
+#[collect(require_static)]
+enum PendingEvent {
+    WorkerState(WorkerHandle),
+    ChannelMessage(ChannelHandle),
+    ChannelState(ChannelHandle),
+}
+
+/// Manages the workers of the player.
+#[derive(Collect)]
+#[collect(no_drop)]
+pub struct Workers<'gc> {
+    workers: BTreeMap<WorkerHandle, Worker<'gc>>,
+    channels: BTreeMap<ChannelHandle, Channel<'gc>>,
+
+    /// The worker whose code is currently running.
+    current: WorkerHandle,
+
+    next_worker: u32,
+    next_channel: u32,
+
+    pending_events: Vec<PendingEvent>,
+}
+
+impl Default for Workers<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'gc> Workers<'gc> {
+    pub fn new() -> Self {
+        let mut workers = BTreeMap::new();
+        workers.insert(
+            WorkerHandle::PRIMORDIAL,
+            Worker::new(WorkerState::Running, None),
+        );
+
+        Self {
+            workers,
+            channels: BTreeMap::new(),
+            current: WorkerHandle::PRIMORDIAL,
+            next_worker: 1,
+            next_channel: 0,
+            pending_events: Vec::new(),
+        }
+    }
+
+    /// The worker whose code is currently running.
+    pub fn current(&self) -> WorkerHandle {
+        self.current
+    }
+
+    /// The state of a worker. Workers that no longer exist are terminated.
+    pub fn state(&self, handle: WorkerHandle) -> WorkerState {
+        self.workers
+            .get(&handle)
+            .map_or(WorkerState::Terminated, |worker| worker.state)
+    }
+
+    /// All workers that have not been terminated.
+    pub fn live_workers(&self) -> Vec<WorkerHandle> {
+        self.workers
+            .iter()
+            .filter(|(_, worker)| worker.state != WorkerState::Terminated)
+            .map(|(handle, _)| *handle)
+            .collect()
+    }
+
+    /// Create a new worker that will run the given movie once started.
+    pub fn create_worker(&mut self, movie: Arc<SwfMovie>) -> WorkerHandle {
+        let handle = WorkerHandle(self.next_worker);
+        self.next_worker += 1;
+        self.workers
+            .insert(handle, Worker::new(WorkerState::New, Some(movie)));
+        handle
+    }
+
+    /// Start a new worker.
+    ///
+    /// The worker's movie starts running on the next frame.
+    pub fn start(context: &mut UpdateContext<'gc>, handle: WorkerHandle) {
+        let avm2 = Avm2::new(
+            &mut context.strings,
+            context.player_version,
+            context.avm2.player_runtime,
+        );
+
+        let workers = &mut *context.workers;
+        if let Some(worker) = workers.workers.get_mut(&handle) {
+            if worker.state == WorkerState::New {
+                worker.state = WorkerState::Running;
+                worker.avm2 = Some(avm2);
+                workers
+                    .pending_events
+                    .push(PendingEvent::WorkerState(handle));
+            }
+        }
+    }
+
+    /// Terminate a worker, discarding its interpreter and movie.
+    ///
+    /// Returns `false` if the worker was not running. The primordial worker
+    /// can't be terminated.
+    pub fn terminate(&mut self, handle: WorkerHandle) -> bool {
+        if handle == WorkerHandle::PRIMORDIAL {
+            return false;
+        }
+
+        let Some(worker) = self.workers.get_mut(&handle) else {
+            return false;
+        };
+        let was_running = worker.state == WorkerState::Running;
+        worker.state = WorkerState::Terminated;
+        worker.avm2 = None;
+        worker.root = None;
+        worker.shared_properties.clear();
+        worker.worker_objects.clear();
+        worker.channel_objects.clear();
+
+        for (channel_handle, channel) in &mut self.channels {
+            if (channel.sender == handle || channel.receiver == handle)
+                && channel.state != ChannelState::Closed
+            {
+                channel.state = ChannelState::Closed;
+                channel.messages.clear();
+                self.pending_events
+                    .push(PendingEvent::ChannelState(*channel_handle));
+            }
+        }
+
+        self.pending_events.push(PendingEvent::WorkerState(handle));
+        was_running
+    }
+
+    pub fn set_shared_property(
+        &mut self,
+        handle: WorkerHandle,
+        key: String,
+        value: SharedValue<'gc>,
+    ) {
+        if let Some(worker) = self.workers.get_mut(&handle) {
+            worker.shared_properties.insert(key, value);
+        }
+    }
+
+    pub fn shared_property(&self, handle: WorkerHandle, key: &str) -> Option<&SharedValue<'gc>> {
+        self.workers.get(&handle)?.shared_properties.get(key)
+    }
+
+    /// Create a channel sending messages from one worker to another.
+    pub fn create_channel(
+        &mut self,
+        sender: WorkerHandle,
+        receiver: WorkerHandle,
+    ) -> ChannelHandle {
+        let handle = ChannelHandle(self.next_channel);
+        self.next_channel += 1;
+        self.channels.insert(
+            handle,
+            Channel {
+                sender,
+                receiver,
+                state: ChannelState::Open,
+                messages: VecDeque::new(),
+            },
+        );
+        handle
+    }
+
+    pub fn channel_state(&self, handle: ChannelHandle) -> ChannelState {
+        self.channels
+            .get(&handle)
+            .map_or(ChannelState::Closed, |channel| channel.state)
+    }
+
+    pub fn has_message(&self, handle: ChannelHandle) -> bool {
+        self.channels
+            .get(&handle)
+            .is_some_and(|channel| !channel.messages.is_empty())
+    }
+
+    /// Queue a message on a channel.
+    ///
+    /// Returns `false` if the channel is closed.
+    pub fn send(&mut self, handle: ChannelHandle, message: SharedValue<'gc>) -> bool {
+        let Some(channel) = self.channels.get_mut(&handle) else {
+            return false;
+        };
+        if channel.state != ChannelState::Open {
+            return false;
+        }
+
+        channel.messages.push_back(message);
+        self.pending_events
+            .push(PendingEvent::ChannelMessage(handle));
+        true
+    }
+
+    /// Take the oldest message waiting on a channel.
+    pub fn receive(&mut self, handle: ChannelHandle) -> Option<SharedValue<'gc>> {
+        let channel = self.channels.get_mut(&handle)?;
+        let message = channel.messages.pop_front()?;
+
+        if channel.state == ChannelState::Closing && channel.messages.is_empty() {
+            channel.state = ChannelState::Closed;
+            self.pending_events.push(PendingEvent::ChannelState(handle));
+        }
+
+        Some(message)
+    }
+
+    /// Close a channel. Messages that have already been sent can still be
+    /// received.
+    pub fn close(&mut self, handle: ChannelHandle) {
+        let Some(channel) = self.channels.get_mut(&handle) else {
+            return;
+        };
+        if channel.state != ChannelState::Open {
+            return;
+        }
+
+        channel.state = if channel.messages.is_empty() {
+            ChannelState::Closed
+        } else {
+            ChannelState::Closing
+        };
+        self.pending_events.push(PendingEvent::ChannelState(handle));
+    }
+
+    /// Get the object representing a worker in the current worker's
+    /// interpreter, creating it if needed.
+    pub fn worker_object(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        handle: WorkerHandle,
+    ) -> Result<Avm2Object<'gc>, Avm2Error<'gc>> {
+        let workers = &activation.context.workers;
+        if let Some(object) = workers
+            .workers
+            .get(&workers.current)
+            .and_then(|worker| worker.worker_objects.get(&handle))
+        {
+            return Ok(*object);
+        }
+
+        let class = activation.avm2().classes().worker;
+        let object: Avm2Object<'gc> =
+            ScriptObject(Gc::new(activation.gc(), ScriptObjectData::new(class))).into();
+        object.set_slot(worker_slots::_HANDLE, handle.0.into(), activation)?;
+        class.call_init(object.into(), &[], activation)?;
+
+        let workers = &mut *activation.context.workers;
+        if let Some(worker) = workers.workers.get_mut(&workers.current) {
+            worker.worker_objects.insert(handle, object);
+        }
+
+        Ok(object)
+    }
+
+    /// Get the object representing a channel in the current worker's
+    /// interpreter, creating it if needed.
+    pub fn channel_object(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        handle: ChannelHandle,
+    ) -> Result<Avm2Object<'gc>, Avm2Error<'gc>> {
+        let workers = &activation.context.workers;
+        if let Some(object) = workers
+            .workers
+            .get(&workers.current)
+            .and_then(|worker| worker.channel_objects.get(&handle))
+        {
+            return Ok(*object);
+        }
+
+        let class = activation.avm2().classes().messagechannel;
+        let object: Avm2Object<'gc> =
+            ScriptObject(Gc::new(activation.gc(), ScriptObjectData::new(class))).into();
+        object.set_slot(channel_slots::_HANDLE, handle.0.into(), activation)?;
+        class.call_init(object.into(), &[], activation)?;
+
+        let workers = &mut *activation.context.workers;
+        if let Some(worker) = workers.workers.get_mut(&workers.current) {
+            worker.channel_objects.insert(handle, object);
+        }
+
+        Ok(object)
+    }
+
+    /// Throw if `api` is called from a background worker.
+    ///
+    /// Loaders, sounds, net streams and sockets finish asynchronously, and
+    /// their events are always dispatched in the primordial worker. Until
+    /// they remember the worker that started them, only the primordial
+    /// worker can use them.
+    pub fn require_primordial(
+        activation: &mut Avm2Activation<'_, 'gc>,
+        api: &str,
+    ) -> Result<(), Avm2Error<'gc>> {
+        if activation.context.workers.current == WorkerHandle::PRIMORDIAL {
+            return Ok(());
+        }
+
+        Err(Avm2Error::AvmError(illegal_operation_error(
+            activation,
+            &format!("{api} is not available in background workers"),
+            0,
+        )?))
+    }
+
+    /// Run a function with the given worker's interpreter swapped into the
+    /// context.
+    ///
+    /// Returns `None` without calling the function if the worker isn't
+    /// running.
+    pub fn run_in<R>(
+        context: &mut UpdateContext<'gc>,
+        handle: WorkerHandle,
+        f: impl FnOnce(&mut UpdateContext<'gc>) -> R,
+    ) -> Option<R> {
+        if handle == context.workers.current {
+            return Some(f(context));
+        }
+
+        let mut avm2 = context.workers.workers.get_mut(&handle)?.avm2.take()?;
+        std::mem::swap(context.avm2, &mut avm2);
+        let previous = std::mem::replace(&mut context.workers.current, handle);
+
+        let result = f(context);
+
+        context.workers.current = previous;
+        std::mem::swap(context.avm2, &mut avm2);
+        if let Some(worker) = context.workers.workers.get_mut(&handle) {
+            // A worker that terminated itself doesn't get its interpreter back.
+            if worker.state == WorkerState::Running {
+                worker.avm2 = Some(avm2);
+            }
+        }
+
+        Some(result)
+    }
+
+    /// Run a frame of every background worker, then dispatch the events
+    /// queued since the last frame.
+    ///
+    /// This must be called from the primordial worker.
+    pub fn run_frame(context: &mut UpdateContext<'gc>) {
+        let handles: Vec<_> = context
+            .workers
+            .workers
+            .iter()
+            .filter(|(handle, worker)| {
+                **handle != WorkerHandle::PRIMORDIAL && worker.state == WorkerState::Running
+            })
+            .map(|(handle, _)| *handle)
+            .collect();
+
+        for handle in handles {
+            Self::run_in(context, handle, |context| {
+                let root = match context.workers.workers.get(&handle).and_then(|w| w.root) {
+                    Some(root) => root,
+                    None => {
+                        let root = Self::start_movie(context, handle);
+                        if let Some(worker) = context.workers.workers.get_mut(&handle) {
+                            worker.root = Some(root);
+                        }
+                        root
+                    }
+                };
+
+                run_worker_phases_avm2(context, root);
+            });
+        }
+
+        Self::dispatch_events(context);
+    }
+
+    /// Set up a freshly started worker's interpreter, and create the root of
+    /// its movie.
+    fn start_movie(context: &mut UpdateContext<'gc>, handle: WorkerHandle) -> DisplayObject<'gc> {
+        let movie = context.workers.workers[&handle]
+            .movie
+            .clone()
+            .expect("Background workers have a movie");
+
+        if let Err(e) = Avm2::load_player_globals(context) {
+            tracing::error!("Unable to load AVM2 globals for worker: {e:?}");
+        }
+        context.avm2.root_api_version =
+            ApiVersion::from_swf_version(movie.version(), context.avm2.player_runtime)
+                .unwrap_or(ApiVersion::AllVersions);
+
+        let domain = context.avm2.stage_domain();
+        let mut activation = Avm2Activation::from_domain(context, domain);
+        activation
+            .context
+            .library
+            .library_for_movie_mut(movie.clone())
+            .set_avm2_domain(domain);
+        let root = MovieClip::player_root_movie(&mut activation, movie);
+        drop(activation);
+
+        root.preload(context, &mut ExecutionLimit::none());
+        root.set_depth(0);
+        root.post_instantiation(context, None, Instantiator::Movie, false);
+
+        root.into()
+    }
+
+    /// Dispatch queued events to every object representing the worker or
+    /// channel they concern.
+    fn dispatch_events(context: &mut UpdateContext<'gc>) {
+        for event in std::mem::take(&mut context.workers.pending_events) {
+            let (event_type, targets): (_, Vec<_>) = match event {
+                PendingEvent::WorkerState(handle) => (
+                    "workerState",
+                    context
+                        .workers
+                        .workers
+                        .iter()
+                        .filter_map(|(owner, worker)| {
+                            Some((*owner, *worker.worker_objects.get(&handle)?))
+                        })
+                        .collect(),
+                ),
+                PendingEvent::ChannelMessage(handle) => (
+                    "channelMessage",
+                    context
+                        .workers
+                        .channels
+                        .get(&handle)
+                        .and_then(|channel| {
+                            let receiver = context.workers.workers.get(&channel.receiver)?;
+                            Some((channel.receiver, *receiver.channel_objects.get(&handle)?))
+                        })
+                        .into_iter()
+                        .collect(),
+                ),
+                PendingEvent::ChannelState(handle) => (
+                    "channelState",
+                    context
+                        .workers
+                        .workers
+                        .iter()
+                        .filter_map(|(owner, worker)| {
+                            Some((*owner, *worker.channel_objects.get(&handle)?))
+                        })
+                        .collect(),
+                ),
+            };
+
+            for (owner, target) in targets {
+                Self::run_in(context, owner, |context| {
+                    let event = Avm2EventObject::bare_default_event(context, event_type);
+                    Avm2::dispatch_event(context, event, target);
+                });
+            }
+        }
+    }
+}
@@ -1,6 +1,11 @@
 file-picker-title-open-file = Open a single file
+file-picker-title-save-bundle = Save as a bundle
+file-picker-title-import-shared-object = Replace saved data
+file-picker-title-export-shared-object = Export saved data
+file-picker-title-import-flash-player-shared-objects = Select the Flash Player #SharedObjects directory
 file-picker-filter-supported = All Supported Files
 file-picker-filter-swf = SWF (*.swf)
 file-picker-filter-spl = FutureSplash Animator (*.spl)
 file-picker-filter-ruf = Ruffle Bundle (*.ruf)
+file-picker-filter-sol = Flash Shared Object (*.sol)
 file-picker-filter-all = All Files
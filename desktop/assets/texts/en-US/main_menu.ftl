@@ -12,9 +12,12 @@ file-menu-open-quick = Open...
 file-menu-open-advanced = Open Advanced...
 file-menu-close = Close
 file-menu-reload = Reload
+file-menu-capture-bundle = Reload and Capture Bundle
+file-menu-save-bundle = Save as Bundle...
 file-menu-recents = Recents
 file-menu-recents-empty = No recent entries
 file-menu-preferences = Preferences...
+file-menu-shared-objects = Saved Data...
 file-menu-exit = Exit
 
 controls-menu = Controls
@@ -40,6 +43,7 @@ debug-menu-open-movie = View Movie
 debug-menu-open-movie-list = Show Known Movies
 debug-menu-open-domain-list = Show Domains
 debug-menu-search-display-objects = Search Display Objects...
+debug-menu-open-avm1-trace-recorder = AVM1 Trace Recorder
 
 view-menu = View
 view-menu-fullscreen = Full Screen
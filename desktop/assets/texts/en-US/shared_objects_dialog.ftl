@@ -0,0 +1,19 @@
+shared-objects-dialog = Saved Data
+shared-objects-dialog-empty = No movie has saved any data yet.
+shared-objects-dialog-not-selected = Select a shared object to view its contents.
+shared-objects-dialog-refresh = Refresh
+shared-objects-dialog-import-flash-player = Import from Flash Player...
+shared-objects-dialog-import-flash-player-tooltip =
+    Copies all shared objects saved by Flash Player into Ruffle.
+    Shared objects that Ruffle already has are overwritten.
+shared-objects-dialog-imported = Imported { $count ->
+    [one] 1 shared object
+    *[other] { $count } shared objects
+}.
+shared-objects-dialog-exported = Exported the shared object.
+shared-objects-dialog-export = Export...
+shared-objects-dialog-replace = Replace...
+shared-objects-dialog-replace-tooltip = Replaces this shared object with the contents of a .sol file.
+shared-objects-dialog-delete = Delete
+shared-objects-dialog-revert = Revert
+shared-objects-dialog-in-use = A movie from { $domain } is running and may overwrite this shared object at any time. Close it to save changes.
@@ -369,12 +369,10 @@ impl MainWindow {
             let dt = new_time.duration_since(self.time).as_nanos();
             if dt > 0 {
                 self.time = new_time;
-                if let Some(mut player) = self.player.get() {
-                    player.tick(dt as f64 / 1_000_000.0);
-                    self.next_frame_time = Some(new_time + player.time_til_next_frame());
-                } else {
-                    self.next_frame_time = None;
-                }
+                self.next_frame_time = self
+                    .player
+                    .tick(dt as f64 / 1_000_000.0)
+                    .map(|time_til_next_frame| new_time + time_til_next_frame);
                 self.check_redraw();
             }
         }
@@ -572,6 +570,19 @@ impl ApplicationHandler<RuffleEvent> for App {
                 main_window.gui.open_dialog(descriptor);
             }
 
+            (Some(main_window), RuffleEvent::SaveBundle) => {
+                if let Some(capture) = main_window.player.bundle_capture() {
+                    let picker = main_window.gui.file_picker();
+                    tokio::spawn(async move {
+                        if let Some(path) = picker.pick_bundle_save_path(capture.name()).await {
+                            if let Err(e) = capture.save(&path) {
+                                tracing::error!("Couldn't save bundle: {e:#}");
+                            }
+                        }
+                    });
+                }
+            }
+
             (Some(main_window), RuffleEvent::CloseFile) => {
                 main_window.gui.window().set_title("Ruffle"); // Reset title since file has been closed.
                 main_window.gui.close_movie(&mut main_window.player);
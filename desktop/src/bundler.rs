@@ -0,0 +1,212 @@
+//! Creation of bundles (.ruf) out of movies, either the one being played or headlessly.
+
+use crate::player::{compatibility_rules_for, open_content, LaunchOptions};
+use crate::preferences::GlobalPreferences;
+use anyhow::{anyhow, Context};
+use ruffle_core::backend::navigator::SocketMode;
+use ruffle_core::compatibility_rules::CompatibilityRules;
+use ruffle_core::PlayerBuilder;
+use ruffle_frontend_utils::backends::executor::{AsyncExecutor, PollRequester};
+use ruffle_frontend_utils::backends::navigator::{
+    ExternalNavigatorBackend, FetchedFiles, NavigatorInterface,
+};
+use ruffle_frontend_utils::bundle::write::BundleWriter;
+use ruffle_frontend_utils::content::PlayingContent;
+use ruffle_frontend_utils::player_options::PlayerOptions;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Everything needed to create a bundle out of a movie that is being played.
+#[derive(Clone)]
+pub struct BundleCapture {
+    name: String,
+    movie_url: Url,
+    player_options: PlayerOptions,
+    compatibility_rules: CompatibilityRules,
+    fetched_files: FetchedFiles,
+}
+
+impl BundleCapture {
+    pub fn new(
+        name: String,
+        movie_url: Url,
+        player_options: PlayerOptions,
+        compatibility_rules: CompatibilityRules,
+    ) -> Self {
+        Self {
+            name,
+            movie_url,
+            player_options,
+            compatibility_rules,
+            fetched_files: FetchedFiles::default(),
+        }
+    }
+
+    /// The files fetched by the movie, which the navigator should record into.
+    pub fn fetched_files(&self) -> &FetchedFiles {
+        &self.fetched_files
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Writes a bundle with everything fetched so far to `path`.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let writer = BundleWriter::from_fetched_files(
+            &self.name,
+            &self.movie_url,
+            self.player_options.clone(),
+            &self.compatibility_rules,
+            &self.fetched_files.files(),
+        );
+        let file = File::create(path).with_context(|| format!("Couldn't create {path:?}"))?;
+        writer.write_zip(BufWriter::new(file))?;
+        tracing::info!(
+            "Saved bundle with {} files to {path:?}",
+            writer.content().len()
+        );
+        if !writer.conflicts().is_empty() {
+            tracing::warn!(
+                "{} files were left out of the bundle, as they only differ by their query from another file",
+                writer.conflicts().len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The executor is polled every frame, so there's nothing to do when it asks for it.
+#[derive(Clone)]
+struct NoPollRequests;
+
+impl PollRequester for NoPollRequests {
+    fn request_poll(&self) {}
+}
+
+/// Gives the movie access to the files it asks for without asking, as nobody is there to answer.
+#[derive(Clone)]
+struct HeadlessNavigatorInterface;
+
+impl NavigatorInterface for HeadlessNavigatorInterface {
+    fn navigate_to_website(&self, url: Url) {
+        tracing::info!("Not opening {url} while creating a bundle");
+    }
+
+    async fn open_file(&self, path: &Path) -> std::io::Result<File> {
+        File::open(path)
+    }
+
+    async fn confirm_socket(&self, _host: &str, _port: u16) -> bool {
+        false
+    }
+}
+
+/// Plays the movie given on the command line without a window for `--bundle-duration`,
+/// then writes everything it loaded to the bundle at `output`.
+pub fn create_bundle_headless(
+    preferences: &GlobalPreferences,
+    output: &Path,
+) -> anyhow::Result<()> {
+    let movie_url = preferences
+        .cli
+        .movie_url
+        .clone()
+        .ok_or_else(|| anyhow!("A movie is required to create a bundle"))?;
+    let opt = LaunchOptions::from(preferences);
+
+    let content = open_content(&movie_url);
+    let player_options = match &content {
+        PlayingContent::DirectFile(_) => opt.player.clone(),
+        PlayingContent::Bundle(_, bundle) => opt.player.or(&bundle.information().player),
+    };
+    let compatibility_rules = compatibility_rules_for(preferences, &content);
+    let movie_url = content.initial_swf_url().clone();
+    let movie_compatibility_rules = compatibility_rules.for_movie(movie_url.as_str(), None);
+
+    let capture = BundleCapture::new(
+        content.name(),
+        movie_url.clone(),
+        player_options.clone(),
+        compatibility_rules.clone(),
+    );
+
+    let (executor, future_spawner) = AsyncExecutor::new(NoPollRequests);
+    let navigator = ExternalNavigatorBackend::new(
+        player_options
+            .base
+            .clone()
+            .unwrap_or_else(|| movie_url.clone()),
+        player_options.referer.clone(),
+        player_options.cookie.clone(),
+        future_spawner,
+        opt.proxy.clone(),
+        player_options.upgrade_to_https.unwrap_or_default(),
+        opt.socket_allowed.clone(),
+        SocketMode::Deny,
+        Rc::new(content),
+        HeadlessNavigatorInterface,
+    )
+    .with_fetched_files(capture.fetched_files().clone());
+
+    let player = PlayerBuilder::new()
+        .with_navigator(navigator)
+        .with_autoplay(true)
+        .with_max_execution_duration(
+            player_options
+                .max_execution_duration
+                .unwrap_or(Duration::MAX),
+        )
+        .with_spoofed_url(player_options.spoof_url.clone().map(|url| url.to_string()))
+        .with_player_version(
+            player_options
+                .player_version
+                .or(movie_compatibility_rules.player_version()),
+        )
+        .with_player_runtime(
+            player_options
+                .player_runtime
+                .or(movie_compatibility_rules.player_runtime())
+                .unwrap_or_default(),
+        )
+        .with_compatibility_rules(compatibility_rules)
+        .with_frame_rate(player_options.frame_rate)
+        .build();
+
+    player
+        .lock()
+        .expect("Player lock must be available")
+        .fetch_root_movie(
+            movie_url.to_string(),
+            player_options.parameters.clone(),
+            Box::new(|_: &ruffle_core::swf::HeaderExt| {}),
+        );
+
+    tracing::info!(
+        "Playing {movie_url} for {:?} to create a bundle",
+        preferences.cli.bundle_duration
+    );
+    let start = Instant::now();
+    let mut last_tick = start;
+    while start.elapsed() < preferences.cli.bundle_duration {
+        executor.poll_all();
+
+        let next_frame = {
+            let mut player = player.lock().expect("Player lock must be available");
+            let now = Instant::now();
+            player.tick((now - last_tick).as_secs_f64() * 1000.0);
+            last_tick = now;
+            player.time_til_next_frame()
+        };
+
+        // Network requests are handled by tokio in the background in the meantime.
+        std::thread::sleep(next_frame.min(Duration::from_millis(50)));
+    }
+    executor.poll_all();
+
+    capture.save(output)
+}
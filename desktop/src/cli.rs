@@ -243,6 +243,36 @@ pub struct Opt {
     /// (like inlining constant pool entries) can't be disabled.
     #[clap(long)]
     pub no_avm2_optimizer: bool,
+
+    /// Start a Debug Adapter Protocol server on this port of localhost,
+    /// so that editors such as VS Code can debug ActionScript 3 code.
+    /// The player window doesn't respond while execution is stopped.
+    #[clap(long)]
+    pub dap_port: Option<u16>,
+
+    /// Record all input given to the movie into this file, in the format used by the regression tests.
+    ///
+    /// While recording, the movie is advanced exactly one frame at a time so that
+    /// the recording can be replayed deterministically.
+    #[clap(long, conflicts_with = "replay_input")]
+    pub record_input: Option<std::path::PathBuf>,
+
+    /// Replay input previously recorded with `--record-input` (or written for a regression test).
+    ///
+    /// Input from the user is ignored while replaying.
+    #[clap(long)]
+    pub replay_input: Option<std::path::PathBuf>,
+
+    /// Create a bundle (.ruf) out of the movie at this path, without opening a window.
+    ///
+    /// The movie is played for `--bundle-duration` seconds, and every file it loads in that time
+    /// is stored in the bundle along with the player options given here.
+    #[clap(long, requires = "FILE")]
+    pub bundle_output: Option<std::path::PathBuf>,
+
+    /// How many seconds to play the movie for when creating a bundle with `--bundle-output`.
+    #[clap(long, default_value = "10", value_parser(parse_duration_seconds))]
+    pub bundle_duration: Duration,
 }
 
 fn parse_movie_file_or_url(path: &str) -> Result<Url, Error> {
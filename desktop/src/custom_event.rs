@@ -21,6 +21,9 @@ pub enum RuffleEvent {
     /// The user requested to close the current SWF.
     CloseFile,
 
+    /// The user requested to save the current movie and everything it loaded as a bundle.
+    SaveBundle,
+
     /// The user requested to enter full screen.
     EnterFullScreen,
 
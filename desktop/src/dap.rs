@@ -0,0 +1,643 @@
+//! A Debug Adapter Protocol server, for debugging ActionScript 3 from editors
+//! such as VS Code.
+//!
+//! Editors connect over TCP on localhost, one at a time. Every player that
+//! gets opened is attached to the server, and the editor's requests are
+//! translated into requests to that player's AVM2 debugger.
+//!
+//! While execution is stopped, the player blocks the event loop until the
+//! editor says to carry on, so this server runs on threads of its own.
+
+use ruffle_core::avm2_debugger::{
+    Breakpoint, Debugger, DebuggerEvent, DebuggerRequest, StackFrame, StepKind, StopReason,
+    Variable,
+};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait for the player to answer. A running player only
+/// handles requests once per tick.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// AVM2 runs on a single thread, which is all that editors get to see.
+const THREAD_ID: i64 = 1;
+
+#[derive(Clone)]
+pub struct DapServer {
+    shared: Arc<Mutex<Shared>>,
+    port: u16,
+}
+
+#[derive(Default)]
+struct Shared {
+    /// Where to send requests for the debugger of the open player.
+    player: Option<Sender<DebuggerRequest>>,
+
+    /// The connected editor, which events get written to.
+    client: Option<TcpStream>,
+
+    seq: i64,
+
+    /// Line breakpoints, by the source path that the editor gave.
+    lines: BTreeMap<String, Vec<u32>>,
+
+    instructions: Vec<Breakpoint>,
+}
+
+impl Shared {
+    fn breakpoints(&self) -> Vec<Breakpoint> {
+        self.lines
+            .iter()
+            .flat_map(|(file, lines)| {
+                lines.iter().map(|line| Breakpoint::Line {
+                    file: file.clone(),
+                    line: *line,
+                })
+            })
+            .chain(self.instructions.iter().cloned())
+            .collect()
+    }
+
+    fn update_breakpoints(&mut self) {
+        self.send_to_player(DebuggerRequest::SetBreakpoints(self.breakpoints()));
+    }
+
+    fn send_to_player(&mut self, request: DebuggerRequest) {
+        if let Some(player) = &self.player {
+            if player.send(request).is_err() {
+                self.player = None;
+            }
+        }
+    }
+
+    /// Write a message to the editor, numbering it.
+    fn send(&mut self, mut message: Value) {
+        let Some(client) = &mut self.client else {
+            return;
+        };
+
+        self.seq += 1;
+        message["seq"] = self.seq.into();
+        if let Err(e) = write_message(client, &message) {
+            tracing::warn!("Couldn't write to debug adapter client: {e}");
+        }
+    }
+}
+
+impl DapServer {
+    /// Start listening on `port` of localhost. A port of 0 picks any free port.
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let port = listener.local_addr()?.port();
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let server = Self { shared, port };
+        let accepting = server.clone();
+        thread::Builder::new()
+            .name("Debug adapter".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    match stream {
+                        Ok(stream) => accepting.serve(stream),
+                        Err(e) => tracing::warn!("Couldn't accept debug adapter client: {e}"),
+                    }
+                }
+            })?;
+
+        tracing::info!("Debug adapter listening on port {port}");
+        Ok(server)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Create a debugger for a newly opened player, replacing the last one.
+    pub fn attach(&self) -> Debugger {
+        let (requests, events) = self.connect();
+        Debugger::new(requests, events)
+    }
+
+    /// Create the player's ends of a debugger connection.
+    fn connect(&self) -> (Receiver<DebuggerRequest>, Sender<DebuggerEvent>) {
+        let (request_sender, requests) = mpsc::channel();
+        let (event_sender, events) = mpsc::channel();
+
+        let mut shared = self.lock();
+        shared.player = Some(request_sender);
+        shared.update_breakpoints();
+        drop(shared);
+
+        // This ends when the player goes away along with its debugger.
+        let shared = self.shared.clone();
+        let forwarding = thread::Builder::new()
+            .name("Debug adapter events".to_string())
+            .spawn(move || {
+                for event in events {
+                    let DebuggerEvent::Stopped(reason) = event;
+                    let reason = match reason {
+                        StopReason::Breakpoint => "breakpoint",
+                        StopReason::Step => "step",
+                        StopReason::Pause => "pause",
+                    };
+                    lock(&shared).send(json!({
+                        "type": "event",
+                        "event": "stopped",
+                        "body": {
+                            "reason": reason,
+                            "threadId": THREAD_ID,
+                            "allThreadsStopped": true,
+                        },
+                    }));
+                }
+            });
+        if let Err(e) = forwarding {
+            tracing::error!("Couldn't start forwarding debugger events: {e}");
+        }
+
+        (requests, event_sender)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        lock(&self.shared)
+    }
+
+    /// Handle one editor until it disconnects.
+    fn serve(&self, stream: TcpStream) {
+        match stream.try_clone() {
+            Ok(writer) => self.lock().client = Some(writer),
+            Err(e) => {
+                tracing::warn!("Couldn't accept debug adapter client: {e}");
+                return;
+            }
+        }
+
+        let mut session = Session::default();
+        let mut reader = BufReader::new(stream);
+        loop {
+            let message = match read_message(&mut reader) {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Couldn't read from debug adapter client: {e}");
+                    break;
+                }
+            };
+            if message["type"] != "request" {
+                continue;
+            }
+
+            let command = message["command"].as_str().unwrap_or_default();
+            let mut response = json!({
+                "type": "response",
+                "request_seq": message["seq"],
+                "command": command,
+            });
+            match self.handle(&mut session, command, &message["arguments"]) {
+                Ok(body) => {
+                    response["success"] = true.into();
+                    response["body"] = body;
+                }
+                Err(error) => {
+                    response["success"] = false.into();
+                    response["message"] = error.into();
+                }
+            }
+            self.lock().send(response);
+
+            match command {
+                "initialize" => self.lock().send(json!({
+                    "type": "event",
+                    "event": "initialized",
+                })),
+                "disconnect" => break,
+                _ => {}
+            }
+        }
+
+        // Don't leave the player stuck on a breakpoint that nobody can see.
+        let mut shared = self.lock();
+        shared.client = None;
+        shared.lines.clear();
+        shared.instructions.clear();
+        shared.update_breakpoints();
+        shared.send_to_player(DebuggerRequest::Continue);
+    }
+
+    fn handle(
+        &self,
+        session: &mut Session,
+        command: &str,
+        arguments: &Value,
+    ) -> Result<Value, String> {
+        match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsInstructionBreakpoints": true,
+            })),
+            "launch" | "attach" | "configurationDone" | "setExceptionBreakpoints" => Ok(json!({})),
+            "setBreakpoints" => {
+                let source = &arguments["source"];
+                let path = source["path"]
+                    .as_str()
+                    .or_else(|| source["name"].as_str())
+                    .ok_or("Missing source path")?;
+                let lines: Vec<u32> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .map(|line| line as u32)
+                    .collect();
+
+                let breakpoints: Vec<Value> = lines
+                    .iter()
+                    .map(|line| json!({ "verified": true, "line": line }))
+                    .collect();
+
+                let mut shared = self.lock();
+                shared.lines.insert(path.to_string(), lines);
+                shared.update_breakpoints();
+                Ok(json!({ "breakpoints": breakpoints }))
+            }
+            "setInstructionBreakpoints" => {
+                let requested = arguments["breakpoints"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default();
+                let parsed: Vec<Option<Breakpoint>> = requested
+                    .iter()
+                    .map(|breakpoint| {
+                        let (method, offset) = parse_instruction_reference(
+                            breakpoint["instructionReference"].as_str()?,
+                        )?;
+                        let extra = breakpoint["offset"].as_i64().unwrap_or_default();
+                        Some(Breakpoint::Instruction {
+                            method,
+                            offset: usize::try_from(offset as i64 + extra).ok()?,
+                        })
+                    })
+                    .collect();
+
+                let breakpoints: Vec<Value> = parsed
+                    .iter()
+                    .map(|breakpoint| json!({ "verified": breakpoint.is_some() }))
+                    .collect();
+
+                let mut shared = self.lock();
+                shared.instructions = parsed.into_iter().flatten().collect();
+                shared.update_breakpoints();
+                Ok(json!({ "breakpoints": breakpoints }))
+            }
+            "threads" => Ok(json!({
+                "threads": [{ "id": THREAD_ID, "name": "ActionScript" }],
+            })),
+            "stackTrace" => {
+                let frames = self.ask_player(DebuggerRequest::StackTrace);
+                session.locals = frames.iter().map(|frame| frame.locals).collect();
+
+                let frames: Vec<Value> = frames.iter().enumerate().map(stack_frame).collect();
+                Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+            }
+            "scopes" => {
+                let frame = arguments["frameId"].as_u64().unwrap_or_default() as usize;
+                let scopes = match session.locals.get(frame) {
+                    Some(&locals) if locals != 0 => vec![json!({
+                        "name": "Locals",
+                        "presentationHint": "locals",
+                        "variablesReference": locals,
+                        "expensive": false,
+                    })],
+                    _ => vec![],
+                };
+                Ok(json!({ "scopes": scopes }))
+            }
+            "variables" => {
+                let reference = arguments["variablesReference"].as_u64().unwrap_or_default() as u32;
+                let variables: Vec<Value> = self
+                    .ask_player(|reply| DebuggerRequest::Variables(reference, reply))
+                    .iter()
+                    .map(variable)
+                    .collect();
+                Ok(json!({ "variables": variables }))
+            }
+            "continue" => {
+                self.lock().send_to_player(DebuggerRequest::Continue);
+                Ok(json!({ "allThreadsContinued": true }))
+            }
+            "next" | "stepIn" | "stepOut" => {
+                let kind = match command {
+                    "next" => StepKind::Over,
+                    "stepIn" => StepKind::Into,
+                    _ => StepKind::Out,
+                };
+                self.lock().send_to_player(DebuggerRequest::Step(kind));
+                Ok(json!({}))
+            }
+            "pause" => {
+                self.lock().send_to_player(DebuggerRequest::Pause);
+                Ok(json!({}))
+            }
+            "disconnect" => Ok(json!({})),
+            _ => Err(format!("Unsupported request '{command}'")),
+        }
+    }
+
+    /// Send a request to the player and wait for its reply, which is empty
+    /// if there's no player to answer.
+    fn ask_player<T: Default>(&self, request: impl FnOnce(Sender<T>) -> DebuggerRequest) -> T {
+        let (reply, replies) = mpsc::channel();
+        self.lock().send_to_player(request(reply));
+        replies.recv_timeout(REPLY_TIMEOUT).unwrap_or_default()
+    }
+}
+
+/// What an editor has been told about, which later requests refer to.
+#[derive(Default)]
+struct Session {
+    /// The local variable references of each frame from the last stack trace.
+    locals: Vec<u32>,
+}
+
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn stack_frame((id, frame): (usize, &StackFrame)) -> Value {
+    let mut value = json!({
+        "id": id,
+        "name": frame.name,
+        "line": 0,
+        "column": 0,
+    });
+
+    if let Some(location) = &frame.location {
+        value["instructionPointerReference"] = format!("{}@{}", frame.name, location.offset).into();
+        if let Some(line) = location.line {
+            value["line"] = line.into();
+            value["column"] = 1.into();
+        }
+        if let Some(file) = &location.file {
+            // Compilers separate the source root, package and file name with semicolons.
+            let path: PathBuf = file.split(';').collect();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.clone());
+            value["source"] = json!({
+                "name": name,
+                "path": path.to_string_lossy(),
+            });
+        }
+    } else {
+        value["presentationHint"] = "subtle".into();
+    }
+
+    value
+}
+
+fn variable(variable: &Variable) -> Value {
+    json!({
+        "name": variable.name,
+        "value": variable.value,
+        "type": variable.type_name,
+        "variablesReference": variable.children,
+    })
+}
+
+/// Parse an instruction reference of the form `method@offset`, as given in
+/// stack frames.
+fn parse_instruction_reference(reference: &str) -> Option<(String, usize)> {
+    let (method, offset) = reference.rsplit_once('@')?;
+    Some((method.to_string(), offset.parse().ok()?))
+}
+
+/// Read one message, or `None` once the client disconnects.
+fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let content_length = content_length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length"))?;
+    let mut content = vec![0; content_length];
+    Read::read_exact(reader, &mut content)?;
+
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{content}", content.len())?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::avm2_debugger::Location;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+        seq: i64,
+    }
+
+    impl Client {
+        fn connect(server: &DapServer) -> Self {
+            let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, server.port()))
+                .expect("Must connect to server");
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .expect("Must set timeout");
+            Self {
+                writer: stream.try_clone().expect("Must clone stream"),
+                reader: BufReader::new(stream),
+                seq: 0,
+            }
+        }
+
+        fn send(&mut self, command: &str, arguments: Value) {
+            self.seq += 1;
+            let request = json!({
+                "seq": self.seq,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            });
+            write_message(&mut self.writer, &request).expect("Must write request");
+        }
+
+        fn receive(&mut self) -> Value {
+            read_message(&mut self.reader)
+                .expect("Must read message")
+                .expect("Server must not disconnect")
+        }
+
+        fn request(&mut self, command: &str, arguments: Value) -> Value {
+            self.send(command, arguments);
+            let response = self.receive();
+            assert_eq!(response["type"], "response");
+            assert_eq!(response["command"], command);
+            assert_eq!(response["success"], true, "{response}");
+            response["body"].clone()
+        }
+    }
+
+    fn next_request(requests: &Receiver<DebuggerRequest>) -> DebuggerRequest {
+        requests
+            .recv_timeout(Duration::from_secs(10))
+            .expect("Player must receive a request")
+    }
+
+    #[test]
+    fn scripted_session() {
+        let server = DapServer::start(0).expect("Must start server");
+        let (requests, events) = server.connect();
+        assert!(matches!(
+            next_request(&requests),
+            DebuggerRequest::SetBreakpoints(breakpoints) if breakpoints.is_empty()
+        ));
+
+        let mut client = Client::connect(&server);
+        let capabilities = client.request("initialize", json!({ "adapterID": "ruffle" }));
+        assert_eq!(capabilities["supportsInstructionBreakpoints"], true);
+        assert_eq!(client.receive()["event"], "initialized");
+
+        let body = client.request(
+            "setBreakpoints",
+            json!({
+                "source": { "path": "src/Main.as" },
+                "breakpoints": [{ "line": 12 }],
+            }),
+        );
+        assert_eq!(body["breakpoints"][0]["verified"], true);
+        match next_request(&requests) {
+            DebuggerRequest::SetBreakpoints(breakpoints) => assert_eq!(
+                breakpoints,
+                vec![Breakpoint::Line {
+                    file: "src/Main.as".to_string(),
+                    line: 12,
+                }]
+            ),
+            request => panic!("Unexpected request {request:?}"),
+        }
+
+        let body = client.request(
+            "setInstructionBreakpoints",
+            json!({ "breakpoints": [{ "instructionReference": "Main/init()@4", "offset": 2 }] }),
+        );
+        assert_eq!(body["breakpoints"][0]["verified"], true);
+        match next_request(&requests) {
+            DebuggerRequest::SetBreakpoints(breakpoints) => assert_eq!(
+                breakpoints[1],
+                Breakpoint::Instruction {
+                    method: "Main/init()".to_string(),
+                    offset: 6,
+                }
+            ),
+            request => panic!("Unexpected request {request:?}"),
+        }
+
+        client.request("configurationDone", json!({}));
+
+        events
+            .send(DebuggerEvent::Stopped(StopReason::Breakpoint))
+            .expect("Server must be listening");
+        let stopped = client.receive();
+        assert_eq!(stopped["event"], "stopped");
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+
+        client.send("stackTrace", json!({ "threadId": THREAD_ID }));
+        match next_request(&requests) {
+            DebuggerRequest::StackTrace(reply) => reply
+                .send(vec![
+                    StackFrame {
+                        name: "Main/init()".to_string(),
+                        location: Some(Location {
+                            offset: 6,
+                            file: Some("src;Main.as".to_string()),
+                            line: Some(12),
+                        }),
+                        locals: 1,
+                    },
+                    StackFrame {
+                        name: "flash.events::EventDispatcher/dispatchEvent()".to_string(),
+                        location: None,
+                        locals: 0,
+                    },
+                ])
+                .expect("Server must be waiting"),
+            request => panic!("Unexpected request {request:?}"),
+        }
+        let response = client.receive();
+        let frames = &response["body"]["stackFrames"];
+        assert_eq!(frames[0]["name"], "Main/init()");
+        assert_eq!(frames[0]["line"], 12);
+        assert_eq!(frames[0]["source"]["name"], "Main.as");
+        assert_eq!(frames[0]["instructionPointerReference"], "Main/init()@6");
+        assert_eq!(frames[1]["presentationHint"], "subtle");
+
+        let body = client.request("scopes", json!({ "frameId": 0 }));
+        assert_eq!(body["scopes"][0]["variablesReference"], 1);
+        let body = client.request("scopes", json!({ "frameId": 1 }));
+        assert_eq!(body["scopes"], json!([]));
+
+        client.send("variables", json!({ "variablesReference": 1 }));
+        match next_request(&requests) {
+            DebuggerRequest::Variables(1, reply) => reply
+                .send(vec![Variable {
+                    name: "this".to_string(),
+                    value: "[object Main]".to_string(),
+                    type_name: "Main".to_string(),
+                    children: 2,
+                }])
+                .expect("Server must be waiting"),
+            request => panic!("Unexpected request {request:?}"),
+        }
+        let response = client.receive();
+        let variables = &response["body"]["variables"];
+        assert_eq!(variables[0]["name"], "this");
+        assert_eq!(variables[0]["type"], "Main");
+        assert_eq!(variables[0]["variablesReference"], 2);
+
+        client.request("next", json!({ "threadId": THREAD_ID }));
+        assert!(matches!(
+            next_request(&requests),
+            DebuggerRequest::Step(StepKind::Over)
+        ));
+        client.request("continue", json!({ "threadId": THREAD_ID }));
+        assert!(matches!(next_request(&requests), DebuggerRequest::Continue));
+
+        client.send("evaluate", json!({ "expression": "this" }));
+        assert_eq!(client.receive()["success"], false);
+
+        client.request("disconnect", json!({}));
+        assert!(matches!(
+            next_request(&requests),
+            DebuggerRequest::SetBreakpoints(breakpoints) if breakpoints.is_empty()
+        ));
+        assert!(matches!(next_request(&requests), DebuggerRequest::Continue));
+    }
+}
@@ -6,6 +6,7 @@ pub mod network_access_dialog;
 mod open_dialog;
 mod open_url_dialog;
 mod preferences_dialog;
+mod shared_objects_dialog;
 mod volume_controls;
 
 use crate::custom_event::RuffleEvent;
@@ -19,6 +20,8 @@ use open_dialog::OpenDialog;
 use open_url_dialog::OpenUrlDialog;
 use preferences_dialog::PreferencesDialog;
 use ruffle_core::Player;
+use ruffle_frontend_utils::shared_objects::shared_object_domain;
+use shared_objects_dialog::SharedObjectsDialog;
 use std::{collections::VecDeque, sync::Weak};
 use unic_langid::LanguageIdentifier;
 use url::Url;
@@ -32,6 +35,7 @@ pub struct Dialogs {
 
     picker: FilePicker,
     preferences_dialog: Option<PreferencesDialog>,
+    shared_objects_dialog: Option<SharedObjectsDialog>,
     bookmarks_dialog: Option<BookmarksDialog>,
     bookmark_add_dialog: Option<BookmarkAddDialog>,
     open_url_dialog: Option<OpenUrlDialog>,
@@ -74,6 +78,7 @@ impl Dialogs {
         let picker = FilePicker::new(window, preferences.clone());
         Self {
             preferences_dialog: None,
+            shared_objects_dialog: None,
             bookmarks_dialog: None,
             bookmark_add_dialog: None,
             open_url_dialog: None,
@@ -134,6 +139,13 @@ impl Dialogs {
         self.preferences_dialog = Some(PreferencesDialog::new(self.preferences.clone()));
     }
 
+    pub fn open_shared_objects(&mut self) {
+        self.shared_objects_dialog = Some(SharedObjectsDialog::new(
+            self.preferences.cli.save_directory.clone(),
+            self.picker.clone(),
+        ));
+    }
+
     pub fn open_bookmarks(&mut self) {
         self.bookmarks_dialog = Some(BookmarksDialog::new(
             self.preferences.clone(),
@@ -183,6 +195,14 @@ impl Dialogs {
     ) {
         self.show_open_dialog(locale, egui_ctx);
         self.show_preferences_dialog(locale, egui_ctx);
+        let movie_url = player
+            .as_deref()
+            .and_then(|player| Url::parse(player.swf_url()).ok());
+        self.show_shared_objects_dialog(
+            locale,
+            egui_ctx,
+            movie_url.as_ref().and_then(shared_object_domain),
+        );
         self.show_bookmarks_dialog(locale, egui_ctx);
         self.show_bookmark_add_dialog(locale, egui_ctx);
         self.show_volume_controls(locale, egui_ctx, player);
@@ -211,6 +231,22 @@ impl Dialogs {
         }
     }
 
+    fn show_shared_objects_dialog(
+        &mut self,
+        locale: &LanguageIdentifier,
+        egui_ctx: &egui::Context,
+        running_domain: Option<&str>,
+    ) {
+        let keep_open = if let Some(dialog) = &mut self.shared_objects_dialog {
+            dialog.show(locale, egui_ctx, running_domain)
+        } else {
+            true
+        };
+        if !keep_open {
+            self.shared_objects_dialog = None;
+        }
+    }
+
     fn show_bookmarks_dialog(&mut self, locale: &LanguageIdentifier, egui_ctx: &egui::Context) {
         let keep_open = if let Some(dialog) = &mut self.bookmarks_dialog {
             dialog.show(locale, egui_ctx)
@@ -0,0 +1,497 @@
+use crate::gui::{text, text_with_args, FilePicker};
+use egui::{Align2, Button, CollapsingHeader, DragValue, Grid, Layout, ScrollArea, Ui, Window};
+use flash_lso::types::{Element, Lso, Value};
+use fluent_templates::fluent_bundle::FluentValue;
+use ruffle_frontend_utils::shared_objects::{SharedObjectManager, StoredSharedObject};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use unic_langid::LanguageIdentifier;
+
+/// The result of the last operation done in the background.
+enum Status {
+    Imported(usize),
+    Exported,
+    Error(String),
+}
+
+struct SelectedSharedObject {
+    name: String,
+    lso: Lso,
+    modified: bool,
+}
+
+pub struct SharedObjectsDialog {
+    save_directory: PathBuf,
+    picker: FilePicker,
+    shared_objects: BTreeMap<String, Vec<StoredSharedObject>>,
+    selected: Option<SelectedSharedObject>,
+    status: Arc<Mutex<Option<Status>>>,
+    needs_refresh: Arc<AtomicBool>,
+}
+
+impl SharedObjectsDialog {
+    pub fn new(save_directory: PathBuf, picker: FilePicker) -> Self {
+        let mut dialog = Self {
+            save_directory,
+            picker,
+            shared_objects: BTreeMap::new(),
+            selected: None,
+            status: Default::default(),
+            needs_refresh: Default::default(),
+        };
+        dialog.refresh();
+        dialog
+    }
+
+    fn manager(&self) -> SharedObjectManager {
+        SharedObjectManager::new(self.save_directory.clone())
+    }
+
+    fn refresh(&mut self) {
+        let manager = self.manager();
+        self.shared_objects = manager.list_by_domain();
+        if let Some(selected) = &self.selected {
+            let name = selected.name.clone();
+            self.select(&manager, name);
+        }
+    }
+
+    fn select(&mut self, manager: &SharedObjectManager, name: String) {
+        self.selected = match manager.read(&name) {
+            Ok(lso) => Some(SelectedSharedObject {
+                name,
+                lso,
+                modified: false,
+            }),
+            Err(e) => {
+                self.set_status(Status::Error(e.to_string()));
+                None
+            }
+        };
+    }
+
+    fn set_status(&self, status: Status) {
+        *self.status.lock().expect("Non-poisoned status") = Some(status);
+    }
+
+    /// Shows the dialog, returning whether it should stay open.
+    ///
+    /// `running_domain` is the SharedObject domain of the movie being played, if any.
+    /// That movie may flush its SharedObjects at any time, so those can't be changed here
+    /// until it's closed, as the changes would be silently overwritten.
+    pub fn show(
+        &mut self,
+        locale: &LanguageIdentifier,
+        egui_ctx: &egui::Context,
+        running_domain: Option<&str>,
+    ) -> bool {
+        let mut keep_open = true;
+
+        if self.needs_refresh.swap(false, Ordering::Relaxed) {
+            self.refresh();
+        }
+
+        Window::new(text(locale, "shared-objects-dialog"))
+            .open(&mut keep_open)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(true)
+            .default_width(600.0)
+            .default_height(500.0)
+            .show(egui_ctx, |ui| {
+                self.show_toolbar(locale, ui);
+                ui.separator();
+
+                egui::TopBottomPanel::top("shared-objects-dialog-top-panel")
+                    .resizable(true)
+                    .min_height(100.0)
+                    .show_inside(ui, |ui| {
+                        if self.shared_objects.is_empty() {
+                            ui.centered_and_justified(|ui| {
+                                ui.label(text(locale, "shared-objects-dialog-empty"));
+                            });
+                        } else {
+                            self.show_list(ui);
+                        }
+                    });
+
+                self.show_selected(locale, ui, running_domain);
+            });
+
+        keep_open
+    }
+
+    fn show_toolbar(&mut self, locale: &LanguageIdentifier, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            if ui
+                .button(text(locale, "shared-objects-dialog-import-flash-player"))
+                .on_hover_text(text(
+                    locale,
+                    "shared-objects-dialog-import-flash-player-tooltip",
+                ))
+                .clicked()
+            {
+                let picker = self.picker.clone();
+                let manager = self.manager();
+                let status = self.status.clone();
+                let needs_refresh = self.needs_refresh.clone();
+                tokio::spawn(async move {
+                    if let Some(directory) = picker.pick_flash_player_directory().await {
+                        let result = match manager.import_flash_player_directory(&directory) {
+                            Ok(imported) => Status::Imported(imported.len()),
+                            Err(e) => Status::Error(e.to_string()),
+                        };
+                        *status.lock().expect("Non-poisoned status") = Some(result);
+                        needs_refresh.store(true, Ordering::Relaxed);
+                    }
+                });
+            }
+
+            if ui
+                .button(text(locale, "shared-objects-dialog-refresh"))
+                .clicked()
+            {
+                self.refresh();
+            }
+        });
+
+        if let Some(status) = &*self.status.lock().expect("Non-poisoned status") {
+            match status {
+                Status::Imported(count) => ui.label(text_with_args(
+                    locale,
+                    "shared-objects-dialog-imported",
+                    &HashMap::from([("count".into(), FluentValue::from(*count))]),
+                )),
+                Status::Exported => ui.label(text(locale, "shared-objects-dialog-exported")),
+                Status::Error(error) => ui.colored_label(ui.visuals().error_fg_color, error),
+            };
+        }
+    }
+
+    fn show_list(&mut self, ui: &mut Ui) {
+        let mut clicked = None;
+        ScrollArea::vertical().show(ui, |ui| {
+            for (domain, shared_objects) in &self.shared_objects {
+                CollapsingHeader::new(domain)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for shared_object in shared_objects {
+                            let is_selected = self
+                                .selected
+                                .as_ref()
+                                .is_some_and(|selected| selected.name == shared_object.name);
+                            let label = format!(
+                                "{} ({})",
+                                shared_object.path(),
+                                format_size(shared_object.size)
+                            );
+                            if ui.selectable_label(is_selected, label).clicked() {
+                                clicked = Some(shared_object.name.clone());
+                            }
+                        }
+                    });
+            }
+        });
+
+        if let Some(name) = clicked {
+            let manager = self.manager();
+            self.select(&manager, name);
+        }
+    }
+
+    fn show_selected(
+        &mut self,
+        locale: &LanguageIdentifier,
+        ui: &mut Ui,
+        running_domain: Option<&str>,
+    ) {
+        let Some(selected) = &mut self.selected else {
+            ui.vertical_centered_justified(|ui| {
+                ui.label(text(locale, "shared-objects-dialog-not-selected"));
+            });
+            return;
+        };
+
+        let domain = selected
+            .name
+            .split_once('/')
+            .map_or(selected.name.as_str(), |(domain, _)| domain);
+        let in_use = running_domain == Some(domain);
+
+        let manager = SharedObjectManager::new(self.save_directory.clone());
+        let mut deleted = false;
+        let mut reverted = false;
+
+        ui.horizontal(|ui| {
+            ui.strong(&selected.name);
+            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(
+                        !in_use,
+                        Button::new(text(locale, "shared-objects-dialog-delete")),
+                    )
+                    .clicked()
+                {
+                    match manager.delete(&selected.name) {
+                        Ok(()) => deleted = true,
+                        Err(e) => {
+                            *self.status.lock().expect("Non-poisoned status") =
+                                Some(Status::Error(e.to_string()))
+                        }
+                    }
+                }
+
+                if ui
+                    .button(text(locale, "shared-objects-dialog-export"))
+                    .clicked()
+                {
+                    let picker = self.picker.clone();
+                    let name = selected.name.clone();
+                    let status = self.status.clone();
+                    let manager = SharedObjectManager::new(self.save_directory.clone());
+                    tokio::spawn(async move {
+                        if let Some(path) = picker.pick_sol_save_path(&name).await {
+                            let result = match manager.export(&name, &path) {
+                                Ok(()) => Status::Exported,
+                                Err(e) => Status::Error(e.to_string()),
+                            };
+                            *status.lock().expect("Non-poisoned status") = Some(result);
+                        }
+                    });
+                }
+
+                if ui
+                    .add_enabled(
+                        !in_use,
+                        Button::new(text(locale, "shared-objects-dialog-replace")),
+                    )
+                    .on_hover_text(text(locale, "shared-objects-dialog-replace-tooltip"))
+                    .clicked()
+                {
+                    let picker = self.picker.clone();
+                    let name = selected.name.clone();
+                    let status = self.status.clone();
+                    let needs_refresh = self.needs_refresh.clone();
+                    let manager = SharedObjectManager::new(self.save_directory.clone());
+                    tokio::spawn(async move {
+                        if let Some(path) = picker.pick_sol_file().await {
+                            if let Err(e) = manager.import(&path, &name) {
+                                *status.lock().expect("Non-poisoned status") =
+                                    Some(Status::Error(e.to_string()));
+                            }
+                            needs_refresh.store(true, Ordering::Relaxed);
+                        }
+                    });
+                }
+            });
+        });
+        ui.separator();
+
+        ScrollArea::vertical()
+            .max_height(ui.available_height() - 30.0)
+            .show(ui, |ui| {
+                if elements_ui(ui, "shared-object", &mut selected.lso.body, true) {
+                    selected.modified = true;
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if in_use {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    text_with_args(
+                        locale,
+                        "shared-objects-dialog-in-use",
+                        &HashMap::from([("domain".into(), FluentValue::from(domain))]),
+                    ),
+                );
+            }
+            ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(
+                        selected.modified && !in_use,
+                        Button::new(text(locale, "save")),
+                    )
+                    .clicked()
+                {
+                    match manager.write(&selected.name, &mut selected.lso) {
+                        Ok(()) => selected.modified = false,
+                        Err(e) => {
+                            *self.status.lock().expect("Non-poisoned status") =
+                                Some(Status::Error(e.to_string()))
+                        }
+                    }
+                    self.needs_refresh.store(true, Ordering::Relaxed);
+                }
+                if ui
+                    .add_enabled(
+                        selected.modified,
+                        Button::new(text(locale, "shared-objects-dialog-revert")),
+                    )
+                    .clicked()
+                {
+                    reverted = true;
+                }
+            });
+        });
+
+        if deleted {
+            self.selected = None;
+            self.refresh();
+        } else if reverted {
+            let name = selected.name.clone();
+            self.select(&manager, name);
+        }
+    }
+}
+
+/// Shows an editor for the properties of an object, returning whether any of them changed.
+///
+/// Only primitive values can be edited. Properties can only be removed from the top level,
+/// as nested objects may be referenced from several places.
+fn elements_ui(ui: &mut Ui, id: &str, elements: &mut Vec<Element>, removable: bool) -> bool {
+    let mut changed = false;
+    let mut removed = None;
+    Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+        for (index, element) in elements.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                if removable && ui.small_button("🗑").clicked() {
+                    removed = Some(index);
+                }
+                ui.label(&element.name);
+            });
+            changed |= value_ui(ui, &format!("{id}/{}", element.name), &mut element.value);
+            ui.end_row();
+        }
+    });
+    if let Some(index) = removed {
+        elements.remove(index);
+        changed = true;
+    }
+    changed
+}
+
+/// Shows an editor for a single value, returning whether it changed.
+fn value_ui(ui: &mut Ui, id: &str, value: &mut Rc<Value>) -> bool {
+    match &**value {
+        Value::Number(number) => {
+            let mut number = *number;
+            let changed = ui.add(DragValue::new(&mut number)).changed();
+            if changed {
+                *value = Rc::new(Value::Number(number));
+            }
+            changed
+        }
+        Value::Integer(integer) => {
+            let mut integer = *integer;
+            let changed = ui.add(DragValue::new(&mut integer)).changed();
+            if changed {
+                *value = Rc::new(Value::Integer(integer));
+            }
+            changed
+        }
+        Value::Bool(boolean) => {
+            let mut boolean = *boolean;
+            let changed = ui.checkbox(&mut boolean, "").changed();
+            if changed {
+                *value = Rc::new(Value::Bool(boolean));
+            }
+            changed
+        }
+        Value::String(string) => {
+            let mut string = string.clone();
+            let changed = ui.text_edit_singleline(&mut string).changed();
+            if changed {
+                *value = Rc::new(Value::String(string));
+            }
+            changed
+        }
+        Value::Null => {
+            ui.weak("null");
+            false
+        }
+        Value::Undefined => {
+            ui.weak("undefined");
+            false
+        }
+        Value::Object(..) | Value::ECMAArray(..) | Value::StrictArray(..) | Value::AMF3(..) => {
+            let mut changed = false;
+            CollapsingHeader::new(container_label(value))
+                .id_salt(id)
+                .show(ui, |ui| match Rc::make_mut(value) {
+                    Value::Object(_, elements, _) => {
+                        changed = elements_ui(ui, id, elements, false);
+                    }
+                    Value::ECMAArray(_, dense, elements, _) => {
+                        changed = values_ui(ui, id, dense);
+                        changed |= elements_ui(ui, &format!("{id}/sparse"), elements, false);
+                    }
+                    Value::StrictArray(_, values) => {
+                        changed = values_ui(ui, id, values);
+                    }
+                    Value::AMF3(inner) => {
+                        changed = value_ui(ui, &format!("{id}/amf3"), inner);
+                    }
+                    _ => {}
+                });
+            changed
+        }
+        other => {
+            ui.weak(value_type_name(other));
+            false
+        }
+    }
+}
+
+fn values_ui(ui: &mut Ui, id: &str, values: &mut [Rc<Value>]) -> bool {
+    let mut changed = false;
+    Grid::new(id).num_columns(2).striped(true).show(ui, |ui| {
+        for (index, value) in values.iter_mut().enumerate() {
+            ui.label(index.to_string());
+            changed |= value_ui(ui, &format!("{id}/{index}"), value);
+            ui.end_row();
+        }
+    });
+    changed
+}
+
+fn container_label(value: &Value) -> String {
+    match value {
+        Value::Object(_, elements, Some(class)) if !class.name.is_empty() => {
+            format!("{} ({})", class.name, elements.len())
+        }
+        Value::Object(_, elements, _) => format!("Object ({})", elements.len()),
+        Value::ECMAArray(_, dense, elements, _) => {
+            format!("Array ({})", dense.len() + elements.len())
+        }
+        Value::StrictArray(_, values) => format!("Array ({})", values.len()),
+        Value::AMF3(inner) => container_label(inner),
+        other => value_type_name(other).to_string(),
+    }
+}
+
+fn value_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Date(..) => "Date",
+        Value::XML(..) => "XML",
+        Value::ByteArray(..) => "ByteArray",
+        Value::VectorInt(..)
+        | Value::VectorUInt(..)
+        | Value::VectorDouble(..)
+        | Value::VectorObject(..) => "Vector",
+        Value::Dictionary(..) => "Dictionary",
+        _ => "?",
+    }
+}
+
+fn format_size(size: u64) -> String {
+    if size < 1024 {
+        format!("{size} B")
+    } else {
+        format!("{:.1} KiB", size as f64 / 1024.0)
+    }
+}
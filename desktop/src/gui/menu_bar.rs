@@ -159,6 +159,12 @@ impl MenuBar {
                                 player.debug_ui().queue_message(DebugMessage::SearchForDisplayObject);
                             }
                         }
+                        if Button::new(text(locale, "debug-menu-open-avm1-trace-recorder")).ui(ui).clicked() {
+                            ui.close_menu();
+                            if let Some(player) = &mut player {
+                                player.debug_ui().queue_message(DebugMessage::ShowAvm1TraceRecorder);
+                            }
+                        }
                     });
                 });
                 menu::menu_button(ui, text(locale, "help-menu"), |ui| {
@@ -223,6 +229,30 @@ impl MenuBar {
             {
                 self.close_movie(ui);
             }
+
+            let capturing_bundle = self
+                .currently_opened
+                .as_ref()
+                .is_some_and(|(_, opts)| opts.capture_bundle);
+            if ui
+                .add_enabled(
+                    player_exists && !capturing_bundle,
+                    Button::new(text(locale, "file-menu-capture-bundle")),
+                )
+                .clicked()
+            {
+                self.capture_bundle(ui);
+            }
+
+            if ui
+                .add_enabled(
+                    player_exists && capturing_bundle,
+                    Button::new(text(locale, "file-menu-save-bundle")),
+                )
+                .clicked()
+            {
+                self.save_bundle(ui);
+            }
             ui.separator();
 
             let recent_menu_response = ui
@@ -275,6 +305,13 @@ impl MenuBar {
                 ui.close_menu();
                 dialogs.open_preferences();
             }
+            if Button::new(text(locale, "file-menu-shared-objects"))
+                .ui(ui)
+                .clicked()
+            {
+                ui.close_menu();
+                dialogs.open_shared_objects();
+            }
             ui.separator();
 
             if Button::new(text(locale, "file-menu-exit"))
@@ -473,6 +510,19 @@ impl MenuBar {
         ui.close_menu();
     }
 
+    fn save_bundle(&mut self, ui: &mut egui::Ui) {
+        let _ = self.event_loop.send_event(RuffleEvent::SaveBundle);
+        ui.close_menu();
+    }
+
+    /// Reloads the movie, keeping everything it loads so that it can be saved as a bundle.
+    fn capture_bundle(&mut self, ui: &mut egui::Ui) {
+        if let Some((_, opts)) = &mut self.currently_opened {
+            opts.capture_bundle = true;
+        }
+        self.reload_movie(ui);
+    }
+
     fn reload_movie(&mut self, ui: &mut egui::Ui) {
         let _ = self.event_loop.send_event(RuffleEvent::CloseFile);
         if let Some((movie_url, opts)) = self.currently_opened.take() {
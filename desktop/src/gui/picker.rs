@@ -56,6 +56,63 @@ impl FilePicker {
         }
     }
 
+    pub async fn pick_bundle_save_path(&self, name: &str) -> Option<PathBuf> {
+        let locale = &self.data.preferences.language();
+        let dialog = AsyncFileDialog::new()
+            .add_filter(text(locale, "file-picker-filter-ruf"), &["ruf"])
+            .set_file_name(format!("{name}.ruf"))
+            .set_title(text(locale, "file-picker-title-save-bundle"));
+
+        if let Some(result) = self.show_dialog(dialog, |d| d.save_file()) {
+            result.await.map(|h| h.into())
+        } else {
+            None
+        }
+    }
+
+    pub async fn pick_sol_file(&self) -> Option<PathBuf> {
+        let locale = &self.data.preferences.language();
+        let dialog = AsyncFileDialog::new()
+            .add_filter(text(locale, "file-picker-filter-sol"), &["sol"])
+            .add_filter(text(locale, "file-picker-filter-all"), &["*"])
+            .set_title(text(locale, "file-picker-title-import-shared-object"));
+
+        if let Some(result) = self.show_dialog(dialog, |d| d.pick_file()) {
+            result.await.map(|h| h.into())
+        } else {
+            None
+        }
+    }
+
+    pub async fn pick_sol_save_path(&self, name: &str) -> Option<PathBuf> {
+        let locale = &self.data.preferences.language();
+        let file_name = name.rsplit('/').next().unwrap_or(name);
+        let dialog = AsyncFileDialog::new()
+            .add_filter(text(locale, "file-picker-filter-sol"), &["sol"])
+            .set_file_name(format!("{file_name}.sol"))
+            .set_title(text(locale, "file-picker-title-export-shared-object"));
+
+        if let Some(result) = self.show_dialog(dialog, |d| d.save_file()) {
+            result.await.map(|h| h.into())
+        } else {
+            None
+        }
+    }
+
+    pub async fn pick_flash_player_directory(&self) -> Option<PathBuf> {
+        let locale = &self.data.preferences.language();
+        let dialog = AsyncFileDialog::new().set_title(text(
+            locale,
+            "file-picker-title-import-flash-player-shared-objects",
+        ));
+
+        if let Some(result) = self.show_dialog(dialog, |d| d.pick_folder()) {
+            result.await.map(|h| h.into())
+        } else {
+            None
+        }
+    }
+
     pub fn show_dialog<F, O>(&self, mut dialog: AsyncFileDialog, f: F) -> Option<O>
     where
         F: FnOnce(AsyncFileDialog) -> O,
@@ -0,0 +1,215 @@
+//! Recording and replaying of user input.
+//!
+//! Sessions are stored in the same format as the `input.json` files of the
+//! regression tests, so that a recorded bug reproduction can be turned into a
+//! test directly. Mouse positions are stored in stage coordinates and every
+//! `Wait` marks one frame of the movie.
+//!
+//! Tests inject the input of a frame right after ticking it, so there's no
+//! way to give input to a movie before its first frame. Input that arrives
+//! that early while recording is held back until the first frame has run,
+//! which is where a replay will give it to the movie.
+
+use ruffle_core::events::TextControlCode;
+use ruffle_core::{Player, PlayerEvent};
+use ruffle_frontend_utils::input_format::{automated_event_to_player, player_event_to_automated};
+use ruffle_input_format::{AutomatedEvent, InputInjector};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+enum Mode {
+    Record {
+        path: PathBuf,
+        events: Vec<AutomatedEvent>,
+        frames: u32,
+
+        /// Events that arrived before the first frame, to be given to the
+        /// player once it has run.
+        early: Vec<PlayerEvent>,
+    },
+    Replay(InputInjector),
+}
+
+/// An input session that is either being recorded or replayed.
+///
+/// While a session is active, the player is driven by a deterministic clock:
+/// wall-clock time only decides *when* the next frame runs, but the movie
+/// always sees exactly one frame's worth of time passing per frame.
+/// This matches how the regression tests tick a movie when `num_ticks` is set.
+pub struct InputSession {
+    mode: Mode,
+
+    /// Wall-clock time accumulated towards the next frame, in milliseconds.
+    accumulator: f64,
+}
+
+impl InputSession {
+    /// Start recording a new session, to be saved at `path` when it ends.
+    pub fn record(path: PathBuf) -> Self {
+        tracing::info!("Recording input to {path:?}");
+        Self {
+            mode: Mode::Record {
+                path,
+                events: vec![],
+                frames: 0,
+                early: vec![],
+            },
+            accumulator: 0.0,
+        }
+    }
+
+    /// Replay a session previously saved at `path`.
+    pub fn replay(path: &Path) -> Result<Self, io::Error> {
+        let injector = InputInjector::from_file(path)?;
+        tracing::info!("Replaying input from {path:?}");
+        Ok(Self {
+            mode: Mode::Replay(injector),
+            accumulator: 0.0,
+        })
+    }
+
+    /// Give an event from the user to the player, recording it if needed.
+    ///
+    /// Returns whether the player handled the event.
+    pub fn handle_event(&mut self, player: &mut Player, event: PlayerEvent) -> bool {
+        match &mut self.mode {
+            // The user can't interfere with a replay.
+            Mode::Replay(_) => false,
+            Mode::Record {
+                frames: 0, early, ..
+            } => {
+                early.push(event);
+                false
+            }
+            Mode::Record { events, .. } => {
+                record_event(events, player, &event);
+                player.handle_event(event)
+            }
+        }
+    }
+
+    /// Advance the player by `dt` milliseconds of wall-clock time.
+    pub fn tick(&mut self, player: &mut Player, dt: f64) {
+        if !player.is_playing() {
+            return;
+        }
+
+        let frame_time = 1000.0 / player.frame_rate();
+        self.accumulator += dt;
+        if self.accumulator < frame_time {
+            return;
+        }
+        // Never catch up on more than one frame: when we're running behind, the
+        // movie should slow down instead of receiving several frames' worth of input at once.
+        self.accumulator = (self.accumulator - frame_time).min(frame_time);
+
+        player.tick(frame_time);
+
+        match &mut self.mode {
+            Mode::Record {
+                events,
+                frames,
+                early,
+                ..
+            } => {
+                // Tests inject the input of a frame right after ticking it,
+                // so the first frame doesn't need a `Wait` in front of it.
+                if *frames > 0 {
+                    events.push(AutomatedEvent::Wait);
+                }
+                *frames += 1;
+
+                for event in std::mem::take(early) {
+                    record_event(events, player, &event);
+                    player.handle_event(event);
+                }
+            }
+            Mode::Replay(injector) => injector.next(|event, _buttons| {
+                if let AutomatedEvent::SetClipboardText { text } = event {
+                    player.ui_mut().set_clipboard_content(text.to_owned());
+                } else if let Some(event) =
+                    automated_event_to_player(event, |x, y| player.stage_to_viewport_position(x, y))
+                {
+                    player.handle_event(event);
+                } else {
+                    tracing::warn!("Couldn't replay {event:?}");
+                }
+            }),
+        }
+    }
+
+    fn save(path: &Path, events: &[AutomatedEvent]) -> Result<(), io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, events)?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
+
+impl Drop for InputSession {
+    fn drop(&mut self) {
+        if let Mode::Record {
+            path,
+            events,
+            frames,
+            ..
+        } = &self.mode
+        {
+            match Self::save(path, events) {
+                Ok(()) => tracing::info!("Saved {frames} frames of input to {path:?}"),
+                Err(e) => tracing::error!("Couldn't save input recording to {path:?}: {e}"),
+            }
+        }
+    }
+}
+
+/// Record an event that is about to be given to the player.
+fn record_event(events: &mut Vec<AutomatedEvent>, player: &mut Player, event: &PlayerEvent) {
+    if let PlayerEvent::TextControl {
+        code: TextControlCode::Paste,
+    } = event
+    {
+        // Pasting depends on the contents of the system clipboard, so record them too.
+        events.push(AutomatedEvent::SetClipboardText {
+            text: player.ui_mut().clipboard_content(),
+        });
+    }
+
+    if let Some(event) =
+        player_event_to_automated(event, |x, y| player.viewport_to_stage_position(x, y))
+    {
+        events.push(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::PlayerBuilder;
+
+    #[test]
+    fn holds_back_events_until_the_first_frame() {
+        let path =
+            std::env::temp_dir().join(format!("ruffle_input_session_{}.json", std::process::id()));
+        let player = PlayerBuilder::new().with_autoplay(true).build();
+        let mut player = player.lock().unwrap();
+        let frame_time = 1000.0 / player.frame_rate();
+
+        let mut session = InputSession::record(path.clone());
+        assert!(!session.handle_event(&mut player, PlayerEvent::FocusGained));
+        session.tick(&mut player, frame_time);
+        session.handle_event(&mut player, PlayerEvent::FocusLost);
+        session.tick(&mut player, frame_time);
+        session.handle_event(&mut player, PlayerEvent::FocusGained);
+        drop(session);
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let events: Vec<AutomatedEvent> = serde_json::from_str(&recording).unwrap();
+        assert_eq!(
+            format!("{events:?}"),
+            "[FocusGained, FocusLost, Wait, FocusGained]"
+        );
+    }
+}
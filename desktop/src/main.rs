@@ -8,10 +8,13 @@
 
 mod app;
 mod backends;
+mod bundler;
 mod cli;
 mod custom_event;
+mod dap;
 mod dbus;
 mod gui;
+mod input_session;
 mod log;
 mod player;
 mod preferences;
@@ -188,6 +191,12 @@ async fn main() -> Result<(), Error> {
 
     subscriber.init();
 
+    if let Some(output) = &preferences.cli.bundle_output {
+        let result = bundler::create_bundle_headless(&preferences, output);
+        shutdown();
+        return result;
+    }
+
     let result = App::new(preferences)
         .await
         .and_then(|(mut app, event_loop)| {
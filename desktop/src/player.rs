@@ -2,14 +2,18 @@ use crate::backends::{
     DesktopExternalInterfaceProvider, DesktopFSCommandProvider, DesktopNavigatorInterface,
     DesktopUiBackend,
 };
+use crate::bundler::BundleCapture;
 use crate::cli::FilesystemAccessMode;
 use crate::cli::GameModePreference;
 use crate::custom_event::RuffleEvent;
+use crate::dap::DapServer;
 use crate::gui::{FilePicker, MovieView};
+use crate::input_session::InputSession;
 use crate::preferences::GlobalPreferences;
 use crate::{CALLSTACK, RENDER_INFO, SWF_INFO};
 use anyhow::anyhow;
 use ruffle_core::backend::navigator::SocketMode;
+use ruffle_core::compatibility_rules::CompatibilityRules;
 use ruffle_core::config::Letterbox;
 use ruffle_core::events::{GamepadButton, KeyCode};
 use ruffle_core::{DefaultFont, LoadBehavior, Player, PlayerBuilder, PlayerEvent};
@@ -27,6 +31,7 @@ use ruffle_render_wgpu::backend::WgpuRenderBackend;
 use ruffle_render_wgpu::clap::PowerPreference;
 use ruffle_render_wgpu::descriptors::Descriptors;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::path::PathBuf;
@@ -51,6 +56,9 @@ pub struct LaunchOptions {
     pub filesystem_access_mode: FilesystemAccessMode,
     pub gamepad_button_mapping: HashMap<GamepadButton, KeyCode>,
     pub avm2_optimizer_enabled: bool,
+
+    /// Whether to keep everything the movie loads, so that it can be saved as a bundle.
+    pub capture_bundle: bool,
 }
 
 impl From<&GlobalPreferences> for LaunchOptions {
@@ -101,8 +109,55 @@ impl From<&GlobalPreferences> for LaunchOptions {
             tcp_connections: value.cli.tcp_connections,
             gamepad_button_mapping: HashMap::from_iter(value.cli.gamepad_button.iter().cloned()),
             avm2_optimizer_enabled: !value.cli.no_avm2_optimizer,
+            capture_bundle: false,
+        }
+    }
+}
+
+/// Opens the content at `movie_url`, which is either a bundle or a movie to play directly.
+pub fn open_content(movie_url: &Url) -> PlayingContent {
+    if movie_url.scheme() == "file" {
+        if let Ok(path) = movie_url.to_file_path() {
+            match Bundle::from_path(&path) {
+                Ok(bundle) => {
+                    if bundle.warnings().is_empty() {
+                        tracing::info!("Opening bundle at {path:?}");
+                    } else {
+                        // TODO: Show warnings to user (toast?)
+                        tracing::warn!("Opening bundle at {path:?} with warnings");
+                        for warning in bundle.warnings() {
+                            tracing::warn!("{warning}");
+                        }
+                    }
+                    return PlayingContent::Bundle(movie_url.clone(), Box::new(bundle));
+                }
+                Err(BundleError::BundleDoesntExist)
+                | Err(BundleError::InvalidSource(BundleSourceError::UnknownSource)) => {
+                    // Do nothing and carry on opening it as a swf - this likely isn't a bundle at all
+                }
+                Err(e) => {
+                    // TODO: Visible popup when a bundle (or regular file) fails to open
+                    tracing::error!("Couldn't open bundle at {path:?}: {e}");
+                }
+            }
         }
     }
+    PlayingContent::DirectFile(movie_url.clone())
+}
+
+/// Returns all compatibility rules that may apply to `content`.
+pub fn compatibility_rules_for(
+    preferences: &GlobalPreferences,
+    content: &PlayingContent,
+) -> CompatibilityRules {
+    // Rules written by the user take precedence over those shipped in a
+    // bundle, which take precedence over the built-in ones.
+    let mut compatibility_rules = preferences.compatibility_rules().clone();
+    if let PlayingContent::Bundle(_, bundle) = content {
+        compatibility_rules.extend(bundle.information().compatibility_rules.clone());
+    }
+    compatibility_rules.extend(CompatibilityRules::default());
+    compatibility_rules
 }
 
 #[derive(Clone)]
@@ -121,6 +176,8 @@ impl PollRequester for WinitWaker {
 struct ActivePlayer {
     player: Arc<Mutex<Player>>,
     executor: Arc<AsyncExecutor<WinitWaker>>,
+    input_session: Option<RefCell<InputSession>>,
+    bundle_capture: Option<BundleCapture>,
 
     #[cfg(target_os = "linux")]
     _gamemode_session: crate::dbus::GameModeSession,
@@ -138,6 +195,7 @@ impl ActivePlayer {
         font_database: Rc<fontdb::Database>,
         preferences: GlobalPreferences,
         file_picker: FilePicker,
+        dap_server: Option<&DapServer>,
     ) -> Self {
         let mut builder = PlayerBuilder::new();
 
@@ -150,33 +208,7 @@ impl ActivePlayer {
             }
         };
 
-        let mut content = PlayingContent::DirectFile(movie_url.clone());
-        if movie_url.scheme() == "file" {
-            if let Ok(path) = movie_url.to_file_path() {
-                match Bundle::from_path(&path) {
-                    Ok(bundle) => {
-                        if bundle.warnings().is_empty() {
-                            tracing::info!("Opening bundle at {path:?}");
-                        } else {
-                            // TODO: Show warnings to user (toast?)
-                            tracing::warn!("Opening bundle at {path:?} with warnings");
-                            for warning in bundle.warnings() {
-                                tracing::warn!("{warning}");
-                            }
-                        }
-                        content = PlayingContent::Bundle(movie_url.clone(), Box::new(bundle));
-                    }
-                    Err(BundleError::BundleDoesntExist)
-                    | Err(BundleError::InvalidSource(BundleSourceError::UnknownSource)) => {
-                        // Do nothing and carry on opening it as a swf - this likely isn't a bundle at all
-                    }
-                    Err(e) => {
-                        // TODO: Visible popup when a bundle (or regular file) fails to open
-                        tracing::error!("Couldn't open bundle at {path:?}: {e}");
-                    }
-                }
-            }
-        }
+        let content = open_content(movie_url);
 
         let recent_limit = preferences.recent_limit();
         if let Err(e) = preferences.write_recents(|writer| {
@@ -207,14 +239,40 @@ impl ActivePlayer {
                     filesystem_access_mode: opt.filesystem_access_mode,
                     gamepad_button_mapping: opt.gamepad_button_mapping.clone(),
                     avm2_optimizer_enabled: opt.avm2_optimizer_enabled,
+                    capture_bundle: opt.capture_bundle,
                 })
             }
         };
 
+        let compatibility_rules = compatibility_rules_for(&preferences, &content);
+
         let (executor, future_spawner) = AsyncExecutor::new(WinitWaker(event_loop.clone()));
         let movie_url = content.initial_swf_url().clone();
+
+        // The player version and runtime can't change once the player is built,
+        // so apply the rules that force them now. Rules matched by the hash of a
+        // local file can be applied here as well, as we can read it ourselves.
+        // The file is only read when some rule needs its hash.
+        let movie_compatibility_rules = {
+            let movie_data = match &content {
+                PlayingContent::DirectFile(_) if compatibility_rules.matches_by_hash() => movie_url
+                    .to_file_path()
+                    .ok()
+                    .and_then(|path| std::fs::read(path).ok()),
+                PlayingContent::DirectFile(_) | PlayingContent::Bundle(..) => None,
+            };
+            compatibility_rules.for_movie(movie_url.as_str(), movie_data.as_deref())
+        };
         let readable_name = content.name();
-        let navigator = ExternalNavigatorBackend::new(
+        let bundle_capture = opt.capture_bundle.then(|| {
+            BundleCapture::new(
+                readable_name.clone(),
+                movie_url.clone(),
+                opt.player.clone(),
+                compatibility_rules.clone(),
+            )
+        });
+        let mut navigator = ExternalNavigatorBackend::new(
             opt.player
                 .base
                 .to_owned()
@@ -234,6 +292,9 @@ impl ActivePlayer {
                 opt.filesystem_access_mode,
             ),
         );
+        if let Some(bundle_capture) = &bundle_capture {
+            navigator = navigator.with_fetched_files(bundle_capture.fetched_files().clone());
+        }
 
         if cfg!(feature = "external_video") && preferences.openh264_enabled() {
             #[cfg(feature = "external_video")]
@@ -275,6 +336,16 @@ impl ActivePlayer {
             GameModePreference::Off => false,
         };
 
+        let input_session = if let Some(path) = &preferences.cli.record_input {
+            Some(InputSession::record(path.clone()))
+        } else if let Some(path) = &preferences.cli.replay_input {
+            InputSession::replay(path)
+                .inspect_err(|e| tracing::error!("Couldn't open input recording {path:?}: {e}"))
+                .ok()
+        } else {
+            None
+        };
+
         let renderer = WgpuRenderBackend::new(descriptors, movie_view)
             .map_err(|e| anyhow!(e.to_string()))
             .expect("Couldn't create wgpu rendering backend");
@@ -337,10 +408,23 @@ impl ActivePlayer {
             .with_load_behavior(opt.player.load_behavior.unwrap_or(LoadBehavior::Streaming))
             .with_spoofed_url(opt.player.spoof_url.clone().map(|url| url.to_string()))
             .with_page_url(opt.player.spoof_url.clone().map(|url| url.to_string()))
-            .with_player_version(opt.player.player_version)
-            .with_player_runtime(opt.player.player_runtime.unwrap_or_default())
+            .with_player_version(
+                opt.player
+                    .player_version
+                    .or(movie_compatibility_rules.player_version()),
+            )
+            .with_player_runtime(
+                opt.player
+                    .player_runtime
+                    .or(movie_compatibility_rules.player_runtime())
+                    .unwrap_or_default(),
+            )
+            .with_compatibility_rules(compatibility_rules)
             .with_frame_rate(opt.player.frame_rate)
             .with_avm2_optimizer_enabled(opt.avm2_optimizer_enabled);
+        if let Some(dap_server) = dap_server {
+            builder = builder.with_avm2_debugger(dap_server.attach());
+        }
         let player = builder.build();
 
         window.set_title(&format!("Ruffle - {readable_name}"));
@@ -421,6 +505,8 @@ impl ActivePlayer {
         Self {
             player,
             executor,
+            input_session: input_session.map(RefCell::new),
+            bundle_capture,
             #[cfg(target_os = "linux")]
             _gamemode_session: crate::dbus::GameModeSession::new(gamemode_enable),
         }
@@ -437,6 +523,7 @@ pub struct PlayerController {
     font_database: Rc<fontdb::Database>,
     preferences: GlobalPreferences,
     file_picker: FilePicker,
+    dap_server: Option<DapServer>,
 }
 
 impl PlayerController {
@@ -448,6 +535,17 @@ impl PlayerController {
         preferences: GlobalPreferences,
         file_picker: FilePicker,
     ) -> Self {
+        let dap_server = preferences
+            .cli
+            .dap_port
+            .and_then(|port| match DapServer::start(port) {
+                Ok(server) => Some(server),
+                Err(e) => {
+                    tracing::error!("Couldn't start debug adapter on port {port}: {e}");
+                    None
+                }
+            });
+
         Self {
             player: None,
             event_loop,
@@ -456,6 +554,7 @@ impl PlayerController {
             font_database: Rc::new(font_database),
             preferences,
             file_picker,
+            dap_server,
         }
     }
 
@@ -470,6 +569,7 @@ impl PlayerController {
             self.font_database.clone(),
             self.preferences.clone(),
             self.file_picker.clone(),
+            self.dap_server.as_ref(),
         ));
     }
 
@@ -493,6 +593,9 @@ impl PlayerController {
     pub fn handle_event(&self, event: PlayerEvent) -> bool {
         if let Some(mut player) = self.get() {
             if player.is_playing() {
+                if let Some(session) = self.input_session() {
+                    return session.borrow_mut().handle_event(&mut player, event);
+                }
                 return player.handle_event(event);
             }
         }
@@ -500,6 +603,25 @@ impl PlayerController {
         false
     }
 
+    /// Advance the player by `dt` milliseconds, returning how long it is until the next frame is due.
+    pub fn tick(&self, dt: f64) -> Option<Duration> {
+        let mut player = self.get()?;
+        match self.input_session() {
+            Some(session) => session.borrow_mut().tick(&mut player, dt),
+            None => player.tick(dt),
+        }
+        Some(player.time_til_next_frame())
+    }
+
+    /// Returns what is needed to save the current movie as a bundle, if it's being captured.
+    pub fn bundle_capture(&self) -> Option<BundleCapture> {
+        self.player.as_ref()?.bundle_capture.clone()
+    }
+
+    fn input_session(&self) -> Option<&RefCell<InputSession>> {
+        self.player.as_ref()?.input_session.as_ref()
+    }
+
     pub fn poll(&self) {
         if let Some(player) = &self.player {
             player.executor.poll_all()
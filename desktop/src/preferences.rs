@@ -10,7 +10,11 @@ use crate::preferences::read::read_preferences;
 use crate::preferences::write::PreferencesWriter;
 use anyhow::{Context, Error};
 use ruffle_core::backend::ui::US_ENGLISH;
+use ruffle_core::compatibility_rules::CompatibilityRules;
 use ruffle_frontend_utils::bookmarks::{read_bookmarks, Bookmarks, BookmarksWriter};
+use ruffle_frontend_utils::compatibility_rules::{
+    read_compatibility_rules, COMPATIBILITY_RULES_FILENAME,
+};
 use ruffle_frontend_utils::parse::DocumentHolder;
 use ruffle_frontend_utils::recents::{read_recents, Recents, RecentsWriter};
 use ruffle_render_wgpu::clap::{GraphicsBackend, PowerPreference};
@@ -46,6 +50,9 @@ pub struct GlobalPreferences {
 
     recents: Arc<Mutex<DocumentHolder<Recents>>>,
 
+    /// Compatibility rule sets written by the user, which are never changed by Ruffle.
+    compatibility_rules: Arc<CompatibilityRules>,
+
     watchers: GlobalPreferencesWatchers,
 }
 
@@ -92,11 +99,35 @@ impl GlobalPreferences {
             Default::default()
         };
 
+        let compatibility_rules_path = cli.config.join(COMPATIBILITY_RULES_FILENAME);
+        // Broken compatibility rules shouldn't stop the player from starting.
+        let compatibility_rules = if compatibility_rules_path.exists() {
+            match std::fs::read_to_string(&compatibility_rules_path) {
+                Ok(contents) => {
+                    let result = read_compatibility_rules(&contents);
+                    for warning in result.warnings {
+                        tracing::warn!("{warning}");
+                    }
+                    result.result.take()
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to read compatibility rules from {}: {e}",
+                        compatibility_rules_path.display()
+                    );
+                    CompatibilityRules::empty()
+                }
+            }
+        } else {
+            CompatibilityRules::empty()
+        };
+
         Ok(Self {
             cli,
             preferences: Arc::new(Mutex::new(preferences)),
             bookmarks: Arc::new(Mutex::new(bookmarks)),
             recents: Arc::new(Mutex::new(recents)),
+            compatibility_rules: Arc::new(compatibility_rules),
             watchers: Default::default(),
         })
     }
@@ -185,6 +216,11 @@ impl GlobalPreferences {
         !bookmarks.is_empty() && !bookmarks.iter().all(|x| x.is_invalid())
     }
 
+    /// The compatibility rule sets from the user's configuration directory.
+    pub fn compatibility_rules(&self) -> &CompatibilityRules {
+        &self.compatibility_rules
+    }
+
     pub fn storage_backend(&self) -> storage::StorageBackend {
         self.cli.storage.unwrap_or_else(|| {
             self.preferences
@@ -0,0 +1,104 @@
+use ruffle_core::backend::audio::{
+    swf, AudioBackend, AudioMixer, DecodeError, RegisterError, SoundHandle, SoundInstanceHandle,
+    SoundStreamInfo, SoundTransform,
+};
+use ruffle_core::impl_audio_mixer_backend;
+use std::io::{self, Write};
+
+/// An audio backend that mixes audio on demand instead of in realtime.
+///
+/// The exporter mixes exactly one frame's worth of audio after running each
+/// frame, so the captured audio is deterministic and stays in step with the
+/// captured frames, no matter how long rendering takes.
+pub struct CaptureAudioBackend {
+    mixer: AudioMixer,
+    frame_rate: f64,
+
+    /// The fraction of a sample frame that was owed, but not mixed, by
+    /// previous frames.
+    remainder: f64,
+
+    /// The captured audio, as interleaved stereo samples.
+    samples: Vec<i16>,
+}
+
+impl CaptureAudioBackend {
+    const NUM_CHANNELS: u8 = 2;
+    const SAMPLE_RATE: u32 = 44100;
+
+    pub fn new(frame_rate: f64) -> Self {
+        Self {
+            mixer: AudioMixer::new(Self::NUM_CHANNELS, Self::SAMPLE_RATE),
+            frame_rate,
+            remainder: 0.0,
+            samples: vec![],
+        }
+    }
+
+    /// Mix the audio that plays during a single frame.
+    ///
+    /// The audio is only kept if `keep` is set; skipped frames still have
+    /// to be mixed, so that sounds progress as they would have.
+    pub fn mix_frame(&mut self, keep: bool) {
+        if self.frame_rate <= 0.0 || !self.frame_rate.is_finite() {
+            return;
+        }
+
+        let sample_frames = Self::SAMPLE_RATE as f64 / self.frame_rate + self.remainder;
+        self.remainder = sample_frames.fract();
+
+        let mut buffer = vec![0i16; sample_frames as usize * Self::NUM_CHANNELS as usize];
+        self.mixer.mix::<i16>(&mut buffer);
+        if keep {
+            self.samples.extend_from_slice(&buffer);
+        }
+    }
+
+    /// Take the audio captured so far.
+    pub fn take_samples(&mut self) -> Vec<i16> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// Write interleaved stereo samples, as captured by this backend, to a
+    /// 16-bit PCM WAV file.
+    pub fn write_wav(samples: &[i16], mut writer: impl Write) -> io::Result<()> {
+        const BITS_PER_SAMPLE: u16 = 16;
+        const FORMAT_PCM: u16 = 1;
+
+        let num_channels = Self::NUM_CHANNELS as u16;
+        let block_align = num_channels * BITS_PER_SAMPLE / 8;
+        let byte_rate = Self::SAMPLE_RATE * block_align as u32;
+        let data_length = (samples.len() * std::mem::size_of::<i16>()) as u32;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&(36 + data_length).to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&FORMAT_PCM.to_le_bytes())?;
+        writer.write_all(&num_channels.to_le_bytes())?;
+        writer.write_all(&Self::SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&byte_rate.to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&data_length.to_le_bytes())?;
+        for sample in samples {
+            writer.write_all(&sample.to_le_bytes())?;
+        }
+
+        writer.flush()
+    }
+}
+
+impl AudioBackend for CaptureAudioBackend {
+    impl_audio_mixer_backend!(mixer);
+    fn play(&mut self) {}
+    fn pause(&mut self) {}
+
+    fn set_frame_rate(&mut self, frame_rate: f64) {
+        self.frame_rate = frame_rate;
+    }
+}
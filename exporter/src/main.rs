@@ -1,20 +1,32 @@
+mod audio;
+mod trigger;
+mod video;
+
+use crate::audio::CaptureAudioBackend;
+use crate::trigger::{CaptureTrigger, TriggerEvents, TriggerFsCommandProvider, TriggerLogBackend};
+use crate::video::{SequenceWriter, Y4mWriter};
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use image::RgbaImage;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
+use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
 use ruffle_core::limits::ExecutionLimit;
 use ruffle_core::tag_utils::SwfMovie;
-use ruffle_core::PlayerBuilder;
+use ruffle_core::{PlayerBuilder, ViewportDimensions};
+use ruffle_render::backend::RenderBackend;
+use ruffle_render_software::SoftwareRenderBackend;
 use ruffle_render_wgpu::backend::{request_adapter_and_device, WgpuRenderBackend};
 use ruffle_render_wgpu::clap::{GraphicsBackend, PowerPreference};
 use ruffle_render_wgpu::descriptors::Descriptors;
 use ruffle_render_wgpu::target::TextureTarget;
 use ruffle_render_wgpu::wgpu;
-use std::fs::create_dir_all;
-use std::io::{self, Write};
+use std::cell::RefCell;
+use std::fs::{create_dir_all, File};
+use std::io::{self, BufWriter, Write};
 use std::panic::catch_unwind;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::Arc;
 use walkdir::{DirEntry, WalkDir};
 
@@ -52,6 +64,19 @@ struct Opt {
     #[clap(short = 'f', long = "frames", default_value = "1")]
     frames: u32,
 
+    /// Only capture frames during which one of these events happens, instead of
+    /// every frame after the skipped ones. May be given multiple times.
+    /// One of "label:<name>" (the main timeline entered the frame labelled <name>),
+    /// "fscommand" or "fscommand:<command>" (fscommand was called),
+    /// or "trace:<text>" (a line containing <text> was traced).
+    #[clap(long = "capture-on", value_name = "TRIGGER")]
+    capture_on: Vec<CaptureTrigger>,
+
+    /// The maximum number of frames to run per file while waiting for
+    /// `--capture-on` triggers.
+    #[clap(long = "max-frames", default_value = "10000")]
+    max_frames: u32,
+
     /// Number of frames to skip
     #[clap(long = "skipframes", default_value = "0")]
     skipframes: u32,
@@ -73,26 +98,140 @@ struct Opt {
     #[clap(long, short, default_value = "high")]
     power: PowerPreference,
 
-    /// Skip unsupported movie types (currently AVM 2)
+    /// Render on the CPU instead of the GPU. This is much slower, but works on machines
+    /// without a GPU, and produces the same images on every machine.
     #[clap(long, action)]
-    skip_unsupported: bool,
+    software: bool,
+
+    /// Also capture the audio played during the captured frames, and save it
+    /// as a WAV file next to the frames.
+    #[clap(long, action)]
+    audio: bool,
+
+    /// Export a single swf as a video instead of individual frames.
+    /// With "y4m", the output may be "-" to pipe the video into an encoder.
+    #[clap(long, value_enum)]
+    video: Option<VideoFormat>,
+}
+
+#[derive(ValueEnum, Debug, Copy, Clone, PartialEq, Eq)]
+enum VideoFormat {
+    /// Numbered PNG files, plus an ffconcat manifest with the duration of each frame
+    Sequence,
+
+    /// A single uncompressed YUV4MPEG2 stream
+    Y4m,
+}
+
+/// What frames are rendered with.
+enum Renderer {
+    Wgpu(Arc<Descriptors>),
+    Software,
+}
+
+impl Renderer {
+    fn create_backend(
+        &self,
+        width: u32,
+        height: u32,
+        scale: f64,
+    ) -> Result<Box<dyn RenderBackend>> {
+        match self {
+            Renderer::Wgpu(descriptors) => {
+                let target = TextureTarget::new(&descriptors.device, (width, height))
+                    .map_err(|e| anyhow!(e.to_string()))?;
+                let backend = WgpuRenderBackend::new(descriptors.clone(), target)
+                    .map_err(|e| anyhow!(e.to_string()))?;
+                Ok(Box::new(backend))
+            }
+            Renderer::Software => Ok(Box::new(SoftwareRenderBackend::new(ViewportDimensions {
+                width,
+                height,
+                scale_factor: scale,
+            }))),
+        }
+    }
+}
+
+/// Captures the last frame rendered by a backend created by [`Renderer::create_backend`],
+/// using straight alpha.
+fn capture_frame(backend: &mut dyn RenderBackend) -> Option<RgbaImage> {
+    if let Some(backend) = backend.downcast_mut::<SoftwareRenderBackend>() {
+        return Some(backend.capture_frame());
+    }
+    backend
+        .downcast_mut::<WgpuRenderBackend<TextureTarget>>()?
+        .capture_frame()
+}
+
+/// The output of running a movie.
+struct Capture {
+    /// The captured frames, using straight alpha.
+    frames: Vec<RgbaImage>,
+
+    /// The audio played during the captured frames, as interleaved stereo
+    /// samples, if audio capture was requested.
+    audio: Option<Vec<i16>>,
+}
+
+impl Capture {
+    /// Save the captured audio, if any, next to `frames_path`.
+    fn save_audio(&self, frames_path: &Path) -> Result<()> {
+        save_audio(self.audio.as_deref(), frames_path)
+    }
+}
+
+/// Save captured audio, if any, next to `frames_path`.
+fn save_audio(audio: Option<&[i16]>, frames_path: &Path) -> Result<()> {
+    if let Some(audio) = audio {
+        let path = frames_path.with_extension("wav");
+        let writer = BufWriter::new(File::create(path)?);
+        CaptureAudioBackend::write_wav(audio, writer)?;
+    }
+
+    Ok(())
 }
 
 /// Captures a screenshot. The resulting image uses straight alpha
 fn take_screenshot(
-    descriptors: Arc<Descriptors>,
+    renderer: &Renderer,
     swf_path: &Path,
-    frames: u32,
-    skipframes: u32,
     progress: &Option<ProgressBar>,
-    size: SizeOpt,
-    skip_unsupported: bool,
-) -> Result<Vec<RgbaImage>> {
-    let movie = SwfMovie::from_path(swf_path, None).map_err(|e| anyhow!(e.to_string()))?;
+    opt: &Opt,
+) -> Result<Capture> {
+    let mut frames = Vec::new();
+    let audio = run_movie(renderer, swf_path, progress, opt, &mut |image, _| {
+        frames.push(image);
+        Ok(())
+    })?;
 
-    if movie.is_action_script_3() && skip_unsupported {
-        return Err(anyhow!("Skipping unsupported movie"));
-    }
+    Ok(Capture { frames, audio })
+}
+
+/// Runs a movie, passing each captured frame to `on_frame` along with the
+/// duration of that frame in milliseconds.
+///
+/// Timers run on a fixed clock, advancing by exactly one frame each frame,
+/// so that the captured frames don't depend on how long rendering takes.
+///
+/// Returns the captured audio, if audio capture was requested.
+fn run_movie(
+    renderer: &Renderer,
+    swf_path: &Path,
+    progress: &Option<ProgressBar>,
+    opt: &Opt,
+    on_frame: &mut dyn FnMut(RgbaImage, f64) -> Result<()>,
+) -> Result<Option<Vec<i16>>> {
+    let Opt {
+        frames,
+        skipframes,
+        size,
+        audio,
+        max_frames,
+        ..
+    } = *opt;
+
+    let movie = SwfMovie::from_path(swf_path, None).map_err(|e| anyhow!(e.to_string()))?;
 
     let width = size
         .width
@@ -106,20 +245,43 @@ fn take_screenshot(
         .unwrap_or_else(|| movie.height().to_pixels());
     let height = (height * size.scale).round() as u32;
 
-    let target = TextureTarget::new(&descriptors.device, (width, height))
-        .map_err(|e| anyhow!(e.to_string()))?;
-    let player = PlayerBuilder::new()
-        .with_renderer(
-            WgpuRenderBackend::new(descriptors, target).map_err(|e| anyhow!(e.to_string()))?,
-        )
+    // Relative URLs, such as those of movies loaded with `Loader`, are
+    // resolved next to the swf.
+    let mut executor = NullExecutor::new();
+    let base_path = swf_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    let navigator = NullNavigatorBackend::with_base_path(base_path, &executor)?;
+
+    let events = Rc::new(RefCell::new(TriggerEvents::default()));
+
+    let mut builder = PlayerBuilder::new()
+        .with_navigator(navigator)
+        .with_log(TriggerLogBackend::new(events.clone()))
+        .with_fs_commands(Box::new(TriggerFsCommandProvider::new(events.clone())));
+    if audio {
+        builder = builder.with_audio(CaptureAudioBackend::new(movie.frame_rate().to_f64()));
+    }
+    let player = builder
+        .with_boxed_renderer(renderer.create_backend(width, height, size.scale)?)
         .with_movie(movie)
         .with_viewport_dimensions(width, height, size.scale)
         .build();
 
-    let mut result = Vec::new();
-    let totalframes = frames + skipframes;
+    let totalframes = if opt.capture_on.is_empty() {
+        frames + skipframes
+    } else {
+        max_frames
+    };
+    let mut captured = 0;
+    let mut last_frame = None;
 
     for i in 0..totalframes {
+        if captured >= frames {
+            break;
+        }
+
         if let Some(progress) = &progress {
             progress.set_message(format!(
                 "{} frame {}",
@@ -131,18 +293,44 @@ fn take_screenshot(
         player.lock().unwrap().preload(&mut ExecutionLimit::none());
 
         player.lock().unwrap().run_frame();
-        if i >= skipframes {
+        let frame_time = 1000.0 / player.lock().unwrap().frame_rate();
+        player.lock().unwrap().update_timers(frame_time);
+        executor.run();
+
+        let frame_events = std::mem::take(&mut *events.borrow_mut());
+        let capture = i >= skipframes
+            && (opt.capture_on.is_empty() || {
+                let mut player = player.lock().unwrap();
+                let frame = player.current_frame();
+                let entered_label = if frame != last_frame {
+                    last_frame = frame;
+                    player.current_frame_label()
+                } else {
+                    None
+                };
+
+                opt.capture_on
+                    .iter()
+                    .any(|trigger| trigger.fired(&frame_events, entered_label.as_deref()))
+            });
+
+        if audio {
+            let mut player = player.lock().unwrap();
+            let backend = player
+                .audio_mut()
+                .downcast_mut::<CaptureAudioBackend>()
+                .unwrap();
+            backend.mix_frame(capture);
+        }
+        if capture {
+            captured += 1;
             let image = || {
                 player.lock().unwrap().render();
                 let mut player = player.lock().unwrap();
-                let renderer = player
-                    .renderer_mut()
-                    .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
-                    .unwrap();
-                renderer.capture_frame()
+                capture_frame(player.renderer_mut().as_mut())
             };
             match catch_unwind(image) {
-                Ok(Some(image)) => result.push(image),
+                Ok(Some(image)) => on_frame(image, frame_time)?,
                 Ok(None) => return Err(anyhow!("Unable to capture frame {} of {:?}", i, swf_path)),
                 Err(e) => {
                     return Err(anyhow!(
@@ -159,7 +347,15 @@ fn take_screenshot(
             progress.inc(1);
         }
     }
-    Ok(result)
+
+    Ok(audio.then(|| {
+        let mut player = player.lock().unwrap();
+        player
+            .audio_mut()
+            .downcast_mut::<CaptureAudioBackend>()
+            .unwrap()
+            .take_samples()
+    }))
 }
 
 fn find_files(root: &Path, with_progress: bool) -> Vec<DirEntry> {
@@ -192,7 +388,7 @@ fn find_files(root: &Path, with_progress: bool) -> Vec<DirEntry> {
     results
 }
 
-fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
+fn capture_single_swf(renderer: &Renderer, opt: &Opt) -> Result<()> {
     let output = opt.output_path.clone().unwrap_or_else(|| {
         let mut result = PathBuf::new();
         result.set_file_name(opt.swf.file_stem().unwrap());
@@ -220,15 +416,8 @@ fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
         None
     };
 
-    let frames = take_screenshot(
-        descriptors,
-        &opt.swf,
-        opt.frames,
-        opt.skipframes,
-        &progress,
-        opt.size,
-        opt.skip_unsupported,
-    )?;
+    let capture = take_screenshot(renderer, &opt.swf, &progress, opt)?;
+    let frames = &capture.frames;
 
     if let Some(progress) = &progress {
         progress.set_message(opt.swf.file_stem().unwrap().to_string_lossy().into_owned());
@@ -255,6 +444,8 @@ fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
         }
     }
 
+    capture.save_audio(&output)?;
+
     let message = if frames.len() == 1 {
         if !opt.silent {
             Some(format!(
@@ -285,8 +476,125 @@ fn capture_single_swf(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
     Ok(())
 }
 
+fn capture_video(renderer: &Renderer, opt: &Opt, format: VideoFormat) -> Result<()> {
+    let to_stdout = opt.output_path == Some(PathBuf::from("-"));
+    let output = opt.output_path.clone().unwrap_or_else(|| {
+        let mut result = PathBuf::new();
+        result.set_file_name(opt.swf.file_stem().unwrap());
+        if format == VideoFormat::Y4m {
+            result.set_extension("y4m");
+        }
+        result
+    });
+
+    let progress = if !opt.silent {
+        let progress = ProgressBar::new(opt.frames as u64);
+        progress.set_style(
+            ProgressStyle::with_template(
+                "[{elapsed_precise}] {bar:40.cyan/blue} [{eta_precise}] {pos:>7}/{len:7} {msg}",
+            )
+            .unwrap()
+            .progress_chars("##-"),
+        );
+        Some(progress)
+    } else {
+        None
+    };
+
+    let mut frame_count = 0;
+    let audio = match format {
+        VideoFormat::Sequence => {
+            if to_stdout {
+                return Err(anyhow!("An image sequence can't be written to stdout."));
+            }
+
+            let mut writer = SequenceWriter::new(output.clone())?;
+            let audio = run_movie(
+                renderer,
+                &opt.swf,
+                &progress,
+                opt,
+                &mut |image, duration| {
+                    frame_count += 1;
+                    writer.write_frame(&image, duration)
+                },
+            )?;
+            writer.finish()?;
+            audio
+        }
+        VideoFormat::Y4m => {
+            let mut out: Option<Box<dyn Write>> = Some(if to_stdout {
+                Box::new(BufWriter::new(io::stdout().lock()))
+            } else {
+                Box::new(BufWriter::new(File::create(&output)?))
+            });
+
+            // The stream header needs the frame size and rate, so it's only
+            // written once the first frame has been captured.
+            let mut writer = None;
+            let mut warned_frame_rate = false;
+            let audio = run_movie(
+                renderer,
+                &opt.swf,
+                &progress,
+                opt,
+                &mut |image, duration| {
+                    let frame_rate = 1000.0 / duration;
+                    let writer = match &mut writer {
+                        Some(writer) => writer,
+                        None => writer.insert(Y4mWriter::new(
+                            out.take().unwrap(),
+                            image.width(),
+                            image.height(),
+                            frame_rate,
+                        )?),
+                    };
+
+                    if !warned_frame_rate && !writer.has_frame_rate(frame_rate) {
+                        // Y4M streams have a single frame rate, so the rest of the
+                        // video will play back at the wrong speed.
+                        warned_frame_rate = true;
+                        let message = format!(
+                            "Warning: the frame rate changed to {frame_rate:.2} fps at frame {frame_count}, \
+                             which a Y4M stream can't represent. Use --video sequence to keep the timing."
+                        );
+                        match &progress {
+                            Some(progress) => progress.println(message),
+                            None => eprintln!("{message}"),
+                        }
+                    }
+
+                    frame_count += 1;
+                    writer.write_frame(&image)
+                },
+            )?;
+            if let Some(writer) = writer {
+                writer.finish()?;
+            }
+            audio
+        }
+    };
+
+    save_audio(audio.as_deref(), &output)?;
+
+    let message = format!(
+        "Saved {} frames of {} to {}",
+        frame_count,
+        opt.swf.to_string_lossy(),
+        output.to_string_lossy()
+    );
+
+    if let Some(progress) = progress {
+        progress.finish_with_message(message);
+    } else if !to_stdout {
+        println!("{message}");
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::branches_sharing_code)]
-fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()> {
+fn capture_multiple_swfs(renderer: &Renderer, opt: &Opt) -> Result<()> {
     let output = opt.output_path.clone().unwrap();
     let files = find_files(&opt.swf, !opt.silent);
 
@@ -314,15 +622,8 @@ fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()>
                     .into_owned(),
             );
         }
-        if let Ok(frames) = take_screenshot(
-            descriptors.clone(),
-            file.path(),
-            opt.frames,
-            opt.skipframes,
-            &progress,
-            opt.size,
-            opt.skip_unsupported,
-        ) {
+        if let Ok(capture) = take_screenshot(renderer, file.path(), &progress, opt) {
+            let frames = &capture.frames;
             let mut relative_path = file
                 .path()
                 .strip_prefix(&opt.swf)
@@ -337,6 +638,7 @@ fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()>
                     let _ = create_dir_all(parent);
                 }
                 frames.first().unwrap().save(&destination)?;
+                capture.save_audio(&destination)?;
             } else {
                 let mut parent: PathBuf = (&output).into();
                 relative_path.set_extension("");
@@ -347,6 +649,7 @@ fn capture_multiple_swfs(descriptors: Arc<Descriptors>, opt: &Opt) -> Result<()>
                     destination.push(format!("{frame}.png"));
                     image.save(&destination)?;
                 }
+                capture.save_audio(&parent)?;
             }
         }
 
@@ -383,27 +686,40 @@ fn trace_path(_opt: &Opt) -> Option<&Path> {
 
 fn main() -> Result<()> {
     let opt: Opt = Opt::parse();
-    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-        backends: opt.graphics.into(),
-        ..Default::default()
-    });
-    let (adapter, device, queue) = futures::executor::block_on(request_adapter_and_device(
-        opt.graphics.into(),
-        &instance,
-        None,
-        opt.power.into(),
-        trace_path(&opt),
-    ))
-    .map_err(|e| anyhow!(e.to_string()))?;
-
-    let descriptors = Arc::new(Descriptors::new(instance, adapter, device, queue));
-
-    if opt.swf.is_file() {
-        capture_single_swf(descriptors, &opt)?;
+    let renderer = if opt.software {
+        Renderer::Software
+    } else {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: opt.graphics.into(),
+            ..Default::default()
+        });
+        let (adapter, device, queue) = futures::executor::block_on(request_adapter_and_device(
+            opt.graphics.into(),
+            &instance,
+            None,
+            opt.power.into(),
+            trace_path(&opt),
+        ))
+        .map_err(|e| anyhow!(e.to_string()))?;
+
+        Renderer::Wgpu(Arc::new(Descriptors::new(instance, adapter, device, queue)))
+    };
+
+    if opt.audio && opt.output_path == Some(PathBuf::from("-")) {
+        return Err(anyhow!("Audio can't be captured when writing to stdout."));
+    }
+
+    if let Some(format) = opt.video {
+        if !opt.swf.is_file() {
+            return Err(anyhow!("Only a single swf can be exported as a video."));
+        }
+        capture_video(&renderer, &opt, format)?;
+    } else if opt.swf.is_file() {
+        capture_single_swf(&renderer, &opt)?;
     } else if !opt.swf.is_dir() {
         return Err(anyhow!("Given path is not a file or directory."));
     } else if opt.output_path.is_some() {
-        capture_multiple_swfs(descriptors, &opt)?;
+        capture_multiple_swfs(&renderer, &opt)?;
     } else {
         return Err(anyhow!(
             "Output directory is required when exporting multiple files."
@@ -0,0 +1,97 @@
+use ruffle_core::backend::log::LogBackend;
+use ruffle_core::external::FsCommandProvider;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// An event that causes the frame it happens during to be captured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptureTrigger {
+    /// The main timeline entered a frame with this label.
+    FrameLabel(String),
+
+    /// `fscommand` was called, with this command if given.
+    FsCommand(Option<String>),
+
+    /// A line containing this text was traced.
+    Trace(String),
+}
+
+impl CaptureTrigger {
+    /// Whether this trigger fired during a frame.
+    ///
+    /// `entered_label` is the label of the frame that the main timeline
+    /// entered, if it moved to a labelled frame.
+    pub fn fired(&self, events: &TriggerEvents, entered_label: Option<&str>) -> bool {
+        match self {
+            Self::FrameLabel(label) => entered_label == Some(label.as_str()),
+            Self::FsCommand(None) => !events.fs_commands.is_empty(),
+            Self::FsCommand(Some(command)) => events.fs_commands.contains(command),
+            Self::Trace(text) => events
+                .traces
+                .iter()
+                .any(|trace| trace.contains(text.as_str())),
+        }
+    }
+}
+
+impl FromStr for CaptureTrigger {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once(':') {
+            Some(("label", label)) => Ok(Self::FrameLabel(label.to_string())),
+            Some(("fscommand", command)) => Ok(Self::FsCommand(Some(command.to_string()))),
+            Some(("trace", text)) => Ok(Self::Trace(text.to_string())),
+            None if value == "fscommand" => Ok(Self::FsCommand(None)),
+            _ => Err(format!(
+                "unknown trigger '{value}', expected one of 'label:<name>', 'fscommand', 'fscommand:<command>' or 'trace:<text>'"
+            )),
+        }
+    }
+}
+
+/// The events that happened while running a frame.
+#[derive(Default, Debug)]
+pub struct TriggerEvents {
+    fs_commands: Vec<String>,
+    traces: Vec<String>,
+}
+
+/// Records traces for `CaptureTrigger::Trace`.
+pub struct TriggerLogBackend {
+    events: Rc<RefCell<TriggerEvents>>,
+}
+
+impl TriggerLogBackend {
+    pub fn new(events: Rc<RefCell<TriggerEvents>>) -> Self {
+        Self { events }
+    }
+}
+
+impl LogBackend for TriggerLogBackend {
+    fn avm_trace(&self, message: &str) {
+        self.events.borrow_mut().traces.push(message.to_string());
+    }
+}
+
+/// Records `fscommand` calls for `CaptureTrigger::FsCommand`.
+pub struct TriggerFsCommandProvider {
+    events: Rc<RefCell<TriggerEvents>>,
+}
+
+impl TriggerFsCommandProvider {
+    pub fn new(events: Rc<RefCell<TriggerEvents>>) -> Self {
+        Self { events }
+    }
+}
+
+impl FsCommandProvider for TriggerFsCommandProvider {
+    fn on_fs_command(&self, command: &str, _args: &str) -> bool {
+        self.events
+            .borrow_mut()
+            .fs_commands
+            .push(command.to_string());
+        true
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::Result;
+use image::RgbaImage;
+use std::fs::{create_dir_all, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// Writes frames as an uncompressed YUV4MPEG2 stream.
+///
+/// Frames are converted to 4:4:4 BT.601 YCbCr, composited over black.
+pub struct Y4mWriter<W: Write> {
+    writer: W,
+    width: u32,
+    height: u32,
+    frame_rate: (u32, u32),
+}
+
+impl<W: Write> Y4mWriter<W> {
+    /// Start a stream of `width`x`height` frames, shown `frame_rate` times
+    /// per second.
+    pub fn new(mut writer: W, width: u32, height: u32, frame_rate: f64) -> Result<Self> {
+        let frame_rate = frame_rate_ratio(frame_rate);
+        let (numerator, denominator) = frame_rate;
+        writeln!(
+            writer,
+            "YUV4MPEG2 W{width} H{height} F{numerator}:{denominator} Ip A1:1 C444"
+        )?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            frame_rate,
+        })
+    }
+
+    /// Whether frames shown `frame_rate` times per second match the rate in
+    /// the stream header. Y4M has no way to change the rate mid-stream.
+    pub fn has_frame_rate(&self, frame_rate: f64) -> bool {
+        frame_rate_ratio(frame_rate) == self.frame_rate
+    }
+
+    pub fn write_frame(&mut self, image: &RgbaImage) -> Result<()> {
+        if image.width() != self.width || image.height() != self.height {
+            return Err(anyhow::anyhow!(
+                "Frame is {}x{}, but the stream is {}x{}",
+                image.width(),
+                image.height(),
+                self.width,
+                self.height
+            ));
+        }
+
+        let len = (self.width * self.height) as usize;
+        let mut planes = vec![0u8; len * 3];
+        let (y_plane, chroma) = planes.split_at_mut(len);
+        let (u_plane, v_plane) = chroma.split_at_mut(len);
+
+        for (i, pixel) in image.pixels().enumerate() {
+            let [r, g, b, a] = pixel.0.map(i32::from);
+            let [r, g, b] = [r, g, b].map(|c| c * a / 255);
+
+            y_plane[i] = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+            u_plane[i] = (((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128) as u8;
+            v_plane[i] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&planes)?;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes frames as numbered PNG files, along with an `ffconcat` manifest
+/// that records how long each frame is shown for.
+///
+/// The manifest can be given to an encoder such as
+/// `ffmpeg -f concat -i frames.ffconcat`, and keeps exact frame pacing even
+/// if the movie changes its frame rate.
+pub struct SequenceWriter {
+    directory: PathBuf,
+    manifest: BufWriter<File>,
+    frame: u32,
+}
+
+impl SequenceWriter {
+    pub const MANIFEST_NAME: &'static str = "frames.ffconcat";
+
+    pub fn new(directory: PathBuf) -> Result<Self> {
+        create_dir_all(&directory)?;
+        let mut manifest = BufWriter::new(File::create(directory.join(Self::MANIFEST_NAME))?);
+        writeln!(manifest, "ffconcat version 1.0")?;
+
+        Ok(Self {
+            directory,
+            manifest,
+            frame: 0,
+        })
+    }
+
+    /// Write a frame that is shown for `duration` milliseconds.
+    pub fn write_frame(&mut self, image: &RgbaImage, duration: f64) -> Result<()> {
+        let name = format!("{}.png", self.frame);
+        image.save(self.directory.join(&name))?;
+        writeln!(self.manifest, "file '{name}'")?;
+        writeln!(self.manifest, "duration {:.6}", duration / 1000.0)?;
+        self.frame += 1;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        if self.frame > 0 {
+            // The duration of the last entry is ignored unless it is repeated.
+            writeln!(self.manifest, "file '{}.png'", self.frame - 1)?;
+        }
+
+        self.manifest.flush()?;
+        Ok(())
+    }
+}
+
+/// Express a frame rate as a reduced ratio of integers.
+///
+/// SWF frame rates are 8.8 fixed point numbers, so this is exact for the
+/// frame rate of any movie.
+fn frame_rate_ratio(frame_rate: f64) -> (u32, u32) {
+    fn gcd(a: u32, b: u32) -> u32 {
+        if b == 0 {
+            a
+        } else {
+            gcd(b, a % b)
+        }
+    }
+
+    let numerator = (frame_rate * 256.0).round().max(1.0) as u32;
+    let divisor = gcd(numerator, 256);
+    (numerator / divisor, 256 / divisor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn frame_rate_ratio_is_reduced() {
+        assert_eq!(frame_rate_ratio(24.0), (24, 1));
+        assert_eq!(frame_rate_ratio(12.5), (25, 2));
+        assert_eq!(frame_rate_ratio(29.97), (959, 32));
+        assert_eq!(frame_rate_ratio(0.25), (1, 4));
+        assert_eq!(frame_rate_ratio(1.0 / 256.0), (1, 256));
+    }
+
+    #[test]
+    fn frame_rate_ratio_is_never_zero() {
+        assert_eq!(frame_rate_ratio(0.0), (1, 256));
+    }
+
+    #[test]
+    fn has_frame_rate() {
+        let writer = Y4mWriter::new(vec![], 1, 1, 30.0).unwrap();
+        assert!(writer.has_frame_rate(1000.0 / (1000.0 / 30.0)));
+        assert!(!writer.has_frame_rate(24.0));
+    }
+
+    fn encode(pixels: &[[u8; 4]]) -> Vec<u8> {
+        let width = pixels.len() as u32;
+        let image = RgbaImage::from_fn(width, 1, |x, _| Rgba(pixels[x as usize]));
+        let mut output = vec![];
+        let mut writer = Y4mWriter::new(&mut output, width, 1, 24.0).unwrap();
+        writer.write_frame(&image).unwrap();
+        writer.finish().unwrap();
+        output
+    }
+
+    #[test]
+    fn header() {
+        let output = encode(&[[0, 0, 0, 255]]);
+        assert!(output.starts_with(b"YUV4MPEG2 W1 H1 F24:1 Ip A1:1 C444\nFRAME\n"));
+    }
+
+    #[test]
+    fn rgb_to_yuv() {
+        let output = encode(&[
+            [0, 0, 0, 255],
+            [255, 255, 255, 255],
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+        ]);
+        let planes = &output[output.len() - 15..];
+        assert_eq!(&planes[0..5], &[16, 235, 82, 144, 41], "Y");
+        assert_eq!(&planes[5..10], &[128, 128, 90, 54, 240], "U");
+        assert_eq!(&planes[10..15], &[128, 128, 240, 34, 110], "V");
+    }
+
+    #[test]
+    fn rgb_to_yuv_composites_over_black() {
+        let output = encode(&[[255, 255, 255, 0], [255, 255, 255, 128]]);
+        let planes = &output[output.len() - 6..];
+        assert_eq!(planes, &[16, 126, 128, 128, 128, 128]);
+    }
+
+    #[test]
+    fn wrong_frame_size_is_an_error() {
+        let mut writer = Y4mWriter::new(vec![], 2, 2, 24.0).unwrap();
+        assert!(writer.write_frame(&RgbaImage::new(1, 1)).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 mod fetch;
 
 use crate::backends::executor::{spawn_tokio, FutureSpawner};
-use crate::backends::navigator::fetch::{Response, ResponseBody};
+use crate::backends::navigator::fetch::{Capture, Response, ResponseBody};
 use crate::content::PlayingContent;
 use async_channel::{Receiver, Sender, TryRecvError};
 use async_io::Timer;
@@ -14,7 +14,7 @@ use ruffle_core::backend::navigator::{
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
 use ruffle_core::socket::{ConnectionState, SocketAction, SocketHandle};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
 use std::io;
 use std::io::ErrorKind;
@@ -39,6 +39,30 @@ pub trait NavigatorInterface: Clone + Send + 'static {
     ) -> impl std::future::Future<Output = bool> + Send;
 }
 
+/// Every file successfully fetched by a movie, keyed by its url.
+///
+/// Local files are keyed without their query, as that is how they are read from disk.
+/// This is shared between the navigator and whoever wants to inspect what was loaded,
+/// for example to create a bundle out of a running movie.
+#[derive(Clone, Default)]
+pub struct FetchedFiles(Arc<Mutex<BTreeMap<Url, Vec<u8>>>>);
+
+impl FetchedFiles {
+    /// Records a fetched file. The first copy of a url is kept.
+    pub fn insert(&self, url: Url, data: Vec<u8>) {
+        self.0
+            .lock()
+            .expect("fetched files lock")
+            .entry(url)
+            .or_insert(data);
+    }
+
+    /// Returns a snapshot of all files fetched so far.
+    pub fn files(&self) -> BTreeMap<Url, Vec<u8>> {
+        self.0.lock().expect("fetched files lock").clone()
+    }
+}
+
 /// Implementation of `NavigatorBackend` for non-web environments that can call
 /// out to a web browser.
 pub struct ExternalNavigatorBackend<F: FutureSpawner, I: NavigatorInterface> {
@@ -60,6 +84,9 @@ pub struct ExternalNavigatorBackend<F: FutureSpawner, I: NavigatorInterface> {
     content: Rc<PlayingContent>,
 
     interface: I,
+
+    /// Where to record fetched files, if anywhere.
+    fetched_files: Option<FetchedFiles>,
 }
 
 impl<F: FutureSpawner, I: NavigatorInterface> ExternalNavigatorBackend<F, I> {
@@ -126,8 +153,15 @@ impl<F: FutureSpawner, I: NavigatorInterface> ExternalNavigatorBackend<F, I> {
             socket_mode,
             content,
             interface,
+            fetched_files: None,
         }
     }
+
+    /// Records every file fetched by the movie into the given collection.
+    pub fn with_fetched_files(mut self, fetched_files: FetchedFiles) -> Self {
+        self.fetched_files = Some(fetched_files);
+        self
+    }
 }
 
 impl<F: FutureSpawner + 'static, I: NavigatorInterface> NavigatorBackend
@@ -190,7 +224,23 @@ impl<F: FutureSpawner + 'static, I: NavigatorInterface> NavigatorBackend
             }
         };
 
+        if request.method() == NavigationMethod::Get {
+            // If the bundle has a copy of this network file, serve that instead.
+            if let Some(contents) = self.content.get_mirrored_file(&processed_url) {
+                let response: Box<dyn SuccessResponse> = Box::new(Response {
+                    url: processed_url.to_string(),
+                    response_body: ResponseBody::File(Ok(contents)),
+                    text_encoding: None,
+                    status: 200,
+                    redirected: false,
+                    capture: None,
+                });
+                return async_return(Ok(response));
+            }
+        }
+
         let client = self.client.clone();
+        let fetched_files = self.fetched_files.clone();
 
         match processed_url.scheme() {
             "file" => {
@@ -207,12 +257,17 @@ impl<F: FutureSpawner + 'static, I: NavigatorInterface> NavigatorBackend
 
                     let contents = content.get_local_file(&processed_url, interface).await;
 
+                    if let (Some(fetched_files), Ok(contents)) = (&fetched_files, &contents) {
+                        fetched_files.insert(processed_url, contents.clone());
+                    }
+
                     let response: Box<dyn SuccessResponse> = Box::new(Response {
                         url: response_url.to_string(),
                         response_body: ResponseBody::File(contents),
                         text_encoding: None,
                         status: 0,
                         redirected: false,
+                        capture: None,
                     });
 
                     Ok(response)
@@ -266,12 +321,18 @@ impl<F: FutureSpawner + 'static, I: NavigatorInterface> NavigatorBackend
                     return Err(ErrorResponse { url, error });
                 }
 
+                // Only plain GET requests can be replayed from a bundle later on.
+                let capture = fetched_files
+                    .filter(|_| request.method() == NavigationMethod::Get)
+                    .map(|files| Capture::new(files, processed_url));
+
                 let response: Box<dyn SuccessResponse> = Box::new(Response {
                     url,
                     response_body: ResponseBody::Network(Arc::new(Mutex::new(Some(response)))),
                     text_encoding,
                     status,
                     redirected,
+                    capture,
                 });
                 Ok(response)
             }),
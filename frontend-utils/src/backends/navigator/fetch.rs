@@ -3,6 +3,9 @@ use ruffle_core::backend::navigator::{OwnedFuture, SuccessResponse};
 use ruffle_core::loader::Error;
 use ruffle_core::swf::Encoding;
 use std::sync::{Arc, Mutex};
+use url::Url;
+
+use super::FetchedFiles;
 
 pub enum ResponseBody {
     /// The response's body comes from a file.
@@ -22,6 +25,30 @@ pub struct Response {
     pub text_encoding: Option<&'static Encoding>,
     pub status: u16,
     pub redirected: bool,
+    pub capture: Option<Capture>,
+}
+
+/// Collects the body of a network response as it is read, so that it can be
+/// recorded in [`FetchedFiles`] once complete.
+#[derive(Clone)]
+pub struct Capture {
+    files: FetchedFiles,
+    url: Url,
+    data: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Capture {
+    pub fn new(files: FetchedFiles, url: Url) -> Self {
+        Self {
+            files,
+            url,
+            data: Default::default(),
+        }
+    }
+
+    fn finish(&self, body: Vec<u8>) {
+        self.files.insert(self.url.clone(), body);
+    }
 }
 
 impl SuccessResponse for Response {
@@ -31,12 +58,17 @@ impl SuccessResponse for Response {
 
     #[allow(clippy::await_holding_lock)]
     fn body(self: Box<Self>) -> OwnedFuture<Vec<u8>, Error> {
-        match self.response_body {
+        let Response {
+            response_body,
+            capture,
+            ..
+        } = *self;
+        match response_body {
             ResponseBody::File(file) => {
                 Box::pin(async move { file.map_err(|e| Error::FetchError(e.to_string())) })
             }
             ResponseBody::Network(response) => Box::pin(async move {
-                Ok(response
+                let body = response
                     .lock()
                     .expect("working lock during fetch body read")
                     .take()
@@ -44,7 +76,11 @@ impl SuccessResponse for Response {
                     .bytes()
                     .await
                     .map_err(|e| Error::FetchError(e.to_string()))?
-                    .to_vec())
+                    .to_vec();
+                if let Some(capture) = capture {
+                    capture.finish(body.clone());
+                }
+                Ok(body)
             }),
         }
     }
@@ -80,6 +116,7 @@ impl SuccessResponse for Response {
             }
             ResponseBody::Network(response) => {
                 let response = response.clone();
+                let capture = self.capture.clone();
                 Box::pin(async move {
                     let lock = response.try_lock();
                     if matches!(lock, Err(std::sync::TryLockError::WouldBlock)) {
@@ -97,8 +134,25 @@ impl SuccessResponse for Response {
                         .await;
 
                     match result {
-                        Ok(Some(bytes)) => Ok(Some(bytes.to_vec())),
-                        Ok(None) => Ok(None),
+                        Ok(Some(bytes)) => {
+                            if let Some(capture) = &capture {
+                                capture
+                                    .data
+                                    .lock()
+                                    .expect("capture lock")
+                                    .extend_from_slice(&bytes);
+                            }
+                            Ok(Some(bytes.to_vec()))
+                        }
+                        Ok(None) => {
+                            if let Some(capture) = &capture {
+                                let body = std::mem::take(
+                                    &mut *capture.data.lock().expect("capture lock"),
+                                );
+                                capture.finish(body);
+                            }
+                            Ok(None)
+                        }
                         Err(e) => Err(Error::FetchError(e.to_string())),
                     }
                 })
@@ -4,9 +4,11 @@ use crate::bundle::info::{
 use crate::bundle::source::BundleSource;
 use crate::parse::ParseWarning;
 use std::path::Path;
+use url::Url;
 
 pub mod info;
 pub mod source;
+pub mod write;
 
 #[derive(Debug, thiserror::Error)]
 pub enum BundleError {
@@ -63,6 +65,26 @@ impl Bundle {
     }
 }
 
+/// Returns the path inside `content/` at which a copy of the given network url is stored.
+///
+/// Files are mirrored as `<host>/<path>`, ignoring the scheme, port and query.
+/// Returns `None` for urls which cannot be mirrored, such as local files or directories.
+pub fn network_content_path(url: &Url) -> Option<String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let mut path = url.host_str()?.to_owned();
+    for segment in url.path_segments()? {
+        let segment = urlencoding::decode(segment).ok()?;
+        if segment.is_empty() || segment == "." || segment == ".." || segment.contains('/') {
+            return None;
+        }
+        path.push('/');
+        path.push_str(&segment);
+    }
+    Some(path)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::bundle::info::{
@@ -71,6 +93,7 @@ mod tests {
     use crate::bundle::source::BundleSourceError;
     use crate::bundle::{Bundle, BundleError};
     use crate::parse::ParseWarning;
+    use ruffle_core::compatibility_rules::CompatibilityRules;
     use tempfile::tempdir;
     use url::Url;
 
@@ -150,6 +173,7 @@ mod tests {
                 name: "Cool Game!".to_string(),
                 url: Url::parse("file:///game.swf").unwrap(),
                 player: Default::default(),
+                compatibility_rules: CompatibilityRules::empty(),
             },
             result.information
         );
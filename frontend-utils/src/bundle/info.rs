@@ -1,5 +1,7 @@
+use crate::compatibility_rules::read_rule_sets;
 use crate::parse::{DocumentHolder, ParseContext, ParseDetails, ReadExt};
 use crate::player_options::{read_player_options, PlayerOptions};
+use ruffle_core::compatibility_rules::CompatibilityRules;
 use toml_edit::DocumentMut;
 use url::Url;
 
@@ -26,6 +28,10 @@ pub struct BundleInformation {
     pub url: Url,
 
     pub player: PlayerOptions,
+
+    /// Compatibility rule sets shipped with this bundle, in addition to
+    /// those of the player.
+    pub compatibility_rules: CompatibilityRules,
 }
 
 impl BundleInformation {
@@ -53,6 +59,8 @@ impl BundleInformation {
             read_player_options(cx, table)
         });
 
+        let compatibility_rules = read_rule_sets(&mut cx, document.as_table());
+
         Ok(ParseDetails {
             warnings: cx.warnings,
             result: DocumentHolder::new(
@@ -60,6 +68,7 @@ impl BundleInformation {
                     name,
                     url,
                     player: player_options.unwrap_or_default(),
+                    compatibility_rules,
                 },
                 document,
             ),
@@ -72,6 +81,7 @@ mod test {
     use crate::bundle::info::{BundleInformation, BundleInformationParseError};
     use crate::parse::ParseWarning;
     use crate::player_options::PlayerOptions;
+    use ruffle_core::compatibility_rules::{CompatibilityRules, RuleSet, UrlRewriteRule};
     use ruffle_core::PlayerRuntime;
     use url::Url;
 
@@ -175,6 +185,7 @@ mod test {
                     name: "Cool Game!".to_string(),
                     url: Url::parse("file:///game.swf").unwrap(),
                     player: Default::default(),
+                    compatibility_rules: CompatibilityRules::empty(),
                 },
                 vec![]
             ))
@@ -213,7 +224,41 @@ mod test {
                         player_runtime: Some(PlayerRuntime::AIR),
                         frame_rate: Some(15.0),
                         ..Default::default()
-                    }
+                    },
+                    compatibility_rules: CompatibilityRules::empty(),
+                },
+                vec![]
+            ))
+        )
+    }
+
+    #[test]
+    fn valid_with_compatibility_rules() {
+        assert_eq!(
+            read(
+                r#"
+            [bundle]
+            name = "Compatibility Rules Example"
+            url = "file:///example.swf"
+
+            [[rule_set]]
+            name = "dead_assets"
+
+            [[rule_set.fetch_url_rewrite]]
+            host = "assets.example.com"
+            replacement = "archive.example.org"
+            "#
+            ),
+            Ok((
+                BundleInformation {
+                    name: "Compatibility Rules Example".to_string(),
+                    url: Url::parse("file:///example.swf").unwrap(),
+                    player: Default::default(),
+                    compatibility_rules: CompatibilityRules::new(vec![RuleSet::new("dead_assets")
+                        .with_fetch_url_rewrite_rule(UrlRewriteRule::new(
+                            "assets.example.com",
+                            "archive.example.org"
+                        ))]),
                 },
                 vec![]
             ))
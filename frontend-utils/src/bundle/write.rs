@@ -0,0 +1,426 @@
+use crate::bundle::info::BUNDLE_INFORMATION_FILENAME;
+use crate::bundle::network_content_path;
+use crate::compatibility_rules::create_rule_set_table;
+use crate::player_options::{create_player_options_table, PlayerOptions};
+use ruffle_core::compatibility_rules::CompatibilityRules;
+use std::collections::BTreeMap;
+use std::io::{Seek, Write};
+use toml_edit::{value, ArrayOfTables, DocumentMut, Table};
+use url::Url;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundleWriteError {
+    #[error("IO error writing bundle: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Zip error writing bundle: {0}")]
+    Zip(#[from] zip::result::ZipError),
+}
+
+/// Builds a bundle out of a movie and the files it needs.
+///
+/// See `README.md` in this directory for the structure this produces.
+pub struct BundleWriter {
+    name: String,
+    url: Url,
+    player: PlayerOptions,
+    compatibility_rules: CompatibilityRules,
+    content: BTreeMap<String, Vec<u8>>,
+    conflicts: Vec<Url>,
+}
+
+impl BundleWriter {
+    pub fn new(name: impl ToString, url: Url) -> Self {
+        Self {
+            name: name.to_string(),
+            url,
+            player: Default::default(),
+            compatibility_rules: CompatibilityRules::empty(),
+            content: Default::default(),
+            conflicts: Default::default(),
+        }
+    }
+
+    /// Creates a bundle out of a movie that has been played,
+    /// containing every file it fetched while doing so.
+    ///
+    /// Local files are stored relative to the directory of `root_url`,
+    /// and network files are mirrored as `<host>/<path>` so that they keep working offline.
+    /// Local files outside of that directory cannot be represented and are skipped.
+    ///
+    /// Urls that only differ by their query are stored at the same path, as a bundle
+    /// looks up files without their query. When their contents differ, the root movie
+    /// (or else the first of them) is kept and the others are reported in [`Self::conflicts`].
+    ///
+    /// Compatibility rules which apply to the movie are kept without their matchers,
+    /// as the movie will not be found at its original url anymore.
+    pub fn from_fetched_files(
+        name: impl ToString,
+        root_url: &Url,
+        mut player: PlayerOptions,
+        compatibility_rules: &CompatibilityRules,
+        fetched_files: &BTreeMap<Url, Vec<u8>>,
+    ) -> Self {
+        let root_directory = root_url.join(".").unwrap_or_else(|_| root_url.clone());
+        let content_path = |url: &Url| {
+            if url.scheme() == "file" {
+                local_content_path(&root_directory, url)
+            } else {
+                network_content_path(url)
+            }
+        };
+
+        let url = match root_url.scheme() {
+            "file" => content_path(root_url)
+                .map(|path| content_url(&path))
+                .unwrap_or_else(|| root_url.clone()),
+            _ => root_url.clone(),
+        };
+
+        if let Some(base) = player.base.take() {
+            if base.scheme() == "file" {
+                match local_content_path(&root_directory, &base) {
+                    Some(path) => player.base = Some(content_url(&path)),
+                    None => tracing::warn!("Base url {base} is not inside the bundle, ignoring"),
+                }
+            } else {
+                player.base = Some(base);
+            }
+        }
+
+        let mut root_url_without_query = root_url.clone();
+        root_url_without_query.set_query(None);
+        let root_data = fetched_files
+            .get(root_url)
+            .or_else(|| fetched_files.get(&root_url_without_query));
+        let rule_sets = compatibility_rules
+            .for_movie(root_url.as_str(), root_data.map(Vec::as_slice))
+            .rule_sets()
+            .iter()
+            .map(|rule_set| rule_set.clone().without_matchers())
+            .collect();
+
+        let mut writer = Self::new(name, url)
+            .with_player_options(player)
+            .with_compatibility_rules(CompatibilityRules::new(rule_sets));
+        if let (Some(path), Some(data)) = (content_path(root_url), root_data) {
+            writer.add_content(path, data.clone());
+        }
+        for (url, data) in fetched_files {
+            let Some(path) = content_path(url) else {
+                tracing::warn!("Cannot store {url} in the bundle, skipping");
+                continue;
+            };
+            match writer.content.get(&path) {
+                Some(existing) if existing != data => {
+                    tracing::warn!(
+                        "{url} differs from another file stored at {path} in the bundle, skipping"
+                    );
+                    writer.conflicts.push(url.clone());
+                }
+                Some(_) => {}
+                None => writer.add_content(path, data.clone()),
+            }
+        }
+        writer
+    }
+
+    pub fn with_player_options(mut self, player: PlayerOptions) -> Self {
+        self.player = player;
+        self
+    }
+
+    pub fn with_compatibility_rules(mut self, compatibility_rules: CompatibilityRules) -> Self {
+        self.compatibility_rules = compatibility_rules;
+        self
+    }
+
+    /// Adds a file to `content/`, at the given path relative to it.
+    pub fn add_content(&mut self, path: impl ToString, data: Vec<u8>) {
+        self.content.insert(path.to_string(), data);
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    pub fn content(&self) -> &BTreeMap<String, Vec<u8>> {
+        &self.content
+    }
+
+    /// Fetched files that were left out because another file with different contents
+    /// is stored at the same path.
+    pub fn conflicts(&self) -> &[Url] {
+        &self.conflicts
+    }
+
+    /// Returns the contents of `ruffle-bundle.toml` for this bundle.
+    pub fn information(&self) -> String {
+        let mut document = DocumentMut::new();
+
+        let mut bundle = Table::new();
+        bundle["name"] = value(self.name.as_str());
+        bundle["url"] = value(self.url.as_str());
+        document.insert("bundle", bundle.into());
+
+        let player = create_player_options_table(&self.player);
+        if !player.is_empty() {
+            document.insert("player", player.into());
+        }
+
+        if !self.compatibility_rules.is_empty() {
+            let mut rule_sets = ArrayOfTables::new();
+            for rule_set in self.compatibility_rules.rule_sets() {
+                rule_sets.push(create_rule_set_table(rule_set));
+            }
+            document.insert("rule_set", rule_sets.into());
+        }
+
+        document.to_string()
+    }
+
+    /// Writes this bundle as a zip, which is the format of `.ruf` files.
+    pub fn write_zip(&self, writer: impl Write + Seek) -> Result<(), BundleWriteError> {
+        let mut zip = ZipWriter::new(writer);
+        let options = SimpleFileOptions::default();
+
+        zip.start_file(BUNDLE_INFORMATION_FILENAME, options)?;
+        zip.write_all(self.information().as_bytes())?;
+
+        for (path, data) in &self.content {
+            zip.start_file(format!("content/{path}"), options)?;
+            zip.write_all(data)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+/// Returns the `file:///` url at which a file in `content/` is visible to the movie.
+fn content_url(path: &str) -> Url {
+    let mut url = Url::parse("file:///").expect("valid url");
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments.clear().extend(path.split('/'));
+    }
+    url
+}
+
+/// Returns the path of a local file relative to `root_directory`, if it is inside of it.
+fn local_content_path(root_directory: &Url, url: &Url) -> Option<String> {
+    if url.scheme() != "file" || url.host() != root_directory.host() {
+        return None;
+    }
+    let root_segments = root_directory.path_segments()?.filter(|s| !s.is_empty());
+    let mut segments = url.path_segments()?.peekable();
+    for root_segment in root_segments {
+        if segments.next() != Some(root_segment) {
+            return None;
+        }
+    }
+
+    let mut path = Vec::new();
+    while let Some(segment) = segments.next() {
+        let segment = urlencoding::decode(segment).ok()?;
+        // Only a directory may end with an empty segment.
+        let is_last = segments.peek().is_none();
+        if (segment.is_empty() && !is_last) || segment == ".." || segment.contains('/') {
+            return None;
+        }
+        path.push(segment.into_owned());
+    }
+    Some(path.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bundle::write::BundleWriter;
+    use crate::bundle::Bundle;
+    use crate::content::PlayingContent;
+    use crate::player_options::PlayerOptions;
+    use ruffle_core::compatibility_rules::{
+        CompatibilityRules, MovieMatcher, RuleSet, UrlRewriteRule,
+    };
+    use std::collections::BTreeMap;
+    use std::fs::File;
+    use tempfile::tempdir;
+    use url::Url;
+
+    fn fetched_files(files: &[(&str, &[u8])]) -> BTreeMap<Url, Vec<u8>> {
+        files
+            .iter()
+            .map(|(url, data)| (Url::parse(url).unwrap(), data.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn from_local_movie() {
+        let files = fetched_files(&[
+            ("file:///games/cool/game.swf", b"swf"),
+            ("file:///games/cool/levels/level%201.xml", b"xml"),
+            ("file:///games/other.swf", b"outside"),
+            ("https://example.com/scores/top.txt", b"scores"),
+        ]);
+        let writer = BundleWriter::from_fetched_files(
+            "Cool Game",
+            &Url::parse("file:///games/cool/game.swf").unwrap(),
+            PlayerOptions {
+                base: Some(Url::parse("file:///games/cool/levels/").unwrap()),
+                ..Default::default()
+            },
+            &CompatibilityRules::empty(),
+            &files,
+        );
+
+        assert_eq!(writer.url().as_str(), "file:///game.swf");
+        assert_eq!(
+            writer.content().keys().collect::<Vec<_>>(),
+            vec![
+                "example.com/scores/top.txt",
+                "game.swf",
+                "levels/level 1.xml"
+            ]
+        );
+        assert_eq!(
+            writer.player.base,
+            Some(Url::parse("file:///levels/").unwrap())
+        );
+    }
+
+    #[test]
+    fn from_network_movie() {
+        let root_url = Url::parse("https://example.com/game.swf?level=2").unwrap();
+        let files = fetched_files(&[("https://example.com/game.swf?level=2", b"swf")]);
+        let rules = CompatibilityRules::new(vec![
+            RuleSet::new("applies")
+                .with_matcher(MovieMatcher::Url("https://example.com/*".to_string()))
+                .with_fetch_url_rewrite_rule(UrlRewriteRule::new("old.example.com", "example.com")),
+            RuleSet::new("doesn't apply")
+                .with_matcher(MovieMatcher::Url("https://example.org/*".to_string())),
+        ]);
+        let writer = BundleWriter::from_fetched_files(
+            "Network Game",
+            &root_url,
+            Default::default(),
+            &rules,
+            &files,
+        );
+
+        assert_eq!(writer.url(), &root_url);
+        assert_eq!(
+            writer.content().keys().collect::<Vec<_>>(),
+            vec!["example.com/game.swf"]
+        );
+        assert_eq!(
+            writer.compatibility_rules,
+            CompatibilityRules::new(vec![RuleSet::new("applies").with_fetch_url_rewrite_rule(
+                UrlRewriteRule::new("old.example.com", "example.com")
+            )])
+        );
+    }
+
+    #[test]
+    fn urls_differing_by_query() {
+        let root_url = Url::parse("file:///games/game.swf?level=2").unwrap();
+        let files = fetched_files(&[
+            ("file:///games/game.swf", b"swf"),
+            ("file:///games/game.swf?level=2", b"swf"),
+            ("https://example.com/scores.php?page=1", b"first page"),
+            ("https://example.com/scores.php?page=2", b"second page"),
+            ("https://example.com/logo.png?v=1", b"png"),
+            ("https://example.com/logo.png?v=2", b"png"),
+        ]);
+        let writer = BundleWriter::from_fetched_files(
+            "Game",
+            &root_url,
+            Default::default(),
+            &CompatibilityRules::empty(),
+            &files,
+        );
+
+        assert_eq!(
+            writer.content().keys().collect::<Vec<_>>(),
+            vec!["example.com/logo.png", "example.com/scores.php", "game.swf"]
+        );
+        assert_eq!(
+            writer.content()["example.com/scores.php"],
+            b"first page".to_vec()
+        );
+        assert_eq!(
+            writer.conflicts(),
+            &[Url::parse("https://example.com/scores.php?page=2").unwrap()]
+        );
+    }
+
+    #[test]
+    fn root_movie_wins_conflicts() {
+        let root_url = Url::parse("file:///games/game.swf?level=2").unwrap();
+        let files = fetched_files(&[
+            ("file:///games/game.swf", b"other swf"),
+            ("file:///games/game.swf?level=2", b"swf"),
+        ]);
+        let writer = BundleWriter::from_fetched_files(
+            "Game",
+            &root_url,
+            Default::default(),
+            &CompatibilityRules::empty(),
+            &files,
+        );
+
+        assert_eq!(writer.content()["game.swf"], b"swf".to_vec());
+        assert_eq!(
+            writer.conflicts(),
+            &[Url::parse("file:///games/game.swf").unwrap()]
+        );
+    }
+
+    #[test]
+    fn write_and_read_back() {
+        let mut writer = BundleWriter::new(
+            "Cool Game!",
+            Url::parse("https://example.com/game.swf").unwrap(),
+        )
+        .with_player_options(PlayerOptions {
+            parameters: vec![("key".to_string(), "value".to_string())],
+            ..Default::default()
+        })
+        .with_compatibility_rules(CompatibilityRules::new(vec![
+            RuleSet::new("Some rules").with_player_version(9)
+        ]));
+        writer.add_content("example.com/game.swf", b"swf".to_vec());
+        writer.add_content("local.txt", b"text".to_vec());
+
+        let tmp_dir = tempdir().unwrap();
+        let path = tmp_dir.path().join("game.ruf");
+        writer.write_zip(File::create(&path).unwrap()).unwrap();
+        let bundle = Bundle::from_path(&path).unwrap();
+
+        assert!(bundle.warnings().is_empty());
+        assert_eq!(bundle.information().name, "Cool Game!");
+        assert_eq!(bundle.information().url, writer.url);
+        assert_eq!(bundle.information().player, writer.player);
+        assert_eq!(
+            bundle.information().compatibility_rules,
+            writer.compatibility_rules
+        );
+
+        let content = PlayingContent::Bundle(Url::from_file_path(&path).unwrap(), Box::new(bundle));
+        assert_eq!(
+            content.get_mirrored_file(&Url::parse("https://example.com/game.swf").unwrap()),
+            Some(b"swf".to_vec())
+        );
+        assert_eq!(
+            content.get_mirrored_file(&Url::parse("https://example.org/game.swf").unwrap()),
+            None
+        );
+        assert_eq!(
+            futures_lite::future::block_on(
+                content.get_local_file(&Url::parse("file:///local.txt").unwrap(), ())
+            )
+            .unwrap(),
+            b"text".to_vec()
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod read;
+mod write;
+
+pub use read::{read_compatibility_rules, read_rule_sets};
+pub use write::create_rule_set_table;
+
+/// The name of the file that desktop frontends load user compatibility rules from.
+pub const COMPATIBILITY_RULES_FILENAME: &str = "compatibility_rules.toml";
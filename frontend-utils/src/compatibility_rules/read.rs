@@ -0,0 +1,246 @@
+use crate::parse::{DocumentHolder, ItemExt, ParseContext, ParseDetails, ParseWarning, ReadExt};
+use ruffle_core::compatibility_rules::{CompatibilityRules, MovieMatcher, RuleSet, UrlRewriteRule};
+use toml_edit::{DocumentMut, Table, TableLike};
+
+/// Reads a standalone file of compatibility rules, consisting of `[[rule_set]]` tables.
+pub fn read_compatibility_rules(input: &str) -> ParseDetails<CompatibilityRules> {
+    let document = match input.parse::<DocumentMut>() {
+        Ok(document) => document,
+        Err(e) => {
+            return ParseDetails {
+                result: DocumentHolder::new(CompatibilityRules::empty(), DocumentMut::new()),
+                warnings: vec![ParseWarning::InvalidToml(e)],
+            }
+        }
+    };
+
+    let mut cx = ParseContext::default();
+    let result = read_rule_sets(&mut cx, document.as_table());
+
+    ParseDetails {
+        warnings: cx.warnings,
+        result: DocumentHolder::new(result, document),
+    }
+}
+
+/// Reads the `[[rule_set]]` tables inside of `table`.
+pub fn read_rule_sets<'a>(
+    cx: &mut ParseContext<'a>,
+    table: &'a dyn TableLike,
+) -> CompatibilityRules {
+    let mut result = CompatibilityRules::empty();
+
+    table.get_array_of_tables(cx, "rule_set", |cx, rule_sets| {
+        for rule_set in rule_sets.iter() {
+            if let Some(rule_set) = read_rule_set(cx, rule_set) {
+                result.add_rule_set(rule_set);
+            }
+        }
+    });
+
+    result
+}
+
+fn read_rule_set<'a>(cx: &mut ParseContext<'a>, table: &'a Table) -> RuleSet {
+    // Names are only used for logging, so a missing one isn't fatal.
+    let name = table
+        .parse_from_str::<String>(cx, "name")
+        .unwrap_or_else(|| "unnamed".to_string());
+    let mut rule_set = RuleSet::new(name);
+
+    // Movies this rule set applies to, or every movie if there are none.
+    let mut matchers = Vec::new();
+    table.get_array_of_tables(cx, "match", |cx, tables| {
+        for table in tables.iter() {
+            matchers.extend(table.parse_from_str(cx, "md5").map(MovieMatcher::Md5));
+            matchers.extend(table.parse_from_str(cx, "sha256").map(MovieMatcher::Sha256));
+            matchers.extend(table.parse_from_str(cx, "url").map(MovieMatcher::Url));
+        }
+    });
+    for matcher in matchers {
+        rule_set = rule_set.with_matcher(matcher);
+    }
+
+    for rule in read_url_rewrite_rules(cx, table, "swf_domain_rewrite") {
+        rule_set = rule_set.with_swf_domain_rewrite_rule(rule);
+    }
+
+    for rule in read_url_rewrite_rules(cx, table, "fetch_url_rewrite") {
+        rule_set = rule_set.with_fetch_url_rewrite_rule(rule);
+    }
+
+    // Parameters (FlashVars) key-value table, all values must be strings.
+    let mut flash_vars = Vec::new();
+    table.get_table_like(cx, "parameters", |cx, parameters| {
+        for (key, value) in parameters.iter() {
+            cx.push_key(key);
+
+            if let Some(value) = value.as_str_or_warn(cx) {
+                flash_vars.push((key.to_owned(), value.to_owned()));
+            }
+
+            cx.pop_key();
+        }
+    });
+    for (key, value) in flash_vars {
+        rule_set = rule_set.with_flash_var(key, value);
+    }
+
+    // Player version
+    if let Some(version) = table.get_integer(cx, "version") {
+        rule_set = rule_set.with_player_version(version as u8);
+    }
+
+    // Player runtime
+    if let Some(runtime) = table.parse_from_str(cx, "runtime") {
+        rule_set = rule_set.with_player_runtime(runtime);
+    }
+
+    // Frame rate
+    if let Some(frame_rate) = table.get_float_like(cx, "frame_rate") {
+        rule_set = rule_set.with_frame_rate(frame_rate);
+    }
+
+    rule_set
+}
+
+fn read_url_rewrite_rules<'a>(
+    cx: &mut ParseContext<'a>,
+    table: &'a Table,
+    key: &'static str,
+) -> Vec<UrlRewriteRule> {
+    let mut result = Vec::new();
+
+    table.get_array_of_tables(cx, key, |cx, rules| {
+        for rule in rules.iter() {
+            let host = rule.parse_from_str::<String>(cx, "host");
+            let replacement = rule.parse_from_str::<String>(cx, "replacement");
+
+            if let (Some(host), Some(replacement)) = (host, replacement) {
+                result.push(UrlRewriteRule::new(host, replacement));
+            }
+        }
+    });
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::PlayerRuntime;
+
+    #[test]
+    fn empty() {
+        let result = read_compatibility_rules("");
+        assert_eq!(&CompatibilityRules::empty(), result.values());
+        assert_eq!(Vec::<ParseWarning>::new(), result.warnings);
+    }
+
+    #[test]
+    fn rule_set_not_array() {
+        let result = read_compatibility_rules("[rule_set]");
+        assert_eq!(&CompatibilityRules::empty(), result.values());
+        assert_eq!(
+            vec![ParseWarning::UnexpectedType {
+                expected: "array of tables",
+                actual: "table",
+                path: "rule_set".to_string()
+            }],
+            result.warnings
+        );
+    }
+
+    #[test]
+    fn unnamed() {
+        let result = read_compatibility_rules("[[rule_set]]");
+        assert_eq!(
+            &CompatibilityRules::new(vec![RuleSet::new("unnamed")]),
+            result.values()
+        );
+        assert_eq!(Vec::<ParseWarning>::new(), result.warnings);
+    }
+
+    #[test]
+    fn all_rule_kinds() {
+        let result = read_compatibility_rules(
+            r#"
+            [[rule_set]]
+            name = "example"
+            version = 9
+            runtime = "air"
+            frame_rate = 30
+
+            [[rule_set.match]]
+            md5 = "5d41402abc4b2a76b9719d911017c592"
+
+            [[rule_set.match]]
+            url = "https://example.com/*"
+
+            [[rule_set.swf_domain_rewrite]]
+            host = "*.example.com"
+            replacement = "example.org"
+
+            [[rule_set.fetch_url_rewrite]]
+            host = "assets.example.com"
+            replacement = "archive.example.org"
+
+            [rule_set.parameters]
+            key = "value"
+            "#,
+        );
+        assert_eq!(
+            &CompatibilityRules::new(vec![RuleSet::new("example")
+                .with_matcher(MovieMatcher::Md5(
+                    "5d41402abc4b2a76b9719d911017c592".to_string()
+                ))
+                .with_matcher(MovieMatcher::Url("https://example.com/*".to_string()))
+                .with_swf_domain_rewrite_rule(UrlRewriteRule::new("*.example.com", "example.org"))
+                .with_fetch_url_rewrite_rule(UrlRewriteRule::new(
+                    "assets.example.com",
+                    "archive.example.org"
+                ))
+                .with_flash_var("key", "value")
+                .with_player_version(9)
+                .with_player_runtime(PlayerRuntime::AIR)
+                .with_frame_rate(30.0)]),
+            result.values()
+        );
+        assert_eq!(Vec::<ParseWarning>::new(), result.warnings);
+    }
+
+    #[test]
+    fn invalid_values() {
+        let result = read_compatibility_rules(
+            r#"
+            [[rule_set]]
+            name = "invalid"
+            runtime = "shockwave"
+
+            [[rule_set.match]]
+            sha256 = 1234
+
+            [[rule_set.fetch_url_rewrite]]
+            host = "example.com"
+            "#,
+        );
+        assert_eq!(
+            &CompatibilityRules::new(vec![RuleSet::new("invalid")]),
+            result.values()
+        );
+        assert_eq!(
+            vec![
+                ParseWarning::UnexpectedType {
+                    expected: "string",
+                    actual: "integer",
+                    path: "rule_set.match.sha256".to_string()
+                },
+                ParseWarning::UnsupportedValue {
+                    value: "shockwave".to_string(),
+                    path: "rule_set.runtime".to_string()
+                },
+            ],
+            result.warnings
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use crate::player_options::runtime_name;
+use ruffle_core::compatibility_rules::{MovieMatcher, RuleSet, UrlRewriteRule};
+use toml_edit::{value, ArrayOfTables, InlineTable, Table};
+
+/// Creates a `[[rule_set]]` table describing `rule_set`, in the format read by [`crate::compatibility_rules::read_rule_sets`].
+pub fn create_rule_set_table(rule_set: &RuleSet) -> Table {
+    let mut table = Table::new();
+    table["name"] = value(rule_set.name());
+
+    if !rule_set.matchers().is_empty() {
+        let mut matchers = ArrayOfTables::new();
+        for matcher in rule_set.matchers() {
+            let mut matcher_table = Table::new();
+            match matcher {
+                MovieMatcher::Md5(hash) => matcher_table["md5"] = value(hash.as_str()),
+                MovieMatcher::Sha256(hash) => matcher_table["sha256"] = value(hash.as_str()),
+                MovieMatcher::Url(pattern) => matcher_table["url"] = value(pattern.as_str()),
+            }
+            matchers.push(matcher_table);
+        }
+        table.insert("match", matchers.into());
+    }
+
+    write_url_rewrite_rules(
+        &mut table,
+        "swf_domain_rewrite",
+        rule_set.swf_domain_rewrite_rules(),
+    );
+    write_url_rewrite_rules(
+        &mut table,
+        "fetch_url_rewrite",
+        rule_set.fetch_url_rewrite_rules(),
+    );
+
+    if !rule_set.flash_vars().is_empty() {
+        let mut parameters = InlineTable::new();
+        for (key, flash_var) in rule_set.flash_vars() {
+            parameters.insert(key.as_str(), flash_var.as_str().into());
+        }
+        table["parameters"] = value(parameters);
+    }
+
+    if let Some(version) = rule_set.player_version() {
+        table["version"] = value(i64::from(version));
+    }
+
+    if let Some(runtime) = rule_set.player_runtime() {
+        table["runtime"] = value(runtime_name(runtime));
+    }
+
+    if let Some(frame_rate) = rule_set.frame_rate() {
+        table["frame_rate"] = value(frame_rate);
+    }
+
+    table
+}
+
+fn write_url_rewrite_rules(table: &mut Table, key: &str, rules: &[UrlRewriteRule]) {
+    if rules.is_empty() {
+        return;
+    }
+
+    let mut array = ArrayOfTables::new();
+    for rule in rules {
+        let mut rule_table = Table::new();
+        rule_table["host"] = value(rule.host.as_str());
+        rule_table["replacement"] = value(rule.replacement.as_str());
+        array.push(rule_table);
+    }
+    table.insert(key, array.into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compatibility_rules::read_compatibility_rules;
+    use ruffle_core::compatibility_rules::CompatibilityRules;
+    use ruffle_core::PlayerRuntime;
+    use toml_edit::DocumentMut;
+
+    #[test]
+    fn roundtrip() {
+        let rule_set = RuleSet::new("everything")
+            .with_matcher(MovieMatcher::Md5("0123456789abcdef".to_string()))
+            .with_matcher(MovieMatcher::Url("https://example.com/*".to_string()))
+            .with_swf_domain_rewrite_rule(UrlRewriteRule::new("*.old.com", "new.com"))
+            .with_fetch_url_rewrite_rule(UrlRewriteRule::new("assets.dead.com", "archive.org"))
+            .with_flash_var("key", "value")
+            .with_player_version(9)
+            .with_player_runtime(PlayerRuntime::AIR)
+            .with_frame_rate(30.0);
+
+        let mut document = DocumentMut::new();
+        let mut rule_sets = ArrayOfTables::new();
+        rule_sets.push(create_rule_set_table(&rule_set));
+        document.insert("rule_set", rule_sets.into());
+
+        let result = read_compatibility_rules(&document.to_string());
+        assert_eq!(Vec::<crate::parse::ParseWarning>::new(), result.warnings);
+        assert_eq!(&CompatibilityRules::new(vec![rule_set]), result.values());
+    }
+}
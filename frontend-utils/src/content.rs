@@ -1,5 +1,5 @@
 use crate::backends::navigator::NavigatorInterface;
-use crate::bundle::Bundle;
+use crate::bundle::{network_content_path, Bundle};
 use std::fmt::{Debug, Formatter};
 use std::io::{ErrorKind, Read};
 use url::Url;
@@ -74,4 +74,15 @@ impl PlayingContent {
             }
         }
     }
+
+    /// Returns the bundled copy of a network file, if this content is a bundle which has one.
+    pub fn get_mirrored_file(&self, url: &Url) -> Option<Vec<u8>> {
+        match self {
+            PlayingContent::DirectFile(_) => None,
+            PlayingContent::Bundle(_, bundle) => {
+                let path = network_content_path(url)?;
+                bundle.source().read_content(&format!("/{path}")).ok()
+            }
+        }
+    }
 }
@@ -0,0 +1,461 @@
+//! Conversion between input format events and player events.
+
+use ruffle_core::events::{
+    ImeEvent, KeyDescriptor, KeyLocation, LogicalKey, MouseButton, MouseWheelDelta, NamedKey,
+    PhysicalKey, TextControlCode,
+};
+use ruffle_core::PlayerEvent;
+use ruffle_input_format::{
+    AutomatedEvent, AutomatedKey, MouseButton as InputMouseButton, MousePosition,
+    TextControlCode as InputTextControlCode,
+};
+
+/// Convert an event given to the player into an event of the input format.
+///
+/// Mouse positions are passed through `to_stage`, which should turn viewport
+/// coordinates into stage coordinates. Returns `None` for events that the
+/// input format can't express.
+pub fn player_event_to_automated(
+    event: &PlayerEvent,
+    mut to_stage: impl FnMut(f64, f64) -> (f64, f64),
+) -> Option<AutomatedEvent> {
+    let mut position = |x, y| {
+        let (x, y) = to_stage(x, y);
+        MousePosition(x, y)
+    };
+
+    Some(match event {
+        PlayerEvent::MouseMove { x, y } => AutomatedEvent::MouseMove {
+            pos: position(*x, *y),
+        },
+        PlayerEvent::MouseDown {
+            x,
+            y,
+            button,
+            index,
+        } => AutomatedEvent::MouseDown {
+            pos: position(*x, *y),
+            btn: mouse_button_to_automated(*button)?,
+            index: *index,
+            assert_handled: None,
+        },
+        PlayerEvent::MouseUp { x, y, button } => AutomatedEvent::MouseUp {
+            pos: position(*x, *y),
+            btn: mouse_button_to_automated(*button)?,
+        },
+        PlayerEvent::MouseWheel { delta } => match delta {
+            MouseWheelDelta::Lines(lines) => AutomatedEvent::MouseWheel {
+                lines: Some(*lines),
+                pixels: None,
+            },
+            MouseWheelDelta::Pixels(pixels) => AutomatedEvent::MouseWheel {
+                lines: None,
+                pixels: Some(*pixels),
+            },
+        },
+        PlayerEvent::KeyDown { key } => AutomatedEvent::KeyDown {
+            key: key_descriptor_to_automated(key),
+        },
+        PlayerEvent::KeyUp { key } => AutomatedEvent::KeyUp {
+            key: key_descriptor_to_automated(key),
+        },
+        PlayerEvent::TextInput { codepoint } => AutomatedEvent::TextInput {
+            codepoint: *codepoint,
+        },
+        PlayerEvent::TextControl { code } => AutomatedEvent::TextControl {
+            code: text_control_to_automated(*code),
+        },
+        PlayerEvent::Ime(ImeEvent::Preedit(text, cursor)) => AutomatedEvent::ImePreedit {
+            text: text.clone(),
+            cursor: *cursor,
+        },
+        PlayerEvent::Ime(ImeEvent::Commit(text)) => {
+            AutomatedEvent::ImeCommit { text: text.clone() }
+        }
+        PlayerEvent::FocusGained => AutomatedEvent::FocusGained,
+        PlayerEvent::FocusLost => AutomatedEvent::FocusLost,
+        // The input format has no way to express these.
+        PlayerEvent::MouseLeave
+        | PlayerEvent::GamepadButtonDown { .. }
+        | PlayerEvent::GamepadButtonUp { .. } => return None,
+    })
+}
+
+/// Convert an event of the input format into an event for the player.
+///
+/// Mouse positions are passed through `to_viewport`, which should turn stage
+/// coordinates into viewport coordinates. Returns `None` for events that
+/// aren't given to the player, such as `Wait` and `SetClipboardText`, and for
+/// mouse wheel events that give both or neither of `lines` and `pixels`.
+pub fn automated_event_to_player(
+    event: &AutomatedEvent,
+    mut to_viewport: impl FnMut(f64, f64) -> (f64, f64),
+) -> Option<PlayerEvent> {
+    let mut position = |pos: &MousePosition| to_viewport(pos.0, pos.1);
+
+    Some(match event {
+        AutomatedEvent::MouseMove { pos } => {
+            let (x, y) = position(pos);
+            PlayerEvent::MouseMove { x, y }
+        }
+        AutomatedEvent::MouseDown {
+            pos, btn, index, ..
+        } => {
+            let (x, y) = position(pos);
+            PlayerEvent::MouseDown {
+                x,
+                y,
+                button: automated_to_mouse_button(*btn),
+                index: *index,
+            }
+        }
+        AutomatedEvent::MouseUp { pos, btn } => {
+            let (x, y) = position(pos);
+            PlayerEvent::MouseUp {
+                x,
+                y,
+                button: automated_to_mouse_button(*btn),
+            }
+        }
+        AutomatedEvent::MouseWheel { lines, pixels } => PlayerEvent::MouseWheel {
+            delta: match (lines, pixels) {
+                (Some(lines), None) => MouseWheelDelta::Lines(*lines),
+                (None, Some(pixels)) => MouseWheelDelta::Pixels(*pixels),
+                _ => return None,
+            },
+        },
+        AutomatedEvent::KeyDown { key } => PlayerEvent::KeyDown {
+            key: automated_key_to_descriptor(*key),
+        },
+        AutomatedEvent::KeyUp { key } => PlayerEvent::KeyUp {
+            key: automated_key_to_descriptor(*key),
+        },
+        AutomatedEvent::TextInput { codepoint } => PlayerEvent::TextInput {
+            codepoint: *codepoint,
+        },
+        AutomatedEvent::TextControl { code } => PlayerEvent::TextControl {
+            code: automated_to_text_control(*code),
+        },
+        AutomatedEvent::FocusGained => PlayerEvent::FocusGained,
+        AutomatedEvent::FocusLost => PlayerEvent::FocusLost,
+        AutomatedEvent::ImePreedit { text, cursor } => {
+            PlayerEvent::Ime(ImeEvent::Preedit(text.clone(), *cursor))
+        }
+        AutomatedEvent::ImeCommit { text } => PlayerEvent::Ime(ImeEvent::Commit(text.clone())),
+        AutomatedEvent::Wait | AutomatedEvent::SetClipboardText { .. } => return None,
+    })
+}
+
+fn mouse_button_to_automated(button: MouseButton) -> Option<InputMouseButton> {
+    match button {
+        MouseButton::Left => Some(InputMouseButton::Left),
+        MouseButton::Middle => Some(InputMouseButton::Middle),
+        MouseButton::Right => Some(InputMouseButton::Right),
+        MouseButton::Unknown => None,
+    }
+}
+
+fn automated_to_mouse_button(button: InputMouseButton) -> MouseButton {
+    match button {
+        InputMouseButton::Left => MouseButton::Left,
+        InputMouseButton::Middle => MouseButton::Middle,
+        InputMouseButton::Right => MouseButton::Right,
+    }
+}
+
+/// The key of the input format that is closest to `key`.
+pub fn key_descriptor_to_automated(key: &KeyDescriptor) -> AutomatedKey {
+    let numpad = key.key_location == KeyLocation::Numpad;
+    let right = key.key_location == KeyLocation::Right;
+    match key.logical_key {
+        LogicalKey::Character(' ') if !numpad => AutomatedKey::Space,
+        LogicalKey::Character(ch) if numpad => AutomatedKey::Numpad(ch),
+        LogicalKey::Character(ch) => AutomatedKey::Char(ch),
+        LogicalKey::Named(named) => match named {
+            NamedKey::ArrowDown if numpad => AutomatedKey::NumpadDown,
+            NamedKey::ArrowDown => AutomatedKey::ArrowDown,
+            NamedKey::ArrowLeft if numpad => AutomatedKey::NumpadLeft,
+            NamedKey::ArrowLeft => AutomatedKey::ArrowLeft,
+            NamedKey::ArrowRight if numpad => AutomatedKey::NumpadRight,
+            NamedKey::ArrowRight => AutomatedKey::ArrowRight,
+            NamedKey::ArrowUp if numpad => AutomatedKey::NumpadUp,
+            NamedKey::ArrowUp => AutomatedKey::ArrowUp,
+            NamedKey::Backspace => AutomatedKey::Backspace,
+            NamedKey::CapsLock => AutomatedKey::CapsLock,
+            NamedKey::Delete if numpad => AutomatedKey::NumpadDelete,
+            NamedKey::Delete => AutomatedKey::Delete,
+            NamedKey::End if numpad => AutomatedKey::NumpadEnd,
+            NamedKey::End => AutomatedKey::End,
+            NamedKey::Enter => AutomatedKey::Enter,
+            NamedKey::Escape => AutomatedKey::Escape,
+            NamedKey::F1 => AutomatedKey::F1,
+            NamedKey::F2 => AutomatedKey::F2,
+            NamedKey::F3 => AutomatedKey::F3,
+            NamedKey::F4 => AutomatedKey::F4,
+            NamedKey::F5 => AutomatedKey::F5,
+            NamedKey::F6 => AutomatedKey::F6,
+            NamedKey::F7 => AutomatedKey::F7,
+            NamedKey::F8 => AutomatedKey::F8,
+            NamedKey::F9 => AutomatedKey::F9,
+            NamedKey::Home if numpad => AutomatedKey::NumpadHome,
+            NamedKey::Home => AutomatedKey::Home,
+            NamedKey::Insert if numpad => AutomatedKey::NumpadInsert,
+            NamedKey::Insert => AutomatedKey::Insert,
+            // The input format has no right alt key.
+            NamedKey::Alt => AutomatedKey::LeftAlt,
+            NamedKey::Control if right => AutomatedKey::RightControl,
+            NamedKey::Control => AutomatedKey::LeftControl,
+            NamedKey::Shift if right => AutomatedKey::RightShift,
+            NamedKey::Shift => AutomatedKey::LeftShift,
+            NamedKey::NumLock => AutomatedKey::NumLock,
+            NamedKey::PageDown if numpad => AutomatedKey::NumpadPageDown,
+            NamedKey::PageDown => AutomatedKey::PageDown,
+            NamedKey::PageUp if numpad => AutomatedKey::NumpadPageUp,
+            NamedKey::PageUp => AutomatedKey::PageUp,
+            NamedKey::Pause => AutomatedKey::Pause,
+            NamedKey::ScrollLock => AutomatedKey::ScrollLock,
+            NamedKey::Tab => AutomatedKey::Tab,
+            _ => AutomatedKey::Unknown,
+        },
+        LogicalKey::Unknown => AutomatedKey::Unknown,
+    }
+}
+
+/// The key that the player sees for a key of the input format.
+pub fn automated_key_to_descriptor(automated_key: AutomatedKey) -> KeyDescriptor {
+    let logical_key = match automated_key {
+        AutomatedKey::Char(ch) | AutomatedKey::Numpad(ch) => LogicalKey::Character(ch),
+        AutomatedKey::ArrowDown | AutomatedKey::NumpadDown => {
+            LogicalKey::Named(NamedKey::ArrowDown)
+        }
+        AutomatedKey::ArrowLeft | AutomatedKey::NumpadLeft => {
+            LogicalKey::Named(NamedKey::ArrowLeft)
+        }
+        AutomatedKey::ArrowRight | AutomatedKey::NumpadRight => {
+            LogicalKey::Named(NamedKey::ArrowRight)
+        }
+        AutomatedKey::ArrowUp | AutomatedKey::NumpadUp => LogicalKey::Named(NamedKey::ArrowUp),
+        AutomatedKey::Backspace => LogicalKey::Named(NamedKey::Backspace),
+        AutomatedKey::CapsLock => LogicalKey::Named(NamedKey::CapsLock),
+        AutomatedKey::Delete | AutomatedKey::NumpadDelete => LogicalKey::Named(NamedKey::Delete),
+        AutomatedKey::End | AutomatedKey::NumpadEnd => LogicalKey::Named(NamedKey::End),
+        AutomatedKey::Enter => LogicalKey::Named(NamedKey::Enter),
+        AutomatedKey::Escape => LogicalKey::Named(NamedKey::Escape),
+        AutomatedKey::F1 => LogicalKey::Named(NamedKey::F1),
+        AutomatedKey::F2 => LogicalKey::Named(NamedKey::F2),
+        AutomatedKey::F3 => LogicalKey::Named(NamedKey::F3),
+        AutomatedKey::F4 => LogicalKey::Named(NamedKey::F4),
+        AutomatedKey::F5 => LogicalKey::Named(NamedKey::F5),
+        AutomatedKey::F6 => LogicalKey::Named(NamedKey::F6),
+        AutomatedKey::F7 => LogicalKey::Named(NamedKey::F7),
+        AutomatedKey::F8 => LogicalKey::Named(NamedKey::F8),
+        AutomatedKey::F9 => LogicalKey::Named(NamedKey::F9),
+        AutomatedKey::Home | AutomatedKey::NumpadHome => LogicalKey::Named(NamedKey::Home),
+        AutomatedKey::Insert | AutomatedKey::NumpadInsert => LogicalKey::Named(NamedKey::Insert),
+        AutomatedKey::LeftAlt => LogicalKey::Named(NamedKey::Alt),
+        AutomatedKey::LeftControl | AutomatedKey::RightControl => {
+            LogicalKey::Named(NamedKey::Control)
+        }
+        AutomatedKey::LeftShift | AutomatedKey::RightShift => LogicalKey::Named(NamedKey::Shift),
+        AutomatedKey::NumLock => LogicalKey::Named(NamedKey::NumLock),
+        AutomatedKey::PageDown | AutomatedKey::NumpadPageDown => {
+            LogicalKey::Named(NamedKey::PageDown)
+        }
+        AutomatedKey::PageUp | AutomatedKey::NumpadPageUp => LogicalKey::Named(NamedKey::PageUp),
+        AutomatedKey::Pause => LogicalKey::Named(NamedKey::Pause),
+        AutomatedKey::ScrollLock => LogicalKey::Named(NamedKey::ScrollLock),
+        AutomatedKey::Space => LogicalKey::Character(' '),
+        AutomatedKey::Tab => LogicalKey::Named(NamedKey::Tab),
+        AutomatedKey::Unknown => LogicalKey::Unknown,
+    };
+    let key_location = match automated_key {
+        AutomatedKey::Numpad(_)
+        | AutomatedKey::NumLock
+        | AutomatedKey::NumpadDelete
+        | AutomatedKey::NumpadDown
+        | AutomatedKey::NumpadEnd
+        | AutomatedKey::NumpadHome
+        | AutomatedKey::NumpadInsert
+        | AutomatedKey::NumpadLeft
+        | AutomatedKey::NumpadPageDown
+        | AutomatedKey::NumpadPageUp
+        | AutomatedKey::NumpadRight
+        | AutomatedKey::NumpadUp => KeyLocation::Numpad,
+        AutomatedKey::LeftAlt | AutomatedKey::LeftControl | AutomatedKey::LeftShift => {
+            KeyLocation::Left
+        }
+        AutomatedKey::RightControl | AutomatedKey::RightShift => KeyLocation::Right,
+        _ => KeyLocation::Standard,
+    };
+    KeyDescriptor {
+        // The input format only keeps logical keys.
+        physical_key: PhysicalKey::Unknown,
+        logical_key,
+        key_location,
+    }
+}
+
+fn text_control_to_automated(code: TextControlCode) -> InputTextControlCode {
+    match code {
+        TextControlCode::MoveLeft => InputTextControlCode::MoveLeft,
+        TextControlCode::MoveLeftWord => InputTextControlCode::MoveLeftWord,
+        TextControlCode::MoveLeftLine => InputTextControlCode::MoveLeftLine,
+        TextControlCode::MoveLeftDocument => InputTextControlCode::MoveLeftDocument,
+        TextControlCode::MoveRight => InputTextControlCode::MoveRight,
+        TextControlCode::MoveRightWord => InputTextControlCode::MoveRightWord,
+        TextControlCode::MoveRightLine => InputTextControlCode::MoveRightLine,
+        TextControlCode::MoveRightDocument => InputTextControlCode::MoveRightDocument,
+        TextControlCode::SelectLeft => InputTextControlCode::SelectLeft,
+        TextControlCode::SelectLeftWord => InputTextControlCode::SelectLeftWord,
+        TextControlCode::SelectLeftLine => InputTextControlCode::SelectLeftLine,
+        TextControlCode::SelectLeftDocument => InputTextControlCode::SelectLeftDocument,
+        TextControlCode::SelectRight => InputTextControlCode::SelectRight,
+        TextControlCode::SelectRightWord => InputTextControlCode::SelectRightWord,
+        TextControlCode::SelectRightLine => InputTextControlCode::SelectRightLine,
+        TextControlCode::SelectRightDocument => InputTextControlCode::SelectRightDocument,
+        TextControlCode::SelectAll => InputTextControlCode::SelectAll,
+        TextControlCode::Copy => InputTextControlCode::Copy,
+        TextControlCode::Paste => InputTextControlCode::Paste,
+        TextControlCode::Cut => InputTextControlCode::Cut,
+        TextControlCode::Backspace => InputTextControlCode::Backspace,
+        TextControlCode::BackspaceWord => InputTextControlCode::BackspaceWord,
+        TextControlCode::Enter => InputTextControlCode::Enter,
+        TextControlCode::Delete => InputTextControlCode::Delete,
+        TextControlCode::DeleteWord => InputTextControlCode::DeleteWord,
+    }
+}
+
+fn automated_to_text_control(code: InputTextControlCode) -> TextControlCode {
+    match code {
+        InputTextControlCode::MoveLeft => TextControlCode::MoveLeft,
+        InputTextControlCode::MoveLeftWord => TextControlCode::MoveLeftWord,
+        InputTextControlCode::MoveLeftLine => TextControlCode::MoveLeftLine,
+        InputTextControlCode::MoveLeftDocument => TextControlCode::MoveLeftDocument,
+        InputTextControlCode::MoveRight => TextControlCode::MoveRight,
+        InputTextControlCode::MoveRightWord => TextControlCode::MoveRightWord,
+        InputTextControlCode::MoveRightLine => TextControlCode::MoveRightLine,
+        InputTextControlCode::MoveRightDocument => TextControlCode::MoveRightDocument,
+        InputTextControlCode::SelectLeft => TextControlCode::SelectLeft,
+        InputTextControlCode::SelectLeftWord => TextControlCode::SelectLeftWord,
+        InputTextControlCode::SelectLeftLine => TextControlCode::SelectLeftLine,
+        InputTextControlCode::SelectLeftDocument => TextControlCode::SelectLeftDocument,
+        InputTextControlCode::SelectRight => TextControlCode::SelectRight,
+        InputTextControlCode::SelectRightWord => TextControlCode::SelectRightWord,
+        InputTextControlCode::SelectRightLine => TextControlCode::SelectRightLine,
+        InputTextControlCode::SelectRightDocument => TextControlCode::SelectRightDocument,
+        InputTextControlCode::SelectAll => TextControlCode::SelectAll,
+        InputTextControlCode::Copy => TextControlCode::Copy,
+        InputTextControlCode::Paste => TextControlCode::Paste,
+        InputTextControlCode::Cut => TextControlCode::Cut,
+        InputTextControlCode::Backspace => TextControlCode::Backspace,
+        InputTextControlCode::BackspaceWord => TextControlCode::BackspaceWord,
+        InputTextControlCode::Enter => TextControlCode::Enter,
+        InputTextControlCode::Delete => TextControlCode::Delete,
+        InputTextControlCode::DeleteWord => TextControlCode::DeleteWord,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_input_format::InputInjector;
+
+    #[test]
+    fn keys_round_trip() {
+        let keys = [
+            AutomatedKey::Char('a'),
+            AutomatedKey::Numpad('5'),
+            AutomatedKey::Space,
+            AutomatedKey::ArrowLeft,
+            AutomatedKey::NumpadLeft,
+            AutomatedKey::NumLock,
+            AutomatedKey::LeftControl,
+            AutomatedKey::RightControl,
+            AutomatedKey::RightShift,
+            AutomatedKey::Tab,
+        ];
+        for key in keys {
+            let round_tripped = key_descriptor_to_automated(&automated_key_to_descriptor(key));
+            assert_eq!(format!("{key:?}"), format!("{round_tripped:?}"));
+        }
+    }
+
+    #[test]
+    fn text_controls_round_trip() {
+        for code in [
+            TextControlCode::SelectAll,
+            TextControlCode::BackspaceWord,
+            TextControlCode::DeleteWord,
+        ] {
+            assert_eq!(
+                automated_to_text_control(text_control_to_automated(code)),
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn events_round_trip_through_a_recording() {
+        let key = automated_key_to_descriptor;
+        let frames = vec![
+            vec![
+                PlayerEvent::MouseMove { x: 20.0, y: 10.0 },
+                PlayerEvent::MouseDown {
+                    x: 20.0,
+                    y: 10.0,
+                    button: MouseButton::Left,
+                    index: Some(1),
+                },
+            ],
+            vec![],
+            vec![
+                PlayerEvent::MouseUp {
+                    x: 22.0,
+                    y: 12.0,
+                    button: MouseButton::Left,
+                },
+                PlayerEvent::MouseWheel {
+                    delta: MouseWheelDelta::Lines(-3.0),
+                },
+                PlayerEvent::KeyDown {
+                    key: key(AutomatedKey::RightShift),
+                },
+                PlayerEvent::TextInput { codepoint: 'A' },
+                PlayerEvent::KeyUp {
+                    key: key(AutomatedKey::RightShift),
+                },
+            ],
+            vec![
+                PlayerEvent::TextControl {
+                    code: TextControlCode::SelectAll,
+                },
+                PlayerEvent::Ime(ImeEvent::Preedit("ka".to_string(), Some((0, 2)))),
+                PlayerEvent::Ime(ImeEvent::Commit("か".to_string())),
+                PlayerEvent::FocusLost,
+            ],
+        ];
+
+        // The viewport is twice the size of the stage.
+        let mut recording = vec![];
+        for (index, events) in frames.iter().enumerate() {
+            if index > 0 {
+                recording.push(AutomatedEvent::Wait);
+            }
+            recording.extend(
+                events.iter().filter_map(|event| {
+                    player_event_to_automated(event, |x, y| (x / 2.0, y / 2.0))
+                }),
+            );
+        }
+        let json = serde_json::to_string(&recording).unwrap();
+
+        let mut injector = InputInjector::from_reader(json.as_bytes()).unwrap();
+        for events in &frames {
+            let mut replayed = vec![];
+            injector.next(|event, _buttons| {
+                replayed.extend(automated_event_to_player(event, |x, y| (x * 2.0, y * 2.0)));
+            });
+            assert_eq!(format!("{replayed:?}"), format!("{events:?}"));
+        }
+    }
+}
@@ -1,7 +1,10 @@
 pub mod bookmarks;
 pub mod bundle;
+pub mod compatibility_rules;
+pub mod input_format;
 pub mod parse;
 pub mod recents;
+pub mod shared_objects;
 pub mod write;
 
 pub mod backends;
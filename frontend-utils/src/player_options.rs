@@ -1,5 +1,8 @@
 mod read;
+mod write;
 pub use read::read_player_options;
+pub use write::create_player_options_table;
+pub(crate) use write::runtime_name;
 
 use ruffle_core::config::Letterbox;
 use ruffle_core::{LoadBehavior, PlayerRuntime, StageAlign, StageScaleMode};
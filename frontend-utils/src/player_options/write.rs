@@ -0,0 +1,188 @@
+use crate::player_options::PlayerOptions;
+use ruffle_core::config::Letterbox;
+use ruffle_core::{LoadBehavior, PlayerRuntime, StageAlign, StageScaleMode};
+use ruffle_render::quality::StageQuality;
+use toml_edit::{value, InlineTable, Table};
+
+/// Creates a table describing `options`, in the format read by [`crate::player_options::read_player_options`].
+///
+/// Options that are not set are left out of the table.
+pub fn create_player_options_table(options: &PlayerOptions) -> Table {
+    let mut table = Table::new();
+
+    if !options.parameters.is_empty() {
+        let mut parameters = InlineTable::new();
+        for (key, parameter) in &options.parameters {
+            parameters.insert(key.as_str(), parameter.as_str().into());
+        }
+        table["parameters"] = value(parameters);
+    }
+
+    if let Some(max_execution_duration) = options.max_execution_duration {
+        table["script_timeout"] = value(max_execution_duration.as_secs_f64());
+    }
+
+    if let Some(base) = &options.base {
+        table["base_url"] = value(base.as_str());
+    }
+
+    if let Some(quality) = options.quality {
+        table["quality"] = value(quality_name(quality));
+    }
+
+    if let Some(align) = options.align.and_then(align_name) {
+        table["align"] = value(align);
+    }
+
+    if let Some(force_align) = options.force_align {
+        table["force_align"] = value(force_align);
+    }
+
+    if let Some(scale) = options.scale {
+        table["scale_mode"] = value(scale_mode_name(scale));
+    }
+
+    if let Some(force_scale) = options.force_scale {
+        table["force_scale_mode"] = value(force_scale);
+    }
+
+    if let Some(upgrade_to_https) = options.upgrade_to_https {
+        table["upgrade_http_to_https"] = value(upgrade_to_https);
+    }
+
+    if let Some(load_behavior) = options.load_behavior {
+        table["load_behavior"] = value(load_behavior_name(load_behavior));
+    }
+
+    if let Some(letterbox) = options.letterbox {
+        table["letterbox"] = value(letterbox_name(letterbox));
+    }
+
+    if let Some(spoof_url) = &options.spoof_url {
+        table["spoof_url"] = value(spoof_url.as_str());
+    }
+
+    if let Some(player_version) = options.player_version {
+        table["version"] = value(i64::from(player_version));
+    }
+
+    if let Some(player_runtime) = options.player_runtime {
+        table["runtime"] = value(runtime_name(player_runtime));
+    }
+
+    if let Some(frame_rate) = options.frame_rate {
+        table["frame_rate"] = value(frame_rate);
+    }
+
+    if let Some(dummy_external_interface) = options.dummy_external_interface {
+        table["mock_external_interface"] = value(dummy_external_interface);
+    }
+
+    table
+}
+
+fn quality_name(quality: StageQuality) -> &'static str {
+    // Only these qualities can be read back, the higher ones are all treated as high.
+    match quality {
+        StageQuality::Low => "low",
+        StageQuality::Medium => "medium",
+        _ => "high",
+    }
+}
+
+fn align_name(align: StageAlign) -> Option<&'static str> {
+    [
+        ("bottom", StageAlign::BOTTOM),
+        ("bottom_left", StageAlign::BOTTOM | StageAlign::LEFT),
+        ("bottom_right", StageAlign::BOTTOM | StageAlign::RIGHT),
+        ("left", StageAlign::LEFT),
+        ("right", StageAlign::RIGHT),
+        ("top", StageAlign::TOP),
+        ("top_left", StageAlign::TOP | StageAlign::LEFT),
+        ("top_right", StageAlign::TOP | StageAlign::RIGHT),
+        ("center", StageAlign::empty()),
+    ]
+    .into_iter()
+    .find(|(_, value)| *value == align)
+    .map(|(name, _)| name)
+}
+
+fn scale_mode_name(scale_mode: StageScaleMode) -> &'static str {
+    match scale_mode {
+        StageScaleMode::ExactFit => "exact_fit",
+        StageScaleMode::NoBorder => "no_border",
+        StageScaleMode::NoScale => "no_scale",
+        StageScaleMode::ShowAll => "show_all",
+    }
+}
+
+fn load_behavior_name(load_behavior: LoadBehavior) -> &'static str {
+    match load_behavior {
+        LoadBehavior::Streaming => "streaming",
+        LoadBehavior::Delayed => "delayed",
+        LoadBehavior::Blocking => "blocking",
+    }
+}
+
+fn letterbox_name(letterbox: Letterbox) -> &'static str {
+    match letterbox {
+        Letterbox::Off => "off",
+        Letterbox::Fullscreen => "fullscreen",
+        Letterbox::On => "on",
+    }
+}
+
+pub fn runtime_name(runtime: PlayerRuntime) -> &'static str {
+    match runtime {
+        PlayerRuntime::FlashPlayer => "flash_player",
+        PlayerRuntime::AIR => "air",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::ParseContext;
+    use crate::player_options::read_player_options;
+    use std::time::Duration;
+    use toml_edit::DocumentMut;
+    use url::Url;
+
+    #[test]
+    fn roundtrip() {
+        let options = PlayerOptions {
+            parameters: vec![("key".to_string(), "value".to_string())],
+            max_execution_duration: Some(Duration::from_secs_f64(1.5)),
+            base: Some(Url::parse("https://example.com/").unwrap()),
+            quality: Some(StageQuality::Low),
+            align: Some(StageAlign::TOP | StageAlign::LEFT),
+            force_align: Some(true),
+            scale: Some(StageScaleMode::NoBorder),
+            force_scale: Some(false),
+            upgrade_to_https: Some(true),
+            load_behavior: Some(LoadBehavior::Blocking),
+            letterbox: Some(Letterbox::Fullscreen),
+            spoof_url: Some(Url::parse("https://example.com/game.swf").unwrap()),
+            referer: None,
+            cookie: None,
+            player_version: Some(10),
+            player_runtime: Some(PlayerRuntime::AIR),
+            frame_rate: Some(60.0),
+            dummy_external_interface: Some(true),
+        };
+
+        let mut document = DocumentMut::new();
+        document.insert("player", create_player_options_table(&options).into());
+        let document = document
+            .to_string()
+            .parse::<DocumentMut>()
+            .expect("Written options should be valid TOML");
+
+        let mut cx = ParseContext::default();
+        let result = document.get_table_like(&mut cx, "player", |cx, table| {
+            read_player_options(cx, table)
+        });
+        assert_eq!(Some(options), result);
+        assert!(cx.warnings.is_empty());
+    }
+}
@@ -0,0 +1,394 @@
+//! Management of the SharedObjects stored by [`DiskStorageBackend`](crate::backends::storage::DiskStorageBackend).
+//!
+//! SharedObjects are stored as regular Flash Player `.sol` files, laid out as
+//! `<domain>/<path to movie>/<name>.sol`, which is also how Flash Player lays them out
+//! inside of its `#SharedObjects/<random>/` directory.
+
+use flash_lso::read::Reader;
+use flash_lso::types::Lso;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// The directory in which Flash Player stores SharedObjects, inside of its data directory.
+pub const FLASH_PLAYER_SHARED_OBJECTS_DIRECTORY: &str = "#SharedObjects";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SharedObjectError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid SharedObject name")]
+    InvalidName,
+
+    #[error("Not a valid .sol file")]
+    InvalidFile,
+
+    #[error("Couldn't serialize SharedObject")]
+    Serialization,
+}
+
+/// A SharedObject stored on disk.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StoredSharedObject {
+    /// The name used by the storage backend, such as `example.com/game.swf/save`.
+    pub name: String,
+
+    /// The size of the `.sol` file, in bytes.
+    pub size: u64,
+}
+
+impl StoredSharedObject {
+    /// The domain of the movies that can access this SharedObject, such as `example.com`.
+    ///
+    /// Local movies use `localhost`.
+    pub fn domain(&self) -> &str {
+        self.name
+            .split_once('/')
+            .map_or(&self.name, |(domain, _)| domain)
+    }
+
+    /// The path of this SharedObject inside of its domain, such as `game.swf/save`.
+    pub fn path(&self) -> &str {
+        self.name.split_once('/').map_or("", |(_, path)| path)
+    }
+}
+
+/// Lists, edits, imports and exports the SharedObjects in a directory.
+pub struct SharedObjectManager {
+    shared_objects_path: PathBuf,
+}
+
+impl SharedObjectManager {
+    pub fn new(shared_objects_path: PathBuf) -> Self {
+        Self {
+            shared_objects_path,
+        }
+    }
+
+    /// Lists every stored SharedObject, sorted by name.
+    pub fn list(&self) -> Vec<StoredSharedObject> {
+        let mut result = Vec::new();
+        collect_sol_files(&self.shared_objects_path, &mut |path, size| {
+            let relative_path = path.strip_prefix(&self.shared_objects_path).ok();
+            if let Some(name) = relative_path.and_then(name_from_relative_path) {
+                result.push(StoredSharedObject { name, size });
+            }
+        });
+        result.sort();
+        result
+    }
+
+    /// Lists every stored SharedObject, grouped by domain.
+    pub fn list_by_domain(&self) -> BTreeMap<String, Vec<StoredSharedObject>> {
+        let mut result: BTreeMap<String, Vec<StoredSharedObject>> = BTreeMap::new();
+        for shared_object in self.list() {
+            result
+                .entry(shared_object.domain().to_owned())
+                .or_default()
+                .push(shared_object);
+        }
+        result
+    }
+
+    /// Reads and decodes a stored SharedObject.
+    pub fn read(&self, name: &str) -> Result<Lso, SharedObjectError> {
+        let data = fs::read(self.path_of(name)?)?;
+        parse(&data)
+    }
+
+    /// Encodes and stores a SharedObject, replacing any existing one.
+    pub fn write(&self, name: &str, lso: &mut Lso) -> Result<(), SharedObjectError> {
+        let data =
+            flash_lso::write::write_to_bytes(lso).map_err(|_| SharedObjectError::Serialization)?;
+        self.write_raw(name, &data)
+    }
+
+    /// Deletes a stored SharedObject, along with any directories left empty by doing so.
+    pub fn delete(&self, name: &str) -> Result<(), SharedObjectError> {
+        let path = self.path_of(name)?;
+        fs::remove_file(&path)?;
+
+        let mut directory = path.parent();
+        while let Some(dir) = directory {
+            if dir == self.shared_objects_path || fs::remove_dir(dir).is_err() {
+                break;
+            }
+            directory = dir.parent();
+        }
+        Ok(())
+    }
+
+    /// Copies a stored SharedObject to a `.sol` file which Flash Player can read.
+    pub fn export(&self, name: &str, destination: &Path) -> Result<(), SharedObjectError> {
+        let data = fs::read(self.path_of(name)?)?;
+        parse(&data)?;
+        fs::write(destination, data)?;
+        Ok(())
+    }
+
+    /// Stores the `.sol` file at `source` under the given name, replacing any existing one.
+    pub fn import(&self, source: &Path, name: &str) -> Result<(), SharedObjectError> {
+        let data = fs::read(source)?;
+        parse(&data)?;
+        self.write_raw(name, &data)
+    }
+
+    /// Imports every SharedObject from a Flash Player data directory,
+    /// such as `%APPDATA%\Macromedia\Flash Player` on Windows or `~/.macromedia/Flash_Player` on Linux.
+    ///
+    /// Files which aren't valid SharedObjects are skipped.
+    /// Returns the names of the imported SharedObjects.
+    pub fn import_flash_player_directory(
+        &self,
+        directory: &Path,
+    ) -> Result<Vec<String>, SharedObjectError> {
+        let shared_objects = directory.join(FLASH_PLAYER_SHARED_OBJECTS_DIRECTORY);
+        let root = if shared_objects.is_dir() {
+            shared_objects.as_path()
+        } else {
+            directory
+        };
+
+        let mut imported = Vec::new();
+        let mut sources = Vec::new();
+        collect_sol_files(root, &mut |path, _| sources.push(path.to_owned()));
+        for source in sources {
+            let Some(name) = flash_player_name(&source) else {
+                tracing::warn!(
+                    "Skipping {source:?}, as it's not inside of a Flash Player directory"
+                );
+                continue;
+            };
+            match self.import(&source, &name) {
+                Ok(()) => imported.push(name),
+                Err(SharedObjectError::Io(e)) => return Err(e.into()),
+                Err(e) => tracing::warn!("Skipping {source:?}: {e}"),
+            }
+        }
+        Ok(imported)
+    }
+
+    fn write_raw(&self, name: &str, data: &[u8]) -> Result<(), SharedObjectError> {
+        let path = self.path_of(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    fn path_of(&self, name: &str) -> Result<PathBuf, SharedObjectError> {
+        // The same rules as `SharedObject.getLocal`, which also keep us inside of our directory.
+        if name
+            .split('/')
+            .any(|segment| segment.is_empty() || segment.starts_with('.') || segment.contains('\\'))
+        {
+            return Err(SharedObjectError::InvalidName);
+        }
+        Ok(self.shared_objects_path.join(format!("{name}.sol")))
+    }
+}
+
+/// Returns the domain of the SharedObjects that a movie at `movie_url` can access.
+///
+/// This is the same domain `SharedObject.getLocal` uses, so while such a movie is running,
+/// it may overwrite any SharedObject of this domain at any time.
+pub fn shared_object_domain(movie_url: &Url) -> Option<&str> {
+    if movie_url.scheme() == "file" {
+        Some("localhost")
+    } else {
+        movie_url.host_str()
+    }
+}
+
+/// Returns the name of a SharedObject stored by Flash Player at `path`,
+/// which is inside of a `#SharedObjects/<random>/` directory.
+pub fn flash_player_name(path: &Path) -> Option<String> {
+    let mut components = path.components().map(|c| c.as_os_str());
+    components.find(|c| *c == FLASH_PLAYER_SHARED_OBJECTS_DIRECTORY)?;
+    // Skip the randomly named directory of this Flash Player installation.
+    components.next()?;
+    let relative: PathBuf = components.collect();
+    let name = name_from_relative_path(&relative)?;
+
+    // Flash Player keeps SharedObjects of local movies that can access the network separately.
+    match name.strip_prefix("#localWithNet/") {
+        Some(path) => Some(format!("localhost/{path}")),
+        None => Some(name),
+    }
+}
+
+fn parse(data: &[u8]) -> Result<Lso, SharedObjectError> {
+    Reader::default()
+        .parse(data)
+        .map_err(|_| SharedObjectError::InvalidFile)
+}
+
+fn name_from_relative_path(path: &Path) -> Option<String> {
+    if path.extension() != Some(OsStr::new("sol")) {
+        return None;
+    }
+    let path = path.with_extension("");
+    let segments = path
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(segments.join("/"))
+}
+
+fn collect_sol_files(directory: &Path, found: &mut impl FnMut(&Path, u64)) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if metadata.is_dir() {
+            collect_sol_files(&path, found);
+        } else if path.extension() == Some(OsStr::new("sol")) {
+            found(&path, metadata.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flash_lso::types::{AMFVersion, Element, Value};
+    use std::rc::Rc;
+    use tempfile::tempdir;
+
+    fn sol(name: &str) -> Vec<u8> {
+        let mut lso = Lso::new(
+            vec![Element::new("score", Rc::new(Value::Number(42.0)))],
+            name,
+            AMFVersion::AMF3,
+        );
+        flash_lso::write::write_to_bytes(&mut lso).unwrap()
+    }
+
+    #[test]
+    fn domains_of_movies() {
+        let domain = |url| shared_object_domain(&Url::parse(url).unwrap()).map(str::to_owned);
+        assert_eq!(
+            domain("https://example.com/games/game.swf?level=2").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            domain("file:///C:/games/game.swf").as_deref(),
+            Some("localhost")
+        );
+        assert_eq!(domain("data:,swf"), None);
+    }
+
+    #[test]
+    fn list_and_delete() {
+        let tmp_dir = tempdir().unwrap();
+        let manager = SharedObjectManager::new(tmp_dir.path().to_owned());
+        let root = tmp_dir.path();
+        fs::create_dir_all(root.join("example.com/game.swf")).unwrap();
+        fs::create_dir_all(root.join("localhost/games/other.swf")).unwrap();
+        fs::write(root.join("example.com/game.swf/save.sol"), sol("save")).unwrap();
+        fs::write(root.join("localhost/games/other.swf/data.sol"), sol("data")).unwrap();
+        fs::write(root.join("localhost/games/other.swf/notes.txt"), "").unwrap();
+
+        let domains = manager.list_by_domain();
+        assert_eq!(
+            domains.keys().collect::<Vec<_>>(),
+            vec!["example.com", "localhost"]
+        );
+        assert_eq!(domains["localhost"][0].path(), "games/other.swf/data");
+
+        manager.delete("example.com/game.swf/save").unwrap();
+        assert_eq!(
+            manager
+                .list()
+                .iter()
+                .map(|so| so.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["localhost/games/other.swf/data"]
+        );
+        assert!(!root.join("example.com").exists());
+    }
+
+    #[test]
+    fn read_write_round_trip() {
+        let tmp_dir = tempdir().unwrap();
+        let manager = SharedObjectManager::new(tmp_dir.path().to_owned());
+
+        let mut lso = flash_lso::read::Reader::default()
+            .parse(&sol("save"))
+            .unwrap();
+        manager
+            .write("example.com/game.swf/save", &mut lso)
+            .unwrap();
+        let read = manager.read("example.com/game.swf/save").unwrap();
+        assert_eq!(read.header.name, "save");
+        assert_eq!(read.body[0].name, "score");
+        assert_eq!(*read.body[0].value, Value::Number(42.0));
+    }
+
+    #[test]
+    fn invalid_names() {
+        let tmp_dir = tempdir().unwrap();
+        let manager = SharedObjectManager::new(tmp_dir.path().to_owned());
+        for name in ["../escape", "example.com//save", "example.com/.hidden", ""] {
+            assert!(matches!(
+                manager.read(name),
+                Err(SharedObjectError::InvalidName)
+            ));
+        }
+    }
+
+    #[test]
+    fn import_export() {
+        let tmp_dir = tempdir().unwrap();
+        let storage = tmp_dir.path().join("storage");
+        let manager = SharedObjectManager::new(storage.clone());
+
+        let not_a_sol = tmp_dir.path().join("not_a.sol");
+        fs::write(&not_a_sol, "hello").unwrap();
+        assert!(matches!(
+            manager.import(&not_a_sol, "example.com/game.swf/save"),
+            Err(SharedObjectError::InvalidFile)
+        ));
+
+        let flash_player = tmp_dir.path().join("Flash Player");
+        let installation = flash_player.join("#SharedObjects/ABCD1234");
+        fs::create_dir_all(installation.join("example.com/game.swf")).unwrap();
+        fs::create_dir_all(installation.join("#localWithNet/games/game.swf")).unwrap();
+        fs::write(
+            installation.join("example.com/game.swf/save.sol"),
+            sol("save"),
+        )
+        .unwrap();
+        fs::write(
+            installation.join("#localWithNet/games/game.swf/save.sol"),
+            sol("save"),
+        )
+        .unwrap();
+
+        let mut imported = manager
+            .import_flash_player_directory(&flash_player)
+            .unwrap();
+        imported.sort();
+        assert_eq!(
+            imported,
+            vec![
+                "example.com/game.swf/save".to_string(),
+                "localhost/games/game.swf/save".to_string()
+            ]
+        );
+
+        let exported = tmp_dir.path().join("exported.sol");
+        manager
+            .export("example.com/game.swf/save", &exported)
+            .unwrap();
+        assert_eq!(fs::read(exported).unwrap(), sol("save"));
+    }
+}
@@ -12,7 +12,9 @@ use ruffle_render::bitmap::{
 };
 use ruffle_render::commands::{CommandHandler, CommandList, RenderBlendMode};
 use ruffle_render::error::Error;
+use ruffle_render::filters::{cpu, Filter};
 use ruffle_render::matrix::Matrix;
+use ruffle_render::pixmap::{Pixmap, PixmapSyncHandle};
 use ruffle_render::quality::StageQuality;
 use ruffle_render::shape_utils::{DistilledShape, DrawCommand, LineScaleMode, LineScales};
 use ruffle_render::transform::Transform;
@@ -211,6 +213,26 @@ impl BitmapData {
             .into_js_result()?;
         Ok(())
     }
+
+    /// Reads the pixels back from the <canvas>, in the same format that they were put in.
+    fn pixmap(&self) -> Result<Pixmap, JsValue> {
+        let (width, height) = (self.canvas.width(), self.canvas.height());
+        let image_data = self
+            .context
+            .get_image_data(0.0, 0.0, width.into(), height.into())
+            .into_js_result()?;
+        Ok(Pixmap::from_rgba(width, height, image_data.data().0))
+    }
+
+    fn put_pixmap(&self, pixmap: &Pixmap) -> Result<(), JsValue> {
+        let image_data =
+            ImageData::new_with_u8_clamped_array(Clamped(pixmap.data()), pixmap.width())
+                .into_js_result()?;
+        self.context
+            .put_image_data(&image_data, 0.0, 0.0)
+            .into_js_result()?;
+        Ok(())
+    }
 }
 
 impl WebCanvasRenderBackend {
@@ -518,6 +540,52 @@ impl RenderBackend for WebCanvasRenderBackend {
         None
     }
 
+    fn apply_filter(
+        &mut self,
+        source: BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: BitmapHandle,
+        dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        // The canvas API can't do any of these filters, so they're all done on the CPU.
+        let source = match as_bitmap_data(&source).pixmap() {
+            Ok(pixmap) => {
+                pixmap.region(source_point.0, source_point.1, source_size.0, source_size.1)
+            }
+            Err(e) => {
+                log::error!("Couldn't read filter source: {:?}", e);
+                return None;
+            }
+        };
+        let filtered = cpu::apply_filter(&source, &filter, |handle| {
+            as_bitmap_data(handle).pixmap().ok()
+        });
+
+        let destination = as_bitmap_data(&destination);
+        let mut pixmap = match destination.pixmap() {
+            Ok(pixmap) => pixmap,
+            Err(e) => {
+                log::error!("Couldn't read filter destination: {:?}", e);
+                return None;
+            }
+        };
+        pixmap.copy_from(&filtered, (0, 0), dest_point, source_size);
+        if let Err(e) = destination.put_pixmap(&pixmap) {
+            log::error!("Couldn't write filter result: {:?}", e);
+            return None;
+        }
+        Some(PixmapSyncHandle::new(
+            &pixmap,
+            PixelRegion::for_whole_size(pixmap.width(), pixmap.height()),
+        ))
+    }
+
+    fn is_filter_supported(&self, filter: &Filter) -> bool {
+        cpu::is_filter_supported(filter)
+    }
+
     fn submit_frame(
         &mut self,
         clear: Color,
@@ -585,10 +653,14 @@ impl RenderBackend for WebCanvasRenderBackend {
 
     fn resolve_sync_handle(
         &mut self,
-        _handle: Box<dyn SyncHandle>,
-        _with_rgba: RgbaBufRead,
+        handle: Box<dyn SyncHandle>,
+        with_rgba: RgbaBufRead,
     ) -> Result<(), Error> {
-        Err(Error::Unimplemented("Sync handle resolution".into()))
+        let handle = handle
+            .downcast::<PixmapSyncHandle>()
+            .map_err(|_| Error::Unimplemented("Foreign sync handle".into()))?;
+        handle.resolve(with_rgba);
+        Ok(())
     }
 
     fn create_empty_texture(&mut self, width: u32, height: u32) -> Result<BitmapHandle, Error> {
@@ -0,0 +1,285 @@
+use crate::commands::{CommandRenderer, LayerRef};
+use crate::mesh::Mesh;
+use crate::raster::Target;
+use ruffle_render::backend::{
+    BitmapCacheEntry, Context3D, Context3DProfile, PixelBenderOutput, PixelBenderTarget,
+    RenderBackend, ShapeHandle, ViewportDimensions,
+};
+use ruffle_render::bitmap::{
+    Bitmap, BitmapHandle, BitmapHandleImpl, BitmapSource, PixelRegion, RgbaBufRead, SyncHandle,
+};
+use ruffle_render::commands::CommandList;
+use ruffle_render::error::Error;
+use ruffle_render::filters::{cpu, Filter};
+use ruffle_render::pixel_bender::{
+    PixelBenderShader, PixelBenderShaderArgument, PixelBenderShaderHandle,
+};
+use ruffle_render::pixmap::{Pixmap, PixmapSyncHandle};
+use ruffle_render::quality::StageQuality;
+use ruffle_render::shape_utils::DistilledShape;
+use ruffle_render::tessellator::ShapeTessellator;
+use ruffle_render::utils::unmultiply_alpha_rgba;
+use std::borrow::Cow;
+use std::cell::{Ref, RefCell};
+use std::sync::Arc;
+use swf::Color;
+
+#[derive(Debug)]
+pub struct SoftwareBitmap {
+    pixmap: RefCell<Pixmap>,
+}
+
+impl BitmapHandleImpl for SoftwareBitmap {}
+
+impl SoftwareBitmap {
+    pub fn pixmap(&self) -> Ref<'_, Pixmap> {
+        self.pixmap.borrow()
+    }
+
+    fn replace(&self, pixmap: Pixmap) {
+        *self.pixmap.borrow_mut() = pixmap;
+    }
+}
+
+pub fn as_bitmap(handle: &BitmapHandle) -> &SoftwareBitmap {
+    <dyn BitmapHandleImpl>::downcast_ref(&*handle.0)
+        .expect("Bitmap handle must be a software bitmap")
+}
+
+fn read_bitmap(handle: &BitmapHandle) -> Option<Pixmap> {
+    Some(as_bitmap(handle).pixmap().clone())
+}
+
+/// A render backend which draws everything on the CPU.
+///
+/// It draws the same way as the wgpu backend, including its anti-aliasing, blend modes
+/// and filters, but doesn't need a GPU. Every frame is kept in memory and can be
+/// captured with [`SoftwareRenderBackend::capture_frame`].
+pub struct SoftwareRenderBackend {
+    viewport_dimensions: ViewportDimensions,
+    quality: StageQuality,
+    shape_tessellator: ShapeTessellator,
+    frame: Pixmap,
+}
+
+impl SoftwareRenderBackend {
+    pub fn new(viewport_dimensions: ViewportDimensions) -> Self {
+        Self {
+            viewport_dimensions,
+            quality: StageQuality::default(),
+            shape_tessellator: ShapeTessellator::new(),
+            frame: Pixmap::new(viewport_dimensions.width, viewport_dimensions.height),
+        }
+    }
+
+    /// Returns the last submitted frame, with straight alpha.
+    pub fn capture_frame(&self) -> image::RgbaImage {
+        let mut data = self.frame.data().to_vec();
+        unmultiply_alpha_rgba(&mut data);
+        image::RgbaImage::from_raw(self.frame.width(), self.frame.height(), data)
+            .expect("Pixmap data must be a valid RgbaImage")
+    }
+
+    /// Every pixel is drawn using `scale * scale` samples, which is at least
+    /// as many as the multisampling of the GPU backends uses at this quality.
+    fn scale(quality: StageQuality) -> u32 {
+        f64::from(quality.sample_count()).sqrt().ceil() as u32
+    }
+
+    fn render_cache_entry(&self, entry: BitmapCacheEntry) {
+        let bitmap = as_bitmap(&entry.handle);
+        let (width, height) = {
+            let pixmap = bitmap.pixmap();
+            (pixmap.width(), pixmap.height())
+        };
+
+        let mut target = Target::new(width, height, Self::scale(self.quality), entry.clear);
+        entry
+            .commands
+            .execute(&mut CommandRenderer::new(&mut target, LayerRef::None));
+        let mut pixmap = target.resolve();
+        for filter in &entry.filters {
+            pixmap = cpu::apply_filter(&pixmap, filter, read_bitmap);
+        }
+        bitmap.replace(pixmap);
+    }
+}
+
+impl RenderBackend for SoftwareRenderBackend {
+    fn viewport_dimensions(&self) -> ViewportDimensions {
+        self.viewport_dimensions
+    }
+
+    fn set_viewport_dimensions(&mut self, dimensions: ViewportDimensions) {
+        self.viewport_dimensions = dimensions;
+    }
+
+    fn register_shape(
+        &mut self,
+        shape: DistilledShape,
+        bitmap_source: &dyn BitmapSource,
+    ) -> ShapeHandle {
+        let mesh = self
+            .shape_tessellator
+            .tessellate_shape(shape, bitmap_source);
+        ShapeHandle(Arc::new(Mesh::new(self, bitmap_source, mesh)))
+    }
+
+    fn render_offscreen(
+        &mut self,
+        handle: BitmapHandle,
+        commands: CommandList,
+        quality: StageQuality,
+        bounds: PixelRegion,
+    ) -> Option<Box<dyn SyncHandle>> {
+        let bitmap = as_bitmap(&handle);
+        let mut target = Target::from_pixmap(&bitmap.pixmap(), Self::scale(quality));
+        commands.execute(&mut CommandRenderer::new(&mut target, LayerRef::Current));
+        let pixmap = target.resolve();
+        let sync_handle = PixmapSyncHandle::new(&pixmap, bounds);
+        bitmap.replace(pixmap);
+        Some(sync_handle)
+    }
+
+    fn apply_filter(
+        &mut self,
+        source: BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: BitmapHandle,
+        dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        let source = as_bitmap(&source).pixmap().region(
+            source_point.0,
+            source_point.1,
+            source_size.0,
+            source_size.1,
+        );
+        let filtered = cpu::apply_filter(&source, &filter, read_bitmap);
+
+        let destination = as_bitmap(&destination);
+        let mut pixmap = destination.pixmap().clone();
+        pixmap.copy_from(&filtered, (0, 0), dest_point, source_size);
+        let sync_handle = PixmapSyncHandle::new(
+            &pixmap,
+            PixelRegion::for_whole_size(pixmap.width(), pixmap.height()),
+        );
+        destination.replace(pixmap);
+        Some(sync_handle)
+    }
+
+    fn is_filter_supported(&self, filter: &Filter) -> bool {
+        cpu::is_filter_supported(filter)
+    }
+
+    fn is_offscreen_supported(&self) -> bool {
+        true
+    }
+
+    fn submit_frame(
+        &mut self,
+        clear: Color,
+        commands: CommandList,
+        cache_entries: Vec<BitmapCacheEntry>,
+    ) {
+        for entry in cache_entries {
+            self.render_cache_entry(entry);
+        }
+
+        let mut target = Target::new(
+            self.viewport_dimensions.width,
+            self.viewport_dimensions.height,
+            Self::scale(self.quality),
+            clear,
+        );
+        commands.execute(&mut CommandRenderer::new(&mut target, LayerRef::None));
+        self.frame = target.resolve();
+    }
+
+    fn create_empty_texture(&mut self, width: u32, height: u32) -> Result<BitmapHandle, Error> {
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidSize);
+        }
+        Ok(BitmapHandle(Arc::new(SoftwareBitmap {
+            pixmap: RefCell::new(Pixmap::new(width, height)),
+        })))
+    }
+
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error> {
+        Ok(BitmapHandle(Arc::new(SoftwareBitmap {
+            pixmap: RefCell::new(Pixmap::from_bitmap(bitmap)),
+        })))
+    }
+
+    fn update_texture(
+        &mut self,
+        handle: &BitmapHandle,
+        bitmap: Bitmap,
+        region: PixelRegion,
+    ) -> Result<(), Error> {
+        let source = Pixmap::from_bitmap(bitmap);
+        let texture = as_bitmap(handle);
+        let mut pixmap = texture.pixmap.borrow_mut();
+        pixmap.copy_from(
+            &source,
+            (region.x_min, region.y_min),
+            (region.x_min, region.y_min),
+            (region.width(), region.height()),
+        );
+        Ok(())
+    }
+
+    fn create_context3d(
+        &mut self,
+        _profile: Context3DProfile,
+    ) -> Result<Box<dyn Context3D>, Error> {
+        Err(Error::Unimplemented("createContext3D".into()))
+    }
+
+    fn context3d_present(&mut self, _context: &mut dyn Context3D) -> Result<(), Error> {
+        Err(Error::Unimplemented("Context3D.present".into()))
+    }
+
+    fn debug_info(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Renderer: Software")
+    }
+
+    fn name(&self) -> &'static str {
+        "software"
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.quality = quality;
+    }
+
+    fn compile_pixelbender_shader(
+        &mut self,
+        _shader: PixelBenderShader,
+    ) -> Result<PixelBenderShaderHandle, Error> {
+        Err(Error::Unimplemented(
+            "Pixel bender shader compilation".into(),
+        ))
+    }
+
+    fn run_pixelbender_shader(
+        &mut self,
+        _shader: PixelBenderShaderHandle,
+        _arguments: &[PixelBenderShaderArgument],
+        _target: &PixelBenderTarget,
+    ) -> Result<PixelBenderOutput, Error> {
+        Err(Error::Unimplemented("Pixel bender shader".into()))
+    }
+
+    fn resolve_sync_handle(
+        &mut self,
+        handle: Box<dyn SyncHandle>,
+        with_rgba: RgbaBufRead,
+    ) -> Result<(), Error> {
+        let handle = handle
+            .downcast::<PixmapSyncHandle>()
+            .map_err(|_| Error::Unimplemented("Foreign sync handle".into()))?;
+        handle.resolve(with_rgba);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::raster::over;
+use ruffle_render::pixmap::Rgba;
+use swf::BlendMode;
+
+/// Blends a premultiplied `src` onto a premultiplied `dst` with the given blend mode,
+/// matching the blend states and shaders of the wgpu backend.
+///
+/// Returns `None` if `dst` should be left untouched.
+pub fn blend(mode: BlendMode, src: Rgba, dst: Rgba) -> Option<Rgba> {
+    let alpha = src[3] + dst[3] * (1.0 - src[3]);
+    let trivial = |f: fn(f32, f32) -> f32| {
+        Some([
+            f(src[0], dst[0]),
+            f(src[1], dst[1]),
+            f(src[2], dst[2]),
+            alpha,
+        ])
+    };
+
+    match mode {
+        BlendMode::Normal | BlendMode::Layer => Some(over(src, dst)),
+        BlendMode::Add => trivial(|s, d| s + d),
+        BlendMode::Subtract => trivial(|s, d| d - s),
+        BlendMode::Screen => trivial(|s, d| s + d * (1.0 - s)),
+        BlendMode::Alpha => Some([
+            dst[0] * src[3],
+            dst[1] * src[3],
+            dst[2] * src[3],
+            dst[3] * src[3],
+        ]),
+        BlendMode::Erase => {
+            let inverse_alpha = 1.0 - src[3];
+            Some([
+                dst[0] * inverse_alpha,
+                dst[1] * inverse_alpha,
+                dst[2] * inverse_alpha,
+                dst[3] * inverse_alpha,
+            ])
+        }
+        BlendMode::Multiply => complex(src, dst, |s, d| s * d),
+        BlendMode::Lighten => complex(src, dst, f32::max),
+        BlendMode::Darken => complex(src, dst, f32::min),
+        BlendMode::Difference => complex(src, dst, |s, d| (d - s).abs()),
+        BlendMode::Invert => complex(src, dst, |_, d| 1.0 - d),
+        BlendMode::Overlay => complex(src, dst, |s, d| hard_light(d, s)),
+        BlendMode::HardLight => complex(src, dst, hard_light),
+    }
+}
+
+/// Blends two colors by applying `f` to their unmultiplied channels where they overlap,
+/// like `blend.wgsl` does.
+fn complex(src: Rgba, dst: Rgba, f: impl Fn(f32, f32) -> f32) -> Option<Rgba> {
+    let (sa, da) = (src[3], dst[3]);
+    if sa <= 0.0 {
+        return None;
+    }
+    if da <= 0.0 {
+        return Some(src);
+    }
+
+    let channel = |i: usize| {
+        src[i] * (1.0 - da) + dst[i] * (1.0 - sa) + sa * da * f(src[i] / sa, dst[i] / da)
+    };
+    Some([channel(0), channel(1), channel(2), sa + da * (1.0 - sa)])
+}
+
+fn hard_light(s: f32, d: f32) -> f32 {
+    if s <= 0.5 {
+        2.0 * s * d
+    } else {
+        1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HALF_RED: Rgba = [0.5, 0.0, 0.0, 0.5];
+    const GRAY: Rgba = [0.5, 0.5, 0.5, 1.0];
+
+    #[test]
+    fn trivial_blends() {
+        assert_eq!(
+            blend(BlendMode::Normal, HALF_RED, GRAY),
+            Some([0.75, 0.25, 0.25, 1.0])
+        );
+        assert_eq!(
+            blend(BlendMode::Add, HALF_RED, GRAY),
+            Some([1.0, 0.5, 0.5, 1.0])
+        );
+        assert_eq!(
+            blend(BlendMode::Subtract, HALF_RED, GRAY),
+            Some([0.0, 0.5, 0.5, 1.0])
+        );
+        assert_eq!(
+            blend(BlendMode::Erase, HALF_RED, GRAY),
+            Some([0.25, 0.25, 0.25, 0.5])
+        );
+    }
+
+    #[test]
+    fn complex_blends() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        assert_eq!(
+            blend(BlendMode::Multiply, red, GRAY),
+            Some([0.5, 0.0, 0.0, 1.0])
+        );
+        assert_eq!(
+            blend(BlendMode::Difference, red, GRAY),
+            Some([0.5, 0.5, 0.5, 1.0])
+        );
+        assert_eq!(blend(BlendMode::Multiply, [0.0; 4], GRAY), None);
+        assert_eq!(blend(BlendMode::Invert, red, [0.0; 4]), Some(red));
+    }
+}
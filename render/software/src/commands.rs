@@ -0,0 +1,306 @@
+use crate::backend::as_bitmap;
+use crate::blend::blend;
+use crate::mesh::{as_mesh, DrawType};
+use crate::raster::{Affine, Fragment, Mask, MaskState, Target};
+use ruffle_render::backend::ShapeHandle;
+use ruffle_render::bitmap::{BitmapHandle, PixelSnapping};
+use ruffle_render::commands::{CommandHandler, CommandList, RenderBlendMode};
+use ruffle_render::lines::{emulate_line, emulate_line_rect};
+use ruffle_render::matrix::Matrix;
+use ruffle_render::pixmap::{from_color, Pixmap, Rgba};
+use ruffle_render::tessellator::Vertex;
+use ruffle_render::transform::Transform;
+use swf::{BlendMode, Color, ColorTransform};
+
+/// The layer which `BlendMode::Alpha` and `BlendMode::Erase` apply to.
+#[derive(Clone, Copy)]
+pub enum LayerRef<'a> {
+    /// There is no layer, so these blend modes do nothing.
+    None,
+
+    /// The target being drawn onto is the layer.
+    Current,
+
+    /// The layer is an ancestor of the target being drawn onto.
+    Parent(&'a Pixmap),
+}
+
+/// Executes a [`CommandList`] by drawing directly onto a [`Target`].
+pub struct CommandRenderer<'a> {
+    target: &'a mut Target,
+    nearest_layer: LayerRef<'a>,
+    mask: Mask,
+}
+
+impl<'a> CommandRenderer<'a> {
+    pub fn new(target: &'a mut Target, nearest_layer: LayerRef<'a>) -> Self {
+        Self {
+            target,
+            nearest_layer,
+            mask: Mask::NONE,
+        }
+    }
+
+    /// Draws the `width` by `height` rectangle at the origin, transformed by `to_screen`.
+    fn fill_quad(
+        &mut self,
+        to_screen: &Affine,
+        width: f64,
+        height: f64,
+        mut shade: impl FnMut(&Fragment) -> Rgba,
+    ) {
+        let corners = [(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)]
+            .map(|(x, y)| to_screen.apply(x, y));
+        self.target
+            .fill_triangle([corners[0], corners[1], corners[2]], self.mask, &mut shade);
+        self.target
+            .fill_triangle([corners[0], corners[2], corners[3]], self.mask, &mut shade);
+    }
+
+    /// Draws the triangles of a tessellated shape.
+    /// `shade` is given the vertex indices of the triangle being drawn.
+    fn fill_triangles(
+        &mut self,
+        to_screen: &Affine,
+        vertices: &[Vertex],
+        indices: &[u32],
+        mut shade: impl FnMut([u32; 3], &Fragment) -> Rgba,
+    ) {
+        for triangle in indices.chunks_exact(3) {
+            let triangle = [triangle[0], triangle[1], triangle[2]];
+            let points = triangle.map(|index| {
+                let vertex = &vertices[index as usize];
+                to_screen.apply(vertex.x.into(), vertex.y.into())
+            });
+            self.target
+                .fill_triangle(points, self.mask, |fragment| shade(triangle, fragment));
+        }
+    }
+
+    fn draw_bitmap(
+        &mut self,
+        bitmap: &BitmapHandle,
+        matrix: &Matrix,
+        color_transform: &ColorTransform,
+        smoothing: bool,
+    ) {
+        let pixmap = as_bitmap(bitmap).pixmap();
+        let to_screen = Affine::from_matrix(matrix);
+        let Some(from_screen) = to_screen.inverse() else {
+            return;
+        };
+        let colors = ColorAdjustments::new(color_transform);
+        self.fill_quad(
+            &to_screen,
+            pixmap.width().into(),
+            pixmap.height().into(),
+            |fragment| {
+                let (x, y) = from_screen.apply(fragment.x, fragment.y);
+                colors.apply_premultiplied(pixmap.sample(x, y, smoothing, false))
+            },
+        );
+    }
+}
+
+impl CommandHandler for CommandRenderer<'_> {
+    fn render_bitmap(
+        &mut self,
+        bitmap: BitmapHandle,
+        transform: Transform,
+        smoothing: bool,
+        pixel_snapping: PixelSnapping,
+    ) {
+        let mut matrix = transform.matrix;
+        pixel_snapping.apply(&mut matrix);
+        self.draw_bitmap(&bitmap, &matrix, &transform.color_transform, smoothing);
+    }
+
+    fn render_stage3d(&mut self, bitmap: BitmapHandle, transform: Transform) {
+        self.draw_bitmap(
+            &bitmap,
+            &transform.matrix,
+            &transform.color_transform,
+            false,
+        );
+    }
+
+    fn render_shape(&mut self, shape: ShapeHandle, transform: Transform) {
+        let to_screen = Affine::from_matrix(&transform.matrix);
+        let Some(from_screen) = to_screen.inverse() else {
+            return;
+        };
+        let colors = ColorAdjustments::new(&transform.color_transform);
+        let mesh = as_mesh(&shape);
+
+        for draw in &mesh.draws {
+            let indices = if self.mask.draws_color() {
+                &draw.indices[..]
+            } else {
+                &draw.indices[..draw.mask_index_count]
+            };
+
+            match &draw.draw_type {
+                DrawType::Color => {
+                    let vertex_colors: Vec<Rgba> = draw
+                        .vertices
+                        .iter()
+                        .map(|vertex| colors.apply_straight(from_color(vertex.color)))
+                        .collect();
+                    self.fill_triangles(
+                        &to_screen,
+                        &draw.vertices,
+                        indices,
+                        |triangle, fragment| {
+                            let mut color = [0.0; 4];
+                            for (&index, weight) in triangle.iter().zip(fragment.weights) {
+                                let vertex_color = vertex_colors[index as usize];
+                                for (channel, vertex_channel) in color.iter_mut().zip(vertex_color)
+                                {
+                                    *channel += vertex_channel * weight;
+                                }
+                            }
+                            color
+                        },
+                    );
+                }
+                DrawType::Gradient { matrix, gradient } => {
+                    let gradient = &mesh.gradients[*gradient];
+                    let to_gradient = from_screen.then(matrix);
+                    self.fill_triangles(&to_screen, &draw.vertices, indices, |_, fragment| {
+                        let (u, v) = to_gradient.apply(fragment.x, fragment.y);
+                        colors.apply_straight(gradient.color_at(u, v))
+                    });
+                }
+                DrawType::Bitmap {
+                    matrix,
+                    bitmap,
+                    is_smoothed,
+                    is_repeating,
+                } => {
+                    let pixmap = as_bitmap(bitmap).pixmap();
+                    let to_texels = from_screen.then(matrix).then(&Affine::scale(
+                        pixmap.width().into(),
+                        pixmap.height().into(),
+                    ));
+                    self.fill_triangles(&to_screen, &draw.vertices, indices, |_, fragment| {
+                        let (x, y) = to_texels.apply(fragment.x, fragment.y);
+                        colors.apply_premultiplied(pixmap.sample(x, y, *is_smoothed, *is_repeating))
+                    });
+                }
+            }
+        }
+    }
+
+    fn draw_rect(&mut self, color: Color, matrix: Matrix) {
+        let color =
+            ColorAdjustments::new(&ColorTransform::multiply_from(color)).apply_straight([1.0; 4]);
+        self.fill_quad(&Affine::from_matrix(&matrix), 1.0, 1.0, |_| color);
+    }
+
+    fn draw_line(&mut self, color: Color, matrix: Matrix) {
+        emulate_line(self, color, matrix);
+    }
+
+    fn draw_line_rect(&mut self, color: Color, matrix: Matrix) {
+        emulate_line_rect(self, color, matrix);
+    }
+
+    fn push_mask(&mut self) {
+        self.mask.num_masks += 1;
+        self.mask.state = MaskState::DrawMaskStencil;
+    }
+
+    fn activate_mask(&mut self) {
+        self.mask.state = MaskState::DrawMaskedContent;
+    }
+
+    fn deactivate_mask(&mut self) {
+        self.mask.state = MaskState::ClearMaskStencil;
+    }
+
+    fn pop_mask(&mut self) {
+        self.mask.num_masks = self.mask.num_masks.saturating_sub(1);
+        self.mask.state = if self.mask.num_masks == 0 {
+            MaskState::NoMask
+        } else {
+            MaskState::DrawMaskedContent
+        };
+    }
+
+    fn blend(&mut self, commands: CommandList, blend_mode: RenderBlendMode) {
+        let blend_mode = match blend_mode {
+            RenderBlendMode::Builtin(blend_mode) => blend_mode,
+            RenderBlendMode::Shader(_) => {
+                tracing::warn!("Shader blend modes are not supported by the software renderer");
+                BlendMode::Normal
+            }
+        };
+
+        let mut layer = Target::new(
+            self.target.width(),
+            self.target.height(),
+            self.target.scale(),
+            Color::from_rgba(0),
+        );
+        let child_layer = if blend_mode == BlendMode::Layer {
+            LayerRef::Current
+        } else {
+            match self.nearest_layer {
+                LayerRef::Current => LayerRef::Parent(self.target.samples()),
+                nearest_layer => nearest_layer,
+            }
+        };
+        commands.execute(&mut CommandRenderer::new(&mut layer, child_layer));
+
+        let backdrop = match blend_mode {
+            BlendMode::Alpha | BlendMode::Erase => match self.nearest_layer {
+                LayerRef::None => return,
+                LayerRef::Current => None,
+                LayerRef::Parent(layer) => Some(layer),
+            },
+            _ => None,
+        };
+        self.target
+            .composite(layer.samples(), backdrop, self.mask, |src, dst| {
+                blend(blend_mode, src, dst)
+            });
+    }
+}
+
+/// A color transform, ready to be applied to colors between 0 and 1.
+struct ColorAdjustments {
+    mult: [f32; 4],
+    add: [f32; 4],
+}
+
+impl ColorAdjustments {
+    fn new(color_transform: &ColorTransform) -> Self {
+        Self {
+            mult: color_transform.mult_rgba_normalized(),
+            add: color_transform.add_rgba_normalized(),
+        }
+    }
+
+    /// Transforms a color with straight alpha, and returns it premultiplied.
+    #[inline]
+    fn apply_straight(&self, color: Rgba) -> Rgba {
+        let channel = |i: usize| (color[i] * self.mult[i] + self.add[i]).clamp(0.0, 1.0);
+        let alpha = channel(3);
+        [
+            channel(0) * alpha,
+            channel(1) * alpha,
+            channel(2) * alpha,
+            alpha,
+        ]
+    }
+
+    /// Transforms a premultiplied color, like the bitmap shader does.
+    #[inline]
+    fn apply_premultiplied(&self, color: Rgba) -> Rgba {
+        let alpha = color[3];
+        if alpha <= 0.0 {
+            return color;
+        }
+        self.apply_straight([color[0] / alpha, color[1] / alpha, color[2] / alpha, alpha])
+    }
+}
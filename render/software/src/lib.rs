@@ -0,0 +1,17 @@
+//! A render backend which draws everything on the CPU.
+//!
+//! It doesn't need a GPU or a windowing system, and produces the same images on every machine,
+//! which makes it a good fit for exporting frames and running image tests on headless machines.
+//! It is much slower than the GPU backends, so it isn't meant for interactive use.
+
+#![deny(clippy::unwrap_used)]
+// Bitmaps are shared through `Arc` handles, but they are never sent across threads.
+#![allow(clippy::arc_with_non_send_sync)]
+
+mod backend;
+mod blend;
+mod commands;
+mod mesh;
+mod raster;
+
+pub use backend::SoftwareRenderBackend;
@@ -0,0 +1,230 @@
+use crate::raster::Affine;
+use ruffle_render::backend::{RenderBackend, ShapeHandle, ShapeHandleImpl};
+use ruffle_render::bitmap::{BitmapHandle, BitmapSource};
+use ruffle_render::pixmap::{lerp, Rgba};
+use ruffle_render::shape_utils::GradientType;
+use ruffle_render::tessellator::{
+    DrawType as TessDrawType, Gradient as TessGradient, Mesh as TessMesh, Vertex,
+};
+use swf::{GradientInterpolation, GradientSpread};
+
+/// The number of colors that gradients are sampled from, like the gradient textures of the GPU backends.
+const GRADIENT_SIZE: usize = 256;
+
+#[derive(Debug)]
+pub struct Mesh {
+    pub draws: Vec<Draw>,
+    pub gradients: Vec<Gradient>,
+}
+
+impl ShapeHandleImpl for Mesh {}
+
+pub fn as_mesh(handle: &ShapeHandle) -> &Mesh {
+    handle
+        .0
+        .downcast_ref::<Mesh>()
+        .expect("Shape handle must be a software mesh")
+}
+
+impl Mesh {
+    pub fn new(
+        backend: &mut dyn RenderBackend,
+        bitmap_source: &dyn BitmapSource,
+        mesh: TessMesh,
+    ) -> Self {
+        let draws = mesh
+            .draws
+            .into_iter()
+            .filter_map(|draw| {
+                let draw_type = match draw.draw_type {
+                    TessDrawType::Color => DrawType::Color,
+                    TessDrawType::Gradient { matrix, gradient } => DrawType::Gradient {
+                        matrix: Affine::from_columns(&matrix),
+                        gradient,
+                    },
+                    TessDrawType::Bitmap(bitmap) => DrawType::Bitmap {
+                        matrix: Affine::from_columns(&bitmap.matrix),
+                        bitmap: bitmap_source.bitmap_handle(bitmap.bitmap_id, backend)?,
+                        is_smoothed: bitmap.is_smoothed,
+                        is_repeating: bitmap.is_repeating,
+                    },
+                };
+                Some(Draw {
+                    draw_type,
+                    vertices: draw.vertices,
+                    indices: draw.indices,
+                    mask_index_count: draw.mask_index_count as usize,
+                })
+            })
+            .collect();
+
+        Self {
+            draws,
+            gradients: mesh.gradients.into_iter().map(Gradient::new).collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Draw {
+    pub draw_type: DrawType,
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    /// The number of indices to draw when drawing this shape as a mask, which excludes strokes.
+    pub mask_index_count: usize,
+}
+
+#[derive(Debug)]
+pub enum DrawType {
+    Color,
+    Gradient {
+        /// Maps object space to the gradient square, from (0, 0) to (1, 1).
+        matrix: Affine,
+        gradient: usize,
+    },
+    Bitmap {
+        /// Maps object space to the bitmap, from (0, 0) to (1, 1).
+        matrix: Affine,
+        bitmap: BitmapHandle,
+        is_smoothed: bool,
+        is_repeating: bool,
+    },
+}
+
+/// A gradient, with its colors precomputed the same way the GPU backends do it.
+#[derive(Debug)]
+pub struct Gradient {
+    /// The colors of the gradient, with straight alpha.
+    colors: Box<[Rgba; GRADIENT_SIZE]>,
+    gradient_type: GradientType,
+    repeat_mode: GradientSpread,
+    focal_point: f64,
+    is_linear_rgb: bool,
+}
+
+impl Gradient {
+    fn new(gradient: TessGradient) -> Self {
+        let is_linear_rgb = gradient.interpolation == GradientInterpolation::LinearRgb;
+        let mut colors = Box::new([[0.0; 4]; GRADIENT_SIZE]);
+
+        if !gradient.records.is_empty() {
+            let convert = |channel: u8| {
+                let channel = f32::from(channel);
+                if is_linear_rgb {
+                    srgb_to_linear(channel / 255.0) * 255.0
+                } else {
+                    channel
+                }
+            };
+
+            for (t, color) in colors.iter_mut().enumerate() {
+                let mut last = 0;
+                let mut next = 0;
+                for (i, record) in gradient.records.iter().enumerate().rev() {
+                    if (record.ratio as usize) < t {
+                        last = i;
+                        next = (i + 1).min(gradient.records.len() - 1);
+                        break;
+                    }
+                }
+
+                let last_record = &gradient.records[last];
+                let next_record = &gradient.records[next];
+                let a = if next == last {
+                    // This happens before the first record, or after the last one.
+                    0.0
+                } else {
+                    (t as f32 - f32::from(last_record.ratio))
+                        / (f32::from(next_record.ratio) - f32::from(last_record.ratio))
+                };
+                // Truncating to 8 bits matches the gradient textures of the GPU backends.
+                let mix = |last: f32, next: f32| (last + (next - last) * a).trunc() / 255.0;
+                *color = [
+                    mix(convert(last_record.color.r), convert(next_record.color.r)),
+                    mix(convert(last_record.color.g), convert(next_record.color.g)),
+                    mix(convert(last_record.color.b), convert(next_record.color.b)),
+                    mix(
+                        f32::from(last_record.color.a),
+                        f32::from(next_record.color.a),
+                    ),
+                ];
+            }
+        }
+
+        Self {
+            colors,
+            gradient_type: gradient.gradient_type,
+            repeat_mode: gradient.repeat_mode,
+            focal_point: gradient.focal_point.to_f64(),
+            is_linear_rgb,
+        }
+    }
+
+    /// Returns the color at a position within the gradient square, with straight alpha.
+    pub fn color_at(&self, u: f64, v: f64) -> Rgba {
+        let t = match self.gradient_type {
+            GradientType::Linear => u,
+            GradientType::Radial => (u * 2.0 - 1.0).hypot(v * 2.0 - 1.0),
+            GradientType::Focal => {
+                let (x, y) = (u * 2.0 - 1.0, v * 2.0 - 1.0);
+                let focal_point = self.focal_point;
+                let (dx, dy) = (focal_point - x, -y);
+                let length = dx.hypot(dy);
+                let (dx, dy) = (dx / length, dy / length);
+                length / ((1.0 - focal_point * focal_point * dy * dy).sqrt() + focal_point * dx)
+            }
+        };
+
+        let t = match self.repeat_mode {
+            GradientSpread::Pad => t.clamp(0.0, 1.0),
+            GradientSpread::Reflect => {
+                let t = t.abs();
+                if (t as i64) & 1 == 0 {
+                    t.fract()
+                } else {
+                    1.0 - t.fract()
+                }
+            }
+            GradientSpread::Repeat => t - t.floor(),
+        };
+        // The focal gradient is undefined at the focal point itself.
+        let t = if t.is_nan() { 0.0 } else { t };
+
+        // Linearly interpolate between the two nearest colors, like a smoothed texture would.
+        let position = (t * GRADIENT_SIZE as f64 - 0.5).clamp(0.0, (GRADIENT_SIZE - 1) as f64);
+        let index = position as usize;
+        let next = (index + 1).min(GRADIENT_SIZE - 1);
+        let color = lerp(
+            self.colors[index],
+            self.colors[next],
+            (position - index as f64) as f32,
+        );
+
+        if self.is_linear_rgb {
+            [
+                linear_to_srgb(color[0]),
+                linear_to_srgb(color[1]),
+                linear_to_srgb(color[2]),
+                color[3],
+            ]
+        } else {
+            color
+        }
+    }
+}
+
+fn srgb_to_linear(color: f32) -> f32 {
+    if color <= 0.04045 {
+        color / 12.92
+    } else {
+        f32::powf((color + 0.055) / 1.055, 2.4)
+    }
+}
+
+fn linear_to_srgb(color: f32) -> f32 {
+    if color < 0.0031308 {
+        color * 12.92
+    } else {
+        1.055 * color.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,458 @@
+use ruffle_render::matrix::Matrix;
+use ruffle_render::pixmap::{Pixmap, Rgba};
+
+/// An affine transformation, mapping `(x, y)` to `(a * x + c * y + tx, b * x + d * y + ty)`.
+///
+/// Unlike [`Matrix`], the translation isn't rounded to twips,
+/// as these are also used to map screen positions to texture coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub tx: f64,
+    pub ty: f64,
+}
+
+impl Affine {
+    pub fn scale(x: f64, y: f64) -> Self {
+        Self {
+            a: x,
+            b: 0.0,
+            c: 0.0,
+            d: y,
+            tx: 0.0,
+            ty: 0.0,
+        }
+    }
+
+    /// Converts a [`Matrix`], with its translation in pixels.
+    pub fn from_matrix(matrix: &Matrix) -> Self {
+        Self {
+            a: matrix.a.into(),
+            b: matrix.b.into(),
+            c: matrix.c.into(),
+            d: matrix.d.into(),
+            tx: matrix.tx.to_pixels(),
+            ty: matrix.ty.to_pixels(),
+        }
+    }
+
+    /// Converts a column-major 3x3 matrix, as produced by the tessellator.
+    pub fn from_columns(matrix: &[[f32; 3]; 3]) -> Self {
+        Self {
+            a: matrix[0][0].into(),
+            b: matrix[0][1].into(),
+            c: matrix[1][0].into(),
+            d: matrix[1][1].into(),
+            tx: matrix[2][0].into(),
+            ty: matrix[2][1].into(),
+        }
+    }
+
+    /// Returns the transformation which applies `self` first, and then `other`.
+    pub fn then(&self, other: &Affine) -> Self {
+        Self {
+            a: other.a * self.a + other.c * self.b,
+            b: other.b * self.a + other.d * self.b,
+            c: other.a * self.c + other.c * self.d,
+            d: other.b * self.c + other.d * self.d,
+            tx: other.a * self.tx + other.c * self.ty + other.tx,
+            ty: other.b * self.tx + other.d * self.ty + other.ty,
+        }
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+        let (a, b, c, d) = (
+            self.d / determinant,
+            -self.b / determinant,
+            -self.c / determinant,
+            self.a / determinant,
+        );
+        Some(Self {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        })
+    }
+
+    #[inline]
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.c * y + self.tx,
+            self.b * x + self.d * y + self.ty,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MaskState {
+    NoMask,
+    DrawMaskStencil,
+    DrawMaskedContent,
+    ClearMaskStencil,
+}
+
+/// How drawing interacts with the stencil buffer, which keeps track of the masks.
+///
+/// This mirrors the stencil states used by the wgpu backend: a masker increments the stencil
+/// of what it covers, the masked content is only drawn where every active masker has been drawn,
+/// and the masker is drawn again afterwards to decrement the stencil back.
+#[derive(Clone, Copy, Debug)]
+pub struct Mask {
+    pub state: MaskState,
+    pub num_masks: u32,
+}
+
+enum StencilAction {
+    Skip,
+    Draw,
+    Increment,
+    Decrement,
+}
+
+impl Mask {
+    pub const NONE: Self = Self {
+        state: MaskState::NoMask,
+        num_masks: 0,
+    };
+
+    /// Whether drawing in this state changes colors, instead of just the stencil.
+    pub fn draws_color(&self) -> bool {
+        matches!(self.state, MaskState::NoMask | MaskState::DrawMaskedContent)
+    }
+
+    #[inline]
+    fn action(&self, stencil: u8) -> StencilAction {
+        let stencil = u32::from(stencil);
+        match self.state {
+            MaskState::NoMask => StencilAction::Draw,
+            MaskState::DrawMaskStencil if stencil + 1 == self.num_masks => StencilAction::Increment,
+            MaskState::DrawMaskedContent if stencil == self.num_masks => StencilAction::Draw,
+            MaskState::ClearMaskStencil if stencil == self.num_masks => StencilAction::Decrement,
+            _ => StencilAction::Skip,
+        }
+    }
+}
+
+/// A position being drawn by [`Target::fill_triangle`].
+pub struct Fragment {
+    /// The center of the sample, in pixels.
+    pub x: f64,
+    pub y: f64,
+
+    /// The barycentric weights of the triangle's vertices at this position.
+    pub weights: [f32; 3],
+}
+
+/// Triangle vertices are snapped to 1/16th of a sample, so that the
+/// coverage tests can be done exactly with integers.
+const SUBSAMPLE_BITS: u32 = 4;
+const SUBSAMPLES: f64 = (1 << SUBSAMPLE_BITS) as f64;
+
+/// Keeps snapped coordinates small enough that the edge functions can't overflow.
+const MAX_COORDINATE: f64 = (1i64 << 28) as f64;
+
+/// Something to draw onto, supersampled according to the stage quality.
+///
+/// Every pixel is made up of `scale * scale` samples, each with their own color and stencil.
+/// This is the equivalent of a multisampled texture with its stencil attachment.
+pub struct Target {
+    pixmap: Pixmap,
+    stencil: Vec<u8>,
+    scale: u32,
+}
+
+impl Target {
+    /// Creates a target of `width` by `height` pixels, initially filled with `clear`.
+    pub fn new(width: u32, height: u32, scale: u32, clear: swf::Color) -> Self {
+        Self::from_samples(Pixmap::filled(width * scale, height * scale, clear), scale)
+    }
+
+    /// Creates a target whose initial contents are those of `pixmap`.
+    pub fn from_pixmap(pixmap: &Pixmap, scale: u32) -> Self {
+        Self::from_samples(pixmap.upscale(scale), scale)
+    }
+
+    fn from_samples(pixmap: Pixmap, scale: u32) -> Self {
+        let stencil = vec![0; pixmap.width() as usize * pixmap.height() as usize];
+        Self {
+            pixmap,
+            stencil,
+            scale,
+        }
+    }
+
+    /// The width in pixels, not samples.
+    pub fn width(&self) -> u32 {
+        self.pixmap.width() / self.scale
+    }
+
+    /// The height in pixels, not samples.
+    pub fn height(&self) -> u32 {
+        self.pixmap.height() / self.scale
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// The individual samples of this target.
+    pub fn samples(&self) -> &Pixmap {
+        &self.pixmap
+    }
+
+    /// Averages the samples of every pixel into the final image.
+    pub fn resolve(&self) -> Pixmap {
+        self.pixmap.downscale(self.scale)
+    }
+
+    /// Draws a triangle given in pixels, using normal blending.
+    ///
+    /// `shade` is called for every covered sample which passes the mask test,
+    /// and returns its color. When drawing a masker, only the stencil is updated.
+    pub fn fill_triangle(
+        &mut self,
+        vertices: [(f64, f64); 3],
+        mask: Mask,
+        mut shade: impl FnMut(&Fragment) -> Rgba,
+    ) {
+        let scale = f64::from(self.scale) * SUBSAMPLES;
+        let snap = |(x, y): (f64, f64)| {
+            let x = (x * scale).round().clamp(-MAX_COORDINATE, MAX_COORDINATE);
+            let y = (y * scale).round().clamp(-MAX_COORDINATE, MAX_COORDINATE);
+            // NaN saturates to 0 when casting, which is as good as anything.
+            (x as i64, y as i64)
+        };
+        let mut v = vertices.map(snap);
+
+        let mut area = edge(v[0], v[1], v[2]);
+        if area == 0 {
+            return;
+        }
+        let mut weight_order = [0, 1, 2];
+        if area < 0 {
+            v.swap(1, 2);
+            weight_order.swap(1, 2);
+            area = -area;
+        }
+
+        let width = i64::from(self.pixmap.width());
+        let height = i64::from(self.pixmap.height());
+        let half = 1i64 << (SUBSAMPLE_BITS - 1);
+        // The range of samples whose centers may be inside of the triangle.
+        let sample_range = |min: i64, max: i64, size: i64| {
+            let first = ((min - half) >> SUBSAMPLE_BITS).max(0);
+            let last = ((max - half) >> SUBSAMPLE_BITS).min(size - 1);
+            first..=last
+        };
+        let xs = sample_range(
+            v[0].0.min(v[1].0).min(v[2].0),
+            v[0].0.max(v[1].0).max(v[2].0),
+            width,
+        );
+        let ys = sample_range(
+            v[0].1.min(v[1].1).min(v[2].1),
+            v[0].1.max(v[1].1).max(v[2].1),
+            height,
+        );
+
+        let edges = [(v[1], v[2]), (v[2], v[0]), (v[0], v[1])];
+        let area = area as f64;
+        let sample_size = 1.0 / f64::from(self.scale);
+
+        for sample_y in ys {
+            let py = (sample_y << SUBSAMPLE_BITS) + half;
+            for sample_x in xs.clone() {
+                let px = (sample_x << SUBSAMPLE_BITS) + half;
+                let w = edges.map(|(a, b)| edge(a, b, (px, py)));
+                if !edges
+                    .iter()
+                    .zip(w)
+                    .all(|(&(a, b), w)| w > 0 || (w == 0 && is_top_left(a, b)))
+                {
+                    continue;
+                }
+
+                let index = (sample_y * width + sample_x) as usize;
+                match mask.action(self.stencil[index]) {
+                    StencilAction::Skip => {}
+                    StencilAction::Increment => {
+                        self.stencil[index] = self.stencil[index].saturating_add(1)
+                    }
+                    StencilAction::Decrement => {
+                        self.stencil[index] = self.stencil[index].saturating_sub(1)
+                    }
+                    StencilAction::Draw => {
+                        let mut weights = [0.0; 3];
+                        for (w, &vertex) in w.iter().zip(&weight_order) {
+                            weights[vertex] = (*w as f64 / area) as f32;
+                        }
+                        let color = shade(&Fragment {
+                            x: (sample_x as f64 + 0.5) * sample_size,
+                            y: (sample_y as f64 + 0.5) * sample_size,
+                            weights,
+                        });
+                        let dst = self.pixmap.get_index(index);
+                        self.pixmap.set_index(index, over(color, dst));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Combines every sample of `source`, which must be the same size as this target,
+    /// into this target using `blend(source, backdrop)`.
+    ///
+    /// The backdrop is read from this target, or from `backdrop` if given.
+    /// If `blend` returns `None`, the sample is left untouched.
+    /// When drawing a masker, every sample which isn't fully transparent in `source` counts as covered.
+    pub fn composite(
+        &mut self,
+        source: &Pixmap,
+        backdrop: Option<&Pixmap>,
+        mask: Mask,
+        blend: impl Fn(Rgba, Rgba) -> Option<Rgba>,
+    ) {
+        debug_assert_eq!(source.width(), self.pixmap.width());
+        debug_assert_eq!(source.height(), self.pixmap.height());
+
+        for index in 0..self.stencil.len() {
+            if source.data()[index * 4 + 3] == 0 && !mask.draws_color() {
+                continue;
+            }
+            match mask.action(self.stencil[index]) {
+                StencilAction::Skip => {}
+                StencilAction::Increment => {
+                    self.stencil[index] = self.stencil[index].saturating_add(1)
+                }
+                StencilAction::Decrement => {
+                    self.stencil[index] = self.stencil[index].saturating_sub(1)
+                }
+                StencilAction::Draw => {
+                    let dst = backdrop.unwrap_or(&self.pixmap).get_index(index);
+                    if let Some(color) = blend(source.get_index(index), dst) {
+                        self.pixmap.set_index(index, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Twice the signed area of the triangle `(a, b, p)`.
+#[inline]
+fn edge(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+    (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+}
+
+/// Samples exactly on an edge belong to only one of the two triangles sharing it,
+/// so that they are neither drawn twice nor skipped.
+#[inline]
+fn is_top_left(a: (i64, i64), b: (i64, i64)) -> bool {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    dy > 0 || (dy == 0 && dx < 0)
+}
+
+/// Normal blending of premultiplied colors.
+#[inline]
+pub fn over(src: Rgba, dst: Rgba) -> Rgba {
+    let inverse_alpha = 1.0 - src[3];
+    [
+        src[0] + dst[0] * inverse_alpha,
+        src[1] + dst[1] * inverse_alpha,
+        src[2] + dst[2] * inverse_alpha,
+        src[3] + dst[3] * inverse_alpha,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::Color;
+
+    const RED: Rgba = [1.0, 0.0, 0.0, 1.0];
+
+    fn opaque_pixels(target: &Target) -> usize {
+        target
+            .resolve()
+            .data()
+            .chunks_exact(4)
+            .filter(|pixel| pixel[3] == 255)
+            .count()
+    }
+
+    #[test]
+    fn affine_inverse() {
+        let affine = Affine {
+            a: 2.0,
+            b: 1.0,
+            c: -1.0,
+            d: 3.0,
+            tx: 10.0,
+            ty: -5.0,
+        };
+        let inverse = affine.inverse().expect("Matrix is invertible");
+        let (x, y) = affine.then(&inverse).apply(7.0, 11.0);
+        approx::assert_relative_eq!(x, 7.0, epsilon = 1e-9);
+        approx::assert_relative_eq!(y, 11.0, epsilon = 1e-9);
+        assert_eq!(Affine::scale(0.0, 1.0).inverse(), None);
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        let mut target = Target::new(8, 8, 1, Color::from_rgba(0));
+        let quad = [(0.0, 0.0), (8.0, 0.0), (8.0, 8.0), (0.0, 8.0)];
+        let half_red = [0.5, 0.0, 0.0, 0.5];
+        target.fill_triangle([quad[0], quad[1], quad[2]], Mask::NONE, |_| half_red);
+        target.fill_triangle([quad[0], quad[3], quad[2]], Mask::NONE, |_| half_red);
+        let resolved = target.resolve();
+        assert!(resolved
+            .data()
+            .chunks_exact(4)
+            .all(|pixel| pixel == [128, 0, 0, 128]));
+    }
+
+    #[test]
+    fn supersampled_edges_are_antialiased() {
+        let mut target = Target::new(1, 1, 4, Color::from_rgba(0));
+        target.fill_triangle([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)], Mask::NONE, |_| RED);
+        // 10 of the 16 samples are covered, including the 4 exactly on the diagonal.
+        assert_eq!(target.resolve().data(), &[159, 0, 0, 159]);
+    }
+
+    #[test]
+    fn masks_limit_drawing() {
+        fn fill_rect(target: &mut Target, width: f64, mask: Mask) {
+            let rect = [(0.0, 0.0), (width, 0.0), (width, 4.0), (0.0, 4.0)];
+            target.fill_triangle([rect[0], rect[1], rect[2]], mask, |_| RED);
+            target.fill_triangle([rect[0], rect[2], rect[3]], mask, |_| RED);
+        }
+
+        let mut target = Target::new(4, 4, 1, Color::from_rgba(0));
+        let mut mask = Mask {
+            state: MaskState::DrawMaskStencil,
+            num_masks: 1,
+        };
+        fill_rect(&mut target, 2.0, mask);
+        assert_eq!(opaque_pixels(&target), 0);
+
+        mask.state = MaskState::DrawMaskedContent;
+        fill_rect(&mut target, 4.0, mask);
+        assert_eq!(opaque_pixels(&target), 8);
+
+        mask.state = MaskState::ClearMaskStencil;
+        fill_rect(&mut target, 2.0, mask);
+        fill_rect(&mut target, 4.0, Mask::NONE);
+        assert_eq!(opaque_pixels(&target), 16);
+        assert!(target.stencil.iter().all(|&stencil| stencil == 0));
+    }
+}
@@ -0,0 +1,548 @@
+//! CPU implementations of the filters, following the filter shaders of the wgpu backend.
+//!
+//! These can be used by any backend, or without one, at the cost of speed.
+
+use crate::bitmap::BitmapHandle;
+use crate::filters::{DisplacementMapFilter, DisplacementMapFilterMode, Filter};
+use crate::pixmap::{from_color, lerp, Pixmap, Rgba};
+use std::sync::OnceLock;
+use swf::{
+    BevelFilter, BlurFilter, ColorMatrixFilter, ConvolutionFilter, GlowFilter, GradientFilter,
+    GradientRecord,
+};
+
+pub fn is_filter_supported(filter: &Filter) -> bool {
+    matches!(
+        filter,
+        Filter::BlurFilter(_)
+            | Filter::GlowFilter(_)
+            | Filter::DropShadowFilter(_)
+            | Filter::ColorMatrixFilter(_)
+            | Filter::BevelFilter(_)
+            | Filter::DisplacementMapFilter(_)
+            | Filter::ConvolutionFilter(_)
+            | Filter::GradientGlowFilter(_)
+            | Filter::GradientBevelFilter(_)
+    )
+}
+
+/// Applies a filter to every pixel of `source`, returning a pixmap of the same size.
+///
+/// `read_bitmap` is used to read the pixels of the map of a displacement map filter.
+/// Unsupported filters, and displacement maps whose map can't be read, leave the source unchanged.
+pub fn apply_filter(
+    source: &Pixmap,
+    filter: &Filter,
+    read_bitmap: impl FnOnce(&BitmapHandle) -> Option<Pixmap>,
+) -> Pixmap {
+    match filter {
+        Filter::BlurFilter(filter) => blur(source, filter),
+        Filter::GlowFilter(filter) => glow(source, filter, (0.0, 0.0)),
+        Filter::DropShadowFilter(filter) => {
+            let distance = filter.distance.to_f32();
+            let angle = filter.angle.to_f32();
+            glow(
+                source,
+                &filter.inner_glow_filter(),
+                (-angle.cos() * distance, -angle.sin() * distance),
+            )
+        }
+        Filter::ColorMatrixFilter(filter) => color_matrix(source, filter),
+        Filter::BevelFilter(filter) => bevel(source, filter),
+        Filter::DisplacementMapFilter(filter) => {
+            match filter.map_bitmap.as_ref().and_then(read_bitmap) {
+                Some(map) => displacement_map(source, &map, filter),
+                None => source.clone(),
+            }
+        }
+        Filter::ConvolutionFilter(filter) => convolution(source, filter),
+        Filter::GradientGlowFilter(filter) => gradient(source, filter, false),
+        Filter::GradientBevelFilter(filter) => gradient(source, filter, true),
+        Filter::ShaderFilter(_) => {
+            // Only warn once, as this would otherwise happen every frame
+            static WARNED: OnceLock<()> = OnceLock::new();
+            if WARNED.set(()).is_ok() {
+                tracing::warn!("Shader filters can't be applied on the CPU");
+            }
+            source.clone()
+        }
+    }
+}
+
+/// Returns the alpha of the pixel at a position in pixels, or 0 outside of the pixmap.
+fn alpha_at(pixmap: &Pixmap, x: f32, y: f32) -> f32 {
+    if x < 0.0 || y < 0.0 || x > pixmap.width() as f32 || y > pixmap.height() as f32 {
+        return 0.0;
+    }
+    pixmap.sample(x.into(), y.into(), false, false)[3]
+}
+
+fn premultiply(color: Rgba) -> Rgba {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+fn unmultiply(color: Rgba) -> Rgba {
+    let alpha = color[3];
+    if alpha > 0.0 {
+        [color[0] / alpha, color[1] / alpha, color[2] / alpha, alpha]
+    } else {
+        [0.0; 4]
+    }
+}
+
+/// Combines a premultiplied glow with the pixel of the source that it's drawn on.
+fn composite_glow(glow: Rgba, dest: Rgba, inner: bool, outer: bool, knockout: bool) -> Rgba {
+    match (inner, outer, knockout) {
+        (true, true, true) => glow,
+        (true, true, false) => std::array::from_fn(|i| dest[i] - dest[i] * glow[3] + glow[i]),
+        (true, false, true) => glow.map(|c| c * dest[3]),
+        (true, false, false) => {
+            std::array::from_fn(|i| glow[i] * dest[3] + dest[i] * (1.0 - glow[3]))
+        }
+        (false, _, true) => glow.map(|c| c - c * dest[3]),
+        (false, _, false) => std::array::from_fn(|i| dest[i] + glow[i] - glow[i] * dest[3]),
+    }
+}
+
+/// Blurs with a box filter of fractional size, one direction at a time.
+/// See `blur.wgsl` for the details of the kernel.
+pub fn blur(source: &Pixmap, filter: &BlurFilter) -> Pixmap {
+    let mut result = source.clone();
+    for _ in 0..filter.num_passes() {
+        for horizontal in [true, false] {
+            let strength = if horizontal {
+                filter.blur_x.to_f32()
+            } else {
+                filter.blur_y.to_f32()
+            };
+            let full_size = strength.min(255.0);
+            if full_size <= 1.0 {
+                continue;
+            }
+            blur_pass(&mut result, horizontal, full_size);
+        }
+    }
+    result
+}
+
+fn blur_pass(pixmap: &mut Pixmap, horizontal: bool, full_size: f32) {
+    let radius = (full_size - 1.0) / 2.0;
+    // Every pixel up to `m` away has a weight of 1,
+    // and the two pixels right after them have a fractional weight of `alpha`.
+    let m = radius.ceil() - 1.0;
+    let alpha = ((radius - m) * 255.0).floor() / 255.0;
+    let m = m as i64;
+
+    let (width, height) = (pixmap.width() as usize, pixmap.height() as usize);
+    let (lines, length, step) = if horizontal {
+        (height, width, 1)
+    } else {
+        (width, height, width)
+    };
+    if length == 0 {
+        return;
+    }
+
+    let mut line = vec![[0u32; 4]; length];
+    for line_index in 0..lines {
+        let start = if horizontal {
+            line_index * width
+        } else {
+            line_index
+        };
+        for (i, pixel) in line.iter_mut().enumerate() {
+            let offset = (start + i * step) * 4;
+            for (channel, value) in pixel.iter_mut().enumerate() {
+                *value = u32::from(pixmap.data()[offset + channel]);
+            }
+        }
+
+        let at = |i: i64| line[i.clamp(0, length as i64 - 1) as usize];
+        let mut sum = [0u32; 4];
+        for i in -m..=m {
+            for (total, value) in sum.iter_mut().zip(at(i)) {
+                *total += value;
+            }
+        }
+
+        for i in 0..length as i64 {
+            let (before, after) = (at(i - m - 1), at(i + m + 1));
+            let offset = (start + i as usize * step) * 4;
+            for channel in 0..4 {
+                let total = sum[channel] as f32 + (before[channel] + after[channel]) as f32 * alpha;
+                // Truncating imitates the fixed-point computations of Flash Player.
+                pixmap.data_mut()[offset + channel] = (total / full_size).floor().min(255.0) as u8;
+            }
+
+            let (entering, leaving) = (at(i + m + 1), at(i - m));
+            for channel in 0..4 {
+                sum[channel] = sum[channel] + entering[channel] - leaving[channel];
+            }
+        }
+    }
+}
+
+/// Draws a glow of `filter.color` around or inside of `source`, like `glow.wgsl`.
+/// The blurred alpha that the glow is made out of is read at an offset, for drop shadows.
+fn glow(source: &Pixmap, filter: &GlowFilter, blur_offset: (f32, f32)) -> Pixmap {
+    let blurred = blur(source, &filter.inner_blur_filter());
+    let strength = filter.strength.to_f32();
+    let inner = filter.is_inner();
+    let knockout = filter.is_knockout();
+    let composite_source = filter.composite_source();
+    let [r, g, b, color_alpha] = from_color(filter.color);
+    let color = [r, g, b, 1.0];
+
+    let mut result = Pixmap::new(source.width(), source.height());
+    for y in 0..source.height() {
+        for x in 0..source.width() {
+            let blur = alpha_at(
+                &blurred,
+                x as f32 + 0.5 + blur_offset.0,
+                y as f32 + 0.5 + blur_offset.1,
+            );
+            let dest = source.get(x, y);
+
+            let pixel: Rgba = if inner {
+                let alpha = color_alpha * ((1.0 - blur) * strength).clamp(0.0, 1.0);
+                if composite_source && !knockout {
+                    std::array::from_fn(|i| color[i] * alpha * dest[3] + dest[i] * (1.0 - alpha))
+                } else {
+                    color.map(|c| c * alpha * dest[3])
+                }
+            } else {
+                let alpha = color_alpha * (blur * strength).clamp(0.0, 1.0);
+                if knockout {
+                    color.map(|c| c * alpha * (1.0 - dest[3]))
+                } else if composite_source {
+                    std::array::from_fn(|i| color[i] * alpha * (1.0 - dest[3]) + dest[i])
+                } else {
+                    color.map(|c| c * alpha)
+                }
+            };
+            result.set(x, y, pixel);
+        }
+    }
+    result
+}
+
+/// Draws highlights and shadows on opposite sides of `source`, like `bevel.wgsl`.
+fn bevel(source: &Pixmap, filter: &BevelFilter) -> Pixmap {
+    let blurred = blur(source, &filter.inner_blur_filter());
+    let strength = filter.strength.to_f32();
+    let knockout = filter.is_knockout();
+    let (inner, outer) = if filter.is_on_top() {
+        (true, true)
+    } else {
+        (filter.is_inner(), !filter.is_inner())
+    };
+    let distance = filter.distance.to_f32();
+    let angle = filter.angle.to_f32();
+    let offset = (angle.cos() * distance, angle.sin() * distance);
+    let highlight_color = premultiply(from_color(filter.highlight_color));
+    let shadow_color = premultiply(from_color(filter.shadow_color));
+
+    let mut result = Pixmap::new(source.width(), source.height());
+    for y in 0..source.height() {
+        for x in 0..source.width() {
+            let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+            let left = alpha_at(&blurred, center_x + offset.0, center_y + offset.1);
+            let right = alpha_at(&blurred, center_x - offset.0, center_y - offset.1);
+            let dest = source.get(x, y);
+
+            let highlight_alpha = ((left - right) * strength).clamp(0.0, 1.0);
+            let shadow_alpha = ((right - left) * strength).clamp(0.0, 1.0);
+            let glow: Rgba = std::array::from_fn(|i| {
+                highlight_color[i] * highlight_alpha + shadow_color[i] * shadow_alpha
+            });
+
+            result.set(x, y, composite_glow(glow, dest, inner, outer, knockout));
+        }
+    }
+    result
+}
+
+/// Draws a glow or a bevel with colors looked up in a gradient, like `gradient.wgsl`.
+fn gradient(source: &Pixmap, filter: &GradientFilter, bevel: bool) -> Pixmap {
+    let blurred = blur(source, &filter.inner_blur_filter());
+    let colors = gradient_colors(&filter.colors);
+    let strength = filter.strength.to_f32();
+    let knockout = filter.is_knockout();
+    let (inner, outer) = if filter.is_on_top() {
+        (true, true)
+    } else {
+        (filter.is_inner(), !filter.is_inner())
+    };
+    let distance = filter.distance.to_f32();
+    let angle = filter.angle.to_f32();
+    // Glows are offset like drop shadows, while bevels compare both sides of the shape.
+    let offset = if bevel {
+        (angle.cos() * distance, angle.sin() * distance)
+    } else {
+        (-angle.cos() * distance, -angle.sin() * distance)
+    };
+
+    let mut result = Pixmap::new(source.width(), source.height());
+    for y in 0..source.height() {
+        for x in 0..source.width() {
+            let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+            let left = alpha_at(&blurred, center_x + offset.0, center_y + offset.1);
+            let dest = source.get(x, y);
+
+            let position = if bevel {
+                let right = alpha_at(&blurred, center_x - offset.0, center_y - offset.1);
+                0.5 - 0.5 * ((left - right) * strength).clamp(-1.0, 1.0)
+            } else if inner && !outer {
+                ((1.0 - left) * strength).clamp(0.0, 1.0)
+            } else {
+                (left * strength).clamp(0.0, 1.0)
+            };
+            let glow = colors[(position * (GRADIENT_SIZE - 1) as f32).round() as usize];
+            result.set(x, y, composite_glow(glow, dest, inner, outer, knockout));
+        }
+    }
+    result
+}
+
+/// The number of colors in the gradients of gradient filters.
+const GRADIENT_SIZE: usize = 256;
+
+/// Builds the premultiplied colors of a gradient filter, padded with its first and last colors.
+fn gradient_colors(records: &[GradientRecord]) -> Vec<Rgba> {
+    if records.is_empty() {
+        return vec![[0.0; 4]; GRADIENT_SIZE];
+    }
+
+    (0..GRADIENT_SIZE)
+        .map(|t| {
+            let next = records
+                .iter()
+                .position(|record| usize::from(record.ratio) >= t)
+                .unwrap_or(records.len() - 1);
+            let (last_record, next_record) = (&records[next.saturating_sub(1)], &records[next]);
+            let range = f32::from(next_record.ratio) - f32::from(last_record.ratio);
+            let a = if range > 0.0 {
+                ((t as f32 - f32::from(last_record.ratio)) / range).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let color = lerp(
+                from_color(last_record.color),
+                from_color(next_record.color),
+                a,
+            );
+            premultiply(color)
+        })
+        .collect()
+}
+
+/// Convolves the unmultiplied colors of every pixel and its neighbours with a matrix,
+/// like `convolution.wgsl`.
+fn convolution(source: &Pixmap, filter: &ConvolutionFilter) -> Pixmap {
+    let columns = i64::from(filter.num_matrix_cols);
+    let rows = i64::from(filter.num_matrix_rows);
+    let (width, height) = (i64::from(source.width()), i64::from(source.height()));
+    // Flash treats a divisor of 0 as 1.
+    let divisor = if filter.divisor == 0.0 {
+        1.0
+    } else {
+        filter.divisor
+    };
+    let default_color = from_color(filter.default_color);
+    let load = |x: i64, y: i64| {
+        if filter.is_clamped() {
+            unmultiply(source.get(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32))
+        } else if x < 0 || y < 0 || x >= width || y >= height {
+            default_color
+        } else {
+            unmultiply(source.get(x as u32, y as u32))
+        }
+    };
+
+    let mut result = Pixmap::new(source.width(), source.height());
+    for y in 0..height {
+        for x in 0..width {
+            let mut total = [0.0; 4];
+            for row in 0..rows {
+                for column in 0..columns {
+                    // Missing values of the matrix are treated as 0.
+                    let Some(&weight) = filter.matrix.get((row * columns + column) as usize) else {
+                        continue;
+                    };
+                    let color = load(x + column - columns / 2, y + row - rows / 2);
+                    for (total, channel) in total.iter_mut().zip(color) {
+                        *total += channel * weight;
+                    }
+                }
+            }
+
+            let mut color = total.map(|c| (c / divisor + filter.bias / 255.0).clamp(0.0, 1.0));
+            if filter.is_preserve_alpha() {
+                color[3] = load(x, y)[3];
+            }
+            result.set(x as u32, y as u32, premultiply(color));
+        }
+    }
+    result
+}
+
+/// Transforms the unmultiplied color of every pixel with a 4x5 matrix, like `color_matrix.wgsl`.
+fn color_matrix(source: &Pixmap, filter: &ColorMatrixFilter) -> Pixmap {
+    let matrix = &filter.matrix;
+    let mut result = Pixmap::new(source.width(), source.height());
+    for index in 0..source.width() as usize * source.height() as usize {
+        let [r, g, b, alpha] = unmultiply(source.get_index(index));
+        let color: Rgba = std::array::from_fn(|row| {
+            let row = &matrix[row * 5..row * 5 + 5];
+            (row[0] * r + row[1] * g + row[2] * b + row[3] * alpha + row[4] / 255.0).clamp(0.0, 1.0)
+        });
+        result.set_index(index, premultiply(color));
+    }
+    result
+}
+
+/// Moves every pixel according to the colors of a map bitmap, like `displacement_map.wgsl`.
+fn displacement_map(source: &Pixmap, map: &Pixmap, filter: &DisplacementMapFilter) -> Pixmap {
+    let (source_width, source_height) = (source.width() as f32, source.height() as f32);
+    let (map_width, map_height) = (map.width() as f32, map.height() as f32);
+    let scale = (
+        filter.viewscale_x * filter.scale_x,
+        filter.viewscale_y * filter.scale_y,
+    );
+    let component = |map: Rgba, component: u8| match component {
+        1 => map[0] * 255.0,
+        2 => map[1] * 255.0,
+        4 => map[2] * 255.0,
+        8 => map[3] * 255.0,
+        // This means no displacement at all.
+        _ => 128.0,
+    };
+    let [r, g, b, a] = from_color(filter.color);
+    let out_of_bounds_color = [r * a, g * a, b * a, a];
+
+    let mut result = Pixmap::new(source.width(), source.height());
+    for y in 0..source.height() {
+        for x in 0..source.width() {
+            let (source_x, source_y) = (x as f32 + 0.5, y as f32 + 0.5);
+            let map_x = (source_x - filter.map_point.0 as f32) / filter.viewscale_x;
+            let map_y = (source_y - filter.map_point.1 as f32) / filter.viewscale_y;
+            let map_color = if map_x < 0.0 || map_x > map_width || map_y < 0.0 || map_y > map_height
+            {
+                [0.5; 4]
+            } else {
+                map.sample(map_x.into(), map_y.into(), false, false)
+            };
+
+            let mut displaced_x =
+                source_x + (component(map_color, filter.component_x) - 128.0) * scale.0 / 256.0;
+            let mut displaced_y =
+                source_y + (component(map_color, filter.component_y) - 128.0) * scale.1 / 256.0;
+            let out_of_bounds = displaced_x < 0.0
+                || displaced_x > source_width
+                || displaced_y < 0.0
+                || displaced_y > source_height;
+
+            let pixel = match filter.mode {
+                DisplacementMapFilterMode::Color if out_of_bounds => out_of_bounds_color,
+                mode => {
+                    if mode == DisplacementMapFilterMode::Clamp {
+                        displaced_x = displaced_x.clamp(0.0, source_width);
+                        displaced_y = displaced_y.clamp(0.0, source_height);
+                    } else if mode == DisplacementMapFilterMode::Ignore && out_of_bounds {
+                        (displaced_x, displaced_y) = (source_x, source_y);
+                    }
+                    source.sample(displaced_x.into(), displaced_y.into(), true, true)
+                }
+            };
+            result.set(x, y, pixel);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{BlurFilterFlags, Color, ConvolutionFilterFlags, Fixed16};
+
+    #[test]
+    fn blur_spreads_evenly() {
+        let mut source = Pixmap::new(5, 1);
+        source.set(2, 0, [1.0; 4]);
+        let filter = BlurFilter {
+            blur_x: Fixed16::from_f32(3.0),
+            blur_y: Fixed16::ZERO,
+            flags: BlurFilterFlags::from_passes(1),
+        };
+        let alphas: Vec<u8> = blur(&source, &filter)
+            .data()
+            .chunks_exact(4)
+            .map(|pixel| pixel[3])
+            .collect();
+        assert_eq!(alphas, [0, 85, 85, 85, 0]);
+    }
+
+    #[test]
+    fn color_matrix_swaps_channels() {
+        let mut source = Pixmap::new(1, 1);
+        source.set(0, 0, [0.5, 0.0, 0.0, 0.5]);
+        #[rustfmt::skip]
+        let filter = ColorMatrixFilter {
+            matrix: [
+                0.0, 0.0, 0.0, 0.0, 0.0,
+                1.0, 0.0, 0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0, 0.0, 0.0,
+                0.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+        };
+        assert_eq!(color_matrix(&source, &filter).data(), &[0, 128, 0, 128]);
+    }
+
+    #[test]
+    fn convolution_uses_default_color_outside() {
+        let mut source = Pixmap::new(2, 1);
+        source.set(0, 0, [1.0, 0.0, 0.0, 1.0]);
+        source.set(1, 0, [0.0, 1.0, 0.0, 1.0]);
+        let mut filter = ConvolutionFilter {
+            num_matrix_rows: 1,
+            num_matrix_cols: 3,
+            matrix: vec![1.0, 0.0, 1.0],
+            divisor: 2.0,
+            bias: 0.0,
+            default_color: Color::from_rgba(0xFF0000FF),
+            flags: ConvolutionFilterFlags::empty(),
+        };
+        assert_eq!(
+            convolution(&source, &filter).data(),
+            &[0, 128, 128, 255, 128, 0, 128, 255]
+        );
+
+        filter.flags = ConvolutionFilterFlags::CLAMP;
+        assert_eq!(
+            convolution(&source, &filter).data(),
+            &[128, 128, 0, 255, 128, 128, 0, 255]
+        );
+    }
+
+    #[test]
+    fn gradient_colors_are_padded() {
+        let colors = gradient_colors(&[
+            GradientRecord {
+                ratio: 64,
+                color: Color::from_rgba(0xFFFF0000),
+            },
+            GradientRecord {
+                ratio: 192,
+                color: Color::from_rgba(0x000000FF),
+            },
+        ]);
+        assert_eq!(colors[0], [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(colors[128], [0.25, 0.0, 0.25, 0.5]);
+        assert_eq!(colors[255], [0.0; 4]);
+    }
+}
@@ -10,6 +10,7 @@ pub mod lines;
 pub mod matrix;
 pub mod matrix3d;
 pub mod pixel_bender;
+pub mod pixmap;
 // The `renderdoc` crate doesn't compile on apple platforms
 #[cfg(all(feature = "renderdoc", not(target_vendor = "apple")))]
 pub mod renderdoc;
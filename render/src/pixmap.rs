@@ -0,0 +1,357 @@
+//! Images stored in memory, for backends and filters that draw on the CPU.
+
+use crate::bitmap::{Bitmap, PixelRegion, RgbaBufRead, SyncHandle};
+
+/// A color with premultiplied alpha, with every channel between 0 and 1.
+pub type Rgba = [f32; 4];
+
+pub const TRANSPARENT: Rgba = [0.0; 4];
+
+/// Converts a channel between 0 and 1 to its 8-bit representation.
+#[inline]
+pub fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Converts a color to one with channels between 0 and 1, keeping its straight alpha.
+#[inline]
+pub fn from_color(color: swf::Color) -> Rgba {
+    [
+        f32::from(color.r) / 255.0,
+        f32::from(color.g) / 255.0,
+        f32::from(color.b) / 255.0,
+        f32::from(color.a) / 255.0,
+    ]
+}
+
+#[inline]
+pub fn lerp(a: Rgba, b: Rgba, t: f32) -> Rgba {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+        a[3] + (b[3] - a[3]) * t,
+    ]
+}
+
+/// An image stored as 8-bit RGBA with premultiplied alpha,
+/// which is the same format that the GPU backends use for their textures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pixmap {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Pixmap {
+    /// Creates a fully transparent pixmap.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    pub fn filled(width: u32, height: u32, color: swf::Color) -> Self {
+        let pixel = [color.r, color.g, color.b, color.a];
+        Self {
+            width,
+            height,
+            data: pixel.repeat(width as usize * height as usize),
+        }
+    }
+
+    /// Creates a pixmap out of the pixels of a bitmap.
+    pub fn from_bitmap(bitmap: Bitmap) -> Self {
+        let bitmap = bitmap.to_rgba();
+        Self::from_rgba(bitmap.width(), bitmap.height(), bitmap.data().to_vec())
+    }
+
+    /// Creates a pixmap out of premultiplied RGBA data, which must be of the correct size.
+    pub fn from_rgba(width: u32, height: u32, data: Vec<u8>) -> Self {
+        debug_assert_eq!(data.len(), width as usize * height as usize * 4);
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    #[inline]
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Rgba {
+        let pixel = &self.data[index * 4..index * 4 + 4];
+        [
+            f32::from(pixel[0]) / 255.0,
+            f32::from(pixel[1]) / 255.0,
+            f32::from(pixel[2]) / 255.0,
+            f32::from(pixel[3]) / 255.0,
+        ]
+    }
+
+    #[inline]
+    pub fn set_index(&mut self, index: usize, color: Rgba) {
+        let pixel = &mut self.data[index * 4..index * 4 + 4];
+        pixel[0] = to_u8(color[0]);
+        pixel[1] = to_u8(color[1]);
+        pixel[2] = to_u8(color[2]);
+        pixel[3] = to_u8(color[3]);
+    }
+
+    #[inline]
+    pub fn get(&self, x: u32, y: u32) -> Rgba {
+        self.get_index(y as usize * self.width as usize + x as usize)
+    }
+
+    #[inline]
+    pub fn set(&mut self, x: u32, y: u32, color: Rgba) {
+        self.set_index(y as usize * self.width as usize + x as usize, color)
+    }
+
+    /// Returns the pixel at the given coordinates, which may lie outside of the pixmap.
+    /// Coordinates outside of it either wrap around or are clamped to the nearest edge.
+    #[inline]
+    fn get_wrapped(&self, x: i64, y: i64, repeat: bool) -> Rgba {
+        let (width, height) = (i64::from(self.width), i64::from(self.height));
+        let (x, y) = if repeat {
+            (x.rem_euclid(width), y.rem_euclid(height))
+        } else {
+            (x.clamp(0, width - 1), y.clamp(0, height - 1))
+        };
+        self.get(x as u32, y as u32)
+    }
+
+    /// Samples the pixmap like a GPU texture would, at a position in pixels.
+    ///
+    /// With `smoothing`, the four nearest pixels are bilinearly interpolated.
+    /// Positions outside of the pixmap either repeat it, or use its nearest edge.
+    pub fn sample(&self, x: f64, y: f64, smoothing: bool, repeat: bool) -> Rgba {
+        if self.width == 0 || self.height == 0 || !x.is_finite() || !y.is_finite() {
+            return TRANSPARENT;
+        }
+
+        if !smoothing {
+            return self.get_wrapped(x.floor() as i64, y.floor() as i64, repeat);
+        }
+
+        let (x, y) = (x - 0.5, y - 0.5);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = ((x - x0) as f32, (y - y0) as f32);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let top = lerp(
+            self.get_wrapped(x0, y0, repeat),
+            self.get_wrapped(x0 + 1, y0, repeat),
+            tx,
+        );
+        let bottom = lerp(
+            self.get_wrapped(x0, y0 + 1, repeat),
+            self.get_wrapped(x0 + 1, y0 + 1, repeat),
+            tx,
+        );
+        lerp(top, bottom, ty)
+    }
+
+    /// Scales the pixmap up by an integer factor, repeating every pixel.
+    pub fn upscale(&self, factor: u32) -> Self {
+        if factor == 1 {
+            return self.clone();
+        }
+
+        let width = self.width * factor;
+        let mut data = Vec::with_capacity(self.data.len() * (factor * factor) as usize);
+        for row in self.data.chunks_exact(self.width as usize * 4) {
+            let start = data.len();
+            for pixel in row.chunks_exact(4) {
+                for _ in 0..factor {
+                    data.extend_from_slice(pixel);
+                }
+            }
+            for _ in 1..factor {
+                data.extend_from_within(start..start + width as usize * 4);
+            }
+        }
+
+        Self {
+            width,
+            height: self.height * factor,
+            data,
+        }
+    }
+
+    /// Scales the pixmap down by an integer factor, averaging every block of pixels.
+    pub fn downscale(&self, factor: u32) -> Self {
+        if factor == 1 {
+            return self.clone();
+        }
+
+        let mut result = Self::new(self.width / factor, self.height / factor);
+        let factor = factor as usize;
+        let divisor = (factor * factor) as u32;
+        let stride = self.width as usize * 4;
+        for y in 0..result.height as usize {
+            for x in 0..result.width as usize {
+                let mut total = [0u32; 4];
+                for row in 0..factor {
+                    let start = (y * factor + row) * stride + x * factor * 4;
+                    for pixel in self.data[start..start + factor * 4].chunks_exact(4) {
+                        for (total, &channel) in total.iter_mut().zip(pixel) {
+                            *total += u32::from(channel);
+                        }
+                    }
+                }
+
+                let index = (y * result.width as usize + x) * 4;
+                for (channel, total) in total.into_iter().enumerate() {
+                    result.data[index + channel] = ((total + divisor / 2) / divisor) as u8;
+                }
+            }
+        }
+        result
+    }
+
+    /// Copies a region of this pixmap into a new one.
+    /// Parts of the region which are outside of this pixmap are transparent.
+    pub fn region(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+        let mut result = Self::new(width, height);
+        result.copy_from(self, (x, y), (0, 0), (width, height));
+        result
+    }
+
+    /// Copies `size` pixels from `source` at `source_point` into this pixmap at `dest_point`,
+    /// clipping away anything outside of either pixmap.
+    pub fn copy_from(
+        &mut self,
+        source: &Pixmap,
+        source_point: (u32, u32),
+        dest_point: (u32, u32),
+        size: (u32, u32),
+    ) {
+        let width = size
+            .0
+            .min(source.width.saturating_sub(source_point.0))
+            .min(self.width.saturating_sub(dest_point.0)) as usize;
+        let height = size
+            .1
+            .min(source.height.saturating_sub(source_point.1))
+            .min(self.height.saturating_sub(dest_point.1)) as usize;
+
+        for row in 0..height {
+            let source_start = ((source_point.1 as usize + row) * source.width as usize
+                + source_point.0 as usize)
+                * 4;
+            let dest_start =
+                ((dest_point.1 as usize + row) * self.width as usize + dest_point.0 as usize) * 4;
+            self.data[dest_start..dest_start + width * 4]
+                .copy_from_slice(&source.data[source_start..source_start + width * 4]);
+        }
+    }
+
+    /// Returns the pixels inside of `region` as tightly packed rows.
+    pub fn read_region(&self, region: PixelRegion) -> Vec<u8> {
+        self.region(region.x_min, region.y_min, region.width(), region.height())
+            .data
+    }
+}
+
+/// A copy of the pixels of a region of a pixmap, taken right after it was drawn to.
+#[derive(Debug)]
+pub struct PixmapSyncHandle {
+    data: Vec<u8>,
+    width: u32,
+}
+
+impl SyncHandle for PixmapSyncHandle {}
+
+impl PixmapSyncHandle {
+    pub fn new(pixmap: &Pixmap, region: PixelRegion) -> Box<Self> {
+        Box::new(Self {
+            data: pixmap.read_region(region),
+            width: region.width(),
+        })
+    }
+
+    /// Passes the copied pixels to `with_rgba`, like `RenderBackend::resolve_sync_handle` does.
+    pub fn resolve(self, with_rgba: RgbaBufRead) {
+        with_rgba(&self.data, self.width * 4);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixmap_2x2() -> Pixmap {
+        Pixmap::from_rgba(
+            2,
+            2,
+            vec![
+                255, 0, 0, 255, 0, 255, 0, 255, //
+                0, 0, 255, 255, 0, 0, 0, 0,
+            ],
+        )
+    }
+
+    #[test]
+    fn upscale_then_downscale_is_lossless() {
+        let pixmap = pixmap_2x2();
+        let upscaled = pixmap.upscale(3);
+        assert_eq!(upscaled.width(), 6);
+        assert_eq!(upscaled.height(), 6);
+        assert_eq!(upscaled.get(2, 2), pixmap.get(0, 0));
+        assert_eq!(upscaled.get(3, 2), pixmap.get(1, 0));
+        assert_eq!(upscaled.get(5, 5), pixmap.get(1, 1));
+        assert_eq!(upscaled.downscale(3), pixmap);
+    }
+
+    #[test]
+    fn downscale_averages() {
+        let downscaled = pixmap_2x2().downscale(2);
+        assert_eq!(downscaled.data(), &[64, 64, 64, 191]);
+    }
+
+    #[test]
+    fn sample_nearest_and_repeat() {
+        let pixmap = pixmap_2x2();
+        assert_eq!(pixmap.sample(1.5, 0.5, false, false), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(pixmap.sample(3.5, 0.5, false, false), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(pixmap.sample(2.5, 0.5, false, true), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(pixmap.sample(-0.5, 1.5, false, true), [0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn sample_bilinear() {
+        let pixmap = pixmap_2x2();
+        assert_eq!(pixmap.sample(1.0, 0.5, true, false), [0.5, 0.5, 0.0, 1.0]);
+        assert_eq!(
+            pixmap.sample(1.0, 1.0, true, false),
+            [0.25, 0.25, 0.25, 0.75]
+        );
+    }
+
+    #[test]
+    fn copy_is_clipped() {
+        let mut dest = Pixmap::new(3, 1);
+        dest.copy_from(&pixmap_2x2(), (0, 0), (2, 0), (2, 2));
+        assert_eq!(dest.data(), &[0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 255]);
+    }
+}
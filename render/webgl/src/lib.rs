@@ -13,7 +13,9 @@ use ruffle_render::bitmap::{
 };
 use ruffle_render::commands::{CommandHandler, CommandList, RenderBlendMode};
 use ruffle_render::error::Error as BitmapError;
+use ruffle_render::filters::{cpu, Filter};
 use ruffle_render::matrix::Matrix;
+use ruffle_render::pixmap::{Pixmap, PixmapSyncHandle};
 use ruffle_render::quality::StageQuality;
 use ruffle_render::shape_utils::{DistilledShape, GradientType};
 use ruffle_render::tessellator::{
@@ -981,6 +983,97 @@ impl WebGlRenderBackend {
         self.gl
             .draw_elements_with_i32(MODE, count, Gl::UNSIGNED_INT, 0);
     }
+
+    /// Reads the pixels of a bitmap back from its texture, by attaching it to a temporary framebuffer.
+    fn read_texture(&self, bitmap: &RegistryData) -> Result<Pixmap, Error> {
+        let gl = &self.gl;
+        let framebuffer = gl
+            .create_framebuffer()
+            .ok_or(Error::UnableToCreateFrameBuffer)?;
+        gl.bind_framebuffer(Gl::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(
+            Gl::FRAMEBUFFER,
+            Gl::COLOR_ATTACHMENT0,
+            Gl::TEXTURE_2D,
+            Some(&bitmap.texture),
+            0,
+        );
+
+        let mut data = vec![0; bitmap.width as usize * bitmap.height as usize * 4];
+        let result = if gl.check_framebuffer_status(Gl::FRAMEBUFFER) == Gl::FRAMEBUFFER_COMPLETE {
+            gl.read_pixels_with_opt_u8_array(
+                0,
+                0,
+                bitmap.width as i32,
+                bitmap.height as i32,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                Some(&mut data),
+            )
+            .into_js_result()
+            .map_err(Error::from)
+        } else {
+            Err(Error::UnableToCreateFrameBuffer)
+        };
+
+        // Frames always bind their own framebuffer, so there's nothing else to restore.
+        gl.bind_framebuffer(Gl::FRAMEBUFFER, None);
+        gl.delete_framebuffer(Some(&framebuffer));
+        result?;
+
+        Ok(Pixmap::from_rgba(bitmap.width, bitmap.height, data))
+    }
+
+    /// Replaces the pixels of a bitmap with the pixels of `pixmap`, which must be the same size.
+    fn write_texture(&self, bitmap: &RegistryData, pixmap: &Pixmap) -> Result<(), Error> {
+        self.gl.bind_texture(Gl::TEXTURE_2D, Some(&bitmap.texture));
+        self.gl
+            .tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::RGBA as i32,
+                pixmap.width() as i32,
+                pixmap.height() as i32,
+                0,
+                Gl::RGBA,
+                Gl::UNSIGNED_BYTE,
+                Some(pixmap.data()),
+            )
+            .into_js_result()?;
+        Ok(())
+    }
+
+    /// Applies a filter on the CPU, as WebGL doesn't have shaders for any of them.
+    fn apply_filter_on_cpu(
+        &self,
+        source: &BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: &BitmapHandle,
+        dest_point: (u32, u32),
+        filter: &Filter,
+    ) -> Result<Box<dyn SyncHandle>, Error> {
+        let source = self.read_texture(as_registry_data(source))?.region(
+            source_point.0,
+            source_point.1,
+            source_size.0,
+            source_size.1,
+        );
+        let filtered = cpu::apply_filter(&source, filter, |handle| {
+            self.read_texture(as_registry_data(handle))
+                .map_err(|e| log::error!("Couldn't read displacement map: {:?}", e))
+                .ok()
+        });
+
+        let destination = as_registry_data(destination);
+        let mut pixmap = self.read_texture(destination)?;
+        pixmap.copy_from(&filtered, (0, 0), dest_point, source_size);
+        self.write_texture(destination, &pixmap)?;
+        Ok(PixmapSyncHandle::new(
+            &pixmap,
+            PixelRegion::for_whole_size(pixmap.width(), pixmap.height()),
+        ))
+    }
 }
 
 fn same_blend_mode(first: Option<&RenderBlendMode>, second: &RenderBlendMode) -> bool {
@@ -1001,6 +1094,31 @@ impl RenderBackend for WebGlRenderBackend {
         None
     }
 
+    fn apply_filter(
+        &mut self,
+        source: BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: BitmapHandle,
+        dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        self.apply_filter_on_cpu(
+            &source,
+            source_point,
+            source_size,
+            &destination,
+            dest_point,
+            &filter,
+        )
+        .map_err(|e| log::error!("Couldn't apply filter: {:?}", e))
+        .ok()
+    }
+
+    fn is_filter_supported(&self, filter: &Filter) -> bool {
+        cpu::is_filter_supported(filter)
+    }
+
     fn viewport_dimensions(&self) -> ViewportDimensions {
         ViewportDimensions {
             width: self.renderbuffer_width as u32,
@@ -1206,12 +1324,14 @@ impl RenderBackend for WebGlRenderBackend {
 
     fn resolve_sync_handle(
         &mut self,
-        _handle: Box<dyn SyncHandle>,
-        _with_rgba: RgbaBufRead,
+        handle: Box<dyn SyncHandle>,
+        with_rgba: RgbaBufRead,
     ) -> Result<(), ruffle_render::error::Error> {
-        Err(ruffle_render::error::Error::Unimplemented(
-            "Sync handle resolution".into(),
-        ))
+        let handle = handle.downcast::<PixmapSyncHandle>().map_err(|_| {
+            ruffle_render::error::Error::Unimplemented("Foreign sync handle".into())
+        })?;
+        handle.resolve(with_rgba);
+        Ok(())
     }
 
     fn run_pixelbender_shader(
@@ -0,0 +1,63 @@
+// NOTE: The `shader_filter_common.wgsl` source is prepended to this before compilation.
+
+struct Filter {
+    /// The matrix, row by row, packed 4 values at a time. Up to 15x15 values are used.
+    matrix: array<vec4<f32>, 64>,
+    /// The color of pixels outside of the source, with straight alpha.
+    default_color: vec4<f32>,
+    /// The pixels of the source texture that are being filtered (left, top, right, bottom).
+    source_rect: vec4<u32>,
+    bias: f32,
+    divisor: f32,
+    columns: u32,
+    rows: u32,
+    clamp: u32,
+    preserve_alpha: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> filter_args: Filter;
+
+@vertex
+fn main_vertex(in: filter__VertexInput) -> filter__VertexOutput {
+    return filter__main_vertex(in);
+}
+
+/// Loads a pixel of the source with straight alpha, handling pixels outside of the source like Flash.
+fn load_straight(position: vec2<i32>) -> vec4<f32> {
+    let top_left = vec2<i32>(filter_args.source_rect.xy);
+    let bottom_right = vec2<i32>(filter_args.source_rect.zw) - 1;
+    var color: vec4<f32>;
+    if (all(position >= top_left) && all(position <= bottom_right)) {
+        color = textureLoad(texture, position, 0);
+    } else if (filter_args.clamp > 0u) {
+        color = textureLoad(texture, clamp(position, top_left, bottom_right), 0);
+    } else {
+        return filter_args.default_color;
+    }
+    if (color.a > 0.0) {
+        return vec4<f32>(color.rgb / color.a, color.a);
+    }
+    return vec4<f32>(0.0);
+}
+
+@fragment
+fn main_fragment(in: filter__VertexOutput) -> @location(0) vec4<f32> {
+    let position = vec2<i32>(floor(in.position.xy)) + vec2<i32>(filter_args.source_rect.xy);
+    let center = vec2<i32>(vec2<u32>(filter_args.columns / 2u, filter_args.rows / 2u));
+
+    var total = vec4<f32>(0.0);
+    for (var y = 0u; y < filter_args.rows; y += 1u) {
+        for (var x = 0u; x < filter_args.columns; x += 1u) {
+            let index = y * filter_args.columns + x;
+            let weight = filter_args.matrix[index / 4u][index % 4u];
+            total += load_straight(position + vec2<i32>(vec2<u32>(x, y)) - center) * weight;
+        }
+    }
+
+    var color = saturate(total / filter_args.divisor + filter_args.bias / 255.0);
+    if (filter_args.preserve_alpha > 0u) {
+        color.a = load_straight(position).a;
+    }
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
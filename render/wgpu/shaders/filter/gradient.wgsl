@@ -0,0 +1,94 @@
+struct Filter {
+    /// The premultiplied colors of the gradient, looked up by the strength of the glow or bevel.
+    colors: array<vec4<f32>, 256>,
+    strength: f32,
+    filter_type: u32, // 0 outer, 1 inner, 2 full
+    knockout: u32,
+    bevel: u32,
+}
+
+@group(0) @binding(0) var texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+@group(0) @binding(2) var<uniform> filter_args: Filter;
+@group(0) @binding(3) var blurred: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) source_uv: vec2<f32>,
+    @location(1) blur_uv_left: vec2<f32>,
+    @location(2) blur_uv_right: vec2<f32>,
+};
+
+struct VertexInput {
+    /// The position of the vertex in texture space (topleft 0,0, bottomright 1,1)
+    @location(0) position: vec2<f32>,
+
+    /// The coordinate of the source texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(1) source_uv: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    @location(2) blur_uv_left: vec2<f32>,
+
+    /// The coordinate of the blur texture to sample in texture space (topleft 0,0, bottomright 1,1)
+    /// This is only used by bevels.
+    @location(3) blur_uv_right: vec2<f32>,
+};
+
+@vertex
+fn main_vertex(in: VertexInput) -> VertexOutput {
+    // Convert texture space (topleft 0,0 to bottomright 1,1) to render space (topleft -1,1 to bottomright 1,-1)
+    let pos = vec4<f32>((in.position.x * 2.0 - 1.0), (1.0 - in.position.y * 2.0), 0.0, 1.0);
+    return VertexOutput(pos, in.source_uv, in.blur_uv_left, in.blur_uv_right);
+}
+
+fn sample_blur(uv: vec2<f32>) -> f32 {
+    // Sampling has to happen in uniform control flow, so this can't return early.
+    let blur = textureSample(blurred, texture_sampler, uv).a;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return 0.0;
+    }
+    return blur;
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let knockout = filter_args.knockout > 0u;
+    let blur_left = sample_blur(in.blur_uv_left);
+    let blur_right = sample_blur(in.blur_uv_right);
+    let dest = textureSample(texture, texture_sampler, in.source_uv);
+
+    let outer = filter_args.filter_type == 0u || filter_args.filter_type == 2u;
+    let inner = filter_args.filter_type == 1u || filter_args.filter_type == 2u;
+
+    // Bevels put their highlight at the start of the gradient and their shadow at the end of it,
+    // while glows go from the outside of the glow to its strongest part.
+    var position: f32;
+    if (filter_args.bevel > 0u) {
+        position = 0.5 - 0.5 * clamp((blur_left - blur_right) * filter_args.strength, -1.0, 1.0);
+    } else if (inner && !outer) {
+        position = saturate((1.0 - blur_left) * filter_args.strength);
+    } else {
+        position = saturate(blur_left * filter_args.strength);
+    }
+    let glow = filter_args.colors[u32(round(position * 255.0))];
+
+    if (inner && outer) {
+        if (knockout) {
+            return glow;
+        } else {
+            return dest - dest * glow.a + glow;
+        }
+    } else if (inner) {
+        if (knockout) {
+            return glow * dest.a;
+        } else {
+            return glow * dest.a + dest * (1.0 - glow.a);
+        }
+    } else {
+        if (knockout) {
+            return glow - glow * dest.a;
+        } else {
+            return dest + glow - glow * dest.a;
+        }
+    }
+}
@@ -1,8 +1,34 @@
 //! Post-scan analysis
 
-use crate::cli_options::AnalyzeOpt;
+use crate::cli_options::{AnalyzeOpt, ResultsFormat};
 use crate::file_results::{FileResults, Step};
-use std::fs::File;
+use crate::results::read_results;
+use std::collections::HashMap;
+
+/// How many of the most common stubs and errors to list.
+const TOP_ENTRIES: usize = 25;
+
+/// Print the most common entries of a tally, along with how many files each
+/// one affected.
+fn print_ranking(title: &str, tally: HashMap<String, usize>) {
+    if tally.is_empty() {
+        return;
+    }
+
+    let mut ranking: Vec<_> = tally.into_iter().collect();
+    ranking.sort_by(|(a_name, a_count), (b_name, b_count)| {
+        b_count.cmp(a_count).then_with(|| a_name.cmp(b_name))
+    });
+
+    println!("{title} (by number of affected movies):");
+    for (name, count) in ranking.iter().take(TOP_ENTRIES) {
+        println!("{count:>8}  {name}");
+    }
+    if ranking.len() > TOP_ENTRIES {
+        println!("{:>8}  ...and {} more", "", ranking.len() - TOP_ENTRIES);
+    }
+    println!();
+}
 
 /// Generate and print statistics related to a scan's results
 pub fn analyze(results: impl Iterator<Item = FileResults>) {
@@ -13,10 +39,23 @@ pub fn analyze(results: impl Iterator<Item = FileResults>) {
     let mut parse = 0;
     let mut execute = 0;
     let mut complete = 0;
+    let mut stubs = HashMap::new();
+    let mut error_classes = HashMap::new();
+    let mut compile_errors = 0;
 
     for result in results {
         total += 1;
 
+        for stub in result.stubs {
+            *stubs.entry(stub).or_insert(0) += 1;
+        }
+        if let Some(error_class) = result.avm2_error_class {
+            *error_classes.entry(error_class).or_insert(0) += 1;
+        }
+        if result.avm2_compile_error.is_some() {
+            compile_errors += 1;
+        }
+
         match result.progress {
             Step::Start => start += 1,
             Step::Read => read += 1,
@@ -48,19 +87,29 @@ pub fn analyze(results: impl Iterator<Item = FileResults>) {
     println!("{execute:>digits$} movies failed to execute");
     println!("{complete:>digits$} movies completed without errors");
     println!();
+
+    if compile_errors > 0 {
+        println!("{compile_errors:>digits$} movies failed to load or verify AVM2 code");
+        println!();
+    }
+
+    print_ranking("Most common stubs", stubs);
+    print_ranking("Most common AVM2 errors", error_classes);
 }
 
 pub fn analyze_main(opt: AnalyzeOpt) -> Result<(), std::io::Error> {
-    let file = File::open(opt.input_path)?;
-    let reader = csv::Reader::from_reader(file);
+    let format = opt
+        .format
+        .unwrap_or_else(|| ResultsFormat::from_path(&opt.input_path));
+    let results = read_results(&opt.input_path, format)?;
 
-    analyze(reader.into_deserialize::<FileResults>().map(|r| {
+    analyze(results.into_iter().map(|r| {
         match r {
             Ok(fr) => fr,
             Err(e) => {
-                // Treat unparsable CSV rows as a scanner panic
+                // Treat unparsable results as a scanner panic
                 FileResults {
-                    error: Some(format!("{e}")),
+                    error: Some(e),
                     ..FileResults::default()
                 }
             }
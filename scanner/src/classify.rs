@@ -0,0 +1,120 @@
+//! Classification of errors logged while running a movie
+
+/// The prefix Ruffle logs ABC files that failed to load with.
+const ABC_LOAD_ERROR: &str = "Error loading ABC file: ";
+
+/// Find the class of the first AVM2 error in a logged message.
+///
+/// AVM2 errors are logged as `<class>: <message>`, usually after some text
+/// describing what was running when the error was thrown, such as
+/// `Error running abc/symbol in frame: TypeError: Error #1009: ...`.
+pub fn avm2_error_class(message: &str) -> Option<&str> {
+    message.match_indices(": ").find_map(|(end, _)| {
+        let before = &message[..end];
+        let name = &before[before
+            .trim_end_matches(|c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            .len()..];
+
+        (name.ends_with("Error") && name.starts_with(|c: char| c.is_ascii_uppercase()))
+            .then_some(name)
+    })
+}
+
+/// Find the first error that stopped AVM2 code from being compiled, given
+/// the errors and warnings logged while running a movie.
+///
+/// This is either an ABC file that could not be loaded at all, or a method
+/// that failed verification.
+pub fn avm2_compile_error(errors: &[String], warnings: &[String]) -> Option<String> {
+    if let Some(error) = warnings
+        .iter()
+        .find_map(|warning| warning.strip_prefix(ABC_LOAD_ERROR))
+    {
+        return Some(error.to_string());
+    }
+
+    errors.iter().find_map(|error| {
+        let (_, rest) = error.split_once("VerifyError: ")?;
+        let message = rest.lines().next().unwrap_or(rest);
+        (avm2_error_class(error) == Some("VerifyError")).then(|| message.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_class_after_context() {
+        assert_eq!(
+            avm2_error_class(
+                "Error running abc/symbol in frame: TypeError: Error #1009: Cannot access a property or method of a null object reference."
+            ),
+            Some("TypeError")
+        );
+    }
+
+    #[test]
+    fn error_class_at_start() {
+        assert_eq!(
+            avm2_error_class("ReferenceError: Error #1065: Variable Foo is not defined."),
+            Some("ReferenceError")
+        );
+    }
+
+    #[test]
+    fn error_class_with_identifier_characters() {
+        assert_eq!(
+            avm2_error_class("Uncaught error: My_Custom$Error: Oops"),
+            Some("My_Custom$Error")
+        );
+    }
+
+    #[test]
+    fn error_class_is_first_one() {
+        assert_eq!(
+            avm2_error_class("RangeError: Error #1125: ArgumentError: Error #1063"),
+            Some("RangeError")
+        );
+    }
+
+    #[test]
+    fn error_class_skips_other_words() {
+        assert_eq!(avm2_error_class("Note: something happened"), None);
+        assert_eq!(avm2_error_class("Error running script: timeout"), None);
+        assert_eq!(avm2_error_class("customError: not a class"), None);
+        assert_eq!(avm2_error_class("TypeError without a message"), None);
+    }
+
+    #[test]
+    fn compile_error_from_abc_load_warning() {
+        let warnings = vec![
+            "Unrelated warning".to_string(),
+            "Error loading ABC file: Invalid method body".to_string(),
+        ];
+        let errors = vec!["VerifyError: Error #1021: At least one branch target was not on a valid instruction in the method.".to_string()];
+        assert_eq!(
+            avm2_compile_error(&errors, &warnings).as_deref(),
+            Some("Invalid method body")
+        );
+    }
+
+    #[test]
+    fn compile_error_from_verify_error() {
+        let errors = vec![
+            "Error running abc/symbol in frame: TypeError: Error #1009: Cannot access a property or method of a null object reference.".to_string(),
+            "Error running abc/symbol in frame: VerifyError: Error #1014: Class Foo could not be found.\n\tat Main()".to_string(),
+        ];
+        assert_eq!(
+            avm2_compile_error(&errors, &[]).as_deref(),
+            Some("Error #1014: Class Foo could not be found.")
+        );
+    }
+
+    #[test]
+    fn compile_error_ignores_other_classes() {
+        let errors = vec!["Error running script: NotAVerifyError: Error #1014".to_string()];
+        assert_eq!(avm2_compile_error(&errors, &[]), None);
+        assert_eq!(avm2_compile_error(&[], &[]), None);
+    }
+}
@@ -1,7 +1,7 @@
 //! CLI Options
 
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{Parser, ValueEnum};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[clap(version, about, author)]
@@ -28,20 +28,76 @@ pub struct ScanOpt {
     #[clap(name = "directory")]
     pub input_path: PathBuf,
 
-    /// The file to store results in CSV format
+    /// The file to store results in
     #[clap(name = "results")]
     pub output_path: PathBuf,
 
     /// Filenames to ignore
     #[clap(short = 'i', long = "ignore", action = clap::ArgAction::Append)]
     pub ignore: Vec<String>,
+
+    /// The format to store results in.
+    ///
+    /// Defaults to the format matching the extension of the results file,
+    /// or CSV if it isn't recognized.
+    #[clap(long = "format", value_enum)]
+    pub format: Option<ResultsFormat>,
+
+    /// Skip files that already have results in the results file, and add
+    /// new results to it instead of replacing it
+    #[clap(long = "resume")]
+    pub resume: bool,
+
+    /// How many seconds a single file may run for before it is killed
+    #[clap(long = "timeout", default_value = "300")]
+    pub timeout: u64,
+
+    /// How many megabytes of memory a single file may allocate before it is
+    /// killed
+    #[clap(long = "memory-limit")]
+    pub memory_limit: Option<usize>,
+
+    /// How many files to scan at once. Defaults to the number of CPUs
+    #[clap(short = 'j', long = "jobs")]
+    pub jobs: Option<usize>,
+}
+
+/// The file formats that scan results can be stored in.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultsFormat {
+    /// Comma-separated values, with a header row
+    Csv,
+
+    /// A single JSON array of results
+    Json,
+
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl ResultsFormat {
+    /// Guess the format of a results file from its extension.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => ResultsFormat::Json,
+            Some("ndjson" | "jsonl") => ResultsFormat::Ndjson,
+            _ => ResultsFormat::Csv,
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
 pub struct AnalyzeOpt {
-    /// The CSV file to reanalyze
+    /// The results file to reanalyze
     #[clap(name = "input")]
     pub input_path: PathBuf,
+
+    /// The format of the results file.
+    ///
+    /// Defaults to the format matching its extension, or CSV if it isn't
+    /// recognized.
+    #[clap(long = "format", value_enum)]
+    pub format: Option<ResultsFormat>,
 }
 
 #[derive(Parser, Debug)]
@@ -49,4 +105,8 @@ pub struct ExecuteReportOpt {
     /// The single SWF file to parse and run
     #[clap(name = "file")]
     pub input_path: PathBuf,
+
+    /// How many megabytes of memory may be allocated before aborting
+    #[clap(long = "memory-limit")]
+    pub memory_limit: Option<usize>,
 }
@@ -1,8 +1,10 @@
 //! Child/executor process impls
 
+use crate::classify::{avm2_compile_error, avm2_error_class};
 use crate::cli_options::ExecuteReportOpt;
 use crate::file_results::{AvmType, FileResults, Step};
-use crate::logging::{ScanLogBackend, ThreadLocalScanLogger, LOCAL_LOGGER};
+use crate::logging::{ScanLogBackend, ThreadLocalScanLogger, LOCAL_LOGGER, LOCAL_WARNINGS};
+use crate::memory_limit::set_memory_limit;
 use ruffle_core::backend::navigator::{NullExecutor, NullNavigatorBackend};
 use ruffle_core::limits::ExecutionLimit;
 use ruffle_core::swf::{decompress_swf, parse_swf};
@@ -14,7 +16,8 @@ use std::panic::catch_unwind;
 use std::path::Path;
 use std::time::{Duration, Instant};
 
-fn execute_swf(file: &Path) {
+/// Run one frame of a movie, returning the stubs that were hit.
+fn execute_swf(file: &Path) -> Vec<String> {
     let base_path = file.parent().unwrap();
     let executor = NullExecutor::new();
     let movie = SwfMovie::from_path(file, None).unwrap();
@@ -31,6 +34,16 @@ fn execute_swf(file: &Path) {
     player.lock().unwrap().run_frame();
     player.lock().unwrap().update_timers(frame_time);
     //executor.poll_all().unwrap();
+
+    let mut stubs: Vec<String> = player
+        .lock()
+        .unwrap()
+        .stubs()
+        .iter()
+        .map(|stub| stub.to_string())
+        .collect();
+    stubs.sort();
+    stubs
 }
 
 fn checkpoint<W: Write>(
@@ -43,6 +56,9 @@ fn checkpoint<W: Write>(
     file_result.testing_time = start.elapsed().as_millis();
     writer.serialize(file_result).unwrap();
 
+    // Make sure the scanner sees how far we got, even if we're killed.
+    writer.flush()?;
+
     if has_error {
         Err(std::io::Error::other("Error encountered, test terminated"))
     } else {
@@ -53,6 +69,10 @@ fn checkpoint<W: Write>(
 pub fn execute_report_main(execute_report_opt: ExecuteReportOpt) -> Result<(), std::io::Error> {
     ThreadLocalScanLogger::init();
 
+    if let Some(megabytes) = execute_report_opt.memory_limit {
+        set_memory_limit(megabytes.saturating_mul(1024 * 1024));
+    }
+
     let start = Instant::now();
     let file_path = execute_report_opt.input_path;
     let name = file_path
@@ -64,6 +84,9 @@ pub fn execute_report_main(execute_report_opt: ExecuteReportOpt) -> Result<(), s
     LOCAL_LOGGER.with(|log_buffer| {
         log_buffer.borrow_mut().truncate(0);
     });
+    LOCAL_WARNINGS.with(|log_buffer| {
+        log_buffer.borrow_mut().truncate(0);
+    });
 
     let mut file_result = FileResults::new(&name);
 
@@ -147,8 +170,9 @@ pub fn execute_report_main(execute_report_opt: ExecuteReportOpt) -> Result<(), s
     file_result.progress = Step::Execute;
 
     //Run one frame of the movie in Ruffle.
-    if let Err(e) = catch_unwind(|| execute_swf(&file_path)) {
-        match e.downcast::<String>() {
+    match catch_unwind(|| execute_swf(&file_path)) {
+        Ok(stubs) => file_result.stubs = stubs,
+        Err(e) => match e.downcast::<String>() {
             Ok(e) => {
                 file_result.error = Some(format!("PANIC: {e}"));
                 checkpoint(&mut file_result, &start, &mut writer)?;
@@ -157,14 +181,24 @@ pub fn execute_report_main(execute_report_opt: ExecuteReportOpt) -> Result<(), s
                 file_result.error = Some("PANIC".to_string());
                 checkpoint(&mut file_result, &start, &mut writer)?;
             }
-        }
+        },
     }
 
-    let errors = LOCAL_LOGGER.with(|log_buffer| {
+    let (errors, warnings) = LOCAL_LOGGER.with(|log_buffer| {
         log_buffer.borrow_mut().dedup();
 
-        log_buffer.borrow_mut().join("\n")
+        let warnings = LOCAL_WARNINGS.with(|warnings| warnings.borrow().clone());
+        (log_buffer.borrow().clone(), warnings)
     });
+    if matches!(file_result.vm_type, Some(AvmType::Avm2)) {
+        file_result.avm2_error_class = errors
+            .iter()
+            .find_map(|error| avm2_error_class(error))
+            .map(str::to_string);
+        file_result.avm2_compile_error = avm2_compile_error(&errors, &warnings);
+    }
+
+    let errors = errors.join("\n");
     if !errors.is_empty() {
         file_result.error = Some(errors);
     } else {
@@ -126,6 +126,23 @@ pub struct FileResults {
     /// The AVM type of the movie.
     #[serde(rename = "AVM Version")]
     pub vm_type: Option<AvmType>,
+
+    /// The stubs that were hit while running the movie.
+    #[serde(
+        rename = "Stubs",
+        default,
+        serialize_with = "into_lines",
+        deserialize_with = "from_lines"
+    )]
+    pub stubs: Vec<String>,
+
+    /// The class of the first AVM2 error that was thrown and not caught.
+    #[serde(rename = "AVM2 Error Class", default)]
+    pub avm2_error_class: Option<String>,
+
+    /// The first error that stopped AVM2 code from being loaded or verified.
+    #[serde(rename = "AVM2 Compile Error", default)]
+    pub avm2_compile_error: Option<String>,
 }
 
 impl Default for FileResults {
@@ -153,6 +170,9 @@ impl FileResults {
             use_gpu: None,
             use_network_sandbox: None,
             vm_type: None,
+            stubs: vec![],
+            avm2_error_class: None,
+            avm2_compile_error: None,
         }
     }
 }
@@ -207,3 +227,25 @@ where
 
     d.deserialize_str(HexVisitor())
 }
+
+/// Formats a list of strings as a single string, one per line
+fn into_lines<S>(lines: &[String], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&lines.join("\n"))
+}
+
+/// Parses a string with one list entry per line
+fn from_lines<'de, D>(d: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let lines = String::deserialize(d)?;
+
+    Ok(lines
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
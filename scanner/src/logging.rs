@@ -21,10 +21,13 @@ impl LogBackend for ScanLogBackend {
 thread_local! {
     /// Thread local log buffer.
     pub static LOCAL_LOGGER: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
+
+    /// Thread local buffer of warnings.
+    pub static LOCAL_WARNINGS: Rc<RefCell<Vec<String>>> = Rc::new(RefCell::new(Vec::new()));
 }
 
 /// `log` backend (not to be confused with Ruffle's notion of a log backend)
-/// that only logs errors to a thread-local area.
+/// that only logs errors and warnings to a thread-local area.
 pub struct ThreadLocalScanLogger();
 
 static GLOBAL_LOGGER: ThreadLocalScanLogger = ThreadLocalScanLogger();
@@ -39,14 +42,18 @@ impl ThreadLocalScanLogger {
 
 impl Log for ThreadLocalScanLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() == Level::Error
+        metadata.level() <= Level::Warn
     }
 
     fn log(&self, record: &Record) {
-        if self.enabled(record.metadata()) {
-            LOCAL_LOGGER.with(|log_buffer| {
+        match record.level() {
+            Level::Error => LOCAL_LOGGER.with(|log_buffer| {
+                log_buffer.borrow_mut().push(format!("{}", record.args()));
+            }),
+            Level::Warn => LOCAL_WARNINGS.with(|log_buffer| {
                 log_buffer.borrow_mut().push(format!("{}", record.args()));
-            })
+            }),
+            _ => {}
         }
     }
 
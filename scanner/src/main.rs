@@ -5,10 +5,13 @@ use crate::scan::scan_main;
 use clap::Parser;
 
 mod analyze;
+mod classify;
 mod cli_options;
 mod execute;
 mod file_results;
 mod logging;
+mod memory_limit;
+mod results;
 mod scan;
 mod ser_bridge;
 
@@ -0,0 +1,97 @@
+//! Per-process memory limits
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[global_allocator]
+static ALLOCATOR: LimitedAllocator = LimitedAllocator {
+    allocated: AtomicUsize::new(0),
+    limit: AtomicUsize::new(usize::MAX),
+};
+
+/// Limit how many bytes this process may have allocated at once.
+///
+/// Any allocation past the limit fails, which aborts the process with a
+/// "memory allocation failed" message.
+pub fn set_memory_limit(bytes: usize) {
+    ALLOCATOR.limit.store(bytes, Ordering::Relaxed);
+}
+
+/// Whether a child process was aborted by going over its memory limit,
+/// judging by what it wrote to stderr.
+pub fn exceeded_memory_limit(stderr: &str) -> bool {
+    stderr.contains("memory allocation of") && stderr.contains("failed")
+}
+
+/// The system allocator, with a limit on the total size of live
+/// allocations.
+struct LimitedAllocator {
+    allocated: AtomicUsize,
+    limit: AtomicUsize,
+}
+
+impl LimitedAllocator {
+    fn reserve(&self, size: usize) -> bool {
+        let limit = self.limit.load(Ordering::Relaxed);
+        let previous = self.allocated.fetch_add(size, Ordering::Relaxed);
+        if previous.saturating_add(size) > limit {
+            self.allocated.fetch_sub(size, Ordering::Relaxed);
+            false
+        } else {
+            true
+        }
+    }
+
+    fn release(&self, size: usize) {
+        self.allocated.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for LimitedAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if !self.reserve(layout.size()) {
+            return null_mut();
+        }
+
+        let ptr = System.alloc(layout);
+        if ptr.is_null() {
+            self.release(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        if !self.reserve(layout.size()) {
+            return null_mut();
+        }
+
+        let ptr = System.alloc_zeroed(layout);
+        if ptr.is_null() {
+            self.release(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.release(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let old_size = layout.size();
+        if new_size > old_size && !self.reserve(new_size - old_size) {
+            return null_mut();
+        }
+
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if new_ptr.is_null() {
+            if new_size > old_size {
+                self.release(new_size - old_size);
+            }
+        } else if new_size < old_size {
+            self.release(old_size - new_size);
+        }
+        new_ptr
+    }
+}
@@ -0,0 +1,167 @@
+//! Reading and writing results files
+
+use crate::cli_options::ResultsFormat;
+use crate::file_results::FileResults;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Error, Write};
+use std::path::Path;
+
+/// Read every result stored in a results file.
+///
+/// Results that could not be read are returned as errors. A file that was
+/// cut off partway through, such as by a crashed scan, still yields every
+/// result before the point it was cut off.
+pub fn read_results(
+    path: &Path,
+    format: ResultsFormat,
+) -> Result<Vec<Result<FileResults, String>>, Error> {
+    match format {
+        ResultsFormat::Csv => {
+            let reader = csv::Reader::from_path(path)?;
+            Ok(reader
+                .into_deserialize::<FileResults>()
+                .map(|r| r.map_err(|e| e.to_string()))
+                .collect())
+        }
+        ResultsFormat::Json => Ok(read_json_array(&fs::read_to_string(path)?)),
+        ResultsFormat::Ndjson => Ok(fs::read_to_string(path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(|e| e.to_string()))
+            .collect()),
+    }
+}
+
+/// Read the elements of a JSON array of results one at a time, so that an
+/// unterminated array can still be read.
+fn read_json_array(data: &str) -> Vec<Result<FileResults, String>> {
+    let mut results = vec![];
+    let Some(mut rest) = data.trim_start().strip_prefix('[') else {
+        if !data.trim().is_empty() {
+            results.push(Err("Expected a JSON array of results".to_string()));
+        }
+
+        return results;
+    };
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() || rest.starts_with(']') {
+            break;
+        }
+
+        let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<FileResults>();
+        match stream.next() {
+            Some(Ok(result)) => {
+                results.push(Ok(result));
+                rest = &rest[stream.byte_offset()..];
+            }
+            Some(Err(e)) => {
+                results.push(Err(e.to_string()));
+                break;
+            }
+            None => break,
+        }
+    }
+
+    results
+}
+
+/// Writes results to a results file as they come in.
+///
+/// Every result is flushed as soon as it is written, so that an interrupted
+/// scan can be resumed from the results it left behind.
+pub enum ResultsWriter {
+    Csv(csv::Writer<File>),
+    Json {
+        writer: BufWriter<File>,
+        has_results: bool,
+    },
+    Ndjson(BufWriter<File>),
+}
+
+impl ResultsWriter {
+    /// Start a new results file, replacing any file at `path`.
+    pub fn create(path: &Path, format: ResultsFormat) -> Result<Self, Error> {
+        Ok(Self::new(File::create(path)?, format, false))
+    }
+
+    /// Start a results file that contains `previous`, and continue writing
+    /// after them.
+    ///
+    /// The previous results are rewritten rather than appended to, so that
+    /// a result that was only partially written before a crash is dropped.
+    /// They are written to a temporary file first, so that they're never
+    /// lost if this is interrupted as well.
+    pub fn resume(
+        path: &Path,
+        format: ResultsFormat,
+        previous: &[FileResults],
+    ) -> Result<Self, Error> {
+        let temp_path = path.with_extension("resume");
+        let mut writer = Self::create(&temp_path, format)?;
+        for result in previous {
+            writer.write(result)?;
+        }
+        drop(writer);
+
+        fs::rename(&temp_path, path)?;
+        let file = OpenOptions::new().append(true).open(path)?;
+        Ok(Self::new(file, format, !previous.is_empty()))
+    }
+
+    fn new(file: File, format: ResultsFormat, has_results: bool) -> Self {
+        match format {
+            ResultsFormat::Csv => Self::Csv(
+                csv::WriterBuilder::new()
+                    .has_headers(!has_results)
+                    .from_writer(file),
+            ),
+            ResultsFormat::Json => Self::Json {
+                writer: BufWriter::new(file),
+                has_results,
+            },
+            ResultsFormat::Ndjson => Self::Ndjson(BufWriter::new(file)),
+        }
+    }
+
+    pub fn write(&mut self, result: &FileResults) -> Result<(), Error> {
+        match self {
+            Self::Csv(writer) => {
+                writer.serialize(result)?;
+                writer.flush()?;
+            }
+            Self::Json {
+                writer,
+                has_results,
+            } => {
+                writer.write_all(if *has_results { b",\n" } else { b"[\n" })?;
+                serde_json::to_writer(&mut *writer, result)?;
+                writer.flush()?;
+                *has_results = true;
+            }
+            Self::Ndjson(writer) => {
+                serde_json::to_writer(&mut *writer, result)?;
+                writer.write_all(b"\n")?;
+                writer.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finish the results file, once every result has been written.
+    pub fn finish(self) -> Result<(), Error> {
+        match self {
+            Self::Csv(mut writer) => writer.flush(),
+            Self::Json {
+                mut writer,
+                has_results,
+            } => {
+                writer.write_all(if has_results { b"\n]\n" } else { b"[]\n" })?;
+                writer.flush()
+            }
+            Self::Ndjson(mut writer) => writer.flush(),
+        }
+    }
+}
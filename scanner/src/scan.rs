@@ -1,21 +1,36 @@
 //! Main/scanner process impls
 
 use crate::analyze::analyze;
-use crate::cli_options::ScanOpt;
+use crate::cli_options::{ResultsFormat, ScanOpt};
 use crate::file_results::FileResults;
+use crate::memory_limit::exceeded_memory_limit;
+use crate::results::{read_results, ResultsWriter};
 use crate::ser_bridge::SerBridge;
 use indicatif::{ProgressBar, ProgressStyle};
 use path_slash::PathExt;
 use rayon::prelude::*;
 
+use std::collections::HashSet;
 use std::path::Path;
 
 use walkdir::{DirEntry, WalkDir};
 
 use std::env;
 use std::ffi::OsStr;
-use std::process::Command;
-use std::time::Instant;
+use std::io::{Error, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Limits on the resources that scanning a single file may use.
+#[derive(Clone, Copy, Debug)]
+pub struct ScanLimits {
+    /// How long the executor process may run for.
+    pub timeout: Duration,
+
+    /// How many megabytes the executor process may allocate.
+    pub memory_limit: Option<usize>,
+}
 
 pub fn find_files(root: &Path, ignore: &[String]) -> Vec<DirEntry> {
     let progress = ProgressBar::new_spinner();
@@ -38,16 +53,86 @@ pub fn find_files(root: &Path, ignore: &[String]) -> Vec<DirEntry> {
     results
 }
 
-pub fn scan_file<P: AsRef<OsStr>>(exec_path: P, file: &DirEntry, name: &str) -> FileResults {
+/// Wait for a child process to exit, killing it if it takes longer than
+/// `timeout`.
+///
+/// Returns the exit status, everything the process wrote to stdout and
+/// stderr, and whether it was killed.
+fn wait_with_timeout(
+    mut child: Child,
+    timeout: Duration,
+) -> Result<(ExitStatus, Vec<u8>, Vec<u8>, bool), Error> {
+    fn read_all(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut data = vec![];
+            let _ = pipe.read_to_end(&mut data);
+            data
+        })
+    }
+
+    let stdout = child.stdout.take().map(read_all);
+    let stderr = child.stderr.take().map(read_all);
+
+    let start = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if start.elapsed() >= timeout {
+            // The child may have exited just now, so don't fail if it
+            // can't be killed.
+            let _ = child.kill();
+            timed_out = true;
+            break child.wait()?;
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let stdout = stdout.and_then(|t| t.join().ok()).unwrap_or_default();
+    let stderr = stderr.and_then(|t| t.join().ok()).unwrap_or_default();
+
+    Ok((status, stdout, stderr, timed_out))
+}
+
+/// Add an error to a result, after any errors it already has.
+fn append_error(file_results: &mut FileResults, error: String) {
+    file_results.error = Some(
+        file_results
+            .error
+            .take()
+            .map(|e| format!("{e}\n{error}"))
+            .unwrap_or(error),
+    );
+}
+
+pub fn scan_file<P: AsRef<OsStr>>(
+    exec_path: P,
+    file: &DirEntry,
+    name: &str,
+    limits: ScanLimits,
+) -> FileResults {
     let start = Instant::now();
     let mut file_results = FileResults::new(name);
 
-    let subproc = Command::new(exec_path)
-        .args(["execute-report", &file.path().to_string_lossy()])
-        .output();
+    let mut command = Command::new(exec_path);
+    command.arg("execute-report");
+    if let Some(memory_limit) = limits.memory_limit {
+        command.args(["--memory-limit", &memory_limit.to_string()]);
+    }
+
+    let subproc = command
+        .arg(file.path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|child| wait_with_timeout(child, limits.timeout));
     match subproc {
-        Ok(output) => {
-            let mut reader = csv::Reader::from_reader(&output.stdout[..]);
+        Ok((_status, stdout, stderr, timed_out)) => {
+            let mut reader = csv::Reader::from_reader(&stdout[..]);
             for row in reader.deserialize::<FileResults>() {
                 match row {
                     Ok(child_results) => {
@@ -68,6 +153,9 @@ pub fn scan_file<P: AsRef<OsStr>>(exec_path: P, file: &DirEntry, name: &str) ->
                             use_gpu,
                             use_network_sandbox,
                             vm_type,
+                            stubs,
+                            avm2_error_class,
+                            avm2_compile_error,
                         } = child_results;
 
                         file_results.hash = hash;
@@ -85,21 +173,36 @@ pub fn scan_file<P: AsRef<OsStr>>(exec_path: P, file: &DirEntry, name: &str) ->
                         file_results.use_gpu = use_gpu;
                         file_results.use_network_sandbox = use_network_sandbox;
                         file_results.vm_type = vm_type;
+                        file_results.stubs = stubs;
+                        file_results.avm2_error_class = avm2_error_class;
+                        file_results.avm2_compile_error = avm2_compile_error;
                     }
                     Err(e) => {
-                        file_results.error = Some(e.to_string());
+                        // A killed child may leave its last row unfinished.
+                        if !timed_out {
+                            file_results.error = Some(e.to_string());
+                        }
                     }
                 }
             }
 
-            if !output.stderr.is_empty() {
-                let panic_error = String::from_utf8_lossy(&output.stderr).into_owned();
-                file_results.error = Some(
-                    file_results
-                        .error
-                        .map(|e| format!("{e}\n{panic_error}"))
-                        .unwrap_or(panic_error),
+            let stderr = String::from_utf8_lossy(&stderr).into_owned();
+            if timed_out {
+                append_error(
+                    &mut file_results,
+                    format!("Timed out after {} seconds", limits.timeout.as_secs()),
                 );
+            } else if let (Some(memory_limit), true) =
+                (limits.memory_limit, exceeded_memory_limit(&stderr))
+            {
+                append_error(
+                    &mut file_results,
+                    format!("Exceeded memory limit of {memory_limit} MB"),
+                );
+            }
+
+            if !stderr.is_empty() {
+                append_error(&mut file_results, stderr);
             }
         }
         Err(e) => file_results.error = Some(e.to_string()),
@@ -114,9 +217,46 @@ pub fn scan_file<P: AsRef<OsStr>>(exec_path: P, file: &DirEntry, name: &str) ->
 ///
 /// Should be called with parsed options corresponding to the `scan` command.
 pub fn scan_main(opt: ScanOpt) -> Result<(), std::io::Error> {
+    if let Some(jobs) = opt.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .map_err(Error::other)?;
+    }
+
     let binary_path = env::current_exe()?;
-    let to_scan = find_files(&opt.input_path, &opt.ignore);
-    let mut writer = csv::Writer::from_path(opt.output_path.clone())?;
+    let format = opt
+        .format
+        .unwrap_or_else(|| ResultsFormat::from_path(&opt.output_path));
+    let limits = ScanLimits {
+        timeout: Duration::from_secs(opt.timeout),
+        memory_limit: opt.memory_limit,
+    };
+
+    let mut to_scan = find_files(&opt.input_path, &opt.ignore);
+
+    let previous_results: Vec<FileResults> = if opt.resume && opt.output_path.exists() {
+        // Unreadable results are scanned again.
+        read_results(&opt.output_path, format)?
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect()
+    } else {
+        vec![]
+    };
+    let mut writer = if opt.resume {
+        let scanned: HashSet<&str> = previous_results.iter().map(|r| r.name.as_str()).collect();
+        to_scan.retain(|file| !scanned.contains(&*relative_name(&opt.input_path, file)));
+        println!(
+            "Resuming from {} previous results, {} swf files left to scan",
+            previous_results.len(),
+            to_scan.len()
+        );
+
+        ResultsWriter::resume(&opt.output_path, format, &previous_results)?
+    } else {
+        ResultsWriter::create(&opt.output_path, format)?
+    };
 
     let progress = ProgressBar::new(to_scan.len() as u64);
     progress.set_style(
@@ -127,29 +267,36 @@ pub fn scan_main(opt: ScanOpt) -> Result<(), std::io::Error> {
         .progress_chars("##-"),
     );
 
+    let input_path = opt.input_path;
     let result_iter = to_scan
         .into_par_iter()
         .map(move |file| {
-            let name = file
-                .path()
-                .strip_prefix(&opt.input_path)
-                .unwrap_or_else(|_| file.path())
-                .to_slash_lossy();
-            let result = scan_file(&binary_path, &file, &name);
+            let name = relative_name(&input_path, &file);
+            let result = scan_file(&binary_path, &file, &name, limits);
 
             progress.inc(1);
-            progress.set_message(name.into_owned());
+            progress.set_message(name);
 
             result
         })
         .ser_bridge()
         .inspect(|result| {
-            if let Err(e) = writer.serialize(result.clone()) {
+            if let Err(e) = writer.write(result) {
                 eprintln!("{e}");
             };
         });
 
-    analyze(result_iter);
+    analyze(previous_results.into_iter().chain(result_iter));
+
+    writer.finish()
+}
 
-    Ok(())
+/// The name a file is stored under in the results, relative to the scanned
+/// directory.
+fn relative_name(root: &Path, file: &DirEntry) -> String {
+    file.path()
+        .strip_prefix(root)
+        .unwrap_or_else(|_| file.path())
+        .to_slash_lossy()
+        .into_owned()
 }
@@ -420,8 +420,11 @@ impl<W: Write> Writer<W> {
     fn write_metadata(&mut self, metadata: &Metadata) -> Result<()> {
         self.write_index(&metadata.name)?;
         self.write_u30(metadata.items.len() as u32)?;
+        // All of the keys are written before all of the values.
         for item in &metadata.items {
             self.write_index(&item.key)?;
+        }
+        for item in &metadata.items {
             self.write_index(&item.value)?;
         }
         Ok(())
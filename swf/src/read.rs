@@ -69,44 +69,15 @@ pub fn extract_swz(input: &[u8]) -> Result<Vec<u8>> {
 /// println!("FPS: {}", swf_stream.header.frame_rate());
 /// ```
 pub fn decompress_swf<'a, R: Read + 'a>(mut input: R) -> Result<SwfBuf> {
-    // Read SWF header.
-    let compression = read_compression_type(&mut input)?;
-    let version = input.read_u8()?;
-    let uncompressed_len = input.read_u32::<LittleEndian>()?;
-
-    // Check whether the SWF version is 0.
-    // Note that the behavior should actually vary, depending on the player version:
-    // - Flash Player 9 and later bail out (the behavior we implement).
-    // - Flash Player 8 loops through all the frames, without running any AS code.
-    // - Flash Player 7 and older don't fail and use the player version instead: a
-    // function like `getSWFVersion()` in AVM1 will then return the player version.
-    if version == 0 {
-        return Err(Error::invalid_data("Invalid SWF version"));
-    }
+    let (compression, version, uncompressed_len) = read_file_header(&mut input)?;
 
     // Now the SWF switches to a compressed stream.
     let mut decompress_stream: Box<dyn Read> = match compression {
         Compression::None => Box::new(input),
-        Compression::Zlib => {
-            if version < 6 {
-                log::warn!(
-                    "zlib compressed SWF is version {} but minimum version is 6",
-                    version
-                );
-            }
-            make_zlib_reader(input)?
-        }
-        Compression::Lzma => {
-            if version < 13 {
-                log::warn!(
-                    "LZMA compressed SWF is version {} but minimum version is 13",
-                    version
-                );
-            }
-            // Uncompressed length includes the 4-byte header and 4-byte uncompressed length itself,
-            // subtract it here.
-            make_lzma_reader(input, uncompressed_len - 8)?
-        }
+        Compression::Zlib => make_zlib_reader(input)?,
+        // Uncompressed length includes the 4-byte header and 4-byte uncompressed length itself,
+        // subtract it here.
+        Compression::Lzma => make_lzma_reader(input, uncompressed_len.saturating_sub(8))?,
     };
 
     // Decompress the entire SWF.
@@ -125,7 +96,61 @@ pub fn decompress_swf<'a, R: Read + 'a>(mut input: R) -> Result<SwfBuf> {
         log::warn!("SWF length doesn't match header, may be corrupt");
     }
 
-    let mut reader = Reader::new(&data, version);
+    let (header, header_len) = read_header(&data, compression, version)?;
+    // Remove the header.
+    // As an alternative we could return the entire original buffer with header length,
+    // but that's a nontrivial API change, probably not worth the effort.
+    data.drain(..header_len);
+
+    Ok(SwfBuf {
+        header: read_header_ext(&data, header, uncompressed_len),
+        data,
+    })
+}
+
+/// Reads the uncompressed header at the start of every SWF.
+///
+/// Returns the compression, SWF version and uncompressed length.
+fn read_file_header<R: Read>(mut input: R) -> Result<(Compression, u8, u32)> {
+    let compression = read_compression_type(&mut input)?;
+    let version = input.read_u8()?;
+    let uncompressed_len = input.read_u32::<LittleEndian>()?;
+
+    // Check whether the SWF version is 0.
+    // Note that the behavior should actually vary, depending on the player version:
+    // - Flash Player 9 and later bail out (the behavior we implement).
+    // - Flash Player 8 loops through all the frames, without running any AS code.
+    // - Flash Player 7 and older don't fail and use the player version instead: a
+    // function like `getSWFVersion()` in AVM1 will then return the player version.
+    if version == 0 {
+        return Err(Error::invalid_data("Invalid SWF version"));
+    }
+
+    match compression {
+        Compression::Zlib if version < 6 => {
+            log::warn!(
+                "zlib compressed SWF is version {} but minimum version is 6",
+                version
+            );
+        }
+        Compression::Lzma if version < 13 => {
+            log::warn!(
+                "LZMA compressed SWF is version {} but minimum version is 13",
+                version
+            );
+        }
+        _ => {}
+    }
+
+    Ok((compression, version, uncompressed_len))
+}
+
+/// Reads the start of the decompressed SWF data: the stage size, frame rate
+/// and number of frames.
+///
+/// Returns the header along with the number of bytes that it takes up.
+fn read_header(data: &[u8], compression: Compression, version: u8) -> Result<(Header, usize)> {
+    let mut reader = Reader::new(data, version);
     let stage_size = reader.read_rectangle()?;
     let frame_rate = reader.read_fixed8()?;
     let num_frames = reader.read_u16()?;
@@ -136,12 +161,12 @@ pub fn decompress_swf<'a, R: Read + 'a>(mut input: R) -> Result<SwfBuf> {
         frame_rate,
         num_frames,
     };
-    let offset = reader.as_slice().as_ptr() as usize - data.as_ptr() as usize;
-    // Remove the header.
-    // As an alternative we could return the entire original buffer with header length,
-    // but that's a nontrivial API change, probably not worth the effort.
-    data.drain(..offset);
-    let mut reader = Reader::new(&data, version);
+    Ok((header, reader.pos(data)))
+}
+
+/// Extends the header with the metadata found in the first few tags of the SWF.
+fn read_header_ext(tags: &[u8], header: Header, uncompressed_len: u32) -> HeaderExt {
+    let mut reader = Reader::new(tags, header.version);
 
     // Parse the first two tags, searching for the FileAttributes and SetBackgroundColor tags.
     // This metadata is useful, so we want to return it along with the header.
@@ -167,15 +192,349 @@ pub fn decompress_swf<'a, R: Read + 'a>(mut input: R) -> Result<SwfBuf> {
         tag = reader.read_tag();
     }
 
-    Ok(SwfBuf {
-        header: HeaderExt {
-            header,
-            file_attributes,
-            background_color,
-            uncompressed_len: uncompressed_len as i32,
-        },
-        data,
-    })
+    HeaderExt {
+        header,
+        file_attributes,
+        background_color,
+        uncompressed_len: uncompressed_len as i32,
+    }
+}
+
+/// Decompresses an SWF as its data arrives, such as while it is being downloaded.
+///
+/// Chunks of the file are given to `push` in order, and each tag is available
+/// from `data` as soon as all of it has been decompressed. This allows a movie
+/// to start playing before the whole file has arrived.
+///
+/// LZMA compressed data only becomes available whenever the decoder flushes
+/// its dictionary, which for most SWFs means all at once at the end.
+///
+/// # Example
+/// ```
+/// # std::env::set_current_dir(env!("CARGO_MANIFEST_DIR"));
+/// let data = std::fs::read("tests/swfs/DefineSprite.swf").unwrap();
+/// let mut stream = swf::StreamDecompressor::new();
+/// for chunk in data.chunks(64) {
+///     stream.push(chunk).unwrap();
+///     println!("{} bytes of tags have arrived", stream.data().len());
+/// }
+/// let swf_buf = stream.finish().unwrap();
+/// println!("FPS: {}", swf_buf.header.frame_rate());
+/// ```
+pub struct StreamDecompressor {
+    /// Input that arrived before the compressed stream could be started.
+    pending: Vec<u8>,
+
+    /// The number of bytes that have been pushed.
+    compressed_len: usize,
+
+    /// The decoder for the compressed stream, once its header has arrived.
+    decoder: Option<Decoder>,
+    compression: Compression,
+    version: u8,
+    uncompressed_len: u32,
+
+    /// Whether the compressed stream has ended or couldn't be decompressed any further.
+    is_done: bool,
+
+    /// The decompressed data, starting with the rest of the header.
+    data: Vec<u8>,
+
+    /// The header, along with the number of bytes of `data` that it takes up.
+    header: Option<(Header, usize)>,
+    header_ext: Option<HeaderExt>,
+
+    /// The end of the last complete tag in `data`.
+    tags_end: usize,
+
+    /// The number of complete tags.
+    num_tags: usize,
+}
+
+enum Decoder {
+    None,
+    #[cfg(feature = "flate2")]
+    Zlib(Box<flate2::Decompress>),
+    #[cfg(feature = "lzma")]
+    Lzma(Box<lzma_rs::decompress::Stream<Vec<u8>>>),
+}
+
+impl StreamDecompressor {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            compressed_len: 0,
+            decoder: None,
+            compression: Compression::None,
+            version: 0,
+            uncompressed_len: 0,
+            is_done: false,
+            data: Vec::new(),
+            header: None,
+            header_ext: None,
+            tags_end: 0,
+            num_tags: 0,
+        }
+    }
+
+    /// Decompresses the next chunk of the SWF.
+    ///
+    /// Returns an `Error` if this is not a valid SWF file. Errors in the
+    /// compressed stream are only logged, and stop any further data from
+    /// being decompressed.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<()> {
+        self.compressed_len += chunk.len();
+        if self.decoder.is_some() {
+            self.decompress(chunk);
+        } else {
+            self.pending.extend_from_slice(chunk);
+            if !self.start()? {
+                return Ok(());
+            }
+            let pending = std::mem::take(&mut self.pending);
+            self.decompress(&pending);
+        }
+        self.read_tags();
+        Ok(())
+    }
+
+    /// The header of the SWF, once it and the first few tags have arrived.
+    pub fn header(&self) -> Option<&HeaderExt> {
+        self.header_ext.as_ref()
+    }
+
+    /// The length of the header that precedes `data` in the uncompressed SWF,
+    /// once it has arrived.
+    pub fn header_len(&self) -> Option<usize> {
+        // + 8 for the part of the header before the compressed stream.
+        self.header.as_ref().map(|(_, len)| len + 8)
+    }
+
+    /// The tags that have completely arrived so far.
+    pub fn data(&self) -> &[u8] {
+        match &self.header {
+            Some((_, len)) => &self.data[*len..self.tags_end],
+            None => &[],
+        }
+    }
+
+    /// The number of compressed bytes that have been pushed so far.
+    pub fn compressed_len(&self) -> usize {
+        self.compressed_len
+    }
+
+    /// Finishes decompressing the SWF once all of it has been pushed.
+    ///
+    /// Unlike `data`, the returned data includes any incomplete tag at the end.
+    pub fn finish(mut self) -> Result<SwfBuf> {
+        if self.decoder.is_none() {
+            if !self.start()? {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            let pending = std::mem::take(&mut self.pending);
+            self.decompress(&pending);
+        }
+
+        #[cfg(feature = "lzma")]
+        if let Some(Decoder::Lzma(stream)) = self.decoder.take() {
+            match stream.finish() {
+                Ok(output) => self.data.extend_from_slice(&output),
+                Err(e) => log::error!("Error decompressing SWF: {}", e),
+            }
+        }
+
+        // (+ 8 for header size)
+        if self.data.len() as u64 + 8 != self.uncompressed_len as u64 {
+            log::warn!("SWF length doesn't match header, may be corrupt");
+        }
+
+        let (header, header_len) = match self.header {
+            Some(header) => header,
+            None => read_header(&self.data, self.compression, self.version)?,
+        };
+        let mut data = self.data;
+        data.drain(..header_len);
+
+        Ok(SwfBuf {
+            header: self
+                .header_ext
+                .unwrap_or_else(|| read_header_ext(&data, header, self.uncompressed_len)),
+            data,
+        })
+    }
+
+    /// Starts the compressed stream, if its header has arrived.
+    fn start(&mut self) -> Result<bool> {
+        // LZMA streams also have their compressed length before the compressed data.
+        let header_len = if self.pending.first() == Some(&b'Z') {
+            12
+        } else {
+            8
+        };
+        if self.pending.len() < header_len {
+            return Ok(false);
+        }
+
+        let (compression, version, uncompressed_len) = read_file_header(&self.pending[..])?;
+        self.decoder = Some(match compression {
+            Compression::None => Decoder::None,
+            Compression::Zlib => make_zlib_decoder()?,
+            // Uncompressed length includes the 4-byte header and 4-byte uncompressed length itself,
+            // subtract it here.
+            Compression::Lzma => make_lzma_decoder(uncompressed_len.saturating_sub(8))?,
+        });
+        self.compression = compression;
+        self.version = version;
+        self.uncompressed_len = uncompressed_len;
+        self.pending.drain(..header_len);
+        Ok(true)
+    }
+
+    fn decompress(&mut self, input: &[u8]) {
+        if self.is_done {
+            return;
+        }
+
+        let result: io::Result<()> = match &mut self.decoder {
+            None | Some(Decoder::None) => {
+                self.data.extend_from_slice(input);
+                Ok(())
+            }
+            #[cfg(feature = "flate2")]
+            Some(Decoder::Zlib(decompress)) => {
+                inflate(decompress, input, &mut self.data).map(|is_end| self.is_done = is_end)
+            }
+            #[cfg(feature = "lzma")]
+            Some(Decoder::Lzma(stream)) => {
+                use std::io::Write;
+                stream.write_all(input).map(|()| {
+                    if let Some(output) = stream.get_output_mut() {
+                        self.data.append(output);
+                    }
+                })
+            }
+        };
+
+        if let Err(e) = result {
+            // Data after the end of the SWF is ignored.
+            if (self.data.len() as u64) + 8 < self.uncompressed_len as u64 {
+                log::error!("Error decompressing SWF: {}", e);
+            }
+            self.is_done = true;
+        }
+    }
+
+    /// Finds the header and any tags that have completely arrived.
+    fn read_tags(&mut self) {
+        let header_len = match &self.header {
+            Some((_, len)) => *len,
+            None => match read_header(&self.data, self.compression, self.version) {
+                Ok((header, len)) => {
+                    self.header = Some((header, len));
+                    self.tags_end = len;
+                    len
+                }
+                // Wait for the rest of the header.
+                Err(_) => return,
+            },
+        };
+
+        loop {
+            let remaining = &self.data[self.tags_end..];
+            let mut reader = Reader::new(remaining, self.version);
+            let Ok((_, length)) = reader.read_tag_code_and_length() else {
+                break;
+            };
+            let tag_len = reader.pos(remaining) + length;
+            if tag_len > remaining.len() {
+                break;
+            }
+            self.tags_end += tag_len;
+            self.num_tags += 1;
+        }
+
+        // The FileAttributes and SetBackgroundColor tags are looked for in the first three tags.
+        if self.header_ext.is_none() && self.num_tags >= 3 {
+            if let Some((header, _)) = &self.header {
+                self.header_ext = Some(read_header_ext(
+                    &self.data[header_len..self.tags_end],
+                    header.clone(),
+                    self.uncompressed_len,
+                ));
+            }
+        }
+    }
+}
+
+impl Default for StreamDecompressor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn make_zlib_decoder() -> Result<Decoder> {
+    Ok(Decoder::Zlib(Box::new(flate2::Decompress::new(true))))
+}
+
+#[cfg(not(feature = "flate2"))]
+fn make_zlib_decoder() -> Result<Decoder> {
+    Err(Error::unsupported(
+        "Support for Zlib compressed SWFs is not enabled.",
+    ))
+}
+
+/// Decompresses as much zlib data as possible into `output`.
+///
+/// Returns whether the end of the compressed stream was reached.
+#[cfg(feature = "flate2")]
+fn inflate(
+    decompress: &mut flate2::Decompress,
+    mut input: &[u8],
+    output: &mut Vec<u8>,
+) -> io::Result<bool> {
+    use flate2::{FlushDecompress, Status};
+
+    loop {
+        output.reserve((input.len() * 4).max(0x10000));
+        let total_in = decompress.total_in();
+        let total_out = decompress.total_out();
+        let status = decompress.decompress_vec(input, output, FlushDecompress::None)?;
+        input = &input[(decompress.total_in() - total_in) as usize..];
+
+        if status == Status::StreamEnd {
+            return Ok(true);
+        }
+        // Keep going while the output buffer was filled up.
+        let made_progress =
+            decompress.total_in() != total_in || decompress.total_out() != total_out;
+        if !made_progress || (input.is_empty() && output.len() < output.capacity()) {
+            return Ok(false);
+        }
+    }
+}
+
+#[cfg(feature = "lzma")]
+fn make_lzma_decoder(uncompressed_length: u32) -> Result<Decoder> {
+    use lzma_rs::decompress::{Options, Stream, UnpackedSize};
+    // As in `make_lzma_reader`, the compressed length has been skipped and
+    // the uncompressed length is provided separately.
+    let options = Options {
+        unpacked_size: UnpackedSize::UseProvided(Some(uncompressed_length.into())),
+        allow_incomplete: true,
+        memlimit: None,
+    };
+    Ok(Decoder::Lzma(Box::new(Stream::new_with_options(
+        &options,
+        Vec::new(),
+    ))))
+}
+
+#[cfg(not(feature = "lzma"))]
+fn make_lzma_decoder(_uncompressed_length: u32) -> Result<Decoder> {
+    Err(Error::unsupported(
+        "Support for LZMA compressed SWFs is not enabled.",
+    ))
 }
 
 #[cfg(feature = "flate2")]
@@ -218,7 +577,6 @@ fn make_lzma_reader<'a, R: Read + 'a>(
     // Read compressed length (ignored)
     let _ = input.read_u32::<LittleEndian>()?;
 
-    // TODO: Switch to lzma-rs streaming API when stable.
     let mut output = Vec::with_capacity(uncompressed_length as usize);
     lzma_decompress_with_options(
         &mut io::BufReader::new(input),
@@ -2672,6 +3030,48 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn stream_swfs() {
+        let mut paths = vec!["tests/swfs/uncompressed.swf", "tests/swfs/zlib.swf"];
+        if cfg!(feature = "lzma") {
+            paths.push("tests/swfs/lzma.swf");
+        }
+
+        for path in paths {
+            let data = std::fs::read(path).unwrap();
+            let expected = decompress_swf(&data[..]).unwrap();
+
+            let mut stream = StreamDecompressor::new();
+            for chunk in data.chunks(100) {
+                stream.push(chunk).unwrap();
+                assert!(expected.data.starts_with(stream.data()));
+            }
+            if expected.header.compression() != Compression::Lzma {
+                assert!(stream.header().is_some());
+                assert_eq!(stream.data().len(), expected.data.len());
+            }
+            assert_eq!(stream.compressed_len(), data.len());
+
+            let swf_buf = stream.finish().unwrap();
+            assert_eq!(
+                format!("{:?}", swf_buf.header),
+                format!("{:?}", expected.header)
+            );
+            assert_eq!(swf_buf.data, expected.data);
+        }
+    }
+
+    #[test]
+    fn stream_invalid_swf() {
+        let mut stream = StreamDecompressor::new();
+        assert!(stream.push(&[0u8; 128]).is_err());
+
+        let mut stream = StreamDecompressor::new();
+        stream.push(b"FWS").unwrap();
+        assert!(stream.header().is_none());
+        assert!(stream.finish().is_err());
+    }
+
     #[test]
     fn read_invalid_swf() {
         let junk = [0u8; 128];
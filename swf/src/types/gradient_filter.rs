@@ -1,4 +1,4 @@
-use crate::{BlurFilter, BlurFilterFlags, Fixed16, Fixed8, GradientRecord};
+use crate::{BlurFilter, BlurFilterFlags, Fixed16, Fixed8, GradientRecord, Rectangle, Twips};
 use bitflags::bitflags;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -39,6 +39,20 @@ impl GradientFilter {
         self.distance *= Fixed16::from_f32(y);
     }
 
+    pub fn calculate_dest_rect(&self, source_rect: Rectangle<Twips>) -> Rectangle<Twips> {
+        // Glows are offset in one direction, and bevels in both, so make room for either.
+        let mut result = self.inner_blur_filter().calculate_dest_rect(source_rect);
+        let distance = self.distance.to_f64();
+        let angle = self.angle.to_f64();
+        let x = Twips::from_pixels((angle.cos() * distance).abs());
+        let y = Twips::from_pixels((angle.sin() * distance).abs());
+        result.x_min -= x;
+        result.x_max += x;
+        result.y_min -= y;
+        result.y_max += y;
+        result
+    }
+
     pub fn inner_blur_filter(&self) -> BlurFilter {
         BlurFilter {
             blur_x: self.blur_x,
@@ -0,0 +1,990 @@
+//! ABC disassembly
+//!
+//! Besides the structured dump, an [`AbcInfo`] can be displayed as text.
+//! Every constant is written out by value rather than by its index in the
+//! constant pool, so that the text can be read back by an assembler.
+
+use crate::instructions::{Instruction, Labels};
+use crate::tags::RawTag;
+use serde::Serialize;
+use std::fmt;
+use swf::avm2::read::Reader;
+use swf::avm2::types::{
+    AbcFile, DefaultValue, Index, Method, MethodBody, MethodFlags, Multiname, Namespace,
+    NamespaceSet, Op, Trait, TraitKind,
+};
+use swf::extensions::ReadSwfExt;
+use swf::{Encoding, Tag};
+
+/// How deeply nested `TypeName` multinames are followed, as a malformed file
+/// could make one refer to itself.
+const MAX_TYPE_NAME_DEPTH: usize = 8;
+
+/// An ABC file, as found in a `DoAbc` or `DoAbc2` tag.
+#[derive(Serialize)]
+pub struct AbcDump {
+    /// Where this ABC file was found, such as `DoAbc2 "frame1"`.
+    pub source: String,
+
+    /// The offset of the tag that contains this ABC file.
+    pub tag_offset: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[serde(flatten)]
+    pub abc: Option<AbcInfo>,
+}
+
+/// Disassemble every ABC file in `tags`.
+pub fn blocks(tags: &[RawTag], encoding: &'static Encoding) -> Vec<AbcDump> {
+    tags.iter()
+        .filter_map(|raw| {
+            let (source, data) = match &raw.tag {
+                Ok(Tag::DoAbc(data)) => ("DoAbc".to_string(), *data),
+                Ok(Tag::DoAbc2(do_abc)) => (
+                    format!("DoAbc2 {:?}", do_abc.name.to_string_lossy(encoding)),
+                    do_abc.data,
+                ),
+                _ => return None,
+            };
+
+            let (abc, error) = match Reader::new(data).read() {
+                Ok(abc) => (Some(AbcInfo::new(&abc)), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            Some(AbcDump {
+                source,
+                tag_offset: raw.offset,
+                error,
+                abc,
+            })
+        })
+        .collect()
+}
+
+/// The contents of an ABC file, with every reference to the constant pool
+/// resolved.
+#[derive(Serialize)]
+pub struct AbcInfo {
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub methods: Vec<MethodInfo>,
+    pub metadata: Vec<MetadataInfo>,
+    pub classes: Vec<ClassInfo>,
+    pub scripts: Vec<ScriptInfo>,
+}
+
+#[derive(Serialize)]
+pub struct MethodInfo {
+    pub name: String,
+    pub params: Vec<ParamInfo>,
+    pub return_type: String,
+    pub flags: Vec<&'static str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<BodyInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ParamInfo {
+    pub kind: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_value: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BodyInfo {
+    pub max_stack: u32,
+    pub num_locals: u32,
+    pub init_scope_depth: u32,
+    pub max_scope_depth: u32,
+    pub traits: Vec<TraitInfo>,
+    pub instructions: Vec<Instruction>,
+
+    /// The label just past the last instruction, if anything refers to it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_label: Option<String>,
+
+    pub exceptions: Vec<ExceptionInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ExceptionInfo {
+    pub from: String,
+    pub to: String,
+    pub target: String,
+    pub type_name: String,
+    pub variable_name: String,
+}
+
+#[derive(Serialize)]
+pub struct MetadataInfo {
+    pub name: String,
+    pub items: Vec<(String, String)>,
+}
+
+/// An instance and its class, which share an index.
+#[derive(Serialize)]
+pub struct ClassInfo {
+    pub name: String,
+    pub super_name: String,
+    pub interfaces: Vec<String>,
+    pub flags: Vec<&'static str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protected_namespace: Option<String>,
+
+    pub instance_init: u32,
+    pub class_init: u32,
+    pub instance_traits: Vec<TraitInfo>,
+    pub class_traits: Vec<TraitInfo>,
+}
+
+#[derive(Serialize)]
+pub struct ScriptInfo {
+    pub init: u32,
+    pub traits: Vec<TraitInfo>,
+}
+
+#[derive(Serialize)]
+pub struct TraitInfo {
+    pub kind: &'static str,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slot_id: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disp_id: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class: Option<u32>,
+
+    pub is_final: bool,
+    pub is_override: bool,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metadata: Vec<u32>,
+}
+
+const METHOD_FLAGS: &[(MethodFlags, &str)] = &[
+    (MethodFlags::NEED_ARGUMENTS, "need_arguments"),
+    (MethodFlags::NEED_ACTIVATION, "need_activation"),
+    (MethodFlags::NEED_REST, "need_rest"),
+    (MethodFlags::HAS_OPTIONAL, "has_optional"),
+    (MethodFlags::IGNORE_REST, "ignore_rest"),
+    (MethodFlags::NATIVE, "native"),
+    (MethodFlags::SET_DXNS, "set_dxns"),
+    (MethodFlags::HAS_PARAM_NAMES, "has_param_names"),
+];
+
+impl AbcInfo {
+    pub fn new(abc: &AbcFile) -> Self {
+        let pool = Pool { abc };
+
+        Self {
+            major_version: abc.major_version,
+            minor_version: abc.minor_version,
+            methods: abc
+                .methods
+                .iter()
+                .map(|method| pool.method(method))
+                .collect(),
+            metadata: abc
+                .metadata
+                .iter()
+                .map(|metadata| MetadataInfo {
+                    name: pool.string(&metadata.name),
+                    items: metadata
+                        .items
+                        .iter()
+                        .map(|item| (pool.string(&item.key), pool.string(&item.value)))
+                        .collect(),
+                })
+                .collect(),
+            classes: abc
+                .instances
+                .iter()
+                .zip(&abc.classes)
+                .map(|(instance, class)| {
+                    let flags = [
+                        (instance.is_sealed, "sealed"),
+                        (instance.is_final, "final"),
+                        (instance.is_interface, "interface"),
+                    ];
+
+                    ClassInfo {
+                        name: pool.multiname(&instance.name),
+                        super_name: pool.multiname(&instance.super_name),
+                        interfaces: instance
+                            .interfaces
+                            .iter()
+                            .map(|interface| pool.multiname(interface))
+                            .collect(),
+                        flags: flags
+                            .into_iter()
+                            .filter_map(|(set, name)| set.then_some(name))
+                            .collect(),
+                        protected_namespace: instance
+                            .protected_namespace
+                            .as_ref()
+                            .map(|namespace| pool.namespace(namespace)),
+                        instance_init: instance.init_method.0,
+                        class_init: class.init_method.0,
+                        instance_traits: pool.traits(&instance.traits),
+                        class_traits: pool.traits(&class.traits),
+                    }
+                })
+                .collect(),
+            scripts: abc
+                .scripts
+                .iter()
+                .map(|script| ScriptInfo {
+                    init: script.init_method.0,
+                    traits: pool.traits(&script.traits),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Resolves references into the constant pool of an ABC file.
+struct Pool<'a> {
+    abc: &'a AbcFile,
+}
+
+impl Pool<'_> {
+    /// A string, quoted, or `null` for index 0.
+    fn string(&self, index: &Index<String>) -> String {
+        if index.0 == 0 {
+            return "null".to_string();
+        }
+
+        match self.abc.constant_pool.strings.get(index.0 as usize - 1) {
+            Some(string) => format!("{:?}", String::from_utf8_lossy(string)),
+            None => invalid(index.0),
+        }
+    }
+
+    fn int(&self, index: &Index<i32>) -> String {
+        Self::constant(&self.abc.constant_pool.ints, index.0)
+    }
+
+    fn uint(&self, index: &Index<u32>) -> String {
+        Self::constant(&self.abc.constant_pool.uints, index.0)
+    }
+
+    fn double(&self, index: &Index<f64>) -> String {
+        match index
+            .0
+            .checked_sub(1)
+            .and_then(|index| self.abc.constant_pool.doubles.get(index as usize))
+        {
+            Some(value) => format!("{value:?}"),
+            None => invalid(index.0),
+        }
+    }
+
+    fn constant<T: ToString>(values: &[T], index: u32) -> String {
+        match index
+            .checked_sub(1)
+            .and_then(|index| values.get(index as usize))
+        {
+            Some(value) => value.to_string(),
+            None => invalid(index),
+        }
+    }
+
+    /// A namespace, or `*` for index 0.
+    fn namespace(&self, index: &Index<Namespace>) -> String {
+        if index.0 == 0 {
+            return "*".to_string();
+        }
+
+        let Some(namespace) = self.abc.constant_pool.namespaces.get(index.0 as usize - 1) else {
+            return invalid(index.0);
+        };
+
+        match namespace {
+            Namespace::Namespace(name) => format!("Namespace({})", self.string(name)),
+            Namespace::Package(name) => format!("PackageNamespace({})", self.string(name)),
+            Namespace::PackageInternal(name) => {
+                format!("PackageInternalNs({})", self.string(name))
+            }
+            Namespace::Protected(name) => format!("ProtectedNamespace({})", self.string(name)),
+            Namespace::Explicit(name) => format!("ExplicitNamespace({})", self.string(name)),
+            Namespace::StaticProtected(name) => {
+                format!("StaticProtectedNs({})", self.string(name))
+            }
+            // Private namespaces are only equal to themselves, so they are
+            // told apart by their index.
+            Namespace::Private(name) => {
+                format!("PrivateNamespace({}, {})", self.string(name), index.0)
+            }
+        }
+    }
+
+    fn namespace_set(&self, index: &Index<NamespaceSet>) -> String {
+        let Some(set) = index
+            .0
+            .checked_sub(1)
+            .and_then(|index| self.abc.constant_pool.namespace_sets.get(index as usize))
+        else {
+            return invalid(index.0);
+        };
+
+        let namespaces: Vec<_> = set
+            .iter()
+            .map(|namespace| self.namespace(namespace))
+            .collect();
+        format!("[{}]", namespaces.join(", "))
+    }
+
+    /// A multiname, or `*` for index 0.
+    fn multiname(&self, index: &Index<Multiname>) -> String {
+        self.multiname_at_depth(index, 0)
+    }
+
+    fn multiname_at_depth(&self, index: &Index<Multiname>, depth: usize) -> String {
+        if index.0 == 0 {
+            return "*".to_string();
+        }
+
+        let Some(multiname) = self.abc.constant_pool.multinames.get(index.0 as usize - 1) else {
+            return invalid(index.0);
+        };
+
+        match multiname {
+            Multiname::QName { namespace, name } => {
+                format!(
+                    "QName({}, {})",
+                    self.namespace(namespace),
+                    self.string(name)
+                )
+            }
+            Multiname::QNameA { namespace, name } => {
+                format!(
+                    "QNameA({}, {})",
+                    self.namespace(namespace),
+                    self.string(name)
+                )
+            }
+            Multiname::RTQName { name } => format!("RTQName({})", self.string(name)),
+            Multiname::RTQNameA { name } => format!("RTQNameA({})", self.string(name)),
+            Multiname::RTQNameL => "RTQNameL()".to_string(),
+            Multiname::RTQNameLA => "RTQNameLA()".to_string(),
+            Multiname::Multiname {
+                namespace_set,
+                name,
+            } => format!(
+                "Multiname({}, {})",
+                self.string(name),
+                self.namespace_set(namespace_set)
+            ),
+            Multiname::MultinameA {
+                namespace_set,
+                name,
+            } => format!(
+                "MultinameA({}, {})",
+                self.string(name),
+                self.namespace_set(namespace_set)
+            ),
+            Multiname::MultinameL { namespace_set } => {
+                format!("MultinameL({})", self.namespace_set(namespace_set))
+            }
+            Multiname::MultinameLA { namespace_set } => {
+                format!("MultinameLA({})", self.namespace_set(namespace_set))
+            }
+            Multiname::TypeName {
+                base_type,
+                parameters,
+            } => {
+                if depth >= MAX_TYPE_NAME_DEPTH {
+                    return invalid(index.0);
+                }
+
+                let parameters: Vec<_> = parameters
+                    .iter()
+                    .map(|parameter| self.multiname_at_depth(parameter, depth + 1))
+                    .collect();
+                format!(
+                    "TypeName({}<{}>)",
+                    self.multiname_at_depth(base_type, depth + 1),
+                    parameters.join(", ")
+                )
+            }
+        }
+    }
+
+    fn default_value(&self, value: &DefaultValue) -> String {
+        match value {
+            DefaultValue::Int(index) => format!("Int({})", self.int(index)),
+            DefaultValue::Uint(index) => format!("Uint({})", self.uint(index)),
+            DefaultValue::Double(index) => format!("Double({})", self.double(index)),
+            DefaultValue::String(index) => format!("String({})", self.string(index)),
+            DefaultValue::True => "True".to_string(),
+            DefaultValue::False => "False".to_string(),
+            DefaultValue::Null => "Null".to_string(),
+            DefaultValue::Undefined => "Undefined".to_string(),
+            // The kind of namespace is part of how it is written.
+            DefaultValue::Namespace(index)
+            | DefaultValue::Package(index)
+            | DefaultValue::PackageInternal(index)
+            | DefaultValue::Protected(index)
+            | DefaultValue::Explicit(index)
+            | DefaultValue::StaticProtected(index)
+            | DefaultValue::Private(index) => self.namespace(index),
+        }
+    }
+
+    fn method(&self, method: &Method) -> MethodInfo {
+        MethodInfo {
+            name: self.string(&method.name),
+            params: method
+                .params
+                .iter()
+                .map(|param| ParamInfo {
+                    kind: self.multiname(&param.kind),
+                    name: param.name.as_ref().map(|name| self.string(name)),
+                    default_value: param
+                        .default_value
+                        .as_ref()
+                        .map(|value| self.default_value(value)),
+                })
+                .collect(),
+            return_type: self.multiname(&method.return_type),
+            flags: METHOD_FLAGS
+                .iter()
+                .filter(|(flag, _)| method.flags.contains(*flag))
+                .map(|(_, name)| *name)
+                .collect(),
+            body: method
+                .body
+                .as_ref()
+                .and_then(|body| self.abc.method_bodies.get(body.0 as usize))
+                .map(|body| self.body(body)),
+        }
+    }
+
+    fn traits(&self, traits: &[Trait]) -> Vec<TraitInfo> {
+        traits
+            .iter()
+            .map(|t| {
+                let mut info = TraitInfo {
+                    kind: "",
+                    name: self.multiname(&t.name),
+                    slot_id: None,
+                    disp_id: None,
+                    type_name: None,
+                    value: None,
+                    method: None,
+                    class: None,
+                    is_final: t.is_final,
+                    is_override: t.is_override,
+                    metadata: t.metadata.iter().map(|metadata| metadata.0).collect(),
+                };
+
+                match &t.kind {
+                    TraitKind::Slot {
+                        slot_id,
+                        type_name,
+                        value,
+                    }
+                    | TraitKind::Const {
+                        slot_id,
+                        type_name,
+                        value,
+                    } => {
+                        info.kind = if matches!(t.kind, TraitKind::Slot { .. }) {
+                            "slot"
+                        } else {
+                            "const"
+                        };
+                        info.slot_id = Some(*slot_id);
+                        info.type_name = Some(self.multiname(type_name));
+                        info.value = value.as_ref().map(|value| self.default_value(value));
+                    }
+                    TraitKind::Method { disp_id, method } => {
+                        info.kind = "method";
+                        info.disp_id = Some(*disp_id);
+                        info.method = Some(method.0);
+                    }
+                    TraitKind::Getter { disp_id, method } => {
+                        info.kind = "getter";
+                        info.disp_id = Some(*disp_id);
+                        info.method = Some(method.0);
+                    }
+                    TraitKind::Setter { disp_id, method } => {
+                        info.kind = "setter";
+                        info.disp_id = Some(*disp_id);
+                        info.method = Some(method.0);
+                    }
+                    TraitKind::Class { slot_id, class } => {
+                        info.kind = "class";
+                        info.slot_id = Some(*slot_id);
+                        info.class = Some(class.0);
+                    }
+                    TraitKind::Function { slot_id, function } => {
+                        info.kind = "function";
+                        info.slot_id = Some(*slot_id);
+                        info.method = Some(function.0);
+                    }
+                }
+
+                info
+            })
+            .collect()
+    }
+
+    fn body(&self, body: &MethodBody) -> BodyInfo {
+        // Every op, along with its start and end offsets.
+        let mut ops = vec![];
+        let mut error = None;
+        let mut reader = Reader::new(&body.code);
+        while !reader.as_slice().is_empty() {
+            let start = reader.pos(&body.code);
+            match reader.read_op() {
+                Ok(op) => ops.push((start, reader.pos(&body.code), op)),
+                Err(e) => {
+                    error = Some((start, e.to_string()));
+                    break;
+                }
+            }
+        }
+
+        let mut offsets: Vec<usize> = ops.iter().map(|(start, _, _)| *start).collect();
+        offsets.extend(error.as_ref().map(|(start, _)| *start));
+        offsets.push(body.code.len());
+
+        let exception_targets = body.exceptions.iter().flat_map(|exception| {
+            [
+                exception.from_offset,
+                exception.to_offset,
+                exception.target_offset,
+            ]
+            .map(|offset| offset as isize)
+        });
+        let labels = Labels::new(
+            ops.iter()
+                .flat_map(|(start, end, op)| branch_targets(op, *start, *end))
+                .chain(exception_targets),
+            &offsets,
+        );
+
+        let mut instructions: Vec<_> = ops
+            .iter()
+            .map(|(start, end, op)| Instruction {
+                offset: *start,
+                label: labels.at(*start),
+                depth: 0,
+                op: mnemonic(op),
+                operands: self.operands(op, *start, *end, &labels),
+            })
+            .collect();
+        if let Some((start, error)) = error {
+            instructions.push(Instruction {
+                offset: start,
+                label: labels.at(start),
+                depth: 0,
+                op: "error".to_string(),
+                operands: error,
+            });
+        }
+
+        BodyInfo {
+            max_stack: body.max_stack,
+            num_locals: body.num_locals,
+            init_scope_depth: body.init_scope_depth,
+            max_scope_depth: body.max_scope_depth,
+            traits: self.traits(&body.traits),
+            instructions,
+            end_label: labels.at(body.code.len()),
+            exceptions: body
+                .exceptions
+                .iter()
+                .map(|exception| ExceptionInfo {
+                    from: labels.target(exception.from_offset as isize),
+                    to: labels.target(exception.to_offset as isize),
+                    target: labels.target(exception.target_offset as isize),
+                    type_name: self.multiname(&exception.type_name),
+                    variable_name: self.multiname(&exception.variable_name),
+                })
+                .collect(),
+        }
+    }
+
+    fn operands(&self, op: &Op, start: usize, end: usize, labels: &Labels) -> String {
+        match op {
+            Op::AsType { type_name: index }
+            | Op::Coerce { index }
+            | Op::DeleteProperty { index }
+            | Op::FindDef { index }
+            | Op::FindProperty { index }
+            | Op::FindPropStrict { index }
+            | Op::GetDescendants { index }
+            | Op::GetLex { index }
+            | Op::GetProperty { index }
+            | Op::GetSuper { index }
+            | Op::InitProperty { index }
+            | Op::IsType { index }
+            | Op::SetProperty { index }
+            | Op::SetSuper { index } => self.multiname(index),
+            Op::CallProperty { index, num_args }
+            | Op::CallPropLex { index, num_args }
+            | Op::CallPropVoid { index, num_args }
+            | Op::CallSuper { index, num_args }
+            | Op::CallSuperVoid { index, num_args }
+            | Op::ConstructProp { index, num_args } => {
+                format!("{}, {num_args}", self.multiname(index))
+            }
+            Op::CallMethod { index, num_args } => format!("{index}, {num_args}"),
+            Op::CallStatic { index, num_args } => format!("{}, {num_args}", index.0),
+            Op::ApplyType { num_types: value }
+            | Op::Call { num_args: value }
+            | Op::Construct { num_args: value }
+            | Op::ConstructSuper { num_args: value }
+            | Op::NewArray { num_args: value }
+            | Op::NewObject { num_args: value }
+            | Op::BkptLine { line_num: value }
+            | Op::DebugLine { line_num: value }
+            | Op::DecLocal { index: value }
+            | Op::DecLocalI { index: value }
+            | Op::GetGlobalSlot { index: value }
+            | Op::GetLocal { index: value }
+            | Op::GetOuterScope { index: value }
+            | Op::GetSlot { index: value }
+            | Op::IncLocal { index: value }
+            | Op::IncLocalI { index: value }
+            | Op::Kill { index: value }
+            | Op::SetGlobalSlot { index: value }
+            | Op::SetLocal { index: value }
+            | Op::SetSlot { index: value } => value.to_string(),
+            Op::GetScopeObject { index } => index.to_string(),
+            Op::HasNext2 {
+                object_register,
+                index_register,
+            } => format!("{object_register}, {index_register}"),
+            Op::Debug {
+                is_local_register,
+                register_name,
+                register,
+            } => format!(
+                "{}, {}, {register}",
+                u8::from(*is_local_register),
+                self.string(register_name)
+            ),
+            Op::DebugFile { file_name: value }
+            | Op::Dxns { index: value }
+            | Op::PushString { value } => self.string(value),
+            Op::NewCatch { index } => index.0.to_string(),
+            Op::NewClass { index } => index.0.to_string(),
+            Op::NewFunction { index } => index.0.to_string(),
+            Op::PushByte { value } => value.to_string(),
+            Op::PushShort { value } => value.to_string(),
+            Op::PushInt { value } => self.int(value),
+            Op::PushUint { value } => self.uint(value),
+            Op::PushDouble { value } => self.double(value),
+            Op::PushNamespace { value } => self.namespace(value),
+            Op::LookupSwitch(lookup_switch) => {
+                let cases: Vec<_> = lookup_switch
+                    .case_offsets
+                    .iter()
+                    .map(|offset| labels.target(start as isize + *offset as isize))
+                    .collect();
+                format!(
+                    "{}, [{}]",
+                    labels.target(start as isize + lookup_switch.default_offset as isize),
+                    cases.join(", ")
+                )
+            }
+            _ => match branch_offset(op) {
+                Some(offset) => labels.target(end as isize + offset as isize),
+                None => String::new(),
+            },
+        }
+    }
+}
+
+fn invalid(index: u32) -> String {
+    format!("<invalid {index}>")
+}
+
+/// The name of an op, as it is written in assembly.
+fn mnemonic(op: &Op) -> String {
+    // The name of the variant is all that precedes its fields.
+    let debug = format!("{op:?}");
+    debug
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// The offset of a branch, relative to the end of the op.
+fn branch_offset(op: &Op) -> Option<i32> {
+    match op {
+        Op::IfEq { offset }
+        | Op::IfFalse { offset }
+        | Op::IfGe { offset }
+        | Op::IfGt { offset }
+        | Op::IfLe { offset }
+        | Op::IfLt { offset }
+        | Op::IfNe { offset }
+        | Op::IfNge { offset }
+        | Op::IfNgt { offset }
+        | Op::IfNle { offset }
+        | Op::IfNlt { offset }
+        | Op::IfStrictEq { offset }
+        | Op::IfStrictNe { offset }
+        | Op::IfTrue { offset }
+        | Op::Jump { offset } => Some(*offset),
+        _ => None,
+    }
+}
+
+fn branch_targets(op: &Op, start: usize, end: usize) -> Vec<isize> {
+    if let Op::LookupSwitch(lookup_switch) = op {
+        // Unlike other branches, these are relative to the start of the op.
+        return std::iter::once(lookup_switch.default_offset)
+            .chain(lookup_switch.case_offsets.iter().copied())
+            .map(|offset| start as isize + offset as isize)
+            .collect();
+    }
+
+    branch_offset(op)
+        .map(|offset| end as isize + offset as isize)
+        .into_iter()
+        .collect()
+}
+
+impl fmt::Display for AbcInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version {} {}", self.major_version, self.minor_version)?;
+
+        for (index, method) in self.methods.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "method {index} {}", method.name)?;
+            for param in &method.params {
+                write!(f, "  param {}", param.kind)?;
+                if let Some(name) = &param.name {
+                    write!(f, " name {name}")?;
+                }
+                if let Some(value) = &param.default_value {
+                    write!(f, " default {value}")?;
+                }
+                writeln!(f)?;
+            }
+            writeln!(f, "  returns {}", method.return_type)?;
+            if !method.flags.is_empty() {
+                writeln!(f, "  flags {}", method.flags.join(" "))?;
+            }
+
+            if let Some(body) = &method.body {
+                writeln!(f, "  body")?;
+                writeln!(f, "    max_stack {}", body.max_stack)?;
+                writeln!(f, "    locals {}", body.num_locals)?;
+                writeln!(
+                    f,
+                    "    scope_depth {} {}",
+                    body.init_scope_depth, body.max_scope_depth
+                )?;
+                for t in &body.traits {
+                    writeln!(f, "    trait {t}")?;
+                }
+
+                writeln!(f, "    code")?;
+                for instruction in &body.instructions {
+                    if let Some(label) = &instruction.label {
+                        writeln!(f, "    {label}:")?;
+                    }
+                    if instruction.op == "error" {
+                        writeln!(f, "      ; {}", instruction.operands)?;
+                    } else if instruction.operands.is_empty() {
+                        writeln!(f, "      {}", instruction.op)?;
+                    } else {
+                        writeln!(f, "      {} {}", instruction.op, instruction.operands)?;
+                    }
+                }
+                if let Some(label) = &body.end_label {
+                    writeln!(f, "    {label}:")?;
+                }
+                writeln!(f, "    end")?;
+
+                for exception in &body.exceptions {
+                    writeln!(
+                        f,
+                        "    exception {} {} {} type {} name {}",
+                        exception.from,
+                        exception.to,
+                        exception.target,
+                        exception.type_name,
+                        exception.variable_name
+                    )?;
+                }
+                writeln!(f, "  end")?;
+            }
+            writeln!(f, "end")?;
+        }
+
+        for (index, metadata) in self.metadata.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "metadata {index} {}", metadata.name)?;
+            for (key, value) in &metadata.items {
+                writeln!(f, "  item {key} {value}")?;
+            }
+            writeln!(f, "end")?;
+        }
+
+        for (index, class) in self.classes.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "class {index} {}", class.name)?;
+            writeln!(f, "  extends {}", class.super_name)?;
+            for interface in &class.interfaces {
+                writeln!(f, "  implements {interface}")?;
+            }
+            if !class.flags.is_empty() {
+                writeln!(f, "  flags {}", class.flags.join(" "))?;
+            }
+            if let Some(namespace) = &class.protected_namespace {
+                writeln!(f, "  protected_ns {namespace}")?;
+            }
+            writeln!(f, "  iinit {}", class.instance_init)?;
+            writeln!(f, "  cinit {}", class.class_init)?;
+            for t in &class.instance_traits {
+                writeln!(f, "  trait {t}")?;
+            }
+            for t in &class.class_traits {
+                writeln!(f, "  static trait {t}")?;
+            }
+            writeln!(f, "end")?;
+        }
+
+        for (index, script) in self.scripts.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "script {index}")?;
+            writeln!(f, "  init {}", script.init)?;
+            for t in &script.traits {
+                writeln!(f, "  trait {t}")?;
+            }
+            writeln!(f, "end")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for TraitInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.name)?;
+        if let Some(slot_id) = self.slot_id {
+            write!(f, " slot_id {slot_id}")?;
+        }
+        if let Some(disp_id) = self.disp_id {
+            write!(f, " disp_id {disp_id}")?;
+        }
+        if let Some(type_name) = &self.type_name {
+            write!(f, " type {type_name}")?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, " value {value}")?;
+        }
+        if let Some(method) = self.method {
+            write!(f, " method {method}")?;
+        }
+        if let Some(class) = self.class {
+            write!(f, " class {class}")?;
+        }
+        if self.is_final {
+            write!(f, " final")?;
+        }
+        if self.is_override {
+            write!(f, " override")?;
+        }
+        for metadata in &self.metadata {
+            write!(f, " metadata {metadata}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tags::read_tags;
+    use swf::SwfStr;
+
+    /// Disassemble every ABC file in the given SWF.
+    fn dump(data: &[u8]) -> Vec<AbcDump> {
+        let swf_buf = swf::decompress_swf(data).unwrap();
+        let version = swf_buf.header.version();
+        let tags = read_tags(&swf_buf.data, 0, version);
+        blocks(&tags, SwfStr::encoding_for_version(version))
+    }
+
+    fn dump_text(data: &[u8]) -> String {
+        dump(data)
+            .iter()
+            .map(|block| block.abc.as_ref().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn resolves_constant_pool() {
+        let text = dump_text(include_bytes!("../../swf/tests/swfs/Avm2DefaultValue.swf"));
+        assert!(text.contains(
+            "static trait slot QName(PackageNamespace(\"\"), \"defaultTrue\") slot_id 1 \
+             type QName(PackageNamespace(\"\"), \"Boolean\") value True"
+        ));
+        assert!(text.contains(
+            "static trait slot QName(PackageNamespace(\"\"), \"defaultNull\") slot_id 3 \
+             type QName(PackageNamespace(\"\"), \"String\") value Null"
+        ));
+        assert!(text.contains("getlex QName(PackageNamespace(\"\"), \"undefined\")"));
+    }
+
+    #[test]
+    fn resolves_branch_labels_and_exceptions() {
+        let text = dump_text(include_bytes!(
+            "../../tests/tests/swfs/avm2/try_catch/test.swf"
+        ));
+        let lines: Vec<&str> = text.lines().map(str::trim).collect();
+        let label = |name| lines.iter().position(|line| *line == name).unwrap();
+
+        // Every label is printed before the instruction it marks, and every
+        // branch refers to a label that exists.
+        let (l0, l1, l2, l3) = (label("L0:"), label("L1:"), label("L2:"), label("L3:"));
+        assert!(l0 < l1 && l1 < l2 && l2 < l3);
+        assert_eq!(lines[l1 + 2], "jump L3");
+        assert_eq!(lines[l2 + 3], "newcatch 0");
+
+        assert!(lines
+            .contains(&"exception L0 L1 L2 type * name QName(PackageNamespace(\"\"), \"foobar\")"));
+        assert!(lines
+            .contains(&"exception L3 L4 L5 type * name QName(PackageNamespace(\"\"), \"foobar\")"));
+    }
+
+    #[test]
+    fn json_matches_snapshot() {
+        let blocks = dump(include_bytes!("../../swf/tests/swfs/Avm2DefaultValue.swf"));
+        let json = serde_json::to_string_pretty(&blocks).unwrap();
+        assert_eq!(
+            json.trim_end(),
+            include_str!("../tests/snapshots/Avm2DefaultValue.json").trim_end()
+        );
+    }
+}
@@ -0,0 +1,2101 @@
+//! Assembling ABC files from text
+//!
+//! The text format is the one that [`AbcInfo`](crate::abc::AbcInfo) is
+//! displayed as, so a disassembled file can be assembled again. Constants are
+//! written by value and added to the constant pool as they are found, so that
+//! each one is only stored once.
+//!
+//! Branches refer to labels rather than offsets. The `max_stack`, `locals`
+//! and `scope_depth` lines of a method body may be left out, in which case
+//! they are worked out from its code.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+use swf::avm2::types::{
+    AbcFile, Class, ConstantPool, DefaultValue, Exception, Index, Instance, LookupSwitch, Metadata,
+    MetadataItem, Method, MethodBody, MethodFlags, MethodParam, Multiname, Namespace, Op, Script,
+    Trait, TraitKind,
+};
+use swf::avm2::write::Writer;
+use swf::{
+    Compression, DoAbc2, DoAbc2Flag, FileAttributes, Fixed8, Header, Rectangle, SwfStr, Tag, Twips,
+};
+
+/// The ABC version used when the text doesn't give one.
+const DEFAULT_VERSION: (u16, u16) = (46, 16);
+
+/// The SWF version of movies made by [`assemble_swf`].
+const SWF_VERSION: u8 = 15;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A line of the text couldn't be assembled.
+    Parse { line: usize, message: String },
+
+    /// The assembled file couldn't be written.
+    Write(swf::error::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { line, message } => write!(f, "Line {line}: {message}"),
+            Self::Write(e) => write!(f, "Couldn't write ABC file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Write(error.into())
+    }
+}
+
+/// Assemble the text of an ABC file.
+pub fn assemble(text: &str) -> Result<AbcFile, Error> {
+    let lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, text)| {
+            let number = index + 1;
+            let tokens = tokenize(text).map_err(|message| Error::Parse {
+                line: number,
+                message,
+            })?;
+            Ok(Line { number, tokens })
+        })
+        .filter(|line| !matches!(line, Ok(line) if line.tokens.is_empty()))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let mut assembler = Assembler::default();
+    let mut lines = lines.iter();
+    while let Some(line) = lines.next() {
+        let mut cursor = line.cursor();
+        match cursor.word()? {
+            "version" => {
+                assembler.version = (cursor.number()?, cursor.number()?);
+                cursor.finish()?;
+            }
+            "method" => assembler.method(cursor, line, &mut lines)?,
+            "metadata" => assembler.metadata(cursor, line, &mut lines)?,
+            "class" => assembler.class(cursor, line, &mut lines)?,
+            "script" => assembler.script(cursor, line, &mut lines)?,
+            other => return Err(cursor.error(format!("Unknown directive {other:?}"))),
+        }
+    }
+
+    Ok(assembler.finish())
+}
+
+/// Assemble the text of an ABC file into its binary form.
+pub fn assemble_to_bytes(text: &str) -> Result<Vec<u8>, Error> {
+    let abc = assemble(text)?;
+    let mut data = vec![];
+    Writer::new(&mut data).write(abc)?;
+    Ok(data)
+}
+
+/// Assemble the text of an ABC file, and wrap it in a SWF that runs it.
+///
+/// The SWF has a single frame, and its ABC file is run as soon as it loads.
+pub fn assemble_swf(text: &str) -> Result<Vec<u8>, Error> {
+    let abc = assemble_to_bytes(text)?;
+
+    let header = Header {
+        compression: Compression::None,
+        version: SWF_VERSION,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(550.0),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(400.0),
+        },
+        frame_rate: Fixed8::from_f32(24.0),
+        num_frames: 1,
+    };
+    let tags = [
+        Tag::FileAttributes(FileAttributes::IS_ACTION_SCRIPT_3),
+        Tag::DoAbc2(DoAbc2 {
+            flags: DoAbc2Flag::empty(),
+            name: SwfStr::from_utf8_str(""),
+            data: &abc,
+        }),
+        Tag::ShowFrame,
+    ];
+
+    let mut swf = vec![];
+    swf::write_swf(&header, &tags, &mut swf).map_err(Error::Write)?;
+    Ok(swf)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Str(String),
+    Punct(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Word(word) => write!(f, "{word}"),
+            Self::Str(string) => write!(f, "{string:?}"),
+            Self::Punct(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+const PUNCTUATION: &str = "()[]<>,:*";
+
+/// Split a line into tokens, leaving out any comment.
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = line.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c == ';' {
+            break;
+        } else if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            tokens.push(Token::Str(read_string(&mut chars)?));
+        } else if PUNCTUATION.contains(c) {
+            chars.next();
+            tokens.push(Token::Punct(c));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || PUNCTUATION.contains(c) || c == '"' || c == ';' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read the rest of a string literal, which uses the same escapes as Rust.
+fn read_string(chars: &mut impl Iterator<Item = char>) -> Result<String, String> {
+    let mut string = String::new();
+    loop {
+        let c = match chars.next() {
+            None => return Err("Unterminated string".to_string()),
+            Some('"') => return Ok(string),
+            Some('\\') => match chars.next() {
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('0') => '\0',
+                Some('\\') => '\\',
+                Some('"') => '"',
+                Some('\'') => '\'',
+                Some('u') => {
+                    if chars.next() != Some('{') {
+                        return Err("Expected { after \\u".to_string());
+                    }
+                    let hex: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("Invalid unicode escape {hex:?}"))?
+                }
+                Some(c) => return Err(format!("Unknown escape \\{c}")),
+                None => return Err("Unterminated string".to_string()),
+            },
+            Some(c) => c,
+        };
+        string.push(c);
+    }
+}
+
+struct Line {
+    number: usize,
+    tokens: Vec<Token>,
+}
+
+impl Line {
+    fn cursor(&self) -> Cursor<'_> {
+        Cursor {
+            line: self.number,
+            tokens: &self.tokens,
+            position: 0,
+        }
+    }
+
+    /// Whether this line is a label, such as `L0:`.
+    fn label(&self) -> Option<&str> {
+        match &self.tokens[..] {
+            [Token::Word(name), Token::Punct(':')] => Some(name),
+            _ => None,
+        }
+    }
+}
+
+type Lines<'a> = std::slice::Iter<'a, Line>;
+
+/// The next line in a block that was started by `start`.
+fn next_line<'a>(lines: &mut Lines<'a>, start: &Line) -> Result<&'a Line, Error> {
+    lines.next().ok_or_else(|| Error::Parse {
+        line: start.number,
+        message: "Missing end of block".to_string(),
+    })
+}
+
+struct Cursor<'a> {
+    line: usize,
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::Parse {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<&'a Token, Error> {
+        let token = self
+            .peek()
+            .ok_or_else(|| self.error("Unexpected end of line"))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn word(&mut self) -> Result<&'a str, Error> {
+        match self.next()? {
+            Token::Word(word) => Ok(word),
+            other => Err(self.error(format!("Expected a word, found {other}"))),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, Error> {
+        let word = self.word()?;
+        word.parse()
+            .map_err(|_| self.error(format!("Invalid number {word:?}")))
+    }
+
+    fn punct(&mut self, expected: char) -> Result<(), Error> {
+        match self.next()? {
+            Token::Punct(c) if *c == expected => Ok(()),
+            other => Err(self.error(format!("Expected {expected}, found {other}"))),
+        }
+    }
+
+    /// Skip over `expected` if it is next.
+    fn eat_punct(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(&Token::Punct(expected));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn finish(&self) -> Result<(), Error> {
+        match self.peek() {
+            None => Ok(()),
+            Some(token) => Err(self.error(format!("Unexpected {token}"))),
+        }
+    }
+}
+
+/// A constant pool that stores each constant once.
+struct PoolBuilder {
+    pool: ConstantPool,
+    ints: HashMap<i32, u32>,
+    uints: HashMap<u32, u32>,
+    doubles: HashMap<u64, u32>,
+    strings: HashMap<String, u32>,
+    namespaces: HashMap<(u8, u32, Option<u32>), u32>,
+    namespace_sets: HashMap<Vec<u32>, u32>,
+    multinames: HashMap<(u8, Vec<u32>), u32>,
+}
+
+impl Default for PoolBuilder {
+    fn default() -> Self {
+        Self {
+            pool: ConstantPool {
+                ints: vec![],
+                uints: vec![],
+                doubles: vec![],
+                strings: vec![],
+                namespaces: vec![],
+                namespace_sets: vec![],
+                multinames: vec![],
+            },
+            ints: HashMap::new(),
+            uints: HashMap::new(),
+            doubles: HashMap::new(),
+            strings: HashMap::new(),
+            namespaces: HashMap::new(),
+            namespace_sets: HashMap::new(),
+            multinames: HashMap::new(),
+        }
+    }
+}
+
+/// Find the index of a constant, adding it to `values` if it's new.
+///
+/// Indices start at 1, as index 0 of every constant pool is reserved.
+fn intern<K: Hash + Eq, T>(
+    indices: &mut HashMap<K, u32>,
+    values: &mut Vec<T>,
+    key: K,
+    value: impl FnOnce() -> T,
+) -> u32 {
+    *indices.entry(key).or_insert_with(|| {
+        values.push(value());
+        values.len() as u32
+    })
+}
+
+impl PoolBuilder {
+    fn int(&mut self, value: i32) -> Index<i32> {
+        Index::new(intern(&mut self.ints, &mut self.pool.ints, value, || value))
+    }
+
+    fn uint(&mut self, value: u32) -> Index<u32> {
+        Index::new(intern(&mut self.uints, &mut self.pool.uints, value, || {
+            value
+        }))
+    }
+
+    fn double(&mut self, value: f64) -> Index<f64> {
+        Index::new(intern(
+            &mut self.doubles,
+            &mut self.pool.doubles,
+            value.to_bits(),
+            || value,
+        ))
+    }
+
+    fn string(&mut self, value: &str) -> Index<String> {
+        Index::new(intern(
+            &mut self.strings,
+            &mut self.pool.strings,
+            value.to_string(),
+            || value.as_bytes().to_vec(),
+        ))
+    }
+
+    fn namespace(&mut self, kind: u8, name: Index<String>, id: Option<u32>) -> Index<Namespace> {
+        Index::new(intern(
+            &mut self.namespaces,
+            &mut self.pool.namespaces,
+            (kind, name.0, id),
+            || match kind {
+                0 => Namespace::Namespace(name),
+                1 => Namespace::Package(name),
+                2 => Namespace::PackageInternal(name),
+                3 => Namespace::Protected(name),
+                4 => Namespace::Explicit(name),
+                5 => Namespace::StaticProtected(name),
+                _ => Namespace::Private(name),
+            },
+        ))
+    }
+
+    fn namespace_set(&mut self, namespaces: Vec<Index<Namespace>>) -> Index<Vec<Index<Namespace>>> {
+        let key = namespaces.iter().map(|namespace| namespace.0).collect();
+        Index::new(intern(
+            &mut self.namespace_sets,
+            &mut self.pool.namespace_sets,
+            key,
+            || namespaces,
+        ))
+    }
+
+    fn multiname(&mut self, multiname: Multiname) -> Index<Multiname> {
+        let key = match &multiname {
+            Multiname::QName { namespace, name } => (0, vec![namespace.0, name.0]),
+            Multiname::QNameA { namespace, name } => (1, vec![namespace.0, name.0]),
+            Multiname::RTQName { name } => (2, vec![name.0]),
+            Multiname::RTQNameA { name } => (3, vec![name.0]),
+            Multiname::RTQNameL => (4, vec![]),
+            Multiname::RTQNameLA => (5, vec![]),
+            Multiname::Multiname {
+                namespace_set,
+                name,
+            } => (6, vec![namespace_set.0, name.0]),
+            Multiname::MultinameA {
+                namespace_set,
+                name,
+            } => (7, vec![namespace_set.0, name.0]),
+            Multiname::MultinameL { namespace_set } => (8, vec![namespace_set.0]),
+            Multiname::MultinameLA { namespace_set } => (9, vec![namespace_set.0]),
+            Multiname::TypeName {
+                base_type,
+                parameters,
+            } => (
+                10,
+                std::iter::once(base_type.0)
+                    .chain(parameters.iter().map(|parameter| parameter.0))
+                    .collect(),
+            ),
+        };
+        Index::new(intern(
+            &mut self.multinames,
+            &mut self.pool.multinames,
+            key,
+            || multiname,
+        ))
+    }
+
+    /// How many values a multiname takes from the stack when it is used.
+    fn runtime_parts(&self, index: &Index<Multiname>) -> u32 {
+        let multiname = index
+            .0
+            .checked_sub(1)
+            .and_then(|index| self.pool.multinames.get(index as usize));
+        match multiname {
+            Some(Multiname::RTQName { .. } | Multiname::RTQNameA { .. }) => 1,
+            Some(Multiname::RTQNameL | Multiname::RTQNameLA) => 2,
+            Some(Multiname::MultinameL { .. } | Multiname::MultinameLA { .. }) => 1,
+            _ => 0,
+        }
+    }
+}
+
+/// Where a branch goes to.
+enum Target {
+    Label(String),
+
+    /// An offset from the start of the code, written as `@123`.
+    Offset(usize),
+}
+
+enum CodeItem {
+    Label(String),
+    Op {
+        line: usize,
+        op: Op,
+        targets: Vec<Target>,
+    },
+}
+
+struct ExceptionDef {
+    line: usize,
+    from: Target,
+    to: Target,
+    target: Target,
+    type_name: Index<Multiname>,
+    variable_name: Index<Multiname>,
+}
+
+#[derive(Default)]
+struct BodyDef {
+    line: usize,
+    max_stack: Option<u32>,
+    num_locals: Option<u32>,
+    scope_depth: Option<(u32, Option<u32>)>,
+    traits: Vec<Trait>,
+    code: Vec<CodeItem>,
+    exceptions: Vec<ExceptionDef>,
+}
+
+struct Assembler {
+    version: (u16, u16),
+    pool: PoolBuilder,
+    methods: Vec<Method>,
+    metadata: Vec<Metadata>,
+    instances: Vec<Instance>,
+    classes: Vec<Class>,
+    scripts: Vec<Script>,
+    method_bodies: Vec<MethodBody>,
+}
+
+impl Default for Assembler {
+    fn default() -> Self {
+        Self {
+            version: DEFAULT_VERSION,
+            pool: PoolBuilder::default(),
+            methods: vec![],
+            metadata: vec![],
+            instances: vec![],
+            classes: vec![],
+            scripts: vec![],
+            method_bodies: vec![],
+        }
+    }
+}
+
+impl Assembler {
+    fn finish(self) -> AbcFile {
+        AbcFile {
+            major_version: self.version.0,
+            minor_version: self.version.1,
+            constant_pool: self.pool.pool,
+            methods: self.methods,
+            metadata: self.metadata,
+            instances: self.instances,
+            classes: self.classes,
+            scripts: self.scripts,
+            method_bodies: self.method_bodies,
+        }
+    }
+
+    /// Check that a block has the index it would be given, as blocks must
+    /// be written in order.
+    fn expect_index(cursor: &mut Cursor, kind: &str, expected: usize) -> Result<(), Error> {
+        let index: usize = cursor.number()?;
+        if index != expected {
+            return Err(cursor.error(format!("Expected {kind} {expected}, found {index}")));
+        }
+        Ok(())
+    }
+
+    /// A string, or `null` for no string.
+    fn string(&mut self, cursor: &mut Cursor) -> Result<Index<String>, Error> {
+        match cursor.next()? {
+            Token::Str(string) => Ok(self.pool.string(string)),
+            Token::Word(word) if word == "null" => Ok(Index::new(0)),
+            other => Err(cursor.error(format!("Expected a string, found {other}"))),
+        }
+    }
+
+    /// A namespace, or `*` for any namespace.
+    fn namespace(&mut self, cursor: &mut Cursor) -> Result<Index<Namespace>, Error> {
+        if cursor.eat_punct('*') {
+            return Ok(Index::new(0));
+        }
+
+        let kind = match cursor.word()? {
+            "Namespace" => 0,
+            "PackageNamespace" => 1,
+            "PackageInternalNs" => 2,
+            "ProtectedNamespace" => 3,
+            "ExplicitNamespace" => 4,
+            "StaticProtectedNs" => 5,
+            "PrivateNamespace" => 6,
+            other => return Err(cursor.error(format!("Unknown namespace kind {other:?}"))),
+        };
+
+        cursor.punct('(')?;
+        let name = self.string(cursor)?;
+        // Private namespaces are only equal to themselves, so they are told
+        // apart by an ID.
+        let id = if kind == 6 {
+            cursor.punct(',')?;
+            Some(cursor.number()?)
+        } else {
+            None
+        };
+        cursor.punct(')')?;
+
+        Ok(self.pool.namespace(kind, name, id))
+    }
+
+    fn namespace_set(
+        &mut self,
+        cursor: &mut Cursor,
+    ) -> Result<Index<Vec<Index<Namespace>>>, Error> {
+        cursor.punct('[')?;
+        let mut namespaces = vec![];
+        if !cursor.eat_punct(']') {
+            loop {
+                namespaces.push(self.namespace(cursor)?);
+                if cursor.eat_punct(']') {
+                    break;
+                }
+                cursor.punct(',')?;
+            }
+        }
+        Ok(self.pool.namespace_set(namespaces))
+    }
+
+    /// A multiname, or `*` for any name.
+    fn multiname(&mut self, cursor: &mut Cursor) -> Result<Index<Multiname>, Error> {
+        if cursor.eat_punct('*') {
+            return Ok(Index::new(0));
+        }
+
+        let kind = cursor.word()?;
+        cursor.punct('(')?;
+        let multiname = match kind {
+            "QName" | "QNameA" => {
+                let namespace = self.namespace(cursor)?;
+                cursor.punct(',')?;
+                let name = self.string(cursor)?;
+                if kind == "QName" {
+                    Multiname::QName { namespace, name }
+                } else {
+                    Multiname::QNameA { namespace, name }
+                }
+            }
+            "RTQName" => Multiname::RTQName {
+                name: self.string(cursor)?,
+            },
+            "RTQNameA" => Multiname::RTQNameA {
+                name: self.string(cursor)?,
+            },
+            "RTQNameL" => Multiname::RTQNameL,
+            "RTQNameLA" => Multiname::RTQNameLA,
+            "Multiname" | "MultinameA" => {
+                let name = self.string(cursor)?;
+                cursor.punct(',')?;
+                let namespace_set = self.namespace_set(cursor)?;
+                if kind == "Multiname" {
+                    Multiname::Multiname {
+                        namespace_set,
+                        name,
+                    }
+                } else {
+                    Multiname::MultinameA {
+                        namespace_set,
+                        name,
+                    }
+                }
+            }
+            "MultinameL" => Multiname::MultinameL {
+                namespace_set: self.namespace_set(cursor)?,
+            },
+            "MultinameLA" => Multiname::MultinameLA {
+                namespace_set: self.namespace_set(cursor)?,
+            },
+            "TypeName" => {
+                let base_type = self.multiname(cursor)?;
+                cursor.punct('<')?;
+                let mut parameters = vec![self.multiname(cursor)?];
+                while cursor.eat_punct(',') {
+                    parameters.push(self.multiname(cursor)?);
+                }
+                cursor.punct('>')?;
+                Multiname::TypeName {
+                    base_type,
+                    parameters,
+                }
+            }
+            other => return Err(cursor.error(format!("Unknown multiname kind {other:?}"))),
+        };
+        cursor.punct(')')?;
+
+        Ok(self.pool.multiname(multiname))
+    }
+
+    fn default_value(&mut self, cursor: &mut Cursor) -> Result<DefaultValue, Error> {
+        let kind = match cursor.peek() {
+            Some(Token::Word(word)) => word.as_str(),
+            _ => return Err(cursor.error("Expected a value")),
+        };
+
+        let value = match kind {
+            "True" => DefaultValue::True,
+            "False" => DefaultValue::False,
+            "Null" => DefaultValue::Null,
+            "Undefined" => DefaultValue::Undefined,
+            "Int" | "Uint" | "Double" | "String" => {
+                cursor.next()?;
+                cursor.punct('(')?;
+                let value = match kind {
+                    "Int" => DefaultValue::Int(self.pool.int(cursor.number()?)),
+                    "Uint" => DefaultValue::Uint(self.pool.uint(cursor.number()?)),
+                    "Double" => DefaultValue::Double(self.pool.double(cursor.number()?)),
+                    _ => DefaultValue::String(self.string(cursor)?),
+                };
+                cursor.punct(')')?;
+                return Ok(value);
+            }
+            // The kind of the value is the kind of the namespace.
+            _ => {
+                let index = self.namespace(cursor)?;
+                let namespace = index
+                    .0
+                    .checked_sub(1)
+                    .and_then(|i| self.pool.pool.namespaces.get(i as usize))
+                    .ok_or_else(|| cursor.error("Expected a namespace"))?;
+                return Ok(match namespace {
+                    Namespace::Namespace(_) => DefaultValue::Namespace(index),
+                    Namespace::Package(_) => DefaultValue::Package(index),
+                    Namespace::PackageInternal(_) => DefaultValue::PackageInternal(index),
+                    Namespace::Protected(_) => DefaultValue::Protected(index),
+                    Namespace::Explicit(_) => DefaultValue::Explicit(index),
+                    Namespace::StaticProtected(_) => DefaultValue::StaticProtected(index),
+                    Namespace::Private(_) => DefaultValue::Private(index),
+                });
+            }
+        };
+        cursor.next()?;
+        Ok(value)
+    }
+
+    /// The rest of a `trait` line.
+    fn trait_def(&mut self, cursor: &mut Cursor) -> Result<Trait, Error> {
+        let kind = cursor.word()?;
+        let name = self.multiname(cursor)?;
+
+        let mut slot_id = 0;
+        let mut disp_id = 0;
+        let mut type_name = Index::new(0);
+        let mut value = None;
+        let mut method = None;
+        let mut class = None;
+        let mut is_final = false;
+        let mut is_override = false;
+        let mut metadata = vec![];
+
+        while !cursor.is_empty() {
+            match cursor.word()? {
+                "slot_id" => slot_id = cursor.number()?,
+                "disp_id" => disp_id = cursor.number()?,
+                "type" => type_name = self.multiname(cursor)?,
+                "value" => value = Some(self.default_value(cursor)?),
+                "method" => method = Some(Index::new(cursor.number()?)),
+                "class" => class = Some(Index::new(cursor.number()?)),
+                "final" => is_final = true,
+                "override" => is_override = true,
+                "metadata" => metadata.push(Index::new(cursor.number()?)),
+                other => return Err(cursor.error(format!("Unknown trait attribute {other:?}"))),
+            }
+        }
+
+        let method = |cursor: &Cursor| method.ok_or_else(|| cursor.error("Missing method"));
+        let kind = match kind {
+            "slot" => TraitKind::Slot {
+                slot_id,
+                type_name,
+                value,
+            },
+            "const" => TraitKind::Const {
+                slot_id,
+                type_name,
+                value,
+            },
+            "method" => TraitKind::Method {
+                disp_id,
+                method: method(cursor)?,
+            },
+            "getter" => TraitKind::Getter {
+                disp_id,
+                method: method(cursor)?,
+            },
+            "setter" => TraitKind::Setter {
+                disp_id,
+                method: method(cursor)?,
+            },
+            "class" => TraitKind::Class {
+                slot_id,
+                class: class.ok_or_else(|| cursor.error("Missing class"))?,
+            },
+            "function" => TraitKind::Function {
+                slot_id,
+                function: method(cursor)?,
+            },
+            other => return Err(cursor.error(format!("Unknown trait kind {other:?}"))),
+        };
+
+        Ok(Trait {
+            name,
+            kind,
+            metadata,
+            is_final,
+            is_override,
+        })
+    }
+
+    fn method(&mut self, mut cursor: Cursor, start: &Line, lines: &mut Lines) -> Result<(), Error> {
+        Self::expect_index(&mut cursor, "method", self.methods.len())?;
+        let name = self.string(&mut cursor)?;
+        cursor.finish()?;
+
+        let mut params = vec![];
+        let mut return_type = Index::new(0);
+        let mut flags = MethodFlags::empty();
+        let mut body = None;
+
+        loop {
+            let line = next_line(lines, start)?;
+            let mut cursor = line.cursor();
+            match cursor.word()? {
+                "param" => {
+                    let kind = self.multiname(&mut cursor)?;
+                    let mut name = None;
+                    let mut default_value = None;
+                    while !cursor.is_empty() {
+                        match cursor.word()? {
+                            "name" => name = Some(self.string(&mut cursor)?),
+                            "default" => default_value = Some(self.default_value(&mut cursor)?),
+                            other => {
+                                return Err(
+                                    cursor.error(format!("Unknown param attribute {other:?}"))
+                                )
+                            }
+                        }
+                    }
+                    params.push(MethodParam {
+                        name,
+                        kind,
+                        default_value,
+                    });
+                }
+                "returns" => return_type = self.multiname(&mut cursor)?,
+                "flags" => {
+                    while !cursor.is_empty() {
+                        flags |= match cursor.word()? {
+                            "need_arguments" => MethodFlags::NEED_ARGUMENTS,
+                            "need_activation" => MethodFlags::NEED_ACTIVATION,
+                            "need_rest" => MethodFlags::NEED_REST,
+                            "has_optional" => MethodFlags::HAS_OPTIONAL,
+                            "ignore_rest" => MethodFlags::IGNORE_REST,
+                            "native" => MethodFlags::NATIVE,
+                            "set_dxns" => MethodFlags::SET_DXNS,
+                            "has_param_names" => MethodFlags::HAS_PARAM_NAMES,
+                            other => {
+                                return Err(cursor.error(format!("Unknown method flag {other:?}")))
+                            }
+                        };
+                    }
+                }
+                "body" => body = Some(self.body_def(line, lines)?),
+                "end" => {
+                    cursor.finish()?;
+                    break;
+                }
+                other => return Err(cursor.error(format!("Unknown method directive {other:?}"))),
+            }
+            cursor.finish()?;
+        }
+
+        // These flags describe the params, so they can be worked out.
+        if params.iter().any(|param| param.default_value.is_some()) {
+            flags |= MethodFlags::HAS_OPTIONAL;
+        }
+        if params.iter().any(|param| param.name.is_some()) {
+            flags |= MethodFlags::HAS_PARAM_NAMES;
+        }
+
+        let method = Index::new(self.methods.len() as u32);
+        let body = match body {
+            Some(body) => {
+                let body = self.assemble_body(body, method, params.len() as u32, flags)?;
+                self.method_bodies.push(body);
+                Some(Index::new(self.method_bodies.len() as u32 - 1))
+            }
+            None => None,
+        };
+
+        self.methods.push(Method {
+            name,
+            params,
+            return_type,
+            flags,
+            body,
+        });
+        Ok(())
+    }
+
+    fn body_def(&mut self, start: &Line, lines: &mut Lines) -> Result<BodyDef, Error> {
+        let mut body = BodyDef {
+            line: start.number,
+            ..Default::default()
+        };
+
+        loop {
+            let line = next_line(lines, start)?;
+            let mut cursor = line.cursor();
+            match cursor.word()? {
+                "max_stack" => body.max_stack = Some(cursor.number()?),
+                "locals" => body.num_locals = Some(cursor.number()?),
+                "scope_depth" => {
+                    let init = cursor.number()?;
+                    let max = if cursor.is_empty() {
+                        None
+                    } else {
+                        Some(cursor.number()?)
+                    };
+                    body.scope_depth = Some((init, max));
+                }
+                "trait" => body.traits.push(self.trait_def(&mut cursor)?),
+                "code" => {
+                    cursor.finish()?;
+                    self.code(line, lines, &mut body.code)?;
+                }
+                "exception" => {
+                    let from = target(&mut cursor)?;
+                    let to = target(&mut cursor)?;
+                    let exception_target = target(&mut cursor)?;
+                    let mut type_name = Index::new(0);
+                    let mut variable_name = Index::new(0);
+                    while !cursor.is_empty() {
+                        match cursor.word()? {
+                            "type" => type_name = self.multiname(&mut cursor)?,
+                            "name" => variable_name = self.multiname(&mut cursor)?,
+                            other => {
+                                return Err(
+                                    cursor.error(format!("Unknown exception attribute {other:?}"))
+                                )
+                            }
+                        }
+                    }
+                    body.exceptions.push(ExceptionDef {
+                        line: line.number,
+                        from,
+                        to,
+                        target: exception_target,
+                        type_name,
+                        variable_name,
+                    });
+                }
+                "end" => {
+                    cursor.finish()?;
+                    return Ok(body);
+                }
+                other => return Err(cursor.error(format!("Unknown body directive {other:?}"))),
+            }
+            cursor.finish()?;
+        }
+    }
+
+    fn code(
+        &mut self,
+        start: &Line,
+        lines: &mut Lines,
+        code: &mut Vec<CodeItem>,
+    ) -> Result<(), Error> {
+        loop {
+            let line = next_line(lines, start)?;
+            if let Some(label) = line.label() {
+                code.push(CodeItem::Label(label.to_string()));
+                continue;
+            }
+
+            let mut cursor = line.cursor();
+            let mnemonic = cursor.word()?;
+            if mnemonic == "end" {
+                return cursor.finish();
+            }
+
+            let (op, targets) = self.op(mnemonic, &mut cursor)?;
+            cursor.finish()?;
+            code.push(CodeItem::Op {
+                line: line.number,
+                op,
+                targets,
+            });
+        }
+    }
+
+    /// Parse an op, along with the targets of any branches it makes.
+    ///
+    /// The offsets of branches are left as 0, to be filled in once the code
+    /// is laid out.
+    fn op(&mut self, mnemonic: &str, cursor: &mut Cursor) -> Result<(Op, Vec<Target>), Error> {
+        let op = match mnemonic {
+            "add" => Op::Add,
+            "addi" => Op::AddI,
+            "applytype" => Op::ApplyType {
+                num_types: cursor.number()?,
+            },
+            "astype" => Op::AsType {
+                type_name: self.multiname(cursor)?,
+            },
+            "astypelate" => Op::AsTypeLate,
+            "bitand" => Op::BitAnd,
+            "bitnot" => Op::BitNot,
+            "bitor" => Op::BitOr,
+            "bitxor" => Op::BitXor,
+            "bkpt" => Op::Bkpt,
+            "bkptline" => Op::BkptLine {
+                line_num: cursor.number()?,
+            },
+            "call" => Op::Call {
+                num_args: cursor.number()?,
+            },
+            "callmethod" => {
+                let index = cursor.number()?;
+                cursor.punct(',')?;
+                Op::CallMethod {
+                    index,
+                    num_args: cursor.number()?,
+                }
+            }
+            "callproperty" | "callproplex" | "callpropvoid" | "callsuper" | "callsupervoid"
+            | "constructprop" => {
+                let index = self.multiname(cursor)?;
+                cursor.punct(',')?;
+                let num_args = cursor.number()?;
+                match mnemonic {
+                    "callproperty" => Op::CallProperty { index, num_args },
+                    "callproplex" => Op::CallPropLex { index, num_args },
+                    "callpropvoid" => Op::CallPropVoid { index, num_args },
+                    "callsuper" => Op::CallSuper { index, num_args },
+                    "callsupervoid" => Op::CallSuperVoid { index, num_args },
+                    _ => Op::ConstructProp { index, num_args },
+                }
+            }
+            "callstatic" => {
+                let index = Index::new(cursor.number()?);
+                cursor.punct(',')?;
+                Op::CallStatic {
+                    index,
+                    num_args: cursor.number()?,
+                }
+            }
+            "checkfilter" => Op::CheckFilter,
+            "coerce" => Op::Coerce {
+                index: self.multiname(cursor)?,
+            },
+            "coercea" => Op::CoerceA,
+            "coerceb" => Op::CoerceB,
+            "coerced" => Op::CoerceD,
+            "coercei" => Op::CoerceI,
+            "coerceo" => Op::CoerceO,
+            "coerces" => Op::CoerceS,
+            "coerceu" => Op::CoerceU,
+            "construct" => Op::Construct {
+                num_args: cursor.number()?,
+            },
+            "constructsuper" => Op::ConstructSuper {
+                num_args: cursor.number()?,
+            },
+            "convertb" => Op::ConvertB,
+            "convertd" => Op::ConvertD,
+            "converti" => Op::ConvertI,
+            "converto" => Op::ConvertO,
+            "converts" => Op::ConvertS,
+            "convertu" => Op::ConvertU,
+            "debug" => {
+                let is_local_register = cursor.number::<u8>()? != 0;
+                cursor.punct(',')?;
+                let register_name = self.string(cursor)?;
+                cursor.punct(',')?;
+                Op::Debug {
+                    is_local_register,
+                    register_name,
+                    register: cursor.number()?,
+                }
+            }
+            "debugfile" => Op::DebugFile {
+                file_name: self.string(cursor)?,
+            },
+            "debugline" => Op::DebugLine {
+                line_num: cursor.number()?,
+            },
+            "declocal" => Op::DecLocal {
+                index: cursor.number()?,
+            },
+            "declocali" => Op::DecLocalI {
+                index: cursor.number()?,
+            },
+            "decrement" => Op::Decrement,
+            "decrementi" => Op::DecrementI,
+            "deleteproperty" => Op::DeleteProperty {
+                index: self.multiname(cursor)?,
+            },
+            "divide" => Op::Divide,
+            "dup" => Op::Dup,
+            "dxns" => Op::Dxns {
+                index: self.string(cursor)?,
+            },
+            "dxnslate" => Op::DxnsLate,
+            "equals" => Op::Equals,
+            "escxattr" => Op::EscXAttr,
+            "escxelem" => Op::EscXElem,
+            "finddef" => Op::FindDef {
+                index: self.multiname(cursor)?,
+            },
+            "findproperty" => Op::FindProperty {
+                index: self.multiname(cursor)?,
+            },
+            "findpropstrict" => Op::FindPropStrict {
+                index: self.multiname(cursor)?,
+            },
+            "getdescendants" => Op::GetDescendants {
+                index: self.multiname(cursor)?,
+            },
+            "getglobalscope" => Op::GetGlobalScope,
+            "getglobalslot" => Op::GetGlobalSlot {
+                index: cursor.number()?,
+            },
+            "getlex" => Op::GetLex {
+                index: self.multiname(cursor)?,
+            },
+            "getlocal" => Op::GetLocal {
+                index: cursor.number()?,
+            },
+            "getouterscope" => Op::GetOuterScope {
+                index: cursor.number()?,
+            },
+            "getproperty" => Op::GetProperty {
+                index: self.multiname(cursor)?,
+            },
+            "getscopeobject" => Op::GetScopeObject {
+                index: cursor.number()?,
+            },
+            "getslot" => Op::GetSlot {
+                index: cursor.number()?,
+            },
+            "getsuper" => Op::GetSuper {
+                index: self.multiname(cursor)?,
+            },
+            "greaterequals" => Op::GreaterEquals,
+            "greaterthan" => Op::GreaterThan,
+            "hasnext" => Op::HasNext,
+            "hasnext2" => {
+                let object_register = cursor.number()?;
+                cursor.punct(',')?;
+                Op::HasNext2 {
+                    object_register,
+                    index_register: cursor.number()?,
+                }
+            }
+            "ifeq" | "iffalse" | "ifge" | "ifgt" | "ifle" | "iflt" | "ifne" | "ifnge" | "ifngt"
+            | "ifnle" | "ifnlt" | "ifstricteq" | "ifstrictne" | "iftrue" | "jump" => {
+                let offset = 0;
+                let op = match mnemonic {
+                    "ifeq" => Op::IfEq { offset },
+                    "iffalse" => Op::IfFalse { offset },
+                    "ifge" => Op::IfGe { offset },
+                    "ifgt" => Op::IfGt { offset },
+                    "ifle" => Op::IfLe { offset },
+                    "iflt" => Op::IfLt { offset },
+                    "ifne" => Op::IfNe { offset },
+                    "ifnge" => Op::IfNge { offset },
+                    "ifngt" => Op::IfNgt { offset },
+                    "ifnle" => Op::IfNle { offset },
+                    "ifnlt" => Op::IfNlt { offset },
+                    "ifstricteq" => Op::IfStrictEq { offset },
+                    "ifstrictne" => Op::IfStrictNe { offset },
+                    "iftrue" => Op::IfTrue { offset },
+                    _ => Op::Jump { offset },
+                };
+                return Ok((op, vec![target(cursor)?]));
+            }
+            "in" => Op::In,
+            "inclocal" => Op::IncLocal {
+                index: cursor.number()?,
+            },
+            "inclocali" => Op::IncLocalI {
+                index: cursor.number()?,
+            },
+            "increment" => Op::Increment,
+            "incrementi" => Op::IncrementI,
+            "initproperty" => Op::InitProperty {
+                index: self.multiname(cursor)?,
+            },
+            "instanceof" => Op::InstanceOf,
+            "istype" => Op::IsType {
+                index: self.multiname(cursor)?,
+            },
+            "istypelate" => Op::IsTypeLate,
+            "kill" => Op::Kill {
+                index: cursor.number()?,
+            },
+            "label" => Op::Label,
+            "lessequals" => Op::LessEquals,
+            "lessthan" => Op::LessThan,
+            "lf32" => Op::Lf32,
+            "lf64" => Op::Lf64,
+            "li16" => Op::Li16,
+            "li32" => Op::Li32,
+            "li8" => Op::Li8,
+            "lookupswitch" => {
+                let mut targets = vec![target(cursor)?];
+                cursor.punct(',')?;
+                cursor.punct('[')?;
+                if !cursor.eat_punct(']') {
+                    loop {
+                        targets.push(target(cursor)?);
+                        if cursor.eat_punct(']') {
+                            break;
+                        }
+                        cursor.punct(',')?;
+                    }
+                }
+                let op = Op::LookupSwitch(Box::new(LookupSwitch {
+                    default_offset: 0,
+                    case_offsets: vec![0; targets.len() - 1].into_boxed_slice(),
+                }));
+                return Ok((op, targets));
+            }
+            "lshift" => Op::LShift,
+            "modulo" => Op::Modulo,
+            "multiply" => Op::Multiply,
+            "multiplyi" => Op::MultiplyI,
+            "negate" => Op::Negate,
+            "negatei" => Op::NegateI,
+            "newactivation" => Op::NewActivation,
+            "newarray" => Op::NewArray {
+                num_args: cursor.number()?,
+            },
+            "newcatch" => Op::NewCatch {
+                index: Index::new(cursor.number()?),
+            },
+            "newclass" => Op::NewClass {
+                index: Index::new(cursor.number()?),
+            },
+            "newfunction" => Op::NewFunction {
+                index: Index::new(cursor.number()?),
+            },
+            "newobject" => Op::NewObject {
+                num_args: cursor.number()?,
+            },
+            "nextname" => Op::NextName,
+            "nextvalue" => Op::NextValue,
+            "nop" => Op::Nop,
+            "not" => Op::Not,
+            "pop" => Op::Pop,
+            "popscope" => Op::PopScope,
+            "pushbyte" => {
+                // The byte is sign extended when it is pushed, so a negative
+                // value is allowed too.
+                let value: i16 = cursor.number()?;
+                if !(-128..=255).contains(&value) {
+                    return Err(cursor.error(format!("{value} doesn't fit in a byte")));
+                }
+                Op::PushByte { value: value as u8 }
+            }
+            "pushdouble" => Op::PushDouble {
+                value: self.pool.double(cursor.number()?),
+            },
+            "pushfalse" => Op::PushFalse,
+            "pushint" => Op::PushInt {
+                value: self.pool.int(cursor.number()?),
+            },
+            "pushnamespace" => Op::PushNamespace {
+                value: self.namespace(cursor)?,
+            },
+            "pushnan" => Op::PushNaN,
+            "pushnull" => Op::PushNull,
+            "pushscope" => Op::PushScope,
+            "pushshort" => Op::PushShort {
+                value: cursor.number()?,
+            },
+            "pushstring" => Op::PushString {
+                value: self.string(cursor)?,
+            },
+            "pushtrue" => Op::PushTrue,
+            "pushuint" => Op::PushUint {
+                value: self.pool.uint(cursor.number()?),
+            },
+            "pushundefined" => Op::PushUndefined,
+            "pushwith" => Op::PushWith,
+            "returnvalue" => Op::ReturnValue,
+            "returnvoid" => Op::ReturnVoid,
+            "rshift" => Op::RShift,
+            "setglobalslot" => Op::SetGlobalSlot {
+                index: cursor.number()?,
+            },
+            "setlocal" => Op::SetLocal {
+                index: cursor.number()?,
+            },
+            "setproperty" => Op::SetProperty {
+                index: self.multiname(cursor)?,
+            },
+            "setslot" => Op::SetSlot {
+                index: cursor.number()?,
+            },
+            "setsuper" => Op::SetSuper {
+                index: self.multiname(cursor)?,
+            },
+            "sf32" => Op::Sf32,
+            "sf64" => Op::Sf64,
+            "si16" => Op::Si16,
+            "si32" => Op::Si32,
+            "si8" => Op::Si8,
+            "strictequals" => Op::StrictEquals,
+            "subtract" => Op::Subtract,
+            "subtracti" => Op::SubtractI,
+            "swap" => Op::Swap,
+            "sxi1" => Op::Sxi1,
+            "sxi16" => Op::Sxi16,
+            "sxi8" => Op::Sxi8,
+            "throw" => Op::Throw,
+            "typeof" => Op::TypeOf,
+            "timestamp" => Op::Timestamp,
+            "urshift" => Op::URShift,
+            other => return Err(cursor.error(format!("Unknown op {other:?}"))),
+        };
+
+        Ok((op, vec![]))
+    }
+
+    fn assemble_body(
+        &mut self,
+        body: BodyDef,
+        method: Index<Method>,
+        num_params: u32,
+        flags: MethodFlags,
+    ) -> Result<MethodBody, Error> {
+        let error = |line, message: String| Error::Parse { line, message };
+
+        // Branches are always the same size, whatever their offset, so the
+        // code can be laid out before any of them are known.
+        let mut labels = HashMap::new();
+        let mut ops = vec![];
+        let mut offset = 0;
+        for item in body.code {
+            match item {
+                CodeItem::Label(name) => {
+                    if labels.insert(name.clone(), offset).is_some() {
+                        return Err(error(body.line, format!("Duplicate label {name:?}")));
+                    }
+                }
+                CodeItem::Op { line, op, targets } => {
+                    let length = encode(&op)?.len();
+                    ops.push(LaidOutOp {
+                        line,
+                        start: offset,
+                        end: offset + length,
+                        op,
+                        targets,
+                    });
+                    offset += length;
+                }
+            }
+        }
+
+        let resolve = |line, target: &Target| match target {
+            Target::Label(name) => labels
+                .get(name)
+                .copied()
+                .ok_or_else(|| error(line, format!("Unknown label {name:?}"))),
+            Target::Offset(offset) => Ok(*offset),
+        };
+
+        let mut code = vec![];
+        for laid_out in &mut ops {
+            let targets = laid_out
+                .targets
+                .iter()
+                .map(|target| resolve(laid_out.line, target).map(|target| target as i32))
+                .collect::<Result<Vec<_>, _>>()?;
+            let (start, end) = (laid_out.start as i32, laid_out.end as i32);
+
+            match &mut laid_out.op {
+                Op::LookupSwitch(lookup_switch) => {
+                    // Unlike other branches, these are relative to the start
+                    // of the op.
+                    lookup_switch.default_offset = targets[0] - start;
+                    for (case, target) in lookup_switch.case_offsets.iter_mut().zip(&targets[1..]) {
+                        *case = target - start;
+                    }
+                }
+                op => {
+                    if let (Some(offset), Some(target)) = (branch_offset_mut(op), targets.first()) {
+                        *offset = target - end;
+                    }
+                }
+            }
+
+            code.extend(encode(&laid_out.op)?);
+        }
+
+        let exceptions = body
+            .exceptions
+            .iter()
+            .map(|exception| {
+                Ok(Exception {
+                    from_offset: resolve(exception.line, &exception.from)? as u32,
+                    to_offset: resolve(exception.line, &exception.to)? as u32,
+                    target_offset: resolve(exception.line, &exception.target)? as u32,
+                    variable_name: exception.variable_name,
+                    type_name: exception.type_name,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let (max_stack, max_scope) = self.stack_usage(&ops, &exceptions);
+        let (init_scope_depth, max_scope_depth) = match body.scope_depth {
+            Some((init, Some(max))) => (init, max),
+            Some((init, None)) => (init, init + max_scope),
+            None => (0, max_scope),
+        };
+
+        Ok(MethodBody {
+            method,
+            max_stack: body.max_stack.unwrap_or(max_stack),
+            num_locals: body
+                .num_locals
+                .unwrap_or_else(|| num_locals(&ops, num_params, flags)),
+            init_scope_depth,
+            max_scope_depth,
+            code,
+            exceptions,
+            traits: body.traits,
+        })
+    }
+
+    /// Work out the deepest that the stack and scope stack get while running
+    /// some code.
+    fn stack_usage(&self, ops: &[LaidOutOp], exceptions: &[Exception]) -> (u32, u32) {
+        let index_of: HashMap<usize, usize> = ops
+            .iter()
+            .enumerate()
+            .map(|(index, op)| (op.start, index))
+            .collect();
+
+        // Each op is only visited once, along the first path found to it.
+        // Valid code reaches each op with the same depths along every path.
+        let mut visited = vec![false; ops.len()];
+        let mut pending: Vec<(usize, u32, u32)> = vec![(0, 0, 0)];
+        for exception in exceptions {
+            // Handlers start with just the caught value on the stack.
+            if let Some(index) = index_of.get(&(exception.target_offset as usize)) {
+                pending.push((*index, 1, 0));
+            }
+        }
+
+        let (mut max_stack, mut max_scope) = (0, 0);
+        while let Some((index, stack, scope)) = pending.pop() {
+            let Some(laid_out) = ops.get(index) else {
+                continue;
+            };
+            if std::mem::replace(&mut visited[index], true) {
+                continue;
+            }
+
+            let (pops, pushes) = self.stack_effect(&laid_out.op);
+            let stack_after = stack.saturating_sub(pops) + pushes;
+            let scope = match laid_out.op {
+                Op::PushScope | Op::PushWith => scope + 1,
+                Op::PopScope => scope.saturating_sub(1),
+                _ => scope,
+            };
+            max_stack = max_stack.max(stack).max(stack_after);
+            max_scope = max_scope.max(scope);
+
+            let mut successors = vec![];
+            match &laid_out.op {
+                Op::Jump { offset } => {
+                    successors.push(laid_out.end as isize + *offset as isize);
+                }
+                Op::LookupSwitch(lookup_switch) => {
+                    successors.extend(
+                        std::iter::once(lookup_switch.default_offset)
+                            .chain(lookup_switch.case_offsets.iter().copied())
+                            .map(|offset| laid_out.start as isize + offset as isize),
+                    );
+                }
+                Op::ReturnValue | Op::ReturnVoid | Op::Throw => {}
+                op => {
+                    successors.push(laid_out.end as isize);
+                    if let Some(offset) = branch_offset(op) {
+                        successors.push(laid_out.end as isize + offset as isize);
+                    }
+                }
+            }
+
+            for successor in successors {
+                if let Some(index) = usize::try_from(successor)
+                    .ok()
+                    .and_then(|successor| index_of.get(&successor))
+                {
+                    pending.push((*index, stack_after, scope));
+                }
+            }
+        }
+
+        (max_stack, max_scope)
+    }
+
+    /// How many values an op takes from the stack, and how many it pushes.
+    fn stack_effect(&self, op: &Op) -> (u32, u32) {
+        match op {
+            Op::Add
+            | Op::AddI
+            | Op::AsTypeLate
+            | Op::BitAnd
+            | Op::BitOr
+            | Op::BitXor
+            | Op::Divide
+            | Op::Equals
+            | Op::GreaterEquals
+            | Op::GreaterThan
+            | Op::HasNext
+            | Op::In
+            | Op::InstanceOf
+            | Op::IsTypeLate
+            | Op::LessEquals
+            | Op::LessThan
+            | Op::LShift
+            | Op::Modulo
+            | Op::Multiply
+            | Op::MultiplyI
+            | Op::NextName
+            | Op::NextValue
+            | Op::RShift
+            | Op::StrictEquals
+            | Op::Subtract
+            | Op::SubtractI
+            | Op::URShift => (2, 1),
+            Op::AsType { .. }
+            | Op::BitNot
+            | Op::CheckFilter
+            | Op::Coerce { .. }
+            | Op::CoerceA
+            | Op::CoerceB
+            | Op::CoerceD
+            | Op::CoerceI
+            | Op::CoerceO
+            | Op::CoerceS
+            | Op::CoerceU
+            | Op::ConvertB
+            | Op::ConvertD
+            | Op::ConvertI
+            | Op::ConvertO
+            | Op::ConvertS
+            | Op::ConvertU
+            | Op::Decrement
+            | Op::DecrementI
+            | Op::EscXAttr
+            | Op::EscXElem
+            | Op::GetSlot { .. }
+            | Op::Increment
+            | Op::IncrementI
+            | Op::IsType { .. }
+            | Op::Lf32
+            | Op::Lf64
+            | Op::Li16
+            | Op::Li32
+            | Op::Li8
+            | Op::Negate
+            | Op::NegateI
+            | Op::NewClass { .. }
+            | Op::Not
+            | Op::Sxi1
+            | Op::Sxi16
+            | Op::Sxi8
+            | Op::TypeOf => (1, 1),
+            Op::ApplyType { num_types } => (num_types + 1, 1),
+            Op::Call { num_args } => (num_args + 2, 1),
+            Op::CallMethod { num_args, .. }
+            | Op::CallStatic { num_args, .. }
+            | Op::Construct { num_args } => (num_args + 1, 1),
+            Op::CallProperty { index, num_args }
+            | Op::CallPropLex { index, num_args }
+            | Op::CallSuper { index, num_args }
+            | Op::ConstructProp { index, num_args } => {
+                (num_args + 1 + self.pool.runtime_parts(index), 1)
+            }
+            Op::CallPropVoid { index, num_args } | Op::CallSuperVoid { index, num_args } => {
+                (num_args + 1 + self.pool.runtime_parts(index), 0)
+            }
+            Op::ConstructSuper { num_args } => (num_args + 1, 0),
+            Op::DeleteProperty { index }
+            | Op::GetDescendants { index }
+            | Op::GetProperty { index }
+            | Op::GetSuper { index } => (1 + self.pool.runtime_parts(index), 1),
+            Op::FindProperty { index } | Op::FindPropStrict { index } => {
+                (self.pool.runtime_parts(index), 1)
+            }
+            Op::InitProperty { index } | Op::SetProperty { index } | Op::SetSuper { index } => {
+                (2 + self.pool.runtime_parts(index), 0)
+            }
+            Op::Dup => (1, 2),
+            Op::Swap => (2, 2),
+            Op::DxnsLate
+            | Op::IfFalse { .. }
+            | Op::IfTrue { .. }
+            | Op::LookupSwitch(_)
+            | Op::Pop
+            | Op::PushScope
+            | Op::PushWith
+            | Op::ReturnValue
+            | Op::SetGlobalSlot { .. }
+            | Op::SetLocal { .. }
+            | Op::Throw => (1, 0),
+            Op::IfEq { .. }
+            | Op::IfGe { .. }
+            | Op::IfGt { .. }
+            | Op::IfLe { .. }
+            | Op::IfLt { .. }
+            | Op::IfNe { .. }
+            | Op::IfNge { .. }
+            | Op::IfNgt { .. }
+            | Op::IfNle { .. }
+            | Op::IfNlt { .. }
+            | Op::IfStrictEq { .. }
+            | Op::IfStrictNe { .. }
+            | Op::SetSlot { .. }
+            | Op::Sf32
+            | Op::Sf64
+            | Op::Si16
+            | Op::Si32
+            | Op::Si8 => (2, 0),
+            Op::FindDef { .. }
+            | Op::GetGlobalScope
+            | Op::GetGlobalSlot { .. }
+            | Op::GetLex { .. }
+            | Op::GetLocal { .. }
+            | Op::GetOuterScope { .. }
+            | Op::GetScopeObject { .. }
+            | Op::HasNext2 { .. }
+            | Op::NewActivation
+            | Op::NewCatch { .. }
+            | Op::NewFunction { .. }
+            | Op::PushByte { .. }
+            | Op::PushDouble { .. }
+            | Op::PushFalse
+            | Op::PushInt { .. }
+            | Op::PushNamespace { .. }
+            | Op::PushNaN
+            | Op::PushNull
+            | Op::PushShort { .. }
+            | Op::PushString { .. }
+            | Op::PushTrue
+            | Op::PushUint { .. }
+            | Op::PushUndefined => (0, 1),
+            Op::NewArray { num_args } => (*num_args, 1),
+            Op::NewObject { num_args } => (num_args * 2, 1),
+            Op::Bkpt
+            | Op::BkptLine { .. }
+            | Op::Debug { .. }
+            | Op::DebugFile { .. }
+            | Op::DebugLine { .. }
+            | Op::DecLocal { .. }
+            | Op::DecLocalI { .. }
+            | Op::Dxns { .. }
+            | Op::IncLocal { .. }
+            | Op::IncLocalI { .. }
+            | Op::Jump { .. }
+            | Op::Kill { .. }
+            | Op::Label
+            | Op::Nop
+            | Op::PopScope
+            | Op::ReturnVoid
+            | Op::Timestamp => (0, 0),
+        }
+    }
+
+    fn metadata(
+        &mut self,
+        mut cursor: Cursor,
+        start: &Line,
+        lines: &mut Lines,
+    ) -> Result<(), Error> {
+        Self::expect_index(&mut cursor, "metadata", self.metadata.len())?;
+        let name = self.string(&mut cursor)?;
+        cursor.finish()?;
+
+        let mut items = vec![];
+        loop {
+            let line = next_line(lines, start)?;
+            let mut cursor = line.cursor();
+            match cursor.word()? {
+                "item" => items.push(MetadataItem {
+                    key: self.string(&mut cursor)?,
+                    value: self.string(&mut cursor)?,
+                }),
+                "end" => {
+                    cursor.finish()?;
+                    break;
+                }
+                other => return Err(cursor.error(format!("Unknown metadata directive {other:?}"))),
+            }
+            cursor.finish()?;
+        }
+
+        self.metadata.push(Metadata { name, items });
+        Ok(())
+    }
+
+    fn class(&mut self, mut cursor: Cursor, start: &Line, lines: &mut Lines) -> Result<(), Error> {
+        Self::expect_index(&mut cursor, "class", self.instances.len())?;
+        let name = self.multiname(&mut cursor)?;
+        cursor.finish()?;
+
+        let mut instance = Instance {
+            name,
+            super_name: Index::new(0),
+            is_sealed: false,
+            is_final: false,
+            is_interface: false,
+            protected_namespace: None,
+            interfaces: vec![],
+            init_method: Index::new(0),
+            traits: vec![],
+        };
+        let mut class = Class {
+            init_method: Index::new(0),
+            traits: vec![],
+        };
+        let (mut has_iinit, mut has_cinit) = (false, false);
+
+        loop {
+            let line = next_line(lines, start)?;
+            let mut cursor = line.cursor();
+            match cursor.word()? {
+                "extends" => instance.super_name = self.multiname(&mut cursor)?,
+                "implements" => instance.interfaces.push(self.multiname(&mut cursor)?),
+                "flags" => {
+                    while !cursor.is_empty() {
+                        match cursor.word()? {
+                            "sealed" => instance.is_sealed = true,
+                            "final" => instance.is_final = true,
+                            "interface" => instance.is_interface = true,
+                            other => {
+                                return Err(cursor.error(format!("Unknown class flag {other:?}")))
+                            }
+                        }
+                    }
+                }
+                "protected_ns" => instance.protected_namespace = Some(self.namespace(&mut cursor)?),
+                "iinit" => {
+                    instance.init_method = Index::new(cursor.number()?);
+                    has_iinit = true;
+                }
+                "cinit" => {
+                    class.init_method = Index::new(cursor.number()?);
+                    has_cinit = true;
+                }
+                "trait" => instance.traits.push(self.trait_def(&mut cursor)?),
+                "static" => {
+                    if cursor.word()? != "trait" {
+                        return Err(cursor.error("Expected trait after static"));
+                    }
+                    class.traits.push(self.trait_def(&mut cursor)?);
+                }
+                "end" => {
+                    if !has_iinit || !has_cinit {
+                        return Err(cursor.error("Classes need both an iinit and a cinit"));
+                    }
+                    cursor.finish()?;
+                    break;
+                }
+                other => return Err(cursor.error(format!("Unknown class directive {other:?}"))),
+            }
+            cursor.finish()?;
+        }
+
+        self.instances.push(instance);
+        self.classes.push(class);
+        Ok(())
+    }
+
+    fn script(&mut self, mut cursor: Cursor, start: &Line, lines: &mut Lines) -> Result<(), Error> {
+        Self::expect_index(&mut cursor, "script", self.scripts.len())?;
+        cursor.finish()?;
+
+        let mut init_method = None;
+        let mut traits = vec![];
+        loop {
+            let line = next_line(lines, start)?;
+            let mut cursor = line.cursor();
+            match cursor.word()? {
+                "init" => init_method = Some(Index::new(cursor.number()?)),
+                "trait" => traits.push(self.trait_def(&mut cursor)?),
+                "end" => {
+                    cursor.finish()?;
+                    break;
+                }
+                other => return Err(cursor.error(format!("Unknown script directive {other:?}"))),
+            }
+            cursor.finish()?;
+        }
+
+        self.scripts.push(Script {
+            init_method: init_method.ok_or_else(|| Error::Parse {
+                line: start.number,
+                message: "Scripts need an init".to_string(),
+            })?,
+            traits,
+        });
+        Ok(())
+    }
+}
+
+/// An op, along with where it was placed in its code.
+struct LaidOutOp {
+    line: usize,
+    start: usize,
+    end: usize,
+    op: Op,
+    targets: Vec<Target>,
+}
+
+/// A label, or an offset such as `@123`.
+fn target(cursor: &mut Cursor) -> Result<Target, Error> {
+    let word = cursor.word()?;
+    match word.strip_prefix('@') {
+        Some(offset) => offset
+            .parse()
+            .map(Target::Offset)
+            .map_err(|_| cursor.error(format!("Invalid offset {word:?}"))),
+        None => Ok(Target::Label(word.to_string())),
+    }
+}
+
+fn encode(op: &Op) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![];
+    Writer::new(&mut bytes).write_op(op)?;
+    Ok(bytes)
+}
+
+/// The offset of a branch, relative to the end of the op.
+fn branch_offset(op: &Op) -> Option<i32> {
+    match op {
+        Op::IfEq { offset }
+        | Op::IfFalse { offset }
+        | Op::IfGe { offset }
+        | Op::IfGt { offset }
+        | Op::IfLe { offset }
+        | Op::IfLt { offset }
+        | Op::IfNe { offset }
+        | Op::IfNge { offset }
+        | Op::IfNgt { offset }
+        | Op::IfNle { offset }
+        | Op::IfNlt { offset }
+        | Op::IfStrictEq { offset }
+        | Op::IfStrictNe { offset }
+        | Op::IfTrue { offset }
+        | Op::Jump { offset } => Some(*offset),
+        _ => None,
+    }
+}
+
+fn branch_offset_mut(op: &mut Op) -> Option<&mut i32> {
+    match op {
+        Op::IfEq { offset }
+        | Op::IfFalse { offset }
+        | Op::IfGe { offset }
+        | Op::IfGt { offset }
+        | Op::IfLe { offset }
+        | Op::IfLt { offset }
+        | Op::IfNe { offset }
+        | Op::IfNge { offset }
+        | Op::IfNgt { offset }
+        | Op::IfNle { offset }
+        | Op::IfNlt { offset }
+        | Op::IfStrictEq { offset }
+        | Op::IfStrictNe { offset }
+        | Op::IfTrue { offset }
+        | Op::Jump { offset } => Some(offset),
+        _ => None,
+    }
+}
+
+/// Work out how many local registers some code needs.
+///
+/// This is at least enough for `this`, the params, and the rest array or
+/// `arguments` object.
+fn num_locals(ops: &[LaidOutOp], num_params: u32, flags: MethodFlags) -> u32 {
+    let mut num_locals = num_params + 1;
+    if flags.intersects(MethodFlags::NEED_REST | MethodFlags::NEED_ARGUMENTS) {
+        num_locals += 1;
+    }
+
+    for laid_out in ops {
+        let registers = match laid_out.op {
+            Op::GetLocal { index }
+            | Op::SetLocal { index }
+            | Op::Kill { index }
+            | Op::IncLocal { index }
+            | Op::IncLocalI { index }
+            | Op::DecLocal { index }
+            | Op::DecLocalI { index } => [Some(index), None],
+            Op::HasNext2 {
+                object_register,
+                index_register,
+            } => [Some(object_register), Some(index_register)],
+            _ => [None, None],
+        };
+        for register in registers.into_iter().flatten() {
+            num_locals = num_locals.max(register + 1);
+        }
+    }
+
+    num_locals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abc::AbcInfo;
+    use swf::avm2::read::Reader;
+
+    const HELLO: &str = r#"
+        ; Traces "Hello" ten times, then throws and catches an error.
+        version 46 16
+
+        method 0 null
+          returns *
+          body
+            code
+              getlocal 0
+              pushscope
+              pushbyte 0
+              setlocal 1
+            L0:
+              findpropstrict QName(PackageNamespace(""), "trace")
+              pushstring "Hello"
+              callpropvoid QName(PackageNamespace(""), "trace"), 1
+              inclocali 1
+              getlocal 1
+              pushbyte 10
+              iflt L0
+            L1:
+              findpropstrict QName(PackageNamespace(""), "Error")
+              pushstring "Oops \"quoted\"\n"
+              constructprop QName(PackageNamespace(""), "Error"), 1
+              throw
+            L2:
+              returnvoid
+            L3:
+              getlocal 0
+              pushscope
+              newcatch 0
+              pop
+              jump L2
+            end
+            exception L1 L2 L3 type * name *
+          end
+        end
+
+        script 0
+          init 0
+        end
+    "#;
+
+    fn write(abc: AbcFile) -> Vec<u8> {
+        let mut data = vec![];
+        Writer::new(&mut data).write(abc).unwrap();
+        data
+    }
+
+    #[test]
+    fn computes_body_sizes() {
+        let abc = assemble(HELLO).unwrap();
+        let body = &abc.method_bodies[0];
+        assert_eq!(body.max_stack, 2);
+        assert_eq!(body.num_locals, 2);
+        assert_eq!(body.init_scope_depth, 0);
+        assert_eq!(body.max_scope_depth, 1);
+        assert_eq!(abc.methods[0].body, Some(Index::new(0)));
+    }
+
+    #[test]
+    fn deduplicates_constants() {
+        let abc = assemble(HELLO).unwrap();
+        let pool = &abc.constant_pool;
+        assert_eq!(pool.namespaces.len(), 1);
+        assert_eq!(pool.multinames.len(), 2);
+        assert_eq!(
+            pool.strings,
+            vec![
+                b"".to_vec(),
+                b"trace".to_vec(),
+                b"Hello".to_vec(),
+                b"Error".to_vec(),
+                b"Oops \"quoted\"\n".to_vec(),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_labels() {
+        let abc = assemble(HELLO).unwrap();
+        let code = &abc.method_bodies[0].code;
+        let mut reader = Reader::new(code);
+        let ops: Vec<Op> = std::iter::from_fn(|| reader.read_op().ok()).collect();
+        assert_eq!(ops[10], Op::IfLt { offset: -16 });
+        assert_eq!(ops[20], Op::Jump { offset: -10 });
+
+        let exception = &abc.method_bodies[0].exceptions[0];
+        assert_eq!(exception.from_offset, 21);
+        assert_eq!(exception.to_offset, 29);
+        assert_eq!(exception.target_offset, 30);
+    }
+
+    #[test]
+    fn round_trips_through_disassembly() {
+        let data = write(assemble(HELLO).unwrap());
+        let abc = Reader::new(&data).read().unwrap();
+        assert_eq!(write(abc.clone()), data);
+
+        let text = AbcInfo::new(&abc).to_string();
+        assert_eq!(write(assemble(&text).unwrap()), data);
+    }
+
+    #[test]
+    fn round_trips_classes_and_defaults() {
+        let text = r#"
+            method 0 "f"
+              param QName(PackageNamespace(""), "int") name "a"
+              param * name "b" default Double(-1.5)
+              param * name "c" default PackageNamespace("flash.display")
+              returns QName(PackageNamespace(""), "void")
+              flags need_rest
+              body
+                code
+                  getlocal 3
+                  returnvalue
+                end
+              end
+            end
+
+            method 1 null
+              returns *
+            end
+
+            metadata 0 "Event"
+              item "name" "change"
+              item null "x"
+            end
+
+            class 0 QName(PackageNamespace("a.b"), "C")
+              extends QName(PackageNamespace(""), "Object")
+              implements Multiname("I", [PackageNamespace("a.b"), PrivateNamespace("C", 1)])
+              flags sealed final
+              protected_ns ProtectedNamespace("a.b:C")
+              iinit 1
+              cinit 1
+              trait method QName(PrivateNamespace("C", 1), "f") disp_id 2 method 0 final metadata 0
+              static trait const QName(PackageNamespace(""), "X") slot_id 1 type * value Int(-7)
+              static trait slot QName(PackageNamespace(""), "V") slot_id 2 type TypeName(QName(PackageNamespace("__AS3__.vec"), "Vector")<QName(PackageNamespace(""), "int")>) value Null
+            end
+
+            script 0
+              init 1
+              trait class QName(PackageNamespace("a.b"), "C") slot_id 1 class 0
+            end
+        "#;
+
+        let abc = assemble(text).unwrap();
+        assert_eq!(
+            abc.methods[0].flags,
+            MethodFlags::NEED_REST | MethodFlags::HAS_OPTIONAL | MethodFlags::HAS_PARAM_NAMES
+        );
+        assert_eq!(abc.method_bodies[0].num_locals, 5);
+        assert_eq!(abc.methods[1].body, None);
+
+        let data = write(abc);
+        let read = Reader::new(&data).read().unwrap();
+        let disassembled = AbcInfo::new(&read).to_string();
+        assert_eq!(write(assemble(&disassembled).unwrap()), data);
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        let text = "method 0 null\n  returns *\n  body\n    code\n      frobnicate\n";
+        match assemble(text) {
+            Err(Error::Parse { line, message }) => {
+                assert_eq!(line, 5);
+                assert_eq!(message, "Unknown op \"frobnicate\"");
+            }
+            _ => panic!("Expected a parse error"),
+        }
+
+        assert!(matches!(
+            assemble("method 0 null\n  body\n    code\n      jump L9\n    end\n  end\nend\n"),
+            Err(Error::Parse { line: 4, .. })
+        ));
+    }
+}
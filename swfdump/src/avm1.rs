@@ -0,0 +1,405 @@
+//! AVM1 disassembly
+
+use crate::instructions::{Instruction, Labels};
+use crate::tags::{string, RawTag};
+use serde::Serialize;
+use swf::avm1::read::Reader;
+use swf::avm1::types::{Action, CatchVar, If, Jump, Value};
+use swf::{CharacterId, Encoding, SwfStr, Tag};
+
+/// A block of AVM1 actions, such as the contents of a `DoAction` tag.
+#[derive(Serialize)]
+pub struct Avm1Block {
+    /// Where these actions are run, such as `DoAction in frame 2 of sprite 5`.
+    pub source: String,
+
+    /// The offset of the tag that contains these actions.
+    pub tag_offset: usize,
+
+    pub instructions: Vec<Instruction>,
+}
+
+/// Disassemble every block of actions in `tags`.
+pub fn blocks(tags: &[RawTag], version: u8, encoding: &'static Encoding) -> Vec<Avm1Block> {
+    let mut blocks = vec![];
+    collect(tags, None, version, encoding, &mut blocks);
+    blocks
+}
+
+fn collect(
+    tags: &[RawTag],
+    sprite: Option<CharacterId>,
+    version: u8,
+    encoding: &'static Encoding,
+    blocks: &mut Vec<Avm1Block>,
+) {
+    let location = |frame: u16| match sprite {
+        Some(id) => format!("frame {frame} of sprite {id}"),
+        None => format!("frame {frame}"),
+    };
+
+    let mut frame = 1;
+    for raw in tags {
+        let Ok(tag) = &raw.tag else {
+            continue;
+        };
+
+        let mut push = |source: String, data: &[u8]| {
+            blocks.push(Avm1Block {
+                source,
+                tag_offset: raw.offset,
+                instructions: disassemble(data, version, encoding),
+            })
+        };
+
+        match tag {
+            Tag::ShowFrame => frame += 1,
+            Tag::DoAction(data) => push(format!("DoAction in {}", location(frame)), data),
+            Tag::DoInitAction { id, action_data } => {
+                push(format!("DoInitAction for sprite {id}"), action_data)
+            }
+            Tag::DefineButton(button) | Tag::DefineButton2(button) => {
+                for action in &button.actions {
+                    push(
+                        format!("button {} on {:?}", button.id, action.conditions),
+                        action.action_data,
+                    );
+                }
+            }
+            Tag::PlaceObject(place_object) => {
+                for clip_action in place_object.clip_actions.iter().flatten() {
+                    push(
+                        format!(
+                            "{:?} of depth {} in {}",
+                            clip_action.events,
+                            place_object.depth,
+                            location(frame)
+                        ),
+                        clip_action.action_data,
+                    );
+                }
+            }
+            Tag::DefineSprite(sprite) => {
+                collect(&raw.children, Some(sprite.id), version, encoding, blocks)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// An action, along with where it was found.
+struct Decoded<'a> {
+    offset: usize,
+
+    /// The offset just past this action, not including any code nested in it.
+    end: usize,
+
+    depth: usize,
+    action: Result<Action<'a>, String>,
+}
+
+/// Disassemble a block of actions.
+///
+/// The bodies of functions, `try` blocks and `with` blocks are shown after
+/// the action that contains them, one level deeper.
+pub fn disassemble(data: &[u8], version: u8, encoding: &'static Encoding) -> Vec<Instruction> {
+    let mut decoded = vec![];
+    decode(data, data, 0, version, &mut decoded);
+
+    let offsets: Vec<usize> = decoded.iter().map(|d| d.offset).collect();
+    let labels = Labels::new(
+        decoded.iter().filter_map(|d| match &d.action {
+            Ok(Action::If(If { offset })) | Ok(Action::Jump(Jump { offset })) => {
+                Some(d.end as isize + *offset as isize)
+            }
+            _ => None,
+        }),
+        &offsets,
+    );
+
+    // The constant pool is set while running, so this is only a best guess
+    // based on the last `ConstantPool` action before each use.
+    let mut pool = vec![];
+    decoded
+        .into_iter()
+        .map(|d| {
+            let (op, operands) = match &d.action {
+                Ok(action) => {
+                    if let Action::ConstantPool(constant_pool) = action {
+                        pool = constant_pool
+                            .strings
+                            .iter()
+                            .map(|s| string(s, encoding))
+                            .collect();
+                    }
+                    (
+                        action_name(action),
+                        operands(action, d.end, &pool, &labels, encoding),
+                    )
+                }
+                Err(e) => ("error".to_string(), e.clone()),
+            };
+
+            Instruction {
+                offset: d.offset,
+                label: labels.at(d.offset),
+                depth: d.depth,
+                op,
+                operands,
+            }
+        })
+        .collect()
+}
+
+fn decode<'a>(
+    root: &'a [u8],
+    block: &'a [u8],
+    depth: usize,
+    version: u8,
+    decoded: &mut Vec<Decoded<'a>>,
+) {
+    let position = |slice: &[u8]| slice.as_ptr() as usize - root.as_ptr() as usize;
+    let mut reader = Reader::new(block, version);
+
+    while !reader.get_ref().is_empty() {
+        let offset = position(reader.get_ref());
+        let action = match reader.read_action() {
+            Ok(action) => action,
+            Err(e) => {
+                decoded.push(Decoded {
+                    offset,
+                    end: offset,
+                    depth,
+                    action: Err(e.to_string()),
+                });
+                break;
+            }
+        };
+
+        let bodies: Vec<&'a [u8]> = match &action {
+            Action::DefineFunction(function) => vec![function.actions],
+            Action::DefineFunction2(function) => vec![function.actions],
+            Action::With(with) => vec![with.actions],
+            Action::Try(try_block) => [
+                Some(try_block.try_body),
+                try_block.catch_body.as_ref().map(|(_, body)| *body),
+                try_block.finally_body,
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+            _ => vec![],
+        };
+
+        // The bodies are included in the length of the action, so they
+        // end just before the next action.
+        let end = position(reader.get_ref()) - bodies.iter().map(|body| body.len()).sum::<usize>();
+        let is_end = matches!(action, Action::End);
+
+        decoded.push(Decoded {
+            offset,
+            end,
+            depth,
+            action: Ok(action),
+        });
+        for body in bodies {
+            decode(root, body, depth + 1, version, decoded);
+        }
+
+        if is_end {
+            break;
+        }
+    }
+}
+
+fn action_name(action: &Action) -> String {
+    if let Action::Unknown(unknown) = action {
+        return format!("Unknown(0x{:02x})", unknown.opcode);
+    }
+
+    // The name of the variant is all that precedes its fields.
+    let debug = format!("{action:?}");
+    debug
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn operands(
+    action: &Action,
+    end: usize,
+    pool: &[String],
+    labels: &Labels,
+    encoding: &'static Encoding,
+) -> String {
+    let quoted = |s: &SwfStr| format!("{:?}", string(s, encoding));
+
+    match action {
+        Action::ConstantPool(constant_pool) => constant_pool
+            .strings
+            .iter()
+            .enumerate()
+            .map(|(index, s)| format!("c{index}:{}", quoted(s)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Action::DefineFunction(function) => format!(
+            "{}({}) {} bytes",
+            quoted(function.name),
+            function
+                .params
+                .iter()
+                .map(|param| string(param, encoding))
+                .collect::<Vec<_>>()
+                .join(", "),
+            function.actions.len()
+        ),
+        Action::DefineFunction2(function) => format!(
+            "{}({}) {} registers, {:?}, {} bytes",
+            quoted(function.name),
+            function
+                .params
+                .iter()
+                .map(|param| match param.register_index {
+                    Some(register) => format!("r{register}:{}", string(param.name, encoding)),
+                    None => string(param.name, encoding),
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+            function.register_count,
+            function.flags,
+            function.actions.len()
+        ),
+        Action::GetUrl(get_url) => format!("{} {}", quoted(get_url.url), quoted(get_url.target)),
+        Action::GetUrl2(get_url) => {
+            let mut operands = format!("{:?}", get_url.send_vars_method());
+            if get_url.is_target_sprite() {
+                operands.push_str(", target sprite");
+            }
+            if get_url.is_load_vars() {
+                operands.push_str(", load variables");
+            }
+            operands
+        }
+        Action::GotoFrame(goto) => goto.frame.to_string(),
+        Action::GotoFrame2(goto) => format!(
+            "scene offset {}{}",
+            goto.scene_offset,
+            if goto.set_playing { ", play" } else { "" }
+        ),
+        Action::GotoLabel(goto) => quoted(goto.label),
+        Action::If(If { offset }) | Action::Jump(Jump { offset }) => {
+            labels.target(end as isize + *offset as isize)
+        }
+        Action::Push(push) => push
+            .values
+            .iter()
+            .map(|value| match value {
+                Value::Undefined => "undefined".to_string(),
+                Value::Null => "null".to_string(),
+                Value::Bool(value) => value.to_string(),
+                Value::Int(value) => value.to_string(),
+                Value::Float(value) => format!("{value:?}f"),
+                Value::Double(value) => format!("{value:?}"),
+                Value::Str(value) => quoted(value),
+                Value::Register(register) => format!("r{register}"),
+                Value::ConstantPool(index) => match pool.get(*index as usize) {
+                    Some(value) => format!("c{index}:{value:?}"),
+                    None => format!("c{index}"),
+                },
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        Action::SetTarget(set_target) => quoted(set_target.target),
+        Action::StoreRegister(store) => format!("r{}", store.register),
+        Action::Try(try_block) => {
+            let mut operands = format!("try {} bytes", try_block.try_body.len());
+            if let Some((var, body)) = &try_block.catch_body {
+                let var = match var {
+                    CatchVar::Var(name) => quoted(name),
+                    CatchVar::Register(register) => format!("r{register}"),
+                };
+                operands.push_str(&format!(", catch {var} {} bytes", body.len()));
+            }
+            if let Some(body) = try_block.finally_body {
+                operands.push_str(&format!(", finally {} bytes", body.len()));
+            }
+            operands
+        }
+        Action::WaitForFrame(wait) => {
+            format!("{}, skip {}", wait.frame, wait.num_actions_to_skip)
+        }
+        Action::WaitForFrame2(wait) => format!("skip {}", wait.num_actions_to_skip),
+        Action::With(with) => format!("{} bytes", with.actions.len()),
+        Action::Unknown(unknown) => format!("{} bytes", unknown.data.len()),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(data: &[u8]) -> Vec<String> {
+        disassemble(data, 10, SwfStr::encoding_for_version(10))
+            .into_iter()
+            .map(|instruction| {
+                let label = instruction
+                    .label
+                    .map(|label| format!("{label}: "))
+                    .unwrap_or_default();
+                format!(
+                    "{} {label}{} {}",
+                    instruction.offset, instruction.op, instruction.operands
+                )
+                .trim_end()
+                .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_constant_pool() {
+        let data = [
+            // ConstantPool "foo", "bar"
+            0x88, 0x0a, 0x00, 0x02, 0x00, b'f', b'o', b'o', 0x00, b'b', b'a', b'r', 0x00,
+            // Push c1, c2
+            0x96, 0x04, 0x00, 0x08, 0x01, 0x08, 0x02, // ConstantPool "baz"
+            0x88, 0x06, 0x00, 0x01, 0x00, b'b', b'a', b'z', 0x00, // Push c0
+            0x96, 0x02, 0x00, 0x08, 0x00, // End
+            0x00,
+        ];
+        assert_eq!(
+            lines(&data),
+            [
+                r#"0 ConstantPool c0:"foo", c1:"bar""#,
+                r#"13 Push c1:"bar", c2"#,
+                r#"20 ConstantPool c0:"baz""#,
+                r#"29 Push c0:"baz""#,
+                "34 End",
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_branch_targets() {
+        let data = [
+            // Jump forwards over the Pop
+            0x99, 0x02, 0x00, 0x01, 0x00, // Pop
+            0x17, // If backwards to the Pop
+            0x9d, 0x02, 0x00, 0xfa, 0xff, // Jump into the middle of the If
+            0x99, 0x02, 0x00, 0xf9, 0xff, // End
+            0x00,
+        ];
+        assert_eq!(
+            lines(&data),
+            [
+                "0 Jump L1",
+                "5 L0: Pop",
+                "6 L1: If L0",
+                "11 Jump @9",
+                "16 End",
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Disassembled bytecode
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A single disassembled instruction.
+#[derive(Serialize)]
+pub struct Instruction {
+    /// The offset of this instruction, from the start of its code.
+    pub offset: usize,
+
+    /// The label of this instruction, if it is the target of a branch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// How deeply this instruction is nested inside of others, such as
+    /// inside the body of an AVM1 function.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub depth: usize,
+
+    pub op: String,
+
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub operands: String,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+/// Names for the targets of branches, in order of their offsets.
+pub struct Labels {
+    names: BTreeMap<usize, String>,
+}
+
+impl Labels {
+    /// Name every target that is the start of an instruction.
+    ///
+    /// Branches to anywhere else are shown by their offset instead, as
+    /// they can't be given a label.
+    pub fn new(targets: impl IntoIterator<Item = isize>, offsets: &[usize]) -> Self {
+        let mut names = BTreeMap::new();
+        for target in targets {
+            if let Ok(target) = usize::try_from(target) {
+                if offsets.binary_search(&target).is_ok() {
+                    names.insert(target, String::new());
+                }
+            }
+        }
+
+        for (index, name) in names.values_mut().enumerate() {
+            *name = format!("L{index}");
+        }
+
+        Self { names }
+    }
+
+    /// The label at `offset`, if any.
+    pub fn at(&self, offset: usize) -> Option<String> {
+        self.names.get(&offset).cloned()
+    }
+
+    /// How to refer to a branch target in an operand.
+    pub fn target(&self, target: isize) -> String {
+        usize::try_from(target)
+            .ok()
+            .and_then(|target| self.at(target))
+            .unwrap_or_else(|| format!("@{target}"))
+    }
+}
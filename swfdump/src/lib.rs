@@ -0,0 +1,10 @@
+//! Dumping the contents of SWF files
+//!
+//! This is the library behind the `swfdump` tool, so that its output can be
+//! reused by tests and other tools.
+
+pub mod abc;
+pub mod assembler;
+pub mod avm1;
+pub mod instructions;
+pub mod tags;
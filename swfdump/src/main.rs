@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use clap::{Parser, ValueEnum};
+use serde::Serialize;
+use std::path::PathBuf;
+use swf::{Compression, SwfStr};
+use swfdump::abc::{self, AbcDump};
+use swfdump::avm1::{self, Avm1Block};
+use swfdump::instructions::Instruction;
+use swfdump::tags::{dictionary, read_tags, Character, TagInfo};
+
+#[derive(Parser, Debug)]
+#[clap(name = "swfdump", version, about, author)]
+struct Opt {
+    /// The SWF file to dump
+    #[clap(name = "swf")]
+    swf: PathBuf,
+
+    /// Print the dump as JSON instead of text
+    #[clap(long = "json")]
+    json: bool,
+
+    /// Only show these sections of the dump. May be given multiple times.
+    /// Defaults to every section
+    #[clap(short = 's', long = "show", value_enum, action = clap::ArgAction::Append)]
+    show: Vec<Section>,
+}
+
+/// The parts of a SWF that can be dumped.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    /// The SWF header
+    Header,
+
+    /// Every tag, with its offset and length
+    Tags,
+
+    /// Every character, along with its export name and linked class
+    Dictionary,
+
+    /// Disassembled AVM1 actions
+    Avm1,
+
+    /// Disassembled ABC files
+    Abc,
+}
+
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    compression: String,
+    uncompressed_length: i32,
+    width: f64,
+    height: f64,
+    frame_rate: f64,
+    num_frames: u16,
+    background_color: Option<String>,
+    is_action_script_3: bool,
+    use_direct_blit: bool,
+    use_gpu: bool,
+    use_network_sandbox: bool,
+}
+
+#[derive(Serialize, Default)]
+struct Dump {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    header: Option<Header>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<TagInfo>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dictionary: Option<Vec<Character>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    avm1: Option<Vec<Avm1Block>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abc: Option<Vec<AbcDump>>,
+}
+
+fn dump(opt: &Opt) -> Result<Dump> {
+    let data =
+        std::fs::read(&opt.swf).with_context(|| format!("Couldn't read {}", opt.swf.display()))?;
+    let swf_buf = swf::decompress_swf(&data[..]).context("Couldn't decompress SWF")?;
+    let version = swf_buf.header.version();
+    let encoding = SwfStr::encoding_for_version(version);
+    let tags = read_tags(&swf_buf.data, 0, version);
+
+    let show = |section| opt.show.is_empty() || opt.show.contains(&section);
+    let mut dump = Dump::default();
+
+    if show(Section::Header) {
+        let header = &swf_buf.header;
+        let stage_size = header.stage_size();
+        dump.header = Some(Header {
+            version,
+            compression: match header.compression() {
+                Compression::None => "none",
+                Compression::Zlib => "zlib",
+                Compression::Lzma => "lzma",
+            }
+            .to_string(),
+            uncompressed_length: header.uncompressed_len(),
+            width: stage_size.width().to_pixels(),
+            height: stage_size.height().to_pixels(),
+            frame_rate: header.frame_rate().to_f64(),
+            num_frames: header.num_frames(),
+            background_color: header
+                .background_color()
+                .map(|color| format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)),
+            is_action_script_3: header.is_action_script_3(),
+            use_direct_blit: header.use_direct_blit(),
+            use_gpu: header.use_gpu(),
+            use_network_sandbox: header.use_network_sandbox(),
+        });
+    }
+    if show(Section::Tags) {
+        dump.tags = Some(
+            tags.iter()
+                .map(|tag| TagInfo::from_raw(tag, encoding))
+                .collect(),
+        );
+    }
+    if show(Section::Dictionary) {
+        dump.dictionary = Some(dictionary(&tags, encoding));
+    }
+    if show(Section::Avm1) {
+        dump.avm1 = Some(avm1::blocks(&tags, version, encoding));
+    }
+    if show(Section::Abc) {
+        dump.abc = Some(abc::blocks(&tags, encoding));
+    }
+
+    Ok(dump)
+}
+
+fn print_text(dump: &Dump) {
+    if let Some(header) = &dump.header {
+        println!("Header");
+        println!("  Version: {}", header.version);
+        println!("  Compression: {}", header.compression);
+        println!("  Uncompressed length: {}", header.uncompressed_length);
+        println!("  Stage size: {}x{}", header.width, header.height);
+        println!("  Frame rate: {}", header.frame_rate);
+        println!("  Frames: {}", header.num_frames);
+        if let Some(color) = &header.background_color {
+            println!("  Background color: {color}");
+        }
+        println!("  ActionScript 3: {}", header.is_action_script_3);
+        println!("  Use direct blit: {}", header.use_direct_blit);
+        println!("  Use GPU: {}", header.use_gpu);
+        println!("  Use network sandbox: {}", header.use_network_sandbox);
+        println!();
+    }
+
+    if let Some(tags) = &dump.tags {
+        println!("Tags");
+        print_tags(tags, 1);
+        println!();
+    }
+
+    if let Some(dictionary) = &dump.dictionary {
+        println!("Dictionary");
+        for character in dictionary {
+            print!("  {:>5} {}", character.id, character.kind);
+            if let Some(name) = &character.export_name {
+                print!(", exported as {name:?}");
+            }
+            if let Some(class_name) = &character.class_name {
+                print!(", linked to {class_name:?}");
+            }
+            println!();
+        }
+        println!();
+    }
+
+    for block in dump.avm1.iter().flatten() {
+        println!("AVM1: {} (tag at {:#x})", block.source, block.tag_offset);
+        print_instructions(&block.instructions);
+        println!();
+    }
+
+    for block in dump.abc.iter().flatten() {
+        println!("ABC: {} (tag at {:#x})", block.source, block.tag_offset);
+        if let Some(error) = &block.error {
+            println!("  Couldn't read ABC file: {error}");
+        }
+        if let Some(abc) = &block.abc {
+            print!("{abc}");
+        }
+        println!();
+    }
+}
+
+fn print_tags(tags: &[TagInfo], depth: usize) {
+    let indent = "  ".repeat(depth);
+    for tag in tags {
+        print!(
+            "{indent}{:#08x} {} (code {}, {} bytes)",
+            tag.offset, tag.name, tag.code, tag.length
+        );
+        if let Some(id) = tag.character_id {
+            print!(" id {id}");
+        }
+        if let Some(summary) = &tag.summary {
+            print!(": {summary}");
+        }
+        if let Some(error) = &tag.error {
+            print!(" [error: {error}]");
+        }
+        println!();
+        print_tags(&tag.children, depth + 1);
+    }
+}
+
+fn print_instructions(instructions: &[Instruction]) {
+    for instruction in instructions {
+        let indent = "  ".repeat(instruction.depth + 1);
+        if let Some(label) = &instruction.label {
+            println!("{indent}{label}:");
+        }
+        println!(
+            "{indent}  {:#06x} {} {}",
+            instruction.offset, instruction.op, instruction.operands
+        );
+    }
+}
+
+fn main() -> Result<()> {
+    let opt = Opt::parse();
+    let dump = dump(&opt)?;
+
+    if opt.json {
+        println!("{}", serde_json::to_string_pretty(&dump)?);
+    } else {
+        print_text(&dump);
+    }
+
+    Ok(())
+}
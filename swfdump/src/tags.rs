@@ -0,0 +1,341 @@
+//! Walking the tag stream of a SWF
+
+use serde::Serialize;
+use swf::read::Reader;
+use swf::{CharacterId, Encoding, PlaceObjectAction, SwfStr, Tag, TagCode};
+
+/// A tag as it was found in the tag stream.
+pub struct RawTag<'a> {
+    /// The offset of the tag header, from the start of the tag stream.
+    pub offset: usize,
+    pub code: u16,
+    pub length: usize,
+    pub tag: Result<Tag<'a>, String>,
+
+    /// The tags inside of a `DefineSprite`.
+    pub children: Vec<RawTag<'a>>,
+}
+
+/// Read every tag in `data`, which starts `base` bytes into the tag stream.
+///
+/// Tags that fail to parse are kept along with their error, and skipped
+/// over using the length in their header.
+pub fn read_tags(data: &[u8], base: usize, version: u8) -> Vec<RawTag<'_>> {
+    let mut tags = vec![];
+    let mut position = 0;
+
+    while position < data.len() {
+        let mut reader = Reader::new(&data[position..], version);
+        let Ok((code, length)) = reader.read_tag_code_and_length() else {
+            break;
+        };
+        let body_start = data.len() - reader.get_ref().len();
+        let body_end = body_start.saturating_add(length).min(data.len());
+
+        let tag = Reader::new(&data[position..], version)
+            .read_tag()
+            .map_err(|e| e.to_string());
+
+        // Sprites are walked separately, so that their tags get offsets too.
+        let children = if code == TagCode::DefineSprite as u16 && body_end - body_start >= 4 {
+            read_tags(
+                &data[body_start + 4..body_end],
+                base + body_start + 4,
+                version,
+            )
+        } else {
+            vec![]
+        };
+
+        tags.push(RawTag {
+            offset: base + position,
+            code,
+            length,
+            tag,
+            children,
+        });
+        position = body_end;
+
+        if code == TagCode::End as u16 {
+            break;
+        }
+    }
+
+    tags
+}
+
+/// A tag, as shown in a dump.
+#[derive(Serialize)]
+pub struct TagInfo {
+    pub offset: usize,
+    pub code: u16,
+    pub name: String,
+    pub length: usize,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character_id: Option<CharacterId>,
+
+    /// A short description of the most interesting fields of the tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TagInfo>,
+}
+
+impl TagInfo {
+    pub fn from_raw(raw: &RawTag, encoding: &'static Encoding) -> Self {
+        let (character_id, summary, error) = match &raw.tag {
+            Ok(tag) => (character_id(tag), summarize(tag, encoding), None),
+            Err(e) => (None, None, Some(e.clone())),
+        };
+
+        Self {
+            offset: raw.offset,
+            code: raw.code,
+            name: TagCode::format(raw.code),
+            length: raw.length,
+            character_id,
+            summary,
+            error,
+            children: raw
+                .children
+                .iter()
+                .map(|child| TagInfo::from_raw(child, encoding))
+                .collect(),
+        }
+    }
+}
+
+/// A character in the dictionary of a SWF.
+#[derive(Serialize)]
+pub struct Character {
+    pub id: CharacterId,
+
+    /// The name of the tag that defined this character.
+    pub kind: String,
+
+    /// The name this character is exported as, for use by AVM1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_name: Option<String>,
+
+    /// The AVM2 class that this character is linked to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+}
+
+/// Collect every character defined in `tags`, ordered by their ID.
+pub fn dictionary(tags: &[RawTag], encoding: &'static Encoding) -> Vec<Character> {
+    fn visit(tags: &[RawTag], encoding: &'static Encoding, characters: &mut Vec<Character>) {
+        for raw in tags {
+            let Ok(tag) = &raw.tag else {
+                continue;
+            };
+
+            if let Some(id) = character_id(tag) {
+                characters.push(Character {
+                    id,
+                    kind: TagCode::format(raw.code),
+                    export_name: None,
+                    class_name: None,
+                });
+            }
+
+            match tag {
+                Tag::ExportAssets(assets) => {
+                    for asset in assets {
+                        if let Some(character) = characters.iter_mut().find(|c| c.id == asset.id) {
+                            character.export_name = Some(string(asset.name, encoding));
+                        }
+                    }
+                }
+                Tag::SymbolClass(links) => {
+                    for link in links {
+                        if let Some(character) = characters.iter_mut().find(|c| c.id == link.id) {
+                            character.class_name = Some(string(link.class_name, encoding));
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            visit(&raw.children, encoding, characters);
+        }
+    }
+
+    let mut characters = vec![];
+    visit(tags, encoding, &mut characters);
+    characters.sort_by_key(|character| character.id);
+    characters
+}
+
+/// The ID of the character that a tag defines, if any.
+pub fn character_id(tag: &Tag) -> Option<CharacterId> {
+    Some(match tag {
+        Tag::DefineBinaryData(data) => data.id,
+        Tag::DefineBits { id, .. } => *id,
+        Tag::DefineBitsJpeg2 { id, .. } => *id,
+        Tag::DefineBitsJpeg3(jpeg) => jpeg.id,
+        Tag::DefineBitsLossless(bitmap) => bitmap.id,
+        Tag::DefineButton(button) | Tag::DefineButton2(button) => button.id,
+        Tag::DefineEditText(edit_text) => edit_text.id(),
+        Tag::DefineFont(font) => font.id,
+        Tag::DefineFont2(font) => font.id,
+        Tag::DefineFont4(font) => font.id,
+        Tag::DefineMorphShape(morph_shape) => morph_shape.id,
+        Tag::DefineShape(shape) => shape.id,
+        Tag::DefineSound(sound) => sound.id,
+        Tag::DefineSprite(sprite) => sprite.id,
+        Tag::DefineText(text) | Tag::DefineText2(text) => text.id,
+        Tag::DefineVideoStream(video) => video.id,
+        _ => return None,
+    })
+}
+
+fn summarize(tag: &Tag, encoding: &'static Encoding) -> Option<String> {
+    Some(match tag {
+        Tag::SetBackgroundColor(color) => {
+            format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+        }
+        Tag::FileAttributes(attributes) => format!("{attributes:?}"),
+        Tag::ScriptLimits {
+            max_recursion_depth,
+            timeout_in_seconds,
+        } => format!("max recursion depth {max_recursion_depth}, timeout {timeout_in_seconds}s"),
+        Tag::FrameLabel(label) => {
+            let mut summary = format!("{:?}", string(label.label, encoding));
+            if label.is_anchor {
+                summary.push_str(" (anchor)");
+            }
+            summary
+        }
+        Tag::DefineSprite(sprite) => format!("{} frames", sprite.num_frames),
+        Tag::DefineBinaryData(data) => format!("{} bytes", data.data.len()),
+        Tag::PlaceObject(place_object) => {
+            let mut summary = format!("depth {}", place_object.depth);
+            match place_object.action {
+                PlaceObjectAction::Place(id) => summary.push_str(&format!(", place {id}")),
+                PlaceObjectAction::Replace(id) => summary.push_str(&format!(", replace with {id}")),
+                PlaceObjectAction::Modify => summary.push_str(", modify"),
+            }
+            if let Some(name) = place_object.name {
+                summary.push_str(&format!(", name {:?}", string(name, encoding)));
+            }
+            if let Some(class_name) = place_object.class_name {
+                summary.push_str(&format!(", class {:?}", string(class_name, encoding)));
+            }
+            summary
+        }
+        Tag::RemoveObject(remove_object) => format!("depth {}", remove_object.depth),
+        Tag::DoInitAction { id, .. } => format!("sprite {id}"),
+        Tag::DoAbc2(do_abc) => format!(
+            "{:?}, flags {:?}",
+            string(do_abc.name, encoding),
+            do_abc.flags
+        ),
+        Tag::ExportAssets(assets)
+        | Tag::ImportAssets {
+            imports: assets, ..
+        } => assets
+            .iter()
+            .map(|asset| format!("{} = {:?}", asset.id, string(asset.name, encoding)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Tag::SymbolClass(links) => links
+            .iter()
+            .map(|link| format!("{} = {:?}", link.id, string(link.class_name, encoding)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Tag::Metadata(metadata) => format!("{} bytes", metadata.len()),
+        _ => return None,
+    })
+}
+
+pub fn string(string: &SwfStr, encoding: &'static Encoding) -> String {
+    string.to_string_lossy(encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(data: &[u8]) -> Vec<TagInfo> {
+        let swf_buf = swf::decompress_swf(data).unwrap();
+        let version = swf_buf.header.version();
+        let encoding = SwfStr::encoding_for_version(version);
+        read_tags(&swf_buf.data, 0, version)
+            .iter()
+            .map(|tag| TagInfo::from_raw(tag, encoding))
+            .collect()
+    }
+
+    #[test]
+    fn lists_sprite_children() {
+        let tags = list(include_bytes!("../../swf/tests/swfs/ExportAssets-CS6.swf"));
+        let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "SetBackgroundColor",
+                "DefineShape",
+                "DefineSprite",
+                "ExportAssets",
+                "ShowFrame",
+                "End"
+            ]
+        );
+
+        let sprite = &tags[2];
+        assert_eq!(sprite.character_id, Some(2));
+        assert_eq!(sprite.summary.as_deref(), Some("1 frames"));
+        assert_eq!(tags[3].summary.as_deref(), Some("2 = \"Test💯\""));
+
+        // The tags of a sprite are listed with offsets into the tag stream,
+        // just past the header of the sprite and its frame count.
+        let children: Vec<(usize, &str)> = sprite
+            .children
+            .iter()
+            .map(|tag| (tag.offset, tag.name.as_str()))
+            .collect();
+        assert_eq!(
+            children,
+            [(0x31, "PlaceObject2"), (0x39, "ShowFrame"), (0x3b, "End")]
+        );
+    }
+
+    #[test]
+    fn keeps_tags_that_fail_to_parse() {
+        // A `DefineShape` with a one byte body, followed by `End`.
+        let data = [0x81, 0x00, 0x01, 0x00, 0x00];
+        let tags: Vec<TagInfo> = read_tags(&data, 0, 10)
+            .iter()
+            .map(|tag| TagInfo::from_raw(tag, SwfStr::encoding_for_version(10)))
+            .collect();
+
+        assert_eq!(tags.len(), 2);
+        assert_eq!(
+            (tags[0].offset, tags[0].name.as_str(), tags[0].length),
+            (0, "DefineShape", 1)
+        );
+        assert!(tags[0].error.is_some());
+        assert_eq!((tags[1].offset, tags[1].name.as_str()), (3, "End"));
+        assert!(tags[1].error.is_none());
+    }
+
+    #[test]
+    fn names_exported_characters() {
+        let data: &[u8] = include_bytes!("../../swf/tests/swfs/ExportAssets-CS6.swf");
+        let swf_buf = swf::decompress_swf(data).unwrap();
+        let version = swf_buf.header.version();
+        let tags = read_tags(&swf_buf.data, 0, version);
+        let characters = dictionary(&tags, SwfStr::encoding_for_version(version));
+        let exported: Vec<(CharacterId, Option<&str>)> = characters
+            .iter()
+            .map(|character| (character.id, character.export_name.as_deref()))
+            .collect();
+        assert_eq!(exported, [(1, None), (2, Some("Test💯"))]);
+    }
+}
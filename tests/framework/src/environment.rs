@@ -43,4 +43,13 @@ pub trait RenderInterface {
     ///
     /// The provided backend is guaranteed to be the same one paired with this interface.
     fn capture(&self, renderer: &mut Box<dyn RenderBackend>) -> image::RgbaImage;
+
+    /// Gets the name of the expected images that this renderer is compared against.
+    ///
+    /// Renderers whose output is too different from the reference renderer's keep their own
+    /// images, stored as `<name>.expected-<goldens>.png` next to the reference ones.
+    /// `None` compares against the reference images.
+    fn goldens(&self) -> Option<&str> {
+        None
+    }
 }
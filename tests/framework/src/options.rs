@@ -408,7 +408,12 @@ impl Default for RenderOptions {
 #[serde(default, deny_unknown_fields)]
 pub struct FontOptions {
     pub family: String,
-    pub path: String,
+
+    /// The font file, relative to the test directory.
+    ///
+    /// Without one, the framework's own test font is used. It is 1000 units
+    /// per em, and its glyphs have simple advances that are easy to check.
+    pub path: Option<String>,
     pub bold: bool,
     pub italic: bool,
 }
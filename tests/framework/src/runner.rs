@@ -9,18 +9,11 @@ use anyhow::{anyhow, Error, Result};
 use image::ImageFormat;
 use pretty_assertions::Comparison;
 use ruffle_core::backend::navigator::NullExecutor;
-use ruffle_core::events::{
-    ImeEvent, KeyDescriptor, KeyLocation, LogicalKey, NamedKey, PhysicalKey,
-    TextControlCode as RuffleTextControlCode,
-};
-use ruffle_core::events::{MouseButton as RuffleMouseButton, MouseWheelDelta};
 use ruffle_core::limits::ExecutionLimit;
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_core::{Player, PlayerBuilder, PlayerEvent};
-use ruffle_input_format::{
-    AutomatedEvent, AutomatedKey, InputInjector, MouseButton as InputMouseButton,
-    TextControlCode as InputTextControlCode,
-};
+use ruffle_frontend_utils::input_format::automated_event_to_player;
+use ruffle_input_format::{AutomatedEvent, InputInjector};
 use ruffle_render::backend::{RenderBackend, ViewportDimensions};
 use ruffle_socket_format::SocketEvent;
 use std::collections::HashMap;
@@ -206,100 +199,16 @@ impl TestRunner {
                 return;
             }
 
-            let handled = player.handle_event(match evt {
-                AutomatedEvent::MouseDown {
-                    pos, btn, index, ..
-                } => PlayerEvent::MouseDown {
-                    x: pos.0,
-                    y: pos.1,
-                    button: match btn {
-                        InputMouseButton::Left => RuffleMouseButton::Left,
-                        InputMouseButton::Middle => RuffleMouseButton::Middle,
-                        InputMouseButton::Right => RuffleMouseButton::Right,
-                    },
-                    // None here means that the core will compute index automatically,
-                    // however we do not want that in tests.
-                    index: Some(index.unwrap_or_default()),
-                },
-                AutomatedEvent::MouseMove { pos } => PlayerEvent::MouseMove { x: pos.0, y: pos.1 },
-                AutomatedEvent::MouseUp { pos, btn } => PlayerEvent::MouseUp {
-                    x: pos.0,
-                    y: pos.1,
-                    button: match btn {
-                        InputMouseButton::Left => RuffleMouseButton::Left,
-                        InputMouseButton::Middle => RuffleMouseButton::Middle,
-                        InputMouseButton::Right => RuffleMouseButton::Right,
-                    },
-                },
-                AutomatedEvent::MouseWheel { lines, pixels } => PlayerEvent::MouseWheel {
-                    delta: match (lines, pixels) {
-                        (Some(lines), None) => MouseWheelDelta::Lines(*lines),
-                        (None, Some(pixels)) => MouseWheelDelta::Pixels(*pixels),
-                        _ => panic!("MouseWheel: expected only one of 'lines' or 'pixels'"),
-                    },
-                },
-                AutomatedEvent::KeyDown { key } => PlayerEvent::KeyDown {
-                    key: automated_key_to_descriptor(*key),
-                },
-                AutomatedEvent::KeyUp { key } => PlayerEvent::KeyUp {
-                    key: automated_key_to_descriptor(*key),
-                },
-                AutomatedEvent::TextInput { codepoint } => PlayerEvent::TextInput {
-                    codepoint: *codepoint,
-                },
-                AutomatedEvent::TextControl { code } => PlayerEvent::TextControl {
-                    code: match code {
-                        InputTextControlCode::MoveLeft => RuffleTextControlCode::MoveLeft,
-                        InputTextControlCode::MoveLeftWord => RuffleTextControlCode::MoveLeftWord,
-                        InputTextControlCode::MoveLeftLine => RuffleTextControlCode::MoveLeftLine,
-                        InputTextControlCode::MoveLeftDocument => {
-                            RuffleTextControlCode::MoveLeftDocument
-                        }
-                        InputTextControlCode::MoveRight => RuffleTextControlCode::MoveRight,
-                        InputTextControlCode::MoveRightWord => RuffleTextControlCode::MoveRightWord,
-                        InputTextControlCode::MoveRightLine => RuffleTextControlCode::MoveRightLine,
-                        InputTextControlCode::MoveRightDocument => {
-                            RuffleTextControlCode::MoveRightDocument
-                        }
-                        InputTextControlCode::SelectLeft => RuffleTextControlCode::SelectLeft,
-                        InputTextControlCode::SelectLeftWord => {
-                            RuffleTextControlCode::SelectLeftWord
-                        }
-                        InputTextControlCode::SelectLeftLine => {
-                            RuffleTextControlCode::SelectLeftLine
-                        }
-                        InputTextControlCode::SelectLeftDocument => {
-                            RuffleTextControlCode::SelectLeftDocument
-                        }
-                        InputTextControlCode::SelectRight => RuffleTextControlCode::SelectRight,
-                        InputTextControlCode::SelectRightWord => {
-                            RuffleTextControlCode::SelectRightWord
-                        }
-                        InputTextControlCode::SelectRightLine => {
-                            RuffleTextControlCode::SelectRightLine
-                        }
-                        InputTextControlCode::SelectRightDocument => {
-                            RuffleTextControlCode::SelectRightDocument
-                        }
-                        InputTextControlCode::SelectAll => RuffleTextControlCode::SelectAll,
-                        InputTextControlCode::Copy => RuffleTextControlCode::Copy,
-                        InputTextControlCode::Paste => RuffleTextControlCode::Paste,
-                        InputTextControlCode::Cut => RuffleTextControlCode::Cut,
-                        InputTextControlCode::Backspace => RuffleTextControlCode::Backspace,
-                        InputTextControlCode::Enter => RuffleTextControlCode::Enter,
-                        InputTextControlCode::Delete => RuffleTextControlCode::Delete,
-                    },
-                },
-                AutomatedEvent::FocusGained => PlayerEvent::FocusGained,
-                AutomatedEvent::FocusLost => PlayerEvent::FocusLost,
-                AutomatedEvent::ImePreedit { text, cursor } => {
-                    PlayerEvent::Ime(ImeEvent::Preedit(text.clone(), *cursor))
-                }
-                AutomatedEvent::ImeCommit { text } => {
-                    PlayerEvent::Ime(ImeEvent::Commit(text.clone()))
-                }
-                AutomatedEvent::Wait | AutomatedEvent::SetClipboardText { .. } => unreachable!(),
-            });
+            // Tests use stage coordinates as they are.
+            let Some(mut event) = automated_event_to_player(evt, |x, y| (x, y)) else {
+                panic!("Can't inject {evt:?}");
+            };
+            if let PlayerEvent::MouseDown { index, .. } = &mut event {
+                // None here means that the core will compute index automatically,
+                // however we do not want that in tests.
+                index.get_or_insert(0);
+            }
+            let handled = player.handle_event(event);
 
             #[allow(clippy::single_match)]
             match evt {
@@ -500,6 +409,15 @@ impl TestRunner {
     }
 }
 
+/// The environment variable that allows saving missing expected images.
+const APPROVE_IMAGES_VAR: &str = "RUFFLE_APPROVE_IMAGES";
+
+/// Whether missing expected images should be created from the current output,
+/// instead of failing the test.
+fn approve_images() -> bool {
+    std::env::var_os(APPROVE_IMAGES_VAR).is_some_and(|value| value != "0")
+}
+
 fn capture_and_compare_image(
     base_path: &VfsPath,
     player: &Arc<Mutex<Player>>,
@@ -516,7 +434,10 @@ fn capture_and_compare_image(
 
         let actual_image = render_interface.capture(player_lock.renderer_mut());
 
-        let expected_image_path = base_path.join(format!("{name}.expected.png"))?;
+        let expected_image_path = match render_interface.goldens() {
+            Some(goldens) => base_path.join(format!("{name}.expected-{goldens}.png"))?,
+            None => base_path.join(format!("{name}.expected.png"))?,
+        };
         if expected_image_path.is_file()? {
             let expected_image = image::load_from_memory(&read_bytes(&expected_image_path)?)
                 .context("Failed to open expected image")?
@@ -534,9 +455,14 @@ fn capture_and_compare_image(
             return Err(anyhow!(
                 "No image to compare to, pretending this failed since we don't know if it worked."
             ));
-        } else {
+        } else if approve_images() {
             // If we're expecting this to be wrong, don't save a likely wrong image
             write_image(&expected_image_path, &actual_image, ImageFormat::Png)?;
+        } else {
+            return Err(anyhow!(
+                "No image to compare to at {}. Rerun with {APPROVE_IMAGES_VAR}=1 to save the current output as the expected image.",
+                expected_image_path.as_str()
+            ));
         }
     } else if known_failure {
         // It's possible that the trace output matched but the image might not.
@@ -581,79 +507,3 @@ fn assert_text_matches(ruffle: &str, flash: &str) -> Result<()> {
         Ok(())
     }
 }
-
-fn automated_key_to_descriptor(automated_key: AutomatedKey) -> KeyDescriptor {
-    let logical_key = match automated_key {
-        AutomatedKey::Char(ch) | AutomatedKey::Numpad(ch) => LogicalKey::Character(ch),
-        AutomatedKey::ArrowDown => LogicalKey::Named(NamedKey::ArrowDown),
-        AutomatedKey::ArrowLeft => LogicalKey::Named(NamedKey::ArrowLeft),
-        AutomatedKey::ArrowRight => LogicalKey::Named(NamedKey::ArrowRight),
-        AutomatedKey::ArrowUp => LogicalKey::Named(NamedKey::ArrowUp),
-        AutomatedKey::Backspace => LogicalKey::Named(NamedKey::Backspace),
-        AutomatedKey::CapsLock => LogicalKey::Named(NamedKey::CapsLock),
-        AutomatedKey::Delete => LogicalKey::Named(NamedKey::Delete),
-        AutomatedKey::End => LogicalKey::Named(NamedKey::End),
-        AutomatedKey::Enter => LogicalKey::Named(NamedKey::Enter),
-        AutomatedKey::Escape => LogicalKey::Named(NamedKey::Escape),
-        AutomatedKey::F1 => LogicalKey::Named(NamedKey::F1),
-        AutomatedKey::F2 => LogicalKey::Named(NamedKey::F2),
-        AutomatedKey::F3 => LogicalKey::Named(NamedKey::F3),
-        AutomatedKey::F4 => LogicalKey::Named(NamedKey::F4),
-        AutomatedKey::F5 => LogicalKey::Named(NamedKey::F5),
-        AutomatedKey::F6 => LogicalKey::Named(NamedKey::F6),
-        AutomatedKey::F7 => LogicalKey::Named(NamedKey::F7),
-        AutomatedKey::F8 => LogicalKey::Named(NamedKey::F8),
-        AutomatedKey::F9 => LogicalKey::Named(NamedKey::F9),
-        AutomatedKey::Home => LogicalKey::Named(NamedKey::Home),
-        AutomatedKey::Insert => LogicalKey::Named(NamedKey::Insert),
-        AutomatedKey::LeftAlt => LogicalKey::Named(NamedKey::Alt),
-        AutomatedKey::LeftControl => LogicalKey::Named(NamedKey::Control),
-        AutomatedKey::LeftShift => LogicalKey::Named(NamedKey::Shift),
-        AutomatedKey::NumLock => LogicalKey::Named(NamedKey::NumLock),
-        AutomatedKey::NumpadDelete => LogicalKey::Named(NamedKey::Delete),
-        AutomatedKey::NumpadDown => LogicalKey::Named(NamedKey::ArrowDown),
-        AutomatedKey::NumpadEnd => LogicalKey::Named(NamedKey::End),
-        AutomatedKey::NumpadHome => LogicalKey::Named(NamedKey::Home),
-        AutomatedKey::NumpadInsert => LogicalKey::Named(NamedKey::Insert),
-        AutomatedKey::NumpadLeft => LogicalKey::Named(NamedKey::ArrowLeft),
-        AutomatedKey::NumpadPageDown => LogicalKey::Named(NamedKey::PageDown),
-        AutomatedKey::NumpadPageUp => LogicalKey::Named(NamedKey::PageUp),
-        AutomatedKey::NumpadRight => LogicalKey::Named(NamedKey::ArrowRight),
-        AutomatedKey::NumpadUp => LogicalKey::Named(NamedKey::ArrowUp),
-        AutomatedKey::PageDown => LogicalKey::Named(NamedKey::PageDown),
-        AutomatedKey::PageUp => LogicalKey::Named(NamedKey::PageUp),
-        AutomatedKey::Pause => LogicalKey::Named(NamedKey::Pause),
-        AutomatedKey::RightControl => LogicalKey::Named(NamedKey::Control),
-        AutomatedKey::RightShift => LogicalKey::Named(NamedKey::Shift),
-        AutomatedKey::ScrollLock => LogicalKey::Named(NamedKey::ScrollLock),
-        AutomatedKey::Space => LogicalKey::Character(' '),
-        AutomatedKey::Tab => LogicalKey::Named(NamedKey::Tab),
-        AutomatedKey::Unknown => LogicalKey::Unknown,
-    };
-    let key_location = match automated_key {
-        AutomatedKey::Numpad(_) => KeyLocation::Numpad,
-        AutomatedKey::LeftAlt => KeyLocation::Left,
-        AutomatedKey::LeftControl => KeyLocation::Left,
-        AutomatedKey::LeftShift => KeyLocation::Left,
-        AutomatedKey::NumLock => KeyLocation::Numpad,
-        AutomatedKey::NumpadDelete => KeyLocation::Numpad,
-        AutomatedKey::NumpadDown => KeyLocation::Numpad,
-        AutomatedKey::NumpadEnd => KeyLocation::Numpad,
-        AutomatedKey::NumpadHome => KeyLocation::Numpad,
-        AutomatedKey::NumpadInsert => KeyLocation::Numpad,
-        AutomatedKey::NumpadLeft => KeyLocation::Numpad,
-        AutomatedKey::NumpadPageDown => KeyLocation::Numpad,
-        AutomatedKey::NumpadPageUp => KeyLocation::Numpad,
-        AutomatedKey::NumpadRight => KeyLocation::Numpad,
-        AutomatedKey::NumpadUp => KeyLocation::Numpad,
-        AutomatedKey::RightControl => KeyLocation::Right,
-        AutomatedKey::RightShift => KeyLocation::Right,
-        _ => KeyLocation::Standard,
-    };
-    KeyDescriptor {
-        // We don't use physical keys in tests
-        physical_key: PhysicalKey::Unknown,
-        logical_key,
-        key_location,
-    }
-}
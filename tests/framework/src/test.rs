@@ -6,8 +6,12 @@ use anyhow::{anyhow, Result};
 use ruffle_core::tag_utils::SwfMovie;
 use ruffle_input_format::InputInjector;
 use ruffle_socket_format::SocketEvent;
+use swfdump::assembler::assemble_swf;
 use vfs::VfsPath;
 
+/// The font used by tests that don't provide their own font file.
+const TEST_FONT: &[u8] = include_bytes!("../assets/TestFont.ttf");
+
 pub struct Font {
     pub bytes: Vec<u8>,
     pub family: String,
@@ -18,6 +22,9 @@ pub struct Font {
 pub struct Test {
     pub options: TestOptions,
     pub swf_path: VfsPath,
+
+    /// ABC assembly to build the movie from, instead of `test.swf`.
+    pub assembly_path: VfsPath,
     pub input_path: VfsPath,
     pub socket_path: VfsPath,
     pub output_path: VfsPath,
@@ -28,6 +35,7 @@ pub struct Test {
 impl Test {
     pub fn from_options(options: TestOptions, test_dir: VfsPath, name: String) -> Result<Self> {
         let swf_path = test_dir.join("test.swf")?;
+        let assembly_path = test_dir.join("test.abcasm")?;
         let input_path = test_dir.join("input.json")?;
         let socket_path = test_dir.join("socket.json")?;
         let output_path = options.output_path(&test_dir)?;
@@ -35,6 +43,7 @@ impl Test {
         Ok(Self {
             options,
             swf_path,
+            assembly_path,
             input_path,
             socket_path,
             output_path,
@@ -65,7 +74,12 @@ impl Test {
     }
 
     pub fn movie(&self) -> Result<SwfMovie> {
-        let data = read_bytes(&self.swf_path)?;
+        let data = if self.assembly_path.is_file()? {
+            let text = String::from_utf8(read_bytes(&self.assembly_path)?)?;
+            assemble_swf(&text)?
+        } else {
+            read_bytes(&self.swf_path)?
+        };
         let movie = SwfMovie::from_data(&data, format!("file:///{}", self.swf_path.as_str()), None)
             .map_err(|e| anyhow!(e.to_string()))?;
         Ok(movie)
@@ -95,7 +109,10 @@ impl Test {
             .values()
             .map(|font| {
                 Ok(Font {
-                    bytes: read_bytes(&self.root_path.join(&font.path)?)?.to_vec(),
+                    bytes: match &font.path {
+                        Some(path) => read_bytes(&self.root_path.join(path)?)?.to_vec(),
+                        None => TEST_FONT.to_vec(),
+                    },
                     family: font.family.to_owned(),
                     bold: font.bold,
                     italic: font.italic,
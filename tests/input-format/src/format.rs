@@ -43,8 +43,10 @@ pub enum TextControlCode {
     Paste,
     Cut,
     Backspace,
+    BackspaceWord,
     Enter,
     Delete,
+    DeleteWord,
 }
 
 /// All possible keys which can be simulated in tests.
@@ -118,7 +120,9 @@ pub enum AutomatedEvent {
     MouseDown {
         pos: MousePosition,
         btn: MouseButton,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         index: Option<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         assert_handled: Option<EventHandledAssertion>,
     },
 
@@ -130,7 +134,9 @@ pub enum AutomatedEvent {
 
     /// Mouse scroll.
     MouseWheel {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         lines: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         pixels: Option<f64>,
     },
 
@@ -158,6 +164,7 @@ pub enum AutomatedEvent {
     /// Update the IME preedit string and cursor.
     ImePreedit {
         text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
         cursor: Option<(usize, usize)>,
     },
 
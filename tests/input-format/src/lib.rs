@@ -1,5 +1,5 @@
 mod format;
 mod injector;
 
-pub use format::{AutomatedEvent, AutomatedKey, MouseButton, TextControlCode};
+pub use format::{AutomatedEvent, AutomatedKey, MouseButton, MousePosition, TextControlCode};
 pub use injector::{InputInjector, MouseButtons};
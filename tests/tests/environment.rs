@@ -27,6 +27,10 @@ impl Environment for NativeEnvironment {
 #[cfg(feature = "imgtests")]
 mod renderer {
     use image::RgbaImage;
+    #[cfg(feature = "imgtests_software")]
+    use ruffle_core::ViewportDimensions;
+    #[cfg(feature = "imgtests_software")]
+    use ruffle_render_software::SoftwareRenderBackend;
     use ruffle_render_wgpu::backend::{request_adapter_and_device, WgpuRenderBackend};
     use ruffle_render_wgpu::descriptors::Descriptors;
     use ruffle_render_wgpu::target::TextureTarget;
@@ -35,7 +39,13 @@ mod renderer {
     use ruffle_test_framework::options::RenderOptions;
     use {std::sync::Arc, std::sync::OnceLock};
 
-    pub struct NativeRenderInterface;
+    /// Renders with wgpu when a device is available. With the `imgtests_software` feature,
+    /// falls back to the software renderer on machines without a GPU.
+    pub enum NativeRenderInterface {
+        Wgpu,
+        #[cfg(feature = "imgtests_software")]
+        Software,
+    }
 
     impl NativeRenderInterface {
         pub fn create_pair(
@@ -47,37 +57,78 @@ mod renderer {
                     "WGPU Texture Target creation must not fail, everything was checked ahead of time",
                 );
 
-                Some( (Box::new(Self), Box::new(
+                Some( (Box::new(Self::Wgpu), Box::new(
                     WgpuRenderBackend::new(descriptors.clone(), target)
                         .expect("WGPU Render backend creation must not fail, everything was checked ahead of time"),
                 )))
             } else {
-                None
+                Self::create_software_pair(width, height)
             }
         }
+
+        #[cfg(feature = "imgtests_software")]
+        fn create_software_pair(
+            width: u32,
+            height: u32,
+        ) -> Option<(Box<dyn RenderInterface>, Box<dyn RenderBackend>)> {
+            let backend = SoftwareRenderBackend::new(ViewportDimensions {
+                width,
+                height,
+                scale_factor: 1.0,
+            });
+            Some((Box::new(Self::Software), Box::new(backend)))
+        }
+
+        #[cfg(not(feature = "imgtests_software"))]
+        fn create_software_pair(
+            _width: u32,
+            _height: u32,
+        ) -> Option<(Box<dyn RenderInterface>, Box<dyn RenderBackend>)> {
+            None
+        }
     }
 
     impl RenderInterface for NativeRenderInterface {
         fn name(&self) -> String {
-            if let Some(descriptors) = descriptors() {
-                let adapter_info = descriptors.adapter.get_info();
-                format!("{}-{:?}", std::env::consts::OS, adapter_info.backend)
-            } else {
-                std::env::consts::OS.to_string()
+            match self {
+                Self::Wgpu => {
+                    let adapter_info = descriptors()
+                        .expect("WGPU was available when this interface was created")
+                        .adapter
+                        .get_info();
+                    format!("{}-{:?}", std::env::consts::OS, adapter_info.backend)
+                }
+                #[cfg(feature = "imgtests_software")]
+                Self::Software => format!("{}-Software", std::env::consts::OS),
             }
         }
 
-        fn capture(&self, backend: &mut Box<dyn RenderBackend>) -> RgbaImage {
-            let renderer = backend
-                .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
-                .unwrap();
+        fn goldens(&self) -> Option<&str> {
+            match self {
+                Self::Wgpu => None,
+                #[cfg(feature = "imgtests_software")]
+                Self::Software => Some("software"),
+            }
+        }
 
-            renderer.capture_frame().expect("Failed to capture image")
+        fn capture(&self, backend: &mut Box<dyn RenderBackend>) -> RgbaImage {
+            match self {
+                Self::Wgpu => backend
+                    .downcast_mut::<WgpuRenderBackend<TextureTarget>>()
+                    .unwrap()
+                    .capture_frame()
+                    .expect("Failed to capture image"),
+                #[cfg(feature = "imgtests_software")]
+                Self::Software => backend
+                    .downcast_mut::<SoftwareRenderBackend>()
+                    .unwrap()
+                    .capture_frame(),
+            }
         }
     }
 
     pub fn is_supported(_requirements: &RenderOptions) -> bool {
-        descriptors().is_some()
+        descriptors().is_some() || cfg!(feature = "imgtests_software")
     }
 
     static WGPU: OnceLock<Option<Arc<Descriptors>>> = OnceLock::new();
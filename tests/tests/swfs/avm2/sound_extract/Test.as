@@ -0,0 +1,46 @@
+package {
+	import flash.display.MovieClip;
+	import flash.media.Sound;
+	import flash.utils.ByteArray;
+
+	// `EmbeddedSound` is a library sound of eight 16-bit mono samples at 44.1kHz:
+	// 0, 0.25, 0.5, -0.5, -1, 0.125, 0.75 and -0.25.
+	public class Test extends MovieClip {
+		public function Test() {
+			var sound:Sound = new EmbeddedSound();
+			var bytes:ByteArray = new ByteArray();
+
+			trace("/// sound.extract(bytes, 3)");
+			trace(sound.extract(bytes, 3));
+			trace("/// sound.extract(bytes, 100)");
+			trace(sound.extract(bytes, 100));
+			trace("/// sound.extract(bytes, 100)");
+			trace(sound.extract(bytes, 100));
+			trace("/// bytes.length");
+			trace(bytes.length);
+			dump(bytes);
+
+			var part:ByteArray = new ByteArray();
+			trace("/// sound.extract(part, 2, 6)");
+			trace(sound.extract(part, 2, 6));
+			dump(part);
+
+			bytes.position = 0;
+			var copy:Sound = new Sound();
+			trace("/// copy.loadPCMFromByteArray(bytes, 8)");
+			copy.loadPCMFromByteArray(bytes, 8, "float", true, 44100);
+
+			var copied:ByteArray = new ByteArray();
+			trace("/// copy.extract(copied, 100, 0)");
+			trace(copy.extract(copied, 100, 0));
+			dump(copied);
+		}
+
+		private function dump(bytes:ByteArray):void {
+			bytes.position = 0;
+			while (bytes.bytesAvailable > 0) {
+				trace(bytes.readFloat() + " " + bytes.readFloat());
+			}
+		}
+	}
+}
@@ -0,0 +1,66 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.system.MessageChannel;
+	import flash.system.Worker;
+	import flash.system.WorkerDomain;
+
+	// The same movie runs as the primordial worker, which starts a background
+	// worker and sends it messages, and as the background worker, which echoes
+	// them back.
+	public class Test extends Sprite {
+		private var worker:Worker;
+		private var fromWorker:MessageChannel;
+
+		public function Test() {
+			if (Worker.current.isPrimordial) {
+				startWorker();
+			} else {
+				runWorker();
+			}
+		}
+
+		private function startWorker():void {
+			worker = WorkerDomain.current.createWorker(loaderInfo.bytes);
+			var toWorker:MessageChannel = Worker.current.createMessageChannel(worker);
+			fromWorker = worker.createMessageChannel(Worker.current);
+			worker.setSharedProperty("toWorker", toWorker);
+			worker.setSharedProperty("fromWorker", fromWorker);
+			worker.setSharedProperty("data", {name: "shared", values: [1, 2, 3]});
+			try {
+				worker.setSharedProperty("sprite", this);
+			} catch (e:ArgumentError) {
+				trace("setSharedProperty(sprite): " + e.errorID);
+			}
+			trace("worker.state: " + worker.state);
+
+			worker.addEventListener(Event.WORKER_STATE, onWorkerState);
+			fromWorker.addEventListener(Event.CHANNEL_MESSAGE, onMessage);
+			worker.start();
+			toWorker.send("hello");
+			toWorker.send(42);
+			toWorker.close();
+		}
+
+		private function onWorkerState(e:Event):void {
+			trace("workerState: " + worker.state);
+		}
+
+		private function onMessage(e:Event):void {
+			trace("primordial received: " + fromWorker.receive());
+		}
+
+		private function runWorker():void {
+			var input:MessageChannel = Worker.current.getSharedProperty("toWorker");
+			var output:MessageChannel = Worker.current.getSharedProperty("fromWorker");
+			var data:Object = Worker.current.getSharedProperty("data");
+			trace("worker isPrimordial: " + Worker.current.isPrimordial);
+			trace("worker data: " + data.name + " " + data.values.join(","));
+			trace("worker sprite: " + Worker.current.getSharedProperty("sprite"));
+			while (input.messageAvailable) {
+				output.send("echo " + input.receive());
+			}
+			trace("worker toWorker.state: " + input.state);
+		}
+	}
+}
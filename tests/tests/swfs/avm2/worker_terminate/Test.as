@@ -0,0 +1,58 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.system.MessageChannel;
+	import flash.system.Worker;
+	import flash.system.WorkerDomain;
+
+	// The primordial worker starts a background worker, and terminates it as
+	// soon as the background worker says it's ready.
+	public class Test extends Sprite {
+		private var worker:Worker;
+		private var fromWorker:MessageChannel;
+
+		public function Test() {
+			if (Worker.current.isPrimordial) {
+				startWorker();
+			} else {
+				runWorker();
+			}
+		}
+
+		private function startWorker():void {
+			worker = WorkerDomain.current.createWorker(loaderInfo.bytes);
+			fromWorker = worker.createMessageChannel(Worker.current);
+			worker.setSharedProperty("fromWorker", fromWorker);
+			worker.addEventListener(Event.WORKER_STATE, onWorkerState);
+			fromWorker.addEventListener(Event.CHANNEL_STATE, onChannelState);
+			fromWorker.addEventListener(Event.CHANNEL_MESSAGE, onMessage);
+			worker.start();
+			trace("terminate primordial: " + Worker.current.terminate());
+		}
+
+		private function onWorkerState(e:Event):void {
+			trace("workerState: " + worker.state);
+		}
+
+		private function onChannelState(e:Event):void {
+			trace("channelState: " + fromWorker.state);
+		}
+
+		private function onMessage(e:Event):void {
+			trace("received: " + fromWorker.receive());
+			trace("terminate: " + worker.terminate());
+			trace("terminate again: " + worker.terminate());
+			trace("worker.state: " + worker.state);
+			trace("listWorkers: " + WorkerDomain.current.listWorkers().length);
+		}
+
+		private function runWorker():void {
+			Worker.current.getSharedProperty("fromWorker").send("ready");
+			addEventListener(Event.ENTER_FRAME, onWorkerEnterFrame);
+		}
+
+		private function onWorkerEnterFrame(e:Event):void {
+			trace("worker enterFrame");
+		}
+	}
+}
@@ -0,0 +1,66 @@
+package {
+	import flash.display.Sprite;
+	import flash.events.Event;
+	import flash.events.TimerEvent;
+	import flash.system.MessageChannel;
+	import flash.system.Worker;
+	import flash.system.WorkerDomain;
+	import flash.utils.Timer;
+
+	// A background worker starts a repeating timer, and is terminated by the
+	// primordial worker after the first tick. Its timer must not tick again,
+	// while a timer of the primordial worker keeps running.
+	public class Test extends Sprite {
+		private var worker:Worker;
+		private var fromWorker:MessageChannel;
+		private var timer:Timer;
+
+		public function Test() {
+			if (Worker.current.isPrimordial) {
+				startWorker();
+			} else {
+				runWorker();
+			}
+		}
+
+		private function startWorker():void {
+			worker = WorkerDomain.current.createWorker(loaderInfo.bytes);
+			fromWorker = worker.createMessageChannel(Worker.current);
+			worker.setSharedProperty("fromWorker", fromWorker);
+			fromWorker.addEventListener(Event.CHANNEL_MESSAGE, onMessage);
+			worker.start();
+		}
+
+		private function onMessage(e:Event):void {
+			trace("received: " + fromWorker.receive());
+			trace("terminate: " + worker.terminate());
+
+			timer = new Timer(50, 2);
+			timer.addEventListener(TimerEvent.TIMER, onPrimordialTimer);
+			timer.addEventListener(TimerEvent.TIMER_COMPLETE, onPrimordialTimerComplete);
+			timer.start();
+		}
+
+		private function onPrimordialTimer(e:TimerEvent):void {
+			trace("primordial tick " + timer.currentCount);
+		}
+
+		private function onPrimordialTimerComplete(e:TimerEvent):void {
+			trace("primordial timer complete");
+		}
+
+		private function runWorker():void {
+			fromWorker = Worker.current.getSharedProperty("fromWorker");
+			timer = new Timer(50);
+			timer.addEventListener(TimerEvent.TIMER, onWorkerTimer);
+			timer.start();
+		}
+
+		private function onWorkerTimer(e:TimerEvent):void {
+			trace("worker tick " + timer.currentCount);
+			if (timer.currentCount == 1) {
+				fromWorker.send("tick");
+			}
+		}
+	}
+}
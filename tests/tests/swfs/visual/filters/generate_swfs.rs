@@ -0,0 +1,306 @@
+//! Writes the `test.swf` of the `convolution`, `gradient_glow` and `gradient_bevel` tests
+//! with the `swf` crate, placing one filtered sprite per case.
+//!
+//! To regenerate the movies, build this as the `main.rs` of a binary that depends on
+//! the `swf` crate of this repository (`swf = { path = "<path to ruffle>/swf" }`),
+//! and run it from this directory.
+
+use swf::*;
+
+fn rect_shape(id: CharacterId, x: f64, y: f64, size: f64, color: Color) -> Tag<'static> {
+    let bounds = Rectangle {
+        x_min: Twips::from_pixels(x),
+        x_max: Twips::from_pixels(x + size),
+        y_min: Twips::from_pixels(y),
+        y_max: Twips::from_pixels(y + size),
+    };
+    Tag::DefineShape(Shape {
+        version: 3,
+        id,
+        shape_bounds: bounds,
+        edge_bounds: bounds,
+        flags: ShapeFlag::empty(),
+        styles: ShapeStyles {
+            fill_styles: vec![FillStyle::Color(color)],
+            line_styles: vec![],
+        },
+        shape: vec![
+            ShapeRecord::StyleChange(Box::new(StyleChangeData {
+                move_to: Some(Point::from_pixels(x, y)),
+                fill_style_0: None,
+                fill_style_1: Some(1),
+                line_style: None,
+                new_styles: None,
+            })),
+            ShapeRecord::StraightEdge {
+                delta: PointDelta::from_pixels(size, 0.0),
+            },
+            ShapeRecord::StraightEdge {
+                delta: PointDelta::from_pixels(0.0, size),
+            },
+            ShapeRecord::StraightEdge {
+                delta: PointDelta::from_pixels(-size, 0.0),
+            },
+            ShapeRecord::StraightEdge {
+                delta: PointDelta::from_pixels(0.0, -size),
+            },
+        ],
+    })
+}
+
+fn place(
+    depth: u16,
+    id: CharacterId,
+    x: f64,
+    y: f64,
+    filters: Option<Vec<Filter>>,
+) -> Tag<'static> {
+    Tag::PlaceObject(Box::new(PlaceObject {
+        version: if filters.is_some() { 3 } else { 2 },
+        action: PlaceObjectAction::Place(id),
+        depth,
+        matrix: Some(Matrix::translate(
+            Twips::from_pixels(x),
+            Twips::from_pixels(y),
+        )),
+        color_transform: None,
+        ratio: None,
+        name: None,
+        clip_depth: None,
+        class_name: None,
+        filters,
+        background_color: None,
+        blend_mode: None,
+        clip_actions: None,
+        has_image: false,
+        is_bitmap_cached: None,
+        is_visible: None,
+        amf_data: None,
+    }))
+}
+
+/// Places a sprite of a blue square on a red square once per filter, four to a row.
+fn write(path: &str, filters: Vec<Filter>) {
+    let red = Color::from_rgba(0xFFFF0000);
+    let blue = Color::from_rgba(0xFF0000FF);
+    let mut tags = vec![
+        Tag::SetBackgroundColor(Color::from_rgba(0xFFFFFFFF)),
+        rect_shape(1, 0.0, 0.0, 40.0, red),
+        rect_shape(2, 10.0, 10.0, 20.0, blue),
+        Tag::DefineSprite(Sprite {
+            id: 3,
+            num_frames: 1,
+            tags: vec![
+                place(1, 1, 0.0, 0.0, None),
+                place(2, 2, 0.0, 0.0, None),
+                Tag::ShowFrame,
+            ],
+        }),
+    ];
+    for (i, filter) in filters.into_iter().enumerate() {
+        let x = 30.0 + (i % 4) as f64 * 90.0;
+        let y = 30.0 + (i / 4) as f64 * 90.0;
+        tags.push(place(i as u16 + 1, 3, x, y, Some(vec![filter])));
+    }
+    tags.push(Tag::ShowFrame);
+    let header = Header {
+        compression: Compression::None,
+        version: 8,
+        stage_size: Rectangle {
+            x_min: Twips::ZERO,
+            x_max: Twips::from_pixels(380.0),
+            y_min: Twips::ZERO,
+            y_max: Twips::from_pixels(200.0),
+        },
+        frame_rate: Fixed8::from_f32(24.0),
+        num_frames: 1,
+    };
+    let mut out = Vec::new();
+    write_swf(&header, &tags, &mut out).unwrap();
+    std::fs::write(path, out).unwrap();
+}
+
+fn convolution(
+    cols: u8,
+    rows: u8,
+    matrix: Vec<f32>,
+    divisor: f32,
+    bias: f32,
+    default_color: u32,
+    flags: ConvolutionFilterFlags,
+) -> Filter {
+    Filter::ConvolutionFilter(Box::new(ConvolutionFilter {
+        num_matrix_rows: rows,
+        num_matrix_cols: cols,
+        matrix,
+        divisor,
+        bias,
+        default_color: Color::from_rgba(default_color),
+        flags,
+    }))
+}
+
+fn gradient(
+    colors: &[(u8, u32)],
+    blur: f64,
+    distance: f64,
+    strength: f64,
+    flags: GradientFilterFlags,
+) -> Box<GradientFilter> {
+    Box::new(GradientFilter {
+        colors: colors
+            .iter()
+            .map(|&(ratio, color)| GradientRecord {
+                ratio,
+                color: Color::from_rgba(color),
+            })
+            .collect(),
+        blur_x: Fixed16::from_f64(blur),
+        blur_y: Fixed16::from_f64(blur),
+        angle: Fixed16::from_f64(45f64.to_radians()),
+        distance: Fixed16::from_f64(distance),
+        strength: Fixed8::from_f64(strength),
+        flags: flags | GradientFilterFlags::COMPOSITE_SOURCE | GradientFilterFlags::from_passes(1),
+    })
+}
+
+fn write_convolution() {
+    let clamp = ConvolutionFilterFlags::CLAMP;
+    let preserve = ConvolutionFilterFlags::PRESERVE_ALPHA;
+    write(
+        "convolution/test.swf",
+        vec![
+            convolution(
+                3,
+                3,
+                vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+                1.0,
+                0.0,
+                0,
+                clamp,
+            ),
+            convolution(
+                3,
+                3,
+                vec![-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0],
+                1.0,
+                128.0,
+                0,
+                clamp | preserve,
+            ),
+            convolution(
+                5,
+                5,
+                vec![1.0; 25],
+                25.0,
+                0.0,
+                0xFF00FF00,
+                ConvolutionFilterFlags::empty(),
+            ),
+            convolution(
+                3,
+                3,
+                vec![-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0],
+                0.0,
+                0.0,
+                0,
+                clamp,
+            ),
+            convolution(
+                3,
+                1,
+                vec![1.0, 0.0, 1.0],
+                2.0,
+                0.0,
+                0x800000FF,
+                ConvolutionFilterFlags::empty(),
+            ),
+            convolution(1, 3, vec![0.5, 0.0, 0.5], 1.0, -64.0, 0, preserve),
+            convolution(2, 2, vec![1.0, 1.0, 1.0, 1.0], 4.0, 0.0, 0, clamp),
+            convolution(
+                3,
+                3,
+                vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+                1.0,
+                0.0,
+                0,
+                clamp,
+            ),
+        ],
+    );
+}
+
+fn write_gradient_glow() {
+    let glow_colors = [(0, 0x00FFFFFF), (128, 0xFFFF0000), (255, 0xFFFFFF00)];
+    let glow = |distance, flags| {
+        Filter::GradientGlowFilter(gradient(&glow_colors, 10.0, distance, 1.0, flags))
+    };
+    write(
+        "gradient_glow/test.swf",
+        vec![
+            glow(0.0, GradientFilterFlags::empty()),
+            glow(0.0, GradientFilterFlags::INNER_SHADOW),
+            glow(0.0, GradientFilterFlags::ON_TOP),
+            glow(0.0, GradientFilterFlags::KNOCKOUT),
+            glow(5.0, GradientFilterFlags::empty()),
+            glow(
+                5.0,
+                GradientFilterFlags::INNER_SHADOW | GradientFilterFlags::KNOCKOUT,
+            ),
+            glow(
+                5.0,
+                GradientFilterFlags::ON_TOP | GradientFilterFlags::KNOCKOUT,
+            ),
+            Filter::GradientGlowFilter(gradient(
+                &glow_colors,
+                10.0,
+                0.0,
+                3.0,
+                GradientFilterFlags::empty(),
+            )),
+        ],
+    );
+}
+
+fn write_gradient_bevel() {
+    let bevel_colors = [(0, 0xFFFFFFFF), (128, 0x00CCCCCC), (255, 0xFF000000)];
+    let bevel = |distance, flags| {
+        Filter::GradientBevelFilter(gradient(&bevel_colors, 6.0, distance, 1.0, flags))
+    };
+    write(
+        "gradient_bevel/test.swf",
+        vec![
+            bevel(4.0, GradientFilterFlags::INNER_SHADOW),
+            bevel(4.0, GradientFilterFlags::empty()),
+            bevel(4.0, GradientFilterFlags::ON_TOP),
+            bevel(
+                4.0,
+                GradientFilterFlags::INNER_SHADOW | GradientFilterFlags::KNOCKOUT,
+            ),
+            bevel(8.0, GradientFilterFlags::INNER_SHADOW),
+            bevel(4.0, GradientFilterFlags::KNOCKOUT),
+            bevel(
+                4.0,
+                GradientFilterFlags::ON_TOP | GradientFilterFlags::KNOCKOUT,
+            ),
+            Filter::GradientBevelFilter(gradient(
+                &[
+                    (0, 0xFF00FF00),
+                    (100, 0x000000FF),
+                    (156, 0x000000FF),
+                    (255, 0xFFFF00FF),
+                ],
+                6.0,
+                4.0,
+                2.0,
+                GradientFilterFlags::ON_TOP,
+            )),
+        ],
+    );
+}
+
+fn main() {
+    write_convolution();
+    write_gradient_glow();
+    write_gradient_bevel();
+}
@@ -24,6 +24,15 @@ pub enum ScreenError {
 
     #[error("Not all blocks were updated by a supposed keyframe")]
     KeyframeInvalid,
+
+    #[error("Invalid color depth: {0}")]
+    InvalidColorDepth(u8),
+
+    #[error("Diff block rows {0}..{1} are outside of the block")]
+    InvalidDiffBlock(usize, usize),
+
+    #[error("Zlib priming from the current frame is not supported")]
+    UnsupportedPriming,
 }
 
 impl From<ScreenError> for Error {
@@ -44,17 +53,17 @@ pub struct ScreenVideoDecoder {
     last_frame: Option<Vec<u8>>,
 }
 
-struct ByteReader<'a> {
+pub(crate) struct ByteReader<'a> {
     data: &'a [u8],
     pos: usize,
 }
 
 impl<'a> ByteReader<'a> {
-    fn new(data: &'a [u8]) -> Self {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
         Self { data, pos: 0 }
     }
 
-    fn read_byte(&mut self) -> Result<u8, ScreenError> {
+    pub(crate) fn read_byte(&mut self) -> Result<u8, ScreenError> {
         if self.pos >= self.data.len() {
             return Err(ScreenError::UnexpectedEOF);
         }
@@ -63,13 +72,13 @@ impl<'a> ByteReader<'a> {
         Ok(byte)
     }
 
-    fn read_u16be(&mut self) -> Result<u16, ScreenError> {
+    pub(crate) fn read_u16be(&mut self) -> Result<u16, ScreenError> {
         let byte1 = self.read_byte()?;
         let byte2 = self.read_byte()?;
         Ok(((byte1 as u16) << 8) | (byte2 as u16))
     }
 
-    fn read_buf_ref(&mut self, length: usize) -> Result<&[u8], ScreenError> {
+    pub(crate) fn read_buf_ref(&mut self, length: usize) -> Result<&[u8], ScreenError> {
         if self.pos + length > self.data.len() {
             return Err(ScreenError::UnexpectedEOF);
         }
@@ -190,17 +199,7 @@ impl VideoDecoder for ScreenVideoDecoder {
             return Err(ScreenError::KeyframeInvalid.into());
         }
 
-        let mut rgb = vec![0u8; w * h * 3];
-
-        // convert from BGR to RGB and flip Y
-        for y in 0..h {
-            let data_row = &data[y * w * 3..(y + 1) * w * 3];
-            let rgb_row = &mut rgb[(h - y - 1) * w * 3..(h - y) * w * 3];
-
-            for (bgr, rgb) in data_row.chunks(3).zip(rgb_row.chunks_mut(3)) {
-                rgb.copy_from_slice(&[bgr[2], bgr[1], bgr[0]]);
-            }
-        }
+        let rgb = bgr_to_rgb_flipped(&data, w, h);
 
         self.last_frame = Some(data);
 
@@ -218,3 +217,20 @@ impl Default for ScreenVideoDecoder {
         Self::new()
     }
 }
+
+/// Converts an image stored bottom-up in BGR order, as both versions of Screen Video do,
+/// to a top-down RGB one.
+pub(crate) fn bgr_to_rgb_flipped(data: &[u8], w: usize, h: usize) -> Vec<u8> {
+    let mut rgb = vec![0u8; w * h * 3];
+
+    for y in 0..h {
+        let data_row = &data[y * w * 3..(y + 1) * w * 3];
+        let rgb_row = &mut rgb[(h - y - 1) * w * 3..(h - y) * w * 3];
+
+        for (bgr, rgb) in data_row.chunks(3).zip(rgb_row.chunks_mut(3)) {
+            rgb.copy_from_slice(&[bgr[2], bgr[1], bgr[0]]);
+        }
+    }
+
+    rgb
+}
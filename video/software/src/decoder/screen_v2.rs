@@ -0,0 +1,719 @@
+// The bitstream handling follows the Screen Video V2 support of flashsv.c in FFmpeg,
+// extended with custom palettes and IFrameImages.
+
+use crate::decoder::screen::{bgr_to_rgb_flipped, ByteReader, ScreenError};
+use crate::decoder::VideoDecoder;
+use ruffle_render::bitmap::BitmapFormat;
+use ruffle_video::error::Error;
+use std::ops::Range;
+
+use flate2::{Decompress, FlushDecompress};
+use ruffle_video::frame::{DecodedFrame, EncodedFrame, FrameDependency};
+
+/// The number of colors in the palette of hybrid blocks.
+const PALETTE_SIZE: usize = 128;
+
+/// The palette used by hybrid blocks until a frame provides its own, as 0xRRGGBB.
+// TODO: The last grays of this table haven't been checked against a video encoded by Flash.
+const DEFAULT_PALETTE: [u32; PALETTE_SIZE] = [
+    0x000000, 0x333333, 0x666666, 0x999999, 0xCCCCCC, 0xFFFFFF, 0x330000, 0x660000, 0x990000,
+    0xCC0000, 0xFF0000, 0x003300, 0x006600, 0x009900, 0x00CC00, 0x00FF00, 0x000033, 0x000066,
+    0x000099, 0x0000CC, 0x0000FF, 0x333300, 0x666600, 0x999900, 0xCCCC00, 0xFFFF00, 0x003333,
+    0x006666, 0x009999, 0x00CCCC, 0x00FFFF, 0x330033, 0x660066, 0x990099, 0xCC00CC, 0xFF00FF,
+    0xFFFF33, 0xFFFF66, 0xFFFF99, 0xFFFFCC, 0xFF33FF, 0xFF66FF, 0xFF99FF, 0xFFCCFF, 0x33FFFF,
+    0x66FFFF, 0x99FFFF, 0xCCFFFF, 0xCCCC33, 0xCCCC66, 0xCCCC99, 0xCCCCFF, 0xCC33CC, 0xCC66CC,
+    0xCC99CC, 0xCCFFCC, 0x33CCCC, 0x66CCCC, 0x99CCCC, 0xFFCCCC, 0x999933, 0x999966, 0x9999CC,
+    0x9999FF, 0x993399, 0x996699, 0x99CC99, 0x99FF99, 0x339999, 0x669999, 0xCC9999, 0xFF9999,
+    0x666633, 0x666699, 0x6666CC, 0x6666FF, 0x663366, 0x669966, 0x66CC66, 0x66FF66, 0x336666,
+    0x996666, 0xCC6666, 0xFF6666, 0x333366, 0x333399, 0x3333CC, 0x3333FF, 0x336633, 0x339933,
+    0x33CC33, 0x33FF33, 0x663333, 0x993333, 0xCC3333, 0xFF3333, 0x003366, 0x336699, 0x6699CC,
+    0x99CCFF, 0x006633, 0x339966, 0x66CC99, 0x99FFCC, 0x660033, 0x993366, 0xCC6699, 0xFF99CC,
+    0x663300, 0x996633, 0xCC9966, 0xFFCC99, 0x330066, 0x663399, 0x9966CC, 0xCC99FF, 0x336600,
+    0x669933, 0x99CC66, 0xCCFF99, 0xEEEEEE, 0xDDDDDD, 0xBBBBBB, 0xAAAAAA, 0x888888, 0x777777,
+    0x555555, 0x444444,
+];
+
+/// Blocks with this color depth store their pixels in BGR order.
+const COLOR_DEPTH_BGR: u8 = 0;
+
+/// Blocks with this color depth store every pixel either as an index into the palette,
+/// or as a 15-bit color.
+const COLOR_DEPTH_HYBRID: u8 = 2;
+
+/// The flags stored at the start of every block which isn't empty.
+struct BlockHeader {
+    color_depth: u8,
+
+    /// The rows stored in a diff block, counted from the bottom of the block.
+    /// The other rows are the same as in the reference image.
+    diff_rows: Option<Range<usize>>,
+
+    /// Whether zlib is primed with the data of the same block of the reference image.
+    prime_previous: bool,
+}
+
+impl BlockHeader {
+    /// Reads the header of a block, and removes its length from `size`.
+    fn read(src: &mut ByteReader, size: &mut usize) -> Result<Self, ScreenError> {
+        let flags = src.read_byte()?;
+        *size = size.checked_sub(1).ok_or(ScreenError::UnexpectedEOF)?;
+
+        let color_depth = (flags >> 3) & 0b11;
+        if color_depth != COLOR_DEPTH_BGR && color_depth != COLOR_DEPTH_HYBRID {
+            return Err(ScreenError::InvalidColorDepth(color_depth));
+        }
+
+        let diff_rows = if flags & 0b100 != 0 {
+            let start = src.read_byte()? as usize;
+            let height = src.read_byte()? as usize;
+            *size = size.checked_sub(2).ok_or(ScreenError::UnexpectedEOF)?;
+            Some(start..start + height)
+        } else {
+            None
+        };
+
+        if flags & 0b10 != 0 {
+            // Flash's own encoder never primes zlib with a block of the current frame.
+            return Err(ScreenError::UnsupportedPriming);
+        }
+
+        Ok(Self {
+            color_depth,
+            diff_rows,
+            prime_previous: flags & 0b1 != 0,
+        })
+    }
+}
+
+/// The image that diff blocks and zlib priming refer to.
+/// It is the last keyframe, unless a later frame came with an IFrameImage.
+struct Reference {
+    /// The pixels of the image, stored bottom-up in BGR order.
+    image: Vec<u8>,
+
+    /// The uncompressed data of every block of the image, which is empty for unchanged blocks.
+    blocks: Vec<Vec<u8>>,
+}
+
+/// Screen Video V2 decoder.
+pub struct ScreenVideoV2Decoder {
+    w: usize,
+    h: usize,
+    block_w: usize,
+    block_h: usize,
+
+    tile: Vec<u8>, // acts as a scratch buffer
+
+    /// The palette of hybrid blocks, in BGR order.
+    palette: [[u8; 3]; PALETTE_SIZE],
+
+    last_frame: Option<Vec<u8>>,
+    reference: Option<Reference>,
+}
+
+impl ScreenVideoV2Decoder {
+    pub fn new() -> Self {
+        Self {
+            w: 0,
+            h: 0,
+            block_w: 0,
+            block_h: 0,
+            tile: vec![],
+            palette: DEFAULT_PALETTE
+                .map(|color| [color as u8, (color >> 8) as u8, (color >> 16) as u8]),
+            last_frame: None,
+            reference: None,
+        }
+    }
+
+    /// Reads a custom palette for hybrid blocks, which is stored like an image block without flags:
+    /// its size, followed by the zlib compressed colors in BGR order.
+    fn read_palette(&mut self, src: &mut ByteReader) -> Result<(), ScreenError> {
+        let size = src.read_u16be()? as usize;
+        let mut colors = [0; PALETTE_SIZE * 3];
+        let length = inflate(src.read_buf_ref(size)?, None, &mut colors)?;
+        for (entry, color) in self
+            .palette
+            .iter_mut()
+            .zip(colors[..length].chunks_exact(3))
+        {
+            entry.copy_from_slice(color);
+        }
+        Ok(())
+    }
+
+    /// Decodes a grid of image blocks into `data`, which is stored bottom-up in BGR order.
+    /// The uncompressed data of every block is added to `blocks`.
+    ///
+    /// Returns whether every block was fully updated.
+    fn decode_blocks(
+        &mut self,
+        src: &mut ByteReader,
+        data: &mut [u8],
+        blocks: &mut Vec<Vec<u8>>,
+    ) -> Result<bool, ScreenError> {
+        let stride = self.w * 3;
+        let mut is_intra = true;
+        for (yy, row) in data.chunks_mut(stride * self.block_h).enumerate() {
+            let cur_h = (self.h - yy * self.block_h).min(self.block_h);
+            for x in (0..self.w).step_by(self.block_w) {
+                let cur_w = (self.w - x).min(self.block_w);
+
+                let mut data_size = src.read_u16be()? as usize;
+                if data_size == 0 {
+                    is_intra = false;
+                    blocks.push(vec![]);
+                    continue;
+                }
+
+                let header = BlockHeader::read(src, &mut data_size)?;
+                let dictionary = if header.prime_previous {
+                    let block = self
+                        .reference
+                        .as_ref()
+                        .and_then(|reference| reference.blocks.get(blocks.len()))
+                        .filter(|block| !block.is_empty())
+                        .ok_or(ScreenError::MissingReferenceFrame)?;
+                    Some(block.as_slice())
+                } else {
+                    None
+                };
+                let length = inflate(src.read_buf_ref(data_size)?, dictionary, &mut self.tile)?;
+                let pixels = &self.tile[..length];
+
+                let rows = match header.diff_rows {
+                    Some(rows) => {
+                        if rows.end > cur_h {
+                            return Err(ScreenError::InvalidDiffBlock(rows.start, rows.end));
+                        }
+                        let reference = self
+                            .reference
+                            .as_ref()
+                            .ok_or(ScreenError::MissingReferenceFrame)?;
+                        let block_start = yy * self.block_h * stride;
+                        for (dst, src) in row
+                            .chunks_mut(stride)
+                            .zip(reference.image[block_start..].chunks(stride))
+                        {
+                            dst[x * 3..(x + cur_w) * 3]
+                                .copy_from_slice(&src[x * 3..(x + cur_w) * 3]);
+                        }
+                        is_intra = false;
+                        rows
+                    }
+                    None => 0..cur_h,
+                };
+
+                let mut pos = 0;
+                for dst in row.chunks_mut(stride).skip(rows.start).take(rows.len()) {
+                    let dst = &mut dst[x * 3..(x + cur_w) * 3];
+                    if header.color_depth == COLOR_DEPTH_HYBRID {
+                        pos += decode_hybrid(&pixels[pos..], &self.palette, dst)?;
+                    } else {
+                        let src = pixels
+                            .get(pos..pos + dst.len())
+                            .ok_or(ScreenError::UnexpectedEOF)?;
+                        dst.copy_from_slice(src);
+                        pos += dst.len();
+                    }
+                }
+
+                blocks.push(pixels.to_vec());
+            }
+        }
+        Ok(is_intra)
+    }
+
+    fn flush(&mut self) {
+        self.last_frame = None;
+        self.reference = None;
+    }
+}
+
+/// Inflates the zlib compressed `data` into `out`, returning how many bytes were written.
+///
+/// Primed blocks are compressed as a continuation of a zlib stream which has already
+/// seen `dictionary`, so the inflater is shown the dictionary as stored blocks first.
+fn inflate(data: &[u8], dictionary: Option<&[u8]>, out: &mut [u8]) -> Result<usize, ScreenError> {
+    let mut decompress = Decompress::new(true);
+
+    if let Some(dictionary) = dictionary {
+        let mut stored = vec![0x78, 0x01];
+        for chunk in dictionary.chunks(u16::MAX as usize) {
+            let length = chunk.len() as u16;
+            stored.push(0);
+            stored.extend_from_slice(&length.to_le_bytes());
+            stored.extend_from_slice(&(!length).to_le_bytes());
+            stored.extend_from_slice(chunk);
+        }
+        let mut discarded = vec![0; dictionary.len()];
+        decompress.decompress(&stored, &mut discarded, FlushDecompress::None)?;
+    }
+
+    let primed_length = decompress.total_out();
+    decompress.decompress(data, out, FlushDecompress::Finish)?;
+    Ok((decompress.total_out() - primed_length) as usize)
+}
+
+/// Decodes the pixels of a hybrid block into `dst`, in BGR order.
+/// Every pixel is either a 7-bit index into the palette, or a 15-bit color with the top bit set.
+///
+/// Returns how many bytes of `src` were used.
+fn decode_hybrid(
+    src: &[u8],
+    palette: &[[u8; 3]; PALETTE_SIZE],
+    dst: &mut [u8],
+) -> Result<usize, ScreenError> {
+    let mut pos = 0;
+    for pixel in dst.chunks_exact_mut(3) {
+        let first = *src.get(pos).ok_or(ScreenError::UnexpectedEOF)?;
+        if first & 0x80 != 0 {
+            let second = *src.get(pos + 1).ok_or(ScreenError::UnexpectedEOF)?;
+            let color = u16::from_be_bytes([first, second]);
+            // Expands 5 bits to 8 by repeating the top bits: 000abcde -> abcdeabc
+            let expand = |channel: u16| {
+                let channel = (channel & 0x1F) as u8;
+                (channel << 3) | (channel >> 2)
+            };
+            pixel.copy_from_slice(&[expand(color), expand(color >> 5), expand(color >> 10)]);
+            pos += 2;
+        } else {
+            pixel.copy_from_slice(&palette[first as usize]);
+            pos += 1;
+        }
+    }
+    Ok(pos)
+}
+
+impl VideoDecoder for ScreenVideoV2Decoder {
+    fn preload_frame(&mut self, encoded_frame: EncodedFrame<'_>) -> Result<FrameDependency, Error> {
+        // Like V1, every frame starts with the FrameType + CodecID byte of the VIDEODATA tag in FLV.
+
+        // Just a quick sanity check for codec IDs...
+        debug_assert!(encoded_frame.data[0] & 0xF == 6);
+
+        match encoded_frame.data[0] >> 4 {
+            1 => Ok(FrameDependency::None),
+            2 => Ok(FrameDependency::Past),
+            x => Err(ScreenError::InvalidFrameType(x).into()),
+        }
+    }
+
+    fn decode_frame(&mut self, encoded_frame: EncodedFrame<'_>) -> Result<DecodedFrame, Error> {
+        let is_keyframe = encoded_frame.data[0] >> 4 == 1;
+
+        if !is_keyframe && self.last_frame.is_none() {
+            return Err(ScreenError::MissingReferenceFrame.into());
+        }
+
+        // Need to drop the extra preceding byte
+        let mut br = ByteReader::new(&encoded_frame.data[1..]);
+
+        let hdr0 = br.read_u16be()? as usize;
+        let blk_w = (hdr0 >> 12) * 16 + 16;
+        let w = hdr0 & 0xFFF;
+
+        let hdr1 = br.read_u16be()? as usize;
+        let blk_h = (hdr1 >> 12) * 16 + 16;
+        let h = hdr1 & 0xFFF;
+
+        debug_assert!(w != 0 && h != 0 && blk_w != 0 && blk_h != 0);
+
+        // The top 6 bits are reserved.
+        let flags = br.read_byte()?;
+        let has_iframe_image = flags & 0b10 != 0;
+        let has_palette_info = flags & 0b1 != 0;
+
+        if self.w != w || self.h != h || self.block_w != blk_w || self.block_h != blk_h {
+            self.flush();
+            self.tile.resize(blk_w * blk_h * 3, 0);
+            self.w = w;
+            self.h = h;
+            self.block_w = blk_w;
+            self.block_h = blk_h;
+        }
+
+        if has_palette_info {
+            self.read_palette(&mut br)?;
+        }
+
+        let mut data = self
+            .last_frame
+            .clone()
+            .unwrap_or_else(|| vec![0; w * h * 3]);
+
+        let mut blocks = vec![];
+        let is_intra = self.decode_blocks(&mut br, &mut data, &mut blocks)?;
+
+        if is_keyframe && !is_intra {
+            return Err(ScreenError::KeyframeInvalid.into());
+        }
+
+        if has_iframe_image {
+            // The IFrameImage isn't shown, it only replaces the reference image of later frames.
+            let mut image = data.clone();
+            let mut blocks = vec![];
+            self.decode_blocks(&mut br, &mut image, &mut blocks)?;
+            self.reference = Some(Reference { image, blocks });
+        } else if is_keyframe {
+            self.reference = Some(Reference {
+                image: data.clone(),
+                blocks,
+            });
+        }
+
+        let rgb = bgr_to_rgb_flipped(&data, w, h);
+
+        self.last_frame = Some(data);
+
+        Ok(DecodedFrame::new(
+            w as u32,
+            h as u32,
+            BitmapFormat::Rgb,
+            rgb,
+        ))
+    }
+}
+
+impl Default for ScreenVideoV2Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+    use flv_rs::{FlvReader, Header, Tag, TagData, VideoPacket};
+    use swf::VideoCodec;
+
+    const WIDTH: usize = 20;
+    const HEIGHT: usize = 18;
+    const BLOCK_SIZE: usize = 16;
+
+    /// The blocks of a 20x18 image split into 16x16 blocks, from the bottom left,
+    /// as `(x, y from the bottom, width, height)`.
+    const BLOCKS: [(usize, usize, usize, usize); 4] = [
+        (0, 0, 16, 16),
+        (16, 0, 4, 16),
+        (0, 16, 16, 2),
+        (16, 16, 4, 2),
+    ];
+
+    /// An RGB image, stored top-down.
+    type Image = Vec<[u8; 3]>;
+
+    fn image(color: impl Fn(usize, usize) -> [u8; 3]) -> Image {
+        (0..WIDTH * HEIGHT)
+            .map(|i| color(i % WIDTH, i / WIDTH))
+            .collect()
+    }
+
+    fn gradient(seed: u8) -> Image {
+        image(|x, y| [x as u8 * 12, y as u8 * 14, seed])
+    }
+
+    /// Returns the given rows of a block, from the bottom, as BGR pixels.
+    fn block_bgr(image: &Image, block: usize, rows: Range<usize>) -> Vec<u8> {
+        let (x, y, width, _) = BLOCKS[block];
+        rows.flat_map(|row| {
+            let top_down = HEIGHT - 1 - (y + row);
+            image[top_down * WIDTH + x..top_down * WIDTH + x + width]
+                .iter()
+                .flat_map(|[r, g, b]| [*b, *g, *r])
+        })
+        .collect()
+    }
+
+    fn full_block_bgr(image: &Image, block: usize) -> Vec<u8> {
+        block_bgr(image, block, 0..BLOCKS[block].3)
+    }
+
+    /// Compresses `data` as a continuation of a zlib stream which has already seen `dictionary`.
+    fn compress(data: &[u8], dictionary: Option<&[u8]>) -> Vec<u8> {
+        let mut compress = Compress::new(Compression::default(), true);
+        let mut out = Vec::with_capacity(data.len() * 2 + 1024);
+        if let Some(dictionary) = dictionary {
+            out.reserve(dictionary.len() * 2);
+            compress
+                .compress_vec(dictionary, &mut out, FlushCompress::Sync)
+                .expect("Dictionary must compress");
+            out.clear();
+        }
+        compress
+            .compress_vec(data, &mut out, FlushCompress::Finish)
+            .expect("Data must compress");
+        out
+    }
+
+    /// Writes the header of a SCREENV2VIDEOPACKET for a 20x18 image with 16x16 blocks.
+    fn packet(flags: u8) -> Vec<u8> {
+        let block = ((BLOCK_SIZE / 16 - 1) << 12) as u16;
+        let mut packet = vec![];
+        packet.extend_from_slice(&(block | WIDTH as u16).to_be_bytes());
+        packet.extend_from_slice(&(block | HEIGHT as u16).to_be_bytes());
+        packet.push(flags);
+        packet
+    }
+
+    fn write_block(packet: &mut Vec<u8>, flags: u8, extra: &[u8], compressed: &[u8]) {
+        let size = 1 + extra.len() + compressed.len();
+        packet.extend_from_slice(&(size as u16).to_be_bytes());
+        packet.push(flags);
+        packet.extend_from_slice(extra);
+        packet.extend_from_slice(compressed);
+    }
+
+    fn write_empty_block(packet: &mut Vec<u8>) {
+        packet.extend_from_slice(&[0, 0]);
+    }
+
+    fn write_image(packet: &mut Vec<u8>, image: &Image) {
+        for block in 0..BLOCKS.len() {
+            let data = full_block_bgr(image, block);
+            write_block(packet, 0, &[], &compress(&data, None));
+        }
+    }
+
+    /// Wraps packets into the video tags of an FLV file, as `(frame type, packet)`.
+    fn flv(packets: &[(u8, Vec<u8>)]) -> Vec<u8> {
+        let mut flv = b"FLV\x01\x01\x00\x00\x00\x09".to_vec();
+        let mut previous_tag_size = 0u32;
+        for (frame_type, packet) in packets {
+            let data_size = packet.len() as u32 + 1;
+            flv.extend_from_slice(&previous_tag_size.to_be_bytes());
+            flv.push(9);
+            flv.extend_from_slice(&data_size.to_be_bytes()[1..]);
+            flv.extend_from_slice(&[0; 7]);
+            flv.push((frame_type << 4) | 6);
+            flv.extend_from_slice(packet);
+            previous_tag_size = data_size + 11;
+        }
+        flv.extend_from_slice(&previous_tag_size.to_be_bytes());
+        flv
+    }
+
+    /// Decodes every video tag of an FLV file, returning the frames as RGB images.
+    fn decode_flv(flv: &[u8]) -> Vec<Result<Image, Error>> {
+        let mut reader = FlvReader::from_source(flv);
+        Header::parse(&mut reader).expect("FLV header must be valid");
+
+        let mut decoder = ScreenVideoV2Decoder::new();
+        let mut frames = vec![];
+        while let Ok(tag) = Tag::parse(&mut reader) {
+            let TagData::Video(video) = tag.data else {
+                panic!("FLV must only have video tags");
+            };
+            let VideoPacket::Data(packet) = video.data else {
+                panic!("FLV must only have video data");
+            };
+            // Like the FLV streams of the player, this keeps the VIDEODATA byte in front.
+            let data = [
+                &[((video.frame_type as u8) << 4) | video.codec_id as u8],
+                packet,
+            ]
+            .concat();
+            let frame = || EncodedFrame {
+                codec: VideoCodec::ScreenVideoV2,
+                data: &data,
+                frame_id: frames.len() as u32,
+            };
+
+            decoder
+                .preload_frame(frame())
+                .expect("Frame type must be valid");
+            frames.push(decoder.decode_frame(frame()).map(|bitmap| {
+                assert_eq!(bitmap.width() as usize, WIDTH);
+                assert_eq!(bitmap.height() as usize, HEIGHT);
+                bitmap
+                    .data()
+                    .chunks_exact(3)
+                    .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+                    .collect()
+            }));
+        }
+        frames
+    }
+
+    fn decode_frames(packets: &[(u8, Vec<u8>)]) -> Vec<Image> {
+        decode_flv(&flv(packets))
+            .into_iter()
+            .map(|frame| frame.expect("Frame must decode"))
+            .collect()
+    }
+
+    /// Replaces the given rows of a block, from the bottom, with a color.
+    fn fill_rows(image: &mut Image, block: usize, rows: Range<usize>, color: [u8; 3]) {
+        let (x, y, width, _) = BLOCKS[block];
+        for row in rows {
+            let top_down = HEIGHT - 1 - (y + row);
+            image[top_down * WIDTH + x..top_down * WIDTH + x + width].fill(color);
+        }
+    }
+
+    fn copy_block(image: &mut Image, source: &Image, block: usize) {
+        let (x, y, width, height) = BLOCKS[block];
+        for row in 0..height {
+            let start = (HEIGHT - 1 - (y + row)) * WIDTH + x;
+            image[start..start + width].copy_from_slice(&source[start..start + width]);
+        }
+    }
+
+    #[test]
+    fn keyframe() {
+        let expected = gradient(0);
+        let mut keyframe = packet(0);
+        write_image(&mut keyframe, &expected);
+
+        assert_eq!(decode_frames(&[(1, keyframe)]), vec![expected]);
+    }
+
+    #[test]
+    fn hybrid_blocks() {
+        // Palette index 10 is red, and 0x83E0 is green as a 15-bit color.
+        let hybrid: Vec<u8> = (0..16 * 16)
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    vec![10]
+                } else {
+                    vec![0x83, 0xE0]
+                }
+            })
+            .collect();
+        let mut keyframe = packet(0);
+        write_block(
+            &mut keyframe,
+            COLOR_DEPTH_HYBRID << 3,
+            &[],
+            &compress(&hybrid, None),
+        );
+        for block in 1..BLOCKS.len() {
+            let data = full_block_bgr(&gradient(0), block);
+            write_block(&mut keyframe, 0, &[], &compress(&data, None));
+        }
+
+        // A custom palette replaces the default one, from its first color.
+        let mut custom_palette = packet(0b1);
+        let palette = compress(&[3, 2, 1, 6, 5, 4], None);
+        custom_palette.extend_from_slice(&(palette.len() as u16).to_be_bytes());
+        custom_palette.extend_from_slice(&palette);
+        write_block(
+            &mut custom_palette,
+            (COLOR_DEPTH_HYBRID << 3) | 0b100,
+            &[0, 1],
+            &compress(&[0; 16], None),
+        );
+        for _ in 1..BLOCKS.len() {
+            write_empty_block(&mut custom_palette);
+        }
+
+        let mut first = gradient(0);
+        for row in 0..16 {
+            for column in 0..16 {
+                let color = if (row * 16 + column) % 2 == 0 {
+                    [0xFF, 0, 0]
+                } else {
+                    [0, 0xFF, 0]
+                };
+                first[(HEIGHT - 1 - row) * WIDTH + column] = color;
+            }
+        }
+        let mut second = first.clone();
+        fill_rows(&mut second, 0, 0..1, [1, 2, 3]);
+
+        assert_eq!(
+            decode_frames(&[(1, keyframe), (2, custom_palette)]),
+            vec![first, second]
+        );
+    }
+
+    #[test]
+    fn diff_blocks_refer_to_the_keyframe() {
+        let first = gradient(0);
+        let mut keyframe = packet(0);
+        write_image(&mut keyframe, &first);
+
+        // Replaces the whole second block.
+        let mut second = first.clone();
+        fill_rows(&mut second, 1, 0..16, [0xFF, 0xFF, 0xFF]);
+        let mut interframe = packet(0);
+        write_empty_block(&mut interframe);
+        let data = block_bgr(&second, 1, 0..16);
+        write_block(&mut interframe, 0, &[], &compress(&data, None));
+        write_empty_block(&mut interframe);
+        write_empty_block(&mut interframe);
+
+        // Only stores rows 2 and 3 of the second block, which is otherwise back to the keyframe,
+        // compressed as a continuation of that block of the keyframe.
+        let mut third = first.clone();
+        fill_rows(&mut third, 1, 2..4, [0x10, 0x20, 0x30]);
+        let mut diff = packet(0);
+        write_empty_block(&mut diff);
+        let dictionary = block_bgr(&first, 1, 0..16);
+        let data = block_bgr(&third, 1, 2..4);
+        write_block(
+            &mut diff,
+            0b101,
+            &[2, 2],
+            &compress(&data, Some(&dictionary)),
+        );
+        write_empty_block(&mut diff);
+        write_empty_block(&mut diff);
+
+        assert_eq!(
+            decode_frames(&[(1, keyframe), (2, interframe), (2, diff)]),
+            vec![first, second, third]
+        );
+    }
+
+    #[test]
+    fn iframe_image_becomes_the_reference() {
+        let first = gradient(0);
+        let mut keyframe = packet(0);
+        write_image(&mut keyframe, &first);
+
+        // Nothing changes on screen, but the IFrameImage is used by the next frame.
+        let iframe_image = gradient(0x80);
+        let mut interframe = packet(0b10);
+        for _ in 0..BLOCKS.len() {
+            write_empty_block(&mut interframe);
+        }
+        write_image(&mut interframe, &iframe_image);
+
+        // The first block goes back to the IFrameImage apart from its top row,
+        // and the third one is compressed as a continuation of that block of the IFrameImage.
+        let mut third = first.clone();
+        copy_block(&mut third, &iframe_image, 0);
+        fill_rows(&mut third, 0, 15..16, [0x40, 0x50, 0x60]);
+        copy_block(&mut third, &iframe_image, 2);
+        let mut diff = packet(0);
+        let data = block_bgr(&third, 0, 15..16);
+        write_block(&mut diff, 0b100, &[15, 1], &compress(&data, None));
+        write_empty_block(&mut diff);
+        let dictionary = block_bgr(&iframe_image, 2, 0..2);
+        write_block(
+            &mut diff,
+            0b101,
+            &[0, 2],
+            &compress(&dictionary, Some(&dictionary)),
+        );
+        write_empty_block(&mut diff);
+
+        assert_eq!(
+            decode_frames(&[(1, keyframe), (2, interframe), (2, diff)]),
+            vec![first.clone(), first, third]
+        );
+    }
+
+    #[test]
+    fn interframe_without_keyframe() {
+        let mut interframe = packet(0);
+        for _ in 0..BLOCKS.len() {
+            write_empty_block(&mut interframe);
+        }
+
+        let frames = decode_flv(&flv(&[(2, interframe)]));
+        assert!(matches!(frames[..], [Err(Error::DecoderError(_))]));
+    }
+}